@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use linkwithmentor_common::{ApiResponse, AppError};
+use crate::{
+    config::CommandConfig,
+    models::{CardAction, CardActionStyle, CardField, CardType, CommandResponse, MessageCard},
+};
+
+use super::{CommandContext, CommandHandler, CommandInvocation};
+
+const MAX_PROPOSED_SLOTS: usize = 5;
+
+/// `/book [duration_minutes] [days_ahead]` - proposes the invoking mentor's
+/// open slots from the meetings service as a bookable card.
+pub struct BookCommand {
+    client: Client,
+    meetings_service_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AvailabilitySlot {
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    is_available: bool,
+}
+
+impl BookCommand {
+    pub fn new(config: &CommandConfig) -> Self {
+        Self {
+            client: Client::new(),
+            meetings_service_url: config.meetings_service_url.clone(),
+        }
+    }
+
+    async fn fetch_slots(
+        &self,
+        ctx: &CommandContext,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilitySlot>, AppError> {
+        let mut request = self.client
+            .get(format!("{}/availability", self.meetings_service_url))
+            .query(&[
+                ("mentor_id", ctx.user_id.to_string()),
+                ("start_date", start.to_rfc3339()),
+                ("end_date", end.to_rfc3339()),
+            ]);
+        if let Some(token) = &ctx.auth_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Meetings service unavailable: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Meetings service returned {}",
+                response.status()
+            )));
+        }
+
+        let body: ApiResponse<Vec<AvailabilitySlot>> = response
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Invalid availability response: {}", e)))?;
+
+        Ok(body.data.unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl CommandHandler for BookCommand {
+    fn name(&self) -> &'static str {
+        "book"
+    }
+
+    fn description(&self) -> &'static str {
+        "Propose your open session slots"
+    }
+
+    fn usage(&self) -> &'static str {
+        "/book [duration_minutes] [days_ahead]"
+    }
+
+    async fn execute(
+        &self,
+        ctx: &CommandContext,
+        invocation: &CommandInvocation,
+    ) -> Result<CommandResponse, AppError> {
+        let duration_minutes = parse_bounded(invocation.args.first(), 60, 15, 240, "duration_minutes")?;
+        let days_ahead = parse_bounded(invocation.args.get(1), 7, 1, 30, "days_ahead")?;
+
+        let start = Utc::now();
+        let end = start + Duration::days(days_ahead as i64);
+
+        let slots: Vec<AvailabilitySlot> = self.fetch_slots(ctx, start, end)
+            .await?
+            .into_iter()
+            .filter(|slot| slot.is_available && slot.start_time > start)
+            .filter(|slot| slot.end_time - slot.start_time >= Duration::minutes(duration_minutes as i64))
+            .take(MAX_PROPOSED_SLOTS)
+            .collect();
+
+        if slots.is_empty() {
+            return Ok(CommandResponse::ephemeral_text(format!(
+                "You have no open {}-minute slots in the next {} days.",
+                duration_minutes, days_ahead
+            )));
+        }
+
+        let actions = slots.iter()
+            .map(|slot| CardAction {
+                action_id: "book_slot".to_string(),
+                label: slot.start_time.format("%a %d %b, %H:%M UTC").to_string(),
+                style: CardActionStyle::Primary,
+                payload: serde_json::json!({
+                    "mentor_id": ctx.user_id,
+                    "start_time": slot.start_time,
+                    "end_time": slot.start_time + Duration::minutes(duration_minutes as i64),
+                    "duration_minutes": duration_minutes,
+                    "session_id": ctx.session_id,
+                }),
+            })
+            .collect();
+
+        Ok(CommandResponse::in_channel_card(MessageCard {
+            card_id: Uuid::new_v4(),
+            card_type: CardType::SessionProposal,
+            title: format!("Book a session with {}", ctx.username),
+            body: Some("Pick a time that works for you.".to_string()),
+            fields: vec![CardField {
+                label: "Duration".to_string(),
+                value: format!("{} minutes", duration_minutes),
+            }],
+            actions,
+        }))
+    }
+}
+
+fn parse_bounded(
+    arg: Option<&String>,
+    default: u32,
+    min: u32,
+    max: u32,
+    name: &str,
+) -> Result<u32, AppError> {
+    let value = match arg {
+        Some(arg) => arg.parse::<u32>()
+            .map_err(|_| AppError::BadRequest(format!("{} must be a number", name)))?,
+        None => default,
+    };
+
+    if value < min || value > max {
+        return Err(AppError::BadRequest(format!("{} must be between {} and {}", name, min, max)));
+    }
+
+    Ok(value)
+}
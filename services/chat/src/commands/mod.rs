@@ -0,0 +1,343 @@
+pub mod book;
+pub mod pay;
+pub mod poll;
+pub mod webhook;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use linkwithmentor_common::{AppError, ModerationStatus};
+use crate::{
+    config::CommandConfig,
+    connection_manager::ConnectionManager,
+    message_service::MessageService,
+    structured_messages::StructuredMessageService,
+    models::{
+        BotResponse, CommandInfo, CommandResponse, CommandSource, CommandVisibility,
+        RegisterBotRequest, WSMessage,
+    },
+};
+
+use self::{
+    book::BookCommand,
+    pay::PayCommand,
+    poll::PollCommand,
+    webhook::WebhookBotClient,
+};
+
+/// A parsed `/name arg1 "arg two"` message.
+#[derive(Debug, Clone)]
+pub struct CommandInvocation {
+    pub name: String,
+    pub args: Vec<String>,
+    pub raw_args: String,
+}
+
+/// Who invoked a command and in which conversation.
+#[derive(Debug, Clone)]
+pub struct CommandContext {
+    pub user_id: Uuid,
+    pub username: String,
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    /// Bearer token of the invoker, forwarded when a handler calls other services
+    pub auth_token: Option<String>,
+}
+
+#[async_trait]
+pub trait CommandHandler: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    fn usage(&self) -> &'static str;
+
+    async fn execute(
+        &self,
+        ctx: &CommandContext,
+        invocation: &CommandInvocation,
+    ) -> Result<CommandResponse, AppError>;
+}
+
+/// Routes slash commands to built-in handlers first, then to registered
+/// webhook bots.
+#[derive(Clone)]
+pub struct CommandRegistry {
+    handlers: Arc<HashMap<String, Arc<dyn CommandHandler>>>,
+    webhook_client: WebhookBotClient,
+    message_service: MessageService,
+}
+
+impl CommandRegistry {
+    pub fn new(
+        db_pool: PgPool,
+        config: &CommandConfig,
+        message_service: MessageService,
+        structured_message_service: StructuredMessageService,
+    ) -> Self {
+        let mut handlers: HashMap<String, Arc<dyn CommandHandler>> = HashMap::new();
+
+        let builtins: Vec<Arc<dyn CommandHandler>> = vec![
+            Arc::new(BookCommand::new(config)),
+            Arc::new(PayCommand::new(config)),
+            Arc::new(PollCommand::new(structured_message_service)),
+        ];
+        for handler in builtins {
+            handlers.insert(handler.name().to_string(), handler);
+        }
+
+        Self {
+            handlers: Arc::new(handlers),
+            webhook_client: WebhookBotClient::new(db_pool, config),
+            message_service,
+        }
+    }
+
+    pub async fn list_commands(&self) -> Result<Vec<CommandInfo>, AppError> {
+        let mut commands: Vec<CommandInfo> = self.handlers.values()
+            .map(|handler| CommandInfo {
+                name: handler.name().to_string(),
+                description: handler.description().to_string(),
+                usage: handler.usage().to_string(),
+                source: CommandSource::BuiltIn,
+            })
+            .collect();
+
+        for bot in self.webhook_client.list_bots(true).await? {
+            commands.push(CommandInfo {
+                name: bot.command,
+                description: bot.description,
+                usage: bot.usage,
+                source: CommandSource::Webhook,
+            });
+        }
+
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(commands)
+    }
+
+    pub async fn dispatch(
+        &self,
+        ctx: &CommandContext,
+        invocation: &CommandInvocation,
+    ) -> Result<CommandResponse, AppError> {
+        if invocation.name == "help" {
+            return self.help().await;
+        }
+
+        if let Some(handler) = self.handlers.get(&invocation.name) {
+            return handler.execute(ctx, invocation).await;
+        }
+
+        if let Some(bot) = self.webhook_client.find_bot(&invocation.name).await? {
+            let response = self.webhook_client.invoke(&bot, ctx, invocation).await?;
+            return self.moderate_bot_reply(&bot.command, response).await;
+        }
+
+        Ok(CommandResponse::ephemeral_text(format!(
+            "Unknown command /{}. Type /help to see available commands.",
+            invocation.name
+        )))
+    }
+
+    /// Runs a command and delivers its reply: ephemeral replies go to the
+    /// invoker only, in-channel replies are stored like any other message
+    /// and sent to the whole conversation.
+    pub async fn execute_and_deliver(
+        &self,
+        connection_manager: &ConnectionManager,
+        ctx: &CommandContext,
+        invocation: &CommandInvocation,
+    ) -> Result<CommandResponse, AppError> {
+        // Handlers and bots act on the conversation IDs, so check them first
+        self.message_service
+            .ensure_participant(ctx.user_id, ctx.recipient_id, ctx.session_id, ctx.group_id)
+            .await?;
+
+        let response = match self.dispatch(ctx, invocation).await {
+            Ok(response) => response,
+            Err(AppError::BadRequest(message)) => CommandResponse::ephemeral_text(message),
+            Err(e) => return Err(e),
+        };
+
+        let message = command_result(ctx, invocation, &response);
+
+        match response.visibility {
+            CommandVisibility::Ephemeral => {
+                connection_manager.send_to_user(ctx.user_id, message).await?;
+            }
+            CommandVisibility::InChannel => {
+                // Built-in commands reply as themselves, webhook commands as their bot
+                let bot_id = if invocation.name == "help" || self.handlers.contains_key(&invocation.name) {
+                    None
+                } else {
+                    self.webhook_client.find_bot(&invocation.name).await?.map(|bot| bot.bot_id)
+                };
+
+                let stored = self.message_service
+                    .send_command_reply(
+                        ctx.user_id,
+                        reply_summary(&response),
+                        ctx.recipient_id,
+                        ctx.session_id,
+                        ctx.group_id,
+                        &invocation.name,
+                        bot_id,
+                    )
+                    .await?;
+
+                if matches!(stored.moderation_status, ModerationStatus::Flagged) {
+                    let notice = CommandResponse::ephemeral_text(format!(
+                        "The reply to /{} was held for moderation",
+                        invocation.name
+                    ));
+                    connection_manager
+                        .send_to_user(ctx.user_id, command_result(ctx, invocation, &notice))
+                        .await?;
+                    return Ok(notice);
+                }
+
+                connection_manager
+                    .send_to_conversation(ctx.user_id, ctx.recipient_id, ctx.session_id, ctx.group_id, message)
+                    .await?;
+            }
+        }
+
+        Ok(response)
+    }
+
+    pub async fn register_bot(&self, created_by: Uuid, request: RegisterBotRequest) -> Result<BotResponse, AppError> {
+        let command = request.command.trim_start_matches('/').to_lowercase();
+        if command == "help" || self.handlers.contains_key(&command) {
+            return Err(AppError::Conflict(format!("/{} is a built-in command", command)));
+        }
+
+        self.webhook_client.register_bot(created_by, command, request).await
+    }
+
+    pub async fn list_bots(&self) -> Result<Vec<BotResponse>, AppError> {
+        self.webhook_client.list_bots(false).await
+    }
+
+    pub async fn deactivate_bot(&self, bot_id: Uuid) -> Result<(), AppError> {
+        self.webhook_client.deactivate_bot(bot_id).await
+    }
+
+    /// Bot replies come from outside the platform, so even ephemeral ones are
+    /// checked before the invoker sees them.
+    async fn moderate_bot_reply(&self, command: &str, response: CommandResponse) -> Result<CommandResponse, AppError> {
+        let status = self.message_service.moderate_content(&reply_summary(&response)).await?;
+        if matches!(status, ModerationStatus::Flagged) {
+            return Ok(CommandResponse::ephemeral_text(format!(
+                "/{} sent a reply that was withheld by moderation",
+                command
+            )));
+        }
+
+        Ok(response)
+    }
+
+    async fn help(&self) -> Result<CommandResponse, AppError> {
+        let lines: Vec<String> = self.list_commands().await?
+            .into_iter()
+            .map(|command| format!("/{} - {} ({})", command.name, command.description, command.usage))
+            .collect();
+
+        Ok(CommandResponse::ephemeral_text(format!("Available commands:\n{}", lines.join("\n"))))
+    }
+}
+
+/// Parses a chat message as a slash command. A leading `//` escapes the
+/// slash so users can still send messages that start with one.
+pub fn parse_command(content: &str) -> Option<CommandInvocation> {
+    let trimmed = content.trim();
+    let body = trimmed.strip_prefix('/')?;
+    if body.starts_with('/') {
+        return None;
+    }
+
+    let (name, raw_args) = match body.find(char::is_whitespace) {
+        Some(index) => (&body[..index], body[index..].trim()),
+        None => (body, ""),
+    };
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return None;
+    }
+
+    Some(CommandInvocation {
+        name: name.to_lowercase(),
+        args: tokenize_args(raw_args),
+        raw_args: raw_args.to_string(),
+    })
+}
+
+fn command_result(ctx: &CommandContext, invocation: &CommandInvocation, response: &CommandResponse) -> WSMessage {
+    WSMessage::CommandResult {
+        command: invocation.name.clone(),
+        invoked_by: ctx.user_id,
+        visibility: response.visibility.clone(),
+        text: response.text.clone(),
+        card: response.card.clone(),
+        recipient_id: ctx.recipient_id,
+        session_id: ctx.session_id,
+        group_id: ctx.group_id,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Plain-text form of a command reply, stored as the message content and
+/// used for moderation.
+fn reply_summary(response: &CommandResponse) -> String {
+    let mut parts: Vec<String> = response.text.iter().cloned().collect();
+
+    if let Some(card) = &response.card {
+        parts.push(card.title.clone());
+        parts.extend(card.body.iter().cloned());
+        parts.extend(card.fields.iter().map(|field| format!("{}: {}", field.label, field.value)));
+    }
+
+    parts.join("\n")
+}
+
+/// Drops the escaping slash from a `//literal` message before it is stored.
+pub fn unescape_slash(content: String) -> String {
+    match content.trim_start().strip_prefix("//") {
+        Some(rest) => format!("/{}", rest),
+        None => content,
+    }
+}
+
+/// Splits on whitespace, keeping "double quoted" segments together.
+fn tokenize_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                if in_quotes {
+                    args.push(std::mem::take(&mut current));
+                }
+                in_quotes = !in_quotes;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        args.push(current);
+    }
+
+    args
+}
@@ -0,0 +1,114 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    config::CommandConfig,
+    models::{CardAction, CardActionStyle, CardField, CardType, CommandResponse, MessageCard},
+};
+
+use super::{CommandContext, CommandHandler, CommandInvocation};
+
+/// `/pay <amount> [currency] [description...]` - posts a payment request
+/// card. The payer's client completes it through the payment service.
+pub struct PayCommand {
+    default_currency: String,
+}
+
+impl PayCommand {
+    pub fn new(config: &CommandConfig) -> Self {
+        Self {
+            default_currency: config.default_currency.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for PayCommand {
+    fn name(&self) -> &'static str {
+        "pay"
+    }
+
+    fn description(&self) -> &'static str {
+        "Request a payment in this conversation"
+    }
+
+    fn usage(&self) -> &'static str {
+        "/pay <amount> [currency] [description]"
+    }
+
+    async fn execute(
+        &self,
+        ctx: &CommandContext,
+        invocation: &CommandInvocation,
+    ) -> Result<CommandResponse, AppError> {
+        let amount = invocation.args.first()
+            .ok_or_else(|| AppError::BadRequest(format!("Usage: {}", self.usage())))
+            .and_then(|arg| {
+                Decimal::from_str(arg)
+                    .map_err(|_| AppError::BadRequest(format!("Invalid amount: {}", arg)))
+            })?;
+
+        if amount <= Decimal::ZERO {
+            return Err(AppError::BadRequest("Amount must be greater than zero".to_string()));
+        }
+
+        // A three-letter second argument is a currency code, anything else starts the description
+        let (currency, description_start) = match invocation.args.get(1) {
+            Some(arg) if arg.len() == 3 && arg.chars().all(|c| c.is_ascii_alphabetic()) => {
+                (arg.to_uppercase(), 2)
+            }
+            _ => (self.default_currency.clone(), 1),
+        };
+
+        let description = invocation.args
+            .get(description_start..)
+            .map(|words| words.join(" "))
+            .filter(|description| !description.is_empty())
+            .unwrap_or_else(|| "Mentorship session".to_string());
+
+        let amount = amount.round_dp(2);
+        let payload = serde_json::json!({
+            "payee_id": ctx.user_id,
+            "payer_id": ctx.recipient_id,
+            "amount": amount.to_string(),
+            "currency": currency,
+            "description": description,
+            "session_id": ctx.session_id,
+        });
+
+        Ok(CommandResponse::in_channel_card(MessageCard {
+            card_id: Uuid::new_v4(),
+            card_type: CardType::PaymentRequest,
+            title: format!("{} requested {} {}", ctx.username, amount, currency),
+            body: Some(description.clone()),
+            fields: vec![
+                CardField {
+                    label: "Amount".to_string(),
+                    value: format!("{} {}", amount, currency),
+                },
+                CardField {
+                    label: "Requested by".to_string(),
+                    value: ctx.username.clone(),
+                },
+            ],
+            actions: vec![
+                CardAction {
+                    action_id: "pay".to_string(),
+                    label: "Pay".to_string(),
+                    style: CardActionStyle::Primary,
+                    payload: payload.clone(),
+                },
+                CardAction {
+                    action_id: "decline_payment".to_string(),
+                    label: "Decline".to_string(),
+                    style: CardActionStyle::Secondary,
+                    payload,
+                },
+            ],
+        }))
+    }
+}
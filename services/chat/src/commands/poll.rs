@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    models::{CommandResponse, CreateStructuredMessageRequest, StructuredMessageContent},
+    structured_messages::StructuredMessageService,
+};
+
+use super::{CommandContext, CommandHandler, CommandInvocation};
+
+/// `/poll "Question" "Option A" "Option B" ...` - shorthand for posting a
+/// single-choice poll message. Option validation lives in the structured
+/// message service.
+pub struct PollCommand {
+    structured_message_service: StructuredMessageService,
+}
+
+impl PollCommand {
+    pub fn new(structured_message_service: StructuredMessageService) -> Self {
+        Self { structured_message_service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for PollCommand {
+    fn name(&self) -> &'static str {
+        "poll"
+    }
+
+    fn description(&self) -> &'static str {
+        "Run a quick poll"
+    }
+
+    fn usage(&self) -> &'static str {
+        "/poll \"Question\" \"Option 1\" \"Option 2\" ..."
+    }
+
+    async fn execute(
+        &self,
+        ctx: &CommandContext,
+        invocation: &CommandInvocation,
+    ) -> Result<CommandResponse, AppError> {
+        let (question, options) = invocation.args
+            .split_first()
+            .ok_or_else(|| AppError::BadRequest(format!("Usage: {}", self.usage())))?;
+
+        // The poll message itself reaches the conversation through the
+        // structured message service, so only the invoker gets a reply here
+        self.structured_message_service
+            .create(
+                ctx.user_id,
+                CreateStructuredMessageRequest {
+                    recipient_id: ctx.recipient_id,
+                    session_id: ctx.session_id,
+                    group_id: ctx.group_id,
+                    content: StructuredMessageContent::Poll {
+                        question: question.clone(),
+                        options: options.to_vec(),
+                        allow_multiple: false,
+                        closes_at: None,
+                    },
+                },
+            )
+            .await?;
+
+        Ok(CommandResponse::ephemeral_text(format!("Poll posted: {}", question.trim())))
+    }
+}
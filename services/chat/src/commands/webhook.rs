@@ -0,0 +1,225 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use sha2::Sha256;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    config::CommandConfig,
+    models::{BotResponse, CommandResponse, RegisterBotRequest},
+};
+
+use super::{CommandContext, CommandInvocation};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Forwards commands owned by external bots to their webhook. Requests are
+/// signed with the bot's secret as `sha256=hex(hmac(timestamp + "." + body))`
+/// so bots can verify they came from the chat service.
+#[derive(Clone)]
+pub struct WebhookBotClient {
+    db_pool: PgPool,
+    client: Client,
+}
+
+impl WebhookBotClient {
+    pub fn new(db_pool: PgPool, config: &CommandConfig) -> Self {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(config.bot_webhook_timeout_seconds))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self { db_pool, client }
+    }
+
+    pub async fn find_bot(&self, command: &str) -> Result<Option<ChatBotRow>, AppError> {
+        let query = format!("{} WHERE command = $1 AND is_active = true", SELECT_BOT);
+
+        sqlx::query_as::<_, ChatBotRow>(&query)
+            .bind(command)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to look up bot: {}", e)))
+    }
+
+    pub async fn invoke(
+        &self,
+        bot: &ChatBotRow,
+        ctx: &CommandContext,
+        invocation: &CommandInvocation,
+    ) -> Result<CommandResponse, AppError> {
+        let body = serde_json::json!({
+            "bot_id": bot.bot_id,
+            "command": invocation.name,
+            "args": invocation.args,
+            "text": invocation.raw_args,
+            "user_id": ctx.user_id,
+            "username": ctx.username,
+            "recipient_id": ctx.recipient_id,
+            "session_id": ctx.session_id,
+            "group_id": ctx.group_id,
+        })
+        .to_string();
+
+        let timestamp = Utc::now().timestamp().to_string();
+        let signature = sign_payload(&bot.signing_secret, &timestamp, &body)?;
+
+        let response = self.client
+            .post(&bot.webhook_url)
+            .header("Content-Type", "application/json")
+            .header("X-LinkWithMentor-Timestamp", &timestamp)
+            .header("X-LinkWithMentor-Signature", format!("sha256={}", signature))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Bot /{} did not respond: {}", bot.command, e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Bot /{} returned {}",
+                bot.command,
+                response.status()
+            )));
+        }
+
+        response
+            .json::<CommandResponse>()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Bot /{} sent an invalid reply: {}", bot.command, e)))
+    }
+
+    pub async fn register_bot(
+        &self,
+        created_by: Uuid,
+        command: String,
+        request: RegisterBotRequest,
+    ) -> Result<BotResponse, AppError> {
+        if command.is_empty() || !command.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(AppError::BadRequest(
+                "Command names may only contain letters, digits, '_' and '-'".to_string()
+            ));
+        }
+
+        let url = reqwest::Url::parse(&request.webhook_url)
+            .map_err(|_| AppError::BadRequest("Invalid webhook URL".to_string()))?;
+        if url.scheme() != "https" && url.scheme() != "http" {
+            return Err(AppError::BadRequest("Webhook URL must use http or https".to_string()));
+        }
+
+        let bot_id = Uuid::new_v4();
+        let signing_secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let created_at = Utc::now();
+
+        let query = r#"
+            INSERT INTO chat_bots (
+                bot_id, command, description, usage, webhook_url, signing_secret,
+                is_active, created_by, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, true, $7, $8)
+        "#;
+
+        sqlx::query(query)
+            .bind(bot_id)
+            .bind(&command)
+            .bind(&request.description)
+            .bind(&request.usage)
+            .bind(&request.webhook_url)
+            .bind(&signing_secret)
+            .bind(created_by)
+            .bind(created_at)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db) if db.is_unique_violation() => {
+                    AppError::Conflict(format!("/{} is already registered", command))
+                }
+                e => AppError::Database(format!("Failed to register bot: {}", e)),
+            })?;
+
+        Ok(BotResponse {
+            bot_id,
+            command,
+            description: request.description,
+            usage: request.usage,
+            webhook_url: request.webhook_url,
+            // Only revealed once, at registration
+            signing_secret: Some(signing_secret),
+            is_active: true,
+            created_by,
+            created_at,
+        })
+    }
+
+    pub async fn list_bots(&self, active_only: bool) -> Result<Vec<BotResponse>, AppError> {
+        let query = if active_only {
+            format!("{} WHERE is_active = true ORDER BY command", SELECT_BOT)
+        } else {
+            format!("{} ORDER BY command", SELECT_BOT)
+        };
+
+        let rows = sqlx::query_as::<_, ChatBotRow>(&query)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch bots: {}", e)))?;
+
+        Ok(rows.into_iter()
+            .map(|row| BotResponse {
+                bot_id: row.bot_id,
+                command: row.command,
+                description: row.description,
+                usage: row.usage,
+                webhook_url: row.webhook_url,
+                signing_secret: None,
+                is_active: row.is_active,
+                created_by: row.created_by,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    pub async fn deactivate_bot(&self, bot_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("UPDATE chat_bots SET is_active = false WHERE bot_id = $1")
+            .bind(bot_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to deactivate bot: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Bot not found".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+fn sign_payload(secret: &str, timestamp: &str, body: &str) -> Result<String, AppError> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|_| AppError::Internal("Invalid bot signing secret".to_string()))?;
+
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+const SELECT_BOT: &str = r#"
+    SELECT bot_id, command, description, usage, webhook_url, signing_secret,
+           is_active, created_by, created_at
+    FROM chat_bots
+"#;
+
+// Database row structs
+#[derive(sqlx::FromRow)]
+pub struct ChatBotRow {
+    pub bot_id: Uuid,
+    pub command: String,
+    description: String,
+    usage: String,
+    webhook_url: String,
+    signing_secret: String,
+    is_active: bool,
+    created_by: Uuid,
+    created_at: DateTime<Utc>,
+}
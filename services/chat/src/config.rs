@@ -9,6 +9,11 @@ pub struct ChatConfig {
     pub jwt: JwtConfig,
     pub websocket: WebSocketConfig,
     pub moderation: ModerationConfig,
+    pub export: ExportConfig,
+    pub commands: CommandConfig,
+    /// Shared secret other services send as `X-Internal-Token` on internal
+    /// endpoints. Internal calls are refused while it is empty.
+    pub internal_service_token: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +23,7 @@ pub struct WebSocketConfig {
     pub connection_timeout_seconds: u64,
     pub max_message_size: usize,
     pub rate_limit_messages_per_minute: u32,
+    pub scheduled_message_poll_seconds: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +34,23 @@ pub struct ModerationConfig {
     pub blocked_words: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    pub storage_path: String,
+    pub public_base_url: String,
+    pub attachment_base_url: String,
+    pub notifications_service_url: String,
+    pub link_expiry_hours: u32,
+    pub batch_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandConfig {
+    pub meetings_service_url: String,
+    pub default_currency: String,
+    pub bot_webhook_timeout_seconds: u64,
+}
+
 impl ChatConfig {
     pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
         Ok(Self {
@@ -103,6 +126,10 @@ impl ChatConfig {
                     .unwrap_or_else(|_| "60".to_string())
                     .parse()
                     .unwrap_or(60),
+                scheduled_message_poll_seconds: std::env::var("SCHEDULED_MESSAGE_POLL_SECONDS")
+                    .unwrap_or_else(|_| "15".to_string())
+                    .parse()
+                    .unwrap_or(15),
             },
             moderation: ModerationConfig {
                 enable_auto_moderation: std::env::var("ENABLE_AUTO_MODERATION")
@@ -122,6 +149,36 @@ impl ChatConfig {
                     .filter(|s| !s.is_empty())
                     .collect(),
             },
+            export: ExportConfig {
+                storage_path: std::env::var("CHAT_EXPORT_STORAGE_PATH")
+                    .unwrap_or_else(|_| "/tmp/linkwithmentor/chat-exports".to_string()),
+                public_base_url: std::env::var("CHAT_PUBLIC_BASE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8000".to_string()),
+                attachment_base_url: std::env::var("CHAT_ATTACHMENT_BASE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8000/attachments".to_string()),
+                notifications_service_url: std::env::var("NOTIFICATIONS_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8006".to_string()),
+                link_expiry_hours: std::env::var("CHAT_EXPORT_LINK_EXPIRY_HOURS")
+                    .unwrap_or_else(|_| "72".to_string())
+                    .parse()
+                    .unwrap_or(72),
+                batch_size: std::env::var("CHAT_EXPORT_BATCH_SIZE")
+                    .unwrap_or_else(|_| "500".to_string())
+                    .parse()
+                    .unwrap_or(500),
+            },
+            commands: CommandConfig {
+                meetings_service_url: std::env::var("MEETINGS_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8004".to_string()),
+                default_currency: std::env::var("CHAT_DEFAULT_CURRENCY")
+                    .unwrap_or_else(|_| "INR".to_string()),
+                bot_webhook_timeout_seconds: std::env::var("CHAT_BOT_WEBHOOK_TIMEOUT")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
+            },
+            internal_service_token: std::env::var("INTERNAL_SERVICE_TOKEN")
+                .unwrap_or_default(),
         })
     }
 }
\ No newline at end of file
@@ -15,8 +15,8 @@ pub type WSSender = mpsc::UnboundedSender<Message>;
 
 #[derive(Clone)]
 pub struct ConnectionManager {
-    // User connections: user_id -> list of connection senders
-    connections: Arc<DashMap<Uuid, Vec<WSSender>>>,
+    // User connections: user_id -> (connection_id, sender) per device
+    connections: Arc<DashMap<Uuid, Vec<(String, WSSender)>>>,
     // Connection info: connection_id -> connection info
     connection_info: Arc<DashMap<String, ConnectionInfo>>,
     // Chat rooms: room_id -> room info
@@ -49,7 +49,7 @@ impl ConnectionManager {
         // Add to connections map
         self.connections.entry(user_id)
             .or_insert_with(Vec::new)
-            .push(sender);
+            .push((connection_id.clone(), sender));
 
         // Store connection info
         let connection_info = ConnectionInfo {
@@ -67,9 +67,6 @@ impl ConnectionManager {
         self.user_presence.insert(user_id, Utc::now());
 
         tracing::info!("User {} connected with connection {}", username, connection_id);
-        
-        // Notify other users about user coming online
-        self.broadcast_user_status_change(user_id, username, true).await;
 
         Ok(())
     }
@@ -78,13 +75,10 @@ impl ConnectionManager {
     pub async fn remove_connection(&self, connection_id: &str) -> Result<(), AppError> {
         if let Some((_, connection_info)) = self.connection_info.remove(connection_id) {
             let user_id = connection_info.user_id;
-            let username = connection_info.username.clone();
 
-            // Remove from connections map
+            // Remove only this device's sender; other devices stay connected
             if let Some(mut connections) = self.connections.get_mut(&user_id) {
-                // In a real implementation, you'd need to identify which sender to remove
-                // For now, we'll clear all connections for this user when any connection closes
-                connections.clear();
+                connections.retain(|(id, _)| id != connection_id);
             }
 
             // Check if user has any remaining connections
@@ -100,9 +94,6 @@ impl ConnectionManager {
                 for mut typing_users in self.typing_indicators.iter_mut() {
                     typing_users.retain(|&id| id != user_id);
                 }
-
-                // Notify other users about user going offline
-                self.broadcast_user_status_change(user_id, username, false).await;
             }
 
             tracing::info!("Connection {} removed for user {}", connection_id, user_id);
@@ -121,7 +112,7 @@ impl ConnectionManager {
         if let Some(connections) = self.connections.get(&user_id) {
             let mut failed_connections = Vec::new();
             
-            for (index, sender) in connections.iter().enumerate() {
+            for (index, (_, sender)) in connections.iter().enumerate() {
                 if sender.send(ws_message.clone()).is_err() {
                     failed_connections.push(index);
                 }
@@ -165,6 +156,26 @@ impl ConnectionManager {
         Ok(())
     }
 
+    // Send message to everyone in the conversation a message belongs to
+    pub async fn send_to_conversation(
+        &self,
+        sender_id: Uuid,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        message: WSMessage,
+    ) -> Result<(), AppError> {
+        if let Some(recipient_id) = recipient_id {
+            self.broadcast_to_users(&[sender_id, recipient_id], message).await
+        } else if let Some(session_id) = session_id {
+            self.send_to_room(&format!("session_{}", session_id), message, None).await
+        } else if let Some(group_id) = group_id {
+            self.send_to_room(&format!("group_{}", group_id), message, None).await
+        } else {
+            Ok(())
+        }
+    }
+
     // Join a chat room
     pub async fn join_room(&self, user_id: Uuid, room_id: String, room_type: ChatRoomType) -> Result<(), AppError> {
         // Create or get existing room
@@ -223,8 +234,8 @@ impl ConnectionManager {
         Ok(())
     }
 
-    // Get online users
-    pub async fn get_online_users(&self) -> Vec<Uuid> {
+    // Get users with a connection to this instance (see PresenceService for cluster-wide presence)
+    pub async fn get_local_users(&self) -> Vec<Uuid> {
         self.connections.iter()
             .filter(|entry| !entry.value().is_empty())
             .map(|entry| *entry.key())
@@ -279,33 +290,6 @@ impl ConnectionManager {
             .unwrap_or(0)
     }
 
-    // Broadcast user status change
-    async fn broadcast_user_status_change(&self, user_id: Uuid, username: String, is_online: bool) {
-        let message = if is_online {
-            WSMessage::UserJoined {
-                user_id,
-                username,
-                session_id: None,
-                group_id: None,
-            }
-        } else {
-            WSMessage::UserLeft {
-                user_id,
-                username,
-                session_id: None,
-                group_id: None,
-            }
-        };
-
-        // Broadcast to all connected users
-        let online_users = self.get_online_users().await;
-        for online_user_id in online_users {
-            if online_user_id != user_id {
-                let _ = self.send_to_user(online_user_id, message.clone()).await;
-            }
-        }
-    }
-
     // Clean up inactive connections
     pub async fn cleanup_inactive_connections(&self, timeout_seconds: u64) {
         let cutoff_time = Utc::now() - chrono::Duration::seconds(timeout_seconds as i64);
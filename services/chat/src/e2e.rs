@@ -0,0 +1,559 @@
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use linkwithmentor_common::{ApiResponse, AppError, RedisService};
+use crate::models::{
+    EncryptedConversationResponse, MessageReportResponse, OneTimePreKey, PreKeyBundleResponse,
+    PreKeyCountResponse, PublishKeyBundleRequest, ReportEncryptedMessageRequest, SignedPreKey,
+};
+
+const MAX_PREKEYS_PER_UPLOAD: usize = 100;
+const MAX_PUBLIC_KEY_BYTES: usize = 64;
+const MAX_SIGNATURE_BYTES: usize = 128;
+/// Upper bound for a ciphertext envelope after base64 decoding
+pub const MAX_CIPHERTEXT_BYTES: usize = 64 * 1024;
+/// Bundle fetches allowed per requester/target pair in each window. Every
+/// fetch burns one of the target's one-time prekeys.
+const BUNDLE_CLAIMS_PER_WINDOW: u32 = 5;
+const BUNDLE_CLAIM_WINDOW_SECONDS: u64 = 3600;
+
+/// Public key directory and opt-in registry for end-to-end encrypted direct
+/// messages. The server only ever sees public keys and ciphertext; private
+/// keys and session state stay on the clients.
+#[derive(Clone)]
+pub struct E2eService {
+    db_pool: PgPool,
+    redis_service: RedisService,
+    http_client: Client,
+    safety_service_url: String,
+}
+
+impl E2eService {
+    pub fn new(db_pool: PgPool, redis_service: RedisService, safety_service_url: String) -> Self {
+        Self {
+            db_pool,
+            redis_service,
+            http_client: Client::new(),
+            safety_service_url,
+        }
+    }
+
+    /// Publishes (or rotates) the caller's identity key and signed prekey,
+    /// optionally with a first batch of one-time prekeys.
+    pub async fn publish_keys(&self, user_id: Uuid, request: PublishKeyBundleRequest) -> Result<PreKeyCountResponse, AppError> {
+        validate_key(&request.identity_key, MAX_PUBLIC_KEY_BYTES, "identity_key")?;
+        validate_key(&request.signed_prekey.public_key, MAX_PUBLIC_KEY_BYTES, "signed_prekey.public_key")?;
+        validate_key(&request.signed_prekey.signature, MAX_SIGNATURE_BYTES, "signed_prekey.signature")?;
+        validate_prekeys(&request.one_time_prekeys)?;
+
+        let mut tx = self.db_pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let existing_identity: Option<String> = sqlx::query_scalar(
+            "SELECT identity_key FROM e2e_identity_keys WHERE user_id = $1 FOR UPDATE"
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch identity key: {}", e)))?;
+
+        // A new identity invalidates every one-time prekey signed for the old one
+        if existing_identity.as_deref().map_or(false, |key| key != request.identity_key) {
+            sqlx::query("DELETE FROM e2e_one_time_prekeys WHERE user_id = $1")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to clear prekeys: {}", e)))?;
+        }
+
+        let query = r#"
+            INSERT INTO e2e_identity_keys (
+                user_id, identity_key, signed_prekey_id, signed_prekey, signed_prekey_signature, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE SET
+                identity_key = EXCLUDED.identity_key,
+                signed_prekey_id = EXCLUDED.signed_prekey_id,
+                signed_prekey = EXCLUDED.signed_prekey,
+                signed_prekey_signature = EXCLUDED.signed_prekey_signature,
+                updated_at = EXCLUDED.updated_at
+        "#;
+
+        sqlx::query(query)
+            .bind(user_id)
+            .bind(&request.identity_key)
+            .bind(request.signed_prekey.key_id)
+            .bind(&request.signed_prekey.public_key)
+            .bind(&request.signed_prekey.signature)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to store identity key: {}", e)))?;
+
+        insert_prekeys(&mut tx, user_id, &request.one_time_prekeys).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to commit key bundle: {}", e)))?;
+
+        self.prekey_count(user_id).await
+    }
+
+    pub async fn upload_prekeys(&self, user_id: Uuid, prekeys: Vec<OneTimePreKey>) -> Result<PreKeyCountResponse, AppError> {
+        validate_prekeys(&prekeys)?;
+
+        if !self.has_identity(user_id).await? {
+            return Err(AppError::BadRequest("Publish an identity key before uploading prekeys".to_string()));
+        }
+
+        let mut tx = self.db_pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        insert_prekeys(&mut tx, user_id, &prekeys).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to commit prekeys: {}", e)))?;
+
+        self.prekey_count(user_id).await
+    }
+
+    pub async fn prekey_count(&self, user_id: Uuid) -> Result<PreKeyCountResponse, AppError> {
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM e2e_one_time_prekeys WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count prekeys: {}", e)))?;
+
+        Ok(PreKeyCountResponse { remaining })
+    }
+
+    /// Returns a user's key bundle for starting a session. Each call consumes
+    /// one one-time prekey so it is never handed out twice, which is why only
+    /// people the user already talks to may fetch it, and only a few times.
+    pub async fn claim_bundle(&self, requester_id: Uuid, user_id: Uuid) -> Result<PreKeyBundleResponse, AppError> {
+        if requester_id == user_id {
+            return Err(AppError::BadRequest("Cannot fetch your own key bundle".to_string()));
+        }
+
+        if !self.has_relationship(requester_id, user_id).await? {
+            return Err(AppError::Forbidden(
+                "You can only fetch keys for users you share a conversation with".to_string()
+            ));
+        }
+
+        let rate_key = format!("rate_limit:e2e_bundle:{}:{}", requester_id, user_id);
+        let allowed = self.redis_service
+            .check_rate_limit(&rate_key, BUNDLE_CLAIMS_PER_WINDOW, BUNDLE_CLAIM_WINDOW_SECONDS)
+            .await?;
+        if !allowed {
+            return Err(AppError::TooManyRequests(
+                "Key bundle requested too often for this user".to_string()
+            ));
+        }
+
+        let identity = sqlx::query_as::<_, IdentityKeyRow>(
+            r#"
+            SELECT identity_key, signed_prekey_id, signed_prekey, signed_prekey_signature
+            FROM e2e_identity_keys
+            WHERE user_id = $1
+            "#
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch identity key: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("User has not published encryption keys".to_string()))?;
+
+        let one_time_prekey = sqlx::query_as::<_, OneTimePreKeyRow>(
+            r#"
+            DELETE FROM e2e_one_time_prekeys
+            WHERE (user_id, key_id) = (
+                SELECT user_id, key_id FROM e2e_one_time_prekeys
+                WHERE user_id = $1
+                ORDER BY created_at, key_id
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING key_id, public_key
+            "#
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to claim prekey: {}", e)))?;
+
+        Ok(PreKeyBundleResponse {
+            user_id,
+            identity_key: identity.identity_key,
+            signed_prekey: SignedPreKey {
+                key_id: identity.signed_prekey_id,
+                public_key: identity.signed_prekey,
+                signature: identity.signed_prekey_signature,
+            },
+            one_time_prekey: one_time_prekey.map(|row| OneTimePreKey {
+                key_id: row.key_id,
+                public_key: row.public_key,
+            }),
+        })
+    }
+
+    /// Opts the caller into E2E for a direct conversation they already have.
+    /// The conversation is encrypted once both users have opted in, since it
+    /// turns off server moderation for the pair. That is one-way: once
+    /// enabled the server refuses plaintext between the pair, so a
+    /// compromised client or server can't silently downgrade it.
+    pub async fn enable_conversation(&self, user_id: Uuid, peer_id: Uuid) -> Result<EncryptedConversationResponse, AppError> {
+        if user_id == peer_id {
+            return Err(AppError::BadRequest("Cannot start an encrypted conversation with yourself".to_string()));
+        }
+
+        if !self.has_relationship(user_id, peer_id).await? {
+            return Err(AppError::Forbidden(
+                "Encrypted conversations are only available with people you already talk to".to_string()
+            ));
+        }
+
+        if !self.has_identity(user_id).await? || !self.has_identity(peer_id).await? {
+            return Err(AppError::BadRequest(
+                "Both participants must publish encryption keys first".to_string()
+            ));
+        }
+
+        let (user_low, user_high) = ordered_pair(user_id, peer_id);
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // Both users opting in at once must still pair up
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("e2e_conversations:{}:{}", user_low, user_high))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to lock conversation: {}", e)))?;
+
+        let peer_opted_in = sqlx::query("DELETE FROM e2e_conversation_requests WHERE requester_id = $1 AND peer_id = $2")
+            .bind(peer_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch encryption request: {}", e)))?
+            .rows_affected() > 0;
+
+        if peer_opted_in {
+            sqlx::query(
+                r#"
+                INSERT INTO e2e_conversations (user_low, user_high, enabled_by, enabled_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_low, user_high) DO NOTHING
+                "#
+            )
+            .bind(user_low)
+            .bind(user_high)
+            .bind(user_id)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to enable encryption: {}", e)))?;
+        } else {
+            sqlx::query(
+                r#"
+                INSERT INTO e2e_conversation_requests (requester_id, peer_id)
+                SELECT $1, $2
+                WHERE NOT EXISTS (SELECT 1 FROM e2e_conversations WHERE user_low = $3 AND user_high = $4)
+                ON CONFLICT (requester_id, peer_id) DO NOTHING
+                "#
+            )
+            .bind(user_id)
+            .bind(peer_id)
+            .bind(user_low)
+            .bind(user_high)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to request encryption: {}", e)))?;
+        }
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        self.conversation_status(user_id, peer_id).await
+    }
+
+    pub async fn conversation_status(&self, user_id: Uuid, peer_id: Uuid) -> Result<EncryptedConversationResponse, AppError> {
+        let (user_low, user_high) = ordered_pair(user_id, peer_id);
+        let row = sqlx::query_as::<_, EncryptedConversationRow>(
+            "SELECT enabled_by, enabled_at FROM e2e_conversations WHERE user_low = $1 AND user_high = $2"
+        )
+        .bind(user_low)
+        .bind(user_high)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch encryption status: {}", e)))?;
+
+        let requested_by = match row {
+            Some(_) => None,
+            None => sqlx::query_scalar::<_, Uuid>(
+                r#"
+                SELECT requester_id FROM e2e_conversation_requests
+                WHERE (requester_id = $1 AND peer_id = $2) OR (requester_id = $2 AND peer_id = $1)
+                ORDER BY requested_at
+                LIMIT 1
+                "#
+            )
+            .bind(user_id)
+            .bind(peer_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch encryption request: {}", e)))?,
+        };
+
+        Ok(EncryptedConversationResponse {
+            peer_id,
+            is_encrypted: row.is_some(),
+            enabled_by: row.as_ref().map(|row| row.enabled_by),
+            enabled_at: row.map(|row| row.enabled_at),
+            requested_by,
+        })
+    }
+
+    /// Forwards a user's report of an encrypted message to the safety service.
+    /// Moderators only ever see what the reporter chooses to decrypt and share.
+    pub async fn report_message(
+        &self,
+        reporter_id: Uuid,
+        auth_header: Option<String>,
+        message_id: Uuid,
+        request: ReportEncryptedMessageRequest,
+    ) -> Result<MessageReportResponse, AppError> {
+        if request.decrypted_content.trim().is_empty() {
+            return Err(AppError::BadRequest("Decrypted content is required".to_string()));
+        }
+
+        let message = sqlx::query_as::<_, EncryptedMessageRow>(
+            "SELECT sender_id, recipient_id, content, is_encrypted FROM messages WHERE message_id = $1"
+        )
+        .bind(message_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch message: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+        if !message.is_encrypted {
+            return Err(AppError::BadRequest(
+                "Only encrypted messages can be reported with decrypted content".to_string()
+            ));
+        }
+
+        // Only the recipient could have decrypted it
+        if message.recipient_id != Some(reporter_id) {
+            return Err(AppError::Forbidden("You can only report messages sent to you".to_string()));
+        }
+
+        let ciphertext_hash = hex::encode(Sha256::digest(message.content.as_bytes()));
+        let payload = serde_json::json!({
+            "reported_content_type": "Text",
+            "reported_content_id": message_id,
+            "reported_user_id": message.sender_id,
+            "report_type": request.report_type,
+            "description": request.description,
+            "evidence": [request.decrypted_content],
+            "metadata": {
+                "source": "chat_e2e_user_report",
+                "ciphertext_sha256": ciphertext_hash,
+            },
+        });
+
+        let mut http_request = self.http_client
+            .post(format!("{}/reports", self.safety_service_url))
+            .json(&payload);
+        if let Some(auth_header) = auth_header {
+            http_request = http_request.header("Authorization", auth_header);
+        }
+
+        let response = http_request
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Safety service unavailable: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Safety service rejected report: {}",
+                response.status()
+            )));
+        }
+
+        let body: ApiResponse<SafetyReportRow> = response
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Invalid report response: {}", e)))?;
+
+        let report = body.data
+            .ok_or_else(|| AppError::ExternalService("Safety service returned no report".to_string()))?;
+
+        tracing::info!("User {} reported encrypted message {}", reporter_id, message_id);
+
+        Ok(MessageReportResponse {
+            report_id: report.report_id,
+            message_id,
+            submitted_at: report.created_at,
+        })
+    }
+
+    /// A shared mentorship session, an active shared group or an earlier
+    /// direct message in either direction.
+    async fn has_relationship(&self, user_a: Uuid, user_b: Uuid) -> Result<bool, AppError> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM mentorship_sessions
+                WHERE (mentor_id = $1 AND mentee_id = $2) OR (mentor_id = $2 AND mentee_id = $1)
+            ) OR EXISTS(
+                SELECT 1 FROM group_chat_participants a
+                JOIN group_chat_participants b ON b.group_id = a.group_id
+                WHERE a.user_id = $1 AND b.user_id = $2 AND a.left_at IS NULL AND b.left_at IS NULL
+            ) OR EXISTS(
+                SELECT 1 FROM messages
+                WHERE (sender_id = $1 AND recipient_id = $2) OR (sender_id = $2 AND recipient_id = $1)
+            )
+            "#
+        )
+        .bind(user_a)
+        .bind(user_b)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to check relationship: {}", e)))
+    }
+
+    async fn has_identity(&self, user_id: Uuid) -> Result<bool, AppError> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM e2e_identity_keys WHERE user_id = $1)")
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check identity key: {}", e)))
+    }
+}
+
+/// True when the direct conversation between two users has opted into E2E.
+pub async fn is_encrypted_conversation(db_pool: &PgPool, user_a: Uuid, user_b: Uuid) -> Result<bool, AppError> {
+    let (user_low, user_high) = ordered_pair(user_a, user_b);
+
+    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM e2e_conversations WHERE user_low = $1 AND user_high = $2)")
+        .bind(user_low)
+        .bind(user_high)
+        .fetch_one(db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to check encryption status: {}", e)))
+}
+
+/// Ciphertext must be base64 so it round-trips through JSON untouched.
+pub fn validate_ciphertext(content: &str) -> Result<(), AppError> {
+    let decoded = general_purpose::STANDARD
+        .decode(content.trim())
+        .map_err(|_| AppError::BadRequest("Encrypted content must be base64".to_string()))?;
+
+    if decoded.is_empty() || decoded.len() > MAX_CIPHERTEXT_BYTES {
+        return Err(AppError::BadRequest("Encrypted content has an invalid size".to_string()));
+    }
+
+    Ok(())
+}
+
+fn ordered_pair(a: Uuid, b: Uuid) -> (Uuid, Uuid) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+fn validate_key(value: &str, max_bytes: usize, field: &str) -> Result<(), AppError> {
+    let decoded = general_purpose::STANDARD
+        .decode(value)
+        .map_err(|_| AppError::BadRequest(format!("{} must be base64", field)))?;
+
+    if decoded.is_empty() || decoded.len() > max_bytes {
+        return Err(AppError::BadRequest(format!("{} has an invalid length", field)));
+    }
+
+    Ok(())
+}
+
+fn validate_prekeys(prekeys: &[OneTimePreKey]) -> Result<(), AppError> {
+    if prekeys.len() > MAX_PREKEYS_PER_UPLOAD {
+        return Err(AppError::BadRequest(format!(
+            "At most {} prekeys can be uploaded at once",
+            MAX_PREKEYS_PER_UPLOAD
+        )));
+    }
+
+    for prekey in prekeys {
+        validate_key(&prekey.public_key, MAX_PUBLIC_KEY_BYTES, "one_time_prekeys.public_key")?;
+    }
+
+    Ok(())
+}
+
+async fn insert_prekeys(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    prekeys: &[OneTimePreKey],
+) -> Result<(), AppError> {
+    let created_at = Utc::now();
+
+    for prekey in prekeys {
+        sqlx::query(
+            r#"
+            INSERT INTO e2e_one_time_prekeys (user_id, key_id, public_key, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, key_id) DO NOTHING
+            "#
+        )
+        .bind(user_id)
+        .bind(prekey.key_id)
+        .bind(&prekey.public_key)
+        .bind(created_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to store prekey: {}", e)))?;
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct SafetyReportRow {
+    report_id: Uuid,
+    created_at: DateTime<Utc>,
+}
+
+// Database row structs
+#[derive(sqlx::FromRow)]
+struct IdentityKeyRow {
+    identity_key: String,
+    signed_prekey_id: i32,
+    signed_prekey: String,
+    signed_prekey_signature: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct OneTimePreKeyRow {
+    key_id: i32,
+    public_key: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct EncryptedConversationRow {
+    enabled_by: Uuid,
+    enabled_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct EncryptedMessageRow {
+    sender_id: Uuid,
+    recipient_id: Option<Uuid>,
+    content: String,
+    is_encrypted: bool,
+}
@@ -0,0 +1,767 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use uuid::Uuid;
+
+use linkwithmentor_common::{AppError, MessageType};
+use crate::{
+    config::ExportConfig,
+    models::{CreateExportRequest, ExportFormat, ExportJobResponse, ExportStatus},
+};
+
+/// Renders conversation transcripts in the background and hands the
+/// requester a download link through the notifications service's internal
+/// endpoint, authenticated with the shared service token.
+#[derive(Clone)]
+pub struct ExportService {
+    db_pool: PgPool,
+    config: ExportConfig,
+    internal_service_token: String,
+    http_client: reqwest::Client,
+}
+
+/// The conversation an export covers.
+#[derive(Debug, Clone)]
+enum ConversationScope {
+    Direct { user_id: Uuid, peer_id: Uuid },
+    Session(Uuid),
+    Group(Uuid),
+}
+
+impl ConversationScope {
+    fn from_request(user_id: Uuid, request: &CreateExportRequest) -> Result<Self, AppError> {
+        match (request.recipient_id, request.session_id, request.group_id) {
+            (Some(peer_id), None, None) => Ok(Self::Direct { user_id, peer_id }),
+            (None, Some(session_id), None) => Ok(Self::Session(session_id)),
+            (None, None, Some(group_id)) => Ok(Self::Group(group_id)),
+            _ => Err(AppError::BadRequest(
+                "Specify exactly one of recipient_id, session_id or group_id".to_string()
+            )),
+        }
+    }
+
+    /// SQL filter over `messages m` and the values to bind for it, in order.
+    fn sql_filter(&self) -> (String, Vec<Uuid>) {
+        match self {
+            Self::Direct { user_id, peer_id } => (
+                "((m.sender_id = $1 AND m.recipient_id = $2) OR (m.sender_id = $2 AND m.recipient_id = $1))"
+                    .to_string(),
+                vec![*user_id, *peer_id],
+            ),
+            Self::Session(session_id) => ("m.session_id = $1".to_string(), vec![*session_id]),
+            Self::Group(group_id) => ("m.group_id = $1".to_string(), vec![*group_id]),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Direct { peer_id, .. } => format!("Direct conversation with {}", peer_id),
+            Self::Session(session_id) => format!("Session {}", session_id),
+            Self::Group(group_id) => format!("Group {}", group_id),
+        }
+    }
+}
+
+impl ExportService {
+    pub fn new(db_pool: PgPool, config: ExportConfig, internal_service_token: String) -> Self {
+        Self {
+            db_pool,
+            config,
+            internal_service_token,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn request_export(
+        &self,
+        user_id: Uuid,
+        request: CreateExportRequest,
+    ) -> Result<ExportJobResponse, AppError> {
+        let scope = ConversationScope::from_request(user_id, &request)?;
+
+        // Postgres can't store chrono's minimum, and nothing predates the epoch anyway
+        let from = request.from.unwrap_or(DateTime::UNIX_EPOCH);
+        let to = request.to.unwrap_or_else(Utc::now);
+        if from >= to {
+            return Err(AppError::BadRequest("Export range start must be before its end".to_string()));
+        }
+
+        self.ensure_conversation_access(user_id, &scope).await?;
+
+        let export_id = Uuid::new_v4();
+        let created_at = Utc::now();
+
+        let query = r#"
+            INSERT INTO conversation_exports (
+                export_id, requested_by, recipient_id, session_id, group_id,
+                format, range_start, range_end, status, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#;
+
+        sqlx::query(query)
+            .bind(export_id)
+            .bind(user_id)
+            .bind(request.recipient_id)
+            .bind(request.session_id)
+            .bind(request.group_id)
+            .bind(&request.format)
+            .bind(from)
+            .bind(to)
+            .bind(&ExportStatus::Pending)
+            .bind(created_at)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create export job: {}", e)))?;
+
+        // Large histories can take a while to render, so the job runs detached
+        let service = self.clone();
+        let format = request.format.clone();
+        tokio::spawn(async move {
+            if let Err(e) = service
+                .run_export(export_id, user_id, scope, format, from, to)
+                .await
+            {
+                tracing::error!("Conversation export {} failed: {}", export_id, e);
+                service.mark_failed(export_id, &e.to_string()).await;
+            }
+        });
+
+        Ok(ExportJobResponse {
+            export_id,
+            format: request.format,
+            status: ExportStatus::Pending,
+            message_count: None,
+            download_url: None,
+            expires_at: None,
+            error_message: None,
+            created_at,
+            completed_at: None,
+        })
+    }
+
+    pub async fn get_export(&self, export_id: Uuid, user_id: Uuid) -> Result<ExportJobResponse, AppError> {
+        let query = r#"
+            SELECT export_id, format, status, message_count, download_token,
+                   expires_at, error_message, created_at, completed_at
+            FROM conversation_exports
+            WHERE export_id = $1 AND requested_by = $2
+        "#;
+
+        let row = sqlx::query_as::<_, ExportJobRow>(query)
+            .bind(export_id)
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch export job: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Export not found".to_string()))?;
+
+        let download_url = match (&row.status, row.download_token) {
+            (ExportStatus::Completed, Some(token)) => Some(self.download_url(token)),
+            _ => None,
+        };
+
+        Ok(ExportJobResponse {
+            export_id: row.export_id,
+            format: row.format,
+            status: row.status,
+            message_count: row.message_count,
+            download_url,
+            expires_at: row.expires_at,
+            error_message: row.error_message,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+        })
+    }
+
+    /// Resolves a download token to the rendered file. The token is the only
+    /// credential, so it must be unexpired and belong to a completed export.
+    pub async fn open_download(&self, download_token: Uuid) -> Result<ExportDownload, AppError> {
+        let query = r#"
+            SELECT export_id, format, file_path
+            FROM conversation_exports
+            WHERE download_token = $1 AND status = $2 AND expires_at > NOW()
+        "#;
+
+        let row = sqlx::query_as::<_, ExportDownloadRow>(query)
+            .bind(download_token)
+            .bind(&ExportStatus::Completed)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch export: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Export link is invalid or has expired".to_string()))?;
+
+        let contents = tokio::fs::read(&row.file_path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to read export file: {}", e)))?;
+
+        Ok(ExportDownload {
+            filename: format!("conversation-{}.{}", row.export_id, row.format.extension()),
+            content_type: row.format.content_type(),
+            contents,
+        })
+    }
+
+    pub async fn start_cleanup_task(&self) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = service.cleanup_expired_exports().await {
+                    tracing::error!("Failed to clean up expired exports: {}", e);
+                }
+            }
+        });
+    }
+
+    // Private helper methods
+
+    async fn run_export(
+        &self,
+        export_id: Uuid,
+        user_id: Uuid,
+        scope: ConversationScope,
+        format: ExportFormat,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        self.update_status(export_id, ExportStatus::Processing).await?;
+
+        tokio::fs::create_dir_all(&self.config.storage_path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to create export directory: {}", e)))?;
+
+        let file_path = PathBuf::from(&self.config.storage_path)
+            .join(format!("{}.{}", export_id, format.extension()));
+        let file = tokio::fs::File::create(&file_path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to create export file: {}", e)))?;
+        let mut writer = BufWriter::new(file);
+
+        let header = TranscriptHeader {
+            export_id,
+            title: scope.describe(),
+            from,
+            to,
+            generated_at: Utc::now(),
+        };
+        write_chunk(&mut writer, &render_header(&format, &header)?).await?;
+
+        // Page through the history with a keyset cursor so memory stays flat
+        let mut cursor = (from, Uuid::nil());
+        let mut message_count: i64 = 0;
+
+        loop {
+            let batch = self.fetch_batch(&scope, to, cursor).await?;
+            let batch_len = batch.len();
+
+            for message in &batch {
+                let chunk = render_message(&format, message, message_count, &self.config.attachment_base_url)?;
+                write_chunk(&mut writer, &chunk).await?;
+                message_count += 1;
+            }
+
+            match batch.last() {
+                Some(last) if batch_len == self.config.batch_size as usize => {
+                    cursor = (last.created_at, last.message_id);
+                }
+                _ => break,
+            }
+        }
+
+        write_chunk(&mut writer, &render_footer(&format, message_count)).await?;
+        writer.flush()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write export file: {}", e)))?;
+
+        let download_token = Uuid::new_v4();
+        let completed_at = Utc::now();
+        let expires_at = completed_at + chrono::Duration::hours(self.config.link_expiry_hours as i64);
+
+        let query = r#"
+            UPDATE conversation_exports
+            SET status = $1, file_path = $2, message_count = $3, download_token = $4,
+                expires_at = $5, completed_at = $6
+            WHERE export_id = $7
+        "#;
+
+        sqlx::query(query)
+            .bind(&ExportStatus::Completed)
+            .bind(file_path.to_string_lossy().to_string())
+            .bind(message_count)
+            .bind(download_token)
+            .bind(expires_at)
+            .bind(completed_at)
+            .bind(export_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to complete export job: {}", e)))?;
+
+        tracing::info!("Conversation export {} completed with {} messages", export_id, message_count);
+
+        self.notify_export_ready(user_id, export_id, download_token, expires_at)
+            .await;
+
+        Ok(())
+    }
+
+    async fn fetch_batch(
+        &self,
+        scope: &ConversationScope,
+        to: DateTime<Utc>,
+        cursor: (DateTime<Utc>, Uuid),
+    ) -> Result<Vec<ExportedMessage>, AppError> {
+        let (scope_filter, scope_params) = scope.sql_filter();
+        let n = scope_params.len();
+
+        let query = format!(
+            r#"
+            SELECT
+                m.message_id, m.sender_id, u.username AS sender_username,
+                -- The server can't decrypt E2E messages; export a placeholder instead of ciphertext
+                CASE WHEN m.is_encrypted THEN '[Encrypted message]' ELSE m.content END AS content,
+                m.message_type, m.created_at, m.is_edited, m.is_deleted,
+                COALESCE(
+                    json_agg(json_build_object(
+                        'filename', a.filename,
+                        'mime_type', a.mime_type,
+                        'file_size', a.file_size,
+                        'file_path', a.file_path
+                    )) FILTER (WHERE a.attachment_id IS NOT NULL),
+                    '[]'
+                ) AS attachments
+            FROM messages m
+            JOIN users u ON m.sender_id = u.user_id
+            LEFT JOIN message_attachments a ON a.message_id = m.message_id
+            WHERE {}
+              AND m.created_at < ${}
+              AND (m.created_at, m.message_id) > (${}, ${})
+            GROUP BY m.message_id, u.username
+            ORDER BY m.created_at ASC, m.message_id ASC
+            LIMIT ${}
+            "#,
+            scope_filter,
+            n + 1,
+            n + 2,
+            n + 3,
+            n + 4,
+        );
+
+        let mut db_query = sqlx::query_as::<_, ExportMessageRow>(&query);
+        for param in scope_params {
+            db_query = db_query.bind(param);
+        }
+
+        let rows = db_query
+            .bind(to)
+            .bind(cursor.0)
+            .bind(cursor.1)
+            .bind(self.config.batch_size as i64)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch messages for export: {}", e)))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let attachments: Vec<ExportedAttachment> = serde_json::from_value(row.attachments)
+                    .map_err(|e| AppError::Internal(format!("Failed to parse attachments: {}", e)))?;
+
+                Ok(ExportedMessage {
+                    message_id: row.message_id,
+                    sender_id: row.sender_id,
+                    sender_username: row.sender_username,
+                    content: row.content,
+                    message_type: row.message_type,
+                    created_at: row.created_at,
+                    is_edited: row.is_edited,
+                    is_deleted: row.is_deleted,
+                    attachments,
+                })
+            })
+            .collect()
+    }
+
+    async fn ensure_conversation_access(&self, user_id: Uuid, scope: &ConversationScope) -> Result<(), AppError> {
+        let has_access = match scope {
+            ConversationScope::Direct { .. } => true,
+            ConversationScope::Session(session_id) => {
+                sqlx::query_scalar::<_, bool>(
+                    "SELECT EXISTS(SELECT 1 FROM mentorship_sessions WHERE session_id = $1 AND (mentor_id = $2 OR mentee_id = $2))"
+                )
+                .bind(session_id)
+                .bind(user_id)
+                .fetch_one(&self.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to check session access: {}", e)))?
+            }
+            ConversationScope::Group(group_id) => {
+                sqlx::query_scalar::<_, bool>(
+                    "SELECT EXISTS(SELECT 1 FROM group_chat_participants WHERE group_id = $1 AND user_id = $2 AND left_at IS NULL)"
+                )
+                .bind(group_id)
+                .bind(user_id)
+                .fetch_one(&self.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to check group access: {}", e)))?
+            }
+        };
+
+        if !has_access {
+            return Err(AppError::Forbidden("You are not a participant in this conversation".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn notify_export_ready(
+        &self,
+        user_id: Uuid,
+        export_id: Uuid,
+        download_token: Uuid,
+        expires_at: DateTime<Utc>,
+    ) {
+        let download_url = self.download_url(download_token);
+        let body = serde_json::json!({
+            "recipient_id": user_id,
+            "notification_type": { "Custom": "ConversationExportReady" },
+            "channels": ["InApp", "Email"],
+            "title": "Your conversation export is ready",
+            "message": format!(
+                "Your conversation export is ready to download until {}: {}",
+                expires_at.format("%Y-%m-%d %H:%M UTC"),
+                download_url
+            ),
+            "template_id": null,
+            "template_data": null,
+            "scheduled_at": null,
+            "priority": "Normal",
+            "metadata": {
+                "export_id": export_id.to_string(),
+                "download_url": download_url,
+            },
+        });
+
+        // The requester's own token may have expired by the time a long export finishes
+        let request = self.http_client
+            .post(format!("{}/internal/notifications", self.config.notifications_service_url))
+            .header("X-Internal-Token", &self.internal_service_token)
+            .json(&body);
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {}
+            Ok(response) => {
+                tracing::warn!("Notification service rejected export {} notice: {}", export_id, response.status());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to notify user {} about export {}: {}", user_id, export_id, e);
+            }
+        }
+    }
+
+    async fn update_status(&self, export_id: Uuid, status: ExportStatus) -> Result<(), AppError> {
+        sqlx::query("UPDATE conversation_exports SET status = $1 WHERE export_id = $2")
+            .bind(&status)
+            .bind(export_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update export status: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn mark_failed(&self, export_id: Uuid, error_message: &str) {
+        let result = sqlx::query(
+            "UPDATE conversation_exports SET status = $1, error_message = $2, completed_at = $3 WHERE export_id = $4"
+        )
+        .bind(&ExportStatus::Failed)
+        .bind(error_message)
+        .bind(Utc::now())
+        .bind(export_id)
+        .execute(&self.db_pool)
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!("Failed to mark export {} as failed: {}", export_id, e);
+        }
+    }
+
+    async fn cleanup_expired_exports(&self) -> Result<(), AppError> {
+        let query = r#"
+            UPDATE conversation_exports
+            SET status = $1
+            WHERE status = $2 AND expires_at <= NOW()
+            RETURNING file_path
+        "#;
+
+        let paths: Vec<Option<String>> = sqlx::query_scalar(query)
+            .bind(&ExportStatus::Expired)
+            .bind(&ExportStatus::Completed)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to expire exports: {}", e)))?;
+
+        for path in paths.into_iter().flatten() {
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                tracing::warn!("Failed to remove expired export file {}: {}", path, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn download_url(&self, download_token: Uuid) -> String {
+        format!("{}/exports/download/{}", self.config.public_base_url, download_token)
+    }
+}
+
+pub struct ExportDownload {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub contents: Vec<u8>,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+            ExportFormat::PlainText => "txt",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Html => "text/html; charset=utf-8",
+            ExportFormat::PlainText => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+// Transcript rendering
+
+#[derive(Debug, Serialize)]
+struct TranscriptHeader {
+    export_id: Uuid,
+    #[serde(rename = "conversation")]
+    title: String,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedMessage {
+    message_id: Uuid,
+    sender_id: Uuid,
+    sender_username: String,
+    content: String,
+    message_type: MessageType,
+    created_at: DateTime<Utc>,
+    is_edited: bool,
+    is_deleted: bool,
+    attachments: Vec<ExportedAttachment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedAttachment {
+    filename: String,
+    mime_type: String,
+    file_size: i64,
+    file_path: String,
+}
+
+fn render_header(format: &ExportFormat, header: &TranscriptHeader) -> Result<String, AppError> {
+    let range = format!(
+        "{} to {}",
+        header.from.format("%Y-%m-%d %H:%M UTC"),
+        header.to.format("%Y-%m-%d %H:%M UTC")
+    );
+
+    Ok(match format {
+        ExportFormat::Json => {
+            let meta = serde_json::to_string(header)
+                .map_err(|e| AppError::Internal(format!("Failed to serialize export header: {}", e)))?;
+            // Messages are streamed into the array after this
+            format!("{{\"export\":{},\"messages\":[", meta)
+        }
+        ExportFormat::Html => format!(
+            concat!(
+                "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n",
+                "<title>{title}</title>\n<style>\n",
+                "body{{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;max-width:820px;margin:2rem auto;color:#1f2933}}\n",
+                "header{{border-bottom:1px solid #d9e2ec;margin-bottom:1rem}}\n",
+                ".message{{padding:.5rem 0;border-bottom:1px solid #f0f4f8}}\n",
+                ".meta{{font-size:.85rem;color:#627d98}}\n",
+                ".sender{{font-weight:600;color:#102a43}}\n",
+                ".deleted{{font-style:italic;color:#9fb3c8}}\n",
+                ".attachments{{margin:.25rem 0 0;padding-left:1.25rem;font-size:.9rem}}\n",
+                "</style>\n</head>\n<body>\n<header>\n<h1>{title}</h1>\n",
+                "<p class=\"meta\">{range} &middot; generated {generated}</p>\n</header>\n<main>\n"
+            ),
+            title = escape_html(&header.title),
+            range = escape_html(&range),
+            generated = header.generated_at.format("%Y-%m-%d %H:%M UTC"),
+        ),
+        ExportFormat::PlainText => format!(
+            "{}\n{}\nGenerated {}\n{}\n\n",
+            header.title,
+            range,
+            header.generated_at.format("%Y-%m-%d %H:%M UTC"),
+            "=".repeat(60)
+        ),
+    })
+}
+
+fn render_message(
+    format: &ExportFormat,
+    message: &ExportedMessage,
+    index: i64,
+    attachment_base_url: &str,
+) -> Result<String, AppError> {
+    let timestamp = message.created_at.format("%Y-%m-%d %H:%M:%S UTC");
+
+    Ok(match format {
+        ExportFormat::Json => {
+            let json = serde_json::to_string(message)
+                .map_err(|e| AppError::Internal(format!("Failed to serialize message: {}", e)))?;
+            if index == 0 { json } else { format!(",{}", json) }
+        }
+        ExportFormat::Html => {
+            let body = if message.is_deleted {
+                "<p class=\"deleted\">Message deleted</p>".to_string()
+            } else {
+                format!("<p>{}</p>", escape_html(&message.content).replace('\n', "<br>"))
+            };
+
+            let attachments = if message.attachments.is_empty() {
+                String::new()
+            } else {
+                let items: String = message.attachments.iter()
+                    .map(|a| format!(
+                        "<li><a href=\"{}\">{}</a> ({}, {} bytes)</li>",
+                        escape_html(&attachment_link(attachment_base_url, &a.file_path)),
+                        escape_html(&a.filename),
+                        escape_html(&a.mime_type),
+                        a.file_size
+                    ))
+                    .collect();
+                format!("<ul class=\"attachments\">{}</ul>", items)
+            };
+
+            format!(
+                "<article class=\"message\" id=\"m-{}\">\n<div class=\"meta\"><span class=\"sender\">{}</span> &middot; <time datetime=\"{}\">{}</time>{}</div>\n{}{}\n</article>\n",
+                message.message_id,
+                escape_html(&message.sender_username),
+                message.created_at.to_rfc3339(),
+                timestamp,
+                if message.is_edited && !message.is_deleted { " &middot; edited" } else { "" },
+                body,
+                attachments
+            )
+        }
+        ExportFormat::PlainText => {
+            let mut line = if message.is_deleted {
+                format!("[{}] {}: [Message deleted]\n", timestamp, message.sender_username)
+            } else {
+                format!(
+                    "[{}] {}: {}{}\n",
+                    timestamp,
+                    message.sender_username,
+                    message.content.replace('\n', "\n    "),
+                    if message.is_edited { " (edited)" } else { "" }
+                )
+            };
+
+            for attachment in &message.attachments {
+                line.push_str(&format!(
+                    "    Attachment: {} <{}>\n",
+                    attachment.filename,
+                    attachment_link(attachment_base_url, &attachment.file_path)
+                ));
+            }
+
+            line
+        }
+    })
+}
+
+fn render_footer(format: &ExportFormat, message_count: i64) -> String {
+    match format {
+        ExportFormat::Json => format!("],\"message_count\":{}}}\n", message_count),
+        ExportFormat::Html => format!(
+            "</main>\n<footer class=\"meta\"><p>{} messages</p></footer>\n</body>\n</html>\n",
+            message_count
+        ),
+        ExportFormat::PlainText => format!("\n{}\n{} messages\n", "=".repeat(60), message_count),
+    }
+}
+
+fn attachment_link(base_url: &str, file_path: &str) -> String {
+    if file_path.starts_with("http://") || file_path.starts_with("https://") {
+        file_path.to_string()
+    } else {
+        format!("{}/{}", base_url.trim_end_matches('/'), file_path.trim_start_matches('/'))
+    }
+}
+
+fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+async fn write_chunk(writer: &mut BufWriter<tokio::fs::File>, chunk: &str) -> Result<(), AppError> {
+    writer.write_all(chunk.as_bytes())
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to write export file: {}", e)))
+}
+
+// Database row structs
+#[derive(sqlx::FromRow)]
+struct ExportJobRow {
+    export_id: Uuid,
+    format: ExportFormat,
+    status: ExportStatus,
+    message_count: Option<i64>,
+    download_token: Option<Uuid>,
+    expires_at: Option<DateTime<Utc>>,
+    error_message: Option<String>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ExportDownloadRow {
+    export_id: Uuid,
+    format: ExportFormat,
+    file_path: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct ExportMessageRow {
+    message_id: Uuid,
+    sender_id: Uuid,
+    sender_username: String,
+    content: String,
+    message_type: MessageType,
+    created_at: DateTime<Utc>,
+    is_edited: bool,
+    is_deleted: bool,
+    attachments: serde_json::Value,
+}
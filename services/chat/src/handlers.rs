@@ -1,20 +1,31 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
 use serde::Deserialize;
 use uuid::Uuid;
 
 use linkwithmentor_auth::Claims;
-use linkwithmentor_common::{ApiResponse, AppError};
+use linkwithmentor_common::{ApiResponse, AppError, MessageType};
 
 use crate::{
     models::{
-        SendMessageRequest, MessageHistoryRequest, MessageHistoryResponse,
+        SendMessageRequest, InternalSystemMessageRequest, MessageHistoryRequest, MessageHistoryResponse,
         UpdateMessageRequest, CreateGroupChatRequest, GroupChatResponse,
         TypingIndicatorRequest, OnlineUser, ChatMessageResponse,
+        MessageAuditResponse, CreateExportRequest, ExportJobResponse,
+        CreateScheduledMessageRequest, UpdateScheduledMessageRequest, ScheduledMessageResponse,
+        CommandInfo, CommandResponse, ExecuteCommandRequest, RegisterBotRequest, BotResponse,
+        CreateStructuredMessageRequest, StructuredMessageResponse, StructuredMessageState,
+        CastVoteRequest, RespondToProposalRequest, UpdateChecklistItemRequest,
+        PublishKeyBundleRequest, UploadPreKeysRequest, PreKeyCountResponse, PreKeyBundleResponse,
+        EnableEncryptionRequest, EncryptedConversationResponse, ReportEncryptedMessageRequest,
+        MessageReportResponse, PresenceResponse, PresenceSettings, UpdatePresenceSettingsRequest,
+        UpdateStatusRequest,
     },
+    commands::{self, CommandContext},
+    structured_messages,
     AppState,
 };
 
@@ -31,6 +42,15 @@ pub async fn send_message(
     claims: Claims,
     Json(request): Json<SendMessageRequest>,
 ) -> Result<Json<ApiResponse<ChatMessageResponse>>, AppError> {
+    if structured_messages::is_structured(&request.message_type) {
+        return Err(AppError::BadRequest(
+            "Polls, proposals and checklists must be created via /messages/structured".to_string()
+        ));
+    }
+    if matches!(request.message_type, MessageType::System) {
+        return Err(AppError::BadRequest("System messages can only be sent by the platform".to_string()));
+    }
+
     let message_response = state.message_service
         .send_message(
             claims.user_id,
@@ -45,6 +65,47 @@ pub async fn send_message(
     Ok(Json(ApiResponse::success(message_response)))
 }
 
+// Store and deliver a system message raised by another service
+pub async fn post_internal_system_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<InternalSystemMessageRequest>,
+) -> Result<Json<ApiResponse<ChatMessageResponse>>, AppError> {
+    let expected = &state.config.internal_service_token;
+    let provided = headers
+        .get("X-Internal-Token")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if expected.is_empty() || provided != expected {
+        return Err(AppError::Unauthorized("Invalid internal service token".to_string()));
+    }
+
+    let message_response = state.message_service
+        .send_message(
+            request.sender_id,
+            request.content.clone(),
+            request.recipient_id,
+            request.session_id,
+            request.group_id,
+            MessageType::System,
+        )
+        .await?;
+
+    state.pubsub
+        .publish_chat_message(
+            message_response.message_id,
+            request.sender_id,
+            request.recipient_id,
+            request.session_id,
+            request.group_id,
+            request.content,
+            MessageType::System,
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success(message_response)))
+}
+
 // Get message history
 pub async fn get_message_history(
     State(state): State<AppState>,
@@ -91,29 +152,459 @@ pub async fn delete_message(
     Ok(Json(ApiResponse::success(())))
 }
 
-// Get online users
+// Get the edit and deletion history of a message (moderator/admin only)
+pub async fn get_message_audit(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(message_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<MessageAuditResponse>>, AppError> {
+    if !claims.roles.contains(&"moderator".to_string()) && !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Insufficient permissions".to_string()));
+    }
+
+    let audit = state.message_service
+        .get_message_audit(message_id)
+        .await?;
+
+    tracing::info!("Moderator {} accessed audit trail for message {}", claims.user_id, message_id);
+
+    Ok(Json(ApiResponse::success(audit)))
+}
+
+// Request a conversation export
+pub async fn create_export(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<CreateExportRequest>,
+) -> Result<Json<ApiResponse<ExportJobResponse>>, AppError> {
+    let export = state.export_service
+        .request_export(claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(export)))
+}
+
+// Get the status of a conversation export
+pub async fn get_export(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(export_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExportJobResponse>>, AppError> {
+    let export = state.export_service
+        .get_export(export_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(export)))
+}
+
+// Download a finished export using the link from the notification
+pub async fn download_export(
+    State(state): State<AppState>,
+    Path(download_token): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let download = state.export_service
+        .open_download(download_token)
+        .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, download.content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", download.filename),
+            ),
+        ],
+        download.contents,
+    )
+        .into_response())
+}
+
+// Schedule a message for later (optionally recurring)
+pub async fn create_scheduled_message(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<CreateScheduledMessageRequest>,
+) -> Result<Json<ApiResponse<ScheduledMessageResponse>>, AppError> {
+    let scheduled = state.scheduled_message_service
+        .create_scheduled_message(claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(scheduled)))
+}
+
+// List the caller's scheduled messages
+pub async fn list_scheduled_messages(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<ApiResponse<Vec<ScheduledMessageResponse>>>, AppError> {
+    let scheduled = state.scheduled_message_service
+        .list_scheduled_messages(claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(scheduled)))
+}
+
+// Edit a scheduled message before it is sent
+pub async fn update_scheduled_message(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(scheduled_message_id): Path<Uuid>,
+    Json(request): Json<UpdateScheduledMessageRequest>,
+) -> Result<Json<ApiResponse<ScheduledMessageResponse>>, AppError> {
+    let scheduled = state.scheduled_message_service
+        .update_scheduled_message(scheduled_message_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(scheduled)))
+}
+
+// Cancel a scheduled message (stops all future occurrences)
+pub async fn cancel_scheduled_message(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(scheduled_message_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.scheduled_message_service
+        .cancel_scheduled_message(scheduled_message_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+// Post a poll, session proposal or checklist
+pub async fn create_structured_message(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<CreateStructuredMessageRequest>,
+) -> Result<Json<ApiResponse<StructuredMessageResponse>>, AppError> {
+    let message = state.structured_message_service
+        .create(claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(message)))
+}
+
+// Get the current state of an interactive message (tallies, status, items)
+pub async fn get_structured_message_state(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(message_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<StructuredMessageState>>, AppError> {
+    let message_state = state.structured_message_service
+        .get_state(message_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(message_state)))
+}
+
+// Vote in a poll (replaces any previous vote)
+pub async fn cast_poll_vote(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(message_id): Path<Uuid>,
+    Json(request): Json<CastVoteRequest>,
+) -> Result<Json<ApiResponse<StructuredMessageState>>, AppError> {
+    let message_state = state.structured_message_service
+        .cast_vote(message_id, claims.user_id, request.option_ids)
+        .await?;
+
+    Ok(Json(ApiResponse::success(message_state)))
+}
+
+// Close a poll (creator only)
+pub async fn close_poll(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(message_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<StructuredMessageState>>, AppError> {
+    let message_state = state.structured_message_service
+        .close_poll(message_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(message_state)))
+}
+
+// Accept, decline or withdraw a session proposal
+pub async fn respond_to_proposal(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(message_id): Path<Uuid>,
+    Json(request): Json<RespondToProposalRequest>,
+) -> Result<Json<ApiResponse<StructuredMessageState>>, AppError> {
+    let message_state = state.structured_message_service
+        .respond_to_proposal(message_id, claims.user_id, request.action)
+        .await?;
+
+    Ok(Json(ApiResponse::success(message_state)))
+}
+
+// Tick or untick a checklist item
+pub async fn update_checklist_item(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((message_id, item_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateChecklistItemRequest>,
+) -> Result<Json<ApiResponse<StructuredMessageState>>, AppError> {
+    let message_state = state.structured_message_service
+        .update_checklist_item(message_id, item_id, claims.user_id, request.is_completed)
+        .await?;
+
+    Ok(Json(ApiResponse::success(message_state)))
+}
+
+// Publish or rotate the caller's E2E identity key and signed prekey
+pub async fn publish_e2e_keys(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<PublishKeyBundleRequest>,
+) -> Result<Json<ApiResponse<PreKeyCountResponse>>, AppError> {
+    let count = state.e2e_service
+        .publish_keys(claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(count)))
+}
+
+// Top up the caller's one-time prekeys
+pub async fn upload_e2e_prekeys(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<UploadPreKeysRequest>,
+) -> Result<Json<ApiResponse<PreKeyCountResponse>>, AppError> {
+    let count = state.e2e_service
+        .upload_prekeys(claims.user_id, request.one_time_prekeys)
+        .await?;
+
+    Ok(Json(ApiResponse::success(count)))
+}
+
+// How many one-time prekeys the caller has left
+pub async fn get_e2e_prekey_count(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<ApiResponse<PreKeyCountResponse>>, AppError> {
+    let count = state.e2e_service.prekey_count(claims.user_id).await?;
+
+    Ok(Json(ApiResponse::success(count)))
+}
+
+// Fetch another user's key bundle (consumes one of their one-time prekeys)
+pub async fn get_e2e_key_bundle(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<PreKeyBundleResponse>>, AppError> {
+    let bundle = state.e2e_service.claim_bundle(claims.user_id, user_id).await?;
+
+    Ok(Json(ApiResponse::success(bundle)))
+}
+
+// Opt a direct conversation into end-to-end encryption
+pub async fn enable_e2e_conversation(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<EnableEncryptionRequest>,
+) -> Result<Json<ApiResponse<EncryptedConversationResponse>>, AppError> {
+    let conversation = state.e2e_service
+        .enable_conversation(claims.user_id, request.peer_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(conversation)))
+}
+
+// Check whether the conversation with a peer is end-to-end encrypted
+pub async fn get_e2e_conversation(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(peer_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EncryptedConversationResponse>>, AppError> {
+    let conversation = state.e2e_service
+        .conversation_status(claims.user_id, peer_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(conversation)))
+}
+
+// Report an encrypted message by sharing its decrypted content
+pub async fn report_encrypted_message(
+    State(state): State<AppState>,
+    claims: Claims,
+    headers: HeaderMap,
+    Path(message_id): Path<Uuid>,
+    Json(request): Json<ReportEncryptedMessageRequest>,
+) -> Result<Json<ApiResponse<MessageReportResponse>>, AppError> {
+    // Forwarded so the safety service attributes the report to the reporter
+    let auth_header = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    let report = state.e2e_service
+        .report_message(claims.user_id, auth_header, message_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+// List slash commands available in chat (built-in and bots)
+pub async fn list_commands(
+    State(state): State<AppState>,
+    _claims: Claims,
+) -> Result<Json<ApiResponse<Vec<CommandInfo>>>, AppError> {
+    let commands = state.command_registry.list_commands().await?;
+
+    Ok(Json(ApiResponse::success(commands)))
+}
+
+// Run a slash command from a REST client
+pub async fn execute_command(
+    State(state): State<AppState>,
+    claims: Claims,
+    headers: HeaderMap,
+    Json(request): Json<ExecuteCommandRequest>,
+) -> Result<Json<ApiResponse<CommandResponse>>, AppError> {
+    let invocation = commands::parse_command(&request.content)
+        .ok_or_else(|| AppError::BadRequest("Content is not a slash command".to_string()))?;
+
+    let auth_token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.to_string());
+
+    let ctx = CommandContext {
+        user_id: claims.user_id,
+        username: claims.username.clone(),
+        recipient_id: request.recipient_id,
+        session_id: request.session_id,
+        group_id: request.group_id,
+        auth_token,
+    };
+
+    let response = state.command_registry
+        .execute_and_deliver(&state.connection_manager, &ctx, &invocation)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Register a webhook bot command (admin only)
+pub async fn register_bot(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<RegisterBotRequest>,
+) -> Result<Json<ApiResponse<BotResponse>>, AppError> {
+    if !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let bot = state.command_registry
+        .register_bot(claims.user_id, request)
+        .await?;
+
+    tracing::info!("Admin {} registered bot command /{}", claims.user_id, bot.command);
+
+    Ok(Json(ApiResponse::success(bot)))
+}
+
+// List registered webhook bots (admin only)
+pub async fn list_bots(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<ApiResponse<Vec<BotResponse>>>, AppError> {
+    if !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let bots = state.command_registry.list_bots().await?;
+
+    Ok(Json(ApiResponse::success(bots)))
+}
+
+// Deactivate a webhook bot (admin only)
+pub async fn deactivate_bot(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    if !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    state.command_registry.deactivate_bot(bot_id).await?;
+
+    tracing::info!("Admin {} deactivated bot {}", claims.user_id, bot_id);
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+// Get online users across all chat instances, filtered by their privacy settings
 pub async fn get_online_users(
     State(state): State<AppState>,
     claims: Claims,
 ) -> Result<Json<ApiResponse<Vec<OnlineUser>>>, AppError> {
-    let online_user_ids = state.connection_manager.get_online_users().await;
-    
-    // Get user details from database
-    let mut online_users = Vec::new();
-    for user_id in online_user_ids {
-        if let Ok(user_info) = get_user_details(&state, user_id).await {
-            online_users.push(OnlineUser {
-                user_id,
-                username: user_info.username,
-                status: crate::models::UserStatus::Online,
-                last_seen: chrono::Utc::now(),
-            });
-        }
-    }
+    let online_users = state.presence_service.list_online(claims.user_id).await?;
 
     Ok(Json(ApiResponse::success(online_users)))
 }
 
+// Get a single user's presence
+pub async fn get_user_presence(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<PresenceResponse>>, AppError> {
+    let presence = state.presence_service.get_presence(claims.user_id, user_id).await?;
+
+    Ok(Json(ApiResponse::success(presence)))
+}
+
+// Set a custom status (busy, in session, away)
+pub async fn set_presence_status(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<UpdateStatusRequest>,
+) -> Result<Json<ApiResponse<PresenceResponse>>, AppError> {
+    let presence = state.presence_service
+        .set_status(claims.user_id, &claims.username, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(presence)))
+}
+
+// Clear the custom status
+pub async fn clear_presence_status(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.presence_service.clear_status(claims.user_id, &claims.username).await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+// Get presence privacy settings
+pub async fn get_presence_settings(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<ApiResponse<PresenceSettings>>, AppError> {
+    let settings = state.presence_service.get_settings(claims.user_id).await?;
+
+    Ok(Json(ApiResponse::success(settings)))
+}
+
+// Update presence privacy settings
+pub async fn update_presence_settings(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<UpdatePresenceSettingsRequest>,
+) -> Result<Json<ApiResponse<PresenceSettings>>, AppError> {
+    let settings = state.presence_service
+        .update_settings(claims.user_id, &claims.username, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(settings)))
+}
+
 // Get users in a specific room/session
 pub async fn get_room_participants(
     State(state): State<AppState>,
@@ -131,7 +622,8 @@ pub async fn get_room_participants(
                 user_id,
                 username: user_info.username,
                 status: crate::models::UserStatus::Online,
-                last_seen: chrono::Utc::now(),
+                status_text: None,
+                last_seen: None,
             });
         }
     }
@@ -6,6 +6,12 @@ mod message_service;
 mod connection_manager;
 mod routes;
 mod pubsub;
+mod export;
+mod scheduled_messages;
+mod commands;
+mod structured_messages;
+mod e2e;
+mod presence;
 
 use axum::{
     http::{StatusCode, Method},
@@ -27,6 +33,12 @@ use crate::config::ChatConfig;
 use crate::connection_manager::ConnectionManager;
 use crate::message_service::MessageService;
 use crate::pubsub::ChatPubSub;
+use crate::export::ExportService;
+use crate::scheduled_messages::ScheduledMessageService;
+use crate::commands::CommandRegistry;
+use crate::structured_messages::StructuredMessageService;
+use crate::e2e::E2eService;
+use crate::presence::PresenceService;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -37,6 +49,12 @@ pub struct AppState {
     pub connection_manager: ConnectionManager,
     pub message_service: MessageService,
     pub pubsub: ChatPubSub,
+    pub export_service: ExportService,
+    pub scheduled_message_service: ScheduledMessageService,
+    pub command_registry: CommandRegistry,
+    pub structured_message_service: StructuredMessageService,
+    pub e2e_service: E2eService,
+    pub presence_service: PresenceService,
 }
 
 #[tokio::main]
@@ -85,6 +103,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize PubSub
     pubsub.initialize().await?;
 
+    // Create export service and start expiring old downloads
+    let export_service = ExportService::new(
+        db_pool.clone(),
+        config.export.clone(),
+        config.internal_service_token.clone(),
+    );
+    export_service.start_cleanup_task().await;
+
+    // Create scheduled message service and start the delivery worker
+    let scheduled_message_service = ScheduledMessageService::new(
+        db_pool.clone(),
+        message_service.clone(),
+        pubsub.clone(),
+    );
+    scheduled_message_service
+        .start_delivery_worker(config.websocket.scheduled_message_poll_seconds)
+        .await;
+
+    // Create structured message service (polls, session proposals, checklists)
+    let structured_message_service = StructuredMessageService::new(
+        db_pool.clone(),
+        message_service.clone(),
+        connection_manager.clone(),
+        pubsub.clone(),
+    );
+
+    // Create slash command registry (built-in commands and webhook bots)
+    let command_registry = CommandRegistry::new(
+        db_pool.clone(),
+        &config.commands,
+        message_service.clone(),
+        structured_message_service.clone(),
+    );
+
+    // Create E2E key directory (ciphertext relay for opted-in direct messages)
+    let e2e_service = E2eService::new(
+        db_pool.clone(),
+        redis_service.clone(),
+        config.moderation.safety_service_url.clone(),
+    );
+
+    // Create cluster-wide presence service and start expiring dead devices
+    let presence_service = PresenceService::new(
+        db_pool.clone(),
+        redis_service.clone(),
+        connection_manager.clone(),
+        pubsub.clone(),
+        config.websocket.heartbeat_interval_seconds,
+    );
+    presence_service.start_sweeper().await;
+
     // Build application state
     let app_state = AppState {
         config: config.clone(),
@@ -94,6 +163,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         connection_manager,
         message_service,
         pubsub,
+        export_service,
+        scheduled_message_service,
+        command_registry,
+        structured_message_service,
+        e2e_service,
+        presence_service,
     };
 
     // Build CORS layer
@@ -1,11 +1,15 @@
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 
 use linkwithmentor_common::{AppError, RedisService, MessageType, ModerationStatus};
 use crate::{
-    models::{ChatMessageResponse, MessageHistoryResponse},
+    models::{
+        ChatMessageResponse, MessageHistoryResponse, MessageAuditResponse,
+        MessageRevision, MessageRevisionType, WSMessage,
+    },
     connection_manager::ConnectionManager,
+    e2e,
 };
 
 #[derive(Clone)]
@@ -36,20 +40,112 @@ impl MessageService {
         session_id: Option<Uuid>,
         group_id: Option<Uuid>,
         message_type: MessageType,
+    ) -> Result<ChatMessageResponse, AppError> {
+        let mut conn = self.db_pool
+            .acquire()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to acquire connection: {}", e)))?;
+
+        let message = self
+            .send_message_in(&mut conn, sender_id, content, recipient_id, session_id, group_id, message_type)
+            .await?;
+
+        self.after_send(&message).await?;
+
+        Ok(message)
+    }
+
+    /// Validates, moderates and stores a message on the given connection, so
+    /// callers can make the insert part of their own transaction. Call
+    /// `after_send` once that transaction has committed.
+    pub async fn send_message_in(
+        &self,
+        conn: &mut PgConnection,
+        sender_id: Uuid,
+        content: String,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        message_type: MessageType,
+    ) -> Result<ChatMessageResponse, AppError> {
+        self.store_message(conn, sender_id, content, recipient_id, session_id, group_id, message_type, None, None)
+            .await
+    }
+
+    /// Stores an in-channel reply to a slash command in the invoker's
+    /// conversation, attributed to the command and, for webhook commands,
+    /// the bot that wrote it.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_command_reply(
+        &self,
+        invoker_id: Uuid,
+        content: String,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        command: &str,
+        bot_id: Option<Uuid>,
+    ) -> Result<ChatMessageResponse, AppError> {
+        let mut conn = self.db_pool
+            .acquire()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to acquire connection: {}", e)))?;
+
+        let message = self
+            .store_message(
+                &mut conn,
+                invoker_id,
+                content,
+                recipient_id,
+                session_id,
+                group_id,
+                MessageType::System,
+                Some(command),
+                bot_id,
+            )
+            .await?;
+
+        self.after_send(&message).await?;
+
+        Ok(message)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn store_message(
+        &self,
+        conn: &mut PgConnection,
+        sender_id: Uuid,
+        content: String,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        message_type: MessageType,
+        command: Option<&str>,
+        bot_id: Option<Uuid>,
     ) -> Result<ChatMessageResponse, AppError> {
         // Validate message content
         if content.trim().is_empty() {
             return Err(AppError::BadRequest("Message content cannot be empty".to_string()));
         }
 
-        // Check rate limiting
-        self.check_rate_limit(sender_id).await?;
+        self.ensure_participant(sender_id, recipient_id, session_id, group_id).await?;
+
+        // Check rate limiting; system messages are sent by the platform, not the sender
+        if !matches!(message_type, MessageType::System) {
+            self.check_rate_limit(sender_id).await?;
+        }
+
+        let is_encrypted = self.check_encryption(sender_id, recipient_id, &message_type, &content).await?;
 
         // Get sender information
         let sender_info = self.get_user_info(sender_id).await?;
 
-        // Perform content moderation
-        let moderation_status = self.moderate_content(&content).await?;
+        // Perform content moderation (ciphertext can't be inspected; recipients report instead)
+        let moderation_status = if is_encrypted {
+            ModerationStatus::Approved
+        } else {
+            self.moderate_content(&content).await?
+        };
 
         // Generate message ID
         let message_id = Uuid::new_v4();
@@ -59,8 +155,9 @@ impl MessageService {
         let query = r#"
             INSERT INTO messages (
                 message_id, sender_id, recipient_id, session_id, group_id,
-                content, message_type, moderation_status, created_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                content, message_type, moderation_status, is_encrypted, created_at,
+                command, bot_id
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
         "#;
 
         sqlx::query(query)
@@ -72,20 +169,14 @@ impl MessageService {
             .bind(&content)
             .bind(&message_type)
             .bind(&moderation_status)
+            .bind(is_encrypted)
             .bind(timestamp)
-            .execute(&self.db_pool)
+            .bind(command)
+            .bind(bot_id)
+            .execute(&mut *conn)
             .await
             .map_err(|e| AppError::Database(format!("Failed to store message: {}", e)))?;
 
-        // Cache recent message for quick retrieval
-        self.cache_recent_message(message_id, &content, sender_id, timestamp).await?;
-
-        // Update conversation last activity
-        self.update_conversation_activity(recipient_id, session_id, group_id).await?;
-
-        // Increment rate limit counter
-        self.increment_rate_limit_counter(sender_id).await?;
-
         Ok(ChatMessageResponse {
             message_id,
             sender_id,
@@ -99,9 +190,73 @@ impl MessageService {
             timestamp,
             edited_at: None,
             is_edited: false,
+            edit_count: 0,
+            is_deleted: false,
+            deleted_at: None,
+            command: command.map(str::to_string),
+            bot_id,
         })
     }
 
+    /// Cache and counter updates for a stored message. These live in Redis,
+    /// so they run after the insert has committed.
+    pub async fn after_send(&self, message: &ChatMessageResponse) -> Result<(), AppError> {
+        // Cache recent message for quick retrieval (previews are meaningless for ciphertext)
+        if !matches!(message.message_type, MessageType::Encrypted) {
+            self.cache_recent_message(message.message_id, &message.content, message.sender_id, message.timestamp).await?;
+        }
+
+        // Update conversation last activity
+        self.update_conversation_activity(message.recipient_id, message.session_id, message.group_id).await?;
+
+        // Increment rate limit counter
+        if !matches!(message.message_type, MessageType::System) {
+            self.increment_rate_limit_counter(message.sender_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Rejects senders who aren't in the session or group they are posting
+    /// to. Anyone may start a direct message.
+    pub async fn ensure_participant(
+        &self,
+        user_id: Uuid,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    ) -> Result<(), AppError> {
+        let is_participant = if recipient_id.is_some() {
+            true
+        } else if let Some(session_id) = session_id {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM mentorship_sessions WHERE session_id = $1 AND (mentor_id = $2 OR mentee_id = $2))"
+            )
+            .bind(session_id)
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check session access: {}", e)))?
+        } else if let Some(group_id) = group_id {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM group_chat_participants WHERE group_id = $1 AND user_id = $2 AND left_at IS NULL)"
+            )
+            .bind(group_id)
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check group access: {}", e)))?
+        } else {
+            true
+        };
+
+        if !is_participant {
+            return Err(AppError::Forbidden("You are not a participant in this conversation".to_string()));
+        }
+
+        Ok(())
+    }
+
     pub async fn get_message_history(
         &self,
         user_id: Uuid,
@@ -117,7 +272,8 @@ impl MessageService {
                 m.message_id, m.sender_id, u.username as sender_username,
                 m.content, m.recipient_id, m.session_id, m.group_id,
                 m.message_type, m.moderation_status, m.created_at,
-                m.updated_at, m.is_edited
+                m.updated_at, m.is_edited, m.edit_count, m.is_deleted, m.deleted_at,
+                m.command, m.bot_id
             FROM messages m
             JOIN users u ON m.sender_id = u.user_id
             WHERE 1=1
@@ -199,8 +355,13 @@ impl MessageService {
                 message_type: row.message_type,
                 moderation_status: row.moderation_status,
                 timestamp: row.created_at,
-                edited_at: row.updated_at,
+                edited_at: if row.is_edited { row.updated_at } else { None },
                 is_edited: row.is_edited,
+                edit_count: row.edit_count,
+                is_deleted: row.is_deleted,
+                deleted_at: row.deleted_at,
+                command: row.command,
+                bot_id: row.bot_id,
             })
             .collect();
 
@@ -225,17 +386,42 @@ impl MessageService {
             return Err(AppError::BadRequest("Message content cannot be empty".to_string()));
         }
 
-        // Perform content moderation
-        let moderation_status = self.moderate_content(&new_content).await?;
+        let mut tx = self.db_pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // Lock the current version so concurrent edits are recorded in order
+        let current = self.lock_message_for_revision(&mut tx, message_id, user_id).await?;
+
+        if current.is_deleted {
+            return Err(AppError::BadRequest("Deleted messages cannot be edited".to_string()));
+        }
+        if current.command.is_some() {
+            return Err(AppError::BadRequest("Command replies cannot be edited".to_string()));
+        }
+
+        // Edits to encrypted messages must stay ciphertext and skip moderation
+        let moderation_status = if current.is_encrypted {
+            e2e::validate_ciphertext(&new_content)?;
+            ModerationStatus::Approved
+        } else {
+            self.moderate_content(&new_content).await?
+        };
+
+        // Keep the previous version before overwriting it
+        self.record_revision(&mut tx, message_id, user_id, MessageRevisionType::Edit, &current)
+            .await?;
 
-        // Update message in database
         let query = r#"
             UPDATE messages 
-            SET content = $1, moderation_status = $2, updated_at = $3, is_edited = true
+            SET content = $1, moderation_status = $2, updated_at = $3,
+                is_edited = true, edit_count = edit_count + 1
             WHERE message_id = $4 AND sender_id = $5
             RETURNING 
                 message_id, sender_id, recipient_id, session_id, group_id,
-                content, message_type, moderation_status, created_at, updated_at, is_edited
+                content, message_type, moderation_status, created_at, updated_at,
+                is_edited, edit_count, command, bot_id
         "#;
 
         let updated_at = Utc::now();
@@ -245,17 +431,26 @@ impl MessageService {
             .bind(updated_at)
             .bind(message_id)
             .bind(user_id)
-            .fetch_optional(&self.db_pool)
+            .fetch_one(&mut *tx)
             .await
             .map_err(|e| AppError::Database(format!("Failed to update message: {}", e)))?;
 
-        let row = row.ok_or_else(|| {
-            AppError::NotFound("Message not found or you don't have permission to edit it".to_string())
-        })?;
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to commit message update: {}", e)))?;
 
         // Get sender information
         let sender_info = self.get_user_info(user_id).await?;
 
+        let edited_message = WSMessage::MessageEdited {
+            message_id: row.message_id,
+            content: row.content.clone(),
+            edited_at: row.updated_at,
+            edit_count: row.edit_count,
+        };
+        self.notify_conversation(row.sender_id, row.recipient_id, row.session_id, row.group_id, edited_message)
+            .await;
+
         Ok(ChatMessageResponse {
             message_id: row.message_id,
             sender_id: row.sender_id,
@@ -269,6 +464,11 @@ impl MessageService {
             timestamp: row.created_at,
             edited_at: Some(row.updated_at),
             is_edited: row.is_edited,
+            edit_count: row.edit_count,
+            is_deleted: false,
+            deleted_at: None,
+            command: row.command,
+            bot_id: row.bot_id,
         })
     }
 
@@ -277,29 +477,116 @@ impl MessageService {
         message_id: Uuid,
         user_id: Uuid,
     ) -> Result<(), AppError> {
+        let mut tx = self.db_pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let current = self.lock_message_for_revision(&mut tx, message_id, user_id).await?;
+
+        if current.is_deleted {
+            return Err(AppError::NotFound("Message has already been deleted".to_string()));
+        }
+
+        // The original content survives only in the revision log
+        self.record_revision(&mut tx, message_id, user_id, MessageRevisionType::Delete, &current)
+            .await?;
+
         let query = r#"
             UPDATE messages 
-            SET content = '[Message deleted]', is_deleted = true, updated_at = $1
-            WHERE message_id = $2 AND sender_id = $3
+            SET content = '[Message deleted]', is_deleted = true,
+                deleted_at = $1, deleted_by = $2, updated_at = $1
+            WHERE message_id = $3 AND sender_id = $2
+            RETURNING sender_id, recipient_id, session_id, group_id
         "#;
 
-        let result = sqlx::query(query)
-            .bind(Utc::now())
-            .bind(message_id)
+        let deleted_at = Utc::now();
+        let row = sqlx::query_as::<_, MessageRoutingRow>(query)
+            .bind(deleted_at)
             .bind(user_id)
-            .execute(&self.db_pool)
+            .bind(message_id)
+            .fetch_one(&mut *tx)
             .await
             .map_err(|e| AppError::Database(format!("Failed to delete message: {}", e)))?;
 
-        if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(
-                "Message not found or you don't have permission to delete it".to_string()
-            ));
-        }
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to commit message deletion: {}", e)))?;
+
+        let deleted_message = WSMessage::MessageDeleted {
+            message_id,
+            deleted_at,
+        };
+        self.notify_conversation(row.sender_id, row.recipient_id, row.session_id, row.group_id, deleted_message)
+            .await;
 
         Ok(())
     }
 
+    /// Returns the full edit and deletion history of a message, including the
+    /// original content of tombstoned messages. Callers must enforce
+    /// moderator access.
+    pub async fn get_message_audit(&self, message_id: Uuid) -> Result<MessageAuditResponse, AppError> {
+        let query = r#"
+            SELECT message_id, sender_id, content, created_at, is_edited,
+                   is_deleted, deleted_at, deleted_by
+            FROM messages
+            WHERE message_id = $1
+        "#;
+
+        let message = sqlx::query_as::<_, MessageAuditRow>(query)
+            .bind(message_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch message: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+        let revisions_query = r#"
+            SELECT revision_id, message_id, revision_number, revision_type, content,
+                   moderation_status, revised_by, revised_at
+            FROM message_revisions
+            WHERE message_id = $1
+            ORDER BY revision_number ASC
+        "#;
+
+        let revisions = sqlx::query_as::<_, MessageRevisionRow>(revisions_query)
+            .bind(message_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch message revisions: {}", e)))?
+            .into_iter()
+            .map(|row| MessageRevision {
+                revision_id: row.revision_id,
+                message_id: row.message_id,
+                revision_number: row.revision_number,
+                revision_type: row.revision_type,
+                content: row.content,
+                moderation_status: row.moderation_status,
+                revised_by: row.revised_by,
+                revised_at: row.revised_at,
+            })
+            .collect::<Vec<_>>();
+
+        // The first revision holds the content as originally sent
+        let original_content = revisions
+            .first()
+            .map(|revision| revision.content.clone())
+            .unwrap_or_else(|| message.content.clone());
+
+        Ok(MessageAuditResponse {
+            message_id: message.message_id,
+            sender_id: message.sender_id,
+            current_content: message.content,
+            original_content,
+            is_edited: message.is_edited,
+            is_deleted: message.is_deleted,
+            deleted_at: message.deleted_at,
+            deleted_by: message.deleted_by,
+            created_at: message.created_at,
+            revisions,
+        })
+    }
+
     // Private helper methods
 
     async fn check_rate_limit(&self, user_id: Uuid) -> Result<(), AppError> {
@@ -328,7 +615,7 @@ impl MessageService {
         Ok(())
     }
 
-    async fn moderate_content(&self, content: &str) -> Result<ModerationStatus, AppError> {
+    pub async fn moderate_content(&self, content: &str) -> Result<ModerationStatus, AppError> {
         // Simple content moderation - in production, use a proper moderation service
         let banned_words = ["spam", "abuse", "inappropriate"];
         
@@ -402,6 +689,111 @@ impl MessageService {
 
         Ok(())
     }
+
+    /// Enforces the E2E contract for direct messages: opted-in conversations
+    /// only accept ciphertext, and ciphertext is only accepted there.
+    async fn check_encryption(
+        &self,
+        sender_id: Uuid,
+        recipient_id: Option<Uuid>,
+        message_type: &MessageType,
+        content: &str,
+    ) -> Result<bool, AppError> {
+        let conversation_encrypted = match recipient_id {
+            Some(recipient_id) => e2e::is_encrypted_conversation(&self.db_pool, sender_id, recipient_id).await?,
+            None => false,
+        };
+
+        match (matches!(message_type, MessageType::Encrypted), conversation_encrypted) {
+            (true, true) => {
+                e2e::validate_ciphertext(content)?;
+                Ok(true)
+            }
+            (true, false) => Err(AppError::BadRequest(
+                "Encrypted messages are only allowed in end-to-end encrypted conversations".to_string()
+            )),
+            // System messages come from the platform itself (call notices,
+            // command replies); users can't send them
+            (false, true) if matches!(message_type, MessageType::System) => Ok(false),
+            (false, true) => Err(AppError::BadRequest(
+                "This conversation is end-to-end encrypted; plaintext messages are not accepted".to_string()
+            )),
+            (false, false) => Ok(false),
+        }
+    }
+
+    async fn lock_message_for_revision(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        message_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MessageSnapshotRow, AppError> {
+        let query = r#"
+            SELECT content, moderation_status, is_deleted, is_encrypted, command
+            FROM messages
+            WHERE message_id = $1 AND sender_id = $2
+            FOR UPDATE
+        "#;
+
+        sqlx::query_as::<_, MessageSnapshotRow>(query)
+            .bind(message_id)
+            .bind(user_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to load message: {}", e)))?
+            .ok_or_else(|| {
+                AppError::NotFound("Message not found or you don't have permission to modify it".to_string())
+            })
+    }
+
+    async fn record_revision(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        message_id: Uuid,
+        revised_by: Uuid,
+        revision_type: MessageRevisionType,
+        snapshot: &MessageSnapshotRow,
+    ) -> Result<(), AppError> {
+        let query = r#"
+            INSERT INTO message_revisions (
+                message_id, revision_number, revision_type, content,
+                moderation_status, revised_by, revised_at
+            )
+            SELECT $1, COALESCE(MAX(revision_number), 0) + 1, $2, $3, $4, $5, $6
+            FROM message_revisions
+            WHERE message_id = $1
+        "#;
+
+        sqlx::query(query)
+            .bind(message_id)
+            .bind(&revision_type)
+            .bind(&snapshot.content)
+            .bind(&snapshot.moderation_status)
+            .bind(revised_by)
+            .bind(Utc::now())
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record message revision: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn notify_conversation(
+        &self,
+        sender_id: Uuid,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        message: WSMessage,
+    ) {
+        let result = self.connection_manager
+            .send_to_conversation(sender_id, recipient_id, session_id, group_id, message)
+            .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to notify conversation of message change: {}", e);
+        }
+    }
 }
 
 // Database row structs
@@ -419,6 +811,11 @@ struct MessageRow {
     created_at: DateTime<Utc>,
     updated_at: Option<DateTime<Utc>>,
     is_edited: bool,
+    edit_count: i32,
+    is_deleted: bool,
+    deleted_at: Option<DateTime<Utc>>,
+    command: Option<String>,
+    bot_id: Option<Uuid>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -434,6 +831,50 @@ struct MessageUpdateRow {
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     is_edited: bool,
+    edit_count: i32,
+    command: Option<String>,
+    bot_id: Option<Uuid>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MessageSnapshotRow {
+    content: String,
+    moderation_status: ModerationStatus,
+    is_deleted: bool,
+    is_encrypted: bool,
+    command: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MessageRoutingRow {
+    sender_id: Uuid,
+    recipient_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    group_id: Option<Uuid>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MessageAuditRow {
+    message_id: Uuid,
+    sender_id: Uuid,
+    content: String,
+    created_at: DateTime<Utc>,
+    is_edited: bool,
+    is_deleted: bool,
+    deleted_at: Option<DateTime<Utc>>,
+    deleted_by: Option<Uuid>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MessageRevisionRow {
+    revision_id: Uuid,
+    message_id: Uuid,
+    revision_number: i32,
+    revision_type: MessageRevisionType,
+    content: String,
+    moderation_status: ModerationStatus,
+    revised_by: Uuid,
+    revised_at: DateTime<Utc>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -0,0 +1,769 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use linkwithmentor_common::{MessageType, ModerationStatus};
+
+// WebSocket Messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WSMessage {
+    // Client -> Server
+    SendMessage {
+        content: String,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        message_type: MessageType,
+    },
+    TypingStart {
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    },
+    TypingStop {
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    },
+    RequestHistory {
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        limit: Option<u32>,
+        before_message_id: Option<Uuid>,
+    },
+
+    // Server -> Client
+    MessageReceived {
+        message_id: Uuid,
+        sender_id: Uuid,
+        content: String,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        message_type: MessageType,
+        timestamp: DateTime<Utc>,
+        moderation_status: ModerationStatus,
+    },
+    MessageEdited {
+        message_id: Uuid,
+        content: String,
+        edited_at: DateTime<Utc>,
+        edit_count: i32,
+    },
+    MessageDeleted {
+        message_id: Uuid,
+        deleted_at: DateTime<Utc>,
+    },
+    StructuredMessageUpdated {
+        message_id: Uuid,
+        state: StructuredMessageState,
+    },
+    CommandResult {
+        command: String,
+        invoked_by: Uuid,
+        visibility: CommandVisibility,
+        text: Option<String>,
+        card: Option<MessageCard>,
+        recipient_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        timestamp: DateTime<Utc>,
+    },
+    MessageHistory {
+        messages: Vec<ChatMessageResponse>,
+        has_more: bool,
+    },
+    TypingIndicator {
+        user_id: Uuid,
+        username: String,
+        is_typing: bool,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    },
+    UserJoined {
+        user_id: Uuid,
+        username: String,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    },
+    UserLeft {
+        user_id: Uuid,
+        username: String,
+        session_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    },
+    PresenceChanged {
+        user_id: Uuid,
+        username: String,
+        status: UserStatus,
+        status_text: Option<String>,
+        last_seen: Option<DateTime<Utc>>,
+    },
+    Ack {
+        message_id: Uuid,
+    },
+    Error {
+        code: String,
+        message: String,
+    },
+
+    // Heartbeat
+    Ping,
+    Pong,
+}
+
+// REST API Models
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendMessageRequest {
+    pub content: String,
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub message_type: MessageType,
+}
+
+/// System message another service posts on a user's behalf, e.g. the video
+/// service noting a missed call in the chat with the caller
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InternalSystemMessageRequest {
+    pub sender_id: Uuid,
+    pub content: String,
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageHistoryRequest {
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub limit: Option<u32>,
+    pub before_message_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageHistoryResponse {
+    pub messages: Vec<ChatMessageResponse>,
+    pub has_more: bool,
+    pub total_count: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateMessageRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessageResponse {
+    pub message_id: Uuid,
+    pub sender_id: Uuid,
+    pub sender_username: String,
+    pub content: String,
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub message_type: MessageType,
+    pub moderation_status: ModerationStatus,
+    pub timestamp: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub is_edited: bool,
+    pub edit_count: i32,
+    pub is_deleted: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
+    // Replies to slash commands are written by the command (and bot, for
+    // webhook commands), not by the sender who invoked it
+    pub command: Option<String>,
+    pub bot_id: Option<Uuid>,
+}
+
+// Message audit trail (moderator access only)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MessageRevisionType {
+    Edit,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageRevision {
+    pub revision_id: Uuid,
+    pub message_id: Uuid,
+    pub revision_number: i32,
+    pub revision_type: MessageRevisionType,
+    pub content: String,
+    pub moderation_status: ModerationStatus,
+    pub revised_by: Uuid,
+    pub revised_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageAuditResponse {
+    pub message_id: Uuid,
+    pub sender_id: Uuid,
+    pub current_content: String,
+    pub original_content: String,
+    pub is_edited: bool,
+    pub is_deleted: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub revisions: Vec<MessageRevision>,
+}
+
+// Conversation export
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Html,
+    PlainText,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ExportStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Expired,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateExportRequest {
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub format: ExportFormat,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportJobResponse {
+    pub export_id: Uuid,
+    pub format: ExportFormat,
+    pub status: ExportStatus,
+    pub message_count: Option<i64>,
+    pub download_url: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+// Scheduled messages
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ScheduledMessageStatus {
+    Scheduled,
+    Sent,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageRecurrence {
+    pub frequency: MessageRecurrenceFrequency,
+    pub interval: u32, // Every N days/weeks/months
+    pub days_of_week: Option<Vec<u8>>, // 0=Sunday, 1=Monday, etc. (weekly only)
+    pub end_date: Option<DateTime<Utc>>,
+    pub max_occurrences: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MessageRecurrenceFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateScheduledMessageRequest {
+    pub content: String,
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub message_type: MessageType,
+    pub send_at: DateTime<Utc>,
+    pub timezone: Option<String>, // IANA name; recurrences keep wall-clock time here
+    pub recurrence: Option<MessageRecurrence>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateScheduledMessageRequest {
+    pub content: Option<String>,
+    pub send_at: Option<DateTime<Utc>>,
+    pub timezone: Option<String>,
+    pub recurrence: Option<MessageRecurrence>,
+    #[serde(default)]
+    pub clear_recurrence: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledMessageResponse {
+    pub scheduled_message_id: Uuid,
+    pub sender_id: Uuid,
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub content: String,
+    pub message_type: MessageType,
+    pub first_send_at: DateTime<Utc>,
+    pub next_send_at: Option<DateTime<Utc>>,
+    pub timezone: String,
+    pub recurrence: Option<MessageRecurrence>,
+    pub status: ScheduledMessageStatus,
+    pub occurrence_count: i32,
+    pub last_sent_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Structured interactive messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum StructuredMessageContent {
+    Poll {
+        question: String,
+        options: Vec<String>,
+        #[serde(default)]
+        allow_multiple: bool,
+        closes_at: Option<DateTime<Utc>>,
+    },
+    SessionProposal {
+        topic: String,
+        start_time: DateTime<Utc>,
+        duration_minutes: u32,
+        notes: Option<String>,
+    },
+    Checklist {
+        title: String,
+        items: Vec<NewChecklistItem>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewChecklistItem {
+    pub text: String,
+    pub assignee_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateStructuredMessageRequest {
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub content: StructuredMessageContent,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StructuredMessageResponse {
+    pub message: ChatMessageResponse,
+    pub state: StructuredMessageState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum StructuredMessageState {
+    Poll(PollState),
+    SessionProposal(SessionProposalState),
+    Checklist(ChecklistState),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollState {
+    pub question: String,
+    pub allow_multiple: bool,
+    pub closes_at: Option<DateTime<Utc>>,
+    pub is_closed: bool,
+    pub total_voters: i64,
+    pub options: Vec<PollOptionTally>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollOptionTally {
+    pub option_id: Uuid,
+    pub label: String,
+    pub vote_count: i64,
+    pub voter_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionProposalState {
+    pub proposed_by: Uuid,
+    pub topic: String,
+    pub start_time: DateTime<Utc>,
+    pub duration_minutes: i32,
+    pub notes: Option<String>,
+    pub status: ProposalStatus,
+    pub responded_by: Option<Uuid>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ProposalStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Withdrawn,
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistState {
+    pub title: String,
+    pub completed_count: i64,
+    pub items: Vec<ChecklistItemState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItemState {
+    pub item_id: Uuid,
+    pub text: String,
+    pub assignee_id: Option<Uuid>,
+    pub is_completed: bool,
+    pub completed_by: Option<Uuid>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CastVoteRequest {
+    pub option_ids: Vec<Uuid>, // Empty clears the caller's vote
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RespondToProposalRequest {
+    pub action: ProposalAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum ProposalAction {
+    Accept,
+    Decline,
+    Withdraw, // Proposer only
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateChecklistItemRequest {
+    pub is_completed: bool,
+}
+
+// End-to-end encryption (key directory and opted-in conversations)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedPreKey {
+    pub key_id: i32,
+    pub public_key: String, // base64
+    pub signature: String, // base64, signed by the identity key
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneTimePreKey {
+    pub key_id: i32,
+    pub public_key: String, // base64
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishKeyBundleRequest {
+    pub identity_key: String, // base64
+    pub signed_prekey: SignedPreKey,
+    #[serde(default)]
+    pub one_time_prekeys: Vec<OneTimePreKey>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadPreKeysRequest {
+    pub one_time_prekeys: Vec<OneTimePreKey>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreKeyCountResponse {
+    pub remaining: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreKeyBundleResponse {
+    pub user_id: Uuid,
+    pub identity_key: String,
+    pub signed_prekey: SignedPreKey,
+    pub one_time_prekey: Option<OneTimePreKey>, // None once the user's supply runs out
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnableEncryptionRequest {
+    pub peer_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedConversationResponse {
+    pub peer_id: Uuid,
+    pub is_encrypted: bool,
+    pub enabled_by: Option<Uuid>,
+    pub enabled_at: Option<DateTime<Utc>>,
+    // Who has opted in while the conversation waits for the other user
+    pub requested_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportEncryptedMessageRequest {
+    pub decrypted_content: String, // Supplied by the reporter; the server cannot verify it
+    pub report_type: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageReportResponse {
+    pub report_id: Uuid,
+    pub message_id: Uuid,
+    pub submitted_at: DateTime<Utc>,
+}
+
+// Slash commands and bots
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CommandVisibility {
+    Ephemeral, // Only the invoker sees the reply
+    InChannel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandResponse {
+    pub visibility: CommandVisibility,
+    pub text: Option<String>,
+    pub card: Option<MessageCard>,
+}
+
+impl CommandResponse {
+    pub fn ephemeral_text(text: impl Into<String>) -> Self {
+        Self {
+            visibility: CommandVisibility::Ephemeral,
+            text: Some(text.into()),
+            card: None,
+        }
+    }
+
+    pub fn in_channel_card(card: MessageCard) -> Self {
+        Self {
+            visibility: CommandVisibility::InChannel,
+            text: None,
+            card: Some(card),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageCard {
+    pub card_id: Uuid,
+    pub card_type: CardType,
+    pub title: String,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<CardField>,
+    #[serde(default)]
+    pub actions: Vec<CardAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CardType {
+    Generic,
+    SessionProposal,
+    PaymentRequest,
+    Poll,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardField {
+    pub label: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardAction {
+    pub action_id: String,
+    pub label: String,
+    pub style: CardActionStyle,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CardActionStyle {
+    Primary,
+    Secondary,
+    Danger,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandInfo {
+    pub name: String,
+    pub description: String,
+    pub usage: String,
+    pub source: CommandSource,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub enum CommandSource {
+    BuiltIn,
+    Webhook,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExecuteCommandRequest {
+    pub content: String,
+    pub recipient_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegisterBotRequest {
+    pub command: String,
+    pub description: String,
+    pub usage: String,
+    pub webhook_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BotResponse {
+    pub bot_id: Uuid,
+    pub command: String,
+    pub description: String,
+    pub usage: String,
+    pub webhook_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>, // Returned once, when the bot is registered
+    pub is_active: bool,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypingIndicatorRequest {
+    pub session_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub is_typing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateGroupChatRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub participants: Vec<Uuid>,
+    pub session_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupChatResponse {
+    pub group_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Uuid,
+    pub participants: Vec<GroupParticipant>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupParticipant {
+    pub user_id: Uuid,
+    pub username: String,
+    pub joined_at: DateTime<Utc>,
+    pub role: GroupRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum GroupRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+// Presence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnlineUser {
+    pub user_id: Uuid,
+    pub username: String,
+    pub status: UserStatus,
+    pub status_text: Option<String>,
+    // None when the user hides it
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum UserStatus {
+    Online,
+    Away,
+    Busy,
+    InSession,
+    Offline,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PresenceVisibility {
+    /// Any signed-in user
+    Everyone,
+    /// Users sharing a mentorship session or an active group chat
+    Connections,
+    Nobody,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceSettings {
+    pub visibility: PresenceVisibility,
+    pub show_last_seen: bool,
+}
+
+impl Default for PresenceSettings {
+    fn default() -> Self {
+        Self {
+            visibility: PresenceVisibility::Everyone,
+            show_last_seen: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdatePresenceSettingsRequest {
+    pub visibility: Option<PresenceVisibility>,
+    pub show_last_seen: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateStatusRequest {
+    pub status: UserStatus,
+    pub text: Option<String>,
+    pub expires_in_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceResponse {
+    pub user_id: Uuid,
+    pub status: UserStatus,
+    pub status_text: Option<String>,
+    pub is_online: bool,
+    /// Only reported to the user themselves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_count: Option<usize>,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+// Connection and room tracking
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    pub user_id: Uuid,
+    pub username: String,
+    pub session_id: Option<Uuid>,
+    pub group_ids: Vec<Uuid>,
+    pub connected_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatRoom {
+    pub room_id: String,
+    pub room_type: ChatRoomType,
+    pub participants: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ChatRoomType {
+    DirectMessage,
+    SessionChat,
+    GroupChat,
+}
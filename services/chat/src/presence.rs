@@ -0,0 +1,534 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use linkwithmentor_common::{AppError, RedisService};
+use crate::{
+    connection_manager::ConnectionManager,
+    models::{
+        OnlineUser, PresenceResponse, PresenceSettings, PresenceVisibility,
+        UpdatePresenceSettingsRequest, UpdateStatusRequest, UserStatus, WSMessage,
+    },
+    pubsub::ChatPubSub,
+};
+
+const MAX_STATUS_TEXT_LENGTH: usize = 100;
+const MAX_STATUS_EXPIRY_MINUTES: u64 = 7 * 24 * 60;
+
+/// Cluster-wide presence backed by Redis. Each WebSocket connection is a
+/// device that heartbeats its own entry, so a user stays online while any of
+/// their devices on any instance is alive. Presence changes are filtered by
+/// the subject's privacy settings before they reach other users.
+#[derive(Clone)]
+pub struct PresenceService {
+    db_pool: PgPool,
+    redis_service: RedisService,
+    connection_manager: ConnectionManager,
+    pubsub: ChatPubSub,
+    ttl_seconds: u64,
+}
+
+struct PresenceSnapshot {
+    status: UserStatus,
+    status_text: Option<String>,
+    is_online: bool,
+    last_seen: Option<DateTime<Utc>>,
+}
+
+impl PresenceService {
+    pub fn new(
+        db_pool: PgPool,
+        redis_service: RedisService,
+        connection_manager: ConnectionManager,
+        pubsub: ChatPubSub,
+        heartbeat_interval_seconds: u64,
+    ) -> Self {
+        Self {
+            db_pool,
+            redis_service,
+            connection_manager,
+            pubsub,
+            // A device survives two missed heartbeats before it is considered gone
+            ttl_seconds: heartbeat_interval_seconds.max(1) * 3,
+        }
+    }
+
+    // Connection lifecycle
+
+    /// Called on connect and on every heartbeat from a device.
+    pub async fn heartbeat(&self, user_id: Uuid, username: &str, connection_id: &str) -> Result<(), AppError> {
+        let came_online = self.redis_service
+            .presence_heartbeat(&user_id.to_string(), connection_id, self.ttl_seconds)
+            .await?;
+
+        if came_online {
+            self.broadcast_presence(user_id, username).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn disconnect(&self, user_id: Uuid, username: &str, connection_id: &str) -> Result<(), AppError> {
+        let went_offline = self.redis_service
+            .remove_presence_device(&user_id.to_string(), connection_id)
+            .await?;
+
+        if went_offline {
+            self.broadcast_presence(user_id, username).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Marks users offline whose devices stopped heartbeating without a clean
+    /// disconnect, e.g. because their instance crashed.
+    pub async fn start_sweeper(&self) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(service.ttl_seconds));
+
+            loop {
+                interval.tick().await;
+
+                let expired = match service.redis_service.expire_stale_presence().await {
+                    Ok(expired) => expired,
+                    Err(e) => {
+                        tracing::error!("Failed to expire stale presence: {}", e);
+                        continue;
+                    }
+                };
+
+                for user_id in expired.iter().filter_map(|id| id.parse::<Uuid>().ok()) {
+                    let username = match service.get_username(user_id).await {
+                        Ok(username) => username,
+                        Err(e) => {
+                            tracing::warn!("Skipping presence expiry broadcast for {}: {}", user_id, e);
+                            continue;
+                        }
+                    };
+
+                    if let Err(e) = service.broadcast_presence(user_id, &username).await {
+                        tracing::error!("Failed to broadcast presence expiry for {}: {}", user_id, e);
+                    }
+                }
+            }
+        });
+    }
+
+    // Custom status
+
+    pub async fn set_status(&self, user_id: Uuid, username: &str, request: UpdateStatusRequest) -> Result<PresenceResponse, AppError> {
+        if request.status == UserStatus::Offline {
+            return Err(AppError::BadRequest(
+                "Use presence settings to hide your online status".to_string()
+            ));
+        }
+
+        let text = request.text
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty());
+        if text.as_ref().map_or(false, |text| text.chars().count() > MAX_STATUS_TEXT_LENGTH) {
+            return Err(AppError::BadRequest(format!(
+                "Status text cannot exceed {} characters",
+                MAX_STATUS_TEXT_LENGTH
+            )));
+        }
+
+        if let Some(minutes) = request.expires_in_minutes {
+            if minutes == 0 || minutes > MAX_STATUS_EXPIRY_MINUTES {
+                return Err(AppError::BadRequest(format!(
+                    "Status expiry must be between 1 and {} minutes",
+                    MAX_STATUS_EXPIRY_MINUTES
+                )));
+            }
+        }
+
+        self.redis_service
+            .set_custom_status(
+                &user_id.to_string(),
+                &status_value(&request.status),
+                text.as_deref(),
+                request.expires_in_minutes.map(|minutes| minutes * 60),
+            )
+            .await?;
+
+        self.broadcast_presence(user_id, username).await?;
+        self.get_presence(user_id, user_id).await
+    }
+
+    pub async fn clear_status(&self, user_id: Uuid, username: &str) -> Result<(), AppError> {
+        self.redis_service.clear_custom_status(&user_id.to_string()).await?;
+        self.broadcast_presence(user_id, username).await
+    }
+
+    // Queries
+
+    /// Presence of `target_id` as seen by `viewer_id`. Users hidden from the
+    /// viewer are reported as offline rather than refused, so hiding is not
+    /// observable.
+    pub async fn get_presence(&self, viewer_id: Uuid, target_id: Uuid) -> Result<PresenceResponse, AppError> {
+        let is_self = viewer_id == target_id;
+        let settings = self.get_settings(target_id).await?;
+
+        if !is_self && !self.can_view(viewer_id, target_id, &settings).await? {
+            return Ok(PresenceResponse {
+                user_id: target_id,
+                status: UserStatus::Offline,
+                status_text: None,
+                is_online: false,
+                device_count: None,
+                last_seen: None,
+            });
+        }
+
+        let snapshot = self.snapshot(target_id, &settings, is_self).await?;
+
+        let device_count = if is_self {
+            Some(self.redis_service.get_presence_devices(&target_id.to_string()).await?.len())
+        } else {
+            None
+        };
+
+        Ok(PresenceResponse {
+            user_id: target_id,
+            status: snapshot.status,
+            status_text: snapshot.status_text,
+            is_online: snapshot.is_online,
+            device_count,
+            last_seen: snapshot.last_seen,
+        })
+    }
+
+    /// Online users across all instances that the viewer is allowed to see.
+    pub async fn list_online(&self, viewer_id: Uuid) -> Result<Vec<OnlineUser>, AppError> {
+        let online_ids: Vec<Uuid> = self.redis_service
+            .get_online_user_ids()
+            .await?
+            .iter()
+            .filter_map(|id| id.parse().ok())
+            .collect();
+
+        if online_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let settings = self.get_settings_for(&online_ids).await?;
+        let connections = self.get_connections(viewer_id).await?;
+        let usernames = self.get_usernames(&online_ids).await?;
+
+        let mut online_users = Vec::new();
+        for user_id in online_ids {
+            let user_settings = settings.get(&user_id).cloned().unwrap_or_default();
+
+            let visible = user_id == viewer_id || match user_settings.visibility {
+                PresenceVisibility::Everyone => true,
+                PresenceVisibility::Connections => connections.contains(&user_id),
+                PresenceVisibility::Nobody => false,
+            };
+            let username = match usernames.get(&user_id) {
+                Some(username) if visible => username.clone(),
+                _ => continue,
+            };
+
+            let snapshot = self.snapshot(user_id, &user_settings, user_id == viewer_id).await?;
+            if !snapshot.is_online {
+                continue;
+            }
+
+            online_users.push(OnlineUser {
+                user_id,
+                username,
+                status: snapshot.status,
+                status_text: snapshot.status_text,
+                last_seen: snapshot.last_seen,
+            });
+        }
+
+        online_users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(online_users)
+    }
+
+    // Privacy settings
+
+    pub async fn get_settings(&self, user_id: Uuid) -> Result<PresenceSettings, AppError> {
+        let row = sqlx::query_as::<_, PresenceSettingsRow>(
+            "SELECT user_id, visibility, show_last_seen FROM presence_settings WHERE user_id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch presence settings: {}", e)))?;
+
+        Ok(row.map(PresenceSettings::from).unwrap_or_default())
+    }
+
+    pub async fn update_settings(
+        &self,
+        user_id: Uuid,
+        username: &str,
+        request: UpdatePresenceSettingsRequest,
+    ) -> Result<PresenceSettings, AppError> {
+        let current = self.get_settings(user_id).await?;
+        let settings = PresenceSettings {
+            visibility: request.visibility.unwrap_or(current.visibility),
+            show_last_seen: request.show_last_seen.unwrap_or(current.show_last_seen),
+        };
+
+        let query = r#"
+            INSERT INTO presence_settings (user_id, visibility, show_last_seen, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO UPDATE
+            SET visibility = EXCLUDED.visibility,
+                show_last_seen = EXCLUDED.show_last_seen,
+                updated_at = EXCLUDED.updated_at
+        "#;
+
+        sqlx::query(query)
+            .bind(user_id)
+            .bind(&settings.visibility)
+            .bind(settings.show_last_seen)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update presence settings: {}", e)))?;
+
+        // Viewers who just lost access would otherwise keep the last status
+        // they saw, so tell them the user went offline
+        let revoked = self.revoked_viewers(user_id, &current.visibility, &settings.visibility).await?;
+        if !revoked.is_empty() {
+            self.hide_presence_from(user_id, username, revoked).await?;
+        }
+
+        // Let viewers who just gained access catch up
+        self.broadcast_presence(user_id, username).await?;
+
+        Ok(settings)
+    }
+
+    // Internal helpers
+
+    async fn snapshot(&self, user_id: Uuid, settings: &PresenceSettings, is_self: bool) -> Result<PresenceSnapshot, AppError> {
+        let key = user_id.to_string();
+        let is_online = self.redis_service.is_user_online(&key).await?;
+
+        let (status, status_text) = if is_online {
+            match self.redis_service.get_custom_status(&key).await? {
+                Some(custom) => (parse_status(&custom.status), custom.text),
+                None => (UserStatus::Online, None),
+            }
+        } else {
+            (UserStatus::Offline, None)
+        };
+
+        let last_seen = if settings.show_last_seen || is_self {
+            self.redis_service
+                .get_last_seen(&key)
+                .await?
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        } else {
+            None
+        };
+
+        Ok(PresenceSnapshot { status, status_text, is_online, last_seen })
+    }
+
+    async fn can_view(&self, viewer_id: Uuid, target_id: Uuid, settings: &PresenceSettings) -> Result<bool, AppError> {
+        match settings.visibility {
+            PresenceVisibility::Everyone => Ok(true),
+            PresenceVisibility::Connections => Ok(self.get_connections(target_id).await?.contains(&viewer_id)),
+            PresenceVisibility::Nobody => Ok(false),
+        }
+    }
+
+    /// Users who could see `user_id` under `before` but not under `after`.
+    async fn revoked_viewers(
+        &self,
+        user_id: Uuid,
+        before: &PresenceVisibility,
+        after: &PresenceVisibility,
+    ) -> Result<Vec<Uuid>, AppError> {
+        let previous: HashSet<Uuid> = match before {
+            PresenceVisibility::Everyone => self.redis_service
+                .get_online_user_ids()
+                .await?
+                .iter()
+                .filter_map(|id| Uuid::parse_str(id).ok())
+                .collect(),
+            PresenceVisibility::Connections => self.get_connections(user_id).await?,
+            PresenceVisibility::Nobody => return Ok(Vec::new()),
+        };
+
+        let allowed: HashSet<Uuid> = match after {
+            PresenceVisibility::Everyone => return Ok(Vec::new()),
+            PresenceVisibility::Connections => self.get_connections(user_id).await?,
+            PresenceVisibility::Nobody => HashSet::new(),
+        };
+
+        Ok(previous
+            .into_iter()
+            .filter(|viewer| *viewer != user_id && !allowed.contains(viewer))
+            .collect())
+    }
+
+    /// Shows the user as offline, with no status text or last seen, to
+    /// `viewers` on every instance.
+    async fn hide_presence_from(&self, user_id: Uuid, username: &str, viewers: Vec<Uuid>) -> Result<(), AppError> {
+        let message = WSMessage::PresenceChanged {
+            user_id,
+            username: username.to_string(),
+            status: UserStatus::Offline,
+            status_text: None,
+            last_seen: None,
+        };
+        self.connection_manager.broadcast_to_users(&viewers, message).await?;
+
+        self.pubsub
+            .publish_user_presence(
+                user_id,
+                username.to_string(),
+                UserStatus::Offline,
+                None,
+                None,
+                Some(viewers),
+            )
+            .await
+    }
+
+    /// Sends the user's current presence to every local viewer allowed to see
+    /// it and publishes it for the other instances. The user's own devices
+    /// always receive it so they stay in sync.
+    async fn broadcast_presence(&self, user_id: Uuid, username: &str) -> Result<(), AppError> {
+        let settings = self.get_settings(user_id).await?;
+        let snapshot = self.snapshot(user_id, &settings, false).await?;
+
+        let visible_to: Option<Vec<Uuid>> = match settings.visibility {
+            PresenceVisibility::Everyone => None,
+            PresenceVisibility::Connections => {
+                let mut viewers: Vec<Uuid> = self.get_connections(user_id).await?.into_iter().collect();
+                viewers.push(user_id);
+                Some(viewers)
+            }
+            PresenceVisibility::Nobody => Some(vec![user_id]),
+        };
+
+        let message = WSMessage::PresenceChanged {
+            user_id,
+            username: username.to_string(),
+            status: snapshot.status.clone(),
+            status_text: snapshot.status_text.clone(),
+            last_seen: snapshot.last_seen,
+        };
+
+        let recipients = match &visible_to {
+            Some(viewers) => viewers.clone(),
+            None => self.connection_manager.get_local_users().await,
+        };
+        self.connection_manager.broadcast_to_users(&recipients, message).await?;
+
+        self.pubsub
+            .publish_user_presence(
+                user_id,
+                username.to_string(),
+                snapshot.status,
+                snapshot.status_text,
+                snapshot.last_seen,
+                visible_to,
+            )
+            .await
+    }
+
+    /// Users who share a mentorship session or an active group chat with `user_id`.
+    async fn get_connections(&self, user_id: Uuid) -> Result<HashSet<Uuid>, AppError> {
+        let query = r#"
+            SELECT mentee_id AS user_id FROM mentorship_sessions WHERE mentor_id = $1
+            UNION
+            SELECT mentor_id AS user_id FROM mentorship_sessions WHERE mentee_id = $1
+            UNION
+            SELECT other.user_id
+            FROM group_chat_participants me
+            JOIN group_chat_participants other ON other.group_id = me.group_id
+            WHERE me.user_id = $1 AND me.left_at IS NULL
+              AND other.left_at IS NULL AND other.user_id <> $1
+        "#;
+
+        let ids = sqlx::query_scalar::<_, Uuid>(query)
+            .bind(user_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch user connections: {}", e)))?;
+
+        Ok(ids.into_iter().collect())
+    }
+
+    async fn get_settings_for(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, PresenceSettings>, AppError> {
+        let rows = sqlx::query_as::<_, PresenceSettingsRow>(
+            "SELECT user_id, visibility, show_last_seen FROM presence_settings WHERE user_id = ANY($1)"
+        )
+        .bind(user_ids)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch presence settings: {}", e)))?;
+
+        Ok(rows.into_iter().map(|row| (row.user_id, PresenceSettings::from(row))).collect())
+    }
+
+    async fn get_usernames(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, String>, AppError> {
+        let rows = sqlx::query_as::<_, UsernameRow>(
+            "SELECT user_id, username FROM users WHERE user_id = ANY($1)"
+        )
+        .bind(user_ids)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch usernames: {}", e)))?;
+
+        Ok(rows.into_iter().map(|row| (row.user_id, row.username)).collect())
+    }
+
+    async fn get_username(&self, user_id: Uuid) -> Result<String, AppError> {
+        sqlx::query_scalar::<_, String>("SELECT username FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch username: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+}
+
+// Statuses are stored in Redis as their serde names, as `parse_status` reads them
+fn status_value(status: &UserStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_status(value: &str) -> UserStatus {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .unwrap_or(UserStatus::Online)
+}
+
+impl From<PresenceSettingsRow> for PresenceSettings {
+    fn from(row: PresenceSettingsRow) -> Self {
+        Self {
+            visibility: row.visibility,
+            show_last_seen: row.show_last_seen,
+        }
+    }
+}
+
+// Database row structs
+#[derive(sqlx::FromRow)]
+struct PresenceSettingsRow {
+    user_id: Uuid,
+    visibility: PresenceVisibility,
+    show_last_seen: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct UsernameRow {
+    user_id: Uuid,
+    username: String,
+}
@@ -7,7 +7,7 @@ use futures::StreamExt;
 
 use linkwithmentor_common::{AppError, RedisService};
 use crate::{
-    models::{WSMessage, ChatRoomType},
+    models::{WSMessage, ChatRoomType, UserStatus},
     connection_manager::ConnectionManager,
 };
 
@@ -35,7 +35,11 @@ pub enum PubSubMessageType {
     UserPresence {
         user_id: Uuid,
         username: String,
-        is_online: bool,
+        status: UserStatus,
+        status_text: Option<String>,
+        last_seen: Option<chrono::DateTime<chrono::Utc>>,
+        /// None when the user's presence is visible to everyone
+        visible_to: Option<Vec<Uuid>>,
     },
     TypingIndicator {
         user_id: Uuid,
@@ -220,29 +224,22 @@ impl ChatPubSub {
                 }
             }
 
-            PubSubMessageType::UserPresence { user_id, username, is_online } => {
-                let ws_message = if is_online {
-                    WSMessage::UserJoined {
-                        user_id,
-                        username,
-                        session_id: None,
-                        group_id: None,
-                    }
-                } else {
-                    WSMessage::UserLeft {
-                        user_id,
-                        username,
-                        session_id: None,
-                        group_id: None,
-                    }
+            PubSubMessageType::UserPresence { user_id, username, status, status_text, last_seen, visible_to } => {
+                let ws_message = WSMessage::PresenceChanged {
+                    user_id,
+                    username,
+                    status,
+                    status_text,
+                    last_seen,
                 };
 
-                // Broadcast to all connected users
-                let online_users = connection_manager.get_online_users().await;
-                for online_user_id in online_users {
-                    if online_user_id != user_id {
-                        let _ = connection_manager.send_to_user(online_user_id, ws_message.clone()).await;
-                    }
+                // Deliver only to local users allowed to see this user's presence
+                let recipients = match visible_to {
+                    Some(viewers) => viewers,
+                    None => connection_manager.get_local_users().await,
+                };
+                for recipient_id in recipients {
+                    let _ = connection_manager.send_to_user(recipient_id, ws_message.clone()).await;
                 }
             }
 
@@ -353,14 +350,20 @@ impl ChatPubSub {
         &self,
         user_id: Uuid,
         username: String,
-        is_online: bool,
+        status: UserStatus,
+        status_text: Option<String>,
+        last_seen: Option<chrono::DateTime<chrono::Utc>>,
+        visible_to: Option<Vec<Uuid>>,
     ) -> Result<(), AppError> {
         let pubsub_msg = PubSubMessage {
             channel: "chat:presence".to_string(),
             message_type: PubSubMessageType::UserPresence {
                 user_id,
                 username,
-                is_online,
+                status,
+                status_text,
+                last_seen,
+                visible_to,
             },
             payload: serde_json::Value::Null,
             sender_instance: self.instance_id.clone(),
@@ -26,13 +26,55 @@ pub fn create_routes() -> Router<AppState> {
         .route("/messages/:message_id", put(handlers::update_message))
         .route("/messages/:message_id", delete(handlers::delete_message))
         
+        // Scheduled message endpoints
+        .route("/messages/scheduled", post(handlers::create_scheduled_message))
+        .route("/messages/scheduled", get(handlers::list_scheduled_messages))
+        .route("/messages/scheduled/:scheduled_message_id", put(handlers::update_scheduled_message))
+        .route("/messages/scheduled/:scheduled_message_id", delete(handlers::cancel_scheduled_message))
+        
+        // Structured message endpoints (polls, session proposals, checklists)
+        .route("/messages/structured", post(handlers::create_structured_message))
+        .route("/messages/:message_id/state", get(handlers::get_structured_message_state))
+        .route("/messages/:message_id/poll/votes", put(handlers::cast_poll_vote))
+        .route("/messages/:message_id/poll/close", post(handlers::close_poll))
+        .route("/messages/:message_id/proposal/response", post(handlers::respond_to_proposal))
+        .route("/messages/:message_id/checklist/:item_id", put(handlers::update_checklist_item))
+        
+        // End-to-end encryption endpoints (key directory and opt-in)
+        .route("/e2e/keys", put(handlers::publish_e2e_keys))
+        .route("/e2e/keys/prekeys", post(handlers::upload_e2e_prekeys))
+        .route("/e2e/keys/prekeys/count", get(handlers::get_e2e_prekey_count))
+        .route("/e2e/keys/:user_id/bundle", get(handlers::get_e2e_key_bundle))
+        .route("/e2e/conversations", post(handlers::enable_e2e_conversation))
+        .route("/e2e/conversations/:peer_id", get(handlers::get_e2e_conversation))
+        .route("/messages/:message_id/report", post(handlers::report_encrypted_message))
+        
+        // Slash command endpoints
+        .route("/commands", get(handlers::list_commands))
+        .route("/commands/execute", post(handlers::execute_command))
+        
+        // Bot management endpoints (admin only)
+        .route("/bots", post(handlers::register_bot))
+        .route("/bots", get(handlers::list_bots))
+        .route("/bots/:bot_id", delete(handlers::deactivate_bot))
+        
+        // Moderation audit endpoints (moderator/admin only)
+        .route("/admin/messages/:message_id/revisions", get(handlers::get_message_audit))
+        
         // User presence endpoints
         .route("/users/online", get(handlers::get_online_users))
+        .route("/users/:user_id/presence", get(handlers::get_user_presence))
+        .route("/presence/status", put(handlers::set_presence_status).delete(handlers::clear_presence_status))
+        .route("/presence/settings", get(handlers::get_presence_settings).put(handlers::update_presence_settings))
         .route("/rooms/:room_id/participants", get(handlers::get_room_participants))
         
         // Typing indicators
         .route("/typing", post(handlers::send_typing_indicator))
         
+        // Conversation export endpoints
+        .route("/exports", post(handlers::create_export))
+        .route("/exports/:export_id", get(handlers::get_export))
+        
         // Group chat endpoints
         .route("/groups", post(handlers::create_group_chat))
         .route("/groups/:group_id/join", post(handlers::join_group_chat))
@@ -43,4 +85,10 @@ pub fn create_routes() -> Router<AppState> {
             (), // We'll pass the JWT service through the app state
             auth_middleware,
         ))
+        
+        // Export downloads are authorized by the unguessable token in the link
+        .route("/exports/download/:download_token", get(handlers::download_export))
+
+        // Service-to-service endpoints (authenticated with X-Internal-Token)
+        .route("/internal/messages/system", post(handlers::post_internal_system_message))
 }
\ No newline at end of file
@@ -0,0 +1,714 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use linkwithmentor_common::{AppError, MessageType};
+use crate::{
+    message_service::MessageService,
+    models::{
+        ChatMessageResponse, CreateScheduledMessageRequest, MessageRecurrence, MessageRecurrenceFrequency,
+        ScheduledMessageResponse, ScheduledMessageStatus, UpdateScheduledMessageRequest,
+    },
+    pubsub::ChatPubSub,
+    structured_messages,
+};
+
+const MAX_DELIVERY_ATTEMPTS: i32 = 3;
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// Stores messages to be sent later and delivers them through the regular
+/// `MessageService::send_message` path, so moderation and rate limits apply.
+#[derive(Clone)]
+pub struct ScheduledMessageService {
+    db_pool: PgPool,
+    message_service: MessageService,
+    pubsub: ChatPubSub,
+}
+
+impl ScheduledMessageService {
+    pub fn new(db_pool: PgPool, message_service: MessageService, pubsub: ChatPubSub) -> Self {
+        Self {
+            db_pool,
+            message_service,
+            pubsub,
+        }
+    }
+
+    pub async fn create_scheduled_message(
+        &self,
+        sender_id: Uuid,
+        request: CreateScheduledMessageRequest,
+    ) -> Result<ScheduledMessageResponse, AppError> {
+        validate_target(request.recipient_id, request.session_id, request.group_id)?;
+
+        if request.content.trim().is_empty() {
+            return Err(AppError::BadRequest("Message content cannot be empty".to_string()));
+        }
+
+        if structured_messages::is_structured(&request.message_type) {
+            return Err(AppError::BadRequest(
+                "Polls, proposals and checklists cannot be scheduled".to_string()
+            ));
+        }
+        if matches!(request.message_type, MessageType::System) {
+            return Err(AppError::BadRequest("System messages can only be sent by the platform".to_string()));
+        }
+
+        if request.send_at <= Utc::now() {
+            return Err(AppError::BadRequest("Scheduled time must be in the future".to_string()));
+        }
+
+        let timezone = request.timezone.unwrap_or_else(|| "UTC".to_string());
+        parse_timezone(&timezone)?;
+
+        if let Some(recurrence) = &request.recurrence {
+            validate_recurrence(recurrence)?;
+        }
+
+        let scheduled_message_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let recurrence_json = request.recurrence.as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| AppError::Internal(format!("Failed to serialize recurrence: {}", e)))?;
+
+        let query = r#"
+            INSERT INTO scheduled_messages (
+                scheduled_message_id, sender_id, recipient_id, session_id, group_id,
+                content, message_type, first_send_at, next_send_at, timezone, recurrence,
+                status, occurrence_count, attempt_count, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8, $9, $10, $11, 0, 0, $12, $12)
+        "#;
+
+        sqlx::query(query)
+            .bind(scheduled_message_id)
+            .bind(sender_id)
+            .bind(request.recipient_id)
+            .bind(request.session_id)
+            .bind(request.group_id)
+            .bind(&request.content)
+            .bind(&request.message_type)
+            .bind(request.send_at)
+            .bind(&timezone)
+            .bind(recurrence_json)
+            .bind(&ScheduledMessageStatus::Scheduled)
+            .bind(now)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to schedule message: {}", e)))?;
+
+        self.get_scheduled_message(scheduled_message_id, sender_id).await
+    }
+
+    pub async fn list_scheduled_messages(&self, sender_id: Uuid) -> Result<Vec<ScheduledMessageResponse>, AppError> {
+        let query = format!("{} WHERE sender_id = $1 ORDER BY next_send_at ASC NULLS LAST", SELECT_SCHEDULED_MESSAGE);
+
+        let rows = sqlx::query_as::<_, ScheduledMessageRow>(&query)
+            .bind(sender_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch scheduled messages: {}", e)))?;
+
+        Ok(rows.into_iter().map(ScheduledMessageRow::into_response).collect())
+    }
+
+    pub async fn get_scheduled_message(
+        &self,
+        scheduled_message_id: Uuid,
+        sender_id: Uuid,
+    ) -> Result<ScheduledMessageResponse, AppError> {
+        let query = format!("{} WHERE scheduled_message_id = $1 AND sender_id = $2", SELECT_SCHEDULED_MESSAGE);
+
+        sqlx::query_as::<_, ScheduledMessageRow>(&query)
+            .bind(scheduled_message_id)
+            .bind(sender_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch scheduled message: {}", e)))?
+            .map(ScheduledMessageRow::into_response)
+            .ok_or_else(|| AppError::NotFound("Scheduled message not found".to_string()))
+    }
+
+    pub async fn update_scheduled_message(
+        &self,
+        scheduled_message_id: Uuid,
+        sender_id: Uuid,
+        request: UpdateScheduledMessageRequest,
+    ) -> Result<ScheduledMessageResponse, AppError> {
+        let current = self.get_scheduled_message(scheduled_message_id, sender_id).await?;
+
+        if current.status != ScheduledMessageStatus::Scheduled {
+            return Err(AppError::BadRequest("Only pending scheduled messages can be edited".to_string()));
+        }
+
+        let content = request.content.unwrap_or(current.content);
+        if content.trim().is_empty() {
+            return Err(AppError::BadRequest("Message content cannot be empty".to_string()));
+        }
+
+        let timezone = request.timezone.unwrap_or(current.timezone);
+        parse_timezone(&timezone)?;
+
+        // Rescheduling moves the anchor of the series as well as the next send
+        let (first_send_at, next_send_at) = match request.send_at {
+            Some(send_at) if send_at <= Utc::now() => {
+                return Err(AppError::BadRequest("Scheduled time must be in the future".to_string()));
+            }
+            Some(send_at) => (send_at, send_at),
+            None => (current.first_send_at, current.next_send_at.unwrap_or(current.first_send_at)),
+        };
+
+        let recurrence = if request.clear_recurrence {
+            None
+        } else {
+            request.recurrence.or(current.recurrence)
+        };
+        if let Some(recurrence) = &recurrence {
+            validate_recurrence(recurrence)?;
+        }
+
+        let recurrence_json = recurrence.as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| AppError::Internal(format!("Failed to serialize recurrence: {}", e)))?;
+
+        let query = r#"
+            UPDATE scheduled_messages
+            SET content = $1, first_send_at = $2, next_send_at = $3, timezone = $4,
+                recurrence = $5, updated_at = $6
+            WHERE scheduled_message_id = $7 AND sender_id = $8 AND status = $9
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(&content)
+            .bind(first_send_at)
+            .bind(next_send_at)
+            .bind(&timezone)
+            .bind(recurrence_json)
+            .bind(Utc::now())
+            .bind(scheduled_message_id)
+            .bind(sender_id)
+            .bind(&ScheduledMessageStatus::Scheduled)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update scheduled message: {}", e)))?;
+
+        // The worker may have picked it up between the read and the write
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict("Scheduled message is no longer pending".to_string()));
+        }
+
+        self.get_scheduled_message(scheduled_message_id, sender_id).await
+    }
+
+    pub async fn cancel_scheduled_message(
+        &self,
+        scheduled_message_id: Uuid,
+        sender_id: Uuid,
+    ) -> Result<(), AppError> {
+        let query = r#"
+            UPDATE scheduled_messages
+            SET status = $1, next_send_at = NULL, updated_at = $2
+            WHERE scheduled_message_id = $3 AND sender_id = $4 AND status = $5
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(&ScheduledMessageStatus::Cancelled)
+            .bind(Utc::now())
+            .bind(scheduled_message_id)
+            .bind(sender_id)
+            .bind(&ScheduledMessageStatus::Scheduled)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to cancel scheduled message: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                "Scheduled message not found or already sent".to_string()
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub async fn start_delivery_worker(&self, poll_interval_seconds: u64) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(poll_interval_seconds));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = service.deliver_due_messages().await {
+                    tracing::error!("Scheduled message delivery failed: {}", e);
+                }
+            }
+        });
+    }
+
+    // Private helper methods
+
+    async fn deliver_due_messages(&self) -> Result<(), AppError> {
+        // Each message gets its own short transaction so a slow send never
+        // holds locks on the rest of the batch
+        for _ in 0..DELIVERY_BATCH_SIZE {
+            if !self.deliver_next().await? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Claims one due message, stores it and advances the schedule in the
+    /// same transaction, so a crash can't leave a message sent but still due.
+    /// Returns false once nothing is due.
+    async fn deliver_next(&self) -> Result<bool, AppError> {
+        let mut tx = self.db_pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // SKIP LOCKED lets several chat instances run the worker without
+        // sending the same message twice
+        let query = format!(
+            "{} WHERE status = $1 AND next_send_at <= NOW() ORDER BY next_send_at ASC LIMIT 1 FOR UPDATE SKIP LOCKED",
+            SELECT_SCHEDULED_MESSAGE
+        );
+
+        let row = sqlx::query_as::<_, ScheduledMessageRow>(&query)
+            .bind(&ScheduledMessageStatus::Scheduled)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch due scheduled message: {}", e)))?;
+
+        let Some(row) = row else {
+            return Ok(false);
+        };
+
+        let scheduled_message_id = row.scheduled_message_id;
+
+        // The send runs in a savepoint: a failed insert aborts only the
+        // savepoint, and the attempt can still be recorded below
+        let outcome = {
+            let mut savepoint = sqlx::Acquire::begin(&mut tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to start savepoint: {}", e)))?;
+
+            match self.store(&mut savepoint, &row).await {
+                Ok(message) => savepoint
+                    .commit()
+                    .await
+                    .map(|_| message)
+                    .map_err(|e| AppError::Database(format!("Failed to release savepoint: {}", e))),
+                Err(e) => Err(e),
+            }
+        };
+
+        let (status, next_send_at, occurrence_count, attempt_count, last_error) = match &outcome {
+            Ok(_) => {
+                let occurrence_count = row.occurrence_count + 1;
+                let next = row.next_occurrence(occurrence_count);
+                let status = if next.is_some() {
+                    ScheduledMessageStatus::Scheduled
+                } else {
+                    ScheduledMessageStatus::Sent
+                };
+                (status, next, occurrence_count, 0, None)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to deliver scheduled message {}: {}", scheduled_message_id, e);
+                let attempt_count = row.attempt_count + 1;
+                if attempt_count >= MAX_DELIVERY_ATTEMPTS {
+                    // Give up on this occurrence only; a recurring series
+                    // moves on to its next one with the error kept on the row
+                    let occurrence_count = row.occurrence_count + 1;
+                    match row.next_occurrence(occurrence_count) {
+                        Some(next) => (ScheduledMessageStatus::Scheduled, Some(next), occurrence_count, 0, Some(e.to_string())),
+                        None => (ScheduledMessageStatus::Failed, None, row.occurrence_count, attempt_count, Some(e.to_string())),
+                    }
+                } else {
+                    // Back off before retrying so a rate limit has time to clear
+                    let retry_at = Utc::now() + Duration::minutes(attempt_count as i64);
+                    (ScheduledMessageStatus::Scheduled, Some(retry_at), row.occurrence_count, attempt_count, Some(e.to_string()))
+                }
+            }
+        };
+
+        let update = r#"
+            UPDATE scheduled_messages
+            SET status = $1, next_send_at = $2, occurrence_count = $3, attempt_count = $4,
+                last_error = $5, last_sent_at = CASE WHEN $6 THEN NOW() ELSE last_sent_at END,
+                updated_at = NOW()
+            WHERE scheduled_message_id = $7
+        "#;
+
+        sqlx::query(update)
+            .bind(&status)
+            .bind(next_send_at)
+            .bind(occurrence_count)
+            .bind(attempt_count)
+            .bind(last_error)
+            .bind(outcome.is_ok())
+            .bind(scheduled_message_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update scheduled message: {}", e)))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to commit scheduled delivery: {}", e)))?;
+
+        // The message is stored and the schedule advanced; from here on a
+        // failure must not make the worker send it again
+        if let Ok(message) = outcome {
+            self.announce(&row, &message).await;
+        }
+
+        Ok(true)
+    }
+
+    async fn store(
+        &self,
+        conn: &mut PgConnection,
+        row: &ScheduledMessageRow,
+    ) -> Result<ChatMessageResponse, AppError> {
+        self.message_service
+            .send_message_in(
+                conn,
+                row.sender_id,
+                row.content.clone(),
+                row.recipient_id,
+                row.session_id,
+                row.group_id,
+                row.message_type.clone(),
+            )
+            .await
+    }
+
+    async fn announce(&self, row: &ScheduledMessageRow, message: &ChatMessageResponse) {
+        if let Err(e) = self.message_service.after_send(message).await {
+            tracing::warn!("Failed to update caches for scheduled message {}: {}", row.scheduled_message_id, e);
+        }
+
+        if let Err(e) = self.pubsub
+            .publish_chat_message(
+                message.message_id,
+                row.sender_id,
+                row.recipient_id,
+                row.session_id,
+                row.group_id,
+                row.content.clone(),
+                row.message_type.clone(),
+            )
+            .await
+        {
+            tracing::warn!("Failed to publish scheduled message {}: {}", row.scheduled_message_id, e);
+        }
+    }
+}
+
+const SELECT_SCHEDULED_MESSAGE: &str = r#"
+    SELECT scheduled_message_id, sender_id, recipient_id, session_id, group_id,
+           content, message_type, first_send_at, next_send_at, timezone, recurrence,
+           status, occurrence_count, attempt_count, last_sent_at, last_error,
+           created_at, updated_at
+    FROM scheduled_messages
+"#;
+
+fn validate_target(
+    recipient_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    group_id: Option<Uuid>,
+) -> Result<(), AppError> {
+    let targets = [recipient_id, session_id, group_id].iter().filter(|t| t.is_some()).count();
+    if targets != 1 {
+        return Err(AppError::BadRequest(
+            "Specify exactly one of recipient_id, session_id or group_id".to_string()
+        ));
+    }
+    Ok(())
+}
+
+fn validate_recurrence(recurrence: &MessageRecurrence) -> Result<(), AppError> {
+    if recurrence.interval == 0 {
+        return Err(AppError::BadRequest("Recurrence interval must be at least 1".to_string()));
+    }
+
+    if let Some(days) = &recurrence.days_of_week {
+        if recurrence.frequency != MessageRecurrenceFrequency::Weekly {
+            return Err(AppError::BadRequest("days_of_week only applies to weekly recurrence".to_string()));
+        }
+        if days.is_empty() || days.iter().any(|&d| d > 6) {
+            return Err(AppError::BadRequest("days_of_week must contain values 0 (Sunday) to 6".to_string()));
+        }
+    }
+
+    if recurrence.max_occurrences == Some(0) {
+        return Err(AppError::BadRequest("max_occurrences must be at least 1".to_string()));
+    }
+
+    Ok(())
+}
+
+fn parse_timezone(timezone: &str) -> Result<Tz, AppError> {
+    timezone.parse::<Tz>()
+        .map_err(|_| AppError::BadRequest(format!("Unknown timezone: {}", timezone)))
+}
+
+/// Computes the occurrence after `previous` for a series anchored at
+/// `first`. Wall-clock time is kept in `tz`, so "Monday 9am" stays 9am
+/// across DST changes.
+fn next_occurrence(
+    recurrence: &MessageRecurrence,
+    tz: Tz,
+    first: DateTime<Utc>,
+    previous: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let anchor = first.with_timezone(&tz).naive_local();
+    let previous_date = previous.with_timezone(&tz).date_naive();
+    let interval = recurrence.interval.max(1);
+
+    let next_date = match recurrence.frequency {
+        MessageRecurrenceFrequency::Daily => previous_date + Duration::days(interval as i64),
+        MessageRecurrenceFrequency::Weekly => {
+            let days = recurrence.days_of_week.clone()
+                .unwrap_or_else(|| vec![anchor.weekday().num_days_from_sunday() as u8]);
+            let anchor_week_start = anchor.date() - Duration::days(anchor.weekday().num_days_from_sunday() as i64);
+
+            (1..=(7 * interval as i64 + 7))
+                .map(|offset| previous_date + Duration::days(offset))
+                .find(|date| {
+                    let weeks_since_anchor = (*date - anchor_week_start).num_days().div_euclid(7);
+                    weeks_since_anchor % interval as i64 == 0
+                        && days.contains(&(date.weekday().num_days_from_sunday() as u8))
+                })?
+        }
+        MessageRecurrenceFrequency::Monthly => {
+            // Months without the anchor day (e.g. the 31st) are skipped
+            let mut months_ahead = interval;
+            loop {
+                let total = previous_date.year() * 12 + previous_date.month0() as i32 + months_ahead as i32;
+                if let Some(date) = NaiveDate::from_ymd_opt(total.div_euclid(12), total.rem_euclid(12) as u32 + 1, anchor.day()) {
+                    break date;
+                }
+                months_ahead += interval;
+                if months_ahead > interval * 12 {
+                    return None;
+                }
+            }
+        }
+    };
+
+    Some(local_to_utc(tz, next_date.and_time(anchor.time())))
+}
+
+fn local_to_utc(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local).earliest() {
+        Some(dt) => dt.with_timezone(&Utc),
+        // The wall-clock time falls in a DST gap; send at the first valid instant after it
+        None => tz
+            .from_local_datetime(&(local + Duration::hours(1)))
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local)),
+    }
+}
+
+// Database row structs
+#[derive(sqlx::FromRow)]
+struct ScheduledMessageRow {
+    scheduled_message_id: Uuid,
+    sender_id: Uuid,
+    recipient_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    group_id: Option<Uuid>,
+    content: String,
+    message_type: MessageType,
+    first_send_at: DateTime<Utc>,
+    next_send_at: Option<DateTime<Utc>>,
+    timezone: String,
+    recurrence: Option<serde_json::Value>,
+    status: ScheduledMessageStatus,
+    occurrence_count: i32,
+    attempt_count: i32,
+    last_sent_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl ScheduledMessageRow {
+    fn recurrence(&self) -> Option<MessageRecurrence> {
+        self.recurrence.clone().and_then(|value| serde_json::from_value(value).ok())
+    }
+
+    /// Next send time once `occurrence_count` sends have happened, or `None`
+    /// if the series is finished.
+    fn next_occurrence(&self, occurrence_count: i32) -> Option<DateTime<Utc>> {
+        let recurrence = self.recurrence()?;
+        let tz = self.timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+
+        if let Some(max) = recurrence.max_occurrences {
+            if occurrence_count as u32 >= max {
+                return None;
+            }
+        }
+
+        let previous = self.next_send_at.unwrap_or(self.first_send_at);
+        let mut next = next_occurrence(&recurrence, tz, self.first_send_at, previous)?;
+
+        // Skip occurrences missed while the worker was down rather than flooding the chat
+        while next <= Utc::now() {
+            next = next_occurrence(&recurrence, tz, self.first_send_at, next)?;
+        }
+
+        match recurrence.end_date {
+            Some(end_date) if next > end_date => None,
+            _ => Some(next),
+        }
+    }
+
+    fn into_response(self) -> ScheduledMessageResponse {
+        let recurrence = self.recurrence();
+
+        ScheduledMessageResponse {
+            scheduled_message_id: self.scheduled_message_id,
+            sender_id: self.sender_id,
+            recipient_id: self.recipient_id,
+            session_id: self.session_id,
+            group_id: self.group_id,
+            content: self.content,
+            message_type: self.message_type,
+            first_send_at: self.first_send_at,
+            next_send_at: self.next_send_at,
+            timezone: self.timezone,
+            recurrence,
+            status: self.status,
+            occurrence_count: self.occurrence_count,
+            last_sent_at: self.last_sent_at,
+            last_error: self.last_error,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn local(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn recurrence(frequency: MessageRecurrenceFrequency, interval: u32) -> MessageRecurrence {
+        MessageRecurrence {
+            frequency,
+            interval,
+            days_of_week: None,
+            end_date: None,
+            max_occurrences: None,
+        }
+    }
+
+    fn series(
+        recurrence: &MessageRecurrence,
+        tz: Tz,
+        first: DateTime<Utc>,
+        count: usize,
+    ) -> Vec<DateTime<Utc>> {
+        std::iter::successors(Some(first), |&previous| next_occurrence(recurrence, tz, first, previous))
+            .take(count)
+            .collect()
+    }
+
+    #[test]
+    fn test_local_to_utc_across_dst() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+
+        assert_eq!(local_to_utc(tz, local("2024-03-09 09:00")), utc("2024-03-09T14:00:00Z"));
+        assert_eq!(local_to_utc(tz, local("2024-03-10 09:00")), utc("2024-03-10T13:00:00Z"));
+        // 02:30 doesn't exist on 10 March; the clocks jump from 02:00 to 03:00
+        assert_eq!(local_to_utc(tz, local("2024-03-10 02:30")), utc("2024-03-10T07:30:00Z"));
+        // 01:30 happens twice on 3 November; the first one is used
+        assert_eq!(local_to_utc(tz, local("2024-11-03 01:30")), utc("2024-11-03T05:30:00Z"));
+    }
+
+    #[test]
+    fn test_daily_keeps_local_time_across_dst() {
+        let tz: Tz = "Europe/London".parse().unwrap();
+        let daily = recurrence(MessageRecurrenceFrequency::Daily, 1);
+
+        // 09:00 in London, with the clocks going forward on 31 March
+        assert_eq!(series(&daily, tz, utc("2024-03-30T09:00:00Z"), 3), vec![
+            utc("2024-03-30T09:00:00Z"),
+            utc("2024-03-31T08:00:00Z"),
+            utc("2024-04-01T08:00:00Z"),
+        ]);
+
+        let every_third_day = recurrence(MessageRecurrenceFrequency::Daily, 3);
+        assert_eq!(series(&every_third_day, tz, utc("2024-10-24T08:00:00Z"), 3), vec![
+            utc("2024-10-24T08:00:00Z"),
+            utc("2024-10-27T09:00:00Z"),
+            utc("2024-10-30T09:00:00Z"),
+        ]);
+    }
+
+    #[test]
+    fn test_weekly_days_and_interval() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let mut fortnightly = recurrence(MessageRecurrenceFrequency::Weekly, 2);
+        fortnightly.days_of_week = Some(vec![1, 3]);
+
+        // Monday 09:00 in New York; Monday and Wednesday every other week
+        assert_eq!(series(&fortnightly, tz, utc("2024-10-28T13:00:00Z"), 5), vec![
+            utc("2024-10-28T13:00:00Z"),
+            utc("2024-10-30T13:00:00Z"),
+            utc("2024-11-11T14:00:00Z"),
+            utc("2024-11-13T14:00:00Z"),
+            utc("2024-11-25T14:00:00Z"),
+        ]);
+
+        // Without days_of_week the anchor's weekday is used
+        let weekly = recurrence(MessageRecurrenceFrequency::Weekly, 1);
+        assert_eq!(series(&weekly, tz, utc("2024-10-31T13:00:00Z"), 2), vec![
+            utc("2024-10-31T13:00:00Z"),
+            utc("2024-11-07T14:00:00Z"),
+        ]);
+    }
+
+    #[test]
+    fn test_monthly_skips_months_without_the_day() {
+        let tz: Tz = "UTC".parse().unwrap();
+        let monthly = recurrence(MessageRecurrenceFrequency::Monthly, 1);
+
+        assert_eq!(series(&monthly, tz, utc("2024-01-31T10:00:00Z"), 4), vec![
+            utc("2024-01-31T10:00:00Z"),
+            utc("2024-03-31T10:00:00Z"),
+            utc("2024-05-31T10:00:00Z"),
+            utc("2024-07-31T10:00:00Z"),
+        ]);
+
+        // 29 February only comes round in leap years
+        let leap_day = recurrence(MessageRecurrenceFrequency::Monthly, 12);
+        assert_eq!(series(&leap_day, tz, utc("2024-02-29T10:00:00Z"), 2), vec![
+            utc("2024-02-29T10:00:00Z"),
+            utc("2028-02-29T10:00:00Z"),
+        ]);
+
+        let quarterly = recurrence(MessageRecurrenceFrequency::Monthly, 3);
+        assert_eq!(series(&quarterly, tz, utc("2024-11-30T10:00:00Z"), 3), vec![
+            utc("2024-11-30T10:00:00Z"),
+            utc("2025-05-30T10:00:00Z"),
+            utc("2025-08-30T10:00:00Z"),
+        ]);
+    }
+}
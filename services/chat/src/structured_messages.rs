@@ -0,0 +1,785 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use linkwithmentor_common::{AppError, MessageType};
+use crate::{
+    connection_manager::ConnectionManager,
+    message_service::MessageService,
+    models::{
+        ChecklistItemState, ChecklistState, CreateStructuredMessageRequest, NewChecklistItem,
+        PollOptionTally, PollState, ProposalAction, ProposalStatus, SessionProposalState,
+        StructuredMessageContent, StructuredMessageResponse, StructuredMessageState, WSMessage,
+    },
+    pubsub::ChatPubSub,
+};
+
+const MAX_POLL_OPTIONS: usize = 10;
+const MAX_POLL_OPTION_LENGTH: usize = 100;
+const MAX_QUESTION_LENGTH: usize = 300;
+const MAX_CHECKLIST_ITEMS: usize = 50;
+const MAX_CHECKLIST_ITEM_LENGTH: usize = 500;
+const MAX_TOPIC_LENGTH: usize = 200;
+const MAX_PROPOSAL_NOTES_LENGTH: usize = 1000;
+const MAX_CHECKLIST_TITLE_LENGTH: usize = 200;
+const MIN_PROPOSAL_MINUTES: u32 = 15;
+const MAX_PROPOSAL_MINUTES: u32 = 480;
+
+/// Polls, session proposals and checklists. The message itself goes through
+/// `MessageService::send_message_in` with a plain-text summary as its content;
+/// the interactive state lives in side tables keyed by `message_id`, written
+/// in the same transaction.
+#[derive(Clone)]
+pub struct StructuredMessageService {
+    db_pool: PgPool,
+    message_service: MessageService,
+    connection_manager: ConnectionManager,
+    pubsub: ChatPubSub,
+}
+
+impl StructuredMessageService {
+    pub fn new(
+        db_pool: PgPool,
+        message_service: MessageService,
+        connection_manager: ConnectionManager,
+        pubsub: ChatPubSub,
+    ) -> Self {
+        Self {
+            db_pool,
+            message_service,
+            connection_manager,
+            pubsub,
+        }
+    }
+
+    pub async fn create(
+        &self,
+        sender_id: Uuid,
+        request: CreateStructuredMessageRequest,
+    ) -> Result<StructuredMessageResponse, AppError> {
+        let targets = [request.recipient_id, request.session_id, request.group_id]
+            .iter()
+            .filter(|target| target.is_some())
+            .count();
+        if targets != 1 {
+            return Err(AppError::BadRequest(
+                "Exactly one of recipient_id, session_id or group_id is required".to_string()
+            ));
+        }
+
+        validate_content(&request.content)?;
+
+        let (summary, message_type) = summarize(&request.content);
+
+        // The card and its state are stored together, so neither exists without the other
+        let mut tx = self.begin().await?;
+        let message = self.message_service
+            .send_message_in(
+                &mut tx,
+                sender_id,
+                summary.clone(),
+                request.recipient_id,
+                request.session_id,
+                request.group_id,
+                message_type.clone(),
+            )
+            .await?;
+        self.store_state(&mut tx, message.message_id, sender_id, &request.content).await?;
+        self.commit(tx).await?;
+
+        self.message_service.after_send(&message).await?;
+
+        self.pubsub
+            .publish_chat_message(
+                message.message_id,
+                sender_id,
+                request.recipient_id,
+                request.session_id,
+                request.group_id,
+                summary,
+                message_type,
+            )
+            .await?;
+
+        let state = self.load_state(message.message_id).await?;
+
+        Ok(StructuredMessageResponse { message, state })
+    }
+
+    pub async fn get_state(&self, message_id: Uuid, user_id: Uuid) -> Result<StructuredMessageState, AppError> {
+        let routing = self.get_routing(message_id).await?;
+        self.ensure_participant(user_id, &routing).await?;
+
+        self.load_state(message_id).await
+    }
+
+    /// Replaces the caller's vote. An empty `option_ids` retracts it.
+    pub async fn cast_vote(
+        &self,
+        message_id: Uuid,
+        user_id: Uuid,
+        option_ids: Vec<Uuid>,
+    ) -> Result<StructuredMessageState, AppError> {
+        let routing = self.get_routing(message_id).await?;
+        self.ensure_participant(user_id, &routing).await?;
+
+        let mut tx = self.begin().await?;
+
+        let poll = sqlx::query_as::<_, PollRow>(
+            "SELECT question, allow_multiple, closes_at, is_closed FROM message_polls WHERE message_id = $1 FOR UPDATE"
+        )
+        .bind(message_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch poll: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("Poll not found".to_string()))?;
+
+        if poll.is_closed || poll.closes_at.map_or(false, |closes_at| closes_at <= Utc::now()) {
+            return Err(AppError::BadRequest("This poll is closed".to_string()));
+        }
+
+        let unique: HashSet<Uuid> = option_ids.iter().copied().collect();
+        if unique.len() != option_ids.len() {
+            return Err(AppError::BadRequest("Duplicate options in vote".to_string()));
+        }
+        if !poll.allow_multiple && option_ids.len() > 1 {
+            return Err(AppError::BadRequest("This poll allows only one choice".to_string()));
+        }
+
+        let valid_options: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT option_id FROM message_poll_options WHERE message_id = $1"
+        )
+        .bind(message_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch poll options: {}", e)))?;
+
+        if option_ids.iter().any(|option_id| !valid_options.contains(option_id)) {
+            return Err(AppError::BadRequest("Option does not belong to this poll".to_string()));
+        }
+
+        sqlx::query("DELETE FROM message_poll_votes WHERE message_id = $1 AND user_id = $2")
+            .bind(message_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to clear vote: {}", e)))?;
+
+        let voted_at = Utc::now();
+        for option_id in &option_ids {
+            sqlx::query(
+                "INSERT INTO message_poll_votes (message_id, option_id, user_id, voted_at) VALUES ($1, $2, $3, $4)"
+            )
+            .bind(message_id)
+            .bind(option_id)
+            .bind(user_id)
+            .bind(voted_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record vote: {}", e)))?;
+        }
+
+        self.commit(tx).await?;
+
+        self.broadcast_state(message_id, &routing).await
+    }
+
+    pub async fn close_poll(&self, message_id: Uuid, user_id: Uuid) -> Result<StructuredMessageState, AppError> {
+        let routing = self.get_routing(message_id).await?;
+        if routing.sender_id != user_id {
+            return Err(AppError::Forbidden("Only the poll creator can close it".to_string()));
+        }
+
+        let result = sqlx::query("UPDATE message_polls SET is_closed = true WHERE message_id = $1 AND is_closed = false")
+            .bind(message_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to close poll: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::BadRequest("Poll is already closed".to_string()));
+        }
+
+        self.broadcast_state(message_id, &routing).await
+    }
+
+    pub async fn respond_to_proposal(
+        &self,
+        message_id: Uuid,
+        user_id: Uuid,
+        action: ProposalAction,
+    ) -> Result<StructuredMessageState, AppError> {
+        let routing = self.get_routing(message_id).await?;
+        self.ensure_participant(user_id, &routing).await?;
+
+        let mut tx = self.begin().await?;
+
+        let proposal = sqlx::query_as::<_, SessionProposalRow>(&format!("{} FOR UPDATE", SELECT_PROPOSAL))
+            .bind(message_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch proposal: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Session proposal not found".to_string()))?;
+
+        if proposal.status != ProposalStatus::Pending {
+            return Err(AppError::Conflict(format!("Proposal is already {:?}", proposal.status)));
+        }
+
+        let now = Utc::now();
+        let is_proposer = proposal.proposed_by == user_id;
+        let status = match action {
+            ProposalAction::Withdraw if is_proposer => ProposalStatus::Withdrawn,
+            ProposalAction::Withdraw => {
+                return Err(AppError::Forbidden("Only the proposer can withdraw a proposal".to_string()));
+            }
+            _ if is_proposer => {
+                return Err(AppError::BadRequest("You cannot respond to your own proposal".to_string()));
+            }
+            _ if proposal.start_time <= now => {
+                return Err(AppError::BadRequest("This proposal has expired".to_string()));
+            }
+            ProposalAction::Accept => ProposalStatus::Accepted,
+            ProposalAction::Decline => ProposalStatus::Declined,
+        };
+
+        sqlx::query(
+            "UPDATE session_proposals SET status = $1, responded_by = $2, responded_at = $3 WHERE message_id = $4"
+        )
+        .bind(&status)
+        .bind(user_id)
+        .bind(now)
+        .bind(message_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update proposal: {}", e)))?;
+
+        self.commit(tx).await?;
+
+        self.broadcast_state(message_id, &routing).await
+    }
+
+    pub async fn update_checklist_item(
+        &self,
+        message_id: Uuid,
+        item_id: Uuid,
+        user_id: Uuid,
+        is_completed: bool,
+    ) -> Result<StructuredMessageState, AppError> {
+        let routing = self.get_routing(message_id).await?;
+        self.ensure_participant(user_id, &routing).await?;
+
+        let (completed_by, completed_at) = if is_completed {
+            (Some(user_id), Some(Utc::now()))
+        } else {
+            (None, None)
+        };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE checklist_items
+            SET is_completed = $1, completed_by = $2, completed_at = $3
+            WHERE message_id = $4 AND item_id = $5
+            "#
+        )
+        .bind(is_completed)
+        .bind(completed_by)
+        .bind(completed_at)
+        .bind(message_id)
+        .bind(item_id)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update checklist item: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Checklist item not found".to_string()));
+        }
+
+        self.broadcast_state(message_id, &routing).await
+    }
+
+    async fn store_state(
+        &self,
+        conn: &mut PgConnection,
+        message_id: Uuid,
+        sender_id: Uuid,
+        content: &StructuredMessageContent,
+    ) -> Result<(), AppError> {
+
+        match content {
+            StructuredMessageContent::Poll { question, options, allow_multiple, closes_at } => {
+                sqlx::query(
+                    "INSERT INTO message_polls (message_id, question, allow_multiple, closes_at) VALUES ($1, $2, $3, $4)"
+                )
+                .bind(message_id)
+                .bind(question.trim())
+                .bind(allow_multiple)
+                .bind(closes_at)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to store poll: {}", e)))?;
+
+                for (position, label) in options.iter().enumerate() {
+                    sqlx::query(
+                        "INSERT INTO message_poll_options (option_id, message_id, position, label) VALUES ($1, $2, $3, $4)"
+                    )
+                    .bind(Uuid::new_v4())
+                    .bind(message_id)
+                    .bind(position as i32)
+                    .bind(label.trim())
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to store poll option: {}", e)))?;
+                }
+            }
+            StructuredMessageContent::SessionProposal { topic, start_time, duration_minutes, notes } => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO session_proposals (
+                        message_id, proposed_by, topic, start_time, duration_minutes, notes, status
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    "#
+                )
+                .bind(message_id)
+                .bind(sender_id)
+                .bind(topic.trim())
+                .bind(start_time)
+                .bind(*duration_minutes as i32)
+                .bind(notes)
+                .bind(ProposalStatus::Pending)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to store session proposal: {}", e)))?;
+            }
+            StructuredMessageContent::Checklist { title, items } => {
+                sqlx::query("INSERT INTO message_checklists (message_id, title) VALUES ($1, $2)")
+                    .bind(message_id)
+                    .bind(title.trim())
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to store checklist: {}", e)))?;
+
+                for (position, item) in items.iter().enumerate() {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO checklist_items (item_id, message_id, position, text, assignee_id)
+                        VALUES ($1, $2, $3, $4, $5)
+                        "#
+                    )
+                    .bind(Uuid::new_v4())
+                    .bind(message_id)
+                    .bind(position as i32)
+                    .bind(item.text.trim())
+                    .bind(item.assignee_id)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to store checklist item: {}", e)))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn load_state(&self, message_id: Uuid) -> Result<StructuredMessageState, AppError> {
+        let message_type: MessageType = sqlx::query_scalar("SELECT message_type FROM messages WHERE message_id = $1")
+            .bind(message_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch message: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+        match message_type {
+            MessageType::Poll => self.load_poll(message_id).await.map(StructuredMessageState::Poll),
+            MessageType::SessionProposal => {
+                self.load_proposal(message_id).await.map(StructuredMessageState::SessionProposal)
+            }
+            MessageType::Checklist => self.load_checklist(message_id).await.map(StructuredMessageState::Checklist),
+            _ => Err(AppError::BadRequest("Message is not an interactive message".to_string())),
+        }
+    }
+
+    async fn load_poll(&self, message_id: Uuid) -> Result<PollState, AppError> {
+        let poll = sqlx::query_as::<_, PollRow>(
+            "SELECT question, allow_multiple, closes_at, is_closed FROM message_polls WHERE message_id = $1"
+        )
+        .bind(message_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch poll: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("Poll not found".to_string()))?;
+
+        let options = sqlx::query_as::<_, PollOptionRow>(
+            "SELECT option_id, label FROM message_poll_options WHERE message_id = $1 ORDER BY position"
+        )
+        .bind(message_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch poll options: {}", e)))?;
+
+        let votes = sqlx::query_as::<_, PollVoteRow>(
+            "SELECT option_id, user_id FROM message_poll_votes WHERE message_id = $1 ORDER BY voted_at"
+        )
+        .bind(message_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch poll votes: {}", e)))?;
+
+        let total_voters = votes.iter().map(|vote| vote.user_id).collect::<HashSet<_>>().len() as i64;
+        let mut voters_by_option: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for vote in votes {
+            voters_by_option.entry(vote.option_id).or_default().push(vote.user_id);
+        }
+
+        let options = options.into_iter()
+            .map(|option| {
+                let voter_ids = voters_by_option.remove(&option.option_id).unwrap_or_default();
+                PollOptionTally {
+                    option_id: option.option_id,
+                    label: option.label,
+                    vote_count: voter_ids.len() as i64,
+                    voter_ids,
+                }
+            })
+            .collect();
+
+        Ok(PollState {
+            question: poll.question,
+            allow_multiple: poll.allow_multiple,
+            closes_at: poll.closes_at,
+            is_closed: poll.is_closed || poll.closes_at.map_or(false, |closes_at| closes_at <= Utc::now()),
+            total_voters,
+            options,
+        })
+    }
+
+    async fn load_proposal(&self, message_id: Uuid) -> Result<SessionProposalState, AppError> {
+        let row = sqlx::query_as::<_, SessionProposalRow>(SELECT_PROPOSAL)
+            .bind(message_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch proposal: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Session proposal not found".to_string()))?;
+
+        // Pending proposals lapse once their start time has passed
+        let status = if row.status == ProposalStatus::Pending && row.start_time <= Utc::now() {
+            ProposalStatus::Expired
+        } else {
+            row.status
+        };
+
+        Ok(SessionProposalState {
+            proposed_by: row.proposed_by,
+            topic: row.topic,
+            start_time: row.start_time,
+            duration_minutes: row.duration_minutes,
+            notes: row.notes,
+            status,
+            responded_by: row.responded_by,
+            responded_at: row.responded_at,
+        })
+    }
+
+    async fn load_checklist(&self, message_id: Uuid) -> Result<ChecklistState, AppError> {
+        let title: String = sqlx::query_scalar("SELECT title FROM message_checklists WHERE message_id = $1")
+            .bind(message_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch checklist: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Checklist not found".to_string()))?;
+
+        let items = sqlx::query_as::<_, ChecklistItemRow>(
+            r#"
+            SELECT item_id, text, assignee_id, is_completed, completed_by, completed_at
+            FROM checklist_items
+            WHERE message_id = $1
+            ORDER BY position
+            "#
+        )
+        .bind(message_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch checklist items: {}", e)))?;
+
+        let items: Vec<ChecklistItemState> = items.into_iter()
+            .map(|row| ChecklistItemState {
+                item_id: row.item_id,
+                text: row.text,
+                assignee_id: row.assignee_id,
+                is_completed: row.is_completed,
+                completed_by: row.completed_by,
+                completed_at: row.completed_at,
+            })
+            .collect();
+
+        Ok(ChecklistState {
+            title,
+            completed_count: items.iter().filter(|item| item.is_completed).count() as i64,
+            items,
+        })
+    }
+
+    /// Pushes the latest state to everyone in the conversation so tallies
+    /// and checkmarks update live.
+    async fn broadcast_state(
+        &self,
+        message_id: Uuid,
+        routing: &MessageRoutingRow,
+    ) -> Result<StructuredMessageState, AppError> {
+        let state = self.load_state(message_id).await?;
+
+        let update = WSMessage::StructuredMessageUpdated {
+            message_id,
+            state: state.clone(),
+        };
+        if let Err(e) = self.connection_manager
+            .send_to_conversation(routing.sender_id, routing.recipient_id, routing.session_id, routing.group_id, update)
+            .await
+        {
+            tracing::warn!("Failed to broadcast structured message update: {}", e);
+        }
+
+        Ok(state)
+    }
+
+    async fn get_routing(&self, message_id: Uuid) -> Result<MessageRoutingRow, AppError> {
+        let row = sqlx::query_as::<_, MessageRoutingRow>(
+            "SELECT sender_id, recipient_id, session_id, group_id, is_deleted FROM messages WHERE message_id = $1"
+        )
+        .bind(message_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch message: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+        if row.is_deleted {
+            return Err(AppError::NotFound("Message has been deleted".to_string()));
+        }
+
+        Ok(row)
+    }
+
+    async fn ensure_participant(&self, user_id: Uuid, routing: &MessageRoutingRow) -> Result<(), AppError> {
+        let is_participant = if let Some(recipient_id) = routing.recipient_id {
+            user_id == routing.sender_id || user_id == recipient_id
+        } else if let Some(session_id) = routing.session_id {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM mentorship_sessions WHERE session_id = $1 AND (mentor_id = $2 OR mentee_id = $2))"
+            )
+            .bind(session_id)
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check session access: {}", e)))?
+        } else if let Some(group_id) = routing.group_id {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM group_chat_participants WHERE group_id = $1 AND user_id = $2 AND left_at IS NULL)"
+            )
+            .bind(group_id)
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check group access: {}", e)))?
+        } else {
+            false
+        };
+
+        if !is_participant {
+            return Err(AppError::Forbidden("You are not a participant in this conversation".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>, AppError> {
+        self.db_pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))
+    }
+
+    async fn commit(&self, tx: Transaction<'static, Postgres>) -> Result<(), AppError> {
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))
+    }
+}
+
+/// Structured types carry side-table state and can only be created through
+/// `StructuredMessageService::create`.
+pub fn is_structured(message_type: &MessageType) -> bool {
+    matches!(message_type, MessageType::Poll | MessageType::SessionProposal | MessageType::Checklist)
+}
+
+fn validate_content(content: &StructuredMessageContent) -> Result<(), AppError> {
+    match content {
+        StructuredMessageContent::Poll { question, options, closes_at, .. } => {
+            if question.trim().is_empty() || question.chars().count() > MAX_QUESTION_LENGTH {
+                return Err(AppError::BadRequest(format!(
+                    "Poll question must be 1-{} characters",
+                    MAX_QUESTION_LENGTH
+                )));
+            }
+
+            if options.len() < 2 || options.len() > MAX_POLL_OPTIONS {
+                return Err(AppError::BadRequest(format!(
+                    "A poll needs between 2 and {} options",
+                    MAX_POLL_OPTIONS
+                )));
+            }
+
+            let mut seen = HashSet::new();
+            for option in options {
+                let label = option.trim();
+                if label.is_empty() || label.chars().count() > MAX_POLL_OPTION_LENGTH {
+                    return Err(AppError::BadRequest(format!(
+                        "Poll options must be 1-{} characters",
+                        MAX_POLL_OPTION_LENGTH
+                    )));
+                }
+                if !seen.insert(label.to_lowercase()) {
+                    return Err(AppError::BadRequest(format!("Duplicate poll option: {}", label)));
+                }
+            }
+
+            if closes_at.map_or(false, |closes_at| closes_at <= Utc::now()) {
+                return Err(AppError::BadRequest("Poll closing time must be in the future".to_string()));
+            }
+        }
+        StructuredMessageContent::SessionProposal { topic, start_time, duration_minutes, notes } => {
+            if topic.trim().is_empty() || topic.chars().count() > MAX_TOPIC_LENGTH {
+                return Err(AppError::BadRequest(format!(
+                    "Session topic must be 1-{} characters",
+                    MAX_TOPIC_LENGTH
+                )));
+            }
+
+            if notes.as_ref().map_or(false, |notes| notes.chars().count() > MAX_PROPOSAL_NOTES_LENGTH) {
+                return Err(AppError::BadRequest(format!(
+                    "Session notes can be at most {} characters",
+                    MAX_PROPOSAL_NOTES_LENGTH
+                )));
+            }
+
+            if *start_time <= Utc::now() + Duration::minutes(5) {
+                return Err(AppError::BadRequest("Proposed start time must be in the future".to_string()));
+            }
+
+            if *duration_minutes < MIN_PROPOSAL_MINUTES || *duration_minutes > MAX_PROPOSAL_MINUTES {
+                return Err(AppError::BadRequest(format!(
+                    "Session duration must be between {} and {} minutes",
+                    MIN_PROPOSAL_MINUTES, MAX_PROPOSAL_MINUTES
+                )));
+            }
+        }
+        StructuredMessageContent::Checklist { title, items } => {
+            if title.trim().is_empty() || title.chars().count() > MAX_CHECKLIST_TITLE_LENGTH {
+                return Err(AppError::BadRequest(format!(
+                    "Checklist title must be 1-{} characters",
+                    MAX_CHECKLIST_TITLE_LENGTH
+                )));
+            }
+
+            if items.is_empty() || items.len() > MAX_CHECKLIST_ITEMS {
+                return Err(AppError::BadRequest(format!(
+                    "A checklist needs between 1 and {} items",
+                    MAX_CHECKLIST_ITEMS
+                )));
+            }
+
+            if items.iter().any(|item: &NewChecklistItem| {
+                item.text.trim().is_empty() || item.text.chars().count() > MAX_CHECKLIST_ITEM_LENGTH
+            }) {
+                return Err(AppError::BadRequest(format!(
+                    "Checklist items must be 1-{} characters",
+                    MAX_CHECKLIST_ITEM_LENGTH
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Plain-text fallback stored as the message content, used by clients that
+/// don't render cards, search and exports.
+fn summarize(content: &StructuredMessageContent) -> (String, MessageType) {
+    match content {
+        StructuredMessageContent::Poll { question, options, .. } => (
+            format!("Poll: {} ({})", question.trim(), options.iter().map(|o| o.trim()).collect::<Vec<_>>().join(" / ")),
+            MessageType::Poll,
+        ),
+        StructuredMessageContent::SessionProposal { topic, start_time, duration_minutes, .. } => (
+            format!(
+                "Session proposal: {} on {} ({} minutes)",
+                topic.trim(),
+                start_time.format("%Y-%m-%d %H:%M UTC"),
+                duration_minutes
+            ),
+            MessageType::SessionProposal,
+        ),
+        StructuredMessageContent::Checklist { title, items } => (
+            format!("Checklist: {} ({} items)", title.trim(), items.len()),
+            MessageType::Checklist,
+        ),
+    }
+}
+
+const SELECT_PROPOSAL: &str = r#"
+    SELECT proposed_by, topic, start_time, duration_minutes, notes, status, responded_by, responded_at
+    FROM session_proposals
+    WHERE message_id = $1
+"#;
+
+// Database row structs
+#[derive(sqlx::FromRow)]
+struct MessageRoutingRow {
+    sender_id: Uuid,
+    recipient_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    group_id: Option<Uuid>,
+    is_deleted: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct PollRow {
+    question: String,
+    allow_multiple: bool,
+    closes_at: Option<DateTime<Utc>>,
+    is_closed: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct PollOptionRow {
+    option_id: Uuid,
+    label: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct PollVoteRow {
+    option_id: Uuid,
+    user_id: Uuid,
+}
+
+#[derive(sqlx::FromRow)]
+struct SessionProposalRow {
+    proposed_by: Uuid,
+    topic: String,
+    start_time: DateTime<Utc>,
+    duration_minutes: i32,
+    notes: Option<String>,
+    status: ProposalStatus,
+    responded_by: Option<Uuid>,
+    responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ChecklistItemRow {
+    item_id: Uuid,
+    text: String,
+    assignee_id: Option<Uuid>,
+    is_completed: bool,
+    completed_by: Option<Uuid>,
+    completed_at: Option<DateTime<Utc>>,
+}
@@ -13,9 +13,11 @@ use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use linkwithmentor_auth::Claims;
-use linkwithmentor_common::AppError;
+use linkwithmentor_common::{AppError, MessageType};
 
 use crate::{
+    commands::{self, CommandContext},
+    structured_messages,
     models::{WSMessage, ChatRoomType},
     AppState,
 };
@@ -76,6 +78,11 @@ async fn handle_websocket(
         return;
     }
 
+    // Register this device in cluster-wide presence
+    if let Err(e) = state.presence_service.heartbeat(user_id, &username, &connection_id).await {
+        tracing::warn!("Failed to record presence for {}: {}", username, e);
+    }
+
     // Join session or group room if specified
     if let Some(session_id) = params.session_id {
         let room_id = format!("session_{}", session_id);
@@ -109,7 +116,7 @@ async fn handle_websocket(
 
     // Handle incoming messages
     let mut last_heartbeat = tokio::time::Instant::now();
-    let heartbeat_interval = tokio::time::Duration::from_secs(30);
+    let heartbeat_interval = tokio::time::Duration::from_secs(state.config.websocket.heartbeat_interval_seconds);
     let mut heartbeat_timer = tokio::time::interval(heartbeat_interval);
 
     loop {
@@ -180,10 +187,15 @@ async fn handle_websocket(
                 }
                 
                 // Check if client is responsive
-                if last_heartbeat.elapsed() > tokio::time::Duration::from_secs(60) {
+                if last_heartbeat.elapsed() > heartbeat_interval * 2 {
                     tracing::warn!("Client {} not responding to heartbeat, closing connection", username);
                     break;
                 }
+
+                // Keep this device's presence entry alive
+                if let Err(e) = state.presence_service.heartbeat(user_id, &username, &connection_id).await {
+                    tracing::warn!("Failed to refresh presence for {}: {}", username, e);
+                }
             }
         }
     }
@@ -193,6 +205,10 @@ async fn handle_websocket(
         .remove_connection(&connection_id)
         .await;
 
+    if let Err(e) = state.presence_service.disconnect(user_id, &username, &connection_id).await {
+        tracing::warn!("Failed to clear presence for {}: {}", username, e);
+    }
+
     tracing::info!("WebSocket connection closed for user: {}", username);
 }
 
@@ -219,6 +235,41 @@ async fn handle_text_message(
                 return Err(AppError::BadRequest("Message too long".to_string()));
             }
 
+            // Slash commands are handled by the command registry instead of being stored.
+            // Ciphertext is never parsed: base64 may legitimately start with '/'.
+            let invocation = match message_type {
+                MessageType::Encrypted => None,
+                _ => commands::parse_command(&content),
+            };
+            if let Some(invocation) = invocation {
+                let ctx = CommandContext {
+                    user_id,
+                    username: username.to_string(),
+                    recipient_id,
+                    session_id,
+                    group_id,
+                    auth_token: Some(params.token.clone()),
+                };
+
+                state.command_registry
+                    .execute_and_deliver(&state.connection_manager, &ctx, &invocation)
+                    .await?;
+                return Ok(());
+            }
+            let content = match message_type {
+                MessageType::Encrypted => content,
+                _ => commands::unescape_slash(content),
+            };
+
+            if structured_messages::is_structured(&message_type) {
+                return Err(AppError::BadRequest(
+                    "Polls, proposals and checklists must be created via /messages/structured".to_string()
+                ));
+            }
+            if matches!(message_type, MessageType::System) {
+                return Err(AppError::BadRequest("System messages can only be sent by the platform".to_string()));
+            }
+
             // Send message through message service
             let message_response = state.message_service
                 .send_message(
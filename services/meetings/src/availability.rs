@@ -0,0 +1,290 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use linkwithmentor_common::AppError;
+
+// Longest forward jump in the tz database is two hours; leave some headroom
+const MAX_GAP_MINUTES: i64 = 180;
+
+/// A weekly availability window in the owner's local time. Windows whose end
+/// is not after their start run past midnight into the next day.
+#[derive(Debug, Clone)]
+pub struct WeeklyRule {
+    pub day_of_week: u8, // 0=Sunday, 1=Monday, etc.
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub timezone: Tz,
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz, AppError> {
+    name.parse()
+        .map_err(|_| AppError::BadRequest(format!("Unknown timezone: {}", name)))
+}
+
+/// The instant shown in `timezone`, with the offset in effect at that moment.
+pub fn localize(instant: DateTime<Utc>, timezone: Tz) -> DateTime<FixedOffset> {
+    let local = instant.with_timezone(&timezone);
+    local.with_timezone(&local.offset().fix())
+}
+
+/// Expands weekly rules into concrete windows within the range, sorted and
+/// merged. Each occurrence keeps its local wall-clock times, so the UTC
+/// instants shift when the rule's timezone changes offset.
+pub fn expand_weekly_rules(
+    rules: &[WeeklyRule],
+    range_start: DateTime<Utc>,
+    range_end: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut windows = Vec::new();
+
+    for rule in rules {
+        // A day either side covers the offset and windows running past midnight
+        let mut date = range_start.with_timezone(&rule.timezone).date_naive() - Duration::days(1);
+        let last = range_end.with_timezone(&rule.timezone).date_naive() + Duration::days(1);
+
+        while date <= last {
+            if date.weekday().num_days_from_sunday() == rule.day_of_week as u32 {
+                let end_date = if rule.end_time <= rule.start_time { date + Duration::days(1) } else { date };
+                let start = resolve(rule.timezone, date.and_time(rule.start_time), false).max(range_start);
+                let end = resolve(rule.timezone, end_date.and_time(rule.end_time), true).min(range_end);
+
+                if start < end {
+                    windows.push((start, end));
+                }
+            }
+            date += Duration::days(1);
+        }
+    }
+
+    merge(windows)
+}
+
+/// Slots of `duration`, starting every `step` from the start of each window,
+/// that fit inside the window and don't overlap any busy period.
+pub fn bookable_slots(
+    windows: &[(DateTime<Utc>, DateTime<Utc>)],
+    duration: Duration,
+    step: Duration,
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut slots = Vec::new();
+
+    for (window_start, window_end) in windows {
+        let mut start = *window_start;
+        while start + duration <= *window_end {
+            let end = start + duration;
+            if !busy.iter().any(|(busy_start, busy_end)| start < *busy_end && end > *busy_start) {
+                slots.push((start, end));
+            }
+            start += step;
+        }
+    }
+
+    slots
+}
+
+// Local times skipped by a forward jump start when the clocks jump; repeated
+// ones take the first pass for a start and the second for an end, so the
+// window covers the whole repeated stretch
+fn resolve(timezone: Tz, local: NaiveDateTime, end: bool) -> DateTime<Utc> {
+    let instant = match timezone.from_local_datetime(&local) {
+        LocalResult::Single(instant) => instant,
+        LocalResult::Ambiguous(earliest, latest) => if end { latest } else { earliest },
+        LocalResult::None => (1..=MAX_GAP_MINUTES)
+            .find_map(|minutes| timezone.from_local_datetime(&(local + Duration::minutes(minutes))).earliest())
+            .unwrap_or_else(|| timezone.from_utc_datetime(&local)),
+    };
+
+    instant.with_timezone(&Utc)
+}
+
+fn merge(mut windows: Vec<(DateTime<Utc>, DateTime<Utc>)>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    windows.sort();
+
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::with_capacity(windows.len());
+    for (start, end) in windows {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn rule(day_of_week: u8, start: &str, end: &str, timezone: &str) -> WeeklyRule {
+        WeeklyRule {
+            day_of_week,
+            start_time: NaiveTime::parse_from_str(start, "%H:%M").unwrap(),
+            end_time: NaiveTime::parse_from_str(end, "%H:%M").unwrap(),
+            timezone: parse_timezone(timezone).unwrap(),
+        }
+    }
+
+    fn window(start: &str, end: &str) -> (DateTime<Utc>, DateTime<Utc>) {
+        (utc(start), utc(end))
+    }
+
+    #[test]
+    fn test_new_york_keeps_local_time_across_spring_forward() {
+        let windows = expand_weekly_rules(
+            &[rule(0, "09:00", "10:00", "America/New_York")],
+            utc("2024-03-01T00:00:00Z"),
+            utc("2024-03-16T00:00:00Z"),
+        );
+
+        assert_eq!(windows, vec![
+            window("2024-03-03T14:00:00Z", "2024-03-03T15:00:00Z"),
+            window("2024-03-10T13:00:00Z", "2024-03-10T14:00:00Z"),
+        ]);
+    }
+
+    #[test]
+    fn test_new_york_skipped_hour() {
+        let range = (utc("2024-03-09T00:00:00Z"), utc("2024-03-12T00:00:00Z"));
+
+        // 02:00-03:00 doesn't exist on the day the clocks go forward
+        let skipped = expand_weekly_rules(&[rule(0, "02:00", "03:00", "America/New_York")], range.0, range.1);
+        assert!(skipped.is_empty());
+
+        // Only the half hour before the jump and the half hour after remain
+        let spanning = expand_weekly_rules(&[rule(0, "01:30", "03:30", "America/New_York")], range.0, range.1);
+        assert_eq!(spanning, vec![window("2024-03-10T06:30:00Z", "2024-03-10T07:30:00Z")]);
+    }
+
+    #[test]
+    fn test_new_york_repeated_hour() {
+        let range = (utc("2024-11-02T00:00:00Z"), utc("2024-11-05T00:00:00Z"));
+
+        let windows = expand_weekly_rules(&[rule(0, "01:00", "02:00", "America/New_York")], range.0, range.1);
+        assert_eq!(windows, vec![window("2024-11-03T05:00:00Z", "2024-11-03T07:00:00Z")]);
+
+        let windows = expand_weekly_rules(&[rule(0, "00:30", "01:30", "America/New_York")], range.0, range.1);
+        assert_eq!(windows, vec![window("2024-11-03T04:30:00Z", "2024-11-03T06:30:00Z")]);
+    }
+
+    #[test]
+    fn test_london_mentor_seen_from_new_york_between_transitions() {
+        // The US moves on 10 March, the UK on 31 March
+        let windows = expand_weekly_rules(
+            &[rule(1, "09:00", "10:00", "Europe/London")],
+            utc("2024-03-01T00:00:00Z"),
+            utc("2024-04-05T00:00:00Z"),
+        );
+        let viewer = parse_timezone("America/New_York").unwrap();
+
+        let local: Vec<String> = windows.iter()
+            .map(|(start, _)| localize(*start, viewer).to_rfc3339())
+            .collect();
+
+        assert_eq!(local, vec![
+            "2024-03-04T04:00:00-05:00",
+            "2024-03-11T05:00:00-04:00",
+            "2024-03-18T05:00:00-04:00",
+            "2024-03-25T05:00:00-04:00",
+            "2024-04-01T04:00:00-04:00",
+        ]);
+    }
+
+    #[test]
+    fn test_sydney_southern_hemisphere_transitions() {
+        let rules = [rule(1, "18:00", "19:00", "Australia/Sydney")];
+
+        // Daylight saving ends on 7 April
+        let autumn = expand_weekly_rules(&rules, utc("2024-03-31T00:00:00Z"), utc("2024-04-10T00:00:00Z"));
+        assert_eq!(autumn, vec![
+            window("2024-04-01T07:00:00Z", "2024-04-01T08:00:00Z"),
+            window("2024-04-08T08:00:00Z", "2024-04-08T09:00:00Z"),
+        ]);
+
+        // and starts again on 6 October
+        let spring = expand_weekly_rules(&rules, utc("2024-09-29T00:00:00Z"), utc("2024-10-09T00:00:00Z"));
+        assert_eq!(spring, vec![
+            window("2024-09-30T08:00:00Z", "2024-09-30T09:00:00Z"),
+            window("2024-10-07T07:00:00Z", "2024-10-07T08:00:00Z"),
+        ]);
+    }
+
+    #[test]
+    fn test_lord_howe_half_hour_shift() {
+        // Clocks move 02:00 -> 02:30 on 6 October and 02:00 -> 01:30 on 7 April
+        let spring = expand_weekly_rules(
+            &[rule(0, "02:00", "03:00", "Australia/Lord_Howe")],
+            utc("2024-10-04T00:00:00Z"),
+            utc("2024-10-08T00:00:00Z"),
+        );
+        assert_eq!(spring, vec![window("2024-10-05T15:30:00Z", "2024-10-05T16:00:00Z")]);
+
+        let autumn = expand_weekly_rules(
+            &[rule(0, "01:00", "02:00", "Australia/Lord_Howe")],
+            utc("2024-04-05T00:00:00Z"),
+            utc("2024-04-09T00:00:00Z"),
+        );
+        assert_eq!(autumn, vec![window("2024-04-06T14:00:00Z", "2024-04-06T15:30:00Z")]);
+    }
+
+    #[test]
+    fn test_zones_without_daylight_saving_stay_fixed() {
+        let rules = [
+            rule(3, "10:00", "11:00", "Asia/Kathmandu"),
+            rule(3, "12:00", "13:00", "Asia/Kolkata"),
+        ];
+
+        let windows = expand_weekly_rules(&rules, utc("2024-03-05T00:00:00Z"), utc("2024-03-14T00:00:00Z"));
+        assert_eq!(windows, vec![
+            window("2024-03-06T04:15:00Z", "2024-03-06T05:15:00Z"),
+            window("2024-03-06T06:30:00Z", "2024-03-06T07:30:00Z"),
+            window("2024-03-13T04:15:00Z", "2024-03-13T05:15:00Z"),
+            window("2024-03-13T06:30:00Z", "2024-03-13T07:30:00Z"),
+        ]);
+    }
+
+    #[test]
+    fn test_window_past_midnight_and_range_clipping() {
+        // Friday 22:00 to Saturday 01:00 in Tokyo, range starting mid-window
+        let windows = expand_weekly_rules(
+            &[rule(5, "22:00", "01:00", "Asia/Tokyo")],
+            utc("2024-03-08T14:00:00Z"),
+            utc("2024-03-10T00:00:00Z"),
+        );
+
+        assert_eq!(windows, vec![window("2024-03-08T14:00:00Z", "2024-03-08T16:00:00Z")]);
+    }
+
+    #[test]
+    fn test_overlapping_rules_merge() {
+        let windows = expand_weekly_rules(
+            &[rule(2, "09:00", "11:00", "Europe/Berlin"), rule(2, "10:00", "12:00", "Europe/Berlin")],
+            utc("2024-07-01T00:00:00Z"),
+            utc("2024-07-03T00:00:00Z"),
+        );
+
+        assert_eq!(windows, vec![window("2024-07-02T07:00:00Z", "2024-07-02T10:00:00Z")]);
+    }
+
+    #[test]
+    fn test_bookable_slots_skip_busy_times() {
+        let slots = bookable_slots(
+            &[window("2024-07-02T09:00:00Z", "2024-07-02T11:00:00Z")],
+            Duration::minutes(60),
+            Duration::minutes(30),
+            &[window("2024-07-02T09:45:00Z", "2024-07-02T10:00:00Z")],
+        );
+
+        assert_eq!(slots, vec![window("2024-07-02T10:00:00Z", "2024-07-02T11:00:00Z")]);
+    }
+
+    #[test]
+    fn test_unknown_timezone_is_rejected() {
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+        assert!(parse_timezone("America/Sao_Paulo").is_ok());
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+use crate::models::{BookingRuleOverrides, BookingRules, SessionType, SlotUnavailableReason};
+
+// Platform limits on any session, whatever a mentor's policy allows
+pub const MIN_SESSION_MINUTES: u32 = 15;
+pub const MAX_SESSION_MINUTES: u32 = 240;
+
+impl Default for BookingRules {
+    fn default() -> Self {
+        Self {
+            min_notice_minutes: 0,
+            max_horizon_days: 30,
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            max_sessions_per_day: None,
+            max_sessions_per_week: None,
+            allowed_durations: Vec::new(),
+        }
+    }
+}
+
+impl BookingRules {
+    /// The rules for one session type, or None if the mentor doesn't offer it.
+    pub fn for_session_type(
+        &self,
+        session_types: &HashMap<SessionType, BookingRuleOverrides>,
+        session_type: &SessionType,
+    ) -> Option<BookingRules> {
+        let overrides = match session_types.get(session_type) {
+            Some(overrides) => overrides,
+            None => return Some(self.clone()),
+        };
+        if overrides.bookable == Some(false) {
+            return None;
+        }
+
+        Some(BookingRules {
+            min_notice_minutes: overrides.min_notice_minutes.unwrap_or(self.min_notice_minutes),
+            max_horizon_days: overrides.max_horizon_days.unwrap_or(self.max_horizon_days),
+            buffer_before_minutes: overrides.buffer_before_minutes.unwrap_or(self.buffer_before_minutes),
+            buffer_after_minutes: overrides.buffer_after_minutes.unwrap_or(self.buffer_after_minutes),
+            max_sessions_per_day: overrides.max_sessions_per_day.or(self.max_sessions_per_day),
+            max_sessions_per_week: overrides.max_sessions_per_week.or(self.max_sessions_per_week),
+            allowed_durations: overrides.allowed_durations.clone().unwrap_or_else(|| self.allowed_durations.clone()),
+        })
+    }
+
+    pub fn allows_duration(&self, duration_minutes: u32) -> bool {
+        (MIN_SESSION_MINUTES..=MAX_SESSION_MINUTES).contains(&duration_minutes)
+            && (self.allowed_durations.is_empty() || self.allowed_durations.contains(&duration_minutes))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BookedSession {
+    pub session_id: Uuid,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Checks candidate slots against a mentor's rules, their sessions and the
+/// busy times from their connected calendars.
+pub struct SlotChecker<'a> {
+    rules: &'a BookingRules,
+    timezone: Tz,
+    now: DateTime<Utc>,
+    sessions: &'a [BookedSession],
+    busy_times: &'a [(DateTime<Utc>, DateTime<Utc>)],
+}
+
+impl<'a> SlotChecker<'a> {
+    pub fn new(
+        rules: &'a BookingRules,
+        timezone: Tz,
+        now: DateTime<Utc>,
+        sessions: &'a [BookedSession],
+        busy_times: &'a [(DateTime<Utc>, DateTime<Utc>)],
+    ) -> Self {
+        Self { rules, timezone, now, sessions, busy_times }
+    }
+
+    /// How far either side of a range sessions need loading for: weekly caps
+    /// look at the whole week around a slot, buffers a little further.
+    pub fn session_margin(rules: &BookingRules) -> Duration {
+        Duration::days(7) + Duration::minutes(rules.buffer_before_minutes.max(rules.buffer_after_minutes) as i64)
+    }
+
+    pub fn check(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<(), SlotUnavailableReason> {
+        if start < self.now + Duration::minutes(self.rules.min_notice_minutes as i64) {
+            return Err(SlotUnavailableReason::InsufficientNotice);
+        }
+        if start > self.now + Duration::days(self.rules.max_horizon_days as i64) {
+            return Err(SlotUnavailableReason::BeyondBookingHorizon);
+        }
+        if self.overlapping_session(start, end).is_some() {
+            return Err(SlotUnavailableReason::AlreadyBooked);
+        }
+        if self.busy_times.iter().any(|(busy_start, busy_end)| start < *busy_end && end > *busy_start) {
+            return Err(SlotUnavailableReason::CalendarConflict);
+        }
+
+        // Every session keeps its buffers free, so a neighbour needs the larger
+        // of its own buffer and the new session's on the side they share
+        let before = Duration::minutes(self.rules.buffer_before_minutes as i64);
+        let after = Duration::minutes(self.rules.buffer_after_minutes as i64);
+        let too_close = self.sessions.iter().any(|session| {
+            (start - before < session.end && end + after > session.start)
+                || (session.start - before < end && session.end + after > start)
+        });
+        if too_close {
+            return Err(SlotUnavailableReason::BufferConflict);
+        }
+
+        let local_start = start.with_timezone(&self.timezone);
+        if let Some(limit) = self.rules.max_sessions_per_day {
+            let day = local_start.date_naive();
+            let count = self.sessions.iter()
+                .filter(|session| session.start.with_timezone(&self.timezone).date_naive() == day)
+                .count();
+            if count >= limit as usize {
+                return Err(SlotUnavailableReason::DailyLimitReached);
+            }
+        }
+        if let Some(limit) = self.rules.max_sessions_per_week {
+            let week = local_start.iso_week();
+            let count = self.sessions.iter()
+                .filter(|session| session.start.with_timezone(&self.timezone).iso_week() == week)
+                .count();
+            if count >= limit as usize {
+                return Err(SlotUnavailableReason::WeeklyLimitReached);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn overlapping_session(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Option<&BookedSession> {
+        self.sessions.iter().find(|session| start < session.end && end > session.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn session(start: &str, end: &str) -> BookedSession {
+        BookedSession { session_id: Uuid::new_v4(), start: utc(start), end: utc(end) }
+    }
+
+    fn check(rules: &BookingRules, sessions: &[BookedSession], start: &str, end: &str) -> Result<(), SlotUnavailableReason> {
+        SlotChecker::new(rules, Tz::UTC, utc("2024-07-01T08:00:00Z"), sessions, &[]).check(utc(start), utc(end))
+    }
+
+    #[test]
+    fn test_notice_and_horizon() {
+        let rules = BookingRules { min_notice_minutes: 24 * 60, max_horizon_days: 14, ..Default::default() };
+
+        assert_eq!(check(&rules, &[], "2024-07-01T20:00:00Z", "2024-07-01T21:00:00Z"), Err(SlotUnavailableReason::InsufficientNotice));
+        assert_eq!(check(&rules, &[], "2024-07-02T08:00:00Z", "2024-07-02T09:00:00Z"), Ok(()));
+        assert_eq!(check(&rules, &[], "2024-07-16T09:00:00Z", "2024-07-16T10:00:00Z"), Err(SlotUnavailableReason::BeyondBookingHorizon));
+    }
+
+    #[test]
+    fn test_buffers_apply_on_both_sides() {
+        let rules = BookingRules { buffer_before_minutes: 10, buffer_after_minutes: 15, ..Default::default() };
+        let sessions = [session("2024-07-02T10:00:00Z", "2024-07-02T11:00:00Z")];
+
+        assert_eq!(check(&rules, &sessions, "2024-07-02T10:30:00Z", "2024-07-02T11:30:00Z"), Err(SlotUnavailableReason::AlreadyBooked));
+        // The existing session's 15 minute buffer after it
+        assert_eq!(check(&rules, &sessions, "2024-07-02T11:10:00Z", "2024-07-02T12:10:00Z"), Err(SlotUnavailableReason::BufferConflict));
+        assert_eq!(check(&rules, &sessions, "2024-07-02T11:15:00Z", "2024-07-02T12:15:00Z"), Ok(()));
+        // The new session's own 15 minutes after it run into the next one
+        assert_eq!(check(&rules, &sessions, "2024-07-02T08:50:00Z", "2024-07-02T09:50:00Z"), Err(SlotUnavailableReason::BufferConflict));
+        assert_eq!(check(&rules, &sessions, "2024-07-02T08:45:00Z", "2024-07-02T09:45:00Z"), Ok(()));
+    }
+
+    #[test]
+    fn test_daily_and_weekly_caps_use_the_mentor_timezone() {
+        let rules = BookingRules { max_sessions_per_day: Some(1), max_sessions_per_week: Some(2), ..Default::default() };
+        // 21:30 UTC on Tuesday is 23:30 in Berlin, still Tuesday there
+        let sessions = [
+            session("2024-07-02T21:30:00Z", "2024-07-02T22:00:00Z"),
+            session("2024-07-04T09:00:00Z", "2024-07-04T10:00:00Z"),
+        ];
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let checker = SlotChecker::new(&rules, berlin, utc("2024-07-01T08:00:00Z"), &sessions, &[]);
+
+        // Wednesday 00:30 in Berlin is a new day there
+        assert_eq!(
+            checker.check(utc("2024-07-02T22:30:00Z"), utc("2024-07-02T23:00:00Z")),
+            Err(SlotUnavailableReason::WeeklyLimitReached),
+        );
+        assert_eq!(
+            checker.check(utc("2024-07-02T12:00:00Z"), utc("2024-07-02T13:00:00Z")),
+            Err(SlotUnavailableReason::DailyLimitReached),
+        );
+        // Next week starts on Monday
+        assert_eq!(checker.check(utc("2024-07-08T09:00:00Z"), utc("2024-07-08T10:00:00Z")), Ok(()));
+    }
+
+    #[test]
+    fn test_calendar_busy_times() {
+        let rules = BookingRules::default();
+        let busy = [(utc("2024-07-02T12:00:00Z"), utc("2024-07-02T13:00:00Z"))];
+        let checker = SlotChecker::new(&rules, Tz::UTC, utc("2024-07-01T08:00:00Z"), &[], &busy);
+
+        assert_eq!(checker.check(utc("2024-07-02T12:30:00Z"), utc("2024-07-02T13:30:00Z")), Err(SlotUnavailableReason::CalendarConflict));
+        assert_eq!(checker.check(utc("2024-07-02T13:00:00Z"), utc("2024-07-02T14:00:00Z")), Ok(()));
+    }
+
+    #[test]
+    fn test_session_type_overrides() {
+        let rules = BookingRules { allowed_durations: vec![30, 60], ..Default::default() };
+        let mut session_types = HashMap::new();
+        session_types.insert(SessionType::Workshop, BookingRuleOverrides {
+            allowed_durations: Some(vec![120]),
+            max_sessions_per_week: Some(1),
+            ..Default::default()
+        });
+        session_types.insert(SessionType::Consultation, BookingRuleOverrides {
+            bookable: Some(false),
+            ..Default::default()
+        });
+
+        let one_on_one = rules.for_session_type(&session_types, &SessionType::OneOnOne).unwrap();
+        assert!(one_on_one.allows_duration(60));
+        assert!(!one_on_one.allows_duration(120));
+
+        let workshop = rules.for_session_type(&session_types, &SessionType::Workshop).unwrap();
+        assert!(workshop.allows_duration(120));
+        assert_eq!(workshop.max_sessions_per_week, Some(1));
+
+        assert!(rules.for_session_type(&session_types, &SessionType::Consultation).is_none());
+        assert!(!BookingRules::default().allows_duration(300));
+    }
+}
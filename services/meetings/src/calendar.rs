@@ -1,337 +0,0 @@
-use uuid::Uuid;
-use chrono::{DateTime, Utc};
-use icalendar::{Calendar, Event, EventLike, Component};
-
-use linkwithmentor_common::AppError;
-use crate::{
-    config::CalendarConfig,
-    models::{CalendarEvent, CalendarProvider, SessionResponse},
-};
-
-#[derive(Clone)]
-pub struct CalendarService {
-    config: CalendarConfig,
-}
-
-impl CalendarService {
-    pub fn new(config: &CalendarConfig) -> Self {
-        Self {
-            config: config.clone(),
-        }
-    }
-
-    // iCalendar generation
-    pub async fn generate_ical_for_session(&self, session: &SessionResponse) -> Result<String, AppError> {
-        if !self.config.enable_ical_export {
-            return Err(AppError::BadRequest("iCalendar export is disabled".to_string()));
-        }
-
-        let mut calendar = Calendar::new();
-        calendar.name(&self.config.calendar_name);
-        calendar.timezone(&self.config.timezone);
-
-        let event = Event::new()
-            .uid(&format!("session-{}", session.session_id))
-            .summary(&session.title)
-            .description(&session.description.clone().unwrap_or_default())
-            .starts(session.scheduled_start)
-            .ends(session.scheduled_end)
-            .location("LinkWithMentor Platform")
-            .done();
-
-        calendar.push(event);
-
-        Ok(calendar.to_string())
-    }
-
-    pub async fn generate_ical_for_sessions(&self, sessions: &[SessionResponse]) -> Result<String, AppError> {
-        if !self.config.enable_ical_export {
-            return Err(AppError::BadRequest("iCalendar export is disabled".to_string()));
-        }
-
-        let mut calendar = Calendar::new();
-        calendar.name(&self.config.calendar_name);
-        calendar.timezone(&self.config.timezone);
-
-        for session in sessions {
-            let event = Event::new()
-                .uid(&format!("session-{}", session.session_id))
-                .summary(&session.title)
-                .description(&session.description.clone().unwrap_or_default())
-                .starts(session.scheduled_start)
-                .ends(session.scheduled_end)
-                .location("LinkWithMentor Platform")
-                .done();
-
-            calendar.push(event);
-        }
-
-        Ok(calendar.to_string())
-    }
-
-    // Calendar event management
-    pub async fn create_calendar_event(&self, session: &SessionResponse) -> Result<CalendarEvent, AppError> {
-        let event_id = format!("lwm-session-{}", session.session_id);
-        
-        let calendar_event = CalendarEvent {
-            event_id,
-            session_id: session.session_id,
-            title: session.title.clone(),
-            description: session.description.clone(),
-            start_time: session.scheduled_start,
-            end_time: session.scheduled_end,
-            location: Some("LinkWithMentor Platform".to_string()),
-            attendees: self.get_session_attendees(session),
-            calendar_provider: CalendarProvider::ICalendar,
-        };
-
-        // In a real implementation, you would also sync with external calendar providers
-        if self.config.enable_google_calendar {
-            self.sync_with_google_calendar(&calendar_event).await?;
-        }
-
-        if self.config.enable_outlook_calendar {
-            self.sync_with_outlook_calendar(&calendar_event).await?;
-        }
-
-        Ok(calendar_event)
-    }
-
-    pub async fn update_calendar_event(&self, session: &SessionResponse) -> Result<CalendarEvent, AppError> {
-        let event_id = format!("lwm-session-{}", session.session_id);
-        
-        let calendar_event = CalendarEvent {
-            event_id,
-            session_id: session.session_id,
-            title: session.title.clone(),
-            description: session.description.clone(),
-            start_time: session.scheduled_start,
-            end_time: session.scheduled_end,
-            location: Some("LinkWithMentor Platform".to_string()),
-            attendees: self.get_session_attendees(session),
-            calendar_provider: CalendarProvider::ICalendar,
-        };
-
-        // Update in external calendar providers
-        if self.config.enable_google_calendar {
-            self.update_google_calendar_event(&calendar_event).await?;
-        }
-
-        if self.config.enable_outlook_calendar {
-            self.update_outlook_calendar_event(&calendar_event).await?;
-        }
-
-        Ok(calendar_event)
-    }
-
-    pub async fn delete_calendar_event(&self, session_id: Uuid) -> Result<(), AppError> {
-        let event_id = format!("lwm-session-{}", session_id);
-
-        // Delete from external calendar providers
-        if self.config.enable_google_calendar {
-            self.delete_google_calendar_event(&event_id).await?;
-        }
-
-        if self.config.enable_outlook_calendar {
-            self.delete_outlook_calendar_event(&event_id).await?;
-        }
-
-        tracing::info!("Deleted calendar event for session {}", session_id);
-        Ok(())
-    }
-
-    // Google Calendar integration
-    async fn sync_with_google_calendar(&self, event: &CalendarEvent) -> Result<(), AppError> {
-        if !self.config.enable_google_calendar {
-            return Ok(());
-        }
-
-        // In a real implementation, you would use the Google Calendar API
-        // This requires OAuth2 authentication and proper API credentials
-        tracing::info!("Syncing event {} with Google Calendar", event.event_id);
-        
-        // Placeholder implementation
-        Ok(())
-    }
-
-    async fn update_google_calendar_event(&self, event: &CalendarEvent) -> Result<(), AppError> {
-        if !self.config.enable_google_calendar {
-            return Ok(());
-        }
-
-        tracing::info!("Updating Google Calendar event {}", event.event_id);
-        Ok(())
-    }
-
-    async fn delete_google_calendar_event(&self, event_id: &str) -> Result<(), AppError> {
-        if !self.config.enable_google_calendar {
-            return Ok(());
-        }
-
-        tracing::info!("Deleting Google Calendar event {}", event_id);
-        Ok(())
-    }
-
-    // Outlook Calendar integration
-    async fn sync_with_outlook_calendar(&self, event: &CalendarEvent) -> Result<(), AppError> {
-        if !self.config.enable_outlook_calendar {
-            return Ok(());
-        }
-
-        // In a real implementation, you would use the Microsoft Graph API
-        tracing::info!("Syncing event {} with Outlook Calendar", event.event_id);
-        Ok(())
-    }
-
-    async fn update_outlook_calendar_event(&self, event: &CalendarEvent) -> Result<(), AppError> {
-        if !self.config.enable_outlook_calendar {
-            return Ok(());
-        }
-
-        tracing::info!("Updating Outlook Calendar event {}", event.event_id);
-        Ok(())
-    }
-
-    async fn delete_outlook_calendar_event(&self, event_id: &str) -> Result<(), AppError> {
-        if !self.config.enable_outlook_calendar {
-            return Ok(());
-        }
-
-        tracing::info!("Deleting Outlook Calendar event {}", event_id);
-        Ok(())
-    }
-
-    // Helper methods
-    fn get_session_attendees(&self, session: &SessionResponse) -> Vec<String> {
-        let mut attendees = Vec::new();
-        
-        for participant in &session.participants {
-            // In a real implementation, you would fetch email addresses from the database
-            attendees.push(format!("user_{}@example.com", participant.user_id));
-        }
-        
-        attendees
-    }
-
-    // Calendar availability checking
-    pub async fn check_availability(
-        &self,
-        user_id: Uuid,
-        start_time: DateTime<Utc>,
-        end_time: DateTime<Utc>,
-    ) -> Result<bool, AppError> {
-        // In a real implementation, you would check against external calendars
-        // For now, assume available
-        Ok(true)
-    }
-
-    // Bulk calendar operations
-    pub async fn create_recurring_calendar_events(
-        &self,
-        sessions: &[SessionResponse],
-    ) -> Result<Vec<CalendarEvent>, AppError> {
-        let mut events = Vec::new();
-        
-        for session in sessions {
-            let event = self.create_calendar_event(session).await?;
-            events.push(event);
-        }
-        
-        Ok(events)
-    }
-
-    // Calendar export formats
-    pub async fn export_calendar_feed(&self, user_id: Uuid) -> Result<String, AppError> {
-        // In a real implementation, you would fetch user's sessions and generate a calendar feed
-        let mut calendar = Calendar::new();
-        calendar.name(&format!("{} - User {}", self.config.calendar_name, user_id));
-        calendar.timezone(&self.config.timezone);
-
-        // Add sample event (in real implementation, fetch from database)
-        let event = Event::new()
-            .uid(&format!("user-{}-feed", user_id))
-            .summary("Sample Session")
-            .description("This is a sample session")
-            .starts(Utc::now())
-            .ends(Utc::now() + chrono::Duration::hours(1))
-            .location("LinkWithMentor Platform")
-            .done();
-
-        calendar.push(event);
-
-        Ok(calendar.to_string())
-    }
-
-    // Calendar subscription management
-    pub async fn create_calendar_subscription(&self, user_id: Uuid) -> Result<String, AppError> {
-        // Generate a unique subscription URL
-        let subscription_id = Uuid::new_v4();
-        let subscription_url = format!(
-            "https://api.linkwithmentor.com/calendar/feed/{}/{}",
-            user_id,
-            subscription_id
-        );
-
-        // In a real implementation, you would store this subscription in the database
-        tracing::info!("Created calendar subscription for user {}: {}", user_id, subscription_url);
-
-        Ok(subscription_url)
-    }
-
-    pub async fn revoke_calendar_subscription(&self, user_id: Uuid, subscription_id: Uuid) -> Result<(), AppError> {
-        // In a real implementation, you would remove the subscription from the database
-        tracing::info!("Revoked calendar subscription {} for user {}", subscription_id, user_id);
-        Ok(())
-    }
-
-    // Time zone handling
-    pub fn convert_to_user_timezone(&self, datetime: DateTime<Utc>, user_timezone: &str) -> Result<DateTime<Utc>, AppError> {
-        // In a real implementation, you would use proper timezone conversion
-        // For now, return the UTC time
-        Ok(datetime)
-    }
-
-    pub fn convert_from_user_timezone(&self, datetime: DateTime<Utc>, user_timezone: &str) -> Result<DateTime<Utc>, AppError> {
-        // In a real implementation, you would use proper timezone conversion
-        // For now, return the UTC time
-        Ok(datetime)
-    }
-
-    // Calendar integration status
-    pub async fn get_integration_status(&self, user_id: Uuid) -> Result<std::collections::HashMap<String, bool>, AppError> {
-        let mut status = std::collections::HashMap::new();
-        
-        status.insert("google_calendar".to_string(), self.config.enable_google_calendar);
-        status.insert("outlook_calendar".to_string(), self.config.enable_outlook_calendar);
-        status.insert("ical_export".to_string(), self.config.enable_ical_export);
-        
-        // In a real implementation, you would check actual connection status
-        Ok(status)
-    }
-
-    // Calendar sync operations
-    pub async fn sync_all_calendars(&self, user_id: Uuid) -> Result<(), AppError> {
-        if self.config.enable_google_calendar {
-            self.sync_google_calendar(user_id).await?;
-        }
-
-        if self.config.enable_outlook_calendar {
-            self.sync_outlook_calendar(user_id).await?;
-        }
-
-        tracing::info!("Synced all calendars for user {}", user_id);
-        Ok(())
-    }
-
-    async fn sync_google_calendar(&self, user_id: Uuid) -> Result<(), AppError> {
-        // In a real implementation, you would sync with Google Calendar
-        tracing::info!("Syncing Google Calendar for user {}", user_id);
-        Ok(())
-    }
-
-    async fn sync_outlook_calendar(&self, user_id: Uuid) -> Result<(), AppError> {
-        // In a real implementation, you would sync with Outlook Calendar
-        tracing::info!("Syncing Outlook Calendar for user {}", user_id);
-        Ok(())
-    }
-}
\ No newline at end of file
@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+
+use linkwithmentor_common::AppError;
+use crate::models::{CalendarEvent, CalendarProvider};
+
+use super::{
+    oauth::OAuthClient, CalendarClient, ConnectionCredentials, ExternalEvent, ProviderConnection,
+    SyncChanges, SyncWindow,
+};
+
+const API_URL: &str = "https://www.googleapis.com/calendar/v3";
+// Expanded recurring events can produce a lot of pages; stop rather than loop forever
+const MAX_SYNC_PAGES: usize = 40;
+
+#[derive(Clone)]
+pub struct GoogleCalendarClient {
+    client: Client,
+    oauth: OAuthClient,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventsPage {
+    #[serde(default)]
+    items: Vec<GoogleEvent>,
+    next_page_token: Option<String>,
+    next_sync_token: Option<String>,
+    time_zone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleEvent {
+    id: String,
+    status: Option<String>,
+    transparency: Option<String>,
+    start: Option<GoogleTime>,
+    end: Option<GoogleTime>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleTime {
+    date_time: Option<DateTime<FixedOffset>>,
+    date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InsertedEvent {
+    id: String,
+}
+
+impl GoogleCalendarClient {
+    pub fn new(client: Client, client_id: &str, client_secret: &str, redirect_uri: &str) -> Self {
+        let oauth = OAuthClient::new(
+            client.clone(),
+            client_id,
+            client_secret,
+            "https://accounts.google.com/o/oauth2/v2/auth",
+            "https://oauth2.googleapis.com/token",
+            "https://www.googleapis.com/auth/calendar.events",
+            redirect_uri,
+            // Without these Google only hands out a refresh token on first consent
+            vec![("access_type", "offline"), ("prompt", "consent")],
+        );
+
+        Self { client, oauth }
+    }
+
+    fn events_url(calendar_id: &str, event_id: Option<&str>) -> Url {
+        let mut url = Url::parse(API_URL).expect("valid Google Calendar URL");
+        {
+            let mut segments = url.path_segments_mut().expect("base URL");
+            segments.extend(["calendars", calendar_id, "events"]);
+            if let Some(event_id) = event_id {
+                segments.push(event_id);
+            }
+        }
+        url
+    }
+
+    fn access_token(connection: &ProviderConnection) -> Result<&str, AppError> {
+        connection.credentials.access_token.as_deref()
+            .ok_or_else(|| AppError::ExternalService("Google Calendar connection is not authorized".to_string()))
+    }
+
+    fn event_body(event: &CalendarEvent) -> serde_json::Value {
+        json!({
+            "summary": event.title,
+            "description": event.description,
+            "location": event.location,
+            "start": { "dateTime": event.start_time.to_rfc3339() },
+            "end": { "dateTime": event.end_time.to_rfc3339() },
+            "extendedProperties": {
+                "private": { "linkwithmentorSessionId": event.session_id.to_string() }
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl CalendarClient for GoogleCalendarClient {
+    async fn authorize(&self, credentials: &mut ConnectionCredentials) -> Result<bool, AppError> {
+        self.oauth.refresh(credentials).await
+    }
+
+    async fn sync(
+        &self,
+        connection: &ProviderConnection,
+        sync_token: Option<&str>,
+        window: &SyncWindow,
+    ) -> Result<SyncChanges, AppError> {
+        let access_token = Self::access_token(connection)?;
+        let url = Self::events_url(&connection.calendar_id, None);
+
+        let mut sync_token = sync_token;
+        'sync: loop {
+            let mut changes = SyncChanges {
+                full: sync_token.is_none(),
+                ..Default::default()
+            };
+            let mut page_token: Option<String> = None;
+
+            for _ in 0..MAX_SYNC_PAGES {
+                let mut request = self.client
+                    .get(url.clone())
+                    .bearer_auth(access_token)
+                    .query(&[("singleEvents", "true"), ("showDeleted", "true"), ("maxResults", "250")]);
+                // Google refuses time bounds together with a sync token; the
+                // window from the initial full sync carries over
+                request = match sync_token {
+                    Some(token) => request.query(&[("syncToken", token)]),
+                    None => request.query(&[("timeMin", window.start.to_rfc3339())]),
+                };
+                if let Some(page_token) = &page_token {
+                    request = request.query(&[("pageToken", page_token)]);
+                }
+
+                let response = request.send().await
+                    .map_err(|e| AppError::ExternalService(format!("Google Calendar sync failed: {}", e)))?;
+
+                if response.status() == StatusCode::GONE && sync_token.is_some() {
+                    sync_token = None;
+                    continue 'sync;
+                }
+                if !response.status().is_success() {
+                    return Err(AppError::ExternalService(format!(
+                        "Google Calendar sync rejected ({})", response.status()
+                    )));
+                }
+
+                let page: EventsPage = response.json().await
+                    .map_err(|e| AppError::ExternalService(format!("Invalid Google Calendar response: {}", e)))?;
+                let time_zone = page.time_zone.as_deref()
+                    .and_then(|zone| zone.parse::<Tz>().ok())
+                    .unwrap_or(Tz::UTC);
+
+                for event in page.items {
+                    if event.status.as_deref() == Some("cancelled") {
+                        changes.removed.push(event.id);
+                        continue;
+                    }
+
+                    let busy = match (&event.start, &event.end) {
+                        (Some(start), Some(end)) if event.transparency.as_deref() != Some("transparent") => {
+                            match (start.resolve(time_zone), end.resolve(time_zone)) {
+                                (Some(start), Some(end)) if start < window.end && end > window.start => {
+                                    vec![(start, end)]
+                                }
+                                _ => Vec::new(),
+                            }
+                        }
+                        _ => Vec::new(),
+                    };
+
+                    changes.events.push(ExternalEvent { external_id: event.id, busy });
+                }
+
+                if page.next_sync_token.is_some() {
+                    changes.sync_token = page.next_sync_token;
+                    return Ok(changes);
+                }
+                match page.next_page_token {
+                    Some(token) => page_token = Some(token),
+                    None => return Ok(changes),
+                }
+            }
+
+            // Keep what was read; without a sync token the next run starts over
+            tracing::warn!(
+                "Google Calendar sync for connection {} stopped after {} pages",
+                connection.connection_id, MAX_SYNC_PAGES
+            );
+            return Ok(changes);
+        }
+    }
+
+    async fn put_event(
+        &self,
+        connection: &ProviderConnection,
+        event: &CalendarEvent,
+        external_id: Option<&str>,
+    ) -> Result<String, AppError> {
+        let access_token = Self::access_token(connection)?;
+        let body = Self::event_body(event);
+
+        if let Some(external_id) = external_id {
+            let response = self.client
+                .put(Self::events_url(&connection.calendar_id, Some(external_id)))
+                .bearer_auth(access_token)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| AppError::ExternalService(format!("Google Calendar request failed: {}", e)))?;
+
+            // Deleted on the user's side: fall through and create it again
+            if !matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+                if !response.status().is_success() {
+                    return Err(AppError::ExternalService(format!(
+                        "Google Calendar request rejected ({})", response.status()
+                    )));
+                }
+                return Ok(external_id.to_string());
+            }
+        }
+
+        let response = self.client
+            .post(Self::events_url(&connection.calendar_id, None))
+            .bearer_auth(access_token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Google Calendar request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Google Calendar request rejected ({})", response.status()
+            )));
+        }
+
+        let inserted: InsertedEvent = response.json().await
+            .map_err(|e| AppError::ExternalService(format!("Invalid Google Calendar response: {}", e)))?;
+        Ok(inserted.id)
+    }
+
+    async fn delete_event(&self, connection: &ProviderConnection, external_id: &str) -> Result<(), AppError> {
+        let response = self.client
+            .delete(Self::events_url(&connection.calendar_id, Some(external_id)))
+            .bearer_auth(Self::access_token(connection)?)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Google Calendar request failed: {}", e)))?;
+
+        if !response.status().is_success()
+            && !matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE)
+        {
+            return Err(AppError::ExternalService(format!(
+                "Google Calendar request rejected ({})", response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn provider(&self) -> CalendarProvider {
+        CalendarProvider::Google
+    }
+
+    fn oauth(&self) -> Option<&OAuthClient> {
+        Some(&self.oauth)
+    }
+}
+
+impl GoogleTime {
+    // All-day events carry a bare date that is local to the calendar
+    fn resolve(&self, time_zone: Tz) -> Option<DateTime<Utc>> {
+        if let Some(date_time) = self.date_time {
+            return Some(date_time.with_timezone(&Utc));
+        }
+        let midnight = self.date?.and_hms_opt(0, 0, 0)?;
+        time_zone.from_local_datetime(&midnight).earliest()
+            .map(|start| start.with_timezone(&Utc))
+    }
+}
@@ -0,0 +1,798 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+// Periods expanded per rule, so rules without an end stay bounded
+const MAX_RECURRENCE_STEPS: usize = 5000;
+// Largest INTERVAL accepted, which keeps every expanded period within chrono's date range
+pub const MAX_RECURRENCE_INTERVAL: u32 = 1000;
+
+/// A date or date-time as written in an iCalendar file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IcalTime {
+    Date(NaiveDate),
+    // Local time in a zone; floating times (no TZID) are read as UTC
+    Local(NaiveDateTime, Tz),
+    Utc(DateTime<Utc>),
+}
+
+impl IcalTime {
+    fn local(&self) -> (NaiveDateTime, Tz) {
+        match self {
+            IcalTime::Date(date) => (date.and_time(NaiveTime::MIN), Tz::UTC),
+            IcalTime::Local(local, tz) => (*local, *tz),
+            IcalTime::Utc(instant) => (instant.naive_utc(), Tz::UTC),
+        }
+    }
+
+    pub fn to_utc(&self) -> DateTime<Utc> {
+        let (local, tz) = self.local();
+        resolve(local, tz)
+    }
+}
+
+impl From<DateTime<Utc>> for IcalTime {
+    fn from(instant: DateTime<Utc>) -> Self {
+        IcalTime::Utc(instant)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// An RRULE with day-level frequencies. Time-of-day parts (BYHOUR and
+/// below) and BYWEEKNO/BYYEARDAY are not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Utc>>,
+    // Weekday with an optional ordinal, e.g. 2TU or -1FR for monthly rules
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    // Picks from each period's instances, e.g. -1 with BYDAY=MO,TU,WE,TH,FR for the last weekday
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+impl RecurrenceRule {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        }
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        parse_rrule(value.strip_prefix("RRULE:").unwrap_or(value), true)
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self.by_day.iter()
+                .map(|(ordinal, weekday)| match ordinal {
+                    Some(ordinal) => format!("{}{}", ordinal, weekday_code(*weekday)),
+                    None => weekday_code(*weekday).to_string(),
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        write_list(f, "BYMONTHDAY", &self.by_month_day)?;
+        write_list(f, "BYMONTH", &self.by_month)?;
+        write_list(f, "BYSETPOS", &self.by_set_pos)?;
+        if self.week_start != Weekday::Mon {
+            write!(f, ";WKST={}", weekday_code(self.week_start))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A VEVENT, reduced to what availability needs.
+#[derive(Debug, Clone)]
+pub struct IcalEvent {
+    pub uid: String,
+    pub summary: Option<String>,
+    pub start: IcalTime,
+    pub end: Option<IcalTime>,
+    pub duration: Option<Duration>,
+    pub rrule: Option<RecurrenceRule>,
+    pub rdates: Vec<DateTime<Utc>>,
+    pub exdates: Vec<DateTime<Utc>>,
+    // Set on an edited occurrence of a recurring event
+    pub recurrence_id: Option<DateTime<Utc>>,
+    pub transparent: bool,
+    pub cancelled: bool,
+}
+
+impl IcalEvent {
+    fn length(&self) -> Duration {
+        match (&self.end, self.duration) {
+            (Some(end), _) => end.to_utc() - self.start.to_utc(),
+            (None, Some(duration)) => duration,
+            (None, None) if matches!(self.start, IcalTime::Date(_)) => Duration::days(1),
+            (None, None) => Duration::zero(),
+        }
+    }
+
+    fn blocks_time(&self) -> bool {
+        !self.transparent && !self.cancelled
+    }
+}
+
+/// Start times of a recurrence set that start before `until`, in order:
+/// DTSTART, the rule's instances and the RDATEs, less the EXDATEs.
+pub fn expand(
+    start: &IcalTime,
+    rule: Option<&RecurrenceRule>,
+    rdates: &[DateTime<Utc>],
+    exdates: &[DateTime<Utc>],
+    until: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let mut starts = match rule {
+        Some(rule) => rule_instances(start, rule, until),
+        None => vec![start.to_utc()],
+    };
+    starts.extend(rdates);
+
+    starts.retain(|start| *start < until && !exdates.contains(start));
+    starts.sort();
+    starts.dedup();
+    starts
+}
+
+fn rule_instances(start: &IcalTime, rule: &RecurrenceRule, until: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let (first, tz) = start.local();
+    let interval = rule.interval.max(1) as i64;
+
+    // DTSTART is the first instance and counts towards COUNT, whether or not the rule matches it
+    let mut starts = vec![resolve(first, tz)];
+
+    for step in 0..MAX_RECURRENCE_STEPS as i64 {
+        // Periods past the end of the calendar end the expansion
+        let Some(candidates) = period_candidates(rule, first, step * interval) else {
+            break;
+        };
+
+        for local in candidates {
+            if local <= first {
+                continue;
+            }
+
+            let instant = resolve(local, tz);
+            if rule.until.map_or(false, |rule_until| instant > rule_until)
+                || rule.count.map_or(false, |count| starts.len() >= count as usize)
+                || instant >= until
+            {
+                return starts;
+            }
+
+            starts.push(instant);
+        }
+    }
+
+    starts
+}
+
+/// Busy intervals of the events in one calendar resource that overlap the
+/// window. Recurring events are expanded, excluded dates dropped and edited
+/// occurrences replaced by their edits.
+pub fn busy_intervals(
+    events: &[IcalEvent],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut overridden: HashMap<&str, Vec<DateTime<Utc>>> = HashMap::new();
+    for event in events {
+        if let Some(recurrence_id) = event.recurrence_id {
+            overridden.entry(event.uid.as_str()).or_default().push(recurrence_id);
+        }
+    }
+
+    let mut intervals = Vec::new();
+    for event in events {
+        let length = event.length();
+        if length <= Duration::zero() {
+            continue;
+        }
+
+        if event.recurrence_id.is_some() {
+            if event.blocks_time() {
+                let start = event.start.to_utc();
+                intervals.push((start, start + length));
+            }
+            continue;
+        }
+
+        if !event.blocks_time() {
+            continue;
+        }
+
+        let skipped = overridden.get(event.uid.as_str());
+        for start in expand(&event.start, event.rrule.as_ref(), &event.rdates, &event.exdates, window_end) {
+            if skipped.map_or(false, |skipped| skipped.contains(&start)) {
+                continue;
+            }
+            intervals.push((start, start + length));
+        }
+    }
+
+    intervals.retain(|(start, end)| *start < window_end && *end > window_start);
+    intervals.sort();
+    intervals
+}
+
+/// Reads the VEVENTs of an iCalendar document. Properties we do not need
+/// are ignored, and events we cannot make sense of are skipped.
+pub fn parse_events(ics: &str) -> Vec<IcalEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    let mut depth = 0;
+
+    for line in unfold(ics) {
+        let Some(property) = Property::parse(&line) else { continue };
+
+        match (property.name.as_str(), property.value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") if current.is_none() => {
+                current = Some(Vec::new());
+                depth = 0;
+            }
+            ("BEGIN", _) if current.is_some() => depth += 1, // VALARM inside an event
+            ("END", "VEVENT") if depth == 0 => {
+                if let Some(event) = current.take().and_then(|properties| event_from(&properties)) {
+                    events.push(event);
+                }
+            }
+            ("END", _) if current.is_some() => depth -= 1,
+            _ => {
+                if let Some(properties) = current.as_mut() {
+                    if depth == 0 {
+                        properties.push(property);
+                    }
+                }
+            }
+        }
+    }
+
+    events
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut in_quotes = false;
+        let split = line.char_indices().find(|(_, c)| {
+            if *c == '"' {
+                in_quotes = !in_quotes;
+            }
+            *c == ':' && !in_quotes
+        })?.0;
+
+        let (head, value) = (&line[..split], &line[split + 1..]);
+        let mut parts = head.split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (key.to_ascii_uppercase(), value.trim_matches('"').to_string()))
+            .collect();
+
+        Some(Self { name, params, value: value.to_string() })
+    }
+
+    fn time(&self) -> Option<IcalTime> {
+        parse_time(&self.value, self.params.get("TZID").map(String::as_str), self.params.get("VALUE").map(String::as_str))
+    }
+}
+
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+fn event_from(properties: &[Property]) -> Option<IcalEvent> {
+    let get = |name: &str| properties.iter().find(|property| property.name == name);
+
+    let start = get("DTSTART")?.time()?;
+    let dates = |name: &str| -> Vec<DateTime<Utc>> {
+        properties.iter()
+            .filter(|property| property.name == name)
+            .flat_map(|property| {
+                let tzid = property.params.get("TZID").map(String::as_str);
+                let value_type = property.params.get("VALUE").map(String::as_str);
+                // RDATE periods are start/end or start/duration; only the start matters here
+                property.value.split(',')
+                    .filter_map(|value| parse_time(value.split('/').next().unwrap_or(value), tzid, value_type))
+                    .map(|time| time.to_utc())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    };
+
+    Some(IcalEvent {
+        uid: get("UID").map(|property| property.value.clone()).unwrap_or_default(),
+        summary: get("SUMMARY").map(|property| unescape(&property.value)),
+        start,
+        end: get("DTEND").and_then(Property::time),
+        duration: get("DURATION").and_then(|property| parse_duration(&property.value)),
+        // Parts we don't support are skipped, which can only make the event busier
+        rrule: get("RRULE").and_then(|property| parse_rrule(&property.value, false).ok()),
+        rdates: dates("RDATE"),
+        exdates: dates("EXDATE"),
+        recurrence_id: get("RECURRENCE-ID").and_then(Property::time).map(|time| time.to_utc()),
+        transparent: get("TRANSP").map_or(false, |property| property.value.eq_ignore_ascii_case("TRANSPARENT")),
+        cancelled: get("STATUS").map_or(false, |property| property.value.eq_ignore_ascii_case("CANCELLED")),
+    })
+}
+
+fn parse_time(value: &str, tzid: Option<&str>, value_type: Option<&str>) -> Option<IcalTime> {
+    let value = value.trim();
+
+    if value_type.map_or(false, |value_type| value_type.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(IcalTime::Date);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let local = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(IcalTime::Utc(Utc.from_utc_datetime(&local)));
+    }
+
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    // Some clients prefix zone ids with a path, e.g. /mozilla.org/20050126_1/Europe/Berlin
+    let tz = tzid
+        .and_then(|tzid| {
+            tzid.parse::<Tz>().ok().or_else(|| {
+                let parts: Vec<&str> = tzid.rsplitn(3, '/').collect();
+                (parts.len() >= 2).then(|| format!("{}/{}", parts[1], parts[0])).and_then(|name| name.parse().ok())
+            })
+        })
+        .unwrap_or(Tz::UTC);
+
+    Some(IcalTime::Local(local, tz))
+}
+
+// ISO 8601 durations as used by iCalendar: P1W, P1DT2H, PT30M, -PT15M
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim().trim_start_matches('+')),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(amount),
+                    ('D', false) => Duration::days(amount),
+                    ('H', true) => Duration::hours(amount),
+                    ('M', true) => Duration::minutes(amount),
+                    ('S', true) => Duration::seconds(amount),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+// Strict parsing rejects parts we can't honour; lenient parsing ignores them
+fn parse_rrule(value: &str, strict: bool) -> Result<RecurrenceRule, String> {
+    let mut frequency = None;
+    let mut rule = RecurrenceRule::new(Frequency::Daily);
+
+    for part in value.split(';').filter(|part| !part.trim().is_empty()) {
+        let (key, value) = part.split_once('=').ok_or_else(|| format!("Invalid RRULE part: {}", part))?;
+        let invalid = || format!("Invalid {} in RRULE: {}", key, value);
+
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.trim().to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return Err(format!("Unsupported RRULE frequency: {}", value)),
+                });
+            }
+            "INTERVAL" => {
+                rule.interval = value.trim().parse().ok()
+                    .filter(|interval| (1..=MAX_RECURRENCE_INTERVAL).contains(interval))
+                    .ok_or_else(invalid)?;
+            }
+            "COUNT" => rule.count = Some(value.trim().parse().ok().filter(|count| *count > 0).ok_or_else(invalid)?),
+            "UNTIL" => rule.until = Some(parse_time(value, None, None).ok_or_else(invalid)?.to_utc()),
+            "BYDAY" => {
+                rule.by_day = value.split(',').map(parse_weekday).collect::<Option<_>>().ok_or_else(invalid)?;
+            }
+            "BYMONTHDAY" => {
+                rule.by_month_day = parse_list(value, |day: &i32| (1..=31).contains(&day.abs())).ok_or_else(invalid)?;
+            }
+            "BYMONTH" => rule.by_month = parse_list(value, |month: &u32| (1..=12).contains(month)).ok_or_else(invalid)?,
+            "BYSETPOS" => {
+                rule.by_set_pos = parse_list(value, |position: &i32| (1..=366).contains(&position.abs())).ok_or_else(invalid)?;
+            }
+            "WKST" => {
+                rule.week_start = parse_weekday(value).filter(|(ordinal, _)| ordinal.is_none()).ok_or_else(invalid)?.1;
+            }
+            other if strict => return Err(format!("Unsupported RRULE part: {}", other)),
+            _ => {}
+        }
+    }
+
+    rule.frequency = frequency.ok_or_else(|| "RRULE must have a FREQ".to_string())?;
+    if strict && rule.count.is_some() && rule.until.is_some() {
+        return Err("RRULE cannot have both COUNT and UNTIL".to_string());
+    }
+
+    Ok(rule)
+}
+
+fn parse_list<T: FromStr>(value: &str, valid: impl Fn(&T) -> bool) -> Option<Vec<T>> {
+    value.split(',')
+        .map(|item| item.trim().parse().ok().filter(|item| valid(item)))
+        .collect()
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, name: &str, values: &[impl fmt::Display]) -> fmt::Result {
+    if values.is_empty() {
+        return Ok(());
+    }
+    let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+    write!(f, ";{}={}", name, values.join(","))
+}
+
+fn parse_weekday(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let (ordinal, day) = value.split_at(value.len().checked_sub(2)?);
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = if ordinal.is_empty() { None } else { Some(ordinal.trim_start_matches('+').parse().ok()?) };
+    Some((ordinal, weekday))
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("\\n", "\n").replace("\\N", "\n").replace("\\,", ",").replace("\\;", ";").replace("\\\\", "\\")
+}
+
+// Candidate local start times in the period `offset` frequency units after the first occurrence, in order,
+// or None once the period is outside the dates chrono can represent
+fn period_candidates(rule: &RecurrenceRule, first: NaiveDateTime, offset: i64) -> Option<Vec<NaiveDateTime>> {
+    let date = first.date();
+    let in_month = |day: &NaiveDate| rule.by_month.is_empty() || rule.by_month.contains(&day.month());
+
+    let mut dates: Vec<NaiveDate> = match rule.frequency {
+        Frequency::Daily => {
+            let day = date.checked_add_signed(Duration::try_days(offset)?)?;
+            let matches = in_month(&day)
+                && (rule.by_day.is_empty() || rule.by_day.iter().any(|(_, weekday)| *weekday == day.weekday()))
+                && (rule.by_month_day.is_empty()
+                    || rule.by_month_day.iter().any(|month_day_value| month_day(day.year(), day.month(), *month_day_value) == Some(day)));
+            if matches { vec![day] } else { Vec::new() }
+        }
+        Frequency::Weekly => {
+            let days: Vec<NaiveDate> = if rule.by_day.is_empty() {
+                vec![date.checked_add_signed(Duration::try_weeks(offset)?)?]
+            } else {
+                let days_into_week = (7 + date.weekday().num_days_from_monday() - rule.week_start.num_days_from_monday()) % 7;
+                let week_start = date.checked_sub_signed(Duration::days(days_into_week as i64))?
+                    .checked_add_signed(Duration::try_weeks(offset)?)?;
+                (0..7)
+                    .filter_map(|day| week_start.checked_add_signed(Duration::days(day)))
+                    .filter(|day| rule.by_day.iter().any(|(_, weekday)| *weekday == day.weekday()))
+                    .collect()
+            };
+            days.into_iter().filter(in_month).collect()
+        }
+        Frequency::Monthly => {
+            let months = date.year() as i64 * 12 + date.month0() as i64 + offset;
+            let (year, month) = (i32::try_from(months / 12).ok()?, (months % 12) as u32 + 1);
+            NaiveDate::from_ymd_opt(year, month, 1)?;
+            if rule.by_month.is_empty() || rule.by_month.contains(&month) {
+                month_dates(rule, year, month, date.day())
+            } else {
+                Vec::new()
+            }
+        }
+        Frequency::Yearly => {
+            let year = date.year().checked_add(i32::try_from(offset).ok()?)?;
+            NaiveDate::from_ymd_opt(year, 1, 1)?;
+            if rule.by_month.is_empty() && rule.by_month_day.is_empty() && !rule.by_day.is_empty() {
+                // Ordinals count through the whole year, e.g. 20MO
+                let (first_day, last_day) = (NaiveDate::from_ymd_opt(year, 1, 1), NaiveDate::from_ymd_opt(year, 12, 31));
+                match (first_day, last_day) {
+                    (Some(first_day), Some(last_day)) => rule.by_day.iter()
+                        .flat_map(|(ordinal, weekday)| weekdays_between(first_day, last_day, *ordinal, *weekday))
+                        .collect(),
+                    _ => Vec::new(),
+                }
+            } else if rule.by_month.is_empty() {
+                month_dates(rule, year, date.month(), date.day())
+            } else {
+                rule.by_month.iter().flat_map(|month| month_dates(rule, year, *month, date.day())).collect()
+            }
+        }
+    };
+
+    dates.sort();
+    dates.dedup();
+    if !rule.by_set_pos.is_empty() {
+        dates = set_positions(&dates, &rule.by_set_pos);
+    }
+
+    Some(dates.into_iter().map(|date| date.and_time(first.time())).collect())
+}
+
+// BYMONTHDAY and BYDAY narrow each other when both are given
+fn month_dates(rule: &RecurrenceRule, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+    let by_month_day: Vec<NaiveDate> = rule.by_month_day.iter().filter_map(|day| month_day(year, month, *day)).collect();
+    let by_day: Vec<NaiveDate> = match (NaiveDate::from_ymd_opt(year, month, 1), month_day(year, month, -1)) {
+        (Some(first_day), Some(last_day)) => rule.by_day.iter()
+            .flat_map(|(ordinal, weekday)| weekdays_between(first_day, last_day, *ordinal, *weekday))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    match (rule.by_month_day.is_empty(), rule.by_day.is_empty()) {
+        (true, true) => NaiveDate::from_ymd_opt(year, month, default_day).into_iter().collect(),
+        (false, true) => by_month_day,
+        (true, false) => by_day,
+        (false, false) => by_month_day.into_iter().filter(|day| by_day.contains(day)).collect(),
+    }
+}
+
+fn month_day(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    if day > 0 {
+        NaiveDate::from_ymd_opt(year, month, day as u32)
+    } else {
+        let next_month = if month == 12 { NaiveDate::from_ymd_opt(year + 1, 1, 1) } else { NaiveDate::from_ymd_opt(year, month + 1, 1) }?;
+        let date = next_month + Duration::days(day as i64);
+        (date.month() == month).then_some(date)
+    }
+}
+
+fn weekdays_between(first_day: NaiveDate, last_day: NaiveDate, ordinal: Option<i32>, weekday: Weekday) -> Vec<NaiveDate> {
+    let days: Vec<NaiveDate> = first_day.iter_days()
+        .take_while(|day| *day <= last_day)
+        .filter(|day| day.weekday() == weekday)
+        .collect();
+
+    match ordinal {
+        None => days,
+        Some(n) => set_positions(&days, &[n]),
+    }
+}
+
+fn set_positions(dates: &[NaiveDate], positions: &[i32]) -> Vec<NaiveDate> {
+    let mut picked: Vec<NaiveDate> = positions.iter()
+        .filter_map(|position| {
+            let index = if *position > 0 { *position as usize - 1 } else { dates.len().checked_sub(position.unsigned_abs() as usize)? };
+            dates.get(index).copied()
+        })
+        .collect();
+    picked.sort();
+    picked.dedup();
+    picked
+}
+
+// Local times in a DST gap move forward by the gap, ambiguous ones take the first instant
+fn resolve(local: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local).earliest() {
+        Some(instant) => instant.with_timezone(&Utc),
+        None => local.checked_add_signed(Duration::hours(1))
+            .and_then(|local| tz.from_local_datetime(&local).earliest())
+            .map(|instant| instant.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_weekly_event_keeps_local_time_across_dst() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:standup\r\nDTSTART;TZID=Europe/Berlin:20240318T090000\r\n\
+                   DTEND;TZID=Europe/Berlin:20240318T093000\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4\r\n\
+                   EXDATE;TZID=Europe/Berlin:20240320T090000\r\nBEGIN:VALARM\r\nTRIGGER:-PT5M\r\nEND:VALARM\r\n\
+                   END:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let events = parse_events(ics);
+        let busy = busy_intervals(&events, utc("2024-03-01T00:00:00Z"), utc("2024-04-30T00:00:00Z"));
+
+        // Berlin moves to summer time on 31 March
+        assert_eq!(busy, vec![
+            (utc("2024-03-18T08:00:00Z"), utc("2024-03-18T08:30:00Z")),
+            (utc("2024-03-25T08:00:00Z"), utc("2024-03-25T08:30:00Z")),
+            (utc("2024-03-27T08:00:00Z"), utc("2024-03-27T08:30:00Z")),
+        ]);
+    }
+
+    #[test]
+    fn test_edited_and_free_occurrences() {
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:review\nDTSTART:20240102T150000Z\nDURATION:PT1H\n\
+                   RRULE:FREQ=MONTHLY;BYDAY=1TU;UNTIL=20240331T000000Z\nEND:VEVENT\n\
+                   BEGIN:VEVENT\nUID:review\nRECURRENCE-ID:20240206T150000Z\nDTSTART:20240207T100000Z\n\
+                   DTEND:20240207T110000Z\nEND:VEVENT\n\
+                   BEGIN:VEVENT\nUID:holiday\nDTSTART;VALUE=DATE:20240105\nTRANSP:TRANSPARENT\nEND:VEVENT\nEND:VCALENDAR\n";
+
+        let events = parse_events(ics);
+        assert_eq!(events.len(), 3);
+
+        let busy = busy_intervals(&events, utc("2024-01-01T00:00:00Z"), utc("2024-06-01T00:00:00Z"));
+        assert_eq!(busy, vec![
+            (utc("2024-01-02T15:00:00Z"), utc("2024-01-02T16:00:00Z")),
+            (utc("2024-02-07T10:00:00Z"), utc("2024-02-07T11:00:00Z")),
+            (utc("2024-03-05T15:00:00Z"), utc("2024-03-05T16:00:00Z")),
+        ]);
+    }
+
+    #[test]
+    fn test_second_tuesday_except_holidays_with_extra_date() {
+        let rule: RecurrenceRule = "FREQ=MONTHLY;BYDAY=2TU;COUNT=5".parse().unwrap();
+        let start = IcalTime::Local(NaiveDate::from_ymd_opt(2024, 10, 8).unwrap().and_hms_opt(18, 0, 0).unwrap(), Tz::America__New_York);
+
+        let starts = expand(
+            &start,
+            Some(&rule),
+            &[utc("2024-12-17T23:00:00Z")],
+            &[utc("2024-12-10T23:00:00Z")],
+            utc("2025-12-31T00:00:00Z"),
+        );
+
+        // COUNT includes the excluded December date; November is after the clocks go back
+        assert_eq!(starts, vec![
+            utc("2024-10-08T22:00:00Z"),
+            utc("2024-11-12T23:00:00Z"),
+            utc("2024-12-17T23:00:00Z"),
+            utc("2025-01-14T23:00:00Z"),
+            utc("2025-02-11T23:00:00Z"),
+        ]);
+    }
+
+    #[test]
+    fn test_set_positions_months_and_week_start() {
+        // Last weekday of each quarter's final month
+        let rule: RecurrenceRule = "FREQ=MONTHLY;BYMONTH=3,6,9,12;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1".parse().unwrap();
+        let start = IcalTime::Utc(utc("2024-03-29T09:00:00Z"));
+        let starts = expand(&start, Some(&rule), &[], &[], utc("2025-01-01T00:00:00Z"));
+        assert_eq!(starts, vec![
+            utc("2024-03-29T09:00:00Z"),
+            utc("2024-06-28T09:00:00Z"),
+            utc("2024-09-30T09:00:00Z"),
+            utc("2024-12-31T09:00:00Z"),
+        ]);
+
+        // Yearly, the position counts through the whole year's set
+        let rule: RecurrenceRule = "FREQ=YEARLY;BYMONTH=3,6,9,12;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1".parse().unwrap();
+        let starts = expand(&start, Some(&rule), &[], &[], utc("2025-01-01T00:00:00Z"));
+        assert_eq!(starts, vec![utc("2024-03-29T09:00:00Z"), utc("2024-12-31T09:00:00Z")]);
+
+        // With weeks starting on Sunday, the Sunday belongs to the week after the Tuesday
+        let rule: RecurrenceRule = "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,SU;WKST=SU;COUNT=4".parse().unwrap();
+        let starts = expand(&IcalTime::Utc(utc("2024-08-06T09:00:00Z")), Some(&rule), &[], &[], utc("2025-01-01T00:00:00Z"));
+        assert_eq!(starts, vec![
+            utc("2024-08-06T09:00:00Z"),
+            utc("2024-08-18T09:00:00Z"),
+            utc("2024-08-20T09:00:00Z"),
+            utc("2024-09-01T09:00:00Z"),
+        ]);
+    }
+
+    #[test]
+    fn test_rrule_round_trip_and_validation() {
+        let text = "FREQ=MONTHLY;INTERVAL=2;UNTIL=20250101T000000Z;BYDAY=-1FR;BYMONTH=1,7;WKST=SU";
+        let rule: RecurrenceRule = text.parse().unwrap();
+        assert_eq!(rule.to_string(), text);
+        assert_eq!(format!("RRULE:{}", rule).parse::<RecurrenceRule>().unwrap(), rule);
+
+        assert!("FREQ=HOURLY".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;BYHOUR=9".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;COUNT=3;UNTIL=20250101T000000Z".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=MONTHLY;BYMONTHDAY=32".parse::<RecurrenceRule>().is_err());
+        assert!("INTERVAL=2".parse::<RecurrenceRule>().is_err());
+    }
+
+    #[test]
+    fn test_huge_intervals_stop_at_the_end_of_the_calendar() {
+        assert!("FREQ=DAILY;INTERVAL=100000;BYMONTH=2;BYMONTHDAY=30".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;INTERVAL=1000".parse::<RecurrenceRule>().is_ok());
+
+        // Rules built in code aren't capped, but expanding them must not overflow
+        let start = IcalTime::Utc(utc("2024-01-01T09:00:00Z"));
+        for frequency in [Frequency::Daily, Frequency::Weekly, Frequency::Monthly, Frequency::Yearly] {
+            let mut rule = RecurrenceRule::new(frequency);
+            rule.interval = u32::MAX;
+            rule.by_month = vec![2];
+            rule.by_month_day = vec![30];
+            let starts = expand(&start, Some(&rule), &[], &[], DateTime::<Utc>::MAX_UTC);
+            assert_eq!(starts, vec![utc("2024-01-01T09:00:00Z")]);
+        }
+    }
+}
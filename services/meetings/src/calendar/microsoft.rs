@@ -0,0 +1,267 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+
+use linkwithmentor_common::AppError;
+use crate::models::{CalendarEvent, CalendarProvider};
+
+use super::{
+    oauth::OAuthClient, CalendarClient, ConnectionCredentials, ExternalEvent, ProviderConnection,
+    SyncChanges, SyncWindow,
+};
+
+const GRAPH_URL: &str = "https://graph.microsoft.com/v1.0";
+const MAX_SYNC_PAGES: usize = 40;
+
+/// Outlook and Microsoft 365 calendars through Microsoft Graph.
+#[derive(Clone)]
+pub struct MicrosoftCalendarClient {
+    client: Client,
+    oauth: OAuthClient,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeltaPage {
+    #[serde(default)]
+    value: Vec<GraphEvent>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+    #[serde(rename = "@odata.deltaLink")]
+    delta_link: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphEvent {
+    id: String,
+    #[serde(rename = "@removed")]
+    removed: Option<serde_json::Value>,
+    is_cancelled: Option<bool>,
+    show_as: Option<String>,
+    start: Option<GraphTime>,
+    end: Option<GraphTime>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphTime {
+    // Naive, in the zone requested with the Prefer header (UTC)
+    date_time: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedEvent {
+    id: String,
+}
+
+impl MicrosoftCalendarClient {
+    pub fn new(client: Client, client_id: &str, client_secret: &str, tenant: &str, redirect_uri: &str) -> Self {
+        let oauth = OAuthClient::new(
+            client.clone(),
+            client_id,
+            client_secret,
+            &format!("https://login.microsoftonline.com/{}/oauth2/v2.0/authorize", tenant),
+            &format!("https://login.microsoftonline.com/{}/oauth2/v2.0/token", tenant),
+            "offline_access Calendars.ReadWrite",
+            redirect_uri,
+            Vec::new(),
+        );
+
+        Self { client, oauth }
+    }
+
+    fn calendar_path(calendar_id: &str) -> String {
+        if calendar_id == "primary" {
+            "me/calendar".to_string()
+        } else {
+            format!("me/calendars/{}", calendar_id)
+        }
+    }
+
+    fn access_token(connection: &ProviderConnection) -> Result<&str, AppError> {
+        connection.credentials.access_token.as_deref()
+            .ok_or_else(|| AppError::ExternalService("Outlook connection is not authorized".to_string()))
+    }
+
+    fn event_body(event: &CalendarEvent) -> serde_json::Value {
+        json!({
+            "subject": event.title,
+            "body": {
+                "contentType": "text",
+                "content": event.description.clone().unwrap_or_default(),
+            },
+            "start": {
+                "dateTime": event.start_time.naive_utc().format("%Y-%m-%dT%H:%M:%S").to_string(),
+                "timeZone": "UTC",
+            },
+            "end": {
+                "dateTime": event.end_time.naive_utc().format("%Y-%m-%dT%H:%M:%S").to_string(),
+                "timeZone": "UTC",
+            },
+            "location": { "displayName": event.location.clone().unwrap_or_default() },
+        })
+    }
+}
+
+#[async_trait]
+impl CalendarClient for MicrosoftCalendarClient {
+    async fn authorize(&self, credentials: &mut ConnectionCredentials) -> Result<bool, AppError> {
+        self.oauth.refresh(credentials).await
+    }
+
+    async fn sync(
+        &self,
+        connection: &ProviderConnection,
+        sync_token: Option<&str>,
+        window: &SyncWindow,
+    ) -> Result<SyncChanges, AppError> {
+        let access_token = Self::access_token(connection)?;
+        // The delta link is a full URL that already encodes the window
+        let initial_url = format!(
+            "{}/{}/calendarView/delta?startDateTime={}&endDateTime={}",
+            GRAPH_URL,
+            Self::calendar_path(&connection.calendar_id),
+            window.start.format("%Y-%m-%dT%H:%M:%SZ"),
+            window.end.format("%Y-%m-%dT%H:%M:%SZ"),
+        );
+
+        let mut delta_link = sync_token.map(str::to_string);
+        'sync: loop {
+            let mut changes = SyncChanges {
+                full: delta_link.is_none(),
+                ..Default::default()
+            };
+            let mut url = delta_link.clone().unwrap_or_else(|| initial_url.clone());
+
+            for _ in 0..MAX_SYNC_PAGES {
+                let response = self.client
+                    .get(&url)
+                    .bearer_auth(access_token)
+                    .header("Prefer", "outlook.timezone=\"UTC\", odata.maxpagesize=100")
+                    .send()
+                    .await
+                    .map_err(|e| AppError::ExternalService(format!("Outlook sync failed: {}", e)))?;
+
+                if response.status() == StatusCode::GONE && delta_link.is_some() {
+                    delta_link = None;
+                    continue 'sync;
+                }
+                if !response.status().is_success() {
+                    return Err(AppError::ExternalService(format!("Outlook sync rejected ({})", response.status())));
+                }
+
+                let page: DeltaPage = response.json().await
+                    .map_err(|e| AppError::ExternalService(format!("Invalid Outlook response: {}", e)))?;
+
+                for event in page.value {
+                    if event.removed.is_some() || event.is_cancelled == Some(true) {
+                        changes.removed.push(event.id);
+                        continue;
+                    }
+
+                    let free = matches!(event.show_as.as_deref(), Some("free") | Some("workingElsewhere"));
+                    let busy = match (&event.start, &event.end) {
+                        (Some(start), Some(end)) if !free => match (start.to_utc(), end.to_utc()) {
+                            (Some(start), Some(end)) => vec![(start, end)],
+                            _ => Vec::new(),
+                        },
+                        _ => Vec::new(),
+                    };
+
+                    changes.events.push(ExternalEvent { external_id: event.id, busy });
+                }
+
+                if let Some(link) = page.delta_link {
+                    changes.sync_token = Some(link);
+                    return Ok(changes);
+                }
+                match page.next_link {
+                    Some(link) => url = link,
+                    None => return Ok(changes),
+                }
+            }
+
+            tracing::warn!(
+                "Outlook sync for connection {} stopped after {} pages",
+                connection.connection_id, MAX_SYNC_PAGES
+            );
+            return Ok(changes);
+        }
+    }
+
+    async fn put_event(
+        &self,
+        connection: &ProviderConnection,
+        event: &CalendarEvent,
+        external_id: Option<&str>,
+    ) -> Result<String, AppError> {
+        let access_token = Self::access_token(connection)?;
+        let body = Self::event_body(event);
+
+        if let Some(external_id) = external_id {
+            let response = self.client
+                .patch(format!("{}/me/events/{}", GRAPH_URL, external_id))
+                .bearer_auth(access_token)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| AppError::ExternalService(format!("Outlook request failed: {}", e)))?;
+
+            if response.status() != StatusCode::NOT_FOUND {
+                if !response.status().is_success() {
+                    return Err(AppError::ExternalService(format!("Outlook request rejected ({})", response.status())));
+                }
+                return Ok(external_id.to_string());
+            }
+        }
+
+        let response = self.client
+            .post(format!("{}/{}/events", GRAPH_URL, Self::calendar_path(&connection.calendar_id)))
+            .bearer_auth(access_token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Outlook request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!("Outlook request rejected ({})", response.status())));
+        }
+
+        let created: CreatedEvent = response.json().await
+            .map_err(|e| AppError::ExternalService(format!("Invalid Outlook response: {}", e)))?;
+        Ok(created.id)
+    }
+
+    async fn delete_event(&self, connection: &ProviderConnection, external_id: &str) -> Result<(), AppError> {
+        let response = self.client
+            .delete(format!("{}/me/events/{}", GRAPH_URL, external_id))
+            .bearer_auth(Self::access_token(connection)?)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Outlook request failed: {}", e)))?;
+
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            return Err(AppError::ExternalService(format!("Outlook request rejected ({})", response.status())));
+        }
+
+        Ok(())
+    }
+
+    fn provider(&self) -> CalendarProvider {
+        CalendarProvider::Outlook
+    }
+
+    fn oauth(&self) -> Option<&OAuthClient> {
+        Some(&self.oauth)
+    }
+}
+
+impl GraphTime {
+    fn to_utc(&self) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(&self.date_time, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|naive| Utc.from_utc_datetime(&naive))
+    }
+}
@@ -0,0 +1,1097 @@
+pub mod caldav;
+pub mod google;
+pub mod ical;
+pub mod microsoft;
+pub mod oauth;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use icalendar::{Calendar, Event, EventLike, Component};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use linkwithmentor_common::{AppError, RedisService};
+use crate::{
+    availability,
+    config::CalendarConfig,
+    encryption::EncryptionService,
+    models::{
+        CalendarConnection, CalendarEvent, CalendarProvider, ConnectCalendarRequest,
+        CalendarAuthorizationResponse, CalendarImport, SessionResponse,
+    },
+};
+
+use self::{
+    caldav::CalDavClient,
+    google::GoogleCalendarClient,
+    microsoft::MicrosoftCalendarClient,
+    oauth::OAuthClient,
+};
+
+const OAUTH_STATE_TTL_SECONDS: u64 = 600;
+// Uploaded .ics files; exports of a busy calendar run to a few hundred KiB
+const MAX_IMPORT_BYTES: usize = 2 * 1024 * 1024;
+// Recurring events in an import are expanded this far ahead
+const IMPORT_HORIZON_DAYS: i64 = 365;
+
+/// Secrets for one calendar connection, stored encrypted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionCredentials {
+    pub access_token: Option<String>,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    // CalDAV basic auth, usually an app-specific password
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProviderConnection {
+    pub connection_id: Uuid,
+    pub user_id: Uuid,
+    pub provider: CalendarProvider,
+    // Provider calendar id, or the collection URL for CalDAV
+    pub calendar_id: String,
+    pub credentials: ConnectionCredentials,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SyncWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+pub struct SyncChanges {
+    // The provider sent everything rather than changes since the last token
+    pub full: bool,
+    pub events: Vec<ExternalEvent>,
+    pub removed: Vec<String>,
+    pub sync_token: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ExternalEvent {
+    pub external_id: String,
+    // Empty for free, transparent or out-of-window events
+    pub busy: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+#[async_trait]
+pub trait CalendarClient: Send + Sync {
+    /// Refreshes credentials that are about to expire. Returns true if they
+    /// changed and have to be stored again.
+    async fn authorize(&self, credentials: &mut ConnectionCredentials) -> Result<bool, AppError>;
+
+    /// Changes since `sync_token`, or everything in the window when there is
+    /// no token or the provider no longer accepts it.
+    async fn sync(
+        &self,
+        connection: &ProviderConnection,
+        sync_token: Option<&str>,
+        window: &SyncWindow,
+    ) -> Result<SyncChanges, AppError>;
+
+    /// Creates or replaces a session's event and returns its external id.
+    async fn put_event(
+        &self,
+        connection: &ProviderConnection,
+        event: &CalendarEvent,
+        external_id: Option<&str>,
+    ) -> Result<String, AppError>;
+
+    async fn delete_event(&self, connection: &ProviderConnection, external_id: &str) -> Result<(), AppError>;
+
+    fn provider(&self) -> CalendarProvider;
+
+    fn oauth(&self) -> Option<&OAuthClient> {
+        None
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ConnectionRow {
+    connection_id: Uuid,
+    user_id: Uuid,
+    provider: String,
+    calendar_id: String,
+    display_name: Option<String>,
+    credentials: String,
+    sync_token: Option<String>,
+    last_synced_at: Option<DateTime<Utc>>,
+    last_full_sync_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl ConnectionRow {
+    fn provider(&self) -> Result<CalendarProvider, AppError> {
+        self.provider.parse()
+            .map_err(|_| AppError::Internal(format!("Unknown calendar provider {}", self.provider)))
+    }
+
+    fn into_response(self) -> Result<CalendarConnection, AppError> {
+        Ok(CalendarConnection {
+            connection_id: self.connection_id,
+            provider: self.provider()?,
+            calendar_id: self.calendar_id,
+            display_name: self.display_name,
+            last_synced_at: self.last_synced_at,
+            last_error: self.last_error,
+            created_at: self.created_at,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct CalendarService {
+    config: CalendarConfig,
+    db_pool: PgPool,
+    redis_service: RedisService,
+    encryption: EncryptionService,
+    clients: HashMap<CalendarProvider, Arc<dyn CalendarClient>>,
+}
+
+impl CalendarService {
+    pub fn new(db_pool: PgPool, redis_service: RedisService, config: &CalendarConfig) -> Result<Self, AppError> {
+        let http_client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        let mut clients: HashMap<CalendarProvider, Arc<dyn CalendarClient>> = HashMap::new();
+
+        if config.enable_caldav_calendar {
+            // iCloud only speaks CalDAV
+            for provider in [CalendarProvider::CalDav, CalendarProvider::Apple] {
+                clients.insert(provider, Arc::new(CalDavClient::new(
+                    provider,
+                    config.caldav_allowed_hosts.clone(),
+                    config.caldav_allow_private_hosts,
+                )));
+            }
+        }
+
+        if config.enable_google_calendar {
+            match (&config.google_client_id, &config.google_client_secret) {
+                (Some(client_id), Some(client_secret)) => {
+                    clients.insert(CalendarProvider::Google, Arc::new(GoogleCalendarClient::new(
+                        http_client.clone(),
+                        client_id,
+                        client_secret,
+                        &config.oauth_redirect_uri,
+                    )));
+                }
+                _ => tracing::warn!("Google Calendar is enabled but no OAuth client is configured"),
+            }
+        }
+
+        if config.enable_outlook_calendar {
+            match (&config.outlook_client_id, &config.outlook_client_secret) {
+                (Some(client_id), Some(client_secret)) => {
+                    clients.insert(CalendarProvider::Outlook, Arc::new(MicrosoftCalendarClient::new(
+                        http_client.clone(),
+                        client_id,
+                        client_secret,
+                        &config.outlook_tenant,
+                        &config.oauth_redirect_uri,
+                    )));
+                }
+                _ => tracing::warn!("Outlook Calendar is enabled but no OAuth client is configured"),
+            }
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            db_pool,
+            redis_service,
+            encryption: EncryptionService::new(&config.credentials_key)?,
+            clients,
+        })
+    }
+
+    pub async fn initialize(&self) -> Result<(), AppError> {
+        if !self.clients.is_empty() {
+            self.start_sync_task().await?;
+        }
+
+        tracing::info!("Calendar service initialized with {} providers", self.clients.len());
+        Ok(())
+    }
+
+    // iCalendar generation
+    pub async fn generate_ical_for_session(&self, session: &SessionResponse) -> Result<String, AppError> {
+        if !self.config.enable_ical_export {
+            return Err(AppError::BadRequest("iCalendar export is disabled".to_string()));
+        }
+
+        let mut calendar = Calendar::new();
+        calendar.name(&self.config.calendar_name);
+        calendar.timezone(&self.config.timezone);
+
+        let event = Event::new()
+            .uid(&format!("session-{}", session.session_id))
+            .summary(&session.title)
+            .description(&session.description.clone().unwrap_or_default())
+            .starts(session.scheduled_start)
+            .ends(session.scheduled_end)
+            .location("LinkWithMentor Platform")
+            .done();
+
+        calendar.push(event);
+
+        Ok(calendar.to_string())
+    }
+
+    pub async fn generate_ical_for_sessions(&self, sessions: &[SessionResponse]) -> Result<String, AppError> {
+        if !self.config.enable_ical_export {
+            return Err(AppError::BadRequest("iCalendar export is disabled".to_string()));
+        }
+
+        let mut calendar = Calendar::new();
+        calendar.name(&self.config.calendar_name);
+        calendar.timezone(&self.config.timezone);
+
+        for session in sessions {
+            let event = Event::new()
+                .uid(&format!("session-{}", session.session_id))
+                .summary(&session.title)
+                .description(&session.description.clone().unwrap_or_default())
+                .starts(session.scheduled_start)
+                .ends(session.scheduled_end)
+                .location("LinkWithMentor Platform")
+                .done();
+
+            calendar.push(event);
+        }
+
+        Ok(calendar.to_string())
+    }
+
+    // Connections
+    pub async fn authorization_url(
+        &self,
+        user_id: Uuid,
+        provider: CalendarProvider,
+    ) -> Result<CalendarAuthorizationResponse, AppError> {
+        let oauth = self.client(provider)?.oauth()
+            .ok_or_else(|| AppError::BadRequest(format!("{:?} calendars connect with a username and password", provider)))?;
+
+        let state = Uuid::new_v4().to_string();
+        self.redis_service
+            .cache_set(&format!("calendar_oauth:{}", state), &user_id, OAUTH_STATE_TTL_SECONDS)
+            .await?;
+
+        Ok(CalendarAuthorizationResponse {
+            authorization_url: oauth.authorization_url(&state)?,
+            state,
+        })
+    }
+
+    pub async fn connect_calendar(
+        &self,
+        user_id: Uuid,
+        request: ConnectCalendarRequest,
+    ) -> Result<CalendarConnection, AppError> {
+        let client = self.client(request.provider)?;
+
+        let (credentials, calendar_id) = match client.oauth() {
+            Some(oauth) => {
+                let (code, state) = match (&request.authorization_code, &request.state) {
+                    (Some(code), Some(state)) => (code, state),
+                    _ => return Err(AppError::BadRequest("Authorization code and state are required".to_string())),
+                };
+
+                let key = format!("calendar_oauth:{}", state);
+                let owner: Option<Uuid> = self.redis_service.cache_get(&key).await?;
+                if owner != Some(user_id) {
+                    return Err(AppError::BadRequest("Invalid or expired authorization state".to_string()));
+                }
+                self.redis_service.cache_delete(&key).await?;
+
+                let credentials = oauth.exchange_code(code).await?;
+                let calendar_id = request.calendar_id.clone().unwrap_or_else(|| "primary".to_string());
+                (credentials, calendar_id)
+            }
+            None => {
+                let calendar_url = request.calendar_url.clone()
+                    .ok_or_else(|| AppError::BadRequest("Calendar URL is required".to_string()))?;
+                let url = url::Url::parse(&calendar_url)
+                    .map_err(|e| AppError::BadRequest(format!("Invalid calendar URL: {}", e)))?;
+                caldav::check_calendar_url(
+                    &self.config.caldav_allowed_hosts,
+                    self.config.caldav_allow_private_hosts,
+                    &url,
+                )?;
+
+                let credentials = ConnectionCredentials {
+                    username: request.username.clone(),
+                    password: request.password.clone(),
+                    ..Default::default()
+                };
+                (credentials, calendar_url)
+            }
+        };
+
+        let query = r#"
+            INSERT INTO calendar_connections (user_id, provider, calendar_id, display_name, credentials)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id, provider, calendar_id) DO UPDATE
+            SET credentials = EXCLUDED.credentials,
+                display_name = COALESCE(EXCLUDED.display_name, calendar_connections.display_name),
+                sync_token = NULL,
+                last_error = NULL,
+                updated_at = NOW()
+            RETURNING connection_id
+        "#;
+
+        let connection_id: Uuid = sqlx::query_scalar(query)
+            .bind(user_id)
+            .bind(request.provider.as_str())
+            .bind(&calendar_id)
+            .bind(&request.display_name)
+            .bind(self.encryption.encrypt_credentials(&credentials)?)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save calendar connection: {}", e)))?;
+
+        // The first sync doubles as a check that the calendar is reachable
+        if let Err(e) = self.sync_connection(connection_id).await {
+            self.remove_connection(connection_id).await?;
+            return Err(e);
+        }
+
+        self.load_connection(connection_id).await?.into_response()
+    }
+
+    pub async fn list_connections(&self, user_id: Uuid) -> Result<Vec<CalendarConnection>, AppError> {
+        let query = "SELECT * FROM calendar_connections WHERE user_id = $1 ORDER BY created_at";
+
+        let rows: Vec<ConnectionRow> = sqlx::query_as(query)
+            .bind(user_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar connections: {}", e)))?;
+
+        rows.into_iter().map(ConnectionRow::into_response).collect()
+    }
+
+    pub async fn disconnect_calendar(&self, user_id: Uuid, connection_id: Uuid) -> Result<(), AppError> {
+        let row = self.load_connection(connection_id).await?;
+        if row.user_id != user_id {
+            return Err(AppError::NotFound("Calendar connection not found".to_string()));
+        }
+
+        // Take our sessions back out of the user's calendar before forgetting it
+        let query = "SELECT external_event_id FROM calendar_event_links WHERE connection_id = $1";
+        let external_ids: Vec<String> = sqlx::query_scalar(query)
+            .bind(connection_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar events: {}", e)))?;
+
+        if !external_ids.is_empty() {
+            match self.authorized_connection(&row).await {
+                Ok((client, connection)) => {
+                    for external_id in external_ids {
+                        if let Err(e) = client.delete_event(&connection, &external_id).await {
+                            tracing::warn!("Failed to delete event {} from connection {}: {}", external_id, connection_id, e);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to authorize connection {} for cleanup: {}", connection_id, e),
+            }
+        }
+
+        self.remove_connection(connection_id).await
+    }
+
+    // One-off .ics imports, e.g. out-of-office blocks from another tool
+    pub async fn import_ics(&self, user_id: Uuid, name: Option<String>, ics: &str) -> Result<CalendarImport, AppError> {
+        if ics.len() > MAX_IMPORT_BYTES {
+            return Err(AppError::BadRequest("Calendar file is too large".to_string()));
+        }
+        if !ics.contains("BEGIN:VCALENDAR") {
+            return Err(AppError::BadRequest("Not an iCalendar file".to_string()));
+        }
+
+        let events = ical::parse_events(ics);
+        if events.is_empty() {
+            return Err(AppError::BadRequest("Calendar file has no events".to_string()));
+        }
+
+        let now = Utc::now();
+        let busy_until = now + Duration::days(IMPORT_HORIZON_DAYS);
+        let intervals = ical::busy_intervals(&events, now - Duration::days(1), busy_until);
+        let (starts, ends): (Vec<DateTime<Utc>>, Vec<DateTime<Utc>>) = intervals.into_iter().unzip();
+
+        let import = CalendarImport {
+            import_id: Uuid::new_v4(),
+            name: name.filter(|name| !name.trim().is_empty()).unwrap_or_else(|| "Imported calendar".to_string()),
+            event_count: events.len() as u32,
+            busy_period_count: starts.len() as u32,
+            busy_until,
+            created_at: now,
+        };
+
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let query = r#"
+            INSERT INTO calendar_imports (
+                import_id, user_id, name, event_count, busy_period_count, busy_until, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#;
+
+        sqlx::query(query)
+            .bind(import.import_id)
+            .bind(user_id)
+            .bind(&import.name)
+            .bind(import.event_count as i32)
+            .bind(import.busy_period_count as i32)
+            .bind(import.busy_until)
+            .bind(import.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create calendar import: {}", e)))?;
+
+        let query = r#"
+            INSERT INTO imported_busy_times (import_id, user_id, starts_at, ends_at)
+            SELECT $1, $2, starts_at, ends_at
+            FROM UNNEST($3::timestamptz[], $4::timestamptz[]) AS busy(starts_at, ends_at)
+        "#;
+
+        sqlx::query(query)
+            .bind(import.import_id)
+            .bind(user_id)
+            .bind(&starts)
+            .bind(&ends)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to store imported busy times: {}", e)))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        tracing::info!("Imported {} busy periods for user {}", import.busy_period_count, user_id);
+        Ok(import)
+    }
+
+    pub async fn list_imports(&self, user_id: Uuid) -> Result<Vec<CalendarImport>, AppError> {
+        let query = r#"
+            SELECT import_id, name, event_count, busy_period_count, busy_until, created_at
+            FROM calendar_imports
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+        "#;
+
+        let rows = sqlx::query_as::<_, (Uuid, String, i32, i32, DateTime<Utc>, DateTime<Utc>)>(query)
+            .bind(user_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar imports: {}", e)))?;
+
+        Ok(rows.into_iter()
+            .map(|(import_id, name, event_count, busy_period_count, busy_until, created_at)| CalendarImport {
+                import_id,
+                name,
+                event_count: event_count as u32,
+                busy_period_count: busy_period_count as u32,
+                busy_until,
+                created_at,
+            })
+            .collect())
+    }
+
+    pub async fn delete_import(&self, user_id: Uuid, import_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM calendar_imports WHERE import_id = $1 AND user_id = $2")
+            .bind(import_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete calendar import: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Calendar import not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Syncing external calendars
+    pub async fn sync_all_calendars(&self, user_id: Uuid) -> Result<(), AppError> {
+        let query = "SELECT connection_id FROM calendar_connections WHERE user_id = $1";
+        let connection_ids: Vec<Uuid> = sqlx::query_scalar(query)
+            .bind(user_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar connections: {}", e)))?;
+
+        let mut first_error = None;
+        for connection_id in connection_ids {
+            if let Err(e) = self.sync_connection(connection_id).await {
+                tracing::warn!("Failed to sync calendar connection {}: {}", connection_id, e);
+                first_error.get_or_insert(e);
+            }
+        }
+
+        tracing::info!("Synced all calendars for user {}", user_id);
+        first_error.map_or(Ok(()), Err)
+    }
+
+    pub async fn sync_user_connection(&self, user_id: Uuid, connection_id: Uuid) -> Result<CalendarConnection, AppError> {
+        let row = self.load_connection(connection_id).await?;
+        if row.user_id != user_id {
+            return Err(AppError::NotFound("Calendar connection not found".to_string()));
+        }
+
+        self.sync_connection(connection_id).await?;
+        self.load_connection(connection_id).await?.into_response()
+    }
+
+    pub async fn sync_connection(&self, connection_id: Uuid) -> Result<(), AppError> {
+        let row = self.load_connection(connection_id).await?;
+        let result = self.run_sync(&row).await;
+
+        match &result {
+            Ok(_) => {
+                sqlx::query("UPDATE calendar_connections SET last_synced_at = NOW(), last_error = NULL, updated_at = NOW() WHERE connection_id = $1")
+                    .bind(connection_id)
+                    .execute(&self.db_pool)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to update calendar connection: {}", e)))?;
+            }
+            Err(e) => self.record_error(connection_id, e).await,
+        }
+
+        result
+    }
+
+    async fn run_sync(&self, row: &ConnectionRow) -> Result<(), AppError> {
+        let (client, connection) = self.authorized_connection(row).await?;
+
+        let now = Utc::now();
+        let window_days = self.config.sync_window_days as i64;
+        let window = SyncWindow {
+            start: now,
+            end: now + Duration::days(window_days),
+        };
+        // Busy times are expanded inside the window only, so incremental syncs
+        // stop covering the far end as it moves; start over halfway through
+        let stale = row.last_full_sync_at
+            .map_or(true, |synced_at| synced_at < now - Duration::days(window_days / 2));
+        let sync_token = if stale { None } else { row.sync_token.as_deref() };
+
+        let changes = client.sync(&connection, sync_token, &window).await?;
+
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        if changes.full {
+            sqlx::query("DELETE FROM external_busy_times WHERE connection_id = $1")
+                .bind(row.connection_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to clear busy times: {}", e)))?;
+        }
+
+        // Events we pushed for sessions are already blocked by the sessions themselves
+        let own_events: HashSet<String> = sqlx::query_scalar(
+            "SELECT external_event_id FROM calendar_event_links WHERE connection_id = $1"
+        )
+            .bind(row.connection_id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar events: {}", e)))?
+            .into_iter()
+            .collect();
+
+        for event in &changes.events {
+            sqlx::query("DELETE FROM external_busy_times WHERE connection_id = $1 AND external_event_id = $2")
+                .bind(row.connection_id)
+                .bind(&event.external_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update busy times: {}", e)))?;
+
+            if own_events.contains(&event.external_id) {
+                continue;
+            }
+
+            for (starts_at, ends_at) in &event.busy {
+                let query = r#"
+                    INSERT INTO external_busy_times (connection_id, user_id, external_event_id, starts_at, ends_at)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (connection_id, external_event_id, starts_at) DO UPDATE SET ends_at = EXCLUDED.ends_at
+                "#;
+
+                sqlx::query(query)
+                    .bind(row.connection_id)
+                    .bind(row.user_id)
+                    .bind(&event.external_id)
+                    .bind(starts_at)
+                    .bind(ends_at)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to update busy times: {}", e)))?;
+            }
+        }
+
+        for external_id in &changes.removed {
+            sqlx::query("DELETE FROM external_busy_times WHERE connection_id = $1 AND external_event_id = $2")
+                .bind(row.connection_id)
+                .bind(external_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update busy times: {}", e)))?;
+
+            // Deleted on the user's side; the next session update creates it again
+            sqlx::query("DELETE FROM calendar_event_links WHERE connection_id = $1 AND external_event_id = $2")
+                .bind(row.connection_id)
+                .bind(external_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update calendar events: {}", e)))?;
+        }
+
+        let query = r#"
+            UPDATE calendar_connections
+            SET sync_token = $2,
+                last_full_sync_at = CASE WHEN $3 THEN NOW() ELSE last_full_sync_at END
+            WHERE connection_id = $1
+        "#;
+
+        sqlx::query(query)
+            .bind(row.connection_id)
+            .bind(&changes.sync_token)
+            .bind(changes.full)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update calendar connection: {}", e)))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        tracing::debug!(
+            "Synced calendar connection {} ({} changed, {} removed, full: {})",
+            row.connection_id, changes.events.len(), changes.removed.len(), changes.full
+        );
+        Ok(())
+    }
+
+    async fn start_sync_task(&self) -> Result<(), AppError> {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+
+            loop {
+                interval.tick().await;
+
+                // Claiming pushes next_sync_at forward, so each connection is
+                // picked up by one instance only
+                let query = r#"
+                    UPDATE calendar_connections
+                    SET next_sync_at = NOW() + make_interval(secs => $1)
+                    WHERE connection_id IN (
+                        SELECT connection_id FROM calendar_connections
+                        WHERE next_sync_at <= NOW()
+                        ORDER BY next_sync_at
+                        LIMIT 50
+                        FOR UPDATE SKIP LOCKED
+                    )
+                    RETURNING connection_id
+                "#;
+
+                let connection_ids: Vec<Uuid> = match sqlx::query_scalar(query)
+                    .bind(service.config.sync_interval_seconds as f64)
+                    .fetch_all(&service.db_pool)
+                    .await
+                {
+                    Ok(connection_ids) => connection_ids,
+                    Err(e) => {
+                        tracing::error!("Failed to claim calendar connections: {}", e);
+                        continue;
+                    }
+                };
+
+                for connection_id in connection_ids {
+                    if let Err(e) = service.sync_connection(connection_id).await {
+                        tracing::warn!("Failed to sync calendar connection {}: {}", connection_id, e);
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    // Calendar event management
+    pub async fn create_calendar_event(&self, session: &SessionResponse) -> Result<CalendarEvent, AppError> {
+        let calendar_event = self.session_event(session).await?;
+        self.push_event(session, &calendar_event).await?;
+        Ok(calendar_event)
+    }
+
+    pub async fn update_calendar_event(&self, session: &SessionResponse) -> Result<CalendarEvent, AppError> {
+        let calendar_event = self.session_event(session).await?;
+        self.push_event(session, &calendar_event).await?;
+        Ok(calendar_event)
+    }
+
+    pub async fn delete_calendar_event(&self, session_id: Uuid) -> Result<(), AppError> {
+        let query = r#"
+            SELECT c.*, l.external_event_id
+            FROM calendar_event_links l
+            JOIN calendar_connections c ON c.connection_id = l.connection_id
+            WHERE l.session_id = $1
+        "#;
+
+        let links: Vec<(ConnectionRow, String)> = sqlx::query(query)
+            .bind(session_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar events: {}", e)))?
+            .into_iter()
+            .map(|row| {
+                use sqlx::{FromRow, Row};
+                Ok((ConnectionRow::from_row(&row)?, row.try_get("external_event_id")?))
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar events: {}", e)))?;
+
+        for (row, external_id) in links {
+            // Best effort: a provider outage must not block cancelling the session
+            let result = match self.authorized_connection(&row).await {
+                Ok((client, connection)) => client.delete_event(&connection, &external_id).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete session {} from connection {}: {}", session_id, row.connection_id, e);
+                self.record_error(row.connection_id, &e).await;
+            }
+        }
+
+        sqlx::query("DELETE FROM calendar_event_links WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete calendar events: {}", e)))?;
+
+        tracing::info!("Deleted calendar event for session {}", session_id);
+        Ok(())
+    }
+
+    async fn push_event(&self, session: &SessionResponse, event: &CalendarEvent) -> Result<(), AppError> {
+        let query = "SELECT * FROM calendar_connections WHERE user_id = ANY($1)";
+        let rows: Vec<ConnectionRow> = sqlx::query_as(query)
+            .bind(Self::session_user_ids(session))
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar connections: {}", e)))?;
+
+        for row in rows {
+            // Best effort, like deletes: the booking stands even if a calendar is unreachable
+            if let Err(e) = self.push_to_connection(&row, event).await {
+                tracing::warn!("Failed to push session {} to connection {}: {}", event.session_id, row.connection_id, e);
+                self.record_error(row.connection_id, &e).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn push_to_connection(&self, row: &ConnectionRow, event: &CalendarEvent) -> Result<(), AppError> {
+        let (client, connection) = self.authorized_connection(row).await?;
+
+        let query = "SELECT external_event_id FROM calendar_event_links WHERE session_id = $1 AND connection_id = $2";
+        let existing: Option<String> = sqlx::query_scalar(query)
+            .bind(event.session_id)
+            .bind(row.connection_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar event: {}", e)))?;
+
+        let external_id = client.put_event(&connection, event, existing.as_deref()).await?;
+
+        let query = r#"
+            INSERT INTO calendar_event_links (session_id, connection_id, external_event_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (session_id, connection_id) DO UPDATE
+            SET external_event_id = EXCLUDED.external_event_id, updated_at = NOW()
+        "#;
+
+        sqlx::query(query)
+            .bind(event.session_id)
+            .bind(row.connection_id)
+            .bind(&external_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save calendar event: {}", e)))?;
+
+        // A sync may have picked the event up as busy time before it was linked
+        sqlx::query("DELETE FROM external_busy_times WHERE connection_id = $1 AND external_event_id = $2")
+            .bind(row.connection_id)
+            .bind(&external_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update busy times: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Helper methods
+    async fn session_event(&self, session: &SessionResponse) -> Result<CalendarEvent, AppError> {
+        Ok(CalendarEvent {
+            event_id: format!("lwm-session-{}", session.session_id),
+            session_id: session.session_id,
+            title: session.title.clone(),
+            description: session.description.clone(),
+            start_time: session.scheduled_start,
+            end_time: session.scheduled_end,
+            location: Some("LinkWithMentor Platform".to_string()),
+            attendees: self.get_session_attendees(session).await?,
+            calendar_provider: CalendarProvider::ICalendar,
+        })
+    }
+
+    async fn get_session_attendees(&self, session: &SessionResponse) -> Result<Vec<String>, AppError> {
+        let query = "SELECT email FROM users WHERE user_id = ANY($1) ORDER BY email";
+
+        sqlx::query_scalar(query)
+            .bind(Self::session_user_ids(session))
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch attendees: {}", e)))
+    }
+
+    fn session_user_ids(session: &SessionResponse) -> Vec<Uuid> {
+        let mut user_ids: Vec<Uuid> = std::iter::once(session.mentor_id)
+            .chain(session.mentee_id)
+            .chain(session.participants.iter().map(|participant| participant.user_id))
+            .collect();
+        user_ids.sort();
+        user_ids.dedup();
+        user_ids
+    }
+
+    fn client(&self, provider: CalendarProvider) -> Result<Arc<dyn CalendarClient>, AppError> {
+        self.clients.get(&provider)
+            .cloned()
+            .ok_or_else(|| AppError::BadRequest(format!("{:?} calendar integration is not enabled", provider)))
+    }
+
+    async fn authorized_connection(
+        &self,
+        row: &ConnectionRow,
+    ) -> Result<(Arc<dyn CalendarClient>, ProviderConnection), AppError> {
+        let provider = row.provider()?;
+        let client = self.client(provider)?;
+
+        let mut connection = ProviderConnection {
+            connection_id: row.connection_id,
+            user_id: row.user_id,
+            provider,
+            calendar_id: row.calendar_id.clone(),
+            credentials: self.encryption.decrypt_credentials(&row.credentials)?,
+        };
+
+        if client.authorize(&mut connection.credentials).await? {
+            sqlx::query("UPDATE calendar_connections SET credentials = $2, updated_at = NOW() WHERE connection_id = $1")
+                .bind(row.connection_id)
+                .bind(self.encryption.encrypt_credentials(&connection.credentials)?)
+                .execute(&self.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to save calendar credentials: {}", e)))?;
+        }
+
+        Ok((client, connection))
+    }
+
+    async fn load_connection(&self, connection_id: Uuid) -> Result<ConnectionRow, AppError> {
+        sqlx::query_as("SELECT * FROM calendar_connections WHERE connection_id = $1")
+            .bind(connection_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch calendar connection: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Calendar connection not found".to_string()))
+    }
+
+    async fn remove_connection(&self, connection_id: Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM calendar_connections WHERE connection_id = $1")
+            .bind(connection_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete calendar connection: {}", e)))?;
+        Ok(())
+    }
+
+    async fn record_error(&self, connection_id: Uuid, error: &AppError) {
+        let result = sqlx::query("UPDATE calendar_connections SET last_error = $2, updated_at = NOW() WHERE connection_id = $1")
+            .bind(connection_id)
+            .bind(error.to_string())
+            .execute(&self.db_pool)
+            .await;
+
+        if let Err(e) = result {
+            tracing::error!("Failed to record calendar error for {}: {}", connection_id, e);
+        }
+    }
+
+    // Calendar availability checking
+    pub async fn check_availability(
+        &self,
+        user_id: Uuid,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<bool, AppError> {
+        Ok(self.busy_times(user_id, start_time, end_time).await?.is_empty())
+    }
+
+    /// Busy periods from the user's connected and imported calendars
+    /// overlapping the range.
+    pub async fn busy_times(
+        &self,
+        user_id: Uuid,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>, AppError> {
+        let query = r#"
+            SELECT starts_at, ends_at
+            FROM external_busy_times
+            WHERE user_id = $1 AND starts_at < $3 AND ends_at > $2
+            UNION ALL
+            SELECT starts_at, ends_at
+            FROM imported_busy_times
+            WHERE user_id = $1 AND starts_at < $3 AND ends_at > $2
+            ORDER BY starts_at
+        "#;
+
+        sqlx::query_as(query)
+            .bind(user_id)
+            .bind(start_time)
+            .bind(end_time)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch busy times: {}", e)))
+    }
+
+    // Bulk calendar operations
+    pub async fn create_recurring_calendar_events(
+        &self,
+        sessions: &[SessionResponse],
+    ) -> Result<Vec<CalendarEvent>, AppError> {
+        let mut events = Vec::new();
+
+        for session in sessions {
+            let event = self.create_calendar_event(session).await?;
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+
+    // Calendar export formats
+    pub async fn export_calendar_feed(&self, user_id: Uuid) -> Result<String, AppError> {
+        // In a real implementation, you would fetch user's sessions and generate a calendar feed
+        let mut calendar = Calendar::new();
+        calendar.name(&format!("{} - User {}", self.config.calendar_name, user_id));
+        calendar.timezone(&self.config.timezone);
+
+        // Add sample event (in real implementation, fetch from database)
+        let event = Event::new()
+            .uid(&format!("user-{}-feed", user_id))
+            .summary("Sample Session")
+            .description("This is a sample session")
+            .starts(Utc::now())
+            .ends(Utc::now() + chrono::Duration::hours(1))
+            .location("LinkWithMentor Platform")
+            .done();
+
+        calendar.push(event);
+
+        Ok(calendar.to_string())
+    }
+
+    // Calendar subscription management
+    pub async fn create_calendar_subscription(&self, user_id: Uuid) -> Result<String, AppError> {
+        // Generate a unique subscription URL
+        let subscription_id = Uuid::new_v4();
+        let subscription_url = format!(
+            "https://api.linkwithmentor.com/calendar/feed/{}/{}",
+            user_id,
+            subscription_id
+        );
+
+        // In a real implementation, you would store this subscription in the database
+        tracing::info!("Created calendar subscription for user {}: {}", user_id, subscription_url);
+
+        Ok(subscription_url)
+    }
+
+    pub async fn revoke_calendar_subscription(&self, user_id: Uuid, subscription_id: Uuid) -> Result<(), AppError> {
+        // In a real implementation, you would remove the subscription from the database
+        tracing::info!("Revoked calendar subscription {} for user {}", subscription_id, user_id);
+        Ok(())
+    }
+
+    // Time zone handling
+    pub fn convert_to_user_timezone(&self, datetime: DateTime<Utc>, user_timezone: &str) -> Result<DateTime<Tz>, AppError> {
+        let timezone = availability::parse_timezone(user_timezone)?;
+        Ok(datetime.with_timezone(&timezone))
+    }
+
+    pub fn convert_from_user_timezone(&self, datetime: NaiveDateTime, user_timezone: &str) -> Result<DateTime<Utc>, AppError> {
+        let timezone = availability::parse_timezone(user_timezone)?;
+
+        // Repeated wall-clock times take the first pass; skipped ones can't be booked
+        timezone.from_local_datetime(&datetime)
+            .earliest()
+            .map(|local| local.with_timezone(&Utc))
+            .ok_or_else(|| AppError::BadRequest(format!(
+                "{} does not exist in {} because of a daylight saving change", datetime, user_timezone
+            )))
+    }
+
+    // Calendar integration status
+    pub async fn get_integration_status(&self, user_id: Uuid) -> Result<HashMap<String, bool>, AppError> {
+        let connected: HashSet<CalendarProvider> = self.list_connections(user_id).await?
+            .into_iter()
+            .map(|connection| connection.provider)
+            .collect();
+
+        let mut status = HashMap::new();
+        status.insert("google_calendar".to_string(), connected.contains(&CalendarProvider::Google));
+        status.insert("outlook_calendar".to_string(), connected.contains(&CalendarProvider::Outlook));
+        status.insert(
+            "caldav_calendar".to_string(),
+            connected.contains(&CalendarProvider::CalDav) || connected.contains(&CalendarProvider::Apple),
+        );
+        status.insert("ical_export".to_string(), self.config.enable_ical_export);
+
+        Ok(status)
+    }
+}
+
+fn event_to_ical(event: &CalendarEvent) -> String {
+    let mut calendar = Calendar::new();
+
+    let mut ical_event = Event::new();
+    ical_event
+        .uid(&event.event_id)
+        .summary(&event.title)
+        .description(&event.description.clone().unwrap_or_default())
+        .starts(event.start_time)
+        .ends(event.end_time);
+    if let Some(location) = &event.location {
+        ical_event.location(location);
+    }
+
+    calendar.push(ical_event.done());
+    calendar.to_string()
+}
@@ -0,0 +1,148 @@
+use chrono::{Duration, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use linkwithmentor_common::AppError;
+
+use super::ConnectionCredentials;
+
+// Refresh a little early so a request never starts with a token about to lapse
+const REFRESH_MARGIN_SECONDS: i64 = 120;
+
+/// Authorization code flow shared by the Google and Microsoft clients.
+#[derive(Clone)]
+pub struct OAuthClient {
+    client: Client,
+    client_id: String,
+    client_secret: String,
+    authorize_url: String,
+    token_url: String,
+    scope: String,
+    redirect_uri: String,
+    extra_params: Vec<(&'static str, &'static str)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+impl OAuthClient {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        client: Client,
+        client_id: &str,
+        client_secret: &str,
+        authorize_url: &str,
+        token_url: &str,
+        scope: &str,
+        redirect_uri: &str,
+        extra_params: Vec<(&'static str, &'static str)>,
+    ) -> Self {
+        Self {
+            client,
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            authorize_url: authorize_url.to_string(),
+            token_url: token_url.to_string(),
+            scope: scope.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            extra_params,
+        }
+    }
+
+    pub fn authorization_url(&self, state: &str) -> Result<String, AppError> {
+        let mut params = vec![
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", self.scope.as_str()),
+            ("state", state),
+        ];
+        params.extend(self.extra_params.iter().copied());
+
+        let url = Url::parse_with_params(&self.authorize_url, &params)
+            .map_err(|e| AppError::Internal(format!("Invalid authorization URL: {}", e)))?;
+        Ok(url.to_string())
+    }
+
+    pub async fn exchange_code(&self, code: &str) -> Result<ConnectionCredentials, AppError> {
+        let token = self.request_token(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_uri.as_str()),
+        ]).await?;
+
+        if token.refresh_token.is_none() {
+            return Err(AppError::ExternalService(
+                "Calendar provider did not grant offline access".to_string(),
+            ));
+        }
+
+        Ok(ConnectionCredentials {
+            access_token: Some(token.access_token),
+            refresh_token: token.refresh_token,
+            expires_at: token.expires_in.map(|seconds| Utc::now() + Duration::seconds(seconds)),
+            username: None,
+            password: None,
+        })
+    }
+
+    /// Refreshes the access token when it is missing or about to expire.
+    /// Returns true if the credentials changed and need to be stored again.
+    pub async fn refresh(&self, credentials: &mut ConnectionCredentials) -> Result<bool, AppError> {
+        let fresh = credentials.access_token.is_some()
+            && credentials.expires_at.map_or(true, |expires_at| {
+                expires_at > Utc::now() + Duration::seconds(REFRESH_MARGIN_SECONDS)
+            });
+        if fresh {
+            return Ok(false);
+        }
+
+        let refresh_token = credentials.refresh_token.clone()
+            .ok_or_else(|| AppError::ExternalService("Calendar connection has no refresh token".to_string()))?;
+
+        let token = self.request_token(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ]).await?;
+
+        credentials.access_token = Some(token.access_token);
+        credentials.expires_at = token.expires_in.map(|seconds| Utc::now() + Duration::seconds(seconds));
+        // Providers only sometimes rotate the refresh token
+        if let Some(refresh_token) = token.refresh_token {
+            credentials.refresh_token = Some(refresh_token);
+        }
+
+        Ok(true)
+    }
+
+    async fn request_token(&self, params: &[(&str, &str)]) -> Result<TokenResponse, AppError> {
+        let mut form = vec![
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        form.extend_from_slice(params);
+
+        let response = self.client
+            .post(&self.token_url)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Token request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::ExternalService(format!(
+                "Token request rejected ({}): {}", status, body
+            )));
+        }
+
+        response.json().await
+            .map_err(|e| AppError::ExternalService(format!("Invalid token response: {}", e)))
+    }
+}
@@ -23,9 +23,20 @@ pub struct MeetingsServiceConfig {
     pub enable_recurring_sessions: bool,
     pub max_recurring_sessions: u32,
     pub whiteboard_storage_path: String,
+    // Extra fonts for whiteboard exports, on top of the system fonts
+    pub whiteboard_fonts_path: Option<String>,
+    pub whiteboard_default_font: String,
+    // Hosts exports may download whiteboard images from, e.g. the materials CDN
+    pub whiteboard_image_hosts: Vec<String>,
+    // Operations older than this are folded into a snapshot and no longer replayable
+    pub whiteboard_history_retention_days: u32,
     pub session_materials_path: String,
     pub enable_session_recording: bool,
     pub auto_save_interval_seconds: u32,
+    pub max_workshop_capacity: u32,
+    // How long a workshop seat is held for payment before going to the waitlist
+    pub workshop_hold_minutes: u32,
+    pub payment_service_url: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +64,18 @@ pub struct CalendarConfig {
     pub enable_outlook_calendar: bool,
     pub outlook_client_id: Option<String>,
     pub outlook_client_secret: Option<String>,
+    pub outlook_tenant: String,
+    pub enable_caldav_calendar: bool,
+    // Hosts CalDAV calendars may be on; empty allows any public https host
+    pub caldav_allowed_hosts: Vec<String>,
+    // Lets CalDAV reach private hosts and plain http, e.g. the radicale container in development
+    pub caldav_allow_private_hosts: bool,
+    pub oauth_redirect_uri: String,
+    // Encrypts stored calendar credentials, at least 32 characters
+    pub credentials_key: String,
+    pub sync_interval_seconds: u32,
+    // How far ahead external events are mirrored as busy times
+    pub sync_window_days: u32,
 }
 
 impl MeetingsConfig {
@@ -145,6 +168,19 @@ impl MeetingsConfig {
                     .unwrap_or(52),
                 whiteboard_storage_path: std::env::var("MEETINGS_WHITEBOARD_PATH")
                     .unwrap_or_else(|_| "/app/whiteboards".to_string()),
+                whiteboard_fonts_path: std::env::var("MEETINGS_WHITEBOARD_FONTS_PATH").ok(),
+                whiteboard_default_font: std::env::var("MEETINGS_WHITEBOARD_FONT")
+                    .unwrap_or_else(|_| "DejaVu Sans".to_string()),
+                whiteboard_image_hosts: std::env::var("MEETINGS_WHITEBOARD_IMAGE_HOSTS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                whiteboard_history_retention_days: std::env::var("MEETINGS_WHITEBOARD_HISTORY_RETENTION_DAYS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
                 session_materials_path: std::env::var("MEETINGS_MATERIALS_PATH")
                     .unwrap_or_else(|_| "/app/materials".to_string()),
                 enable_session_recording: std::env::var("MEETINGS_ENABLE_RECORDING")
@@ -155,6 +191,16 @@ impl MeetingsConfig {
                     .unwrap_or_else(|_| "30".to_string())
                     .parse()
                     .unwrap_or(30),
+                max_workshop_capacity: std::env::var("MEETINGS_MAX_WORKSHOP_CAPACITY")
+                    .unwrap_or_else(|_| "200".to_string())
+                    .parse()
+                    .unwrap_or(200),
+                workshop_hold_minutes: std::env::var("MEETINGS_WORKSHOP_HOLD_MINUTES")
+                    .unwrap_or_else(|_| "15".to_string())
+                    .parse()
+                    .unwrap_or(15),
+                payment_service_url: std::env::var("PAYMENT_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8005".to_string()),
             },
             notifications: NotificationConfig {
                 smtp_host: std::env::var("SMTP_HOST")
@@ -204,6 +250,34 @@ impl MeetingsConfig {
                     .unwrap_or(false),
                 outlook_client_id: std::env::var("OUTLOOK_CLIENT_ID").ok(),
                 outlook_client_secret: std::env::var("OUTLOOK_CLIENT_SECRET").ok(),
+                outlook_tenant: std::env::var("OUTLOOK_TENANT")
+                    .unwrap_or_else(|_| "common".to_string()),
+                enable_caldav_calendar: std::env::var("CALENDAR_ENABLE_CALDAV")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap_or(true),
+                caldav_allowed_hosts: std::env::var("CALENDAR_CALDAV_ALLOWED_HOSTS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                caldav_allow_private_hosts: std::env::var("CALENDAR_CALDAV_ALLOW_PRIVATE_HOSTS")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap_or(false),
+                oauth_redirect_uri: std::env::var("CALENDAR_OAUTH_REDIRECT_URI")
+                    .unwrap_or_else(|_| "http://localhost:3000/settings/calendars/callback".to_string()),
+                credentials_key: std::env::var("CALENDAR_CREDENTIALS_KEY")
+                    .unwrap_or_else(|_| "dev-calendar-key-change-in-production-32b".to_string()),
+                sync_interval_seconds: std::env::var("CALENDAR_SYNC_INTERVAL")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()
+                    .unwrap_or(300),
+                sync_window_days: std::env::var("CALENDAR_SYNC_WINDOW_DAYS")
+                    .unwrap_or_else(|_| "90".to_string())
+                    .parse()
+                    .unwrap_or(90),
             },
         })
     }
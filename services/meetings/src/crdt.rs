@@ -0,0 +1,631 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::models::{ElementChange, ElementType, Position, Stamp, WhiteboardDelta, WhiteboardElement};
+
+// Undo steps kept per user and whiteboard
+const MAX_UNDO_STEPS: usize = 100;
+
+// How far past the document clock a client's stamp may be: room for a long
+// offline session, while keeping the clock far from overflowing
+pub const MAX_CLOCK_LEAD: u64 = 1_000_000;
+
+/// The replica a client edits as, derived from the user and an id the client
+/// picks for itself (one per tab or device). Nobody can stamp deltas as
+/// another user's replica, or as the server's.
+pub fn client_replica_id(user_id: Uuid, client_id: Uuid) -> Uuid {
+    let digest = Sha256::new()
+        .chain_update(user_id.as_bytes())
+        .chain_update(client_id.as_bytes())
+        .finalize();
+
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Uuid::from_bytes(bytes)
+}
+
+impl Stamp {
+    const ZERO: Stamp = Stamp { counter: 0, replica_id: Uuid::nil() };
+}
+
+/// A last-writer-wins value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Register<T> {
+    pub value: T,
+    pub stamp: Stamp,
+}
+
+impl<T> Register<T> {
+    fn new(value: T, stamp: Stamp) -> Self {
+        Self { value, stamp }
+    }
+
+    /// Keeps the newer write. Returns the write it replaced, unless that
+    /// came from the same delta.
+    fn set(&mut self, value: T, stamp: Stamp) -> Option<Register<T>> {
+        if stamp < self.stamp {
+            return None;
+        }
+
+        let previous = std::mem::replace(self, Register::new(value, stamp));
+        (previous.stamp != stamp).then_some(previous)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementOrigin {
+    pub element_type: ElementType,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub stamp: Stamp,
+}
+
+/// Replicated state of one element. Every field is its own register, so
+/// concurrent edits to different fields of an element all survive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementRecord {
+    // Changes from other replicas can arrive before the element's create
+    pub origin: Option<ElementOrigin>,
+    pub position: Option<Register<Position>>,
+    pub properties: HashMap<String, Register<serde_json::Value>>,
+    pub deleted: Register<bool>,
+}
+
+impl Default for ElementRecord {
+    fn default() -> Self {
+        Self {
+            origin: None,
+            position: None,
+            properties: HashMap::new(),
+            deleted: Register::new(false, Stamp::ZERO),
+        }
+    }
+}
+
+/// A whiteboard as a state-based CRDT. Applying the same deltas in any
+/// order, any number of times, gives the same elements on every replica,
+/// so clients can keep editing offline and merge when they reconnect.
+///
+/// Deltas from one replica must be applied in the order it made them;
+/// anything at or below a replica's entry in the state vector is treated as
+/// already applied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WhiteboardDoc {
+    elements: HashMap<Uuid, ElementRecord>,
+    clock: u64,
+    state_vector: HashMap<Uuid, u64>,
+}
+
+impl WhiteboardDoc {
+    pub fn state_vector(&self) -> &HashMap<Uuid, u64> {
+        &self.state_vector
+    }
+
+    pub fn has_seen(&self, stamp: &Stamp) -> bool {
+        self.state_vector.get(&stamp.replica_id).map_or(false, |&counter| counter >= stamp.counter)
+    }
+
+    /// Whether a stamp from a client is close enough to the document clock
+    /// to be merged.
+    pub fn accepts(&self, stamp: &Stamp) -> bool {
+        stamp.counter <= self.clock.saturating_add(MAX_CLOCK_LEAD)
+    }
+
+    /// Stamps a change made on `replica_id`, newer than everything seen so far.
+    pub fn next_stamp(&mut self, replica_id: Uuid) -> Stamp {
+        self.clock = self.clock.saturating_add(1);
+        Stamp { counter: self.clock, replica_id }
+    }
+
+    /// Merges a delta and returns the changes that would undo it, or `None`
+    /// if the delta was already applied.
+    pub fn apply(&mut self, delta: &WhiteboardDelta) -> Option<Vec<ElementChange>> {
+        if self.has_seen(&delta.stamp) {
+            return None;
+        }
+
+        let stamp = delta.stamp;
+        self.clock = self.clock.max(stamp.counter);
+        self.state_vector.insert(stamp.replica_id, stamp.counter);
+
+        let mut inverse = Vec::new();
+        for change in &delta.changes {
+            let element_id = change.element_id();
+            let record = self.elements.entry(element_id).or_default();
+
+            match change {
+                ElementChange::Create { element_type, position, properties, .. } => {
+                    if record.origin.is_none() {
+                        record.origin = Some(ElementOrigin {
+                            element_type: element_type.clone(),
+                            created_by: delta.user_id,
+                            created_at: delta.timestamp,
+                            stamp,
+                        });
+                        inverse.push(ElementChange::Delete { element_id });
+                    }
+
+                    set_position(record, position, stamp);
+                    for (key, value) in properties {
+                        set_property(record, key, value, stamp);
+                    }
+                }
+                ElementChange::Move { position, .. } => {
+                    if let Some(previous) = set_position(record, position, stamp) {
+                        inverse.push(ElementChange::Move { element_id, position: previous });
+                    }
+                }
+                ElementChange::SetProperties { properties, .. } => {
+                    let mut previous = HashMap::new();
+                    for (key, value) in properties {
+                        if let Some(old) = set_property(record, key, value, stamp) {
+                            previous.insert(key.clone(), old);
+                        }
+                    }
+
+                    if !previous.is_empty() {
+                        inverse.push(ElementChange::SetProperties { element_id, properties: previous });
+                    }
+                }
+                ElementChange::Delete { .. } => {
+                    if let Some(previous) = record.deleted.set(true, stamp) {
+                        if !previous.value {
+                            inverse.push(ElementChange::Restore { element_id });
+                        }
+                    }
+                }
+                ElementChange::Restore { .. } => {
+                    if let Some(previous) = record.deleted.set(false, stamp) {
+                        if previous.value {
+                            inverse.push(ElementChange::Delete { element_id });
+                        }
+                    }
+                }
+            }
+        }
+
+        // Undo runs the other way round
+        inverse.reverse();
+        Some(inverse)
+    }
+
+    /// Visible elements, oldest first.
+    pub fn elements(&self) -> Vec<WhiteboardElement> {
+        let mut elements: Vec<(Stamp, WhiteboardElement)> = self.elements.iter()
+            .filter_map(|(element_id, record)| {
+                let stamp = record.origin.as_ref()?.stamp;
+                Some((stamp, materialize(*element_id, record)?))
+            })
+            .collect();
+
+        elements.sort_by_key(|(stamp, _)| *stamp);
+        elements.into_iter().map(|(_, element)| element).collect()
+    }
+
+    pub fn element(&self, element_id: Uuid) -> Option<WhiteboardElement> {
+        self.elements.get(&element_id).and_then(|record| materialize(element_id, record))
+    }
+
+    /// Changes that make the visible elements equal to `target`, used to
+    /// restore an earlier version as a new edit rather than by rewinding.
+    pub fn changes_to(&self, target: &[WhiteboardElement]) -> Vec<ElementChange> {
+        let mut changes = Vec::new();
+
+        for element in target {
+            let record = self.elements.get(&element.element_id);
+            let mut properties = element.properties.clone();
+            if let Some(record) = record {
+                for (key, property) in &record.properties {
+                    if !property.value.is_null() {
+                        properties.entry(key.clone()).or_insert(serde_json::Value::Null);
+                    }
+                }
+            }
+
+            match self.element(element.element_id) {
+                Some(current) => {
+                    if current.position != element.position {
+                        changes.push(ElementChange::Move {
+                            element_id: element.element_id,
+                            position: element.position.clone(),
+                        });
+                    }
+
+                    properties.retain(|key, value| current.properties.get(key).unwrap_or(&serde_json::Value::Null) != value);
+                    if !properties.is_empty() {
+                        changes.push(ElementChange::SetProperties { element_id: element.element_id, properties });
+                    }
+                }
+                None => {
+                    changes.push(ElementChange::Create {
+                        element_id: element.element_id,
+                        element_type: element.element_type.clone(),
+                        position: element.position.clone(),
+                        properties,
+                    });
+                    if record.map_or(false, |record| record.deleted.value) {
+                        changes.push(ElementChange::Restore { element_id: element.element_id });
+                    }
+                }
+            }
+        }
+
+        let kept: HashSet<Uuid> = target.iter().map(|element| element.element_id).collect();
+        for element in self.elements() {
+            if !kept.contains(&element.element_id) {
+                changes.push(ElementChange::Delete { element_id: element.element_id });
+            }
+        }
+
+        changes
+    }
+
+    /// The part of an undo step that still holds: fields someone else has
+    /// written since are left as they are.
+    fn still_current(&self, entry: &UndoEntry) -> Vec<ElementChange> {
+        entry.changes.iter()
+            .filter_map(|change| {
+                let record = self.elements.get(&change.element_id())?;
+                let owns_deleted = record.deleted.stamp == entry.stamp;
+
+                match change {
+                    ElementChange::Delete { .. } => {
+                        let created_here = record.origin.as_ref().map_or(false, |origin| origin.stamp == entry.stamp);
+                        (!record.deleted.value && (created_here || owns_deleted)).then(|| change.clone())
+                    }
+                    ElementChange::Restore { .. } => {
+                        (record.deleted.value && owns_deleted).then(|| change.clone())
+                    }
+                    ElementChange::Move { .. } => {
+                        record.position.as_ref()
+                            .map_or(false, |position| position.stamp == entry.stamp)
+                            .then(|| change.clone())
+                    }
+                    ElementChange::SetProperties { element_id, properties } => {
+                        let properties: HashMap<String, serde_json::Value> = properties.iter()
+                            .filter(|(key, _)| {
+                                record.properties.get(*key).map_or(false, |property| property.stamp == entry.stamp)
+                            })
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect();
+
+                        (!properties.is_empty()).then_some(ElementChange::SetProperties {
+                            element_id: *element_id,
+                            properties,
+                        })
+                    }
+                    ElementChange::Create { .. } => None,
+                }
+            })
+            .collect()
+    }
+}
+
+fn set_position(record: &mut ElementRecord, position: &Position, stamp: Stamp) -> Option<Position> {
+    match record.position.as_mut() {
+        Some(register) => register.set(position.clone(), stamp).map(|previous| previous.value),
+        None => {
+            record.position = Some(Register::new(position.clone(), stamp));
+            None
+        }
+    }
+}
+
+// Returns the replaced value, null if the property was unset
+fn set_property(
+    record: &mut ElementRecord,
+    key: &str,
+    value: &serde_json::Value,
+    stamp: Stamp,
+) -> Option<serde_json::Value> {
+    match record.properties.get_mut(key) {
+        Some(register) => register.set(value.clone(), stamp).map(|previous| previous.value),
+        None => {
+            record.properties.insert(key.to_string(), Register::new(value.clone(), stamp));
+            Some(serde_json::Value::Null)
+        }
+    }
+}
+
+fn materialize(element_id: Uuid, record: &ElementRecord) -> Option<WhiteboardElement> {
+    let origin = record.origin.as_ref()?;
+    let position = record.position.as_ref()?;
+    if record.deleted.value {
+        return None;
+    }
+
+    Some(WhiteboardElement {
+        element_id,
+        element_type: origin.element_type.clone(),
+        position: position.value.clone(),
+        properties: record.properties.iter()
+            .filter(|(_, property)| !property.value.is_null())
+            .map(|(key, property)| (key.clone(), property.value.clone()))
+            .collect(),
+        created_by: origin.created_by,
+        created_at: origin.created_at,
+    })
+}
+
+/// Changes that reverse the delta with `stamp`.
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub stamp: Stamp,
+    pub changes: Vec<ElementChange>,
+}
+
+/// One user's undo and redo stacks on one whiteboard. Undo only ever
+/// reverses the user's own changes, and never overwrites what someone else
+/// changed afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct UndoHistory {
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+}
+
+impl UndoHistory {
+    /// Records a new edit by the user, which clears what they could redo.
+    pub fn record(&mut self, stamp: Stamp, inverse: Vec<ElementChange>) {
+        if inverse.is_empty() {
+            return;
+        }
+
+        push_bounded(&mut self.undo, UndoEntry { stamp, changes: inverse });
+        self.redo.clear();
+    }
+
+    /// Applies the user's latest change that can still be undone as a new
+    /// delta from `replica_id`, which the caller broadcasts like any other.
+    pub fn undo(&mut self, doc: &mut WhiteboardDoc, user_id: Uuid, replica_id: Uuid) -> Option<WhiteboardDelta> {
+        step(doc, &mut self.undo, &mut self.redo, user_id, replica_id)
+    }
+
+    pub fn redo(&mut self, doc: &mut WhiteboardDoc, user_id: Uuid, replica_id: Uuid) -> Option<WhiteboardDelta> {
+        step(doc, &mut self.redo, &mut self.undo, user_id, replica_id)
+    }
+}
+
+fn step(
+    doc: &mut WhiteboardDoc,
+    from: &mut Vec<UndoEntry>,
+    to: &mut Vec<UndoEntry>,
+    user_id: Uuid,
+    replica_id: Uuid,
+) -> Option<WhiteboardDelta> {
+    // Skip steps others have completely overwritten
+    while let Some(entry) = from.pop() {
+        let changes = doc.still_current(&entry);
+        if changes.is_empty() {
+            continue;
+        }
+
+        let delta = WhiteboardDelta {
+            stamp: doc.next_stamp(replica_id),
+            user_id,
+            changes,
+            timestamp: Utc::now(),
+        };
+
+        let inverse = doc.apply(&delta).unwrap_or_default();
+        if !inverse.is_empty() {
+            push_bounded(to, UndoEntry { stamp: delta.stamp, changes: inverse });
+        }
+
+        return Some(delta);
+    }
+
+    None
+}
+
+fn push_bounded(stack: &mut Vec<UndoEntry>, entry: UndoEntry) {
+    stack.push(entry);
+    if stack.len() > MAX_UNDO_STEPS {
+        stack.remove(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(x: f64, y: f64) -> Position {
+        Position { x, y, width: None, height: None }
+    }
+
+    fn edit(doc: &mut WhiteboardDoc, replica_id: Uuid, user_id: Uuid, changes: Vec<ElementChange>) -> WhiteboardDelta {
+        WhiteboardDelta {
+            stamp: doc.next_stamp(replica_id),
+            user_id,
+            changes,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn create(element_id: Uuid, text: &str) -> ElementChange {
+        ElementChange::Create {
+            element_id,
+            element_type: ElementType::Sticky,
+            position: position(0.0, 0.0),
+            properties: HashMap::from([("text".to_string(), serde_json::json!(text))]),
+        }
+    }
+
+    fn text(doc: &WhiteboardDoc, element_id: Uuid) -> Option<String> {
+        doc.element(element_id)?.properties.get("text")?.as_str().map(str::to_string)
+    }
+
+    #[test]
+    fn test_concurrent_edits_converge() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let element_id = Uuid::new_v4();
+        let mut a = WhiteboardDoc::default();
+        let mut b = WhiteboardDoc::default();
+
+        let created = edit(&mut a, alice, alice, vec![create(element_id, "draft")]);
+        a.apply(&created);
+        b.apply(&created);
+
+        // Alice moves the note while Bob rewrites it, without seeing each other
+        let moved = edit(&mut a, alice, alice, vec![ElementChange::Move { element_id, position: position(50.0, 80.0) }]);
+        let rewritten = edit(&mut b, bob, bob, vec![ElementChange::SetProperties {
+            element_id,
+            properties: HashMap::from([("text".to_string(), serde_json::json!("final"))]),
+        }]);
+        a.apply(&moved);
+        b.apply(&rewritten);
+        a.apply(&rewritten);
+        b.apply(&moved);
+
+        for doc in [&a, &b] {
+            let element = doc.element(element_id).unwrap();
+            assert_eq!(element.position.x, 50.0);
+            assert_eq!(text(doc, element_id).as_deref(), Some("final"));
+        }
+        assert_eq!(a.state_vector(), b.state_vector());
+
+        // Re-applying is a no-op
+        assert!(a.apply(&moved).is_none());
+    }
+
+    #[test]
+    fn test_offline_edits_merge_in_any_order() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let shared = Uuid::new_v4();
+        let mut server = WhiteboardDoc::default();
+        let created = edit(&mut server, alice, alice, vec![create(shared, "agenda")]);
+        server.apply(&created);
+
+        let mut offline = server.clone();
+        let mut offline_log = Vec::new();
+        let added = Uuid::new_v4();
+        for changes in [
+            vec![create(added, "question")],
+            vec![ElementChange::Delete { element_id: shared }],
+        ] {
+            let delta = edit(&mut offline, bob, bob, changes);
+            offline.apply(&delta);
+            offline_log.push(delta);
+        }
+
+        // Meanwhile Alice keeps editing the note Bob deleted
+        let renamed = edit(&mut server, alice, alice, vec![ElementChange::SetProperties {
+            element_id: shared,
+            properties: HashMap::from([("text".to_string(), serde_json::json!("agenda v2"))]),
+        }]);
+        server.apply(&renamed);
+
+        for delta in &offline_log {
+            server.apply(delta);
+        }
+        offline.apply(&renamed);
+
+        let ids = |doc: &WhiteboardDoc| doc.elements().iter().map(|e| e.element_id).collect::<Vec<_>>();
+        assert_eq!(ids(&server), vec![added]);
+        assert_eq!(ids(&server), ids(&offline));
+    }
+
+    #[test]
+    fn test_undo_is_scoped_to_the_user() {
+        let (alice, bob, server_replica) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut doc = WhiteboardDoc::default();
+        let mut alice_history = UndoHistory::default();
+
+        let created = edit(&mut doc, alice, alice, vec![create(first, "mine")]);
+        let inverse = doc.apply(&created).unwrap();
+        alice_history.record(created.stamp, inverse);
+
+        let moved = edit(&mut doc, alice, alice, vec![ElementChange::Move { element_id: first, position: position(10.0, 10.0) }]);
+        let inverse = doc.apply(&moved).unwrap();
+        alice_history.record(moved.stamp, inverse);
+
+        // Bob adds his own note and retitles Alice's
+        let bob_created = edit(&mut doc, bob, bob, vec![create(second, "his")]);
+        doc.apply(&bob_created);
+        let retitled = edit(&mut doc, bob, bob, vec![ElementChange::SetProperties {
+            element_id: first,
+            properties: HashMap::from([("text".to_string(), serde_json::json!("ours"))]),
+        }]);
+        doc.apply(&retitled);
+
+        // Alice's undo reverts her move only
+        alice_history.undo(&mut doc, alice, server_replica).unwrap();
+        assert_eq!(doc.element(first).unwrap().position.x, 0.0);
+        assert_eq!(text(&doc, first).as_deref(), Some("ours"));
+        assert!(doc.element(second).is_some());
+
+        alice_history.undo(&mut doc, alice, server_replica).unwrap();
+        assert!(doc.element(first).is_none());
+        assert!(alice_history.undo(&mut doc, alice, server_replica).is_none());
+
+        alice_history.redo(&mut doc, alice, server_replica).unwrap();
+        alice_history.redo(&mut doc, alice, server_replica).unwrap();
+        assert_eq!(doc.element(first).unwrap().position.x, 10.0);
+        assert_eq!(text(&doc, first).as_deref(), Some("ours"));
+    }
+
+    #[test]
+    fn test_changes_to_restores_an_earlier_version() {
+        let alice = Uuid::new_v4();
+        let (kept, removed, added) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut doc = WhiteboardDoc::default();
+
+        let created = edit(&mut doc, alice, alice, vec![create(kept, "first"), create(removed, "second")]);
+        doc.apply(&created);
+        let earlier = doc.elements();
+
+        let later = edit(&mut doc, alice, alice, vec![
+            ElementChange::Move { element_id: kept, position: position(30.0, 40.0) },
+            ElementChange::SetProperties {
+                element_id: kept,
+                properties: HashMap::from([("color".to_string(), serde_json::json!("#FF0000"))]),
+            },
+            ElementChange::Delete { element_id: removed },
+            create(added, "third"),
+        ]);
+        doc.apply(&later);
+
+        let changes = doc.changes_to(&earlier);
+        let restore = edit(&mut doc, alice, alice, changes);
+        doc.apply(&restore);
+
+        let restored = doc.elements();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(doc.element(kept).unwrap().position.x, 0.0);
+        assert!(!doc.element(kept).unwrap().properties.contains_key("color"));
+        assert_eq!(text(&doc, removed).as_deref(), Some("second"));
+        assert!(doc.element(added).is_none());
+        assert!(doc.changes_to(&earlier).is_empty());
+    }
+
+    #[test]
+    fn test_client_stamps_are_bounded() {
+        let user_id = Uuid::new_v4();
+        let replica_id = client_replica_id(user_id, Uuid::new_v4());
+        let mut doc = WhiteboardDoc::default();
+
+        let created = edit(&mut doc, replica_id, user_id, vec![create(Uuid::new_v4(), "draft")]);
+        doc.apply(&created);
+
+        assert!(doc.accepts(&Stamp { counter: doc.clock + MAX_CLOCK_LEAD, replica_id }));
+        assert!(!doc.accepts(&Stamp { counter: doc.clock + MAX_CLOCK_LEAD + 1, replica_id }));
+        assert!(!doc.accepts(&Stamp { counter: u64::MAX, replica_id }));
+
+        // The clock saturates instead of overflowing
+        doc.clock = u64::MAX;
+        assert_eq!(doc.next_stamp(replica_id).counter, u64::MAX);
+    }
+
+    #[test]
+    fn test_client_replica_ids() {
+        let (alice, bob, client) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(client_replica_id(alice, client), client_replica_id(alice, client));
+        assert_ne!(client_replica_id(alice, client), client_replica_id(bob, client));
+        assert_ne!(client_replica_id(alice, client), client_replica_id(alice, Uuid::new_v4()));
+    }
+}
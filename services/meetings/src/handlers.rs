@@ -1,8 +1,12 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
 };
+use futures::{Stream, StreamExt};
 use serde::Deserialize;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
@@ -14,8 +18,15 @@ use crate::{
     models::{
         SessionRequest, SessionResponse, UpdateSessionRequest, AvailabilityRequest,
         AvailabilityResponse, AvailabilitySlot, RecurringSeriesResponse, UploadMaterialRequest,
-        SessionMaterial, WhiteboardState, WhiteboardElement, WhiteboardOperation,
+        SessionMaterial, WhiteboardState, WhiteboardElement, WhiteboardOperation, OperationType,
+        WhiteboardSyncRequest, WhiteboardSyncResponse, WhiteboardExportRequest,
+        AttachWhiteboardExportRequest, WhiteboardExportResponse, CreateWhiteboardSnapshotRequest,
+        WhiteboardSnapshot, WhiteboardHistoryResponse, RestoreWhiteboardRequest,
         NotificationRequest, NotificationType, SessionAnalytics, MentorAnalytics,
+        CalendarProvider, CalendarConnection, ConnectCalendarRequest, CalendarAuthorizationResponse,
+        SessionType, BookingPolicy, BookingPolicyRequest, CalendarImport, OccurrenceScope,
+        UpdateOccurrenceRequest, Workshop, WorkshopRequest, WorkshopSeat, WorkshopAttendeeList,
+        PaySeatRequest,
     },
     AppState,
 };
@@ -36,8 +47,47 @@ pub struct AvailabilityQuery {
     pub mentor_id: Option<Uuid>,
     pub start_date: Option<DateTime<Utc>>,
     pub end_date: Option<DateTime<Utc>>,
+    pub duration_minutes: Option<u32>,
+    pub session_type: Option<SessionType>,
+    // IANA timezone the slots are shown in, UTC if omitted
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BookingPolicyQuery {
+    pub mentor_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarImportQuery {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OccurrenceScopeQuery {
+    #[serde(default)]
+    pub scope: OccurrenceScope,
 }
 
+// Longest range of slots returned at once
+const MAX_AVAILABILITY_DAYS: i64 = 62;
+
+#[derive(Debug, Deserialize)]
+pub struct WhiteboardHistoryQuery {
+    pub before_version: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WhiteboardReplayQuery {
+    pub from_version: Option<u64>,
+    // Playback speed relative to how the board was drawn; omitted sends everything at once
+    pub speed: Option<f64>,
+}
+
+// Longest pause between replayed steps, however long the author paused
+const MAX_REPLAY_PAUSE: std::time::Duration = std::time::Duration::from_secs(3);
+
 // Health check
 pub async fn health_check() -> Result<Json<ApiResponse<String>>, AppError> {
     Ok(Json(ApiResponse::success("Meetings service is healthy".to_string())))
@@ -153,15 +203,120 @@ pub async fn reschedule_session(
     Err(AppError::NotFound("Session not found".to_string()))
 }
 
+// Workshop handlers
+pub async fn get_workshop(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Workshop>>, AppError> {
+    let workshop = state.workshop_service.get_workshop(session_id).await?;
+    Ok(Json(ApiResponse::success(workshop)))
+}
+
+pub async fn set_workshop(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<WorkshopRequest>,
+) -> Result<Json<ApiResponse<Workshop>>, AppError> {
+    let workshop = state.workshop_service
+        .set_workshop(session_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(workshop)))
+}
+
+pub async fn get_workshop_attendees(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WorkshopAttendeeList>>, AppError> {
+    let attendees = state.workshop_service
+        .get_attendees(session_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(attendees)))
+}
+
+pub async fn get_workshop_seat(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WorkshopSeat>>, AppError> {
+    let seat = state.workshop_service.get_seat(session_id, claims.user_id).await?;
+    Ok(Json(ApiResponse::success(seat)))
+}
+
+pub async fn book_workshop_seat(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WorkshopSeat>>, AppError> {
+    let seat = state.workshop_service.book_seat(session_id, claims.user_id).await?;
+    Ok(Json(ApiResponse::success(seat)))
+}
+
+// Charges the caller through the payment service, as them
+pub async fn pay_workshop_seat(
+    State(state): State<AppState>,
+    claims: Claims,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<PaySeatRequest>,
+) -> Result<Json<ApiResponse<WorkshopSeat>>, AppError> {
+    let seat = state.workshop_service
+        .pay_seat(session_id, claims.user_id, request, authorization_header(&headers))
+        .await?;
+
+    Ok(Json(ApiResponse::success(seat)))
+}
+
+pub async fn cancel_workshop_seat(
+    State(state): State<AppState>,
+    claims: Claims,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WorkshopSeat>>, AppError> {
+    let seat = state.workshop_service
+        .cancel_seat(session_id, claims.user_id, authorization_header(&headers))
+        .await?;
+
+    Ok(Json(ApiResponse::success(seat)))
+}
+
+fn authorization_header(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
 // Availability management handlers
 pub async fn get_availability(
     State(state): State<AppState>,
     claims: Claims,
     Query(query): Query<AvailabilityQuery>,
 ) -> Result<Json<ApiResponse<Vec<AvailabilitySlot>>>, AppError> {
-    // Implementation for getting availability
-    let availability = vec![]; // Placeholder
-    Ok(Json(ApiResponse::success(availability)))
+    let start_date = query.start_date.unwrap_or_else(Utc::now);
+    let end_date = query.end_date.unwrap_or(start_date + chrono::Duration::days(14));
+    if end_date <= start_date || end_date - start_date > chrono::Duration::days(MAX_AVAILABILITY_DAYS) {
+        return Err(AppError::BadRequest(format!(
+            "Availability range must be positive and at most {} days", MAX_AVAILABILITY_DAYS
+        )));
+    }
+
+    let slots = state.scheduling_service
+        .get_available_slots(
+            query.mentor_id.unwrap_or(claims.user_id),
+            start_date,
+            end_date,
+            query.duration_minutes.unwrap_or(state.config.meetings.default_session_duration_minutes),
+            &query.session_type.unwrap_or(SessionType::OneOnOne),
+            query.timezone.as_deref(),
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success(slots)))
 }
 
 pub async fn set_availability(
@@ -169,19 +324,12 @@ pub async fn set_availability(
     claims: Claims,
     Json(request): Json<AvailabilityRequest>,
 ) -> Result<Json<ApiResponse<AvailabilityResponse>>, AppError> {
-    // Implementation for setting availability
-    let response = AvailabilityResponse {
-        availability_id: Uuid::new_v4(),
-        mentor_id: claims.user_id,
-        day_of_week: request.day_of_week,
-        start_time: request.start_time,
-        end_time: request.end_time,
-        timezone: request.timezone,
-        is_available: request.is_available,
-        created_at: Utc::now(),
-        updated_at: Utc::now(),
-    };
-    
+    let response = state.scheduling_service
+        .set_availability(claims.user_id, vec![request])
+        .await?
+        .pop()
+        .ok_or_else(|| AppError::Internal("Availability was not saved".to_string()))?;
+
     Ok(Json(ApiResponse::success(response)))
 }
 
@@ -204,6 +352,31 @@ pub async fn delete_availability(
     Ok(Json(ApiResponse::success(())))
 }
 
+// Booking policy handlers
+pub async fn get_booking_policy(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<BookingPolicyQuery>,
+) -> Result<Json<ApiResponse<BookingPolicy>>, AppError> {
+    let policy = state.scheduling_service
+        .get_booking_policy(query.mentor_id.unwrap_or(claims.user_id))
+        .await?;
+
+    Ok(Json(ApiResponse::success(policy)))
+}
+
+pub async fn set_booking_policy(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<BookingPolicyRequest>,
+) -> Result<Json<ApiResponse<BookingPolicy>>, AppError> {
+    let policy = state.scheduling_service
+        .set_booking_policy(claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(policy)))
+}
+
 // Calendar integration handlers
 pub async fn get_calendar_events(
     State(state): State<AppState>,
@@ -218,7 +391,98 @@ pub async fn sync_calendar(
     State(state): State<AppState>,
     claims: Claims,
 ) -> Result<Json<ApiResponse<()>>, AppError> {
-    // Implementation for syncing calendar
+    state.calendar_service.sync_all_calendars(claims.user_id).await?;
+    Ok(Json(ApiResponse::success(())))
+}
+
+// Starts the OAuth flow for Google or Outlook
+pub async fn authorize_calendar_connection(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(provider): Path<CalendarProvider>,
+) -> Result<Json<ApiResponse<CalendarAuthorizationResponse>>, AppError> {
+    let authorization = state.calendar_service
+        .authorization_url(claims.user_id, provider)
+        .await?;
+
+    Ok(Json(ApiResponse::success(authorization)))
+}
+
+pub async fn list_calendar_connections(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<ApiResponse<Vec<CalendarConnection>>>, AppError> {
+    let connections = state.calendar_service.list_connections(claims.user_id).await?;
+    Ok(Json(ApiResponse::success(connections)))
+}
+
+pub async fn connect_calendar(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<ConnectCalendarRequest>,
+) -> Result<Json<ApiResponse<CalendarConnection>>, AppError> {
+    let connection = state.calendar_service
+        .connect_calendar(claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(connection)))
+}
+
+pub async fn disconnect_calendar(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(connection_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.calendar_service
+        .disconnect_calendar(claims.user_id, connection_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+pub async fn sync_calendar_connection(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(connection_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<CalendarConnection>>, AppError> {
+    let connection = state.calendar_service
+        .sync_user_connection(claims.user_id, connection_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(connection)))
+}
+
+// The request body is the .ics file itself
+pub async fn import_calendar(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<CalendarImportQuery>,
+    body: String,
+) -> Result<Json<ApiResponse<CalendarImport>>, AppError> {
+    let import = state.calendar_service
+        .import_ics(claims.user_id, query.name, &body)
+        .await?;
+
+    Ok(Json(ApiResponse::success(import)))
+}
+
+pub async fn list_calendar_imports(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<ApiResponse<Vec<CalendarImport>>>, AppError> {
+    let imports = state.calendar_service.list_imports(claims.user_id).await?;
+    Ok(Json(ApiResponse::success(imports)))
+}
+
+pub async fn delete_calendar_import(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(import_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.calendar_service
+        .delete_import(claims.user_id, import_id)
+        .await?;
+
     Ok(Json(ApiResponse::success(())))
 }
 
@@ -228,15 +492,13 @@ pub async fn get_whiteboard_state(
     claims: Claims,
     Path(session_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<WhiteboardState>>, AppError> {
-    // Implementation for getting whiteboard state
-    let state = WhiteboardState {
-        session_id,
-        elements: vec![],
-        version: 1,
-        last_updated: Utc::now(),
-    };
-    
-    Ok(Json(ApiResponse::success(state)))
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(whiteboard)))
 }
 
 pub async fn update_whiteboard_state(
@@ -245,14 +507,35 @@ pub async fn update_whiteboard_state(
     Path(session_id): Path<Uuid>,
     Json(operation): Json<WhiteboardOperation>,
 ) -> Result<Json<ApiResponse<WhiteboardState>>, AppError> {
-    // Implementation for updating whiteboard state
-    let whiteboard_state = WhiteboardState {
-        session_id,
-        elements: vec![],
-        version: 1,
-        last_updated: Utc::now(),
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard_id = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?
+        .whiteboard_id;
+    let service = &state.whiteboard_service;
+
+    // Whole-element operations from clients that don't speak deltas
+    let whiteboard_state = match operation.operation_type {
+        OperationType::Create => {
+            let element = operation.element
+                .ok_or_else(|| AppError::BadRequest("element is required".to_string()))?;
+            service.add_element(whiteboard_id, element, claims.user_id).await?
+        }
+        OperationType::Update | OperationType::Move => {
+            let element = operation.element
+                .ok_or_else(|| AppError::BadRequest("element is required".to_string()))?;
+            let element_id = operation.element_id.unwrap_or(element.element_id);
+            service.update_element(whiteboard_id, element_id, element, claims.user_id).await?
+        }
+        OperationType::Delete => {
+            let element_id = operation.element_id
+                .ok_or_else(|| AppError::BadRequest("element_id is required".to_string()))?;
+            service.delete_element(whiteboard_id, element_id, claims.user_id).await?
+        }
+        OperationType::Clear => service.clear_whiteboard(whiteboard_id, claims.user_id).await?,
     };
-    
+
     Ok(Json(ApiResponse::success(whiteboard_state)))
 }
 
@@ -261,10 +544,234 @@ pub async fn clear_whiteboard(
     claims: Claims,
     Path(session_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, AppError> {
-    // Implementation for clearing whiteboard
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    state.whiteboard_service
+        .clear_whiteboard(whiteboard.whiteboard_id, claims.user_id)
+        .await?;
+
     Ok(Json(ApiResponse::success(())))
 }
 
+// Merge a client's offline deltas and return the ones it is missing
+pub async fn sync_whiteboard(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<WhiteboardSyncRequest>,
+) -> Result<Json<ApiResponse<WhiteboardSyncResponse>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let response = state.whiteboard_service
+        .sync(whiteboard.whiteboard_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Undo the caller's own last whiteboard change
+pub async fn undo_whiteboard(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WhiteboardState>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let whiteboard = state.whiteboard_service
+        .undo(whiteboard.whiteboard_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(whiteboard)))
+}
+
+// Redo the caller's last undone whiteboard change
+pub async fn redo_whiteboard(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WhiteboardState>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let whiteboard = state.whiteboard_service
+        .redo(whiteboard.whiteboard_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(whiteboard)))
+}
+
+// Download the whiteboard as JSON, SVG, PNG or PDF
+pub async fn export_whiteboard(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Query(request): Query<WhiteboardExportRequest>,
+) -> Result<Response, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let export = state.whiteboard_service
+        .export_whiteboard(whiteboard.whiteboard_id, &request)
+        .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, export.content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", export.file_name)),
+        ],
+        export.contents,
+    ).into_response())
+}
+
+// Save a whiteboard export to the session materials and attach it to a note
+pub async fn attach_whiteboard_export(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<AttachWhiteboardExportRequest>,
+) -> Result<Json<ApiResponse<WhiteboardExportResponse>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let response = state.whiteboard_service
+        .attach_export_to_notes(session_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Save the current whiteboard as a named version
+pub async fn create_whiteboard_snapshot(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<CreateWhiteboardSnapshotRequest>,
+) -> Result<Json<ApiResponse<WhiteboardSnapshot>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let snapshot = state.whiteboard_service
+        .create_snapshot(whiteboard.whiteboard_id, claims.user_id, request.name)
+        .await?;
+
+    Ok(Json(ApiResponse::success(snapshot)))
+}
+
+// List whiteboard versions and snapshots
+pub async fn get_whiteboard_history(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Query(query): Query<WhiteboardHistoryQuery>,
+) -> Result<Json<ApiResponse<WhiteboardHistoryResponse>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let history = state.whiteboard_service
+        .history(whiteboard.whiteboard_id, query.before_version, query.limit.unwrap_or(50).clamp(1, 200))
+        .await?;
+
+    Ok(Json(ApiResponse::success(history)))
+}
+
+// Restore the whiteboard to an earlier version or snapshot
+pub async fn restore_whiteboard(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<RestoreWhiteboardRequest>,
+) -> Result<Json<ApiResponse<WhiteboardState>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let whiteboard = state.whiteboard_service
+        .restore_version(whiteboard.whiteboard_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(whiteboard)))
+}
+
+// Stream how the whiteboard was built as server-sent events: a "start"
+// event with the initial board, a "delta" event per edit, then "end"
+pub async fn replay_whiteboard(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+    Query(query): Query<WhiteboardReplayQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    ensure_session_participant(&state, session_id, claims.user_id).await?;
+
+    let whiteboard = state.whiteboard_service
+        .get_session_whiteboard(session_id, claims.user_id)
+        .await?;
+    let replay = state.whiteboard_service
+        .replay(whiteboard.whiteboard_id, query.from_version)
+        .await?;
+
+    let speed = query.speed.filter(|speed| speed.is_finite() && *speed > 0.0);
+    let mut previous = replay.start.last_modified;
+    let mut end_version = replay.start.version;
+    let mut steps = Vec::with_capacity(replay.steps.len());
+    for step in replay.steps {
+        let pause = match speed {
+            Some(speed) => (step.delta.timestamp - previous).to_std().unwrap_or_default()
+                .div_f64(speed)
+                .min(MAX_REPLAY_PAUSE),
+            None => std::time::Duration::ZERO,
+        };
+        previous = step.delta.timestamp;
+        end_version = step.version;
+        steps.push((pause, step));
+    }
+
+    let start = Event::default().event("start").json_data(&replay.start);
+    let stream = futures::stream::once(async move { start })
+        .chain(futures::stream::iter(steps).then(|(pause, step)| async move {
+            tokio::time::sleep(pause).await;
+            Event::default().event("delta").json_data(&step)
+        }))
+        .chain(futures::stream::once(async move {
+            Event::default().event("end").json_data(serde_json::json!({ "version": end_version }))
+        }));
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+async fn ensure_session_participant(state: &AppState, session_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+    let is_participant: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM mentorship_sessions WHERE session_id = $1 AND (mentor_id = $2 OR mentee_id = $2))"
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to check session participant: {}", e)))?;
+
+    if !is_participant {
+        return Err(AppError::Forbidden("Not a participant of this session".to_string()));
+    }
+
+    Ok(())
+}
+
 // Materials handlers
 pub async fn list_materials(
     State(state): State<AppState>,
@@ -383,8 +890,11 @@ pub async fn get_recurring_session(
     claims: Claims,
     Path(recurring_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<RecurringSeriesResponse>>, AppError> {
-    // Implementation for getting recurring session
-    Err(AppError::NotFound("Recurring session not found".to_string()))
+    let series = state.scheduling_service
+        .get_recurring_series(recurring_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(series)))
 }
 
 pub async fn update_recurring_session(
@@ -405,6 +915,32 @@ pub async fn delete_recurring_session(
     Ok(Json(ApiResponse::success(())))
 }
 
+pub async fn update_recurring_occurrence(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((recurring_id, session_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateOccurrenceRequest>,
+) -> Result<Json<ApiResponse<RecurringSeriesResponse>>, AppError> {
+    let series = state.scheduling_service
+        .update_occurrence(recurring_id, session_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(ApiResponse::success(series)))
+}
+
+pub async fn cancel_recurring_occurrence(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((recurring_id, session_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<OccurrenceScopeQuery>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.scheduling_service
+        .cancel_occurrence(recurring_id, session_id, claims.user_id, query.scope)
+        .await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
 // Analytics handlers
 pub async fn get_session_analytics(
     State(state): State<AppState>,
@@ -4,8 +4,16 @@ mod models;
 mod scheduling;
 mod collaboration;
 mod whiteboard;
+mod crdt;
+mod whiteboard_export;
 mod notifications;
+mod availability;
+mod booking_rules;
+mod recurrence;
+mod workshops;
 mod calendar;
+mod encryption;
+mod outbound;
 mod routes;
 
 use axum::{
@@ -30,6 +38,7 @@ use crate::collaboration::CollaborationService;
 use crate::whiteboard::WhiteboardService;
 use crate::notifications::NotificationService;
 use crate::calendar::CalendarService;
+use crate::workshops::WorkshopService;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -42,6 +51,7 @@ pub struct AppState {
     pub whiteboard_service: WhiteboardService,
     pub notification_service: NotificationService,
     pub calendar_service: CalendarService,
+    pub workshop_service: WorkshopService,
 }
 
 #[tokio::main]
@@ -75,7 +85,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let notification_service = NotificationService::new(&config.notifications).await?;
 
     // Create calendar service
-    let calendar_service = CalendarService::new(&config.calendar);
+    let calendar_service = CalendarService::new(
+        db_pool.clone(),
+        redis_service.clone(),
+        &config.calendar,
+    )?;
 
     // Create scheduling service
     let scheduling_service = SchedulingService::new(
@@ -85,10 +99,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         calendar_service.clone(),
     );
 
+    // Create workshop service
+    let workshop_service = WorkshopService::new(
+        db_pool.clone(),
+        scheduling_service.clone(),
+        notification_service.clone(),
+        &config.meetings,
+    );
+
     // Create whiteboard service
     let whiteboard_service = WhiteboardService::new(
         db_pool.clone(),
         redis_service.clone(),
+        &config.meetings,
     );
 
     // Create collaboration service
@@ -102,6 +125,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     scheduling_service.initialize().await?;
     collaboration_service.initialize().await?;
     whiteboard_service.initialize().await?;
+    calendar_service.initialize().await?;
+    workshop_service.initialize().await?;
 
     // Build application state
     let app_state = AppState {
@@ -114,6 +139,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         whiteboard_service,
         notification_service,
         calendar_service,
+        workshop_service,
     };
 
     // Build CORS layer
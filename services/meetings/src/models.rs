@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use chrono::{DateTime, Utc, NaiveTime};
+use chrono::{DateTime, FixedOffset, Utc, NaiveTime};
 use std::collections::HashMap;
+use rust_decimal::Decimal;
 
 // Session Management Models
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,7 +51,7 @@ pub struct UpdateSessionRequest {
 }
 
 // Session Types and Status
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum SessionType {
     OneOnOne,
     GroupSession,
@@ -78,6 +79,16 @@ pub struct RecurringPattern {
     pub days_of_week: Option<Vec<u8>>, // 0=Sunday, 1=Monday, etc.
     pub end_date: Option<DateTime<Utc>>,
     pub max_occurrences: Option<u32>,
+    // RFC 5545 RRULE, e.g. FREQ=MONTHLY;BYDAY=2TU; replaces the fields above when set
+    #[serde(default)]
+    pub rrule: Option<String>,
+    #[serde(default)]
+    pub rdates: Vec<DateTime<Utc>>,
+    #[serde(default)]
+    pub exdates: Vec<DateTime<Utc>>,
+    // IANA name; occurrences keep their wall-clock time here, UTC if omitted
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +99,27 @@ pub enum RecurrenceFrequency {
     Custom,
 }
 
+// Which occurrences of a series an edit or cancellation applies to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OccurrenceScope {
+    #[default]
+    ThisOccurrence,
+    ThisAndFollowing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateOccurrenceRequest {
+    #[serde(default)]
+    pub scope: OccurrenceScope,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub duration_minutes: Option<u32>,
+    // Only with this_and_following: a new recurrence for the rest of the series
+    pub pattern: Option<RecurringPattern>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecurringSeriesResponse {
     pub series_id: Uuid,
@@ -133,7 +165,7 @@ pub struct AvailabilityRequest {
     pub day_of_week: u8, // 0=Sunday, 1=Monday, etc.
     pub start_time: NaiveTime,
     pub end_time: NaiveTime,
-    pub timezone: String,
+    pub timezone: String, // IANA name, e.g. Europe/Berlin
     pub is_available: bool,
 }
 
@@ -154,10 +186,180 @@ pub struct AvailabilityResponse {
 pub struct AvailabilitySlot {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
+    // The same slot in the viewer's timezone
+    pub local_start_time: DateTime<FixedOffset>,
+    pub local_end_time: DateTime<FixedOffset>,
+    pub timezone: String,
     pub is_available: bool,
+    pub unavailable_reason: Option<SlotUnavailableReason>,
     pub existing_session_id: Option<Uuid>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SlotUnavailableReason {
+    InsufficientNotice,
+    BeyondBookingHorizon,
+    AlreadyBooked,
+    CalendarConflict,
+    BufferConflict,
+    DailyLimitReached,
+    WeeklyLimitReached,
+}
+
+impl std::fmt::Display for SlotUnavailableReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlotUnavailableReason::InsufficientNotice => write!(f, "Too short notice for this mentor"),
+            SlotUnavailableReason::BeyondBookingHorizon => write!(f, "Too far ahead to book with this mentor"),
+            SlotUnavailableReason::AlreadyBooked => write!(f, "Mentor already has a session at this time"),
+            SlotUnavailableReason::CalendarConflict => write!(f, "Mentor is busy in their calendar at this time"),
+            SlotUnavailableReason::BufferConflict => write!(f, "Too close to another of the mentor's sessions"),
+            SlotUnavailableReason::DailyLimitReached => write!(f, "Mentor has reached their session limit for this day"),
+            SlotUnavailableReason::WeeklyLimitReached => write!(f, "Mentor has reached their session limit for this week"),
+        }
+    }
+}
+
+// Booking Policies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookingRules {
+    pub min_notice_minutes: u32,
+    pub max_horizon_days: u32,
+    pub buffer_before_minutes: u32,
+    pub buffer_after_minutes: u32,
+    pub max_sessions_per_day: Option<u32>,
+    pub max_sessions_per_week: Option<u32>,
+    // Empty allows any duration within the platform limits
+    pub allowed_durations: Vec<u32>,
+}
+
+// Per-session-type changes to the mentor's rules; unset fields keep the default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookingRuleOverrides {
+    pub bookable: Option<bool>,
+    pub min_notice_minutes: Option<u32>,
+    pub max_horizon_days: Option<u32>,
+    pub buffer_before_minutes: Option<u32>,
+    pub buffer_after_minutes: Option<u32>,
+    pub max_sessions_per_day: Option<u32>,
+    pub max_sessions_per_week: Option<u32>,
+    pub allowed_durations: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookingPolicy {
+    pub user_id: Uuid,
+    // Days and weeks for the session caps are counted in this timezone
+    pub timezone: String,
+    pub rules: BookingRules,
+    pub session_types: HashMap<SessionType, BookingRuleOverrides>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookingPolicyRequest {
+    pub timezone: String,
+    pub rules: BookingRules,
+    #[serde(default)]
+    pub session_types: HashMap<SessionType, BookingRuleOverrides>,
+}
+
+// Workshops
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkshopRequest {
+    pub capacity: u32,
+    // Unset or zero for a free workshop
+    pub seat_price: Option<Decimal>,
+    pub currency: Option<String>,
+    pub waitlist_enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workshop {
+    pub session_id: Uuid,
+    pub capacity: u32,
+    pub seat_price: Decimal,
+    pub currency: String,
+    pub waitlist_enabled: bool,
+    // Confirmed seats plus unexpired holds
+    pub seats_taken: u32,
+    pub seats_available: u32,
+    pub waitlist_length: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SeatStatus {
+    Held,
+    Confirmed,
+    Waitlisted,
+    Cancelled,
+    Expired,
+}
+
+impl SeatStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeatStatus::Held => "held",
+            SeatStatus::Confirmed => "confirmed",
+            SeatStatus::Waitlisted => "waitlisted",
+            SeatStatus::Cancelled => "cancelled",
+            SeatStatus::Expired => "expired",
+        }
+    }
+}
+
+impl std::str::FromStr for SeatStatus {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "held" => Ok(SeatStatus::Held),
+            "confirmed" => Ok(SeatStatus::Confirmed),
+            "waitlisted" => Ok(SeatStatus::Waitlisted),
+            "cancelled" => Ok(SeatStatus::Cancelled),
+            "expired" => Ok(SeatStatus::Expired),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkshopSeat {
+    pub seat_id: Uuid,
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub status: SeatStatus,
+    pub amount: Decimal,
+    // A held seat goes to the next in line unless paid for by then
+    pub hold_expires_at: Option<DateTime<Utc>>,
+    // Place in line, from 1, while waitlisted
+    pub waitlist_position: Option<u32>,
+    pub payment_id: Option<Uuid>,
+    pub refund_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaySeatRequest {
+    pub payment_method_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkshopAttendee {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub seat: WorkshopSeat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkshopAttendeeList {
+    pub workshop: Workshop,
+    pub confirmed: Vec<WorkshopAttendee>,
+    pub held: Vec<WorkshopAttendee>,
+    pub waitlist: Vec<WorkshopAttendee>,
+}
+
 // Session Materials
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMaterial {
@@ -200,6 +402,9 @@ pub struct WhiteboardState {
     pub version: u64,
     pub last_modified: DateTime<Utc>,
     pub last_modified_by: Uuid,
+    // Highest Lamport counter applied from each replica; clients sync from here
+    #[serde(default)]
+    pub state_vector: HashMap<Uuid, u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -220,9 +425,11 @@ pub enum ElementType {
     FreeDrawing,
     Image,
     Sticky,
+    // Groups elements into a region; each frame becomes a page when exporting
+    Frame,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -248,14 +455,195 @@ pub enum OperationType {
     Clear,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Svg,
+    Png,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardExportRequest {
+    pub format: ExportFormat,
+    pub width: Option<u32>, // PNG width in pixels
+    pub frame_id: Option<Uuid>, // Export one frame instead of the whole board
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachWhiteboardExportRequest {
+    pub format: ExportFormat,
+    pub width: Option<u32>,
+    pub frame_id: Option<Uuid>,
+    pub note_id: Option<Uuid>, // Attach to this note instead of creating one
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardExportResponse {
+    pub material_id: Uuid,
+    pub note_id: Uuid,
+    pub file_name: String,
+    pub mime_type: String,
+    pub file_size: i64,
+}
+
+/// Identifies a delta and orders concurrent writes. Counters are Lamport
+/// clocks, so a write made after seeing another always wins; writes made
+/// without seeing each other are ordered by replica id, the same way on
+/// every replica.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+    pub counter: u64,
+    pub replica_id: Uuid,
+}
+
+/// A batch of changes made by one user on one replica (a browser tab, or
+/// the server itself). Replicas exchange deltas rather than whole states.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardDelta {
+    pub stamp: Stamp,
+    pub user_id: Uuid,
+    pub changes: Vec<ElementChange>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ElementChange {
+    Create {
+        element_id: Uuid,
+        element_type: ElementType,
+        position: Position,
+        properties: HashMap<String, serde_json::Value>,
+    },
+    Move {
+        element_id: Uuid,
+        position: Position,
+    },
+    // A null value removes the property
+    SetProperties {
+        element_id: Uuid,
+        properties: HashMap<String, serde_json::Value>,
+    },
+    Delete {
+        element_id: Uuid,
+    },
+    Restore {
+        element_id: Uuid,
+    },
+}
+
+impl ElementChange {
+    pub fn element_id(&self) -> Uuid {
+        match self {
+            ElementChange::Create { element_id, .. }
+            | ElementChange::Move { element_id, .. }
+            | ElementChange::SetProperties { element_id, .. }
+            | ElementChange::Delete { element_id }
+            | ElementChange::Restore { element_id } => *element_id,
+        }
+    }
+}
+
+/// Sent by a client when it (re)connects: the deltas it made while offline
+/// and the state vector of everything it has seen. `client_id` is the
+/// client's own id, which its replica id is derived from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardSyncRequest {
+    pub client_id: Uuid,
+    #[serde(default)]
+    pub state_vector: HashMap<Uuid, u64>,
+    #[serde(default)]
+    pub deltas: Vec<WhiteboardDelta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardSyncResponse {
+    pub whiteboard_id: Uuid,
+    // The replica the client must stamp its deltas with
+    pub replica_id: Uuid,
+    // Deltas the client has not seen, in the order the server applied them
+    pub deltas: Vec<WhiteboardDelta>,
+    pub state_vector: HashMap<Uuid, u64>,
+    // Set instead of deltas when the ones the client is missing were compacted;
+    // the client replaces its local state with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<WhiteboardState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotType {
+    Named,
+    Compaction, // Replaces the operations it was built from
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardSnapshot {
+    pub snapshot_id: Uuid,
+    pub whiteboard_id: Uuid,
+    pub name: Option<String>,
+    pub snapshot_type: SnapshotType,
+    pub version: u64,
+    pub element_count: usize,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWhiteboardSnapshotRequest {
+    pub name: String,
+}
+
+/// One logged edit of a whiteboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardVersion {
+    pub version: u64,
+    pub user_id: Uuid,
+    pub change_count: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardHistoryResponse {
+    pub whiteboard_id: Uuid,
+    pub current_version: u64,
+    pub versions: Vec<WhiteboardVersion>, // Newest first
+    pub snapshots: Vec<WhiteboardSnapshot>,
+    // Versions up to here only survive in snapshots
+    pub compacted_through: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreWhiteboardRequest {
+    pub version: Option<u64>,
+    pub snapshot_id: Option<Uuid>,
+}
+
+/// The whiteboard as it was when a replay starts, and every delta made
+/// after it in the order they were applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardReplay {
+    pub start: WhiteboardState,
+    pub steps: Vec<WhiteboardReplayStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteboardReplayStep {
+    pub version: u64,
+    pub delta: WhiteboardDelta,
+}
+
 // Collaboration Models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum CollaborationMessage {
     // Whiteboard operations
-    WhiteboardUpdate {
+    WhiteboardDelta {
         whiteboard_id: Uuid,
-        operation: WhiteboardOperation,
+        delta: WhiteboardDelta,
     },
     WhiteboardSync {
         whiteboard_id: Uuid,
@@ -354,12 +742,82 @@ pub struct CalendarEvent {
     pub calendar_provider: CalendarProvider,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum CalendarProvider {
     Google,
     Outlook,
     Apple,
     ICalendar,
+    CalDav,
+}
+
+impl CalendarProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalendarProvider::Google => "google",
+            CalendarProvider::Outlook => "outlook",
+            CalendarProvider::Apple => "apple",
+            CalendarProvider::ICalendar => "icalendar",
+            CalendarProvider::CalDav => "caldav",
+        }
+    }
+}
+
+impl std::str::FromStr for CalendarProvider {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "google" => Ok(CalendarProvider::Google),
+            "outlook" => Ok(CalendarProvider::Outlook),
+            "apple" => Ok(CalendarProvider::Apple),
+            "icalendar" => Ok(CalendarProvider::ICalendar),
+            "caldav" => Ok(CalendarProvider::CalDav),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConnection {
+    pub connection_id: Uuid,
+    pub provider: CalendarProvider,
+    pub calendar_id: String,
+    pub display_name: Option<String>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectCalendarRequest {
+    pub provider: CalendarProvider,
+    // OAuth providers (Google, Outlook)
+    pub authorization_code: Option<String>,
+    pub state: Option<String>,
+    pub calendar_id: Option<String>,
+    // CalDAV providers (Apple, CalDav)
+    pub calendar_url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarAuthorizationResponse {
+    pub authorization_url: String,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarImport {
+    pub import_id: Uuid,
+    pub name: String,
+    pub event_count: u32,
+    pub busy_period_count: u32,
+    // Recurring events are expanded up to here
+    pub busy_until: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
 }
 
 // Analytics and Reporting
@@ -0,0 +1,109 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use reqwest::Client;
+use url::Url;
+
+use linkwithmentor_common::AppError;
+
+/// Whether `url` is https on one of `allowed_hosts`, without credentials in
+/// the URL itself.
+pub fn https_host_allowed(allowed_hosts: &[String], url: &Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+
+    url.scheme() == "https"
+        && url.username().is_empty()
+        && url.password().is_none()
+        && allowed_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host))
+}
+
+/// A client for requests to a user-supplied URL. The host must only resolve
+/// to public addresses; the client connects to the address checked here, so
+/// the name can't be pointed at an internal host in between, and doesn't
+/// follow redirects anywhere else.
+pub async fn pinned_client(url: &Url, timeout: Duration) -> Result<Client, AppError> {
+    let host = url.host_str()
+        .ok_or_else(|| AppError::BadRequest("URL has no host".to_string()))?
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| AppError::ExternalService(format!("Failed to resolve {}: {}", host, e)))?
+        .collect();
+    if addresses.is_empty() || !addresses.iter().all(|address| is_public_ip(address.ip())) {
+        return Err(AppError::Forbidden(format!("{} does not resolve to a public address", host)));
+    }
+
+    Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(&host, addresses[0])
+        .timeout(timeout)
+        .build()
+        .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Whether an address is on the public internet rather than this host, a
+/// private network or a link-local one such as cloud metadata endpoints.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // 0.0.0.0/8 and carrier-grade NAT 100.64.0.0/10
+                || octets[0] == 0
+                || (octets[0] == 100 && octets[1] & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local fc00::/7 and link-local fe80::/10
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_public_addresses_are_allowed() {
+        for ip in ["8.8.8.8", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1", "10.0.0.1", "172.16.0.1", "192.168.1.1", "169.254.169.254",
+            "0.0.0.0", "100.64.0.1", "255.255.255.255", "::1", "::", "fd00::1",
+            "fe80::1", "::ffff:127.0.0.1", "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_https_host_allowed() {
+        let hosts = vec!["materials.example.com".to_string()];
+        let allowed = |value: &str| https_host_allowed(&hosts, &Url::parse(value).unwrap());
+
+        assert!(allowed("https://materials.example.com/a.png"));
+        assert!(allowed("https://Materials.Example.com/a.png"));
+        assert!(!allowed("http://materials.example.com/a.png"));
+        assert!(!allowed("https://user@materials.example.com/a.png"));
+        assert!(!allowed("https://materials.example.com.evil.com/a.png"));
+        assert!(!allowed("https://169.254.169.254/latest/meta-data"));
+        assert!(!https_host_allowed(&[], &Url::parse("https://materials.example.com/a.png").unwrap()));
+    }
+}
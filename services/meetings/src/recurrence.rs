@@ -0,0 +1,240 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc, Weekday};
+use chrono_tz::Tz;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    availability,
+    calendar::ical::{self, Frequency, IcalTime, RecurrenceRule, MAX_RECURRENCE_INTERVAL},
+    models::{RecurrenceFrequency, RecurringPattern},
+};
+
+// Sessions of a series are created this far ahead; a daily job extends open-ended series
+pub const MATERIALIZE_DAYS: i64 = 90;
+// Upper bound on the sessions created for one series at a time
+pub const MAX_SERIES_OCCURRENCES: usize = 500;
+
+/// The recurrence set of a session series. Occurrences keep the wall-clock
+/// time of `start` in the series timezone across offset changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesRecurrence {
+    pub start: DateTime<Utc>,
+    pub timezone: Tz,
+    pub rule: RecurrenceRule,
+    pub rdates: Vec<DateTime<Utc>>,
+    pub exdates: Vec<DateTime<Utc>>,
+}
+
+impl SeriesRecurrence {
+    pub fn from_pattern(pattern: &RecurringPattern, start: DateTime<Utc>) -> Result<Self, AppError> {
+        let timezone = match &pattern.timezone {
+            Some(name) => availability::parse_timezone(name)?,
+            None => Tz::UTC,
+        };
+        let rule = match &pattern.rrule {
+            Some(rrule) => rrule.parse::<RecurrenceRule>().map_err(AppError::BadRequest)?,
+            None => simple_rule(pattern)?,
+        };
+
+        Ok(Self {
+            start,
+            timezone,
+            rule,
+            rdates: pattern.rdates.clone(),
+            exdates: pattern.exdates.clone(),
+        })
+    }
+
+    pub fn to_pattern(&self) -> RecurringPattern {
+        let frequency = match self.rule.frequency {
+            Frequency::Daily => RecurrenceFrequency::Daily,
+            Frequency::Weekly => RecurrenceFrequency::Weekly,
+            Frequency::Monthly => RecurrenceFrequency::Monthly,
+            Frequency::Yearly => RecurrenceFrequency::Custom,
+        };
+        // Plain weekdays map onto days_of_week; ordinals like 2TU only fit the rrule
+        let days_of_week = (!self.rule.by_day.is_empty() && self.rule.by_day.iter().all(|(ordinal, _)| ordinal.is_none()))
+            .then(|| self.rule.by_day.iter().map(|(_, weekday)| weekday.num_days_from_sunday() as u8).collect());
+
+        RecurringPattern {
+            frequency,
+            interval: self.rule.interval,
+            days_of_week,
+            end_date: self.rule.until,
+            max_occurrences: self.rule.count,
+            rrule: Some(self.rule.to_string()),
+            rdates: self.rdates.clone(),
+            exdates: self.exdates.clone(),
+            timezone: Some(self.timezone.name().to_string()),
+        }
+    }
+
+    /// Occurrence starts before `until`, in order.
+    pub fn occurrences(&self, until: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut starts = ical::expand(&self.dtstart(), Some(&self.rule), &self.rdates, &self.exdates, until);
+        starts.truncate(MAX_SERIES_OCCURRENCES);
+        starts
+    }
+
+    /// Splits the series at the occurrence originally starting at `at`, for a
+    /// "this and following" edit. The first part ends before it; the second
+    /// starts at `new_start` and keeps the rule, moved by the same number of
+    /// days on weekday-based rules.
+    pub fn split(&self, at: DateTime<Utc>, new_start: DateTime<Utc>) -> (SeriesRecurrence, SeriesRecurrence) {
+        let mut before = self.clone();
+        before.rule.count = None;
+        before.rule.until = Some(at - Duration::seconds(1));
+        before.rdates.retain(|rdate| *rdate < at);
+        before.exdates.retain(|exdate| *exdate < at);
+
+        let shift = self.local(new_start) - self.local(at);
+        let days = self.local(new_start).date().signed_duration_since(self.local(at).date()).num_days();
+
+        let mut after = self.clone();
+        after.start = new_start;
+        // COUNT is shared between the parts; it covers the rule's instances, excluded or not
+        if let Some(count) = self.rule.count {
+            let taken = ical::expand(&self.dtstart(), Some(&self.rule), &[], &[], at).len() as u32;
+            after.rule.count = Some(count.saturating_sub(taken).max(1));
+        }
+        after.rule.by_day = self.rule.by_day.iter()
+            .map(|(ordinal, weekday)| (*ordinal, rotate(*weekday, days)))
+            .collect();
+        after.rdates = self.shifted(&self.rdates, at, shift);
+        after.exdates = self.shifted(&self.exdates, at, shift);
+
+        (before, after)
+    }
+
+    fn dtstart(&self) -> IcalTime {
+        IcalTime::Local(self.local(self.start), self.timezone)
+    }
+
+    fn local(&self, instant: DateTime<Utc>) -> NaiveDateTime {
+        instant.with_timezone(&self.timezone).naive_local()
+    }
+
+    // Dates from `at` on, moved by a wall-clock shift in the series timezone
+    fn shifted(&self, dates: &[DateTime<Utc>], at: DateTime<Utc>, shift: Duration) -> Vec<DateTime<Utc>> {
+        dates.iter()
+            .filter(|date| **date >= at)
+            .map(|date| IcalTime::Local(self.local(*date) + shift, self.timezone).to_utc())
+            .collect()
+    }
+}
+
+fn simple_rule(pattern: &RecurringPattern) -> Result<RecurrenceRule, AppError> {
+    let frequency = match pattern.frequency {
+        RecurrenceFrequency::Daily => Frequency::Daily,
+        RecurrenceFrequency::Weekly => Frequency::Weekly,
+        RecurrenceFrequency::Monthly => Frequency::Monthly,
+        RecurrenceFrequency::Custom => {
+            return Err(AppError::BadRequest("Custom recurrence needs an rrule".to_string()));
+        }
+    };
+    if !(1..=MAX_RECURRENCE_INTERVAL).contains(&pattern.interval) {
+        return Err(AppError::BadRequest(format!(
+            "Recurrence interval must be between 1 and {}",
+            MAX_RECURRENCE_INTERVAL
+        )));
+    }
+    if pattern.end_date.is_some() && pattern.max_occurrences.is_some() {
+        return Err(AppError::BadRequest("Set either end_date or max_occurrences, not both".to_string()));
+    }
+    if pattern.max_occurrences == Some(0) {
+        return Err(AppError::BadRequest("max_occurrences must be at least 1".to_string()));
+    }
+
+    let mut rule = RecurrenceRule::new(frequency);
+    rule.interval = pattern.interval;
+    rule.until = pattern.end_date;
+    rule.count = pattern.max_occurrences;
+    for day in pattern.days_of_week.iter().flatten() {
+        let weekday = (*day <= 6).then(|| rotate(Weekday::Sun, *day as i64))
+            .ok_or_else(|| AppError::BadRequest("Day of week must be between 0 (Sunday) and 6".to_string()))?;
+        rule.by_day.push((None, weekday));
+    }
+
+    Ok(rule)
+}
+
+fn rotate(weekday: Weekday, days: i64) -> Weekday {
+    (0..days.rem_euclid(7)).fold(weekday, |weekday, _| weekday.succ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn pattern(frequency: RecurrenceFrequency) -> RecurringPattern {
+        RecurringPattern {
+            frequency,
+            interval: 1,
+            days_of_week: None,
+            end_date: None,
+            max_occurrences: None,
+            rrule: None,
+            rdates: Vec::new(),
+            exdates: Vec::new(),
+            timezone: Some("America/New_York".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_simple_pattern_keeps_local_time() {
+        let mut weekly = pattern(RecurrenceFrequency::Weekly);
+        weekly.days_of_week = Some(vec![2, 4]);
+        weekly.max_occurrences = Some(4);
+
+        // Tuesday 17:00 in New York, with the clocks going back on 3 November
+        let series = SeriesRecurrence::from_pattern(&weekly, utc("2024-10-29T21:00:00Z")).unwrap();
+        assert_eq!(series.occurrences(utc("2025-01-01T00:00:00Z")), vec![
+            utc("2024-10-29T21:00:00Z"),
+            utc("2024-10-31T21:00:00Z"),
+            utc("2024-11-05T22:00:00Z"),
+            utc("2024-11-07T22:00:00Z"),
+        ]);
+        assert_eq!(series.to_pattern().rrule.as_deref(), Some("FREQ=WEEKLY;COUNT=4;BYDAY=TU,TH"));
+    }
+
+    #[test]
+    fn test_split_this_and_following() {
+        let mut monthly = pattern(RecurrenceFrequency::Custom);
+        monthly.rrule = Some("FREQ=MONTHLY;BYDAY=2TU;COUNT=6".to_string());
+        monthly.exdates = vec![utc("2024-12-10T23:00:00Z"), utc("2025-02-11T23:00:00Z")];
+        let series = SeriesRecurrence::from_pattern(&monthly, utc("2024-10-08T22:00:00Z")).unwrap();
+
+        // January's occurrence moves a day and an hour later; the months after follow on the second Wednesday
+        let (before, after) = series.split(utc("2025-01-14T23:00:00Z"), utc("2025-01-16T00:00:00Z"));
+
+        assert_eq!(before.occurrences(utc("2026-01-01T00:00:00Z")), vec![
+            utc("2024-10-08T22:00:00Z"),
+            utc("2024-11-12T23:00:00Z"),
+        ]);
+        assert_eq!(after.rule.to_string(), "FREQ=MONTHLY;COUNT=3;BYDAY=2WE");
+        assert_eq!(after.occurrences(utc("2026-01-01T00:00:00Z")), vec![
+            utc("2025-01-16T00:00:00Z"),
+            utc("2025-03-12T23:00:00Z"),
+        ]);
+        assert_eq!(after.exdates, vec![utc("2025-02-13T00:00:00Z")]);
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        let start = utc("2024-10-08T22:00:00Z");
+
+        assert!(SeriesRecurrence::from_pattern(&pattern(RecurrenceFrequency::Custom), start).is_err());
+
+        let mut both_ends = pattern(RecurrenceFrequency::Daily);
+        both_ends.end_date = Some(utc("2024-12-01T00:00:00Z"));
+        both_ends.max_occurrences = Some(3);
+        assert!(SeriesRecurrence::from_pattern(&both_ends, start).is_err());
+
+        let mut bad_rule = pattern(RecurrenceFrequency::Custom);
+        bad_rule.rrule = Some("FREQ=MINUTELY".to_string());
+        assert!(SeriesRecurrence::from_pattern(&bad_rule, start).is_err());
+    }
+}
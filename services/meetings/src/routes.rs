@@ -28,20 +28,48 @@ pub fn create_routes() -> Router<AppState> {
         .route("/sessions/:session_id/confirm", post(handlers::confirm_session))
         .route("/sessions/:session_id/reschedule", put(handlers::reschedule_session))
         
+        // Workshop seats
+        .route("/sessions/:session_id/workshop", get(handlers::get_workshop))
+        .route("/sessions/:session_id/workshop", put(handlers::set_workshop))
+        .route("/sessions/:session_id/workshop/attendees", get(handlers::get_workshop_attendees))
+        .route("/sessions/:session_id/workshop/seat", get(handlers::get_workshop_seat))
+        .route("/sessions/:session_id/workshop/seat", post(handlers::book_workshop_seat))
+        .route("/sessions/:session_id/workshop/seat", delete(handlers::cancel_workshop_seat))
+        .route("/sessions/:session_id/workshop/seat/payment", post(handlers::pay_workshop_seat))
+        
         // Availability management
         .route("/availability", get(handlers::get_availability))
         .route("/availability", post(handlers::set_availability))
         .route("/availability/:availability_id", put(handlers::update_availability))
         .route("/availability/:availability_id", delete(handlers::delete_availability))
+        .route("/booking-policy", get(handlers::get_booking_policy))
+        .route("/booking-policy", put(handlers::set_booking_policy))
         
         // Calendar integration
         .route("/calendar/events", get(handlers::get_calendar_events))
         .route("/calendar/sync", post(handlers::sync_calendar))
+        .route("/calendar/connections", get(handlers::list_calendar_connections))
+        .route("/calendar/connections", post(handlers::connect_calendar))
+        .route("/calendar/connections/:connection_id", delete(handlers::disconnect_calendar))
+        .route("/calendar/connections/:connection_id/sync", post(handlers::sync_calendar_connection))
+        .route("/calendar/providers/:provider/authorize", get(handlers::authorize_calendar_connection))
+        .route("/calendar/imports", get(handlers::list_calendar_imports))
+        .route("/calendar/imports", post(handlers::import_calendar))
+        .route("/calendar/imports/:import_id", delete(handlers::delete_calendar_import))
         
         // Whiteboard endpoints
         .route("/sessions/:session_id/whiteboard", get(handlers::get_whiteboard_state))
         .route("/sessions/:session_id/whiteboard", post(handlers::update_whiteboard_state))
         .route("/sessions/:session_id/whiteboard/clear", post(handlers::clear_whiteboard))
+        .route("/sessions/:session_id/whiteboard/sync", post(handlers::sync_whiteboard))
+        .route("/sessions/:session_id/whiteboard/undo", post(handlers::undo_whiteboard))
+        .route("/sessions/:session_id/whiteboard/redo", post(handlers::redo_whiteboard))
+        .route("/sessions/:session_id/whiteboard/export", get(handlers::export_whiteboard))
+        .route("/sessions/:session_id/whiteboard/export/notes", post(handlers::attach_whiteboard_export))
+        .route("/sessions/:session_id/whiteboard/history", get(handlers::get_whiteboard_history))
+        .route("/sessions/:session_id/whiteboard/snapshots", post(handlers::create_whiteboard_snapshot))
+        .route("/sessions/:session_id/whiteboard/restore", post(handlers::restore_whiteboard))
+        .route("/sessions/:session_id/whiteboard/replay", get(handlers::replay_whiteboard))
         
         // Materials and file sharing
         .route("/sessions/:session_id/materials", get(handlers::list_materials))
@@ -60,6 +88,8 @@ pub fn create_routes() -> Router<AppState> {
         .route("/recurring-sessions/:recurring_id", get(handlers::get_recurring_session))
         .route("/recurring-sessions/:recurring_id", put(handlers::update_recurring_session))
         .route("/recurring-sessions/:recurring_id", delete(handlers::delete_recurring_session))
+        .route("/recurring-sessions/:recurring_id/occurrences/:session_id", put(handlers::update_recurring_occurrence))
+        .route("/recurring-sessions/:recurring_id/occurrences/:session_id", delete(handlers::cancel_recurring_occurrence))
         
         // Session analytics
         .route("/sessions/:session_id/analytics", get(handlers::get_session_analytics))
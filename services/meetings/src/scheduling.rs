@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use uuid::Uuid;
-use chrono::{DateTime, Utc, Duration, NaiveTime, Datelike, Weekday};
+use chrono::{DateTime, Utc, Duration, NaiveTime, Weekday};
+use chrono_tz::Tz;
 use sqlx::PgPool;
 use tokio_cron_scheduler::{JobScheduler, Job};
 
@@ -10,12 +11,19 @@ use crate::{
         SessionRequest, SessionResponse, SessionStatus, SessionType, RecurringPattern,
         RecurrenceFrequency, AvailabilityRequest, AvailabilityResponse, AvailabilitySlot,
         SessionParticipant, ParticipantRole, ParticipantStatus, RecurringSeriesResponse,
-        UpdateSessionRequest, SessionDb, MeetingsError,
+        UpdateSessionRequest, SessionDb, MeetingsError, BookingPolicy, BookingPolicyRequest,
+        BookingRules, BookingRuleOverrides, SlotUnavailableReason, OccurrenceScope, UpdateOccurrenceRequest,
     },
     notifications::NotificationService,
     calendar::CalendarService,
+    availability::{self, WeeklyRule},
+    booking_rules::{self, BookedSession, SlotChecker},
+    recurrence::{self, SeriesRecurrence},
 };
 
+// Offered slots start on this grid, or every session length if shorter
+const SLOT_INTERVAL_MINUTES: i64 = 30;
+
 #[derive(Clone)]
 pub struct SchedulingService {
     db_pool: PgPool,
@@ -69,11 +77,11 @@ impl SchedulingService {
         // Validate session request
         self.validate_session_request(&request).await?;
 
-        // Check for scheduling conflicts
-        self.check_scheduling_conflicts(&request).await?;
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
 
-        // Check mentor availability
-        self.check_mentor_availability(request.mentor_id, &request.scheduled_start, request.duration_minutes).await?;
+        // Conflicts, availability, the mentor's booking policy and connected calendars
+        self.check_booking(&mut tx, &request, None).await?;
 
         let session_id = Uuid::new_v4();
         let scheduled_end = request.scheduled_start + Duration::minutes(request.duration_minutes as i64);
@@ -98,10 +106,13 @@ impl SchedulingService {
             .bind(&SessionStatus::Scheduled)
             .bind(&request.session_type)
             .bind(now)
-            .execute(&self.db_pool)
+            .execute(&mut *tx)
             .await
             .map_err(|e| AppError::Database(format!("Failed to create session: {}", e)))?;
 
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
         // Add participants
         self.add_session_participant(session_id, request.mentor_id, ParticipantRole::Mentor).await?;
         self.add_session_participant(session_id, mentee_id, ParticipantRole::Mentee).await?;
@@ -132,6 +143,9 @@ impl SchedulingService {
         // Verify user has permission to update session
         self.verify_session_permission(session_id, user_id).await?;
 
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
         let mut update_fields = Vec::new();
         let mut params: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
         let mut param_count = 1;
@@ -149,32 +163,33 @@ impl SchedulingService {
         }
 
         if let Some(scheduled_start) = request.scheduled_start {
-            // Check for conflicts if rescheduling
-            if let Some(duration) = request.duration_minutes {
-                let temp_request = SessionRequest {
-                    mentor_id: Uuid::new_v4(), // Will be filled from existing session
-                    title: "temp".to_string(),
-                    description: None,
-                    scheduled_start,
-                    duration_minutes: duration,
-                    session_type: SessionType::OneOnOne,
-                    recurring_pattern: None,
-                    max_participants: None,
-                    materials: Vec::new(),
-                };
-                self.check_scheduling_conflicts(&temp_request).await?;
-            }
+            // A reschedule goes through the same checks as a new booking; the
+            // session keeps its length unless a new one is given
+            let session = self.get_session(session_id).await?;
+            let duration = request.duration_minutes
+                .unwrap_or_else(|| (session.scheduled_end - session.scheduled_start).num_minutes() as u32);
+            let moved_request = SessionRequest {
+                mentor_id: session.mentor_id,
+                title: session.title.clone(),
+                description: None,
+                scheduled_start,
+                duration_minutes: duration,
+                session_type: session.session_type.clone(),
+                recurring_pattern: None,
+                max_participants: None,
+                materials: Vec::new(),
+            };
+            self.validate_session_request(&moved_request).await?;
+            self.check_booking(&mut tx, &moved_request, Some(session_id)).await?;
 
             update_fields.push(format!("scheduled_start = ${}", param_count));
             params.push(Box::new(scheduled_start));
             param_count += 1;
 
-            if let Some(duration) = request.duration_minutes {
-                let scheduled_end = scheduled_start + Duration::minutes(duration as i64);
-                update_fields.push(format!("scheduled_end = ${}", param_count));
-                params.push(Box::new(scheduled_end));
-                param_count += 1;
-            }
+            let scheduled_end = scheduled_start + Duration::minutes(duration as i64);
+            update_fields.push(format!("scheduled_end = ${}", param_count));
+            params.push(Box::new(scheduled_end));
+            param_count += 1;
         }
 
         if let Some(status) = &request.status {
@@ -205,13 +220,24 @@ impl SchedulingService {
         // This is a simplified version - in a real implementation, you'd need to handle dynamic queries properly
         sqlx::query(&query)
             .bind(session_id)
-            .execute(&self.db_pool)
+            .execute(&mut *tx)
             .await
             .map_err(|e| AppError::Database(format!("Failed to update session: {}", e)))?;
 
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
         // Update cache
         self.cache_session_info(session_id).await?;
 
+        // Keep external calendars in step
+        if request.status == Some(SessionStatus::Cancelled) {
+            self.remove_calendar_events(session_id).await?;
+        } else if request.scheduled_start.is_some() || request.title.is_some() || request.description.is_some() {
+            let session = self.get_session(session_id).await?;
+            self.calendar_service.update_calendar_event(&session).await?;
+        }
+
         // Send notifications if status changed
         if let Some(status) = &request.status {
             match status {
@@ -333,6 +359,13 @@ impl SchedulingService {
         user_id: Uuid,
         availability: Vec<AvailabilityRequest>,
     ) -> Result<Vec<AvailabilityResponse>>, AppError> {
+        for avail in &availability {
+            availability::parse_timezone(&avail.timezone)?;
+            if avail.day_of_week > 6 {
+                return Err(AppError::BadRequest("Day of week must be between 0 (Sunday) and 6".to_string()));
+            }
+        }
+
         let mut responses = Vec::new();
 
         for avail in availability {
@@ -347,6 +380,7 @@ impl SchedulingService {
                 ON CONFLICT (user_id, day_of_week, start_time) 
                 DO UPDATE SET 
                     end_time = EXCLUDED.end_time,
+                    timezone = EXCLUDED.timezone,
                     is_available = EXCLUDED.is_available,
                     updated_at = EXCLUDED.updated_at
                 RETURNING availability_id, created_at, updated_at
@@ -393,39 +427,165 @@ impl SchedulingService {
         start_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
         duration_minutes: u32,
+        session_type: &SessionType,
+        viewer_timezone: Option<&str>,
     ) -> Result<Vec<AvailabilitySlot>, AppError> {
-        // Get mentor's availability
-        let availability = self.get_user_availability(mentor_id).await?;
-        
-        // Get existing sessions in the date range
-        let existing_sessions = self.get_sessions_in_range(mentor_id, start_date, end_date).await?;
-
-        let mut available_slots = Vec::new();
-        let mut current_date = start_date.date_naive();
-        let end_date_naive = end_date.date_naive();
-
-        while current_date <= end_date_naive {
-            let weekday = current_date.weekday().num_days_from_sunday() as u8;
-            
-            // Find availability for this day of week
-            for avail in &availability {
-                if avail.day_of_week == weekday && avail.is_available {
-                    // Generate time slots for this availability window
-                    let slots = self.generate_time_slots(
-                        current_date,
-                        avail.start_time,
-                        avail.end_time,
-                        duration_minutes,
-                        &existing_sessions,
-                    );
-                    available_slots.extend(slots);
+        let viewer_timezone = match viewer_timezone {
+            Some(name) => availability::parse_timezone(name)?,
+            None => Tz::UTC,
+        };
+
+        let policy = self.get_booking_policy(mentor_id).await?;
+        let rules = Self::rules_for(&policy, session_type, duration_minutes)?;
+        let policy_timezone = availability::parse_timezone(&policy.timezone)?;
+
+        // Expand the mentor's weekly rules in their own timezone
+        let weekly_rules = Self::weekly_rules(&self.get_user_availability(mentor_id).await?);
+        let windows = availability::expand_weekly_rules(&weekly_rules, start_date, end_date);
+
+        // Sessions either side of the range still count towards buffers and caps
+        let margin = SlotChecker::session_margin(&rules);
+        let sessions = self.get_booked_times(mentor_id, start_date - margin, end_date + margin).await?;
+        let busy_times = self.calendar_service.busy_times(mentor_id, start_date, end_date).await?;
+        let checker = SlotChecker::new(&rules, policy_timezone, Utc::now(), &sessions, &busy_times);
+
+        let duration = Duration::minutes(duration_minutes as i64);
+        let step = Duration::minutes(SLOT_INTERVAL_MINUTES.min(duration_minutes as i64));
+
+        let slots = availability::bookable_slots(&windows, duration, step, &[])
+            .into_iter()
+            .map(|(start_time, end_time)| {
+                let unavailable_reason = checker.check(start_time, end_time).err();
+                let existing_session_id = match unavailable_reason {
+                    Some(SlotUnavailableReason::AlreadyBooked) => {
+                        checker.overlapping_session(start_time, end_time).map(|session| session.session_id)
+                    }
+                    _ => None,
+                };
+
+                AvailabilitySlot {
+                    start_time,
+                    end_time,
+                    local_start_time: availability::localize(start_time, viewer_timezone),
+                    local_end_time: availability::localize(end_time, viewer_timezone),
+                    timezone: viewer_timezone.name().to_string(),
+                    is_available: unavailable_reason.is_none(),
+                    unavailable_reason,
+                    existing_session_id,
                 }
+            })
+            .collect();
+
+        Ok(slots)
+    }
+
+    // Booking Policies
+    pub async fn get_booking_policy(&self, user_id: Uuid) -> Result<BookingPolicy, AppError> {
+        let query = r#"
+            SELECT timezone, min_notice_minutes, max_horizon_days, buffer_before_minutes,
+                   buffer_after_minutes, max_sessions_per_day, max_sessions_per_week,
+                   allowed_durations, session_type_rules
+            FROM booking_policies
+            WHERE user_id = $1
+        "#;
+
+        let row = sqlx::query_as::<_, (String, i32, i32, i32, i32, Option<i32>, Option<i32>, Vec<i32>, serde_json::Value)>(query)
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch booking policy: {}", e)))?;
+
+        // Mentors who never set a policy get the platform defaults
+        let row = match row {
+            Some(row) => row,
+            None => {
+                return Ok(BookingPolicy {
+                    user_id,
+                    timezone: "UTC".to_string(),
+                    rules: BookingRules::default(),
+                    session_types: HashMap::new(),
+                });
             }
+        };
+
+        let session_types = serde_json::from_value(row.8)
+            .map_err(|e| AppError::Internal(format!("Invalid session type rules: {}", e)))?;
+
+        Ok(BookingPolicy {
+            user_id,
+            timezone: row.0,
+            rules: BookingRules {
+                min_notice_minutes: row.1 as u32,
+                max_horizon_days: row.2 as u32,
+                buffer_before_minutes: row.3 as u32,
+                buffer_after_minutes: row.4 as u32,
+                max_sessions_per_day: row.5.map(|x| x as u32),
+                max_sessions_per_week: row.6.map(|x| x as u32),
+                allowed_durations: row.7.into_iter().map(|x| x as u32).collect(),
+            },
+            session_types,
+        })
+    }
 
-            current_date = current_date.succ_opt().unwrap_or(current_date);
+    pub async fn set_booking_policy(
+        &self,
+        user_id: Uuid,
+        request: BookingPolicyRequest,
+    ) -> Result<BookingPolicy, AppError> {
+        availability::parse_timezone(&request.timezone)?;
+        Self::validate_durations(&request.rules.allowed_durations)?;
+        for overrides in request.session_types.values() {
+            Self::validate_overrides(overrides)?;
+        }
+        if request.rules.max_horizon_days == 0 {
+            return Err(AppError::BadRequest("Booking horizon must be at least one day".to_string()));
         }
 
-        Ok(available_slots)
+        let session_types = serde_json::to_value(&request.session_types)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize session type rules: {}", e)))?;
+
+        let query = r#"
+            INSERT INTO booking_policies (
+                user_id, timezone, min_notice_minutes, max_horizon_days, buffer_before_minutes,
+                buffer_after_minutes, max_sessions_per_day, max_sessions_per_week,
+                allowed_durations, session_type_rules, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())
+            ON CONFLICT (user_id)
+            DO UPDATE SET
+                timezone = EXCLUDED.timezone,
+                min_notice_minutes = EXCLUDED.min_notice_minutes,
+                max_horizon_days = EXCLUDED.max_horizon_days,
+                buffer_before_minutes = EXCLUDED.buffer_before_minutes,
+                buffer_after_minutes = EXCLUDED.buffer_after_minutes,
+                max_sessions_per_day = EXCLUDED.max_sessions_per_day,
+                max_sessions_per_week = EXCLUDED.max_sessions_per_week,
+                allowed_durations = EXCLUDED.allowed_durations,
+                session_type_rules = EXCLUDED.session_type_rules,
+                updated_at = NOW()
+        "#;
+
+        let rules = &request.rules;
+        sqlx::query(query)
+            .bind(user_id)
+            .bind(&request.timezone)
+            .bind(rules.min_notice_minutes as i32)
+            .bind(rules.max_horizon_days as i32)
+            .bind(rules.buffer_before_minutes as i32)
+            .bind(rules.buffer_after_minutes as i32)
+            .bind(rules.max_sessions_per_day.map(|x| x as i32))
+            .bind(rules.max_sessions_per_week.map(|x| x as i32))
+            .bind(rules.allowed_durations.iter().map(|x| *x as i32).collect::<Vec<i32>>())
+            .bind(session_types)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save booking policy: {}", e)))?;
+
+        Ok(BookingPolicy {
+            user_id,
+            timezone: request.timezone,
+            rules: request.rules,
+            session_types: request.session_types,
+        })
     }
 
     // Recurring Sessions
@@ -436,13 +596,16 @@ impl SchedulingService {
     ) -> Result<Uuid, AppError> {
         let series_id = Uuid::new_v4();
         let now = Utc::now();
+        let initial_session = self.get_session(initial_session_id).await?;
+        let recurrence = SeriesRecurrence::from_pattern(pattern, initial_session.scheduled_start)?;
 
         // Create recurring series record
         let query = r#"
             INSERT INTO recurring_series (
                 series_id, initial_session_id, frequency, interval_value,
-                days_of_week, end_date, max_occurrences, created_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                days_of_week, end_date, max_occurrences, rrule, rdates, exdates,
+                timezone, series_start, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
         "#;
 
         let days_of_week_json = pattern.days_of_week.as_ref()
@@ -457,15 +620,21 @@ impl SchedulingService {
             .bind(days_of_week_json)
             .bind(pattern.end_date)
             .bind(pattern.max_occurrences.map(|x| x as i32))
+            .bind(recurrence.rule.to_string())
+            .bind(&recurrence.rdates)
+            .bind(&recurrence.exdates)
+            .bind(recurrence.timezone.name())
+            .bind(recurrence.start)
             .bind(now)
             .execute(&self.db_pool)
             .await
             .map_err(|e| AppError::Database(format!("Failed to create recurring series: {}", e)))?;
 
         // Update initial session with series ID
-        let update_query = "UPDATE mentorship_sessions SET recurring_series_id = $1 WHERE session_id = $2";
+        let update_query = "UPDATE mentorship_sessions SET recurring_series_id = $1, recurrence_id = $2 WHERE session_id = $3";
         sqlx::query(update_query)
             .bind(series_id)
+            .bind(initial_session.scheduled_start)
             .bind(initial_session_id)
             .execute(&self.db_pool)
             .await
@@ -477,6 +646,469 @@ impl SchedulingService {
         Ok(series_id)
     }
 
+    pub async fn get_recurring_series(&self, series_id: Uuid) -> Result<RecurringSeriesResponse, AppError> {
+        let series = self.load_series(series_id).await?;
+        let initial_session = self.get_session(series.initial_session_id).await?;
+
+        let query = r#"
+            SELECT session_id FROM mentorship_sessions
+            WHERE recurring_series_id = $1
+            ORDER BY scheduled_start
+        "#;
+
+        let session_ids = sqlx::query_as::<_, (Uuid,)>(query)
+            .bind(series_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch series sessions: {}", e)))?;
+
+        let mut sessions = Vec::with_capacity(session_ids.len());
+        for (session_id,) in session_ids {
+            sessions.push(self.get_session(session_id).await?);
+        }
+
+        Ok(RecurringSeriesResponse {
+            series_id,
+            mentor_id: initial_session.mentor_id,
+            title: initial_session.title,
+            pattern: series.recurrence()?.to_pattern(),
+            sessions,
+            created_at: series.created_at,
+            updated_at: series.updated_at,
+        })
+    }
+
+    /// Edits one occurrence of a series, or splits the series there so the
+    /// edit applies to that occurrence and every one after it.
+    pub async fn update_occurrence(
+        &self,
+        series_id: Uuid,
+        session_id: Uuid,
+        user_id: Uuid,
+        request: UpdateOccurrenceRequest,
+    ) -> Result<RecurringSeriesResponse, AppError> {
+        self.verify_session_permission(session_id, user_id).await?;
+        let series = self.load_series(series_id).await?;
+        let occurrence_start = self.occurrence_start(series_id, session_id).await?;
+
+        if let Some(duration) = request.duration_minutes {
+            if duration < booking_rules::MIN_SESSION_MINUTES || duration > booking_rules::MAX_SESSION_MINUTES {
+                return Err(AppError::BadRequest("Session duration must be between 15 minutes and 4 hours".to_string()));
+            }
+        }
+
+        match request.scope {
+            OccurrenceScope::ThisOccurrence => {
+                if request.pattern.is_some() {
+                    return Err(AppError::BadRequest("A new pattern only applies to this and following occurrences".to_string()));
+                }
+
+                // Moving a session keeps its length unless a new one is given
+                let session = self.get_session(session_id).await?;
+                let duration_minutes = request.duration_minutes.or_else(|| {
+                    request.scheduled_start.map(|_| (session.scheduled_end - session.scheduled_start).num_minutes() as u32)
+                });
+
+                self.update_session(session_id, user_id, UpdateSessionRequest {
+                    title: request.title,
+                    description: request.description,
+                    scheduled_start: request.scheduled_start,
+                    duration_minutes,
+                    status: None,
+                    notes: None,
+                }).await?;
+
+                sqlx::query("UPDATE mentorship_sessions SET is_recurrence_exception = TRUE WHERE session_id = $1")
+                    .bind(session_id)
+                    .execute(&self.db_pool)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to mark edited occurrence: {}", e)))?;
+
+                self.get_recurring_series(series_id).await
+            }
+            OccurrenceScope::ThisAndFollowing => {
+                let following_series_id = self.update_following(&series, session_id, occurrence_start, request).await?;
+                self.get_recurring_series(following_series_id).await
+            }
+        }
+    }
+
+    /// Cancels one occurrence, or this occurrence and every one after it.
+    pub async fn cancel_occurrence(
+        &self,
+        series_id: Uuid,
+        session_id: Uuid,
+        user_id: Uuid,
+        scope: OccurrenceScope,
+    ) -> Result<(), AppError> {
+        self.verify_session_permission(session_id, user_id).await?;
+        let series = self.load_series(series_id).await?;
+        let occurrence_start = self.occurrence_start(series_id, session_id).await?;
+
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let cancelled: Vec<Uuid> = match scope {
+            OccurrenceScope::ThisOccurrence => {
+                sqlx::query("UPDATE recurring_series SET exdates = array_append(exdates, $2), updated_at = NOW() WHERE series_id = $1")
+                    .bind(series_id)
+                    .bind(occurrence_start)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to exclude occurrence: {}", e)))?;
+
+                vec![session_id]
+            }
+            OccurrenceScope::ThisAndFollowing => {
+                let (before, _) = series.recurrence()?.split(occurrence_start, occurrence_start);
+                Self::save_recurrence(&mut tx, series_id, &before).await?;
+                Self::following_sessions(&mut tx, series_id, occurrence_start).await?
+            }
+        };
+
+        sqlx::query("UPDATE mentorship_sessions SET status = $1, updated_at = NOW() WHERE session_id = ANY($2) AND status = 'scheduled'")
+            .bind(&SessionStatus::Cancelled)
+            .bind(&cancelled)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to cancel occurrences: {}", e)))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        for session_id in cancelled {
+            self.remove_calendar_events(session_id).await?;
+            self.send_session_notifications(session_id, crate::models::NotificationType::SessionCancellation).await?;
+            self.cache_session_info(session_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates sessions for open-ended series as their occurrences come within
+    /// `recurrence::MATERIALIZE_DAYS`.
+    pub async fn extend_recurring_series(&self) -> Result<(), AppError> {
+        let horizon = Utc::now() + Duration::days(recurrence::MATERIALIZE_DAYS);
+        let query = r#"
+            SELECT series_id, initial_session_id, rrule, rdates, exdates, timezone,
+                   series_start, created_at, updated_at
+            FROM recurring_series
+            WHERE rrule IS NOT NULL AND materialized_until < $1
+        "#;
+
+        let series_rows = sqlx::query_as::<_, SeriesRow>(query)
+            .bind(horizon)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch recurring series: {}", e)))?;
+
+        for series in series_rows {
+            let result = match series.recurrence() {
+                Ok(recurrence) => self.materialize_series(series.series_id, &recurrence, horizon).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::error!("Failed to extend recurring series {}: {}", series.series_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn update_following(
+        &self,
+        series: &SeriesRow,
+        session_id: Uuid,
+        occurrence_start: DateTime<Utc>,
+        request: UpdateOccurrenceRequest,
+    ) -> Result<Uuid, AppError> {
+        let recurrence = series.recurrence()?;
+        let session = self.get_session(session_id).await?;
+        let new_start = request.scheduled_start.unwrap_or(occurrence_start);
+        let duration = request.duration_minutes
+            .map(|minutes| Duration::minutes(minutes as i64))
+            .unwrap_or(session.scheduled_end - session.scheduled_start);
+
+        let (before, mut following) = recurrence.split(occurrence_start, new_start);
+        if let Some(pattern) = &request.pattern {
+            following = SeriesRecurrence::from_pattern(pattern, new_start)?;
+        }
+
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // Editing from the first occurrence changes the whole series in place
+        let following_series_id = if occurrence_start <= recurrence.start {
+            Self::save_recurrence(&mut tx, series.series_id, &following).await?;
+            series.series_id
+        } else {
+            Self::save_recurrence(&mut tx, series.series_id, &before).await?;
+
+            let following_series_id = Uuid::new_v4();
+            let pattern = following.to_pattern();
+            let query = r#"
+                INSERT INTO recurring_series (
+                    series_id, initial_session_id, frequency, interval_value, end_date,
+                    max_occurrences, rrule, rdates, exdates, timezone, series_start,
+                    split_from_series_id, created_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())
+            "#;
+
+            sqlx::query(query)
+                .bind(following_series_id)
+                .bind(session_id)
+                .bind(&pattern.frequency)
+                .bind(pattern.interval as i32)
+                .bind(pattern.end_date)
+                .bind(pattern.max_occurrences.map(|x| x as i32))
+                .bind(following.rule.to_string())
+                .bind(&following.rdates)
+                .bind(&following.exdates)
+                .bind(following.timezone.name())
+                .bind(following.start)
+                .bind(series.series_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to create recurring series: {}", e)))?;
+
+            following_series_id
+        };
+
+        // Move the existing sessions onto the new occurrences in order; clear
+        // their occurrence ids first so none collide on the way
+        let sessions = Self::following_sessions(&mut tx, series.series_id, occurrence_start).await?;
+        sqlx::query("UPDATE mentorship_sessions SET recurrence_id = NULL WHERE session_id = ANY($1)")
+            .bind(&sessions)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update occurrences: {}", e)))?;
+
+        let horizon = Utc::now() + Duration::days(recurrence::MATERIALIZE_DAYS);
+        let starts = following.occurrences(horizon);
+        let mut moved = Vec::new();
+        let mut cancelled = Vec::new();
+
+        for (index, following_session_id) in sessions.iter().enumerate() {
+            let Some(start) = starts.get(index) else {
+                cancelled.push(*following_session_id);
+                continue;
+            };
+
+            let query = r#"
+                UPDATE mentorship_sessions
+                SET scheduled_start = $2, scheduled_end = $3, title = COALESCE($4, title),
+                    description = COALESCE($5, description), recurring_series_id = $6,
+                    recurrence_id = $2, is_recurrence_exception = FALSE, updated_at = NOW()
+                WHERE session_id = $1
+            "#;
+
+            sqlx::query(query)
+                .bind(following_session_id)
+                .bind(start)
+                .bind(*start + duration)
+                .bind(&request.title)
+                .bind(&request.description)
+                .bind(following_series_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update occurrence: {}", e)))?;
+
+            moved.push(*following_session_id);
+        }
+
+        sqlx::query("UPDATE mentorship_sessions SET status = $1, updated_at = NOW() WHERE session_id = ANY($2)")
+            .bind(&SessionStatus::Cancelled)
+            .bind(&cancelled)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to cancel occurrences: {}", e)))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        for session_id in &moved {
+            self.cache_session_info(*session_id).await?;
+            let session = self.get_session(*session_id).await?;
+            self.calendar_service.update_calendar_event(&session).await?;
+        }
+        for session_id in cancelled {
+            self.remove_calendar_events(session_id).await?;
+            self.send_session_notifications(session_id, crate::models::NotificationType::SessionCancellation).await?;
+            self.cache_session_info(session_id).await?;
+        }
+        self.send_session_notifications(session_id, crate::models::NotificationType::SessionRescheduled).await?;
+
+        // Occurrences that had no session yet, e.g. after moving to a tighter rule
+        self.materialize_series(following_series_id, &following, horizon).await?;
+
+        Ok(following_series_id)
+    }
+
+    // Creates the sessions for occurrences before `until` that don't have one,
+    // copying the details and participants of the series' latest session
+    async fn materialize_series(
+        &self,
+        series_id: Uuid,
+        recurrence: &SeriesRecurrence,
+        until: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let template_query = r#"
+            SELECT session_id FROM mentorship_sessions
+            WHERE recurring_series_id = $1
+            ORDER BY recurrence_id DESC NULLS LAST
+            LIMIT 1
+        "#;
+
+        let (template_id,) = sqlx::query_as::<_, (Uuid,)>(template_query)
+            .bind(series_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch series template: {}", e)))?;
+        let template = self.get_session(template_id).await?;
+        let duration = template.scheduled_end - template.scheduled_start;
+
+        let insert_query = r#"
+            INSERT INTO mentorship_sessions (
+                session_id, mentor_id, mentee_id, title, description, scheduled_start,
+                scheduled_end, status, session_type, recurring_series_id, recurrence_id, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $6, NOW())
+            ON CONFLICT (recurring_series_id, recurrence_id) WHERE recurrence_id IS NOT NULL DO NOTHING
+        "#;
+
+        let now = Utc::now();
+        for start in recurrence.occurrences(until).into_iter().filter(|start| *start > now) {
+            let session_id = Uuid::new_v4();
+            let result = sqlx::query(insert_query)
+                .bind(session_id)
+                .bind(template.mentor_id)
+                .bind(template.mentee_id)
+                .bind(&template.title)
+                .bind(&template.description)
+                .bind(start)
+                .bind(start + duration)
+                .bind(&SessionStatus::Scheduled)
+                .bind(&template.session_type)
+                .bind(series_id)
+                .execute(&self.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to create recurring session: {}", e)))?;
+
+            if result.rows_affected() == 0 {
+                continue;
+            }
+
+            let participants_query = r#"
+                INSERT INTO session_participants (session_id, user_id, role, status)
+                SELECT $1, user_id, role, 'invited'
+                FROM session_participants
+                WHERE session_id = $2
+            "#;
+
+            sqlx::query(participants_query)
+                .bind(session_id)
+                .bind(template.session_id)
+                .execute(&self.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to copy session participants: {}", e)))?;
+
+            self.create_calendar_events(session_id).await?;
+        }
+
+        sqlx::query("UPDATE recurring_series SET materialized_until = $2 WHERE series_id = $1")
+            .bind(series_id)
+            .bind(until)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update recurring series: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn load_series(&self, series_id: Uuid) -> Result<SeriesRow, AppError> {
+        let query = r#"
+            SELECT series_id, initial_session_id, rrule, rdates, exdates, timezone,
+                   series_start, created_at, updated_at
+            FROM recurring_series
+            WHERE series_id = $1
+        "#;
+
+        sqlx::query_as::<_, SeriesRow>(query)
+            .bind(series_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch recurring series: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Recurring series not found".to_string()))
+    }
+
+    // Where the rule placed the occurrence, even if it has been moved since
+    async fn occurrence_start(&self, series_id: Uuid, session_id: Uuid) -> Result<DateTime<Utc>, AppError> {
+        let query = r#"
+            SELECT COALESCE(recurrence_id, scheduled_start)
+            FROM mentorship_sessions
+            WHERE session_id = $1 AND recurring_series_id = $2
+        "#;
+
+        sqlx::query_as::<_, (DateTime<Utc>,)>(query)
+            .bind(session_id)
+            .bind(series_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch occurrence: {}", e)))?
+            .map(|row| row.0)
+            .ok_or_else(|| AppError::NotFound("Session is not part of this series".to_string()))
+    }
+
+    async fn following_sessions(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        series_id: Uuid,
+        occurrence_start: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, AppError> {
+        let query = r#"
+            SELECT session_id FROM mentorship_sessions
+            WHERE recurring_series_id = $1
+            AND COALESCE(recurrence_id, scheduled_start) >= $2
+            AND status = 'scheduled'
+            ORDER BY COALESCE(recurrence_id, scheduled_start)
+        "#;
+
+        let rows = sqlx::query_as::<_, (Uuid,)>(query)
+            .bind(series_id)
+            .bind(occurrence_start)
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch following occurrences: {}", e)))?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    async fn save_recurrence(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        series_id: Uuid,
+        recurrence: &SeriesRecurrence,
+    ) -> Result<(), AppError> {
+        let query = r#"
+            UPDATE recurring_series
+            SET rrule = $2, rdates = $3, exdates = $4, timezone = $5, series_start = $6,
+                end_date = $7, max_occurrences = $8, updated_at = NOW()
+            WHERE series_id = $1
+        "#;
+
+        sqlx::query(query)
+            .bind(series_id)
+            .bind(recurrence.rule.to_string())
+            .bind(&recurrence.rdates)
+            .bind(&recurrence.exdates)
+            .bind(recurrence.timezone.name())
+            .bind(recurrence.start)
+            .bind(recurrence.rule.until)
+            .bind(recurrence.rule.count.map(|x| x as i32))
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update recurring series: {}", e)))?;
+
+        Ok(())
+    }
+
     // Helper methods
     async fn validate_session_request(&self, request: &SessionRequest) -> Result<(), AppError> {
         // Check if scheduled time is in the future
@@ -484,21 +1116,93 @@ impl SchedulingService {
             return Err(AppError::BadRequest("Session must be scheduled in the future".to_string()));
         }
 
-        // Check duration limits
-        if request.duration_minutes < 15 || request.duration_minutes > 240 {
+        // Check duration limits; how far ahead is up to the mentor's booking policy
+        if request.duration_minutes < booking_rules::MIN_SESSION_MINUTES
+            || request.duration_minutes > booking_rules::MAX_SESSION_MINUTES
+        {
             return Err(AppError::BadRequest("Session duration must be between 15 minutes and 4 hours".to_string()));
         }
 
-        // Check advance booking limit
-        let max_advance = Utc::now() + Duration::days(30);
-        if request.scheduled_start > max_advance {
-            return Err(AppError::BadRequest("Cannot book sessions more than 30 days in advance".to_string()));
+        if let Some(pattern) = &request.recurring_pattern {
+            SeriesRecurrence::from_pattern(pattern, request.scheduled_start)?;
         }
 
         Ok(())
     }
 
-    async fn check_scheduling_conflicts(&self, request: &SessionRequest) -> Result<(), AppError> {
+    /// Runs every check a booking must pass with the mentor's bookings locked.
+    /// The lock is held until `tx` ends, so the session must be written in
+    /// the same transaction; two requests can then never both pass for the
+    /// last slot under a cap or inside each other's buffers. `moving` is a
+    /// session being rescheduled, which doesn't count against its new time.
+    async fn check_booking(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        request: &SessionRequest,
+        moving: Option<Uuid>,
+    ) -> Result<(), AppError> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("mentor_bookings:{}", request.mentor_id))
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to lock mentor bookings: {}", e)))?;
+
+        self.check_scheduling_conflicts(request, moving).await?;
+        self.check_mentor_availability(request.mentor_id, &request.scheduled_start, request.duration_minutes).await?;
+        self.check_booking_rules(request, moving).await
+    }
+
+    async fn check_booking_rules(&self, request: &SessionRequest, moving: Option<Uuid>) -> Result<(), AppError> {
+        let policy = self.get_booking_policy(request.mentor_id).await?;
+        let rules = Self::rules_for(&policy, &request.session_type, request.duration_minutes)?;
+        let policy_timezone = availability::parse_timezone(&policy.timezone)?;
+
+        let scheduled_end = request.scheduled_start + Duration::minutes(request.duration_minutes as i64);
+        let margin = SlotChecker::session_margin(&rules);
+        let mut sessions = self.get_booked_times(request.mentor_id, request.scheduled_start - margin, scheduled_end + margin).await?;
+        sessions.retain(|session| Some(session.session_id) != moving);
+        let busy_times = self.calendar_service.busy_times(request.mentor_id, request.scheduled_start, scheduled_end).await?;
+
+        SlotChecker::new(&rules, policy_timezone, Utc::now(), &sessions, &busy_times)
+            .check(request.scheduled_start, scheduled_end)
+            .map_err(|reason| AppError::BadRequest(reason.to_string()))
+    }
+
+    fn rules_for(policy: &BookingPolicy, session_type: &SessionType, duration_minutes: u32) -> Result<BookingRules, AppError> {
+        let rules = policy.rules.for_session_type(&policy.session_types, session_type)
+            .ok_or_else(|| AppError::BadRequest(format!("Mentor does not offer {:?} sessions", session_type)))?;
+
+        if !rules.allows_duration(duration_minutes) {
+            let message = if rules.allowed_durations.is_empty() {
+                "Session duration must be between 15 minutes and 4 hours".to_string()
+            } else {
+                let allowed: Vec<String> = rules.allowed_durations.iter().map(|x| x.to_string()).collect();
+                format!("Mentor offers sessions of {} minutes", allowed.join(", "))
+            };
+            return Err(AppError::BadRequest(message));
+        }
+
+        Ok(rules)
+    }
+
+    fn validate_durations(durations: &[u32]) -> Result<(), AppError> {
+        if durations.iter().any(|x| *x < booking_rules::MIN_SESSION_MINUTES || *x > booking_rules::MAX_SESSION_MINUTES) {
+            return Err(AppError::BadRequest("Session duration must be between 15 minutes and 4 hours".to_string()));
+        }
+        Ok(())
+    }
+
+    fn validate_overrides(overrides: &BookingRuleOverrides) -> Result<(), AppError> {
+        if let Some(durations) = &overrides.allowed_durations {
+            Self::validate_durations(durations)?;
+        }
+        if overrides.max_horizon_days == Some(0) {
+            return Err(AppError::BadRequest("Booking horizon must be at least one day".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn check_scheduling_conflicts(&self, request: &SessionRequest, moving: Option<Uuid>) -> Result<(), AppError> {
         let session_end = request.scheduled_start + Duration::minutes(request.duration_minutes as i64);
 
         let query = r#"
@@ -506,6 +1210,7 @@ impl SchedulingService {
             FROM mentorship_sessions
             WHERE (mentor_id = $1 OR mentee_id = $1)
             AND status NOT IN ('cancelled', 'completed')
+            AND session_id IS DISTINCT FROM $4
             AND (
                 (scheduled_start <= $2 AND scheduled_end > $2) OR
                 (scheduled_start < $3 AND scheduled_end >= $3) OR
@@ -517,6 +1222,7 @@ impl SchedulingService {
             .bind(request.mentor_id)
             .bind(request.scheduled_start)
             .bind(session_end)
+            .bind(moving)
             .fetch_one(&self.db_pool)
             .await
             .map_err(|e| AppError::Database(format!("Failed to check conflicts: {}", e)))?;
@@ -534,30 +1240,15 @@ impl SchedulingService {
         scheduled_start: &DateTime<Utc>,
         duration_minutes: u32,
     ) -> Result<(), AppError> {
-        let weekday = scheduled_start.weekday().num_days_from_sunday() as u8;
-        let start_time = scheduled_start.time();
-        let end_time = (scheduled_start + Duration::minutes(duration_minutes as i64)).time();
+        let scheduled_end = *scheduled_start + Duration::minutes(duration_minutes as i64);
 
-        let query = r#"
-            SELECT COUNT(*) as available_count
-            FROM user_availability
-            WHERE user_id = $1
-            AND day_of_week = $2
-            AND is_available = true
-            AND start_time <= $3
-            AND end_time >= $4
-        "#;
-
-        let row = sqlx::query_as::<_, (i64,)>(query)
-            .bind(mentor_id)
-            .bind(weekday as i16)
-            .bind(start_time)
-            .bind(end_time)
-            .fetch_one(&self.db_pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to check availability: {}", e)))?;
+        // Windows are clipped to the session, so it fits only if one covers all of it
+        let rules = Self::weekly_rules(&self.get_user_availability(mentor_id).await?);
+        let covered = availability::expand_weekly_rules(&rules, *scheduled_start, scheduled_end)
+            .iter()
+            .any(|(start, end)| start <= scheduled_start && *end >= scheduled_end);
 
-        if row.0 == 0 {
+        if !covered {
             return Err(AppError::BadRequest("Mentor is not available at the requested time".to_string()));
         }
 
@@ -576,13 +1267,13 @@ impl SchedulingService {
     }
 
     async fn create_calendar_events(&self, session_id: Uuid) -> Result<(), AppError> {
-        // Implementation for creating calendar events
+        let session = self.get_session(session_id).await?;
+        self.calendar_service.create_calendar_event(&session).await?;
         Ok(())
     }
 
     async fn remove_calendar_events(&self, session_id: Uuid) -> Result<(), AppError> {
-        // Implementation for removing calendar events
-        Ok(())
+        self.calendar_service.delete_calendar_event(session_id).await
     }
 
     async fn cache_session_info(&self, session_id: Uuid) -> Result<(), AppError> {
@@ -616,30 +1307,82 @@ impl SchedulingService {
     }
 
     async fn get_user_availability(&self, user_id: Uuid) -> Result<Vec<AvailabilityResponse>, AppError> {
-        // Implementation for getting user availability
-        Ok(Vec::new())
+        let query = r#"
+            SELECT availability_id, user_id, day_of_week, start_time, end_time,
+                   timezone, is_available, created_at, updated_at
+            FROM user_availability
+            WHERE user_id = $1
+            ORDER BY day_of_week, start_time
+        "#;
+
+        let rows = sqlx::query_as::<_, (Uuid, Uuid, i16, NaiveTime, NaiveTime, String, bool, DateTime<Utc>, DateTime<Utc>)>(query)
+            .bind(user_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch availability: {}", e)))?;
+
+        Ok(rows.into_iter().map(|row| AvailabilityResponse {
+            availability_id: row.0,
+            user_id: row.1,
+            day_of_week: row.2 as u8,
+            start_time: row.3,
+            end_time: row.4,
+            timezone: row.5,
+            is_available: row.6,
+            created_at: row.7,
+            updated_at: row.8,
+        }).collect())
     }
 
-    async fn get_sessions_in_range(&self, user_id: Uuid, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<SessionResponse>, AppError> {
-        // Implementation for getting sessions in range
-        Ok(Vec::new())
+    async fn get_booked_times(&self, user_id: Uuid, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<BookedSession>, AppError> {
+        let query = r#"
+            SELECT session_id, scheduled_start, scheduled_end
+            FROM mentorship_sessions
+            WHERE (mentor_id = $1 OR mentee_id = $1)
+            AND status NOT IN ('cancelled', 'completed')
+            AND scheduled_start < $3 AND scheduled_end > $2
+            ORDER BY scheduled_start
+        "#;
+
+        let rows = sqlx::query_as::<_, (Uuid, DateTime<Utc>, DateTime<Utc>)>(query)
+            .bind(user_id)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch sessions: {}", e)))?;
+
+        Ok(rows.into_iter().map(|row| BookedSession {
+            session_id: row.0,
+            start: row.1,
+            end: row.2,
+        }).collect())
     }
 
-    async fn generate_time_slots(
-        &self,
-        date: chrono::NaiveDate,
-        start_time: NaiveTime,
-        end_time: NaiveTime,
-        duration_minutes: u32,
-        existing_sessions: &[SessionResponse],
-    ) -> Vec<AvailabilitySlot> {
-        // Implementation for generating time slots
-        Vec::new()
+    fn weekly_rules(availability: &[AvailabilityResponse]) -> Vec<WeeklyRule> {
+        availability.iter()
+            .filter(|avail| avail.is_available)
+            .filter_map(|avail| match availability::parse_timezone(&avail.timezone) {
+                Ok(timezone) => Some(WeeklyRule {
+                    day_of_week: avail.day_of_week,
+                    start_time: avail.start_time,
+                    end_time: avail.end_time,
+                    timezone,
+                }),
+                Err(_) => {
+                    tracing::warn!("Skipping availability {} with unknown timezone {}", avail.availability_id, avail.timezone);
+                    None
+                }
+            })
+            .collect()
     }
 
     async fn schedule_recurring_sessions(&self, series_id: Uuid, initial_session_id: Uuid, pattern: &RecurringPattern) -> Result<(), AppError> {
-        // Implementation for scheduling recurring sessions
-        Ok(())
+        let initial_session = self.get_session(initial_session_id).await?;
+        let recurrence = SeriesRecurrence::from_pattern(pattern, initial_session.scheduled_start)?;
+        let horizon = Utc::now() + Duration::days(recurrence::MATERIALIZE_DAYS);
+
+        self.materialize_series(series_id, &recurrence, horizon).await
     }
 
     async fn schedule_reminder_jobs(&self, scheduler: &JobScheduler) -> Result<(), AppError> {
@@ -648,7 +1391,53 @@ impl SchedulingService {
     }
 
     async fn schedule_recurring_session_jobs(&self, scheduler: &JobScheduler) -> Result<(), AppError> {
-        // Implementation for scheduling recurring session jobs
+        let service = self.clone();
+
+        // Daily at 03:00 UTC
+        let job = Job::new_async("0 0 3 * * *", move |_, _| {
+            let service = service.clone();
+            Box::pin(async move {
+                if let Err(e) = service.extend_recurring_series().await {
+                    tracing::error!("Failed to extend recurring series: {}", e);
+                }
+            })
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to create recurring session job: {}", e)))?;
+
+        scheduler.add(job).await
+            .map_err(|e| AppError::Internal(format!("Failed to schedule recurring session job: {}", e)))?;
+
         Ok(())
     }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SeriesRow {
+    series_id: Uuid,
+    initial_session_id: Uuid,
+    rrule: Option<String>,
+    rdates: Vec<DateTime<Utc>>,
+    exdates: Vec<DateTime<Utc>>,
+    timezone: String,
+    series_start: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl SeriesRow {
+    fn recurrence(&self) -> Result<SeriesRecurrence, AppError> {
+        // Series from before recurrence rules only have the simple columns
+        let (Some(rrule), Some(start)) = (&self.rrule, self.series_start) else {
+            return Err(AppError::BadRequest("This series has no recurrence rule and cannot be edited".to_string()));
+        };
+
+        Ok(SeriesRecurrence {
+            start,
+            timezone: availability::parse_timezone(&self.timezone)?,
+            rule: rrule.parse()
+                .map_err(|e| AppError::Internal(format!("Invalid recurrence rule on series {}: {}", self.series_id, e)))?,
+            rdates: self.rdates.clone(),
+            exdates: self.exdates.clone(),
+        })
+    }
 }
\ No newline at end of file
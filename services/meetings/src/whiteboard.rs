@@ -1,32 +1,96 @@
 use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool, Row};
 use dashmap::DashMap;
+use futures::StreamExt;
 
 use linkwithmentor_common::{AppError, RedisService};
+use crate::config::MeetingsServiceConfig;
+use crate::crdt::{self, UndoHistory, WhiteboardDoc};
 use crate::models::{
-    WhiteboardState, WhiteboardElement, WhiteboardOperation, OperationType,
-    ElementType, Position, CollaborationMessage,
+    WhiteboardState, WhiteboardElement, ElementChange, WhiteboardDelta,
+    WhiteboardSyncRequest, WhiteboardSyncResponse, Position, CollaborationMessage,
+    SnapshotType, WhiteboardSnapshot, WhiteboardVersion, WhiteboardHistoryResponse,
+    RestoreWhiteboardRequest, WhiteboardReplay, WhiteboardReplayStep,
+    ExportFormat, WhiteboardExportRequest, AttachWhiteboardExportRequest, WhiteboardExportResponse,
 };
+use crate::whiteboard_export::{self, WhiteboardRenderer};
 
 #[derive(Clone)]
 pub struct WhiteboardService {
     db_pool: PgPool,
     redis_service: RedisService,
+    // Stamps the changes this instance makes itself
+    replica_id: Uuid,
     // In-memory cache for active whiteboards
-    active_whiteboards: std::sync::Arc<DashMap<Uuid, WhiteboardState>>,
+    active_whiteboards: std::sync::Arc<DashMap<Uuid, ActiveWhiteboard>>,
+    // Undo and redo stacks per (whiteboard, user)
+    undo_histories: std::sync::Arc<DashMap<(Uuid, Uuid), UndoHistory>>,
     // User cursors for real-time collaboration
     user_cursors: std::sync::Arc<DashMap<Uuid, HashMap<Uuid, Position>>>,
+    renderer: WhiteboardRenderer,
+    // Hosts images may be downloaded from when exporting
+    image_hosts: Vec<String>,
+    storage_path: String,
+    // How long operations stay replayable before compaction
+    history_retention: chrono::Duration,
+}
+
+/// A rendered whiteboard, ready to download or store.
+pub struct WhiteboardExport {
+    pub contents: Vec<u8>,
+    pub content_type: &'static str,
+    pub file_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ActiveWhiteboard {
+    whiteboard_id: Uuid,
+    session_id: Uuid,
+    version: u64,
+    last_modified: DateTime<Utc>,
+    last_modified_by: Uuid,
+    doc: WhiteboardDoc,
+}
+
+impl ActiveWhiteboard {
+    fn state(&self) -> WhiteboardState {
+        WhiteboardState {
+            whiteboard_id: self.whiteboard_id,
+            session_id: self.session_id,
+            elements: self.doc.elements(),
+            version: self.version,
+            last_modified: self.last_modified,
+            last_modified_by: self.last_modified_by,
+            state_vector: self.doc.state_vector().clone(),
+        }
+    }
+
+    fn touch(&mut self, user_id: Uuid) {
+        self.version += 1;
+        self.last_modified = Utc::now();
+        self.last_modified_by = user_id;
+    }
 }
 
 impl WhiteboardService {
-    pub fn new(db_pool: PgPool, redis_service: RedisService) -> Self {
+    pub fn new(db_pool: PgPool, redis_service: RedisService, config: &MeetingsServiceConfig) -> Self {
         Self {
             db_pool,
             redis_service,
+            replica_id: Uuid::new_v4(),
             active_whiteboards: std::sync::Arc::new(DashMap::new()),
+            undo_histories: std::sync::Arc::new(DashMap::new()),
             user_cursors: std::sync::Arc::new(DashMap::new()),
+            renderer: WhiteboardRenderer::new(
+                config.whiteboard_fonts_path.as_deref(),
+                &config.whiteboard_default_font,
+            ),
+            image_hosts: config.whiteboard_image_hosts.clone(),
+            storage_path: config.whiteboard_storage_path.clone(),
+            history_retention: chrono::Duration::days(config.whiteboard_history_retention_days as i64),
         }
     }
 
@@ -34,6 +98,10 @@ impl WhiteboardService {
         // Start background tasks for auto-saving and cleanup
         self.start_auto_save_task().await?;
         self.start_cleanup_task().await?;
+        self.start_compaction_task().await?;
+
+        // Merge deltas applied by other instances
+        self.start_delta_listener().await?;
 
         tracing::info!("Whiteboard service initialized");
         Ok(())
@@ -44,64 +112,88 @@ impl WhiteboardService {
         let whiteboard_id = Uuid::new_v4();
         let now = Utc::now();
 
-        let whiteboard_state = WhiteboardState {
+        let whiteboard = ActiveWhiteboard {
             whiteboard_id,
             session_id,
-            elements: Vec::new(),
             version: 1,
             last_modified: now,
             last_modified_by: created_by,
+            doc: WhiteboardDoc::default(),
         };
 
         // Store in database
         let query = r#"
             INSERT INTO whiteboards (
                 whiteboard_id, session_id, elements, version, 
-                last_modified, last_modified_by, created_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                last_modified, last_modified_by, created_at, crdt_state
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
         "#;
 
-        let elements_json = serde_json::to_value(&whiteboard_state.elements)
-            .map_err(|e| AppError::Internal(format!("Failed to serialize elements: {}", e)))?;
+        let crdt_state = serde_json::to_value(&whiteboard.doc)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize whiteboard document: {}", e)))?;
 
         sqlx::query(query)
             .bind(whiteboard_id)
             .bind(session_id)
-            .bind(elements_json)
-            .bind(whiteboard_state.version as i64)
+            .bind(serde_json::json!([]))
+            .bind(whiteboard.version as i64)
             .bind(now)
             .bind(created_by)
             .bind(now)
+            .bind(crdt_state)
             .execute(&self.db_pool)
             .await
             .map_err(|e| AppError::Database(format!("Failed to create whiteboard: {}", e)))?;
 
-        // Cache in memory
-        self.active_whiteboards.insert(whiteboard_id, whiteboard_state.clone());
-
-        // Cache in Redis
-        self.cache_whiteboard_state(&whiteboard_state).await?;
+        // Cache in Redis and memory
+        self.cache_whiteboard(&whiteboard).await?;
+        let state = whiteboard.state();
+        self.active_whiteboards.insert(whiteboard_id, whiteboard);
 
         tracing::info!("Created whiteboard {} for session {}", whiteboard_id, session_id);
-        Ok(whiteboard_state)
+        Ok(state)
+    }
+
+    /// The session's latest whiteboard, created on first use.
+    pub async fn get_session_whiteboard(&self, session_id: Uuid, user_id: Uuid) -> Result<WhiteboardState, AppError> {
+        let whiteboard_id: Option<Uuid> = sqlx::query_scalar(
+            "SELECT whiteboard_id FROM whiteboards WHERE session_id = $1 ORDER BY created_at DESC LIMIT 1"
+        )
+        .bind(session_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch session whiteboard: {}", e)))?;
+
+        match whiteboard_id {
+            Some(whiteboard_id) => self.get_whiteboard(whiteboard_id).await,
+            None => self.create_whiteboard(session_id, user_id).await,
+        }
     }
 
     pub async fn get_whiteboard(&self, whiteboard_id: Uuid) -> Result<WhiteboardState, AppError> {
+        self.ensure_loaded(whiteboard_id).await?;
+
+        self.active_whiteboards.get(&whiteboard_id)
+            .map(|whiteboard| whiteboard.state())
+            .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))
+    }
+
+    async fn ensure_loaded(&self, whiteboard_id: Uuid) -> Result<(), AppError> {
         // Check in-memory cache first
-        if let Some(whiteboard) = self.active_whiteboards.get(&whiteboard_id) {
-            return Ok(whiteboard.clone());
+        if self.active_whiteboards.contains_key(&whiteboard_id) {
+            return Ok(());
         }
 
         // Check Redis cache
-        if let Ok(cached_state) = self.get_cached_whiteboard_state(whiteboard_id).await {
-            self.active_whiteboards.insert(whiteboard_id, cached_state.clone());
-            return Ok(cached_state);
+        if let Ok(Some(cached)) = self.get_cached_whiteboard(whiteboard_id).await {
+            self.active_whiteboards.entry(whiteboard_id).or_insert(cached);
+            return Ok(());
         }
 
         // Load from database
         let query = r#"
             SELECT whiteboard_id, session_id, elements, version, 
-                   last_modified, last_modified_by
+                   last_modified, last_modified_by, crdt_state
             FROM whiteboards 
             WHERE whiteboard_id = $1
         "#;
@@ -114,24 +206,32 @@ impl WhiteboardService {
 
         let row = row.ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
 
-        let elements_json: serde_json::Value = row.get("elements");
-        let elements: Vec<WhiteboardElement> = serde_json::from_value(elements_json)
-            .map_err(|e| AppError::Internal(format!("Failed to deserialize elements: {}", e)))?;
+        let doc = match row.get::<Option<serde_json::Value>, _>("crdt_state") {
+            Some(crdt_state) => serde_json::from_value(crdt_state)
+                .map_err(|e| AppError::Internal(format!("Failed to deserialize whiteboard document: {}", e)))?,
+            None => {
+                // Whiteboards saved before the CRDT only have their elements
+                let elements_json: serde_json::Value = row.get("elements");
+                let elements: Vec<WhiteboardElement> = serde_json::from_value(elements_json)
+                    .map_err(|e| AppError::Internal(format!("Failed to deserialize elements: {}", e)))?;
+                self.doc_from_elements(elements)
+            }
+        };
 
-        let whiteboard_state = WhiteboardState {
+        let whiteboard = ActiveWhiteboard {
             whiteboard_id: row.get("whiteboard_id"),
             session_id: row.get("session_id"),
-            elements,
             version: row.get::<i64, _>("version") as u64,
-            last_modified: row.get("last_modified"),
+            last_modified: row.get::<Option<DateTime<Utc>>, _>("last_modified").unwrap_or_else(Utc::now),
             last_modified_by: row.get("last_modified_by"),
+            doc,
         };
 
         // Cache the result
-        self.active_whiteboards.insert(whiteboard_id, whiteboard_state.clone());
-        self.cache_whiteboard_state(&whiteboard_state).await?;
+        self.cache_whiteboard(&whiteboard).await?;
+        self.active_whiteboards.entry(whiteboard_id).or_insert(whiteboard);
 
-        Ok(whiteboard_state)
+        Ok(())
     }
 
     // Element operations
@@ -141,33 +241,14 @@ impl WhiteboardService {
         element: WhiteboardElement,
         user_id: Uuid,
     ) -> Result<WhiteboardState, AppError> {
-        let mut whiteboard = self.get_whiteboard(whiteboard_id).await?;
-        
-        // Add the element
-        whiteboard.elements.push(element.clone());
-        whiteboard.version += 1;
-        whiteboard.last_modified = Utc::now();
-        whiteboard.last_modified_by = user_id;
-
-        // Update cache
-        self.active_whiteboards.insert(whiteboard_id, whiteboard.clone());
-
-        // Create operation for real-time sync
-        let operation = WhiteboardOperation {
-            operation_type: OperationType::Create,
-            element_id: Some(element.element_id),
-            element: Some(element),
-            user_id,
-            timestamp: Utc::now(),
+        let change = ElementChange::Create {
+            element_id: element.element_id,
+            element_type: element.element_type,
+            position: element.position,
+            properties: element.properties,
         };
 
-        // Broadcast to other users
-        self.broadcast_operation(whiteboard_id, &operation).await?;
-
-        // Schedule auto-save
-        self.schedule_auto_save(whiteboard_id).await?;
-
-        Ok(whiteboard)
+        self.apply_local(whiteboard_id, user_id, vec![change]).await
     }
 
     pub async fn update_element(
@@ -177,37 +258,32 @@ impl WhiteboardService {
         updated_element: WhiteboardElement,
         user_id: Uuid,
     ) -> Result<WhiteboardState, AppError> {
-        let mut whiteboard = self.get_whiteboard(whiteboard_id).await?;
-        
-        // Find and update the element
-        if let Some(element) = whiteboard.elements.iter_mut().find(|e| e.element_id == element_id) {
-            *element = updated_element.clone();
-            whiteboard.version += 1;
-            whiteboard.last_modified = Utc::now();
-            whiteboard.last_modified_by = user_id;
-
-            // Update cache
-            self.active_whiteboards.insert(whiteboard_id, whiteboard.clone());
-
-            // Create operation for real-time sync
-            let operation = WhiteboardOperation {
-                operation_type: OperationType::Update,
-                element_id: Some(element_id),
-                element: Some(updated_element),
-                user_id,
-                timestamp: Utc::now(),
-            };
-
-            // Broadcast to other users
-            self.broadcast_operation(whiteboard_id, &operation).await?;
+        let current = self.get_element(whiteboard_id, element_id).await?;
+
+        // Only send the fields that changed, so concurrent edits to the others survive
+        let mut changes = Vec::new();
+        if current.position != updated_element.position {
+            changes.push(ElementChange::Move {
+                element_id,
+                position: updated_element.position,
+            });
+        }
 
-            // Schedule auto-save
-            self.schedule_auto_save(whiteboard_id).await?;
+        let mut properties: HashMap<String, serde_json::Value> = updated_element.properties.into_iter()
+            .filter(|(key, value)| current.properties.get(key) != Some(value))
+            .collect();
+        for key in current.properties.keys() {
+            properties.entry(key.clone()).or_insert(serde_json::Value::Null);
+        }
+        if !properties.is_empty() {
+            changes.push(ElementChange::SetProperties { element_id, properties });
+        }
 
-            Ok(whiteboard)
-        } else {
-            Err(AppError::NotFound("Element not found".to_string()))
+        if changes.is_empty() {
+            return self.get_whiteboard(whiteboard_id).await;
         }
+
+        self.apply_local(whiteboard_id, user_id, changes).await
     }
 
     pub async fn delete_element(
@@ -216,73 +292,277 @@ impl WhiteboardService {
         element_id: Uuid,
         user_id: Uuid,
     ) -> Result<WhiteboardState, AppError> {
-        let mut whiteboard = self.get_whiteboard(whiteboard_id).await?;
-        
-        // Remove the element
-        let initial_len = whiteboard.elements.len();
-        whiteboard.elements.retain(|e| e.element_id != element_id);
-        
-        if whiteboard.elements.len() == initial_len {
-            return Err(AppError::NotFound("Element not found".to_string()));
+        self.get_element(whiteboard_id, element_id).await?;
+
+        self.apply_local(whiteboard_id, user_id, vec![ElementChange::Delete { element_id }]).await
+    }
+
+    pub async fn clear_whiteboard(
+        &self,
+        whiteboard_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<WhiteboardState, AppError> {
+        let whiteboard = self.get_whiteboard(whiteboard_id).await?;
+
+        // Deletes only what this replica has seen; elements created
+        // concurrently elsewhere survive the clear
+        let changes = whiteboard.elements.iter()
+            .map(|element| ElementChange::Delete { element_id: element.element_id })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return Ok(whiteboard);
         }
 
-        whiteboard.version += 1;
-        whiteboard.last_modified = Utc::now();
-        whiteboard.last_modified_by = user_id;
+        let state = self.apply_local(whiteboard_id, user_id, changes).await?;
 
-        // Update cache
-        self.active_whiteboards.insert(whiteboard_id, whiteboard.clone());
+        // Force immediate save for clear operations
+        self.save_whiteboard_to_database(whiteboard_id).await?;
 
-        // Create operation for real-time sync
-        let operation = WhiteboardOperation {
-            operation_type: OperationType::Delete,
-            element_id: Some(element_id),
-            element: None,
-            user_id,
-            timestamp: Utc::now(),
+        Ok(state)
+    }
+
+    // Delta sync
+    /// Merges a delta from a client. Deltas that were already applied are
+    /// ignored, so clients can safely resend after a dropped connection.
+    pub async fn apply_delta(&self, whiteboard_id: Uuid, delta: WhiteboardDelta) -> Result<WhiteboardState, AppError> {
+        self.ensure_loaded(whiteboard_id).await?;
+
+        let (state, inverse) = {
+            let mut whiteboard = self.active_whiteboards.get_mut(&whiteboard_id)
+                .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
+
+            if !whiteboard.doc.accepts(&delta.stamp) {
+                return Err(AppError::BadRequest("Delta is stamped too far ahead of the whiteboard".to_string()));
+            }
+
+            match whiteboard.doc.apply(&delta) {
+                Some(inverse) => {
+                    whiteboard.touch(delta.user_id);
+                    (whiteboard.state(), inverse)
+                }
+                None => return Ok(whiteboard.state()),
+            }
         };
 
-        // Broadcast to other users
-        self.broadcast_operation(whiteboard_id, &operation).await?;
+        self.undo_histories.entry((whiteboard_id, delta.user_id))
+            .or_default()
+            .record(delta.stamp, inverse);
 
-        // Schedule auto-save
-        self.schedule_auto_save(whiteboard_id).await?;
+        self.publish_delta(&state, &delta).await?;
+        Ok(state)
+    }
 
-        Ok(whiteboard)
+    /// Brings a reconnecting client up to date: merges the deltas it made
+    /// while offline and returns every delta it has not seen yet.
+    pub async fn sync(
+        &self,
+        whiteboard_id: Uuid,
+        user_id: Uuid,
+        request: WhiteboardSyncRequest,
+    ) -> Result<WhiteboardSyncResponse, AppError> {
+        let replica_id = crdt::client_replica_id(user_id, request.client_id);
+        let mut seen = request.state_vector;
+
+        for delta in request.deltas {
+            if delta.user_id != user_id {
+                return Err(AppError::Forbidden("Deltas can only be submitted by their author".to_string()));
+            }
+            if delta.stamp.replica_id != replica_id {
+                return Err(AppError::Forbidden("Deltas must be stamped with the client's own replica".to_string()));
+            }
+
+            let counter = seen.entry(delta.stamp.replica_id).or_insert(0);
+            *counter = (*counter).max(delta.stamp.counter);
+
+            self.apply_delta(whiteboard_id, delta).await?;
+        }
+
+        let state = self.get_whiteboard(whiteboard_id).await?;
+
+        // Deltas older than the last compaction are gone, so send the whole board
+        if self.missed_compacted_deltas(whiteboard_id, &seen).await? {
+            return Ok(WhiteboardSyncResponse {
+                whiteboard_id,
+                replica_id,
+                deltas: Vec::new(),
+                state_vector: state.state_vector.clone(),
+                snapshot: Some(state),
+            });
+        }
+
+        let deltas = self.deltas_since(whiteboard_id, &seen).await?;
+
+        Ok(WhiteboardSyncResponse {
+            whiteboard_id,
+            replica_id,
+            deltas,
+            state_vector: state.state_vector,
+            snapshot: None,
+        })
     }
 
-    pub async fn clear_whiteboard(
+    pub async fn undo(&self, whiteboard_id: Uuid, user_id: Uuid) -> Result<WhiteboardState, AppError> {
+        self.step_history(whiteboard_id, user_id, true).await
+    }
+
+    pub async fn redo(&self, whiteboard_id: Uuid, user_id: Uuid) -> Result<WhiteboardState, AppError> {
+        self.step_history(whiteboard_id, user_id, false).await
+    }
+
+    async fn step_history(&self, whiteboard_id: Uuid, user_id: Uuid, undo: bool) -> Result<WhiteboardState, AppError> {
+        self.ensure_loaded(whiteboard_id).await?;
+
+        let (state, delta) = {
+            let mut whiteboard = self.active_whiteboards.get_mut(&whiteboard_id)
+                .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
+            let mut history = self.undo_histories.entry((whiteboard_id, user_id)).or_default();
+
+            let delta = if undo {
+                history.undo(&mut whiteboard.doc, user_id, self.replica_id)
+                    .ok_or_else(|| AppError::BadRequest("Nothing to undo".to_string()))?
+            } else {
+                history.redo(&mut whiteboard.doc, user_id, self.replica_id)
+                    .ok_or_else(|| AppError::BadRequest("Nothing to redo".to_string()))?
+            };
+
+            whiteboard.touch(user_id);
+            (whiteboard.state(), delta)
+        };
+
+        self.publish_delta(&state, &delta).await?;
+        Ok(state)
+    }
+
+    // Version history
+    pub async fn create_snapshot(
+        &self,
+        whiteboard_id: Uuid,
+        user_id: Uuid,
+        name: String,
+    ) -> Result<WhiteboardSnapshot, AppError> {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.len() > 255 {
+            return Err(AppError::BadRequest("Snapshot name must be between 1 and 255 characters".to_string()));
+        }
+
+        self.ensure_loaded(whiteboard_id).await?;
+        let whiteboard = self.active_whiteboards.get(&whiteboard_id)
+            .map(|whiteboard| whiteboard.clone())
+            .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
+
+        let mut conn = self.db_pool.acquire().await
+            .map_err(|e| AppError::Database(format!("Failed to acquire connection: {}", e)))?;
+        let snapshot = Self::insert_snapshot(&mut conn, &whiteboard, Some(&name), SnapshotType::Named, user_id).await?;
+
+        tracing::info!("Created snapshot '{}' of whiteboard {} at version {}", name, whiteboard_id, whiteboard.version);
+        Ok(snapshot)
+    }
+
+    /// Logged versions newest first, paged with `before_version`, plus every snapshot.
+    pub async fn history(
+        &self,
+        whiteboard_id: Uuid,
+        before_version: Option<u64>,
+        limit: i64,
+    ) -> Result<WhiteboardHistoryResponse, AppError> {
+        let current = self.get_whiteboard(whiteboard_id).await?;
+
+        let query = r#"
+            SELECT version_after, user_id, operation_timestamp,
+                   COALESCE(jsonb_array_length(element_data -> 'changes'), 0) AS change_count
+            FROM whiteboard_operations
+            WHERE whiteboard_id = $1
+              AND replica_id IS NOT NULL
+              AND version_after < $2
+            ORDER BY version_after DESC, lamport DESC
+            LIMIT $3
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(whiteboard_id)
+            .bind(before_version.map(|version| version as i64).unwrap_or(i64::MAX))
+            .bind(limit)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch whiteboard history: {}", e)))?;
+
+        let versions = rows.into_iter()
+            .map(|row| WhiteboardVersion {
+                version: row.get::<i64, _>("version_after") as u64,
+                user_id: row.get("user_id"),
+                change_count: row.get::<i32, _>("change_count") as usize,
+                timestamp: row.get::<Option<DateTime<Utc>>, _>("operation_timestamp").unwrap_or_else(Utc::now),
+            })
+            .collect();
+
+        let snapshots = self.list_snapshots(whiteboard_id).await?;
+        let compacted_through = snapshots.iter()
+            .filter(|snapshot| snapshot.snapshot_type == SnapshotType::Compaction)
+            .map(|snapshot| snapshot.version)
+            .max();
+
+        Ok(WhiteboardHistoryResponse {
+            whiteboard_id,
+            current_version: current.version,
+            versions,
+            snapshots,
+            compacted_through,
+        })
+    }
+
+    /// Brings the board back to an earlier version. The restore is a new
+    /// edit on top of the current state, so it syncs to everyone and can be
+    /// undone like any other change.
+    pub async fn restore_version(
         &self,
         whiteboard_id: Uuid,
         user_id: Uuid,
+        request: RestoreWhiteboardRequest,
     ) -> Result<WhiteboardState, AppError> {
-        let mut whiteboard = self.get_whiteboard(whiteboard_id).await?;
-        
-        // Clear all elements
-        whiteboard.elements.clear();
-        whiteboard.version += 1;
-        whiteboard.last_modified = Utc::now();
-        whiteboard.last_modified_by = user_id;
-
-        // Update cache
-        self.active_whiteboards.insert(whiteboard_id, whiteboard.clone());
-
-        // Create operation for real-time sync
-        let operation = WhiteboardOperation {
-            operation_type: OperationType::Clear,
-            element_id: None,
-            element: None,
-            user_id,
-            timestamp: Utc::now(),
+        let mut conn = self.db_pool.acquire().await
+            .map_err(|e| AppError::Database(format!("Failed to acquire connection: {}", e)))?;
+
+        let target = match (request.snapshot_id, request.version) {
+            (Some(snapshot_id), _) => Self::load_snapshot(&mut conn, whiteboard_id, snapshot_id).await?,
+            (None, Some(version)) => Self::load_version(&mut conn, whiteboard_id, Some(version)).await?,
+            (None, None) => return Err(AppError::BadRequest("Either version or snapshot_id is required".to_string())),
         };
+        drop(conn);
+
+        self.ensure_loaded(whiteboard_id).await?;
+        let changes = self.active_whiteboards.get(&whiteboard_id)
+            .map(|whiteboard| whiteboard.doc.changes_to(&target.doc.elements()))
+            .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
+        if changes.is_empty() {
+            return self.get_whiteboard(whiteboard_id).await;
+        }
 
-        // Broadcast to other users
-        self.broadcast_operation(whiteboard_id, &operation).await?;
+        let state = self.apply_local(whiteboard_id, user_id, changes).await?;
 
-        // Force immediate save for clear operations
-        self.save_whiteboard_to_database(&whiteboard).await?;
+        tracing::info!("User {} restored whiteboard {} to version {}", user_id, whiteboard_id, target.version);
+        Ok(state)
+    }
 
-        Ok(whiteboard)
+    /// Everything needed to watch the board being built from `from_version`,
+    /// or from the oldest version still on record.
+    pub async fn replay(&self, whiteboard_id: Uuid, from_version: Option<u64>) -> Result<WhiteboardReplay, AppError> {
+        let mut conn = self.db_pool.acquire().await
+            .map_err(|e| AppError::Database(format!("Failed to acquire connection: {}", e)))?;
+
+        let from_version = match from_version {
+            Some(version) => version,
+            None => Self::compacted_through(&mut conn, whiteboard_id).await?.unwrap_or(0),
+        };
+        let start = Self::load_version(&mut conn, whiteboard_id, Some(from_version)).await?;
+        let steps = Self::logged_deltas(&mut conn, whiteboard_id, start.version, None).await?
+            .into_iter()
+            .map(|(version, delta)| WhiteboardReplayStep { version, delta })
+            .collect();
+
+        Ok(WhiteboardReplay {
+            start: start.state(),
+            steps,
+        })
     }
 
     // Real-time collaboration
@@ -317,65 +597,377 @@ impl WhiteboardService {
     }
 
     // Persistence operations
-    pub async fn save_whiteboard_to_database(&self, whiteboard: &WhiteboardState) -> Result<(), AppError> {
-        let elements_json = serde_json::to_value(&whiteboard.elements)
-            .map_err(|e| AppError::Internal(format!("Failed to serialize elements: {}", e)))?;
+    pub async fn save_whiteboard_to_database(&self, whiteboard_id: Uuid) -> Result<(), AppError> {
+        let whiteboard = self.active_whiteboards.get(&whiteboard_id)
+            .map(|whiteboard| whiteboard.clone())
+            .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
 
-        let query = r#"
-            UPDATE whiteboards 
-            SET elements = $1, version = $2, last_modified = $3, last_modified_by = $4
-            WHERE whiteboard_id = $5
-        "#;
-
-        sqlx::query(query)
-            .bind(elements_json)
-            .bind(whiteboard.version as i64)
-            .bind(whiteboard.last_modified)
-            .bind(whiteboard.last_modified_by)
-            .bind(whiteboard.whiteboard_id)
-            .execute(&self.db_pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to save whiteboard: {}", e)))?;
+        Self::save_whiteboard_static(&self.db_pool, &whiteboard).await?;
 
         // Update Redis cache
-        self.cache_whiteboard_state(whiteboard).await?;
+        self.cache_whiteboard(&whiteboard).await?;
 
-        tracing::debug!("Saved whiteboard {} to database", whiteboard.whiteboard_id);
+        tracing::debug!("Saved whiteboard {} to database", whiteboard_id);
         Ok(())
     }
 
-    pub async fn export_whiteboard(&self, whiteboard_id: Uuid, format: &str) -> Result<Vec<u8>, AppError> {
+    pub async fn export_whiteboard(
+        &self,
+        whiteboard_id: Uuid,
+        request: &WhiteboardExportRequest,
+    ) -> Result<WhiteboardExport, AppError> {
         let whiteboard = self.get_whiteboard(whiteboard_id).await?;
+        let file_stem = format!("whiteboard-{}", Utc::now().format("%Y%m%d-%H%M"));
+
+        if request.format == ExportFormat::Json {
+            let json = serde_json::to_string_pretty(&whiteboard)
+                .map_err(|e| AppError::Internal(format!("Failed to serialize whiteboard: {}", e)))?;
+            return Ok(WhiteboardExport {
+                contents: json.into_bytes(),
+                content_type: "application/json",
+                file_name: format!("{}.json", file_stem),
+            });
+        }
 
-        match format.to_lowercase().as_str() {
-            "json" => {
-                let json = serde_json::to_string_pretty(&whiteboard)
-                    .map_err(|e| AppError::Internal(format!("Failed to serialize whiteboard: {}", e)))?;
-                Ok(json.into_bytes())
-            }
-            "svg" => {
-                // Convert whiteboard elements to SVG
-                let svg = self.generate_svg(&whiteboard).await?;
-                Ok(svg.into_bytes())
-            }
-            "png" => {
-                // In a real implementation, you would render the whiteboard to PNG
-                Err(AppError::BadRequest("PNG export not implemented".to_string()))
+        let mut elements = whiteboard.elements;
+        let areas = match request.frame_id {
+            Some(frame_id) => vec![whiteboard_export::frame_bounds(&elements, frame_id)
+                .ok_or_else(|| AppError::NotFound("Frame not found".to_string()))?],
+            None if request.format == ExportFormat::Pdf => whiteboard_export::pages(&elements),
+            None => vec![whiteboard_export::board_bounds(&elements)],
+        };
+
+        // Exports are self-contained, so remote images are embedded
+        whiteboard_export::inline_images(&self.image_hosts, &mut elements).await;
+
+        let (contents, content_type, extension) = match request.format {
+            ExportFormat::Svg => (
+                whiteboard_export::render_svg(&elements, &areas[0]).into_bytes(),
+                "image/svg+xml",
+                "svg",
+            ),
+            ExportFormat::Png => {
+                let renderer = self.renderer.clone();
+                let width = request.width.unwrap_or(1920);
+                let png = tokio::task::spawn_blocking(move || renderer.png(&elements, &areas[0], width))
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Whiteboard render task failed: {}", e)))??;
+                (png, "image/png", "png")
             }
-            _ => {
-                Err(AppError::BadRequest("Unsupported export format".to_string()))
+            ExportFormat::Pdf => {
+                let renderer = self.renderer.clone();
+                let pdf = tokio::task::spawn_blocking(move || renderer.pdf(&elements, &areas))
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Whiteboard render task failed: {}", e)))??;
+                (pdf, "application/pdf", "pdf")
             }
-        }
+            ExportFormat::Json => unreachable!("JSON exports return early"),
+        };
+
+        Ok(WhiteboardExport {
+            contents,
+            content_type,
+            file_name: format!("{}.{}", file_stem, extension),
+        })
+    }
+
+    /// Exports the session's whiteboard into the session materials and
+    /// attaches it to one of the user's notes, or to a new shared note.
+    pub async fn attach_export_to_notes(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        request: AttachWhiteboardExportRequest,
+    ) -> Result<WhiteboardExportResponse, AppError> {
+        let whiteboard = self.get_session_whiteboard(session_id, user_id).await?;
+        let export = self.export_whiteboard(whiteboard.whiteboard_id, &WhiteboardExportRequest {
+            format: request.format,
+            width: request.width,
+            frame_id: request.frame_id,
+        }).await?;
+
+        let material_id = Uuid::new_v4();
+        let dir = std::path::Path::new(&self.storage_path).join(session_id.to_string());
+        tokio::fs::create_dir_all(&dir).await
+            .map_err(|e| AppError::Internal(format!("Failed to create export directory: {}", e)))?;
+        let extension = export.file_name.rsplit('.').next().unwrap_or("bin");
+        let file_path = dir.join(format!("{}.{}", material_id, extension));
+        tokio::fs::write(&file_path, &export.contents).await
+            .map_err(|e| AppError::Internal(format!("Failed to write whiteboard export: {}", e)))?;
+
+        let material_type = if request.format == ExportFormat::Png { "image" } else { "document" };
+        let file_size = export.contents.len() as i64;
+
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO session_materials (
+                material_id, session_id, uploaded_by, name, description,
+                file_path, file_size, mime_type, material_type, is_shared
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, true)
+            "#
+        )
+        .bind(material_id)
+        .bind(session_id)
+        .bind(user_id)
+        .bind(&export.file_name)
+        .bind("Whiteboard export")
+        .bind(file_path.display().to_string())
+        .bind(file_size)
+        .bind(export.content_type)
+        .bind(material_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to store whiteboard export: {}", e)))?;
+
+        let note_id: Uuid = match request.note_id {
+            Some(note_id) => sqlx::query_scalar(
+                "UPDATE session_notes SET updated_at = NOW() WHERE note_id = $1 AND session_id = $2 AND author_id = $3 RETURNING note_id"
+            )
+            .bind(note_id)
+            .bind(session_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update session note: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Note not found".to_string()))?,
+            None => sqlx::query_scalar(
+                r#"
+                INSERT INTO session_notes (session_id, author_id, title, content, note_type, is_shared, tags)
+                VALUES ($1, $2, $3, $4, 'general', true, ARRAY['whiteboard'])
+                RETURNING note_id
+                "#
+            )
+            .bind(session_id)
+            .bind(user_id)
+            .bind(request.title.unwrap_or_else(|| "Whiteboard".to_string()))
+            .bind(format!("Whiteboard export: {}", export.file_name))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create session note: {}", e)))?,
+        };
+
+        sqlx::query("INSERT INTO session_note_attachments (note_id, material_id) VALUES ($1, $2)")
+            .bind(note_id)
+            .bind(material_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to attach whiteboard export: {}", e)))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        tracing::info!("Attached whiteboard export {} to note {} in session {}", material_id, note_id, session_id);
+
+        Ok(WhiteboardExportResponse {
+            material_id,
+            note_id,
+            file_name: export.file_name,
+            mime_type: export.content_type.to_string(),
+            file_size,
+        })
     }
 
     // Helper methods
-    async fn broadcast_operation(&self, whiteboard_id: Uuid, operation: &WhiteboardOperation) -> Result<(), AppError> {
-        let message = CollaborationMessage::WhiteboardUpdate {
-            whiteboard_id,
-            operation: operation.clone(),
+    async fn get_element(&self, whiteboard_id: Uuid, element_id: Uuid) -> Result<WhiteboardElement, AppError> {
+        self.ensure_loaded(whiteboard_id).await?;
+
+        self.active_whiteboards.get(&whiteboard_id)
+            .and_then(|whiteboard| whiteboard.doc.element(element_id))
+            .ok_or_else(|| AppError::NotFound("Element not found".to_string()))
+    }
+
+    // Stamps and applies a change made through this instance's API
+    async fn apply_local(
+        &self,
+        whiteboard_id: Uuid,
+        user_id: Uuid,
+        changes: Vec<ElementChange>,
+    ) -> Result<WhiteboardState, AppError> {
+        self.ensure_loaded(whiteboard_id).await?;
+
+        // Stamp and apply under the same lock so this replica's deltas stay in order
+        let (state, delta, inverse) = {
+            let mut whiteboard = self.active_whiteboards.get_mut(&whiteboard_id)
+                .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
+
+            let delta = WhiteboardDelta {
+                stamp: whiteboard.doc.next_stamp(self.replica_id),
+                user_id,
+                changes,
+                timestamp: Utc::now(),
+            };
+            let inverse = whiteboard.doc.apply(&delta).unwrap_or_default();
+            whiteboard.touch(user_id);
+
+            (whiteboard.state(), delta, inverse)
         };
 
-        self.broadcast_collaboration_message(whiteboard_id, &message, Some(operation.user_id)).await
+        self.undo_histories.entry((whiteboard_id, user_id))
+            .or_default()
+            .record(delta.stamp, inverse);
+
+        self.publish_delta(&state, &delta).await?;
+        Ok(state)
+    }
+
+    // Logs, broadcasts and caches a delta that was just applied
+    async fn publish_delta(&self, state: &WhiteboardState, delta: &WhiteboardDelta) -> Result<(), AppError> {
+        let delta_json = serde_json::to_value(delta)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize delta: {}", e)))?;
+        let element_id = match delta.changes.as_slice() {
+            [change] => Some(change.element_id()),
+            _ => None,
+        };
+
+        let query = r#"
+            INSERT INTO whiteboard_operations (
+                whiteboard_id, user_id, operation_type, element_id, element_data,
+                operation_timestamp, version_after, replica_id, lamport
+            ) VALUES ($1, $2, 'delta', $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (whiteboard_id, replica_id, lamport) WHERE replica_id IS NOT NULL DO NOTHING
+        "#;
+
+        sqlx::query(query)
+            .bind(state.whiteboard_id)
+            .bind(delta.user_id)
+            .bind(element_id)
+            .bind(delta_json)
+            .bind(delta.timestamp)
+            .bind(state.version as i64)
+            .bind(delta.stamp.replica_id)
+            .bind(delta.stamp.counter as i64)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to log whiteboard delta: {}", e)))?;
+
+        let message = CollaborationMessage::WhiteboardDelta {
+            whiteboard_id: state.whiteboard_id,
+            delta: delta.clone(),
+        };
+        self.broadcast_collaboration_message(state.whiteboard_id, &message, Some(delta.user_id)).await?;
+
+        if let Some(whiteboard) = self.active_whiteboards.get(&state.whiteboard_id).map(|w| w.clone()) {
+            self.cache_whiteboard(&whiteboard).await?;
+        }
+
+        // Schedule auto-save
+        self.schedule_auto_save(state.whiteboard_id).await
+    }
+
+    // Logged deltas the given state vector does not cover, in the order they were applied
+    async fn deltas_since(
+        &self,
+        whiteboard_id: Uuid,
+        state_vector: &HashMap<Uuid, u64>,
+    ) -> Result<Vec<WhiteboardDelta>, AppError> {
+        let state_vector = serde_json::to_value(state_vector)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize state vector: {}", e)))?;
+
+        let query = r#"
+            SELECT element_data
+            FROM whiteboard_operations
+            WHERE whiteboard_id = $1
+              AND replica_id IS NOT NULL
+              AND lamport > COALESCE(($2::jsonb ->> replica_id::text)::BIGINT, 0)
+            ORDER BY version_after
+        "#;
+
+        let rows: Vec<serde_json::Value> = sqlx::query_scalar(query)
+            .bind(whiteboard_id)
+            .bind(state_vector)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch whiteboard deltas: {}", e)))?;
+
+        rows.into_iter()
+            .map(|delta| serde_json::from_value(delta)
+                .map_err(|e| AppError::Internal(format!("Failed to deserialize delta: {}", e))))
+            .collect()
+    }
+
+    // Whether a client with this state vector needs deltas that compaction removed
+    async fn missed_compacted_deltas(
+        &self,
+        whiteboard_id: Uuid,
+        state_vector: &HashMap<Uuid, u64>,
+    ) -> Result<bool, AppError> {
+        let query = r#"
+            SELECT state_vector
+            FROM whiteboard_snapshots
+            WHERE whiteboard_id = $1 AND snapshot_type = 'compaction'
+            ORDER BY version DESC
+            LIMIT 1
+        "#;
+
+        let compacted: Option<serde_json::Value> = sqlx::query_scalar(query)
+            .bind(whiteboard_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch compaction snapshot: {}", e)))?;
+
+        let Some(compacted) = compacted else { return Ok(false) };
+        let compacted: HashMap<Uuid, u64> = serde_json::from_value(compacted)
+            .map_err(|e| AppError::Internal(format!("Failed to deserialize state vector: {}", e)))?;
+
+        Ok(compacted.iter().any(|(replica_id, counter)| {
+            state_vector.get(replica_id).copied().unwrap_or(0) < *counter
+        }))
+    }
+
+    async fn list_snapshots(&self, whiteboard_id: Uuid) -> Result<Vec<WhiteboardSnapshot>, AppError> {
+        let query = r#"
+            SELECT snapshot_id, whiteboard_id, name, snapshot_type, version,
+                   element_count, created_by, created_at
+            FROM whiteboard_snapshots
+            WHERE whiteboard_id = $1
+            ORDER BY version DESC, created_at DESC
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(whiteboard_id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch whiteboard snapshots: {}", e)))?;
+
+        Ok(rows.into_iter()
+            .map(|row| WhiteboardSnapshot {
+                snapshot_id: row.get("snapshot_id"),
+                whiteboard_id: row.get("whiteboard_id"),
+                name: row.get("name"),
+                snapshot_type: match row.get::<String, _>("snapshot_type").as_str() {
+                    "compaction" => SnapshotType::Compaction,
+                    _ => SnapshotType::Named,
+                },
+                version: row.get::<i64, _>("version") as u64,
+                element_count: row.get::<i32, _>("element_count") as usize,
+                created_by: row.get("created_by"),
+                created_at: row.get::<Option<DateTime<Utc>>, _>("created_at").unwrap_or_else(Utc::now),
+            })
+            .collect())
+    }
+
+    fn doc_from_elements(&self, elements: Vec<WhiteboardElement>) -> WhiteboardDoc {
+        let mut doc = WhiteboardDoc::default();
+
+        for element in elements {
+            let delta = WhiteboardDelta {
+                stamp: doc.next_stamp(self.replica_id),
+                user_id: element.created_by,
+                changes: vec![ElementChange::Create {
+                    element_id: element.element_id,
+                    element_type: element.element_type,
+                    position: element.position,
+                    properties: element.properties,
+                }],
+                timestamp: element.created_at,
+            };
+            doc.apply(&delta);
+        }
+
+        doc
     }
 
     async fn broadcast_collaboration_message(
@@ -395,28 +987,20 @@ impl WhiteboardService {
         Ok(())
     }
 
-    async fn cache_whiteboard_state(&self, whiteboard: &WhiteboardState) -> Result<(), AppError> {
+    async fn cache_whiteboard(&self, whiteboard: &ActiveWhiteboard) -> Result<(), AppError> {
         let cache_key = format!("whiteboard:{}", whiteboard.whiteboard_id);
-        let whiteboard_json = serde_json::to_string(whiteboard)
-            .map_err(|e| AppError::Internal(format!("Failed to serialize whiteboard: {}", e)))?;
 
-        let _: () = self.redis_service
-            .set_with_expiry(&cache_key, &whiteboard_json, 3600)
+        self.redis_service
+            .cache_set(&cache_key, whiteboard, 3600)
             .await
-            .map_err(|e| AppError::Internal(format!("Failed to cache whiteboard: {}", e)))?;
-
-        Ok(())
+            .map_err(|e| AppError::Internal(format!("Failed to cache whiteboard: {}", e)))
     }
 
-    async fn get_cached_whiteboard_state(&self, whiteboard_id: Uuid) -> Result<WhiteboardState, AppError> {
+    async fn get_cached_whiteboard(&self, whiteboard_id: Uuid) -> Result<Option<ActiveWhiteboard>, AppError> {
         let cache_key = format!("whiteboard:{}", whiteboard_id);
-        let whiteboard_json: String = self.redis_service.get(&cache_key).await
-            .map_err(|e| AppError::Internal(format!("Failed to get cached whiteboard: {}", e)))?;
 
-        let whiteboard: WhiteboardState = serde_json::from_str(&whiteboard_json)
-            .map_err(|e| AppError::Internal(format!("Failed to deserialize whiteboard: {}", e)))?;
-
-        Ok(whiteboard)
+        self.redis_service.cache_get(&cache_key).await
+            .map_err(|e| AppError::Internal(format!("Failed to get cached whiteboard: {}", e)))
     }
 
     async fn schedule_auto_save(&self, whiteboard_id: Uuid) -> Result<(), AppError> {
@@ -443,12 +1027,15 @@ impl WhiteboardService {
                 interval.tick().await;
                 
                 // Find whiteboards that need saving
-                for whiteboard_entry in active_whiteboards.iter() {
-                    let whiteboard_id = *whiteboard_entry.key();
+                let whiteboard_ids: Vec<Uuid> = active_whiteboards.iter().map(|entry| *entry.key()).collect();
+                for whiteboard_id in whiteboard_ids {
                     let save_key = format!("whiteboard_save:{}", whiteboard_id);
                     
                     if let Ok(_) = redis_service.get::<String>(&save_key).await {
-                        let whiteboard = whiteboard_entry.value().clone();
+                        let whiteboard = match active_whiteboards.get(&whiteboard_id) {
+                            Some(whiteboard) => whiteboard.clone(),
+                            None => continue,
+                        };
                         
                         // Save to database
                         if let Err(e) = Self::save_whiteboard_static(&db_pool, &whiteboard).await {
@@ -467,6 +1054,7 @@ impl WhiteboardService {
 
     async fn start_cleanup_task(&self) -> Result<(), AppError> {
         let active_whiteboards = self.active_whiteboards.clone();
+        let undo_histories = self.undo_histories.clone();
         let user_cursors = self.user_cursors.clone();
 
         tokio::spawn(async move {
@@ -481,6 +1069,9 @@ impl WhiteboardService {
                 active_whiteboards.retain(|_, whiteboard| {
                     whiteboard.last_modified > cutoff_time
                 });
+
+                // Undo history only lives as long as the whiteboard is active
+                undo_histories.retain(|(whiteboard_id, _), _| active_whiteboards.contains_key(whiteboard_id));
                 
                 // Clean up old cursor positions
                 user_cursors.retain(|_, _| true); // In real implementation, check activity
@@ -492,19 +1083,347 @@ impl WhiteboardService {
         Ok(())
     }
 
+    async fn start_delta_listener(&self) -> Result<(), AppError> {
+        let redis_service = self.redis_service.clone();
+        let active_whiteboards = self.active_whiteboards.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = Self::listen_to_whiteboard_channels(redis_service, active_whiteboards).await {
+                tracing::error!("Whiteboard delta listener error: {}", e);
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn listen_to_whiteboard_channels(
+        redis_service: RedisService,
+        active_whiteboards: std::sync::Arc<DashMap<Uuid, ActiveWhiteboard>>,
+    ) -> Result<(), AppError> {
+        let mut conn = redis_service.get_connection().await
+            .map_err(|e| AppError::Internal(format!("Failed to get Redis connection: {}", e)))?;
+
+        let mut pubsub = conn.as_mut().into_pubsub();
+        pubsub.psubscribe("whiteboard:*").await
+            .map_err(|e| AppError::Internal(format!("Failed to subscribe to whiteboard channels: {}", e)))?;
+
+        let mut stream = pubsub.on_message();
+
+        while let Some(msg) = stream.next().await {
+            let Ok(payload) = msg.get_payload::<String>() else { continue };
+
+            // Our own deltas come back too; the document ignores what it already has
+            if let Ok(CollaborationMessage::WhiteboardDelta { whiteboard_id, delta }) = serde_json::from_str(&payload) {
+                if let Some(mut whiteboard) = active_whiteboards.get_mut(&whiteboard_id) {
+                    if whiteboard.doc.apply(&delta).is_some() {
+                        whiteboard.touch(delta.user_id);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn start_compaction_task(&self) -> Result<(), AppError> {
+        let db_pool = self.db_pool.clone();
+        let retention = self.history_retention;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+
+            loop {
+                interval.tick().await;
+
+                let cutoff = Utc::now() - retention;
+                let query = r#"
+                    SELECT DISTINCT whiteboard_id
+                    FROM whiteboard_operations
+                    WHERE replica_id IS NOT NULL AND operation_timestamp < $1
+                "#;
+
+                let whiteboard_ids: Vec<Uuid> = match sqlx::query_scalar(query).bind(cutoff).fetch_all(&db_pool).await {
+                    Ok(whiteboard_ids) => whiteboard_ids,
+                    Err(e) => {
+                        tracing::error!("Failed to find whiteboards to compact: {}", e);
+                        continue;
+                    }
+                };
+
+                for whiteboard_id in whiteboard_ids {
+                    if let Err(e) = Self::compact_history(&db_pool, whiteboard_id, cutoff).await {
+                        tracing::error!("Failed to compact whiteboard {}: {}", whiteboard_id, e);
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    // Folds the operations logged before `cutoff` into a snapshot and drops them
+    async fn compact_history(db_pool: &PgPool, whiteboard_id: Uuid, cutoff: DateTime<Utc>) -> Result<(), AppError> {
+        let mut tx = db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // Serializes compaction of the same whiteboard across instances
+        sqlx::query("SELECT 1 FROM whiteboards WHERE whiteboard_id = $1 FOR UPDATE")
+            .bind(whiteboard_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to lock whiteboard: {}", e)))?;
+
+        let horizon: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(version_after) FROM whiteboard_operations WHERE whiteboard_id = $1 AND replica_id IS NOT NULL AND operation_timestamp < $2"
+        )
+        .bind(whiteboard_id)
+        .bind(cutoff)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to find compaction horizon: {}", e)))?;
+
+        // Another instance got here first
+        let Some(horizon) = horizon else { return Ok(()) };
+
+        let whiteboard = Self::load_version(&mut tx, whiteboard_id, Some(horizon as u64)).await?;
+        Self::insert_snapshot(&mut tx, &whiteboard, None, SnapshotType::Compaction, whiteboard.last_modified_by).await?;
+
+        let removed = sqlx::query(
+            "DELETE FROM whiteboard_operations WHERE whiteboard_id = $1 AND replica_id IS NOT NULL AND version_after <= $2"
+        )
+        .bind(whiteboard_id)
+        .bind(horizon)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete compacted operations: {}", e)))?
+        .rows_affected();
+
+        // Only the newest compaction snapshot is needed to rebuild later versions
+        sqlx::query("DELETE FROM whiteboard_snapshots WHERE whiteboard_id = $1 AND snapshot_type = 'compaction' AND version < $2")
+            .bind(whiteboard_id)
+            .bind(horizon)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete old compaction snapshots: {}", e)))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        tracing::info!("Compacted {} operations of whiteboard {} through version {}", removed, whiteboard_id, horizon);
+        Ok(())
+    }
+
+    // Rebuilds the whiteboard at `version` (the latest logged one if None) from
+    // the nearest snapshot at or before it and the operations logged since
+    async fn load_version(
+        conn: &mut PgConnection,
+        whiteboard_id: Uuid,
+        version: Option<u64>,
+    ) -> Result<ActiveWhiteboard, AppError> {
+        let row = sqlx::query("SELECT session_id, created_at FROM whiteboards WHERE whiteboard_id = $1")
+            .bind(whiteboard_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch whiteboard: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Whiteboard not found".to_string()))?;
+        let session_id: Uuid = row.get("session_id");
+        let created_at = row.get::<Option<DateTime<Utc>>, _>("created_at").unwrap_or_else(Utc::now);
+
+        let query = r#"
+            SELECT version, crdt_state, created_by, created_at
+            FROM whiteboard_snapshots
+            WHERE whiteboard_id = $1 AND version <= $2
+            ORDER BY version DESC, created_at DESC
+            LIMIT 1
+        "#;
+
+        let base = sqlx::query(query)
+            .bind(whiteboard_id)
+            .bind(version.map(|version| version as i64).unwrap_or(i64::MAX))
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch whiteboard snapshot: {}", e)))?;
+
+        let mut whiteboard = match base {
+            Some(row) => ActiveWhiteboard {
+                whiteboard_id,
+                session_id,
+                version: row.get::<i64, _>("version") as u64,
+                last_modified: row.get::<Option<DateTime<Utc>>, _>("created_at").unwrap_or_else(Utc::now),
+                last_modified_by: row.get("created_by"),
+                doc: serde_json::from_value(row.get("crdt_state"))
+                    .map_err(|e| AppError::Internal(format!("Failed to deserialize whiteboard document: {}", e)))?,
+            },
+            None => ActiveWhiteboard {
+                whiteboard_id,
+                session_id,
+                version: 0,
+                last_modified: created_at,
+                last_modified_by: Uuid::nil(),
+                doc: WhiteboardDoc::default(),
+            },
+        };
+
+        // The operations between the snapshot and an older version are gone
+        if let (Some(version), Some(through)) = (version, Self::compacted_through(conn, whiteboard_id).await?) {
+            if version < through && version != whiteboard.version {
+                return Err(AppError::BadRequest(format!("Version {} has been compacted", version)));
+            }
+        }
+
+        for (version_after, delta) in Self::logged_deltas(conn, whiteboard_id, whiteboard.version, version).await? {
+            whiteboard.doc.apply(&delta);
+            whiteboard.version = version_after;
+            whiteboard.last_modified = delta.timestamp;
+            whiteboard.last_modified_by = delta.user_id;
+        }
+
+        Ok(whiteboard)
+    }
+
+    async fn load_snapshot(
+        conn: &mut PgConnection,
+        whiteboard_id: Uuid,
+        snapshot_id: Uuid,
+    ) -> Result<ActiveWhiteboard, AppError> {
+        let query = r#"
+            SELECT s.version, s.crdt_state, s.created_by, s.created_at, w.session_id
+            FROM whiteboard_snapshots s
+            JOIN whiteboards w ON w.whiteboard_id = s.whiteboard_id
+            WHERE s.snapshot_id = $1 AND s.whiteboard_id = $2
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(snapshot_id)
+            .bind(whiteboard_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch whiteboard snapshot: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Snapshot not found".to_string()))?;
+
+        Ok(ActiveWhiteboard {
+            whiteboard_id,
+            session_id: row.get("session_id"),
+            version: row.get::<i64, _>("version") as u64,
+            last_modified: row.get::<Option<DateTime<Utc>>, _>("created_at").unwrap_or_else(Utc::now),
+            last_modified_by: row.get("created_by"),
+            doc: serde_json::from_value(row.get("crdt_state"))
+                .map_err(|e| AppError::Internal(format!("Failed to deserialize whiteboard document: {}", e)))?,
+        })
+    }
+
+    async fn insert_snapshot(
+        conn: &mut PgConnection,
+        whiteboard: &ActiveWhiteboard,
+        name: Option<&str>,
+        snapshot_type: SnapshotType,
+        created_by: Uuid,
+    ) -> Result<WhiteboardSnapshot, AppError> {
+        let crdt_state = serde_json::to_value(&whiteboard.doc)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize whiteboard document: {}", e)))?;
+        let state_vector = serde_json::to_value(whiteboard.doc.state_vector())
+            .map_err(|e| AppError::Internal(format!("Failed to serialize state vector: {}", e)))?;
+        let element_count = whiteboard.doc.elements().len();
+
+        let query = r#"
+            INSERT INTO whiteboard_snapshots (
+                whiteboard_id, name, snapshot_type, version, crdt_state,
+                state_vector, element_count, created_by
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING snapshot_id, created_at
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(whiteboard.whiteboard_id)
+            .bind(name)
+            .bind(match snapshot_type {
+                SnapshotType::Named => "named",
+                SnapshotType::Compaction => "compaction",
+            })
+            .bind(whiteboard.version as i64)
+            .bind(crdt_state)
+            .bind(state_vector)
+            .bind(element_count as i32)
+            .bind(created_by)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create whiteboard snapshot: {}", e)))?;
+
+        Ok(WhiteboardSnapshot {
+            snapshot_id: row.get("snapshot_id"),
+            whiteboard_id: whiteboard.whiteboard_id,
+            name: name.map(str::to_string),
+            snapshot_type,
+            version: whiteboard.version,
+            element_count,
+            created_by,
+            created_at: row.get::<Option<DateTime<Utc>>, _>("created_at").unwrap_or_else(Utc::now),
+        })
+    }
+
+    async fn compacted_through(conn: &mut PgConnection, whiteboard_id: Uuid) -> Result<Option<u64>, AppError> {
+        let version: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(version) FROM whiteboard_snapshots WHERE whiteboard_id = $1 AND snapshot_type = 'compaction'"
+        )
+        .bind(whiteboard_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch compaction horizon: {}", e)))?;
+
+        Ok(version.map(|version| version as u64))
+    }
+
+    // Deltas logged after `after` and up to `through`, with the version each produced
+    async fn logged_deltas(
+        conn: &mut PgConnection,
+        whiteboard_id: Uuid,
+        after: u64,
+        through: Option<u64>,
+    ) -> Result<Vec<(u64, WhiteboardDelta)>, AppError> {
+        let query = r#"
+            SELECT version_after, element_data
+            FROM whiteboard_operations
+            WHERE whiteboard_id = $1
+              AND replica_id IS NOT NULL
+              AND version_after > $2
+              AND version_after <= $3
+            ORDER BY version_after, lamport
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(whiteboard_id)
+            .bind(after as i64)
+            .bind(through.map(|version| version as i64).unwrap_or(i64::MAX))
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch whiteboard deltas: {}", e)))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let delta = serde_json::from_value(row.get("element_data"))
+                    .map_err(|e| AppError::Internal(format!("Failed to deserialize delta: {}", e)))?;
+                Ok((row.get::<i64, _>("version_after") as u64, delta))
+            })
+            .collect()
+    }
+
     // Static method for auto-save task
-    async fn save_whiteboard_static(db_pool: &PgPool, whiteboard: &WhiteboardState) -> Result<(), AppError> {
-        let elements_json = serde_json::to_value(&whiteboard.elements)
+    async fn save_whiteboard_static(db_pool: &PgPool, whiteboard: &ActiveWhiteboard) -> Result<(), AppError> {
+        let elements_json = serde_json::to_value(whiteboard.doc.elements())
             .map_err(|e| AppError::Internal(format!("Failed to serialize elements: {}", e)))?;
+        let crdt_state = serde_json::to_value(&whiteboard.doc)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize whiteboard document: {}", e)))?;
 
         let query = r#"
             UPDATE whiteboards 
-            SET elements = $1, version = $2, last_modified = $3, last_modified_by = $4
-            WHERE whiteboard_id = $5
+            SET elements = $1, crdt_state = $2, version = GREATEST(version, $3),
+                last_modified = $4, last_modified_by = $5
+            WHERE whiteboard_id = $6
         "#;
 
         sqlx::query(query)
             .bind(elements_json)
+            .bind(crdt_state)
             .bind(whiteboard.version as i64)
             .bind(whiteboard.last_modified)
             .bind(whiteboard.last_modified_by)
@@ -526,37 +1445,4 @@ impl WhiteboardService {
         let index = (user_id.as_u128() % colors.len() as u128) as usize;
         colors[index].to_string()
     }
-
-    async fn generate_svg(&self, whiteboard: &WhiteboardState) -> Result<String, AppError> {
-        let mut svg = String::from(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1920 1080">"#);
-        
-        for element in &whiteboard.elements {
-            match element.element_type {
-                ElementType::Text => {
-                    svg.push_str(&format!(
-                        r#"<text x="{}" y="{}" font-family="Arial" font-size="16">{}</text>"#,
-                        element.position.x,
-                        element.position.y,
-                        element.properties.get("text").and_then(|v| v.as_str()).unwrap_or("")
-                    ));
-                }
-                ElementType::Shape => {
-                    // Add shape rendering logic
-                    svg.push_str(&format!(
-                        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="blue" />"#,
-                        element.position.x,
-                        element.position.y,
-                        element.position.width.unwrap_or(100.0),
-                        element.position.height.unwrap_or(100.0)
-                    ));
-                }
-                _ => {
-                    // Handle other element types
-                }
-            }
-        }
-        
-        svg.push_str("</svg>");
-        Ok(svg)
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,691 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use futures::stream::{self, StreamExt};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+use resvg::usvg::{self, fontdb, PostProcessingSteps, TreeParsing, TreePostProc};
+use url::Url;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    models::{ElementType, WhiteboardElement},
+    outbound,
+};
+
+// Widest PNG we render, in pixels
+pub const MAX_EXPORT_WIDTH: u32 = 8192;
+// Largest remote image embedded in an export
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+// Remote images downloaded at once while exporting
+const IMAGE_FETCH_CONCURRENCY: usize = 4;
+// Blank space around the board when there are no frames
+const PADDING: f64 = 40.0;
+// Average glyph width relative to font size, for wrapping and sizing text
+const CHAR_WIDTH: f64 = 0.55;
+const LINE_HEIGHT: f64 = 1.25;
+
+/// A rectangle in board coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Bounds {
+    fn union(&self, other: &Bounds) -> Bounds {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Bounds {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+
+    fn padded(&self, padding: f64) -> Bounds {
+        Bounds {
+            x: self.x - padding,
+            y: self.y - padding,
+            width: self.width + padding * 2.0,
+            height: self.height + padding * 2.0,
+        }
+    }
+}
+
+/// Area covering every element, or a 1920x1080 canvas for an empty board.
+pub fn board_bounds(elements: &[WhiteboardElement]) -> Bounds {
+    elements.iter()
+        .map(element_bounds)
+        .reduce(|all, bounds| all.union(&bounds))
+        .map(|bounds| bounds.padded(PADDING))
+        .unwrap_or(Bounds { x: 0.0, y: 0.0, width: 1920.0, height: 1080.0 })
+}
+
+/// One page per frame in creation order, or the whole board if it has none.
+pub fn pages(elements: &[WhiteboardElement]) -> Vec<Bounds> {
+    let frames: Vec<Bounds> = elements.iter()
+        .filter(|element| matches!(element.element_type, ElementType::Frame))
+        .map(element_bounds)
+        .collect();
+
+    if frames.is_empty() {
+        vec![board_bounds(elements)]
+    } else {
+        frames
+    }
+}
+
+/// The area covered by a single frame, if `frame_id` names one.
+pub fn frame_bounds(elements: &[WhiteboardElement], frame_id: uuid::Uuid) -> Option<Bounds> {
+    elements.iter()
+        .find(|element| element.element_id == frame_id && matches!(element.element_type, ElementType::Frame))
+        .map(element_bounds)
+}
+
+/// Draws the elements that fall inside `area` as an SVG document of the
+/// same size. Frames go underneath everything else.
+pub fn render_svg(elements: &[WhiteboardElement], area: &Bounds) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        area.width, area.height, area.x, area.y, area.width, area.height
+    );
+    svg.push_str(&format!(
+        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#FFFFFF"/>"##,
+        area.x, area.y, area.width, area.height
+    ));
+
+    let (frames, others): (Vec<&WhiteboardElement>, Vec<&WhiteboardElement>) = elements.iter()
+        .partition(|element| matches!(element.element_type, ElementType::Frame));
+    for element in frames.into_iter().chain(others) {
+        svg.push_str(&render_element(element));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn render_element(element: &WhiteboardElement) -> String {
+    let bounds = element_bounds(element);
+    let position = &element.position;
+
+    let body = match element.element_type {
+        ElementType::Text => {
+            let font_size = number(element, "font_size").unwrap_or(16.0);
+            let text = string(element, "text").unwrap_or_default();
+            let lines = match position.width {
+                Some(width) => wrap_text(text, width, font_size),
+                None => text.lines().map(str::to_string).collect(),
+            };
+            text_lines(element, &lines, position.x, position.y, font_size, &color(element, "color", "#212121"))
+        }
+        ElementType::Sticky => {
+            let font_size = number(element, "font_size").unwrap_or(18.0);
+            let inset = 12.0;
+            let lines = wrap_text(string(element, "text").unwrap_or_default(), bounds.width - inset * 2.0, font_size);
+            format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="4" fill="{}" stroke="{}" stroke-width="1"/>{}"#,
+                bounds.x, bounds.y, bounds.width, bounds.height,
+                color(element, "color", "#FFF59D"),
+                color(element, "stroke", "#E0C85A"),
+                text_lines(element, &lines, bounds.x + inset, bounds.y + inset, font_size, &color(element, "text_color", "#212121")),
+            )
+        }
+        ElementType::Shape => {
+            let paint = format!(
+                r#"fill="{}" stroke="{}" stroke-width="{}""#,
+                color(element, "fill", "none"),
+                color(element, "stroke", "#1E88E5"),
+                number(element, "stroke_width").unwrap_or(2.0),
+            );
+            let (x, y, width, height) = (bounds.x, bounds.y, bounds.width, bounds.height);
+
+            match string(element, "shape").unwrap_or("rectangle") {
+                "ellipse" | "circle" => format!(
+                    r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" {}/>"#,
+                    x + width / 2.0, y + height / 2.0, width / 2.0, height / 2.0, paint
+                ),
+                "triangle" => format!(
+                    r#"<polygon points="{},{} {},{} {},{}" {}/>"#,
+                    x + width / 2.0, y, x + width, y + height, x, y + height, paint
+                ),
+                "diamond" => format!(
+                    r#"<polygon points="{},{} {},{} {},{} {},{}" {}/>"#,
+                    x + width / 2.0, y, x + width, y + height / 2.0, x + width / 2.0, y + height, x, y + height / 2.0, paint
+                ),
+                _ => format!(
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" {}/>"#,
+                    x, y, width, height, number(element, "corner_radius").unwrap_or(0.0), paint
+                ),
+            }
+        }
+        ElementType::Line => {
+            let points = line_points(element);
+            let stroke = color(element, "stroke", "#212121");
+            let stroke_width = number(element, "stroke_width").unwrap_or(2.0);
+            let mut line = format!(
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                points_attribute(&points), stroke, stroke_width
+            );
+
+            if flag(element, "end_arrow") {
+                if let [.., from, to] = points.as_slice() {
+                    line.push_str(&arrow_head(*from, *to, stroke_width, &stroke));
+                }
+            }
+            if flag(element, "start_arrow") {
+                if let [to, from, ..] = points.as_slice() {
+                    line.push_str(&arrow_head(*from, *to, stroke_width, &stroke));
+                }
+            }
+            line
+        }
+        ElementType::FreeDrawing => {
+            let points = line_points(element);
+            let path = points.iter()
+                .enumerate()
+                .map(|(index, (x, y))| format!("{}{} {}", if index == 0 { "M" } else { " L" }, x, y))
+                .collect::<String>();
+            format!(
+                r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                path,
+                color(element, "stroke", "#212121"),
+                number(element, "stroke_width").unwrap_or(3.0),
+            )
+        }
+        ElementType::Image => match string(element, "src").filter(|src| src.starts_with("data:image/")) {
+            Some(src) => format!(
+                r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="xMidYMid meet" xlink:href="{}"/>"#,
+                bounds.x, bounds.y, bounds.width, bounds.height, escape(src)
+            ),
+            // Images that could not be fetched show as an empty placeholder
+            None => format!(
+                r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#EEEEEE" stroke="#BDBDBD" stroke-dasharray="6 4"/>"##,
+                bounds.x, bounds.y, bounds.width, bounds.height
+            ),
+        },
+        ElementType::Frame => {
+            let mut frame = format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="1"/>"#,
+                bounds.x, bounds.y, bounds.width, bounds.height,
+                color(element, "fill", "#FFFFFF"),
+                color(element, "stroke", "#BDBDBD"),
+            );
+            // The title sits above the frame, so it is cut off on the frame's own page
+            if let Some(title) = string(element, "title") {
+                frame.push_str(&format!(
+                    r##"<text x="{}" y="{}" font-size="14" fill="#757575">{}</text>"##,
+                    bounds.x, bounds.y - 8.0, escape(title)
+                ));
+            }
+            frame
+        }
+    };
+
+    let mut attributes = String::new();
+    if let Some(opacity) = number(element, "opacity") {
+        attributes.push_str(&format!(r#" opacity="{}""#, opacity.clamp(0.0, 1.0)));
+    }
+    if let Some(rotation) = number(element, "rotation").filter(|rotation| *rotation != 0.0) {
+        attributes.push_str(&format!(
+            r#" transform="rotate({} {} {})""#,
+            rotation, bounds.x + bounds.width / 2.0, bounds.y + bounds.height / 2.0
+        ));
+    }
+
+    format!("<g{}>{}</g>", attributes, body)
+}
+
+fn text_lines(element: &WhiteboardElement, lines: &[String], x: f64, y: f64, font_size: f64, fill: &str) -> String {
+    let mut text = format!(
+        r#"<text x="{}" y="{}" font-size="{}" fill="{}""#,
+        x, y + font_size, font_size, fill
+    );
+    if let Some(family) = string(element, "font_family") {
+        text.push_str(&format!(r#" font-family="{}""#, escape(family)));
+    }
+    if let Some(weight) = string(element, "font_weight") {
+        text.push_str(&format!(r#" font-weight="{}""#, escape(weight)));
+    }
+    if let Some(style) = string(element, "font_style") {
+        text.push_str(&format!(r#" font-style="{}""#, escape(style)));
+    }
+    text.push('>');
+
+    for (index, line) in lines.iter().enumerate() {
+        let dy = if index == 0 { 0.0 } else { font_size * LINE_HEIGHT };
+        text.push_str(&format!(r#"<tspan x="{}" dy="{}">{}</tspan>"#, x, dy, escape(line)));
+    }
+
+    text.push_str("</text>");
+    text
+}
+
+fn arrow_head(from: (f64, f64), to: (f64, f64), stroke_width: f64, fill: &str) -> String {
+    let angle = (to.1 - from.1).atan2(to.0 - from.0);
+    let length = 8.0 + stroke_width * 3.0;
+    let spread = std::f64::consts::PI / 7.0;
+    let left = (to.0 - length * (angle - spread).cos(), to.1 - length * (angle - spread).sin());
+    let right = (to.0 - length * (angle + spread).cos(), to.1 - length * (angle + spread).sin());
+
+    format!(
+        r#"<polygon points="{},{} {},{} {},{}" fill="{}"/>"#,
+        to.0, to.1, left.0, left.1, right.0, right.1, fill
+    )
+}
+
+/// Lines of at most roughly `width` wide, breaking at spaces and on newlines.
+fn wrap_text(text: &str, width: f64, font_size: f64) -> Vec<String> {
+    let max_chars = ((width / (font_size * CHAR_WIDTH)).floor() as usize).max(1);
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+// Lines and drawings store points relative to their position, as [[x, y], ...]
+fn line_points(element: &WhiteboardElement) -> Vec<(f64, f64)> {
+    let position = &element.position;
+    let points: Vec<(f64, f64)> = element.properties.get("points")
+        .and_then(|points| points.as_array())
+        .map(|points| points.iter()
+            .filter_map(|point| {
+                let point = point.as_array()?;
+                Some((position.x + point.first()?.as_f64()?, position.y + point.get(1)?.as_f64()?))
+            })
+            .collect())
+        .unwrap_or_default();
+
+    if points.is_empty() {
+        // A plain line runs across its box
+        vec![
+            (position.x, position.y),
+            (position.x + position.width.unwrap_or(0.0), position.y + position.height.unwrap_or(0.0)),
+        ]
+    } else {
+        points
+    }
+}
+
+fn points_attribute(points: &[(f64, f64)]) -> String {
+    points.iter()
+        .map(|(x, y)| format!("{},{}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn element_bounds(element: &WhiteboardElement) -> Bounds {
+    let position = &element.position;
+
+    let (default_width, default_height) = match element.element_type {
+        ElementType::Line | ElementType::FreeDrawing => {
+            let points = line_points(element);
+            let margin = number(element, "stroke_width").unwrap_or(3.0);
+            let (min_x, max_x) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), (x, _)| (min.min(*x), max.max(*x)));
+            let (min_y, max_y) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), (_, y)| (min.min(*y), max.max(*y)));
+            return Bounds {
+                x: min_x - margin,
+                y: min_y - margin,
+                width: max_x - min_x + margin * 2.0,
+                height: max_y - min_y + margin * 2.0,
+            };
+        }
+        ElementType::Text => {
+            let font_size = number(element, "font_size").unwrap_or(16.0);
+            let text = string(element, "text").unwrap_or_default();
+            let lines = match position.width {
+                Some(width) => wrap_text(text, width, font_size),
+                None => text.lines().map(str::to_string).collect(),
+            };
+            let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+            (longest as f64 * font_size * CHAR_WIDTH, lines.len().max(1) as f64 * font_size * LINE_HEIGHT)
+        }
+        ElementType::Sticky => (200.0, 200.0),
+        ElementType::Shape => (100.0, 100.0),
+        ElementType::Image => (200.0, 150.0),
+        ElementType::Frame => (1280.0, 720.0),
+    };
+
+    Bounds {
+        x: position.x,
+        y: position.y,
+        width: position.width.unwrap_or(default_width),
+        height: position.height.unwrap_or(default_height),
+    }
+}
+
+fn string<'a>(element: &'a WhiteboardElement, key: &str) -> Option<&'a str> {
+    element.properties.get(key).and_then(|value| value.as_str())
+}
+
+fn number(element: &WhiteboardElement, key: &str) -> Option<f64> {
+    element.properties.get(key).and_then(|value| value.as_f64()).filter(|value| value.is_finite())
+}
+
+fn flag(element: &WhiteboardElement, key: &str) -> bool {
+    element.properties.get(key).and_then(|value| value.as_bool()).unwrap_or(false)
+}
+
+// Colors come from clients, so anything that isn't plainly a color is replaced
+fn color(element: &WhiteboardElement, key: &str, default: &str) -> String {
+    string(element, key)
+        .filter(|value| !value.is_empty() && value.len() <= 32)
+        .filter(|value| value.chars().all(|c| c.is_ascii_alphanumeric() || "#(),.% ".contains(c)))
+        .unwrap_or(default)
+        .to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Replaces remote image sources with data URLs, since the renderer never
+/// touches the network or the file system. Only https images on
+/// `allowed_hosts` are fetched; any other image, or one that fails to
+/// download, is dropped and drawn as a placeholder.
+pub async fn inline_images(allowed_hosts: &[String], elements: &mut [WhiteboardElement]) {
+    let sources: Vec<(usize, String)> = elements.iter()
+        .enumerate()
+        .filter(|(_, element)| matches!(element.element_type, ElementType::Image))
+        .filter_map(|(index, element)| Some((index, string(element, "src")?.to_string())))
+        .filter(|(_, src)| !src.starts_with("data:image/"))
+        .collect();
+
+    let fetched: Vec<(usize, Option<String>)> = stream::iter(sources)
+        .map(|(index, src)| async move {
+            match fetch_image(allowed_hosts, &src).await {
+                Ok(data_url) => (index, Some(data_url)),
+                Err(e) => {
+                    tracing::warn!("Failed to fetch whiteboard image {}: {}", src, e);
+                    (index, None)
+                }
+            }
+        })
+        .buffer_unordered(IMAGE_FETCH_CONCURRENCY)
+        .collect()
+        .await;
+
+    for (index, inlined) in fetched {
+        let element = &mut elements[index];
+        match inlined {
+            Some(data_url) => {
+                element.properties.insert("src".to_string(), serde_json::Value::String(data_url));
+            }
+            None => {
+                element.properties.remove("src");
+            }
+        }
+    }
+}
+
+async fn fetch_image(allowed_hosts: &[String], src: &str) -> Result<String, AppError> {
+    let url = allowed_image_url(allowed_hosts, src)?;
+    let client = outbound::pinned_client(&url, Duration::from_secs(10)).await?;
+
+    let mut response = client.get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::ExternalService(format!("Image request failed: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::ExternalService(format!("Image server returned {}", response.status())));
+    }
+
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(';').next().unwrap_or_default().trim().to_string())
+        .filter(|value| matches!(value.as_str(), "image/png" | "image/jpeg" | "image/gif" | "image/webp"))
+        .ok_or_else(|| AppError::ExternalService("Unsupported image type".to_string()))?;
+
+    if response.content_length().map_or(false, |length| length > MAX_IMAGE_BYTES as u64) {
+        return Err(AppError::ExternalService("Image is too large".to_string()));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await
+        .map_err(|e| AppError::ExternalService(format!("Failed to read image: {}", e)))?
+    {
+        if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+            return Err(AppError::ExternalService("Image is too large".to_string()));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(format!(
+        "data:{};base64,{}",
+        content_type,
+        base64::engine::general_purpose::STANDARD.encode(&bytes)
+    ))
+}
+
+/// Parses an image source, allowing only https URLs on the allowed hosts.
+fn allowed_image_url(allowed_hosts: &[String], src: &str) -> Result<Url, AppError> {
+    let url = Url::parse(src)
+        .map_err(|_| AppError::BadRequest("Invalid image URL".to_string()))?;
+    if !outbound::https_host_allowed(allowed_hosts, &url) {
+        return Err(AppError::Forbidden("Image host is not allowed".to_string()));
+    }
+
+    Ok(url)
+}
+
+/// Rasterizes and prints whiteboards with the fonts it was given. Cheap to
+/// clone; rendering is CPU-bound, so callers run it on a blocking thread.
+#[derive(Clone)]
+pub struct WhiteboardRenderer {
+    fontdb: Arc<fontdb::Database>,
+    font_family: String,
+}
+
+impl WhiteboardRenderer {
+    /// Loads the system fonts plus any in `fonts_path`, and uses
+    /// `font_family` for text that doesn't name an installed font.
+    pub fn new(fonts_path: Option<&str>, font_family: &str) -> Self {
+        let mut fontdb = fontdb::Database::new();
+        fontdb.load_system_fonts();
+        if let Some(fonts_path) = fonts_path {
+            fontdb.load_fonts_dir(fonts_path);
+        }
+        fontdb.set_sans_serif_family(font_family);
+
+        tracing::info!("Loaded {} fonts for whiteboard exports", fontdb.len());
+
+        Self {
+            fontdb: Arc::new(fontdb),
+            font_family: font_family.to_string(),
+        }
+    }
+
+    /// A PNG of `area`, `width` pixels wide.
+    pub fn png(&self, elements: &[WhiteboardElement], area: &Bounds, width: u32) -> Result<Vec<u8>, AppError> {
+        let tree = self.tree(&render_svg(elements, area))?;
+
+        let width = width.clamp(1, MAX_EXPORT_WIDTH);
+        let scale = width as f64 / area.width;
+        let height = ((area.height * scale).round() as u32).clamp(1, MAX_EXPORT_WIDTH);
+
+        let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+            .ok_or_else(|| AppError::BadRequest("Invalid export size".to_string()))?;
+        resvg::render(
+            &tree,
+            resvg::tiny_skia::Transform::from_scale(scale as f32, scale as f32),
+            &mut pixmap.as_mut(),
+        );
+
+        pixmap.encode_png()
+            .map_err(|e| AppError::Internal(format!("Failed to encode PNG: {}", e)))
+    }
+
+    /// A PDF with one page per area, each the size of its area in points.
+    pub fn pdf(&self, elements: &[WhiteboardElement], pages: &[Bounds]) -> Result<Vec<u8>, AppError> {
+        let mut pdf = Pdf::new();
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let mut next_id = Ref::new(3);
+        let mut page_ids = Vec::new();
+
+        for area in pages {
+            let tree = self.tree(&render_svg(elements, area))?;
+
+            let page_id = next_id;
+            let content_id = Ref::new(page_id.get() + 1);
+            let svg_id = Ref::new(page_id.get() + 2);
+            next_id = svg2pdf::convert_tree_into(&tree, svg2pdf::Options::default(), &mut pdf, svg_id);
+
+            let svg_name = Name(b"S1");
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, area.width as f32, area.height as f32));
+            page.parent(page_tree_id);
+            page.contents(content_id);
+            page.resources().x_objects().pair(svg_name, svg_id);
+            page.finish();
+
+            // The converted SVG is a unit square; stretch it over the page
+            let mut content = Content::new();
+            content
+                .transform([area.width as f32, 0.0, 0.0, area.height as f32, 0.0, 0.0])
+                .x_object(svg_name);
+            pdf.stream(content_id, &content.finish());
+
+            page_ids.push(page_id);
+        }
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id).count(page_ids.len() as i32).kids(page_ids);
+
+        Ok(pdf.finish())
+    }
+
+    fn tree(&self, svg: &str) -> Result<usvg::Tree, AppError> {
+        let mut options = usvg::Options {
+            font_family: self.font_family.clone(),
+            ..usvg::Options::default()
+        };
+        // Only inlined images are drawn; never read paths from the file system
+        options.image_href_resolver.resolve_string = Box::new(|_, _| None);
+
+        let mut tree = usvg::Tree::from_str(svg, &options)
+            .map_err(|e| AppError::Internal(format!("Failed to parse whiteboard SVG: {}", e)))?;
+        // Text becomes paths, which both the rasterizer and the PDF writer need
+        tree.postprocess(PostProcessingSteps::default(), &self.fontdb);
+
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::Position;
+
+    fn element(element_type: ElementType, x: f64, y: f64, properties: serde_json::Value) -> WhiteboardElement {
+        WhiteboardElement {
+            element_id: Uuid::new_v4(),
+            element_type,
+            position: Position { x, y, width: None, height: None },
+            properties: serde_json::from_value::<HashMap<String, serde_json::Value>>(properties).unwrap(),
+            created_by: Uuid::new_v4(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_svg_draws_every_element_type() {
+        let elements = vec![
+            element(ElementType::Text, 10.0, 10.0, serde_json::json!({"text": "a < b & \"c\"", "font_weight": "bold"})),
+            element(ElementType::Shape, 100.0, 10.0, serde_json::json!({"shape": "ellipse", "fill": "red\" onload=\"x"})),
+            element(ElementType::Line, 0.0, 200.0, serde_json::json!({"points": [[0, 0], [100, 50]], "end_arrow": true})),
+            element(ElementType::FreeDrawing, 0.0, 300.0, serde_json::json!({"points": [[0, 0], [5, 5], [10, 0]]})),
+            element(ElementType::Image, 300.0, 300.0, serde_json::json!({"src": "file:///etc/passwd"})),
+            element(ElementType::Sticky, 500.0, 10.0, serde_json::json!({"text": "Remember to follow up on the roadmap"})),
+            element(ElementType::Frame, 0.0, 0.0, serde_json::json!({"title": "Plan"})),
+        ];
+
+        let svg = render_svg(&elements, &board_bounds(&elements));
+
+        assert!(svg.contains("a &lt; b &amp; &quot;c&quot;"));
+        assert!(svg.contains(r#"font-weight="bold""#));
+        assert!(svg.contains("<ellipse"));
+        assert!(!svg.contains("onload"));
+        assert!(svg.contains("<polyline") && svg.contains("<polygon"));
+        assert!(svg.contains(r#"<path d="M0 300 L5 305 L10 300""#));
+        assert!(!svg.contains("passwd"));
+        assert!(svg.contains(">Remember to</tspan>"));
+        // Frames are drawn first, underneath the rest
+        assert!(svg.find(">Plan<").unwrap() < svg.find("<ellipse").unwrap());
+
+        usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    }
+
+    #[test]
+    fn test_pages_follow_frames() {
+        let mut frame = element(ElementType::Frame, 0.0, 0.0, serde_json::json!({}));
+        frame.position.width = Some(800.0);
+        frame.position.height = Some(600.0);
+        let second = element(ElementType::Frame, 1000.0, 0.0, serde_json::json!({}));
+        let note = element(ElementType::Sticky, 50.0, 50.0, serde_json::json!({}));
+
+        assert_eq!(pages(&[note.clone()]), vec![Bounds { x: 10.0, y: 10.0, width: 280.0, height: 280.0 }]);
+        assert_eq!(pages(&[frame, note, second]), vec![
+            Bounds { x: 0.0, y: 0.0, width: 800.0, height: 600.0 },
+            Bounds { x: 1000.0, y: 0.0, width: 1280.0, height: 720.0 },
+        ]);
+    }
+
+    #[test]
+    fn test_exports_png_at_requested_width_and_pdf_per_page() {
+        let renderer = WhiteboardRenderer {
+            fontdb: Arc::new(fontdb::Database::new()),
+            font_family: "sans-serif".to_string(),
+        };
+        let elements = vec![element(ElementType::Shape, 0.0, 0.0, serde_json::json!({"fill": "#FF0000"}))];
+        let area = Bounds { x: 0.0, y: 0.0, width: 200.0, height: 100.0 };
+
+        let png = renderer.png(&elements, &area, 400).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 400);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 200);
+
+        let pdf = renderer.pdf(&elements, &[area, area]).unwrap();
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with("%PDF"));
+        assert!(pdf.contains("/Count 2"));
+    }
+
+    #[test]
+    fn test_images_only_come_from_allowed_hosts() {
+        let hosts = vec!["materials.example.com".to_string()];
+
+        assert!(allowed_image_url(&hosts, "https://materials.example.com/a.png").is_ok());
+        assert!(allowed_image_url(&hosts, "http://materials.example.com/a.png").is_err());
+        assert!(allowed_image_url(&hosts, "https://169.254.169.254/latest/meta-data").is_err());
+        assert!(allowed_image_url(&hosts, "file:///etc/passwd").is_err());
+        assert!(allowed_image_url(&hosts, "not a url").is_err());
+        assert!(allowed_image_url(&[], "https://materials.example.com/a.png").is_err());
+    }
+}
@@ -0,0 +1,862 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use linkwithmentor_common::{ApiResponse, AppError};
+use crate::{
+    config::MeetingsServiceConfig,
+    models::{
+        NotificationRequest, NotificationType, PaySeatRequest, SeatStatus, SessionStatus,
+        SessionType, Workshop, WorkshopAttendee, WorkshopAttendeeList, WorkshopRequest, WorkshopSeat,
+    },
+    notifications::NotificationService,
+    scheduling::SchedulingService,
+};
+
+const DEFAULT_CURRENCY: &str = "USD";
+
+/// Seats, payments and waitlists for group sessions. Every change to a
+/// workshop's seats happens with its settings row locked, so concurrent
+/// bookings are serialised and can never oversell it.
+#[derive(Clone)]
+pub struct WorkshopService {
+    db_pool: PgPool,
+    scheduling_service: SchedulingService,
+    notification_service: NotificationService,
+    http_client: Client,
+    config: MeetingsServiceConfig,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SettingsRow {
+    session_id: Uuid,
+    capacity: i32,
+    seat_price: Decimal,
+    currency: String,
+    waitlist_enabled: bool,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SeatRow {
+    seat_id: Uuid,
+    session_id: Uuid,
+    user_id: Uuid,
+    status: String,
+    amount: Decimal,
+    hold_expires_at: Option<DateTime<Utc>>,
+    payment_id: Option<Uuid>,
+    refund_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AttendeeRow {
+    username: String,
+    email: String,
+    #[sqlx(flatten)]
+    seat: SeatRow,
+}
+
+// The parts of the payment service's responses we use
+#[derive(Debug, Deserialize)]
+struct PaymentResult {
+    payment_id: Uuid,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefundResult {
+    refund_id: Uuid,
+}
+
+// Seats released or handed out while rebalancing, for notifications
+struct SeatChanges {
+    expired: Vec<SeatRow>,
+    promoted: Vec<SeatRow>,
+}
+
+impl WorkshopService {
+    pub fn new(
+        db_pool: PgPool,
+        scheduling_service: SchedulingService,
+        notification_service: NotificationService,
+        config: &MeetingsServiceConfig,
+    ) -> Self {
+        Self {
+            db_pool,
+            scheduling_service,
+            notification_service,
+            http_client: Client::new(),
+            config: config.clone(),
+        }
+    }
+
+    pub async fn initialize(&self) -> Result<(), AppError> {
+        self.start_hold_expiry_task().await?;
+
+        tracing::info!("Workshop service initialized");
+        Ok(())
+    }
+
+    pub async fn set_workshop(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        request: WorkshopRequest,
+    ) -> Result<Workshop, AppError> {
+        let session = self.scheduling_service.get_session(session_id).await?;
+        if session.mentor_id != user_id {
+            return Err(AppError::Forbidden("Only the mentor can manage this workshop".to_string()));
+        }
+        if !matches!(session.session_type, SessionType::Workshop | SessionType::GroupSession) {
+            return Err(AppError::BadRequest("Only workshops and group sessions have seats".to_string()));
+        }
+        if request.capacity == 0 || request.capacity > self.config.max_workshop_capacity {
+            return Err(AppError::BadRequest(format!(
+                "Capacity must be between 1 and {}",
+                self.config.max_workshop_capacity
+            )));
+        }
+
+        let seat_price = request.seat_price.unwrap_or(Decimal::ZERO);
+        if seat_price < Decimal::ZERO || seat_price.normalize().scale() > 2 {
+            return Err(AppError::BadRequest("Seat price must not be negative or have more than two decimals".to_string()));
+        }
+        let currency = request.currency.unwrap_or_else(|| DEFAULT_CURRENCY.to_string()).to_uppercase();
+        if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(AppError::BadRequest("Currency must be a three-letter ISO 4217 code".to_string()));
+        }
+
+        let mut tx = self.begin().await?;
+
+        // Lock the existing settings, if any, before checking seats against the new capacity
+        let existing = sqlx::query_as::<_, SettingsRow>(
+            "SELECT session_id, capacity, seat_price, currency, waitlist_enabled FROM workshop_settings WHERE session_id = $1 FOR UPDATE"
+        )
+            .bind(session_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch workshop: {}", e)))?;
+
+        if existing.is_some() {
+            let taken = Self::seats_taken(&mut tx, session_id).await?;
+            if request.capacity < taken {
+                return Err(AppError::BadRequest(format!(
+                    "{} seats are already taken; cancel bookings before lowering the capacity",
+                    taken
+                )));
+            }
+        }
+
+        let query = r#"
+            INSERT INTO workshop_settings (session_id, capacity, seat_price, currency, waitlist_enabled, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
+            ON CONFLICT (session_id) DO UPDATE SET
+                capacity = EXCLUDED.capacity,
+                seat_price = EXCLUDED.seat_price,
+                currency = EXCLUDED.currency,
+                waitlist_enabled = EXCLUDED.waitlist_enabled,
+                updated_at = NOW()
+            RETURNING session_id, capacity, seat_price, currency, waitlist_enabled
+        "#;
+
+        let settings = sqlx::query_as::<_, SettingsRow>(query)
+            .bind(session_id)
+            .bind(request.capacity as i32)
+            .bind(seat_price)
+            .bind(&currency)
+            .bind(request.waitlist_enabled.unwrap_or(true))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save workshop: {}", e)))?;
+
+        // A larger capacity lets people in from the waitlist straight away
+        let changes = self.rebalance(&mut tx, &settings).await?;
+        let workshop = Self::summary(&mut tx, &settings).await?;
+
+        self.commit(tx).await?;
+        self.notify_changes(&session.title, changes).await;
+
+        Ok(workshop)
+    }
+
+    pub async fn get_workshop(&self, session_id: Uuid) -> Result<Workshop, AppError> {
+        let mut tx = self.begin().await?;
+        let settings = Self::load_settings(&mut tx, session_id, false).await?;
+        let workshop = Self::summary(&mut tx, &settings).await?;
+        self.commit(tx).await?;
+
+        Ok(workshop)
+    }
+
+    /// Books a seat, or a waitlist place if the workshop is full. Paid seats
+    /// are held for `workshop_hold_minutes` while the attendee pays.
+    pub async fn book_seat(&self, session_id: Uuid, user_id: Uuid) -> Result<WorkshopSeat, AppError> {
+        let session = self.scheduling_service.get_session(session_id).await?;
+        if session.mentor_id == user_id {
+            return Err(AppError::BadRequest("Mentors cannot book their own workshop".to_string()));
+        }
+        if session.status == SessionStatus::Cancelled || session.scheduled_start <= Utc::now() {
+            return Err(AppError::BadRequest("This workshop is no longer open for booking".to_string()));
+        }
+
+        let mut tx = self.begin().await?;
+        let settings = Self::load_settings(&mut tx, session_id, true).await?;
+
+        // Free seats go to the waitlist first, so nobody jumps the queue
+        let changes = self.rebalance(&mut tx, &settings).await?;
+
+        if Self::active_seat(&mut tx, session_id, user_id).await?.is_some() {
+            return Err(AppError::Conflict("You already have a seat or waitlist place for this workshop".to_string()));
+        }
+
+        let taken = Self::seats_taken(&mut tx, session_id).await?;
+        let status = booking_status(settings.capacity as u32, settings.seat_price, settings.waitlist_enabled, taken)?;
+        let hold_expires_at = (status == SeatStatus::Held).then(|| self.hold_expiry());
+
+        let query = r#"
+            INSERT INTO workshop_seats (seat_id, session_id, user_id, status, amount, hold_expires_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+            RETURNING *
+        "#;
+
+        let seat = sqlx::query_as::<_, SeatRow>(query)
+            .bind(Uuid::new_v4())
+            .bind(session_id)
+            .bind(user_id)
+            .bind(status.as_str())
+            .bind(settings.seat_price)
+            .bind(hold_expires_at)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to book workshop seat: {}", e)))?;
+
+        if status == SeatStatus::Confirmed {
+            Self::add_participant(&mut tx, session_id, user_id).await?;
+        }
+        let seat = Self::with_position(&mut tx, seat).await?;
+
+        self.commit(tx).await?;
+        self.notify_changes(&session.title, changes).await;
+
+        tracing::info!("User {} booked workshop {} as {}", user_id, session_id, status.as_str());
+        Ok(seat)
+    }
+
+    pub async fn get_seat(&self, session_id: Uuid, user_id: Uuid) -> Result<WorkshopSeat, AppError> {
+        let mut tx = self.begin().await?;
+        let seat = Self::active_seat(&mut tx, session_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("You have no booking for this workshop".to_string()))?;
+        let seat = Self::with_position(&mut tx, seat).await?;
+        self.commit(tx).await?;
+
+        Ok(seat)
+    }
+
+    /// Pays for a held seat through the payment service. The caller's
+    /// Authorization header is forwarded so the payment is made as them.
+    pub async fn pay_seat(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        request: PaySeatRequest,
+        auth_header: Option<String>,
+    ) -> Result<WorkshopSeat, AppError> {
+        let session = self.scheduling_service.get_session(session_id).await?;
+
+        let mut tx = self.begin().await?;
+        let settings = Self::load_settings(&mut tx, session_id, false).await?;
+        let seat = Self::active_seat(&mut tx, session_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("You have no booking for this workshop".to_string()))?;
+        self.commit(tx).await?;
+
+        if seat.status != SeatStatus::Held.as_str() {
+            return Err(AppError::BadRequest("This seat is not waiting for payment".to_string()));
+        }
+        if seat.hold_expires_at.map_or(true, |expires_at| expires_at <= Utc::now()) {
+            return Err(AppError::BadRequest("Your seat hold has expired".to_string()));
+        }
+
+        // Charge outside the lock; the hold is checked again before confirming
+        let payment = self.charge(&seat, &settings, &session.title, &request, auth_header.as_deref()).await?;
+
+        let mut tx = self.begin().await?;
+        Self::load_settings(&mut tx, session_id, true).await?;
+
+        let query = r#"
+            UPDATE workshop_seats
+            SET status = $2, payment_id = $3, hold_expires_at = NULL, updated_at = NOW()
+            WHERE seat_id = $1 AND status = $4 AND hold_expires_at > NOW()
+            RETURNING *
+        "#;
+
+        let confirmed = sqlx::query_as::<_, SeatRow>(query)
+            .bind(seat.seat_id)
+            .bind(SeatStatus::Confirmed.as_str())
+            .bind(payment.payment_id)
+            .bind(SeatStatus::Held.as_str())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to confirm workshop seat: {}", e)))?;
+
+        let Some(confirmed) = confirmed else {
+            drop(tx);
+            self.refund(payment.payment_id, "Workshop seat hold expired during payment", auth_header.as_deref()).await?;
+            return Err(AppError::Conflict("Your seat hold expired before the payment completed; it has been refunded".to_string()));
+        };
+
+        Self::add_participant(&mut tx, session_id, user_id).await?;
+        self.commit(tx).await?;
+
+        self.notify(user_id, session_id, NotificationType::SessionConfirmation, "Workshop seat confirmed", format!(
+            "Your payment went through and your seat for \"{}\" is confirmed.",
+            session.title
+        )).await;
+
+        confirmed.into_seat(None)
+    }
+
+    /// Cancels the caller's seat or waitlist place and offers the seat to the
+    /// next in line. Paid seats are refunded outside the cancellation window.
+    pub async fn cancel_seat(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        auth_header: Option<String>,
+    ) -> Result<WorkshopSeat, AppError> {
+        let session = self.scheduling_service.get_session(session_id).await?;
+
+        let mut tx = self.begin().await?;
+        let settings = Self::load_settings(&mut tx, session_id, true).await?;
+        let seat = Self::active_seat(&mut tx, session_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("You have no booking for this workshop".to_string()))?;
+
+        let query = r#"
+            UPDATE workshop_seats
+            SET status = $2, hold_expires_at = NULL, updated_at = NOW()
+            WHERE seat_id = $1
+            RETURNING *
+        "#;
+
+        let cancelled = sqlx::query_as::<_, SeatRow>(query)
+            .bind(seat.seat_id)
+            .bind(SeatStatus::Cancelled.as_str())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to cancel workshop seat: {}", e)))?;
+
+        Self::remove_participant(&mut tx, session_id, user_id).await?;
+        let changes = self.rebalance(&mut tx, &settings).await?;
+
+        self.commit(tx).await?;
+        self.notify_changes(&session.title, changes).await;
+
+        let refundable = session.scheduled_start - Utc::now()
+            >= Duration::hours(self.config.cancellation_window_hours as i64);
+        let Some(payment_id) = cancelled.payment_id.filter(|_| refundable) else {
+            return cancelled.into_seat(None);
+        };
+
+        let refund = self.refund(payment_id, "Workshop seat cancelled", auth_header.as_deref()).await
+            .map_err(|e| {
+                tracing::error!("Failed to refund workshop seat {}: {}", cancelled.seat_id, e);
+                e
+            })?;
+
+        let refunded = sqlx::query_as::<_, SeatRow>(
+            "UPDATE workshop_seats SET refund_id = $2, updated_at = NOW() WHERE seat_id = $1 RETURNING *"
+        )
+            .bind(cancelled.seat_id)
+            .bind(refund.refund_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record workshop refund: {}", e)))?;
+
+        refunded.into_seat(None)
+    }
+
+    pub async fn get_attendees(&self, session_id: Uuid, user_id: Uuid) -> Result<WorkshopAttendeeList, AppError> {
+        let session = self.scheduling_service.get_session(session_id).await?;
+        if session.mentor_id != user_id {
+            return Err(AppError::Forbidden("Only the mentor can see the attendee list".to_string()));
+        }
+
+        let mut tx = self.begin().await?;
+        let settings = Self::load_settings(&mut tx, session_id, false).await?;
+        let workshop = Self::summary(&mut tx, &settings).await?;
+
+        let query = r#"
+            SELECT u.username, u.email, s.*
+            FROM workshop_seats s
+            JOIN users u ON u.user_id = s.user_id
+            WHERE s.session_id = $1 AND s.status IN ('held', 'confirmed', 'waitlisted')
+            ORDER BY s.created_at
+        "#;
+
+        let rows = sqlx::query_as::<_, AttendeeRow>(query)
+            .bind(session_id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch workshop attendees: {}", e)))?;
+        self.commit(tx).await?;
+
+        let mut list = WorkshopAttendeeList {
+            workshop,
+            confirmed: Vec::new(),
+            held: Vec::new(),
+            waitlist: Vec::new(),
+        };
+
+        for row in rows {
+            let position = (row.seat.status == SeatStatus::Waitlisted.as_str()).then(|| list.waitlist.len() as u32 + 1);
+            let seat = row.seat.into_seat(position)?;
+            let status = seat.status;
+            let attendee = WorkshopAttendee {
+                user_id: seat.user_id,
+                username: row.username,
+                email: row.email,
+                seat,
+            };
+
+            match status {
+                SeatStatus::Confirmed => list.confirmed.push(attendee),
+                SeatStatus::Held => list.held.push(attendee),
+                _ => list.waitlist.push(attendee),
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Releases lapsed holds on every workshop that has them.
+    pub async fn expire_holds(&self) -> Result<(), AppError> {
+        let query = r#"
+            SELECT DISTINCT session_id FROM workshop_seats
+            WHERE status = 'held' AND hold_expires_at <= NOW()
+        "#;
+
+        let session_ids: Vec<Uuid> = sqlx::query_scalar(query)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch expired holds: {}", e)))?;
+
+        for session_id in session_ids {
+            if let Err(e) = self.release_lapsed_holds(session_id).await {
+                tracing::warn!("Failed to expire holds for workshop {}: {}", session_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn release_lapsed_holds(&self, session_id: Uuid) -> Result<(), AppError> {
+        let session = self.scheduling_service.get_session(session_id).await?;
+
+        let mut tx = self.begin().await?;
+        let settings = Self::load_settings(&mut tx, session_id, true).await?;
+        let changes = self.rebalance(&mut tx, &settings).await?;
+        self.commit(tx).await?;
+
+        self.notify_changes(&session.title, changes).await;
+        Ok(())
+    }
+
+    async fn start_hold_expiry_task(&self) -> Result<(), AppError> {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = service.expire_holds().await {
+                    tracing::error!("Failed to expire workshop holds: {}", e);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    // Expires lapsed holds, then fills free seats from the front of the
+    // waitlist. Must run with the workshop locked.
+    async fn rebalance(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        settings: &SettingsRow,
+    ) -> Result<SeatChanges, AppError> {
+        let query = r#"
+            UPDATE workshop_seats
+            SET status = $2, hold_expires_at = NULL, updated_at = NOW()
+            WHERE session_id = $1 AND status = $3 AND hold_expires_at <= NOW()
+            RETURNING *
+        "#;
+
+        let expired = sqlx::query_as::<_, SeatRow>(query)
+            .bind(settings.session_id)
+            .bind(SeatStatus::Expired.as_str())
+            .bind(SeatStatus::Held.as_str())
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to expire workshop holds: {}", e)))?;
+
+        let taken = Self::seats_taken(tx, settings.session_id).await?;
+        let free = (settings.capacity as u32).saturating_sub(taken);
+        if free == 0 {
+            return Ok(SeatChanges { expired, promoted: Vec::new() });
+        }
+
+        let status = promoted_status(settings.seat_price);
+        let hold_expires_at = (status == SeatStatus::Held).then(|| self.hold_expiry());
+
+        let query = r#"
+            UPDATE workshop_seats
+            SET status = $2, amount = $3, hold_expires_at = $4, updated_at = NOW()
+            WHERE seat_id IN (
+                SELECT seat_id FROM workshop_seats
+                WHERE session_id = $1 AND status = $5
+                ORDER BY created_at
+                LIMIT $6
+            )
+            RETURNING *
+        "#;
+
+        let promoted = sqlx::query_as::<_, SeatRow>(query)
+            .bind(settings.session_id)
+            .bind(status.as_str())
+            .bind(settings.seat_price)
+            .bind(hold_expires_at)
+            .bind(SeatStatus::Waitlisted.as_str())
+            .bind(free as i64)
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to promote from waitlist: {}", e)))?;
+
+        if status == SeatStatus::Confirmed {
+            for seat in &promoted {
+                Self::add_participant(tx, seat.session_id, seat.user_id).await?;
+            }
+        }
+
+        Ok(SeatChanges { expired, promoted })
+    }
+
+    async fn load_settings(
+        tx: &mut Transaction<'_, Postgres>,
+        session_id: Uuid,
+        for_update: bool,
+    ) -> Result<SettingsRow, AppError> {
+        let query = if for_update {
+            "SELECT session_id, capacity, seat_price, currency, waitlist_enabled FROM workshop_settings WHERE session_id = $1 FOR UPDATE"
+        } else {
+            "SELECT session_id, capacity, seat_price, currency, waitlist_enabled FROM workshop_settings WHERE session_id = $1"
+        };
+
+        sqlx::query_as::<_, SettingsRow>(query)
+            .bind(session_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch workshop: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Workshop not found".to_string()))
+    }
+
+    async fn summary(tx: &mut Transaction<'_, Postgres>, settings: &SettingsRow) -> Result<Workshop, AppError> {
+        let taken = Self::seats_taken(tx, settings.session_id).await?;
+
+        let waitlist_length: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM workshop_seats WHERE session_id = $1 AND status = 'waitlisted'"
+        )
+            .bind(settings.session_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count waitlist: {}", e)))?;
+
+        Ok(Workshop {
+            session_id: settings.session_id,
+            capacity: settings.capacity as u32,
+            seat_price: settings.seat_price,
+            currency: settings.currency.clone(),
+            waitlist_enabled: settings.waitlist_enabled,
+            seats_taken: taken,
+            seats_available: (settings.capacity as u32).saturating_sub(taken),
+            waitlist_length: waitlist_length as u32,
+        })
+    }
+
+    // Confirmed seats and holds that haven't lapsed yet
+    async fn seats_taken(tx: &mut Transaction<'_, Postgres>, session_id: Uuid) -> Result<u32, AppError> {
+        let query = r#"
+            SELECT COUNT(*) FROM workshop_seats
+            WHERE session_id = $1
+            AND (status = 'confirmed' OR (status = 'held' AND hold_expires_at > NOW()))
+        "#;
+
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(session_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count workshop seats: {}", e)))?;
+
+        Ok(count as u32)
+    }
+
+    async fn active_seat(
+        tx: &mut Transaction<'_, Postgres>,
+        session_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<SeatRow>, AppError> {
+        let query = r#"
+            SELECT * FROM workshop_seats
+            WHERE session_id = $1 AND user_id = $2 AND status IN ('held', 'confirmed', 'waitlisted')
+        "#;
+
+        sqlx::query_as::<_, SeatRow>(query)
+            .bind(session_id)
+            .bind(user_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch workshop seat: {}", e)))
+    }
+
+    async fn with_position(tx: &mut Transaction<'_, Postgres>, seat: SeatRow) -> Result<WorkshopSeat, AppError> {
+        if seat.status != SeatStatus::Waitlisted.as_str() {
+            return seat.into_seat(None);
+        }
+
+        let query = r#"
+            SELECT COUNT(*) FROM workshop_seats
+            WHERE session_id = $1 AND status = 'waitlisted' AND created_at <= $2
+        "#;
+
+        let position: i64 = sqlx::query_scalar(query)
+            .bind(seat.session_id)
+            .bind(seat.created_at)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch waitlist position: {}", e)))?;
+
+        seat.into_seat(Some(position as u32))
+    }
+
+    // Confirmed attendees take part in the session like any other participant
+    async fn add_participant(tx: &mut Transaction<'_, Postgres>, session_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let query = r#"
+            INSERT INTO session_participants (session_id, user_id, role, status)
+            VALUES ($1, $2, 'mentee', 'confirmed')
+            ON CONFLICT (session_id, user_id) DO UPDATE SET status = 'confirmed', responded_at = NOW()
+        "#;
+
+        sqlx::query(query)
+            .bind(session_id)
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to add workshop participant: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn remove_participant(tx: &mut Transaction<'_, Postgres>, session_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM session_participants WHERE session_id = $1 AND user_id = $2 AND role = 'mentee'")
+            .bind(session_id)
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to remove workshop participant: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn charge(
+        &self,
+        seat: &SeatRow,
+        settings: &SettingsRow,
+        title: &str,
+        request: &PaySeatRequest,
+        auth_header: Option<&str>,
+    ) -> Result<PaymentResult, AppError> {
+        let payload = serde_json::json!({
+            "amount": seat.amount,
+            "currency": settings.currency,
+            "payment_method_id": request.payment_method_id,
+            "description": format!("Workshop seat: {}", title),
+            "metadata": { "workshop_seat_id": seat.seat_id.to_string() },
+            "session_id": seat.session_id,
+            "subscription_id": null,
+        });
+
+        let payment: PaymentResult = self.post_payment_service("/payments", &payload, auth_header).await?;
+        if payment.status != "Succeeded" {
+            // Anything but an immediate success leaves the seat held until it lapses
+            return Err(AppError::Payment(format!("Payment was not completed ({})", payment.status)));
+        }
+
+        Ok(payment)
+    }
+
+    async fn refund(&self, payment_id: Uuid, reason: &str, auth_header: Option<&str>) -> Result<RefundResult, AppError> {
+        let payload = serde_json::json!({
+            "amount": null,
+            "reason": reason,
+            "metadata": null,
+        });
+
+        self.post_payment_service(&format!("/payments/{}/refund", payment_id), &payload, auth_header).await
+    }
+
+    async fn post_payment_service<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        payload: &serde_json::Value,
+        auth_header: Option<&str>,
+    ) -> Result<T, AppError> {
+        let mut request = self.http_client
+            .post(format!("{}{}", self.config.payment_service_url, path))
+            .json(payload);
+        if let Some(auth_header) = auth_header {
+            request = request.header("Authorization", auth_header);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Payment service unavailable: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Payment service returned {}",
+                response.status()
+            )));
+        }
+
+        let body: ApiResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Invalid payment response: {}", e)))?;
+
+        body.data
+            .ok_or_else(|| AppError::ExternalService("Payment service returned no data".to_string()))
+    }
+
+    async fn notify_changes(&self, title: &str, changes: SeatChanges) {
+        for seat in changes.expired {
+            self.notify(seat.user_id, seat.session_id, NotificationType::SessionCancellation, "Workshop seat released", format!(
+                "Your held seat for \"{}\" was released because it wasn't paid for in time.",
+                title
+            )).await;
+        }
+
+        for seat in changes.promoted {
+            let message = match seat.hold_expires_at {
+                Some(expires_at) => format!(
+                    "A seat opened up for \"{}\". It is held for you until {}; pay before then to keep it.",
+                    title,
+                    expires_at.format("%H:%M UTC")
+                ),
+                None => format!("A seat opened up for \"{}\" and it's yours.", title),
+            };
+            self.notify(seat.user_id, seat.session_id, NotificationType::SessionConfirmation, "Off the waitlist", message).await;
+        }
+    }
+
+    async fn notify(&self, user_id: Uuid, session_id: Uuid, notification_type: NotificationType, title: &str, message: String) {
+        let request = NotificationRequest {
+            recipient_id: user_id,
+            notification_type,
+            title: title.to_string(),
+            message,
+            session_id: Some(session_id),
+            scheduled_for: None,
+        };
+
+        if let Err(e) = self.notification_service.send_notification(request).await {
+            tracing::warn!("Failed to notify user {} about workshop {}: {}", user_id, session_id, e);
+        }
+    }
+
+    fn hold_expiry(&self) -> DateTime<Utc> {
+        Utc::now() + Duration::minutes(self.config.workshop_hold_minutes as i64)
+    }
+
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>, AppError> {
+        self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))
+    }
+
+    async fn commit(&self, tx: Transaction<'static, Postgres>) -> Result<(), AppError> {
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))
+    }
+}
+
+impl SeatRow {
+    fn into_seat(self, waitlist_position: Option<u32>) -> Result<WorkshopSeat, AppError> {
+        let status = self.status.parse()
+            .map_err(|_| AppError::Internal(format!("Unknown workshop seat status: {}", self.status)))?;
+
+        Ok(WorkshopSeat {
+            seat_id: self.seat_id,
+            session_id: self.session_id,
+            user_id: self.user_id,
+            status,
+            amount: self.amount,
+            hold_expires_at: self.hold_expires_at,
+            waitlist_position,
+            payment_id: self.payment_id,
+            refund_id: self.refund_id,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+/// The status of a new booking when `seats_taken` seats are already held or
+/// confirmed.
+fn booking_status(
+    capacity: u32,
+    seat_price: Decimal,
+    waitlist_enabled: bool,
+    seats_taken: u32,
+) -> Result<SeatStatus, AppError> {
+    if seats_taken < capacity {
+        Ok(promoted_status(seat_price))
+    } else if waitlist_enabled {
+        Ok(SeatStatus::Waitlisted)
+    } else {
+        Err(AppError::Conflict("This workshop is full".to_string()))
+    }
+}
+
+// Paid seats wait for payment; free ones are confirmed outright
+fn promoted_status(seat_price: Decimal) -> SeatStatus {
+    if seat_price > Decimal::ZERO {
+        SeatStatus::Held
+    } else {
+        SeatStatus::Confirmed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_booking_status() {
+        let price = Decimal::new(2500, 2);
+
+        assert_eq!(booking_status(10, price, true, 9).unwrap(), SeatStatus::Held);
+        assert_eq!(booking_status(10, Decimal::ZERO, true, 9).unwrap(), SeatStatus::Confirmed);
+        assert_eq!(booking_status(10, price, true, 10).unwrap(), SeatStatus::Waitlisted);
+        assert!(booking_status(10, Decimal::ZERO, false, 10).is_err());
+        // A capacity lowered below the seats taken never lets anyone else in
+        assert_eq!(booking_status(5, price, true, 8).unwrap(), SeatStatus::Waitlisted);
+    }
+}
@@ -12,6 +12,9 @@ pub struct NotificationConfig {
     pub sms: SmsConfig,
     pub push: PushConfig,
     pub templates: TemplateConfig,
+    /// Shared secret other services send as `X-Internal-Token` on internal
+    /// endpoints. Internal calls are refused while it is empty.
+    pub internal_service_token: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -235,6 +238,8 @@ impl NotificationConfig {
                     .map(|s| s.trim().to_string())
                     .collect(),
             },
+            internal_service_token: std::env::var("INTERNAL_SERVICE_TOKEN")
+                .unwrap_or_default(),
         })
     }
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
 use uuid::Uuid;
@@ -41,6 +41,39 @@ pub async fn send_notification(
     Ok(Json(ApiResponse::success(response)))
 }
 
+// Send a notification raised by another service without a user token,
+// e.g. a missed call detected by the video service's ring timeout
+pub async fn send_internal_notification(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<NotificationRequest>,
+) -> Result<Json<ApiResponse<NotificationResponse>>, AppError> {
+    let expected = &state.config.internal_service_token;
+    let provided = headers
+        .get("X-Internal-Token")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if expected.is_empty() || provided != expected {
+        return Err(AppError::Unauthorized("Invalid internal service token".to_string()));
+    }
+
+    state.delivery_manager.deliver_notification(&request).await?;
+
+    let now = chrono::Utc::now();
+
+    let response = NotificationResponse {
+        notification_id: Uuid::new_v4(),
+        recipient_id: request.recipient_id,
+        status: NotificationStatus::Sent,
+        channels: Vec::new(),
+        created_at: now,
+        scheduled_at: request.scheduled_at,
+        sent_at: Some(now),
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
 // Get notification preferences
 pub async fn get_preferences(
     State(state): State<AppState>,
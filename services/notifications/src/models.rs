@@ -48,6 +48,7 @@ pub enum NotificationType {
     PaymentReceived,
     PaymentFailed,
     MessageReceived,
+    MissedCall,
     MentorshipRequest,
     MentorshipAccepted,
     MentorshipRejected,
@@ -28,4 +28,7 @@ pub fn create_routes() -> Router<AppState> {
             (),
             auth_middleware,
         ))
+
+        // Service-to-service endpoints (authenticated with X-Internal-Token)
+        .route("/internal/notifications", post(handlers::send_internal_notification))
 }
\ No newline at end of file
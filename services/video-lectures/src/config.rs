@@ -11,6 +11,9 @@ pub struct VideoLecturesConfig {
     pub processing: ProcessingConfig,
     pub streaming: StreamingConfig,
     pub storage: StorageConfig,
+    /// Shared secret other services send as `X-Internal-Token` on internal
+    /// endpoints. Internal calls are refused while it is empty.
+    pub internal_service_token: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -216,6 +219,8 @@ impl VideoLecturesConfig {
                 local_storage_path: std::env::var("VIDEO_LOCAL_STORAGE")
                     .unwrap_or_else(|_| "/app/videos".to_string()),
             },
+            internal_service_token: std::env::var("INTERNAL_SERVICE_TOKEN")
+                .unwrap_or_default(),
         })
     }
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
 use serde::Deserialize;
@@ -12,6 +12,7 @@ use linkwithmentor_common::{ApiResponse, AppError};
 use crate::{
     models::{
         CreateLectureRequest, UpdateLectureRequest, VideoUploadRequest, VideoUploadResponse,
+        ImportRecordingRequest,
         VideoLecture, VideoSearchRequest, VideoSearchResponse, StreamingManifest, StreamingFormat,
         VideoAnalytics, WatchProgress, CreateCommentRequest, VideoComment,
     },
@@ -37,7 +38,7 @@ pub async fn create_lecture(
 
     sqlx::query(query)
         .bind(lecture_id)
-        .bind(claims.user_id)
+        .bind(request.owner_id)
         .bind(&request.title)
         .bind(&request.description)
         .bind(&request.category)
@@ -74,6 +75,86 @@ pub async fn create_lecture(
     Ok(Json(ApiResponse::success(lecture)))
 }
 
+// Import a call recording as a private lecture and queue it for processing
+// (called by the video service once a recording is stored)
+pub async fn import_recording(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ImportRecordingRequest>,
+) -> Result<Json<ApiResponse<VideoLecture>>, AppError> {
+    let expected = &state.config.internal_service_token;
+    let provided = headers
+        .get("X-Internal-Token")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if expected.is_empty() || provided != expected {
+        return Err(AppError::Unauthorized("Invalid internal service token".to_string()));
+    }
+
+    if !request.storage_key.starts_with("recordings/") || request.storage_key.contains("..") {
+        return Err(AppError::BadRequest("Invalid recording storage key".to_string()));
+    }
+
+    let lecture_id = Uuid::new_v4();
+    let now = chrono::Utc::now();
+    let description = format!("Recording of call {}", request.call_id);
+    let tags = vec!["call-recording".to_string()];
+
+    let query = r#"
+        INSERT INTO video_lectures (
+            lecture_id, mentor_id, title, description, category, tags,
+            visibility, status, duration_seconds, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+    "#;
+
+    sqlx::query(query)
+        .bind(lecture_id)
+        .bind(claims.user_id)
+        .bind(&request.title)
+        .bind(&description)
+        .bind("Session Recording")
+        .bind(&tags)
+        .bind(&crate::models::VideoVisibility::Private)
+        .bind(&crate::models::VideoStatus::Processing)
+        .bind(request.duration_seconds as i32)
+        .bind(now)
+        .bind(now)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create lecture for recording: {}", e)))?;
+
+    state.processing_service
+        .queue_processing_job(lecture_id, request.storage_key.clone())
+        .await?;
+
+    tracing::info!(
+        "Imported recording {} ({} bytes of {}) as lecture {}",
+        request.recording_id, request.file_size, request.format, lecture_id
+    );
+
+    let lecture = VideoLecture {
+        lecture_id,
+        mentor_id: request.owner_id,
+        title: request.title,
+        description: Some(description),
+        category: "Session Recording".to_string(),
+        tags,
+        duration_seconds: Some(request.duration_seconds),
+        thumbnail_url: None,
+        video_urls: std::collections::HashMap::new(),
+        status: crate::models::VideoStatus::Processing,
+        visibility: crate::models::VideoVisibility::Private,
+        price: None,
+        view_count: 0,
+        like_count: 0,
+        created_at: now,
+        updated_at: now,
+        published_at: None,
+    };
+
+    Ok(Json(ApiResponse::success(lecture)))
+}
+
 pub async fn get_lecture(
     State(state): State<AppState>,
     claims: Claims,
@@ -45,6 +45,20 @@ pub struct UpdateLectureRequest {
     pub price: Option<rust_decimal::Decimal>,
 }
 
+/// A finished call recording from the video service, already in storage
+/// under `storage_key`. It becomes a private lecture owned by `owner_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRecordingRequest {
+    pub recording_id: Uuid,
+    pub call_id: Uuid,
+    pub owner_id: Uuid,
+    pub title: String,
+    pub storage_key: String,
+    pub format: String,
+    pub duration_seconds: u32,
+    pub file_size: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoUploadRequest {
     pub lecture_id: Uuid,
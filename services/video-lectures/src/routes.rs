@@ -58,4 +58,7 @@ pub fn create_routes() -> Router<AppState> {
             (), // We'll pass the JWT service through the app state
             auth_middleware,
         ))
+
+        // Service-to-service endpoints (authenticated with X-Internal-Token)
+        .route("/internal/lectures/recordings", post(handlers::import_recording))
 }
\ No newline at end of file
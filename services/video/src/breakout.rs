@@ -0,0 +1,524 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use dashmap::{mapref::entry::Entry, DashMap};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    call_manager::CallManager,
+    models::{
+        ActiveCall, BreakoutAssignment, BreakoutRoomResponse, BreakoutSessionResponse,
+        CallState, CallTopology, CreateBreakoutRoomsRequest, SignalingMessage,
+    },
+    signaling::SignalingService,
+};
+
+/// Splits a group call into breakout rooms. Each room is its own SFU call
+/// linked to the main call; participants stay in the main call while they
+/// are away and move between calls over their existing signaling connection.
+#[derive(Clone)]
+pub struct BreakoutService {
+    db_pool: PgPool,
+    call_manager: CallManager,
+    signaling_service: SignalingService,
+    // Open breakout rooms keyed by main call
+    sessions: Arc<DashMap<Uuid, BreakoutSession>>,
+}
+
+#[derive(Debug, Clone)]
+struct BreakoutSession {
+    // Distinguishes this set of rooms from later ones for the close timer
+    session_id: Uuid,
+    rooms: Vec<BreakoutRoom>,
+    ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+struct BreakoutRoom {
+    call_id: Uuid,
+    name: String,
+    participant_ids: Vec<Uuid>,
+}
+
+impl BreakoutService {
+    pub fn new(db_pool: PgPool, call_manager: CallManager, signaling_service: SignalingService) -> Self {
+        Self {
+            db_pool,
+            call_manager,
+            signaling_service,
+            sessions: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Opens `room_count` breakout rooms and sends everyone assigned to one
+    /// into it. The host stays in the main room and can visit any room.
+    pub async fn create_rooms(
+        &self,
+        call_id: Uuid,
+        host_id: Uuid,
+        request: CreateBreakoutRoomsRequest,
+    ) -> Result<BreakoutSessionResponse, AppError> {
+        let call = self.get_call_as_host(call_id, host_id).await?;
+
+        if call.topology != CallTopology::Sfu || call.parent_call_id.is_some() {
+            return Err(AppError::BadRequest("Breakout rooms need a group call".to_string()));
+        }
+
+        let members: Vec<Uuid> = call.participants.values()
+            .filter(|participant| participant.left_at.is_none() && participant.user_id != host_id)
+            .map(|participant| participant.user_id)
+            .collect();
+
+        if request.room_count == 0 || request.room_count > members.len().max(1) {
+            return Err(AppError::BadRequest(format!(
+                "Choose between 1 and {} breakout rooms",
+                members.len().max(1)
+            )));
+        }
+        if let Some(duration) = request.duration_minutes {
+            if !(1..=240).contains(&duration) {
+                return Err(AppError::BadRequest("Breakout duration must be between 1 and 240 minutes".to_string()));
+            }
+        }
+
+        let assignments = assign(&members, &request)?;
+        let ends_at = request.duration_minutes.map(|minutes| Utc::now() + chrono::Duration::minutes(minutes));
+
+        // Hold the call's place before opening anything, so two requests
+        // can't both open rooms
+        let session_id = Uuid::new_v4();
+        match self.sessions.entry(call_id) {
+            Entry::Occupied(_) => {
+                return Err(AppError::Conflict("Breakout rooms are already open".to_string()));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(BreakoutSession { session_id, rooms: Vec::new(), ends_at });
+            }
+        }
+
+        let mut rooms = Vec::with_capacity(request.room_count);
+        let opened = self.open_rooms(&call, assignments, ends_at, &mut rooms).await;
+        let session = BreakoutSession { session_id, rooms, ends_at };
+
+        // The host may have closed the rooms while they were opening
+        let kept = opened.is_ok() && match self.sessions.get_mut(&call_id) {
+            Some(mut current) if current.session_id == session_id => {
+                *current = session.clone();
+                true
+            }
+            _ => false,
+        };
+
+        if !kept {
+            // Close whatever did open and bring everyone back
+            self.sessions.remove_if(&call_id, |_, current| current.session_id == session_id);
+            if let Err(e) = self.end_session(call_id, session).await {
+                tracing::warn!("Failed to close breakout rooms for call {}: {}", call_id, e);
+            }
+            return Err(opened.err().unwrap_or_else(|| {
+                AppError::Conflict("Breakout rooms were closed while opening".to_string())
+            }));
+        }
+
+        let response = session_response(call_id, &session);
+        if let Some(ends_at) = ends_at {
+            self.start_close_timer(call_id, session_id, ends_at);
+        }
+
+        tracing::info!("Opened {} breakout rooms for call {}", response.rooms.len(), call_id);
+        Ok(response)
+    }
+
+    /// Moves a participant to another breakout room, or back to the main
+    /// room when `room_index` is None.
+    pub async fn move_participant(
+        &self,
+        call_id: Uuid,
+        host_id: Uuid,
+        user_id: Uuid,
+        room_index: Option<usize>,
+    ) -> Result<BreakoutSessionResponse, AppError> {
+        let call = self.get_call_as_host(call_id, host_id).await?;
+
+        let is_present = call.participants.get(&user_id)
+            .map_or(false, |participant| participant.left_at.is_none());
+        if !is_present {
+            return Err(AppError::NotFound("Participant not found in call".to_string()));
+        }
+
+        // A session with no rooms yet is still opening
+        let mut session = self.sessions.get(&call_id)
+            .filter(|session| !session.rooms.is_empty())
+            .map(|session| session.clone())
+            .ok_or_else(|| AppError::NotFound("No breakout rooms are open".to_string()))?;
+
+        if let Some(index) = room_index {
+            if index >= session.rooms.len() {
+                return Err(AppError::BadRequest("Breakout room not found".to_string()));
+            }
+        }
+
+        // Take them out of their current room
+        let current = session.rooms.iter().position(|room| room.participant_ids.contains(&user_id));
+        if current == room_index {
+            return Ok(session_response(call_id, &session));
+        }
+        if let Some(current) = current {
+            let room = &mut session.rooms[current];
+            room.participant_ids.retain(|id| *id != user_id);
+            self.leave_room(room.call_id, user_id).await?;
+        }
+
+        match room_index {
+            Some(index) => {
+                let room = &mut session.rooms[index];
+                self.call_manager.invite_participant(room.call_id, user_id).await?;
+                self.send_to_room(&call, room.call_id, &room.name, session.ends_at, user_id).await?;
+                room.participant_ids.push(user_id);
+            }
+            None => {
+                self.signaling_service
+                    .send_to_user(user_id, SignalingMessage::BreakoutClosed { call_id })
+                    .await?;
+            }
+        }
+
+        let response = session_response(call_id, &session);
+
+        // Unless the rooms were closed or reopened in the meantime
+        if let Some(mut current) = self.sessions.get_mut(&call_id) {
+            if current.session_id == session.session_id {
+                *current = session;
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Sends a message from the host to the main room and every breakout room.
+    pub async fn broadcast(&self, call_id: Uuid, host_id: Uuid, message: String) -> Result<(), AppError> {
+        let call = self.get_call_as_host(call_id, host_id).await?;
+
+        if message.trim().is_empty() || message.len() > 1000 {
+            return Err(AppError::BadRequest("Message must be between 1 and 1000 characters".to_string()));
+        }
+        if !self.sessions.contains_key(&call_id) {
+            return Err(AppError::NotFound("No breakout rooms are open".to_string()));
+        }
+
+        let broadcast_message = SignalingMessage::BreakoutBroadcast {
+            call_id,
+            sender_id: host_id,
+            message,
+        };
+        for participant in call.participants.values().filter(|participant| participant.left_at.is_none()) {
+            self.signaling_service
+                .send_to_user(participant.user_id, broadcast_message.clone())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes every breakout room and brings everyone back to the main room.
+    pub async fn close_rooms(&self, call_id: Uuid, host_id: Uuid) -> Result<(), AppError> {
+        self.get_call_as_host(call_id, host_id).await?;
+
+        if !self.sessions.contains_key(&call_id) {
+            return Err(AppError::NotFound("No breakout rooms are open".to_string()));
+        }
+
+        self.close_session(call_id).await
+    }
+
+    pub async fn get_rooms(&self, call_id: Uuid, user_id: Uuid) -> Result<BreakoutSessionResponse, AppError> {
+        let call = self.call_manager.get_call(call_id).await
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if !call.participants.contains_key(&user_id) {
+            return Err(AppError::Forbidden("Not a participant in this call".to_string()));
+        }
+
+        let session = self.sessions.get(&call_id)
+            .ok_or_else(|| AppError::NotFound("No breakout rooms are open".to_string()))?;
+
+        Ok(session_response(call_id, &session))
+    }
+
+    // Helper methods
+
+    // Opens a room per assignment and sends its participants there. Rooms
+    // are added to `rooms` as they open, so the caller can close them if a
+    // later one fails.
+    async fn open_rooms(
+        &self,
+        call: &ActiveCall,
+        assignments: Vec<Vec<Uuid>>,
+        ends_at: Option<DateTime<Utc>>,
+        rooms: &mut Vec<BreakoutRoom>,
+    ) -> Result<(), AppError> {
+        for (index, participant_ids) in assignments.into_iter().enumerate() {
+            let name = format!("Room {}", index + 1);
+            let breakout_call_id = self.open_room(call, &participant_ids).await?;
+
+            rooms.push(BreakoutRoom {
+                call_id: breakout_call_id,
+                name: name.clone(),
+                participant_ids: participant_ids.clone(),
+            });
+
+            for user_id in &participant_ids {
+                self.send_to_room(call, breakout_call_id, &name, ends_at, *user_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn open_room(&self, call: &ActiveCall, participant_ids: &[Uuid]) -> Result<Uuid, AppError> {
+        let callee_id = participant_ids.first().copied().unwrap_or(call.host_id);
+        let breakout_call_id = self.call_manager
+            .create_call(
+                call.host_id,
+                callee_id,
+                call.session_id,
+                call.call_type.clone(),
+                CallTopology::Sfu,
+                participant_ids,
+            )
+            .await?;
+
+        self.call_manager.set_parent_call(breakout_call_id, call.call_id).await?;
+        self.call_manager.update_call_state(breakout_call_id, CallState::Ringing).await?;
+
+        Ok(breakout_call_id)
+    }
+
+    // Points a participant at their room; their client offers to it next
+    async fn send_to_room(
+        &self,
+        call: &ActiveCall,
+        breakout_call_id: Uuid,
+        name: &str,
+        ends_at: Option<DateTime<Utc>>,
+        user_id: Uuid,
+    ) -> Result<(), AppError> {
+        self.signaling_service.leave_media(call.call_id, user_id).await;
+        self.record_assignment(call.call_id, breakout_call_id, user_id, name).await?;
+
+        let message = SignalingMessage::BreakoutAssigned {
+            call_id: call.call_id,
+            breakout_call_id,
+            name: name.to_string(),
+            ends_at,
+        };
+        self.signaling_service.send_to_user(user_id, message).await
+    }
+
+    async fn leave_room(&self, breakout_call_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.signaling_service.leave_media(breakout_call_id, user_id).await;
+        if let Some(call) = self.call_manager.get_call(breakout_call_id).await {
+            if call.participants.contains_key(&user_id) {
+                self.call_manager.remove_participant(breakout_call_id, user_id).await?;
+            }
+        }
+
+        let query = r#"
+            UPDATE call_breakout_assignments SET left_at = NOW()
+            WHERE breakout_call_id = $1 AND user_id = $2 AND left_at IS NULL
+        "#;
+        sqlx::query(query)
+            .bind(breakout_call_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update breakout assignment: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn close_session(&self, call_id: Uuid) -> Result<(), AppError> {
+        let session = match self.sessions.remove(&call_id) {
+            Some((_, session)) => session,
+            None => return Ok(()),
+        };
+
+        self.end_session(call_id, session).await
+    }
+
+    // Closes a session's rooms once it is no longer in `sessions`
+    async fn end_session(&self, call_id: Uuid, session: BreakoutSession) -> Result<(), AppError> {
+        for room in &session.rooms {
+            // Rooms end on their own once everyone in them hangs up
+            if self.call_manager.get_call(room.call_id).await.is_some() {
+                self.signaling_service.close_call(room.call_id).await?;
+            }
+        }
+
+        let query = r#"
+            UPDATE call_breakout_assignments SET left_at = NOW()
+            WHERE main_call_id = $1 AND left_at IS NULL
+        "#;
+        sqlx::query(query)
+            .bind(call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to close breakout assignments: {}", e)))?;
+
+        for participant_id in self.call_manager.get_active_participants(call_id).await {
+            self.signaling_service
+                .send_to_user(participant_id, SignalingMessage::BreakoutClosed { call_id })
+                .await?;
+        }
+
+        tracing::info!("Closed breakout rooms for call {}", call_id);
+        Ok(())
+    }
+
+    fn start_close_timer(&self, call_id: Uuid, session_id: Uuid, ends_at: DateTime<Utc>) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let delay = (ends_at - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(delay).await;
+
+            // The host may have closed these rooms and opened new ones since
+            let is_current = service.sessions.get(&call_id)
+                .map_or(false, |session| session.session_id == session_id);
+            if is_current {
+                if let Err(e) = service.close_session(call_id).await {
+                    tracing::warn!("Failed to close breakout rooms for call {}: {}", call_id, e);
+                }
+            }
+        });
+    }
+
+    async fn record_assignment(
+        &self,
+        call_id: Uuid,
+        breakout_call_id: Uuid,
+        user_id: Uuid,
+        room_name: &str,
+    ) -> Result<(), AppError> {
+        let query = r#"
+            INSERT INTO call_breakout_assignments (
+                main_call_id, breakout_call_id, user_id, room_name
+            ) VALUES ($1, $2, $3, $4)
+        "#;
+
+        sqlx::query(query)
+            .bind(call_id)
+            .bind(breakout_call_id)
+            .bind(user_id)
+            .bind(room_name)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record breakout assignment: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get_call_as_host(&self, call_id: Uuid, user_id: Uuid) -> Result<ActiveCall, AppError> {
+        let call = self.call_manager.get_call(call_id).await
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if call.host_id != user_id {
+            return Err(AppError::Forbidden("Only the host can manage breakout rooms".to_string()));
+        }
+
+        Ok(call)
+    }
+}
+
+// Splits the main room's participants into the requested rooms
+fn assign(members: &[Uuid], request: &CreateBreakoutRoomsRequest) -> Result<Vec<Vec<Uuid>>, AppError> {
+    let mut rooms = vec![Vec::new(); request.room_count];
+
+    match request.assignment {
+        BreakoutAssignment::Random => {
+            let mut shuffled = members.to_vec();
+            shuffled.sort_by_cached_key(|_| Uuid::new_v4());
+            for (index, user_id) in shuffled.into_iter().enumerate() {
+                rooms[index % request.room_count].push(user_id);
+            }
+        }
+        BreakoutAssignment::Manual => {
+            for (user_id, room_index) in &request.assignments {
+                if !members.contains(user_id) {
+                    return Err(AppError::BadRequest(format!("{} is not in the main room", user_id)));
+                }
+                let room = rooms.get_mut(*room_index)
+                    .ok_or_else(|| AppError::BadRequest(format!("Breakout room {} does not exist", room_index)))?;
+                room.push(*user_id);
+            }
+        }
+    }
+
+    Ok(rooms)
+}
+
+fn session_response(call_id: Uuid, session: &BreakoutSession) -> BreakoutSessionResponse {
+    BreakoutSessionResponse {
+        call_id,
+        rooms: session.rooms.iter()
+            .map(|room| BreakoutRoomResponse {
+                breakout_call_id: room.call_id,
+                name: room.name.clone(),
+                participant_ids: room.participant_ids.clone(),
+            })
+            .collect(),
+        ends_at: session.ends_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn request(room_count: usize, assignment: BreakoutAssignment, assignments: HashMap<Uuid, usize>) -> CreateBreakoutRoomsRequest {
+        CreateBreakoutRoomsRequest {
+            room_count,
+            assignment,
+            assignments,
+            duration_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_random_assignment_balances_rooms() {
+        let members: Vec<Uuid> = (0..7).map(|_| Uuid::new_v4()).collect();
+
+        let rooms = assign(&members, &request(3, BreakoutAssignment::Random, HashMap::new())).unwrap();
+
+        let mut sizes: Vec<usize> = rooms.iter().map(|room| room.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![2, 2, 3]);
+
+        let mut assigned: Vec<Uuid> = rooms.into_iter().flatten().collect();
+        let mut expected = members.clone();
+        assigned.sort();
+        expected.sort();
+        assert_eq!(assigned, expected);
+    }
+
+    #[test]
+    fn test_manual_assignment() {
+        let members: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let assignments = HashMap::from([(members[0], 1), (members[1], 1)]);
+
+        let rooms = assign(&members, &request(2, BreakoutAssignment::Manual, assignments)).unwrap();
+
+        assert!(rooms[0].is_empty());
+        assert_eq!(rooms[1].len(), 2);
+        assert!(!rooms[1].contains(&members[2]));
+
+        let out_of_range = HashMap::from([(members[0], 2)]);
+        assert!(assign(&members, &request(2, BreakoutAssignment::Manual, out_of_range)).is_err());
+
+        let stranger = HashMap::from([(Uuid::new_v4(), 0)]);
+        assert!(assign(&members, &request(2, BreakoutAssignment::Manual, stranger)).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -8,10 +8,26 @@ use sqlx::PgPool;
 
 use linkwithmentor_common::{AppError, RedisService};
 use crate::models::{
-    ActiveCall, CallSession, CallParticipant, CallState, CallType, 
+    ActiveCall, CallSession, CallParticipant, CallState, CallType, CallTopology,
     MediaState, ParticipantConnectionState, CallQualityMetrics,
-    CallConnection, CallError,
+    CallConnection, CallError, WaitingParticipant, CallQualityFlag,
+    CallDirection, CallLogEntry, CallLogQuery, CallLogResponse,
+    RaisedHand, EngagementSummary, ParticipantEngagement,
 };
+use crate::quality::{QualityAssessment, QualityEngine};
+
+// Reactions participants can send
+const ALLOWED_REACTIONS: [&str; 8] = ["👍", "👏", "❤️", "😂", "😮", "🎉", "🤔", "🙌"];
+// Reactions sent faster than this are dropped
+const REACTION_COOLDOWN_MS: i64 = 1000;
+
+/// Whether a participant may join a call now
+#[derive(Debug, Clone, PartialEq)]
+pub enum Admission {
+    Admitted,
+    /// Held in the waiting room until the host lets them in
+    Waiting,
+}
 
 #[derive(Clone)]
 pub struct CallManager {
@@ -23,16 +39,55 @@ pub struct CallManager {
     connections: Arc<DashMap<Uuid, Vec<CallConnection>>>,
     // Call participants mapping
     call_participants: Arc<DashMap<Uuid, Vec<Uuid>>>,
+    // How long a dropped participant is kept in the call
+    reconnect_grace: chrono::Duration,
+    // How long a call rings before it is missed
+    ring_timeout: chrono::Duration,
+    quality: QualityEngine,
+}
+
+// Engagement events grouped by participant and kind
+#[derive(sqlx::FromRow)]
+struct EngagementRow {
+    user_id: Option<Uuid>,
+    event_type: String,
+    emoji: Option<String>,
+    answered: bool,
+    count: i64,
+}
+
+// A call as stored, for the call log
+#[derive(sqlx::FromRow)]
+struct CallLogRow {
+    call_id: Uuid,
+    caller_id: Uuid,
+    callee_id: Uuid,
+    session_id: Option<Uuid>,
+    call_type: CallType,
+    state: CallState,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    duration_seconds: Option<i32>,
+    callback_call_id: Option<Uuid>,
 }
 
 impl CallManager {
-    pub fn new(db_pool: PgPool, redis_service: RedisService) -> Self {
+    pub fn new(
+        db_pool: PgPool,
+        redis_service: RedisService,
+        reconnect_grace_seconds: i64,
+        ring_timeout_seconds: i64,
+        quality_window_seconds: i64,
+    ) -> Self {
         Self {
             db_pool,
             redis_service,
             active_calls: Arc::new(DashMap::new()),
             connections: Arc::new(DashMap::new()),
             call_participants: Arc::new(DashMap::new()),
+            reconnect_grace: chrono::Duration::seconds(reconnect_grace_seconds),
+            ring_timeout: chrono::Duration::seconds(ring_timeout_seconds),
+            quality: QualityEngine::new(quality_window_seconds),
         }
     }
 
@@ -43,6 +98,8 @@ impl CallManager {
         callee_id: Uuid,
         session_id: Option<Uuid>,
         call_type: CallType,
+        topology: CallTopology,
+        invitee_ids: &[Uuid],
     ) -> Result<Uuid, AppError> {
         let call_id = Uuid::new_v4();
         let now = Utc::now();
@@ -50,9 +107,9 @@ impl CallManager {
         // Create call session in database
         let query = r#"
             INSERT INTO call_sessions (
-                call_id, caller_id, callee_id, session_id, call_type, 
+                call_id, caller_id, callee_id, session_id, call_type, topology,
                 state, started_at, created_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         "#;
 
         sqlx::query(query)
@@ -61,6 +118,7 @@ impl CallManager {
             .bind(callee_id)
             .bind(session_id)
             .bind(&call_type)
+            .bind(&topology)
             .bind(&CallState::Initiating)
             .bind(now)
             .bind(now)
@@ -75,16 +133,36 @@ impl CallManager {
             callee_id,
             session_id,
             call_type,
+            topology,
             state: CallState::Initiating,
             participants: HashMap::new(),
             started_at: now,
+            answered_at: None,
             last_activity: now,
             recording_active: false,
             screen_sharing_participant: None,
+            billing_paused_at: None,
+            billing_paused_seconds: 0,
+            parent_call_id: None,
+            host_id: caller_id,
+            locked: false,
+            waiting_room_enabled: false,
+            waiting_room: Vec::new(),
+            admitted: HashSet::new(),
+            removed: HashSet::new(),
+            raised_hands: Vec::new(),
+            last_reactions: HashMap::new(),
         };
 
         self.active_calls.insert(call_id, active_call);
-        self.call_participants.insert(call_id, vec![caller_id, callee_id]);
+        // Everyone invited to the call; `participants` holds those who joined
+        let mut invited = vec![caller_id, callee_id];
+        for invitee_id in invitee_ids {
+            if !invited.contains(invitee_id) {
+                invited.push(*invitee_id);
+            }
+        }
+        self.call_participants.insert(call_id, invited);
 
         // Cache call info in Redis
         self.cache_call_info(call_id).await?;
@@ -102,6 +180,9 @@ impl CallManager {
         if let Some(mut call) = self.active_calls.get_mut(&call_id) {
             call.state = new_state.clone();
             call.last_activity = Utc::now();
+            if matches!(new_state, CallState::Connecting | CallState::Connected) && call.answered_at.is_none() {
+                call.answered_at = Some(call.last_activity);
+            }
         } else {
             return Err(AppError::NotFound("Call not found".to_string()));
         }
@@ -124,28 +205,41 @@ impl CallManager {
 
     pub async fn end_call(&self, call_id: Uuid) -> Result<(), AppError> {
         let now = Utc::now();
-        let duration = if let Some(call) = self.active_calls.get(&call_id) {
-            (now - call.started_at).num_seconds() as i32
+        let (state, duration, billable, session_id) = if let Some(call) = self.active_calls.get(&call_id) {
+            // Calls nobody answered keep the state that ended them and have no duration
+            let state = match call.state {
+                CallState::Rejected | CallState::Cancelled | CallState::Failed | CallState::Missed => call.state.clone(),
+                _ => CallState::Ended,
+            };
+            let duration = if call.answered_at.is_some() {
+                (now - call.started_at).num_seconds() as i32
+            } else {
+                0
+            };
+            (state, duration, call.billable_seconds(now) as i32, call.session_id)
         } else {
-            0
+            (CallState::Ended, 0, 0, None)
         };
 
         // Update database
         let query = r#"
             UPDATE call_sessions 
-            SET state = $1, ended_at = $2, duration_seconds = $3 
-            WHERE call_id = $4
+            SET state = $1, ended_at = $2, duration_seconds = $3, billable_seconds = $4
+            WHERE call_id = $5
         "#;
 
         sqlx::query(query)
-            .bind(&CallState::Ended)
+            .bind(&state)
             .bind(now)
             .bind(duration)
+            .bind(billable)
             .bind(call_id)
             .execute(&self.db_pool)
             .await
             .map_err(|e| AppError::Database(format!("Failed to end call: {}", e)))?;
 
+        self.finish_call_quality(call_id, session_id).await?;
+
         // Remove from active calls
         self.active_calls.remove(&call_id);
         self.call_participants.remove(&call_id);
@@ -172,6 +266,12 @@ impl CallManager {
         let mut call = self.active_calls.get_mut(&call_id)
             .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
 
+        // Checked under the same entry lock as `mark_missed`, so a call can't
+        // be both answered and missed
+        if matches!(call.state, CallState::Missed | CallState::Rejected | CallState::Cancelled | CallState::Ended) {
+            return Err(AppError::Conflict("Call has already ended".to_string()));
+        }
+
         if call.participants.len() >= 10 { // Max participants limit
             return Err(AppError::BadRequest("Maximum participants reached".to_string()));
         }
@@ -190,6 +290,7 @@ impl CallManager {
                 video_muted: false,
             },
             connection_state: ParticipantConnectionState::Connecting,
+            reconnecting_since: None,
         };
 
         call.participants.insert(user_id, participant);
@@ -240,19 +341,35 @@ impl CallManager {
             if let Some(mut participant) = call.participants.get_mut(&user_id) {
                 participant.left_at = Some(now);
                 participant.connection_state = ParticipantConnectionState::Disconnected;
+                participant.reconnecting_since = None;
             }
             call.last_activity = now;
+            resume_billing_if_connected(&mut *call, now);
 
             // If screen sharing, stop it
             if call.screen_sharing_participant == Some(user_id) {
                 call.screen_sharing_participant = None;
             }
+            call.raised_hands.retain(|hand| hand.user_id != user_id);
+            call.last_reactions.remove(&user_id);
+
+            // A departing host hands over to whoever has been in the call longest
+            if call.host_id == user_id {
+                let next_host = call.participants.values()
+                    .filter(|participant| participant.left_at.is_none())
+                    .min_by_key(|participant| participant.joined_at)
+                    .map(|participant| participant.user_id);
+                if let Some(next_host) = next_host {
+                    call.host_id = next_host;
+                }
+            }
         }
 
         // Update participants list
         if let Some(mut participants) = self.call_participants.get_mut(&call_id) {
             participants.retain(|&id| id != user_id);
         }
+        self.quality.remove_participant(call_id, user_id);
 
         // Update database
         let query = "UPDATE call_participants SET left_at = $1 WHERE call_id = $2 AND user_id = $3";
@@ -274,18 +391,31 @@ impl CallManager {
         call_id: Uuid,
         user_id: Uuid,
         media_state: MediaState,
-    ) -> Result<(), AppError> {
-        // Update in memory
-        if let Some(mut call) = self.active_calls.get_mut(&call_id) {
-            if let Some(participant) = call.participants.get_mut(&user_id) {
+    ) -> Result<MediaState, AppError> {
+        // Update in memory. A host mute lasts until the participant turns
+        // their audio back on; video the host stopped can't be turned back on
+        let media_state = if let Some(mut call) = self.active_calls.get_mut(&call_id) {
+            let media_state = if let Some(participant) = call.participants.get_mut(&user_id) {
+                let current = &participant.media_state;
+                if current.video_muted && media_state.video_enabled {
+                    return Err(AppError::Forbidden("The host has stopped your video".to_string()));
+                }
+
+                let media_state = MediaState {
+                    audio_muted: current.audio_muted && !media_state.audio_enabled,
+                    video_muted: current.video_muted,
+                    ..media_state
+                };
                 participant.media_state = media_state.clone();
+                media_state
             } else {
                 return Err(AppError::NotFound("Participant not found in call".to_string()));
-            }
+            };
             call.last_activity = Utc::now();
+            media_state
         } else {
             return Err(AppError::NotFound("Call not found".to_string()));
-        }
+        };
 
         // Update database
         let media_state_json = serde_json::to_value(&media_state)
@@ -301,7 +431,7 @@ impl CallManager {
             .map_err(|e| AppError::Database(format!("Failed to update media state: {}", e)))?;
 
         tracing::debug!("Updated media state for participant {} in call {}", user_id, call_id);
-        Ok(())
+        Ok(media_state)
     }
 
     // Screen sharing management
@@ -352,6 +482,744 @@ impl CallManager {
         Ok(())
     }
 
+    // Recording management
+    pub async fn set_recording_active(&self, call_id: Uuid, active: bool) {
+        if let Some(mut call) = self.active_calls.get_mut(&call_id) {
+            call.recording_active = active;
+            call.last_activity = Utc::now();
+        }
+    }
+
+    // Reconnection
+
+    /// Holds a participant whose connection dropped in the call and pauses
+    /// billing. Returns the reconnect deadline, or None if the participant
+    /// isn't in the call or is already reconnecting.
+    pub async fn mark_reconnecting(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, AppError> {
+        let now = Utc::now();
+
+        {
+            let mut call = match self.active_calls.get_mut(&call_id) {
+                Some(call) => call,
+                None => return Ok(None),
+            };
+
+            match call.participants.get_mut(&user_id) {
+                Some(participant) if participant.left_at.is_none()
+                    && participant.connection_state != ParticipantConnectionState::Reconnecting => {
+                    participant.connection_state = ParticipantConnectionState::Reconnecting;
+                    participant.reconnecting_since = Some(now);
+                }
+                _ => return Ok(None),
+            }
+
+            if call.billing_paused_at.is_none() {
+                call.billing_paused_at = Some(now);
+            }
+            call.last_activity = now;
+        }
+
+        self.record_call_event(call_id, user_id, "participant_reconnecting", None).await?;
+
+        tracing::info!("Participant {} is reconnecting to call {}", user_id, call_id);
+        Ok(Some(now + self.reconnect_grace))
+    }
+
+    /// Marks a participant connected again, resuming billing once nobody is
+    /// reconnecting. Returns true if the participant was reconnecting.
+    pub async fn mark_reconnected(&self, call_id: Uuid, user_id: Uuid) -> Result<bool, AppError> {
+        let now = Utc::now();
+
+        {
+            let mut call = match self.active_calls.get_mut(&call_id) {
+                Some(call) => call,
+                None => return Ok(false),
+            };
+
+            match call.participants.get_mut(&user_id) {
+                Some(participant) if participant.connection_state == ParticipantConnectionState::Reconnecting => {
+                    participant.connection_state = ParticipantConnectionState::Connected;
+                    participant.reconnecting_since = None;
+                }
+                _ => return Ok(false),
+            }
+
+            call.last_activity = now;
+            resume_billing_if_connected(&mut *call, now);
+        }
+
+        self.record_call_event(call_id, user_id, "participant_reconnected", None).await?;
+
+        tracing::info!("Participant {} reconnected to call {}", user_id, call_id);
+        Ok(true)
+    }
+
+    /// Participants whose reconnect grace period is over, as (call, user) pairs
+    pub async fn expired_reconnections(&self) -> Vec<(Uuid, Uuid)> {
+        let cutoff = Utc::now() - self.reconnect_grace;
+
+        self.active_calls.iter()
+            .flat_map(|call| {
+                call.participants.values()
+                    .filter(|participant| participant.left_at.is_none())
+                    .filter(|participant| participant.reconnecting_since.map_or(false, |since| since <= cutoff))
+                    .map(|participant| (call.call_id, participant.user_id))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    async fn record_call_event(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        event_type: &str,
+        event_data: Option<serde_json::Value>,
+    ) -> Result<(), AppError> {
+        let query = "INSERT INTO call_events (call_id, user_id, event_type, event_data) VALUES ($1, $2, $3, $4)";
+        sqlx::query(query)
+            .bind(call_id)
+            .bind(user_id)
+            .bind(event_type)
+            .bind(event_data)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record call event: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Ring timeout and call log
+
+    /// Calls still ringing after the ring timeout. Breakout rooms wait for
+    /// their participants instead.
+    pub async fn expired_rings(&self) -> Vec<ActiveCall> {
+        let cutoff = Utc::now() - self.ring_timeout;
+
+        self.active_calls.iter()
+            .filter(|call| matches!(call.state, CallState::Initiating | CallState::Ringing))
+            .filter(|call| call.parent_call_id.is_none() && call.started_at <= cutoff)
+            .map(|call| call.clone())
+            .collect()
+    }
+
+    /// Moves a call that is still ringing to Missed and returns it as it was
+    /// then. The state is checked and changed under the call's entry lock, so
+    /// a call answered, or being answered, after `expired_rings` took its
+    /// snapshot is left alone and `None` comes back.
+    pub async fn mark_missed(&self, call_id: Uuid) -> Result<Option<ActiveCall>, AppError> {
+        let call = match self.active_calls.get_mut(&call_id) {
+            Some(mut call)
+                if matches!(call.state, CallState::Initiating | CallState::Ringing)
+                    && call.participants.keys().all(|user_id| *user_id == call.caller_id) =>
+            {
+                call.state = CallState::Missed;
+                call.last_activity = Utc::now();
+                call.clone()
+            }
+            _ => return Ok(None),
+        };
+
+        sqlx::query("UPDATE call_sessions SET state = $1 WHERE call_id = $2")
+            .bind(&CallState::Missed)
+            .bind(call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update call state: {}", e)))?;
+
+        self.cache_call_info(call_id).await?;
+
+        Ok(Some(call))
+    }
+
+    /// The user's finished calls, newest first
+    pub async fn get_call_log(&self, user_id: Uuid, query: &CallLogQuery) -> Result<CallLogResponse, AppError> {
+        let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+        let mut sql = String::from(r#"
+            SELECT call_id, caller_id, callee_id, session_id, call_type, state,
+                   started_at, ended_at, duration_seconds, callback_call_id
+            FROM call_sessions
+            WHERE ended_at IS NOT NULL
+              AND (caller_id = $1 OR callee_id = $1
+                   OR EXISTS (SELECT 1 FROM call_participants WHERE call_participants.call_id = call_sessions.call_id AND user_id = $1))
+        "#);
+        let mut bind_count = 1;
+
+        match query.direction {
+            Some(CallDirection::Outgoing) => sql.push_str(" AND caller_id = $1"),
+            Some(CallDirection::Incoming) => sql.push_str(" AND caller_id <> $1"),
+            None => {}
+        }
+
+        if query.outcome.is_some() {
+            bind_count += 1;
+            sql.push_str(&format!(" AND state = ${}", bind_count));
+        }
+
+        if query.min_duration_seconds.is_some() {
+            bind_count += 1;
+            sql.push_str(&format!(" AND COALESCE(duration_seconds, 0) >= ${}", bind_count));
+        }
+
+        if query.max_duration_seconds.is_some() {
+            bind_count += 1;
+            sql.push_str(&format!(" AND COALESCE(duration_seconds, 0) <= ${}", bind_count));
+        }
+
+        // Keyset on (started_at, call_id) so calls started in the same instant
+        // are neither skipped nor repeated across pages
+        if query.before_call_id.is_some() {
+            bind_count += 1;
+            sql.push_str(&format!(
+                " AND (started_at, call_id) < (SELECT started_at, call_id FROM call_sessions WHERE call_id = ${})",
+                bind_count
+            ));
+        }
+
+        sql.push_str(" ORDER BY started_at DESC, call_id DESC");
+
+        bind_count += 1;
+        sql.push_str(&format!(" LIMIT ${}", bind_count));
+
+        let mut db_query = sqlx::query_as::<_, CallLogRow>(&sql).bind(user_id);
+
+        if let Some(outcome) = &query.outcome {
+            db_query = db_query.bind(outcome);
+        }
+
+        if let Some(min_duration) = query.min_duration_seconds {
+            db_query = db_query.bind(min_duration);
+        }
+
+        if let Some(max_duration) = query.max_duration_seconds {
+            db_query = db_query.bind(max_duration);
+        }
+
+        if let Some(before_call_id) = query.before_call_id {
+            db_query = db_query.bind(before_call_id);
+        }
+
+        let rows = db_query
+            .bind(limit as i64)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch call log: {}", e)))?;
+
+        let calls: Vec<CallLogEntry> = rows
+            .into_iter()
+            .map(|row| {
+                let (direction, other_party_id) = if row.caller_id == user_id {
+                    (CallDirection::Outgoing, row.callee_id)
+                } else {
+                    (CallDirection::Incoming, row.caller_id)
+                };
+
+                CallLogEntry {
+                    call_id: row.call_id,
+                    direction,
+                    other_party_id,
+                    session_id: row.session_id,
+                    call_type: row.call_type,
+                    outcome: row.state,
+                    started_at: row.started_at,
+                    ended_at: row.ended_at,
+                    duration_seconds: row.duration_seconds.unwrap_or(0),
+                    callback_call_id: row.callback_call_id,
+                }
+            })
+            .collect();
+
+        let has_more = calls.len() == limit as usize;
+
+        Ok(CallLogResponse { calls, has_more })
+    }
+
+    /// A missed call the user can return, as (other party, session, call type)
+    pub async fn get_missed_call(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(Uuid, Option<Uuid>, CallType), AppError> {
+        let row = sqlx::query_as::<_, CallLogRow>(
+            r#"
+            SELECT call_id, caller_id, callee_id, session_id, call_type, state,
+                   started_at, ended_at, duration_seconds, callback_call_id
+            FROM call_sessions
+            WHERE call_id = $1
+            "#,
+        )
+        .bind(call_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch call: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        let other_party_id = if row.caller_id == user_id {
+            row.callee_id
+        } else if row.callee_id == user_id {
+            row.caller_id
+        } else {
+            return Err(AppError::Forbidden("Not a participant in this call".to_string()));
+        };
+
+        if row.state != CallState::Missed {
+            return Err(AppError::BadRequest("Only missed calls can be called back".to_string()));
+        }
+        if row.call_type.is_group() {
+            return Err(AppError::BadRequest("Group calls can't be called back".to_string()));
+        }
+
+        Ok((other_party_id, row.session_id, row.call_type))
+    }
+
+    /// Links a missed call to the call that returned it
+    pub async fn set_callback_call(&self, call_id: Uuid, callback_call_id: Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE call_sessions SET callback_call_id = $1 WHERE call_id = $2")
+            .bind(callback_call_id)
+            .bind(call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to link callback call: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Breakout rooms
+
+    /// Marks a call as a breakout room of `parent_call_id`.
+    pub async fn set_parent_call(&self, call_id: Uuid, parent_call_id: Uuid) -> Result<(), AppError> {
+        if let Some(mut call) = self.active_calls.get_mut(&call_id) {
+            call.parent_call_id = Some(parent_call_id);
+        } else {
+            return Err(AppError::NotFound("Call not found".to_string()));
+        }
+
+        sqlx::query("UPDATE call_sessions SET parent_call_id = $1 WHERE call_id = $2")
+            .bind(parent_call_id)
+            .bind(call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to link breakout call: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Lets a user who wasn't originally invited join a call.
+    pub async fn invite_participant(&self, call_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let mut participants = self.call_participants.get_mut(&call_id)
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if !participants.contains(&user_id) {
+            participants.push(user_id);
+        }
+
+        Ok(())
+    }
+
+    // Waiting room and host controls
+
+    pub async fn set_waiting_room(&self, call_id: Uuid, enabled: bool) -> Result<(), AppError> {
+        let mut call = self.active_calls.get_mut(&call_id)
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        call.waiting_room_enabled = enabled;
+        Ok(())
+    }
+
+    /// Decides whether an invited user joining the call gets in now, has to
+    /// wait for the host, or is turned away. Waiting users are queued.
+    pub async fn check_admission(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        username: &str,
+    ) -> Result<Admission, AppError> {
+        let mut call = self.active_calls.get_mut(&call_id)
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if call.host_id == user_id {
+            return Ok(Admission::Admitted);
+        }
+        if call.removed.contains(&user_id) {
+            return Err(AppError::Forbidden("You were removed from this call".to_string()));
+        }
+        if call.locked {
+            return Err(AppError::Forbidden("The call is locked".to_string()));
+        }
+        if !call.waiting_room_enabled || call.admitted.remove(&user_id) {
+            return Ok(Admission::Admitted);
+        }
+
+        if !call.waiting_room.iter().any(|waiting| waiting.user_id == user_id) {
+            call.waiting_room.push(WaitingParticipant {
+                user_id,
+                username: username.to_string(),
+                requested_at: Utc::now(),
+            });
+            tracing::info!("Participant {} is waiting to join call {}", user_id, call_id);
+        }
+
+        Ok(Admission::Waiting)
+    }
+
+    pub async fn get_waiting_room(&self, call_id: Uuid) -> Vec<WaitingParticipant> {
+        self.active_calls.get(&call_id)
+            .map(|call| call.waiting_room.clone())
+            .unwrap_or_default()
+    }
+
+    /// Lets a participant in from the waiting room; they join with their next offer.
+    pub async fn admit_participant(&self, call_id: Uuid, host_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        {
+            let mut call = self.get_call_as_host(call_id, host_id)?;
+            take_waiting(&mut *call, user_id)?;
+            call.admitted.insert(user_id);
+        }
+
+        self.record_call_event(call_id, host_id, "participant_admitted", Some(serde_json::json!({ "participant_id": user_id }))).await?;
+
+        tracing::info!("Participant {} admitted to call {}", user_id, call_id);
+        Ok(())
+    }
+
+    pub async fn deny_participant(&self, call_id: Uuid, host_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        {
+            let mut call = self.get_call_as_host(call_id, host_id)?;
+            take_waiting(&mut *call, user_id)?;
+        }
+
+        self.record_call_event(call_id, host_id, "participant_denied", Some(serde_json::json!({ "participant_id": user_id }))).await?;
+
+        tracing::info!("Participant {} denied entry to call {}", user_id, call_id);
+        Ok(())
+    }
+
+    pub async fn mute_participant(&self, call_id: Uuid, host_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.apply_host_media_change(call_id, host_id, user_id, "participant_muted", |_, media_state| {
+            media_state.audio_enabled = false;
+            media_state.audio_muted = true;
+        }).await
+    }
+
+    pub async fn stop_participant_video(&self, call_id: Uuid, host_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.apply_host_media_change(call_id, host_id, user_id, "participant_video_stopped", |_, media_state| {
+            media_state.video_enabled = false;
+            media_state.video_muted = true;
+        }).await
+    }
+
+    /// Lifts a host's stop on a participant's video; it stays off until they
+    /// turn it on.
+    pub async fn allow_participant_video(&self, call_id: Uuid, host_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.apply_host_media_change(call_id, host_id, user_id, "participant_video_allowed", |_, media_state| {
+            media_state.video_muted = false;
+        }).await
+    }
+
+    pub async fn stop_participant_screen_share(&self, call_id: Uuid, host_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.apply_host_media_change(call_id, host_id, user_id, "participant_screen_share_stopped", |call, media_state| {
+            media_state.screen_sharing = false;
+            if call.screen_sharing_participant == Some(user_id) {
+                call.screen_sharing_participant = None;
+            }
+        }).await
+    }
+
+    /// Removes a participant for good; the caller disconnects their media.
+    pub async fn remove_participant_by_host(&self, call_id: Uuid, host_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        {
+            let mut call = self.get_call_as_host(call_id, host_id)?;
+            if user_id == host_id {
+                return Err(AppError::BadRequest("The host cannot remove themselves".to_string()));
+            }
+            call.removed.insert(user_id);
+            call.admitted.remove(&user_id);
+            call.waiting_room.retain(|waiting| waiting.user_id != user_id);
+        }
+
+        self.remove_participant(call_id, user_id).await?;
+        self.record_call_event(call_id, host_id, "participant_removed", Some(serde_json::json!({ "participant_id": user_id }))).await?;
+
+        tracing::info!("Participant {} removed from call {} by host", user_id, call_id);
+        Ok(())
+    }
+
+    pub async fn set_locked(&self, call_id: Uuid, host_id: Uuid, locked: bool) -> Result<(), AppError> {
+        {
+            let mut call = self.get_call_as_host(call_id, host_id)?;
+            call.locked = locked;
+            call.last_activity = Utc::now();
+        }
+
+        let event_type = if locked { "call_locked" } else { "call_unlocked" };
+        self.record_call_event(call_id, host_id, event_type, None).await?;
+
+        tracing::info!("Call {} {}", call_id, if locked { "locked" } else { "unlocked" });
+        Ok(())
+    }
+
+    pub async fn transfer_host(&self, call_id: Uuid, host_id: Uuid, new_host_id: Uuid) -> Result<(), AppError> {
+        {
+            let mut call = self.get_call_as_host(call_id, host_id)?;
+            let is_present = call.participants.get(&new_host_id)
+                .map_or(false, |participant| participant.left_at.is_none());
+            if !is_present {
+                return Err(AppError::BadRequest("The new host must be in the call".to_string()));
+            }
+            call.host_id = new_host_id;
+        }
+
+        self.record_call_event(call_id, host_id, "host_transferred", Some(serde_json::json!({ "participant_id": new_host_id }))).await?;
+
+        tracing::info!("Host of call {} transferred from {} to {}", call_id, host_id, new_host_id);
+        Ok(())
+    }
+
+    // Raised hands and reactions
+
+    /// Puts the participant at the back of the hand queue. Returns their
+    /// position, or None if their hand was already up.
+    pub async fn raise_hand(&self, call_id: Uuid, user_id: Uuid) -> Result<Option<usize>, AppError> {
+        let position = {
+            let mut call = self.active_calls.get_mut(&call_id)
+                .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+            let username = call.participants.get(&user_id)
+                .filter(|participant| participant.left_at.is_none())
+                .map(|participant| participant.username.clone())
+                .ok_or_else(|| AppError::Forbidden("Not a participant in this call".to_string()))?;
+
+            if call.raised_hands.iter().any(|hand| hand.user_id == user_id) {
+                return Ok(None);
+            }
+
+            let now = Utc::now();
+            call.raised_hands.push(RaisedHand {
+                user_id,
+                username,
+                raised_at: now,
+            });
+            call.last_activity = now;
+            call.raised_hands.len()
+        };
+
+        self.record_call_event(call_id, user_id, "hand_raised", Some(serde_json::json!({ "position": position }))).await?;
+
+        tracing::info!("Participant {} raised their hand in call {} at position {}", user_id, call_id, position);
+        Ok(Some(position))
+    }
+
+    /// Takes a hand out of the queue. Participants lower their own hand; the
+    /// host can lower anyone's. Returns false if the hand wasn't raised.
+    pub async fn lower_hand(&self, call_id: Uuid, user_id: Uuid, participant_id: Uuid) -> Result<bool, AppError> {
+        let hand = {
+            let mut call = self.active_calls.get_mut(&call_id)
+                .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+            if participant_id != user_id && call.host_id != user_id {
+                return Err(AppError::Forbidden("Only the host can lower someone else's hand".to_string()));
+            }
+
+            match call.raised_hands.iter().position(|hand| hand.user_id == participant_id) {
+                Some(index) => call.raised_hands.remove(index),
+                None => return Ok(false),
+            }
+        };
+
+        let event_data = serde_json::json!({
+            "lowered_by": user_id,
+            "waited_seconds": (Utc::now() - hand.raised_at).num_seconds(),
+        });
+        self.record_call_event(call_id, participant_id, "hand_lowered", Some(event_data)).await?;
+
+        tracing::info!("Hand of {} lowered by {} in call {}", participant_id, user_id, call_id);
+        Ok(true)
+    }
+
+    pub async fn get_hand_queue(&self, call_id: Uuid) -> Vec<RaisedHand> {
+        self.active_calls.get(&call_id)
+            .map(|call| call.raised_hands.clone())
+            .unwrap_or_default()
+    }
+
+    /// Records a reaction. Returns false if it was dropped because the
+    /// participant is reacting too quickly.
+    pub async fn add_reaction(&self, call_id: Uuid, user_id: Uuid, emoji: &str) -> Result<bool, AppError> {
+        if !ALLOWED_REACTIONS.contains(&emoji) {
+            return Err(AppError::BadRequest(format!("Unsupported reaction: {}", emoji)));
+        }
+
+        {
+            let mut call = self.active_calls.get_mut(&call_id)
+                .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+            let is_present = call.participants.get(&user_id)
+                .map_or(false, |participant| participant.left_at.is_none());
+            if !is_present {
+                return Err(AppError::Forbidden("Not a participant in this call".to_string()));
+            }
+
+            let now = Utc::now();
+            let throttled = call.last_reactions.get(&user_id)
+                .map_or(false, |at| (now - *at).num_milliseconds() < REACTION_COOLDOWN_MS);
+            if throttled {
+                return Ok(false);
+            }
+            call.last_reactions.insert(user_id, now);
+        }
+
+        self.record_call_event(call_id, user_id, "reaction", Some(serde_json::json!({ "emoji": emoji }))).await?;
+        Ok(true)
+    }
+
+    /// Raised hands and reactions in one call.
+    pub async fn get_call_engagement(&self, call_id: Uuid) -> Result<EngagementSummary, AppError> {
+        self.engagement_summary("c.call_id = $1", call_id).await
+    }
+
+    /// Raised hands and reactions across every call of a mentorship session.
+    pub async fn get_session_engagement(&self, session_id: Uuid) -> Result<EngagementSummary, AppError> {
+        self.engagement_summary("c.session_id = $1", session_id).await
+    }
+
+    async fn engagement_summary(&self, filter: &str, id: Uuid) -> Result<EngagementSummary, AppError> {
+        let call_count = sqlx::query_scalar::<_, i64>(
+            &format!("SELECT COUNT(*) FROM call_sessions c WHERE {}", filter)
+        )
+        .bind(id)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to count calls: {}", e)))?;
+
+        let query = format!(
+            r#"
+            SELECT e.user_id, e.event_type, e.event_data->>'emoji' AS emoji,
+                   COALESCE(e.event_data->>'lowered_by' <> e.user_id::text, false) AS answered,
+                   COUNT(*) AS count
+            FROM call_events e
+            JOIN call_sessions c ON c.call_id = e.call_id
+            WHERE {} AND e.event_type IN ('hand_raised', 'hand_lowered', 'reaction')
+            GROUP BY e.user_id, e.event_type, emoji, answered
+            "#,
+            filter
+        );
+
+        let rows = sqlx::query_as::<_, EngagementRow>(&query)
+            .bind(id)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch engagement: {}", e)))?;
+
+        let mut summary = EngagementSummary {
+            call_count,
+            hands_raised: 0,
+            hands_answered: 0,
+            reactions: 0,
+            reactions_by_emoji: HashMap::new(),
+            participants: Vec::new(),
+        };
+        let mut participants: HashMap<Uuid, ParticipantEngagement> = HashMap::new();
+
+        for row in rows {
+            let participant = row.user_id.map(|user_id| {
+                participants.entry(user_id).or_insert(ParticipantEngagement {
+                    user_id,
+                    hands_raised: 0,
+                    reactions: 0,
+                })
+            });
+
+            match row.event_type.as_str() {
+                "hand_raised" => {
+                    summary.hands_raised += row.count;
+                    if let Some(participant) = participant {
+                        participant.hands_raised += row.count;
+                    }
+                }
+                "hand_lowered" if row.answered => summary.hands_answered += row.count,
+                "reaction" => {
+                    summary.reactions += row.count;
+                    if let Some(participant) = participant {
+                        participant.reactions += row.count;
+                    }
+                    if let Some(emoji) = row.emoji {
+                        *summary.reactions_by_emoji.entry(emoji).or_insert(0) += row.count;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        summary.participants = participants.into_values().collect();
+        summary.participants.sort_by(|a, b| {
+            (b.hands_raised + b.reactions).cmp(&(a.hands_raised + a.reactions))
+        });
+
+        Ok(summary)
+    }
+
+    fn get_call_as_host(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<dashmap::mapref::one::RefMut<'_, Uuid, ActiveCall>, AppError> {
+        let call = self.active_calls.get_mut(&call_id)
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if call.host_id != user_id {
+            return Err(AppError::Forbidden("Only the host can do this".to_string()));
+        }
+
+        Ok(call)
+    }
+
+    // Changes a participant's media state on the host's behalf and stores it
+    async fn apply_host_media_change(
+        &self,
+        call_id: Uuid,
+        host_id: Uuid,
+        user_id: Uuid,
+        event_type: &str,
+        change: impl FnOnce(&mut ActiveCall, &mut MediaState),
+    ) -> Result<(), AppError> {
+        let media_state = {
+            let mut call = self.get_call_as_host(call_id, host_id)?;
+            let mut media_state = call.participants.get(&user_id)
+                .filter(|participant| participant.left_at.is_none())
+                .map(|participant| participant.media_state.clone())
+                .ok_or_else(|| AppError::NotFound("Participant not found in call".to_string()))?;
+
+            change(&mut *call, &mut media_state);
+            if let Some(participant) = call.participants.get_mut(&user_id) {
+                participant.media_state = media_state.clone();
+            }
+            call.last_activity = Utc::now();
+            media_state
+        };
+
+        let media_state_json = serde_json::to_value(&media_state)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize media state: {}", e)))?;
+
+        let query = "UPDATE call_participants SET media_state = $1 WHERE call_id = $2 AND user_id = $3";
+        sqlx::query(query)
+            .bind(media_state_json)
+            .bind(call_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update media state: {}", e)))?;
+
+        self.record_call_event(call_id, host_id, event_type, Some(serde_json::json!({ "participant_id": user_id }))).await?;
+
+        tracing::info!("Host {} applied {} to {} in call {}", host_id, event_type, user_id, call_id);
+        Ok(())
+    }
+
     // Connection management
     pub async fn add_connection(
         &self,
@@ -389,7 +1257,7 @@ impl CallManager {
         call_id: Uuid,
         user_id: Uuid,
         metrics: CallQualityMetrics,
-    ) -> Result<(), AppError> {
+    ) -> Result<Option<QualityAssessment>, AppError> {
         // Store metrics in Redis for real-time monitoring
         let metrics_key = format!("call_metrics:{}:{}", call_id, user_id);
         let metrics_json = serde_json::to_string(&metrics)
@@ -400,10 +1268,129 @@ impl CallManager {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to store metrics: {}", e)))?;
 
+        let assessment = self.quality.record(call_id, user_id, &metrics);
+
+        // Keep the history, with the windowed MOS, for analytics and refund reviews
+        let query = r#"
+            INSERT INTO call_quality_metrics (
+                call_id, user_id, audio_bitrate, video_bitrate, packet_loss, jitter,
+                rtt, bandwidth, resolution, frame_rate, mos, recorded_at
+            ) VALUES ($1, $2, $3, $4, $5::NUMERIC, $6::NUMERIC, $7, $8, $9, $10, $11, $12)
+        "#;
+
+        sqlx::query(query)
+            .bind(call_id)
+            .bind(user_id)
+            .bind(metrics.audio_bitrate.map(|bitrate| bitrate as i32))
+            .bind(metrics.video_bitrate.map(|bitrate| bitrate as i32))
+            .bind(metrics.packet_loss)
+            .bind(metrics.jitter)
+            .bind(metrics.rtt.map(|rtt| rtt as i32))
+            .bind(metrics.bandwidth.map(|bandwidth| bandwidth as i32))
+            .bind(&metrics.resolution)
+            .bind(metrics.frame_rate.map(|frame_rate| frame_rate as i32))
+            .bind(assessment.as_ref().map(|assessment| assessment.mos))
+            .bind(metrics.timestamp)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to store quality metrics: {}", e)))?;
+
         tracing::debug!("Recorded quality metrics for participant {} in call {}", user_id, call_id);
+        Ok(assessment)
+    }
+
+    /// Average estimated MOS of the call so far
+    pub fn call_quality_score(&self, call_id: Uuid) -> Option<f32> {
+        self.quality.call_score(call_id)
+    }
+
+    // Stores the call's quality summary and flags poor calls for a refund review
+    async fn finish_call_quality(&self, call_id: Uuid, session_id: Option<Uuid>) -> Result<(), AppError> {
+        let summary = match self.quality.finish_call(call_id) {
+            Some(summary) => summary,
+            None => return Ok(()),
+        };
+
+        let summary_json = serde_json::json!({
+            "average_mos": summary.average_mos,
+            "poor_ratio": summary.poor_ratio,
+            "samples": summary.samples,
+        });
+        sqlx::query("UPDATE call_sessions SET quality_metrics = $1 WHERE call_id = $2")
+            .bind(summary_json)
+            .bind(call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to store call quality: {}", e)))?;
+
+        if let Some(reason) = summary.flag_reason {
+            let query = r#"
+                INSERT INTO call_quality_flags (call_id, session_id, average_mos, poor_ratio, reason)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (call_id) DO NOTHING
+            "#;
+
+            sqlx::query(query)
+                .bind(call_id)
+                .bind(session_id)
+                .bind(summary.average_mos)
+                .bind(summary.poor_ratio)
+                .bind(&reason)
+                .execute(&self.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to flag call quality: {}", e)))?;
+
+            tracing::warn!("Call {} flagged for poor quality: {}", call_id, reason);
+        }
+
         Ok(())
     }
 
+    pub async fn list_quality_flags(&self, status: &str) -> Result<Vec<CallQualityFlag>, AppError> {
+        let query = r#"
+            SELECT call_id, session_id, average_mos, poor_ratio, reason, status,
+                   flagged_at, resolved_at, resolved_by
+            FROM call_quality_flags
+            WHERE status = $1
+            ORDER BY flagged_at DESC
+            LIMIT 100
+        "#;
+
+        sqlx::query_as::<_, CallQualityFlag>(query)
+            .bind(status)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch quality flags: {}", e)))
+    }
+
+    pub async fn resolve_quality_flag(
+        &self,
+        call_id: Uuid,
+        admin_id: Uuid,
+        status: &str,
+    ) -> Result<CallQualityFlag, AppError> {
+        if !matches!(status, "refunded" | "dismissed") {
+            return Err(AppError::BadRequest("Status must be refunded or dismissed".to_string()));
+        }
+
+        let query = r#"
+            UPDATE call_quality_flags
+            SET status = $1, resolved_at = NOW(), resolved_by = $2
+            WHERE call_id = $3 AND status = 'pending'
+            RETURNING call_id, session_id, average_mos, poor_ratio, reason, status,
+                      flagged_at, resolved_at, resolved_by
+        "#;
+
+        sqlx::query_as::<_, CallQualityFlag>(query)
+            .bind(status)
+            .bind(admin_id)
+            .bind(call_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to resolve quality flag: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("No pending quality flag for this call".to_string()))
+    }
+
     // Getters
     pub async fn get_call(&self, call_id: Uuid) -> Option<ActiveCall> {
         self.active_calls.get(&call_id).map(|call| call.clone())
@@ -415,12 +1402,35 @@ impl CallManager {
             .unwrap_or_default()
     }
 
+    /// Participants who joined and have not left yet
+    pub async fn get_active_participants(&self, call_id: Uuid) -> Vec<Uuid> {
+        self.active_calls.get(&call_id)
+            .map(|call| {
+                call.participants.values()
+                    .filter(|participant| participant.left_at.is_none())
+                    .map(|participant| participant.user_id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub async fn get_user_connections(&self, user_id: Uuid) -> Vec<CallConnection> {
         self.connections.get(&user_id)
             .map(|connections| connections.clone())
             .unwrap_or_default()
     }
 
+    /// Calls the user has joined and not left
+    pub async fn get_user_active_calls(&self, user_id: Uuid) -> Vec<ActiveCall> {
+        self.active_calls.iter()
+            .filter(|call| {
+                call.participants.get(&user_id)
+                    .map_or(false, |participant| participant.left_at.is_none())
+            })
+            .map(|call| call.clone())
+            .collect()
+    }
+
     pub async fn is_user_in_call(&self, user_id: Uuid) -> bool {
         for call in self.active_calls.iter() {
             if call.participants.contains_key(&user_id) {
@@ -451,7 +1461,11 @@ impl CallManager {
         let mut calls_to_end = Vec::new();
 
         for call in self.active_calls.iter() {
-            if call.last_activity < cutoff_time && call.state != CallState::Ended {
+            // Calls waiting on a participant to reconnect end when the grace period does
+            let reconnecting = call.participants.values()
+                .any(|participant| participant.connection_state == ParticipantConnectionState::Reconnecting);
+
+            if call.last_activity < cutoff_time && call.state != CallState::Ended && !reconnecting {
                 calls_to_end.push(call.call_id);
             }
         }
@@ -463,4 +1477,27 @@ impl CallManager {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+// Resumes billing once no participant who is still in the call is reconnecting
+fn resume_billing_if_connected(call: &mut ActiveCall, now: DateTime<Utc>) {
+    let reconnecting = call.participants.values().any(|participant| {
+        participant.left_at.is_none()
+            && participant.connection_state == ParticipantConnectionState::Reconnecting
+    });
+
+    if !reconnecting {
+        // Only the part of the pause after the call was answered was billable
+        if let (Some(paused_at), Some(answered_at)) = (call.billing_paused_at.take(), call.answered_at) {
+            call.billing_paused_seconds += (now - paused_at.max(answered_at)).num_seconds().max(0);
+        }
+    }
+}
+
+fn take_waiting(call: &mut ActiveCall, user_id: Uuid) -> Result<WaitingParticipant, AppError> {
+    let index = call.waiting_room.iter()
+        .position(|waiting| waiting.user_id == user_id)
+        .ok_or_else(|| AppError::NotFound("Participant is not in the waiting room".to_string()))?;
+
+    Ok(call.waiting_room.remove(index))
+}
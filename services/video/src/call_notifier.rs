@@ -0,0 +1,132 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    config::VideoServiceConfig,
+    models::{ActiveCall, CallType},
+};
+
+/// Tells users about calls they missed, with a notification and a system
+/// message in their chat with the caller. Both go through the owning
+/// services' internal endpoints, authenticated with the shared service token.
+#[derive(Clone)]
+pub struct CallNotifier {
+    db_pool: PgPool,
+    config: VideoServiceConfig,
+    internal_service_token: String,
+    http_client: reqwest::Client,
+}
+
+impl CallNotifier {
+    pub fn new(db_pool: PgPool, config: &VideoServiceConfig, internal_service_token: String) -> Self {
+        Self {
+            db_pool,
+            config: config.clone(),
+            internal_service_token,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn notify_missed_call(&self, call: &ActiveCall, user_id: Uuid) -> Result<(), AppError> {
+        let caller_name = self.get_username(call.caller_id).await?
+            .unwrap_or_else(|| "Someone".to_string());
+        let label = call_label(&call.call_type);
+
+        // The chat message is still worth posting if the notification fails
+        let notified = self.send_notification(call, user_id, label, &caller_name).await;
+        let posted = self.post_chat_message(call, user_id, format!("Missed {}", label)).await;
+
+        notified.and(posted)
+    }
+
+    async fn send_notification(
+        &self,
+        call: &ActiveCall,
+        user_id: Uuid,
+        label: &str,
+        caller_name: &str,
+    ) -> Result<(), AppError> {
+        let notification = serde_json::json!({
+            "recipient_id": user_id,
+            "notification_type": "MissedCall",
+            "channels": ["Push", "InApp"],
+            "title": format!("Missed {}", label),
+            "message": format!("You missed a {} from {}", label, caller_name),
+            "template_id": null,
+            "template_data": {
+                "call_id": call.call_id,
+                "caller_id": call.caller_id,
+                "call_type": call.call_type,
+            },
+            "scheduled_at": null,
+            "priority": "High",
+            "metadata": {
+                "call_id": call.call_id.to_string(),
+                "action": "call_back",
+            },
+        });
+
+        self.post_internal(
+            format!("{}/internal/notifications", self.config.notifications_service_url),
+            &notification,
+            "Notifications",
+        )
+        .await
+    }
+
+    // Session calls go to the session's chat room, others to the direct chat
+    async fn post_chat_message(&self, call: &ActiveCall, user_id: Uuid, content: String) -> Result<(), AppError> {
+        let recipient_id = if call.session_id.is_some() { None } else { Some(user_id) };
+
+        let message = serde_json::json!({
+            "sender_id": call.caller_id,
+            "content": content,
+            "recipient_id": recipient_id,
+            "session_id": call.session_id,
+            "group_id": null,
+        });
+
+        self.post_internal(
+            format!("{}/internal/messages/system", self.config.chat_service_url),
+            &message,
+            "Chat",
+        )
+        .await
+    }
+
+    async fn post_internal(&self, url: String, body: &serde_json::Value, service: &str) -> Result<(), AppError> {
+        let response = self.http_client
+            .post(url)
+            .header("X-Internal-Token", &self.internal_service_token)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("{} service unavailable: {}", service, e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "{} service rejected missed call notice: {}",
+                service,
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn get_username(&self, user_id: Uuid) -> Result<Option<String>, AppError> {
+        sqlx::query_scalar::<_, String>("SELECT username FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch username: {}", e)))
+    }
+}
+
+fn call_label(call_type: &CallType) -> &'static str {
+    match call_type {
+        CallType::Audio | CallType::GroupAudio => "audio call",
+        CallType::Video | CallType::ScreenShare | CallType::GroupVideo => "video call",
+    }
+}
@@ -9,6 +9,9 @@ pub struct VideoConfig {
     pub jwt: JwtConfig,
     pub video: VideoServiceConfig,
     pub turn: TurnConfig,
+    pub sfu: SfuConfig,
+    pub recording: RecordingConfig,
+    pub transcription: TranscriptionConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +21,15 @@ pub struct VideoServiceConfig {
     pub recording_enabled: bool,
     pub recording_storage_path: String,
     pub max_call_duration_minutes: u32,
+    /// How long a participant whose connection dropped is kept in the call
+    pub reconnect_grace_seconds: i64,
+    /// How long a call rings before it is marked missed
+    pub ring_timeout_seconds: i64,
+    /// Where missed call notices are sent
+    pub notifications_service_url: String,
+    pub chat_service_url: String,
+    /// Sliding window quality scores are averaged over
+    pub quality_window_seconds: i64,
     pub enable_screen_sharing: bool,
     pub enable_call_recording: bool,
     pub video_quality_levels: Vec<String>,
@@ -31,8 +43,63 @@ pub struct TurnConfig {
     pub username: String,
     pub password: String,
     pub realm: String,
+    /// Seeds the `turn_secret` table on first start; rotate secrets through the admin API after that
     pub static_auth_secret: Option<String>,
     pub ttl_seconds: u32,
+    /// Unexpired TURN credentials a user may hold at once
+    pub max_allocations_per_user: u32,
+}
+
+/// In-process SFU used for group calls. When disabled, calls are
+/// peer-to-peer and limited to two participants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SfuConfig {
+    pub enabled: bool,
+    pub max_participants: u32,
+    /// Public address advertised in host candidates when running behind NAT
+    pub public_ip: Option<String>,
+    pub stun_servers: Vec<String>,
+    pub low_layer_bitrate_bps: u32,
+    pub medium_layer_bitrate_bps: u32,
+    pub high_layer_bitrate_bps: u32,
+    pub layer_check_interval_seconds: u64,
+}
+
+/// Server-side call recording. Raw tracks are written under
+/// `VideoServiceConfig::recording_storage_path`, muxed with ffmpeg after the
+/// recording stops and then moved to the storage backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    pub storage_provider: String, // "local", "s3"
+    pub local_storage_path: String,
+    pub bucket_name: String,
+    pub region: String,
+    pub ffmpeg_path: String,
+    pub default_format: String, // "webm", "mp4"
+    pub video_lectures_service_url: String,
+    /// Shared secret sent as `X-Internal-Token` to other services: finished
+    /// recordings are published long after the requester's token expired, and
+    /// missed call notices are raised by the ring timeout with no user token
+    pub internal_service_token: String,
+}
+
+/// Live captions and call transcripts. Audio is cut into chunks of
+/// `chunk_seconds`, converted with the recording ffmpeg and sent to the
+/// speech recognizer; the default runs whisper.cpp locally so no audio
+/// leaves the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionConfig {
+    pub enabled: bool,
+    pub provider: String, // "whisper", "http"
+    pub whisper_path: String,
+    pub model_path: String,
+    pub threads: u32,
+    /// OpenAI-compatible `/v1/audio/transcriptions` endpoint for the http provider
+    pub endpoint_url: Option<String>,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub language: String,
+    pub chunk_seconds: u64,
 }
 
 impl VideoConfig {
@@ -108,6 +175,22 @@ impl VideoConfig {
                     .unwrap_or_else(|_| "120".to_string())
                     .parse()
                     .unwrap_or(120),
+                reconnect_grace_seconds: std::env::var("VIDEO_RECONNECT_GRACE_SECONDS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
+                ring_timeout_seconds: std::env::var("VIDEO_RING_TIMEOUT_SECONDS")
+                    .unwrap_or_else(|_| "45".to_string())
+                    .parse()
+                    .unwrap_or(45),
+                notifications_service_url: std::env::var("NOTIFICATIONS_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8006".to_string()),
+                chat_service_url: std::env::var("CHAT_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8002".to_string()),
+                quality_window_seconds: std::env::var("VIDEO_QUALITY_WINDOW_SECONDS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
                 enable_screen_sharing: std::env::var("VIDEO_ENABLE_SCREEN_SHARING")
                     .unwrap_or_else(|_| "true".to_string())
                     .parse()
@@ -146,6 +229,87 @@ impl VideoConfig {
                     .unwrap_or_else(|_| "86400".to_string())
                     .parse()
                     .unwrap_or(86400),
+                max_allocations_per_user: std::env::var("TURN_MAX_ALLOCATIONS_PER_USER")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10),
+            },
+            sfu: SfuConfig {
+                enabled: std::env::var("VIDEO_SFU_ENABLED")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap_or(false),
+                max_participants: std::env::var("VIDEO_SFU_MAX_PARTICIPANTS")
+                    .unwrap_or_else(|_| "25".to_string())
+                    .parse()
+                    .unwrap_or(25),
+                public_ip: std::env::var("VIDEO_SFU_PUBLIC_IP").ok().filter(|ip| !ip.is_empty()),
+                stun_servers: std::env::var("VIDEO_SFU_STUN_SERVERS")
+                    .unwrap_or_else(|_| "stun:stun.l.google.com:19302".to_string())
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                low_layer_bitrate_bps: std::env::var("VIDEO_SFU_LOW_BITRATE")
+                    .unwrap_or_else(|_| "150000".to_string())
+                    .parse()
+                    .unwrap_or(150_000),
+                medium_layer_bitrate_bps: std::env::var("VIDEO_SFU_MEDIUM_BITRATE")
+                    .unwrap_or_else(|_| "500000".to_string())
+                    .parse()
+                    .unwrap_or(500_000),
+                high_layer_bitrate_bps: std::env::var("VIDEO_SFU_HIGH_BITRATE")
+                    .unwrap_or_else(|_| "1500000".to_string())
+                    .parse()
+                    .unwrap_or(1_500_000),
+                layer_check_interval_seconds: std::env::var("VIDEO_SFU_LAYER_CHECK_INTERVAL")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
+            },
+            recording: RecordingConfig {
+                storage_provider: std::env::var("VIDEO_RECORDING_STORAGE_PROVIDER")
+                    .unwrap_or_else(|_| "local".to_string()),
+                local_storage_path: std::env::var("VIDEO_RECORDING_ARCHIVE_PATH")
+                    .unwrap_or_else(|_| "/app/recordings/archive".to_string()),
+                bucket_name: std::env::var("VIDEO_STORAGE_BUCKET")
+                    .unwrap_or_else(|_| "linkwithmentor-videos".to_string()),
+                region: std::env::var("VIDEO_STORAGE_REGION")
+                    .unwrap_or_else(|_| "us-east-1".to_string()),
+                ffmpeg_path: std::env::var("FFMPEG_PATH")
+                    .unwrap_or_else(|_| "ffmpeg".to_string()),
+                default_format: std::env::var("VIDEO_RECORDING_FORMAT")
+                    .unwrap_or_else(|_| "webm".to_string()),
+                video_lectures_service_url: std::env::var("VIDEO_LECTURES_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8009".to_string()),
+                internal_service_token: std::env::var("INTERNAL_SERVICE_TOKEN")
+                    .unwrap_or_default(),
+            },
+            transcription: TranscriptionConfig {
+                enabled: std::env::var("VIDEO_TRANSCRIPTION_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap_or(true),
+                provider: std::env::var("VIDEO_TRANSCRIPTION_PROVIDER")
+                    .unwrap_or_else(|_| "whisper".to_string()),
+                whisper_path: std::env::var("WHISPER_PATH")
+                    .unwrap_or_else(|_| "whisper-cli".to_string()),
+                model_path: std::env::var("WHISPER_MODEL_PATH")
+                    .unwrap_or_else(|_| "/app/models/ggml-base.bin".to_string()),
+                threads: std::env::var("WHISPER_THREADS")
+                    .unwrap_or_else(|_| "4".to_string())
+                    .parse()
+                    .unwrap_or(4),
+                endpoint_url: std::env::var("VIDEO_TRANSCRIPTION_ENDPOINT").ok(),
+                api_key: std::env::var("VIDEO_TRANSCRIPTION_API_KEY").ok(),
+                model: std::env::var("VIDEO_TRANSCRIPTION_MODEL")
+                    .unwrap_or_else(|_| "whisper-1".to_string()),
+                language: std::env::var("VIDEO_TRANSCRIPTION_LANGUAGE")
+                    .unwrap_or_else(|_| "en".to_string()),
+                chunk_seconds: std::env::var("VIDEO_TRANSCRIPTION_CHUNK_SECONDS")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
             },
         })
     }
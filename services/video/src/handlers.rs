@@ -14,7 +14,13 @@ use crate::{
         InitiateCallRequest, CallResponse, AnswerCallRequest, RejectCallRequest,
         AddIceCandidateRequest, UpdateMediaStateRequest, StartScreenShareRequest,
         ScreenShareResponse, CallQualityRequest, CallStatistics, CallAnalytics,
-        StartRecordingRequest, RecordingResponse, CallState, CallType,
+        StartRecordingRequest, RecordingResponse, RecordingConsentRequest, CallState, CallType,
+        SfuOfferRequest, SfuAnswerRequest, SfuAnswerResponse, ActiveCall,
+        CreateBreakoutRoomsRequest, MoveBreakoutParticipantRequest, BreakoutBroadcastRequest,
+        BreakoutSessionResponse, CallQualityFlag, ResolveQualityFlagRequest,
+        RotateTurnSecretRequest, TurnSecretRotation, RevokeTurnCredentialsRequest, TurnRevocation,
+        CallBackRequest, CallLogQuery, CallLogResponse, TranscriptResponse, CaptionConsentRequest, CallTranscript,
+        RaisedHand, EngagementSummary,
     },
     AppState,
 };
@@ -25,56 +31,48 @@ pub struct CallQuery {
     pub call_type: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct QualityFlagQuery {
+    pub status: Option<String>,
+}
+
 // Initiate a new call
 pub async fn initiate_call(
     State(state): State<AppState>,
     claims: Claims,
     Json(request): Json<InitiateCallRequest>,
 ) -> Result<Json<ApiResponse<CallResponse>>, AppError> {
-    // Validate call type
-    let call_type = request.call_type;
-    
-    // Check if user is already in a call
-    if state.call_manager.is_user_in_call(claims.user_id).await {
-        return Err(AppError::BadRequest("User is already in a call".to_string()));
-    }
+    let response = start_call(&state, &claims, request).await?;
 
-    // Check if callee exists (in a real app, verify user exists in database)
-    if request.callee_id == claims.user_id {
-        return Err(AppError::BadRequest("Cannot call yourself".to_string()));
-    }
+    Ok(Json(ApiResponse::success(response)))
+}
 
-    // Create the call
-    let call_id = state.signaling_service
-        .handle_call_offer(
-            claims.user_id,
-            request.callee_id,
-            request.session_id,
-            call_type.clone(),
-            request.sdp_offer,
-        )
+// Return a missed call to the other party
+pub async fn call_back(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<CallBackRequest>,
+) -> Result<Json<ApiResponse<CallResponse>>, AppError> {
+    let (callee_id, session_id, call_type) = state.call_manager
+        .get_missed_call(call_id, claims.user_id)
         .await?;
 
-    // Generate TURN credentials
-    let turn_credentials = state.signaling_service
-        .get_turn_credentials(claims.user_id)
-        .await
-        .ok();
-
-    let response = CallResponse {
-        call_id,
-        caller_id: claims.user_id,
-        callee_id: request.callee_id,
-        session_id: request.session_id,
+    let response = start_call(&state, &claims, InitiateCallRequest {
+        callee_id,
+        session_id,
         call_type,
-        state: CallState::Initiating,
-        created_at: chrono::Utc::now(),
-        turn_credentials,
-    };
+        sdp_offer: request.sdp_offer,
+        invitee_ids: Vec::new(),
+        waiting_room: false,
+    }).await?;
+
+    state.call_manager.set_callback_call(call_id, response.call_id).await?;
 
     Ok(Json(ApiResponse::success(response)))
 }
 
+// Answer an incoming call
 // Answer an incoming call
 pub async fn answer_call(
     State(state): State<AppState>,
@@ -103,6 +101,34 @@ pub async fn reject_call(
     Ok(Json(ApiResponse::success(())))
 }
 
+// Join a group call's SFU, or renegotiate with it, using an SDP offer
+pub async fn send_sfu_offer(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<SfuOfferRequest>,
+) -> Result<Json<ApiResponse<SfuAnswerResponse>>, AppError> {
+    let sdp_answer = state.signaling_service
+        .handle_sfu_offer(call_id, claims.user_id, &claims.username, request.sdp_offer)
+        .await?;
+
+    Ok(Json(ApiResponse::success(SfuAnswerResponse { sdp_answer })))
+}
+
+// Answer a server-initiated SFU renegotiation
+pub async fn send_sfu_answer(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<SfuAnswerRequest>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.signaling_service
+        .handle_sfu_answer(call_id, claims.user_id, request.sdp_answer)
+        .await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
 // End a call
 pub async fn end_call(
     State(state): State<AppState>,
@@ -197,8 +223,8 @@ pub async fn submit_quality_metrics(
     Path(call_id): Path<Uuid>,
     Json(request): Json<CallQualityRequest>,
 ) -> Result<Json<ApiResponse<()>>, AppError> {
-    state.call_manager
-        .record_quality_metrics(call_id, claims.user_id, request.metrics)
+    state.signaling_service
+        .handle_quality_report(call_id, claims.user_id, request.metrics)
         .await?;
 
     Ok(Json(ApiResponse::success(())))
@@ -225,8 +251,12 @@ pub async fn get_call_info(
         session_id: call.session_id,
         call_type: call.call_type,
         state: call.state,
+        topology: call.topology,
+        host_id: call.host_id,
+        locked: call.locked,
         created_at: call.started_at,
         turn_credentials: None, // Don't include credentials in info response
+        sdp_answer: None,
     };
 
     Ok(Json(ApiResponse::success(response)))
@@ -244,37 +274,69 @@ pub async fn get_ice_servers(
     Ok(Json(ApiResponse::success(ice_servers)))
 }
 
-// Start call recording
+// Start signing TURN credentials with a new secret (admin only)
+pub async fn rotate_turn_secret(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<RotateTurnSecretRequest>,
+) -> Result<Json<ApiResponse<TurnSecretRotation>>, AppError> {
+    if !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let rotation = state.turn_client
+        .rotate_secret(claims.user_id, request.retire_previous)
+        .await?;
+
+    Ok(Json(ApiResponse::success(rotation)))
+}
+
+// Revoke a user's outstanding TURN credentials (admin only)
+pub async fn revoke_turn_credentials(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<RevokeTurnCredentialsRequest>,
+) -> Result<Json<ApiResponse<TurnRevocation>>, AppError> {
+    if !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let revocation = state.turn_client
+        .revoke_user_credentials(user_id, claims.user_id, request.rotate_secrets)
+        .await?;
+
+    Ok(Json(ApiResponse::success(revocation)))
+}
+
+// Request a recording of the call; it starts once every participant consents
 pub async fn start_recording(
     State(state): State<AppState>,
     claims: Claims,
     Path(call_id): Path<Uuid>,
     Json(request): Json<StartRecordingRequest>,
 ) -> Result<Json<ApiResponse<RecordingResponse>>, AppError> {
-    // Verify user is part of the call
-    let call = state.call_manager.get_call(call_id).await
-        .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+    let call = get_participating_call(&state, call_id, claims.user_id).await?;
 
-    if !call.participants.contains_key(&claims.user_id) {
-        return Err(AppError::Forbidden("Not a participant in this call".to_string()));
-    }
+    let response = state.recording_service
+        .request_recording(&call, claims.user_id, request)
+        .await?;
 
-    // Check if recording is enabled
-    if !state.config.video.enable_call_recording {
-        return Err(AppError::BadRequest("Call recording is not enabled".to_string()));
-    }
+    Ok(Json(ApiResponse::success(response)))
+}
 
-    // In a real implementation, you would start the recording process
-    let recording_id = Uuid::new_v4();
-    
-    let response = RecordingResponse {
-        recording_id,
-        status: crate::models::RecordingStatus::Starting,
-        file_path: None,
-        duration_seconds: None,
-    };
+// Grant or refuse consent to record the call
+pub async fn respond_recording_consent(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<RecordingConsentRequest>,
+) -> Result<Json<ApiResponse<RecordingResponse>>, AppError> {
+    let call = get_participating_call(&state, call_id, claims.user_id).await?;
 
-    tracing::info!("Started recording for call {} by user {}", call_id, claims.user_id);
+    let response = state.recording_service
+        .respond_to_consent(&call, claims.user_id, request.granted)
+        .await?;
 
     Ok(Json(ApiResponse::success(response)))
 }
@@ -285,29 +347,166 @@ pub async fn stop_recording(
     claims: Claims,
     Path(call_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<RecordingResponse>>, AppError> {
-    // Verify user is part of the call
-    let call = state.call_manager.get_call(call_id).await
-        .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+    get_participating_call(&state, call_id, claims.user_id).await?;
 
-    if !call.participants.contains_key(&claims.user_id) {
+    let response = state.recording_service
+        .stop_recording(call_id)
+        .await?;
+
+    tracing::info!("Stopped recording for call {} by user {}", call_id, claims.user_id);
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Get the status of the call's latest recording
+pub async fn get_recording_status(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<RecordingResponse>>, AppError> {
+    // The call may have ended, so check who joined it rather than the active call
+    let was_participant = was_call_participant(&state, call_id, claims.user_id).await?;
+    if !was_participant && !claims.roles.contains(&"admin".to_string()) {
         return Err(AppError::Forbidden("Not a participant in this call".to_string()));
     }
 
-    // In a real implementation, you would stop the recording process
-    let recording_id = Uuid::new_v4(); // This would be the actual recording ID
-    
-    let response = RecordingResponse {
-        recording_id,
-        status: crate::models::RecordingStatus::Stopping,
-        file_path: Some("/recordings/call_recording.mp4".to_string()),
-        duration_seconds: Some(300), // 5 minutes example
-    };
+    let response = state.recording_service.get_recording_status(call_id).await?;
 
-    tracing::info!("Stopped recording for call {} by user {}", call_id, claims.user_id);
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Request live captions for everyone in the call; they start once every
+// participant consents
+pub async fn start_captions(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<TranscriptResponse>>, AppError> {
+    let call = get_participating_call(&state, call_id, claims.user_id).await?;
+
+    let response = state.transcription_service
+        .start_captions(&call, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Grant or refuse consent to caption the call
+pub async fn respond_captions_consent(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<CaptionConsentRequest>,
+) -> Result<Json<ApiResponse<TranscriptResponse>>, AppError> {
+    let call = get_participating_call(&state, call_id, claims.user_id).await?;
+
+    let response = state.transcription_service
+        .respond_to_consent(&call, claims.user_id, request.granted)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Turn off live captions and save the transcript
+pub async fn stop_captions(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<TranscriptResponse>>, AppError> {
+    get_participating_call(&state, call_id, claims.user_id).await?;
+
+    let response = state.transcription_service.stop_captions(call_id).await?;
+
+    tracing::info!("Stopped captions for call {} by user {}", call_id, claims.user_id);
 
     Ok(Json(ApiResponse::success(response)))
 }
 
+// Get the call's latest transcript
+pub async fn get_call_transcript(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<CallTranscript>>, AppError> {
+    // The call may have ended, so check who joined it rather than the active call
+    let was_participant = was_call_participant(&state, call_id, claims.user_id).await?;
+    if !was_participant && !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Not a participant in this call".to_string()));
+    }
+
+    let transcript = state.transcription_service.get_transcript(call_id).await?;
+
+    Ok(Json(ApiResponse::success(transcript)))
+}
+
+// Split a group call into breakout rooms (host only)
+pub async fn create_breakout_rooms(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<CreateBreakoutRoomsRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<BreakoutSessionResponse>>), AppError> {
+    let response = state.breakout_service
+        .create_rooms(call_id, claims.user_id, request)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(response))))
+}
+
+// Get the open breakout rooms of a call
+pub async fn get_breakout_rooms(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<BreakoutSessionResponse>>, AppError> {
+    let response = state.breakout_service
+        .get_rooms(call_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Move a participant to another breakout room or back to the main room (host only)
+pub async fn move_breakout_participant(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((call_id, user_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<MoveBreakoutParticipantRequest>,
+) -> Result<Json<ApiResponse<BreakoutSessionResponse>>, AppError> {
+    let response = state.breakout_service
+        .move_participant(call_id, claims.user_id, user_id, request.room_index)
+        .await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Send a message to every breakout room (host only)
+pub async fn broadcast_to_breakout_rooms(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<BreakoutBroadcastRequest>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.breakout_service
+        .broadcast(call_id, claims.user_id, request.message)
+        .await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+// Close all breakout rooms and bring everyone back (host only)
+pub async fn close_breakout_rooms(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.breakout_service
+        .close_rooms(call_id, claims.user_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
 // Get call analytics
 pub async fn get_call_analytics(
     State(state): State<AppState>,
@@ -327,7 +526,7 @@ pub async fn get_call_analytics(
         call_id,
         total_duration_seconds: (chrono::Utc::now() - call.started_at).num_seconds() as i32,
         participant_count: call.participants.len() as i32,
-        average_quality_score: 4.2, // Placeholder
+        average_quality_score: state.call_manager.call_quality_score(call_id).unwrap_or(0.0),
         connection_issues: 0,
         screen_sharing_duration: None,
         recording_duration: None,
@@ -337,6 +536,100 @@ pub async fn get_call_analytics(
     Ok(Json(ApiResponse::success(analytics)))
 }
 
+// Get the queue of raised hands (host only)
+pub async fn get_hand_queue(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<RaisedHand>>>, AppError> {
+    let call = get_participating_call(&state, call_id, claims.user_id).await?;
+    if call.host_id != claims.user_id {
+        return Err(AppError::Forbidden("Only the host can do this".to_string()));
+    }
+
+    Ok(Json(ApiResponse::success(call.raised_hands)))
+}
+
+// Get raised hands and reactions for a call
+pub async fn get_call_engagement(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EngagementSummary>>, AppError> {
+    let was_participant = was_call_participant(&state, call_id, claims.user_id).await?;
+    if !was_participant && !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Not authorized to view call analytics".to_string()));
+    }
+
+    let summary = state.call_manager.get_call_engagement(call_id).await?;
+
+    Ok(Json(ApiResponse::success(summary)))
+}
+
+// Get raised hands and reactions across a mentorship session's calls
+pub async fn get_session_engagement(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EngagementSummary>>, AppError> {
+    let was_participant = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM call_participants p
+            JOIN call_sessions c ON c.call_id = p.call_id
+            WHERE c.session_id = $1 AND p.user_id = $2
+        )
+        "#
+    )
+    .bind(session_id)
+    .bind(claims.user_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to check session participation: {}", e)))?;
+
+    if !was_participant && !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Not authorized to view session analytics".to_string()));
+    }
+
+    let summary = state.call_manager.get_session_engagement(session_id).await?;
+
+    Ok(Json(ApiResponse::success(summary)))
+}
+
+// List calls flagged for poor quality (admin only)
+pub async fn get_quality_flags(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<QualityFlagQuery>,
+) -> Result<Json<ApiResponse<Vec<CallQualityFlag>>>, AppError> {
+    if !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let status = query.status.unwrap_or_else(|| "pending".to_string());
+    let flags = state.call_manager.list_quality_flags(&status).await?;
+
+    Ok(Json(ApiResponse::success(flags)))
+}
+
+// Refund or dismiss a poor quality flag (admin only)
+pub async fn resolve_quality_flag(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(call_id): Path<Uuid>,
+    Json(request): Json<ResolveQualityFlagRequest>,
+) -> Result<Json<ApiResponse<CallQualityFlag>>, AppError> {
+    if !claims.roles.contains(&"admin".to_string()) {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let flag = state.call_manager
+        .resolve_quality_flag(call_id, claims.user_id, &request.status)
+        .await?;
+
+    Ok(Json(ApiResponse::success(flag)))
+}
+
 // Get platform call statistics (admin only)
 pub async fn get_call_statistics(
     State(state): State<AppState>,
@@ -370,20 +663,96 @@ pub async fn health_check() -> Result<Json<ApiResponse<String>>, AppError> {
     Ok(Json(ApiResponse::success("Video service is healthy".to_string())))
 }
 
-// Get active calls for a user
+// Get the user's call history
 pub async fn get_user_calls(
     State(state): State<AppState>,
     claims: Claims,
-) -> Result<Json<ApiResponse<Vec<CallResponse>>>, AppError> {
-    // In a real implementation, this would query active calls for the user
-    let mut user_calls = Vec::new();
+    Query(query): Query<CallLogQuery>,
+) -> Result<Json<ApiResponse<CallLogResponse>>, AppError> {
+    let call_log = state.call_manager
+        .get_call_log(claims.user_id, &query)
+        .await?;
 
-    // Check if user is currently in any calls
+    Ok(Json(ApiResponse::success(call_log)))
+}
+
+// Helpers
+
+// Starts a call from the user to `request.callee_id`
+async fn start_call(state: &AppState, claims: &Claims, request: InitiateCallRequest) -> Result<CallResponse, AppError> {
+    // Validate call type
+    let call_type = request.call_type;
+    
+    // Check if user is already in a call
     if state.call_manager.is_user_in_call(claims.user_id).await {
-        // Find and return the user's active calls
-        // This is a simplified implementation
-        tracing::debug!("User {} has active calls", claims.user_id);
+        return Err(AppError::BadRequest("User is already in a call".to_string()));
+    }
+
+    // Check if callee exists (in a real app, verify user exists in database)
+    if request.callee_id == claims.user_id {
+        return Err(AppError::BadRequest("Cannot call yourself".to_string()));
+    }
+
+    if request.waiting_room && !call_type.is_group() {
+        return Err(AppError::BadRequest("Only group calls can have a waiting room".to_string()));
+    }
+
+    // Create the call
+    let outcome = state.signaling_service
+        .handle_call_offer(
+            claims.user_id,
+            request.callee_id,
+            request.session_id,
+            call_type.clone(),
+            request.sdp_offer,
+            request.invitee_ids,
+        )
+        .await?;
+
+    if request.waiting_room {
+        state.call_manager.set_waiting_room(outcome.call_id, true).await?;
+    }
+
+    // Generate TURN credentials
+    let turn_credentials = state.signaling_service
+        .get_turn_credentials(claims.user_id, Some(outcome.call_id))
+        .await
+        .ok();
+
+    Ok(CallResponse {
+        call_id: outcome.call_id,
+        caller_id: claims.user_id,
+        callee_id: request.callee_id,
+        session_id: request.session_id,
+        call_type,
+        state: CallState::Initiating,
+        topology: outcome.topology,
+        host_id: claims.user_id,
+        locked: false,
+        created_at: chrono::Utc::now(),
+        turn_credentials,
+        sdp_answer: outcome.sdp_answer,
+    })
+}
+
+async fn get_participating_call(state: &AppState, call_id: Uuid, user_id: Uuid) -> Result<ActiveCall, AppError> {
+    let call = state.call_manager.get_call(call_id).await
+        .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+    if !call.participants.contains_key(&user_id) {
+        return Err(AppError::Forbidden("Not a participant in this call".to_string()));
     }
 
-    Ok(Json(ApiResponse::success(user_calls)))
-}
\ No newline at end of file
+    Ok(call)
+}
+
+async fn was_call_participant(state: &AppState, call_id: Uuid, user_id: Uuid) -> Result<bool, AppError> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM call_participants WHERE call_id = $1 AND user_id = $2)"
+    )
+    .bind(call_id)
+    .bind(user_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to check call participation: {}", e)))
+}
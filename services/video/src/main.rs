@@ -6,6 +6,12 @@ mod call_manager;
 mod webrtc_handler;
 mod routes;
 mod turn_client;
+mod sfu;
+mod recording;
+mod breakout;
+mod quality;
+mod call_notifier;
+mod transcription;
 
 use axum::{
     http::{StatusCode, Method},
@@ -27,6 +33,11 @@ use crate::config::VideoConfig;
 use crate::call_manager::CallManager;
 use crate::signaling::SignalingService;
 use crate::turn_client::TurnClient;
+use crate::sfu::SfuService;
+use crate::recording::RecordingService;
+use crate::breakout::BreakoutService;
+use crate::call_notifier::CallNotifier;
+use crate::transcription::TranscriptionService;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -37,6 +48,9 @@ pub struct AppState {
     pub call_manager: CallManager,
     pub signaling_service: SignalingService,
     pub turn_client: TurnClient,
+    pub recording_service: RecordingService,
+    pub transcription_service: TranscriptionService,
+    pub breakout_service: BreakoutService,
 }
 
 #[tokio::main]
@@ -67,12 +81,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let jwt_service = JwtService::new(&config.jwt.secret);
 
     // Create TURN client
-    let turn_client = TurnClient::new(&config.turn).await?;
+    let turn_client = TurnClient::new(&config.turn, db_pool.clone()).await?;
+    turn_client.start_secret_pruner().await;
 
     // Create call manager
     let call_manager = CallManager::new(
         db_pool.clone(),
         redis_service.clone(),
+        config.video.reconnect_grace_seconds,
+        config.video.ring_timeout_seconds,
+        config.video.quality_window_seconds,
+    );
+
+    // Create SFU for group calls and start adapting simulcast layers
+    let sfu_service = SfuService::new(&config.sfu, call_manager.clone())?;
+    if config.sfu.enabled {
+        sfu_service.start_layer_monitor().await;
+    }
+
+    // Create transcription service with the configured speech recognizer
+    let recognizer = transcription::recognizer::create_recognizer(&config.transcription)?;
+    let transcription_service = TranscriptionService::new(
+        db_pool.clone(),
+        &config.transcription,
+        &config.recording,
+        &config.video,
+        call_manager.clone(),
+        sfu_service.clone(),
+        recognizer,
+    );
+
+    // Create recording service with the configured storage backend
+    let recording_storage = recording::storage::create_storage(&config.recording).await?;
+    let recording_service = RecordingService::new(
+        db_pool.clone(),
+        &config.recording,
+        &config.video,
+        call_manager.clone(),
+        sfu_service.clone(),
+        recording_storage,
+        transcription_service.clone(),
+    );
+
+    // Create notifier for missed calls
+    let call_notifier = CallNotifier::new(
+        db_pool.clone(),
+        &config.video,
+        config.recording.internal_service_token.clone(),
     );
 
     // Create signaling service
@@ -80,10 +135,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         call_manager.clone(),
         turn_client.clone(),
         redis_service.clone(),
+        sfu_service,
+        recording_service.clone(),
+        transcription_service.clone(),
+        call_notifier,
     );
 
     // Initialize signaling service
     signaling_service.initialize().await?;
+    signaling_service.start_reconnect_monitor().await;
+    signaling_service.start_ring_monitor().await;
+
+    // Create breakout room service
+    let breakout_service = BreakoutService::new(
+        db_pool.clone(),
+        call_manager.clone(),
+        signaling_service.clone(),
+    );
 
     // Build application state
     let app_state = AppState {
@@ -94,6 +162,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         call_manager,
         signaling_service,
         turn_client,
+        recording_service,
+        transcription_service,
+        breakout_service,
     };
 
     // Build CORS layer
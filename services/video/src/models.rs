@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 // WebRTC Signaling Messages
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,8 +13,11 @@ pub enum SignalingMessage {
         caller_id: Uuid,
         callee_id: Uuid,
         session_id: Option<Uuid>,
+        /// Empty for SFU calls; invitees join by sending `SfuOffer`
         sdp: String,
         call_type: CallType,
+        #[serde(default)]
+        topology: CallTopology,
     },
     CallAnswer {
         call_id: Uuid,
@@ -30,6 +33,10 @@ pub enum SignalingMessage {
     CallEnd {
         call_id: Uuid,
     },
+    // Nobody answered before the ring timeout
+    CallMissed {
+        call_id: Uuid,
+    },
     
     // ICE candidates
     IceCandidate {
@@ -57,6 +64,39 @@ pub enum SignalingMessage {
         participant_id: Uuid,
         username: String,
     },
+
+    // Reconnection. A participant whose connection drops is held in the
+    // call until `reconnect_deadline`; `CallResumed` gives a returning
+    // participant everyone's current state, including their own media state.
+    ParticipantReconnecting {
+        call_id: Uuid,
+        participant_id: Uuid,
+        reconnect_deadline: DateTime<Utc>,
+    },
+    ParticipantReconnected {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    CallResumed {
+        call_id: Uuid,
+        state: CallState,
+        participants: Vec<CallParticipant>,
+    },
+
+    // ICE restart after a network change. Clients set `participant_id` to
+    // the peer they restart with (it can be omitted in one-to-one calls); the
+    // server delivers it with `participant_id` set to the sender. SFU
+    // participants restart by sending an `SfuOffer` instead.
+    IceRestartOffer {
+        call_id: Uuid,
+        participant_id: Option<Uuid>,
+        sdp: String,
+    },
+    IceRestartAnswer {
+        call_id: Uuid,
+        participant_id: Option<Uuid>,
+        sdp: String,
+    },
     
     // Media control
     MediaStateChanged {
@@ -82,13 +122,202 @@ pub enum SignalingMessage {
         call_id: Uuid,
         participant_id: Uuid,
     },
+
+    // Waiting room and host controls. The host sends these to act on a
+    // participant; once applied they are sent on to the participants.
+    WaitingForHost {
+        call_id: Uuid,
+    },
+    WaitingRoomUpdated {
+        call_id: Uuid,
+        waiting: Vec<WaitingParticipant>,
+    },
+    AdmitParticipant {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    DenyParticipant {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    MuteParticipant {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    StopParticipantVideo {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    // Lets a participant whose video the host stopped turn it back on
+    AllowParticipantVideo {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    StopParticipantScreenShare {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    RemoveParticipant {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+    LockCall {
+        call_id: Uuid,
+        locked: bool,
+    },
+    TransferHost {
+        call_id: Uuid,
+        participant_id: Uuid,
+    },
+
+    // Raised hands and reactions. Participants send `RaiseHand`, `LowerHand`
+    // (without `participant_id`) and `Reaction`; the host can lower anyone's
+    // hand. The server fills in `participant_id` on what it sends out, and
+    // keeps the host's view of the queue current with `HandQueueUpdated`.
+    RaiseHand {
+        call_id: Uuid,
+    },
+    LowerHand {
+        call_id: Uuid,
+        participant_id: Option<Uuid>,
+    },
+    HandRaised {
+        call_id: Uuid,
+        participant_id: Uuid,
+        // 1-based place in the queue
+        position: usize,
+    },
+    HandLowered {
+        call_id: Uuid,
+        participant_id: Uuid,
+        lowered_by: Uuid,
+    },
+    HandQueueUpdated {
+        call_id: Uuid,
+        queue: Vec<RaisedHand>,
+    },
+    Reaction {
+        call_id: Uuid,
+        participant_id: Option<Uuid>,
+        emoji: String,
+    },
+
+    // Breakout rooms. `call_id` is always the main call; participants join
+    // `breakout_call_id` with an SFU offer and rejoin the main call the same
+    // way when the rooms close.
+    BreakoutAssigned {
+        call_id: Uuid,
+        breakout_call_id: Uuid,
+        name: String,
+        ends_at: Option<DateTime<Utc>>,
+    },
+    BreakoutBroadcast {
+        call_id: Uuid,
+        sender_id: Uuid,
+        message: String,
+    },
+    BreakoutClosed {
+        call_id: Uuid,
+    },
     
+    // SFU negotiation (group calls). Clients offer to the server and get an
+    // answer back; the server sends `SfuRenegotiate` when forwarded tracks
+    // change and expects an `SfuAnswer` in return. ICE candidates use
+    // `IceCandidate` in both directions.
+    SfuOffer {
+        call_id: Uuid,
+        sdp: String,
+    },
+    SfuAnswer {
+        call_id: Uuid,
+        sdp: String,
+    },
+    SfuRenegotiate {
+        call_id: Uuid,
+        sdp: String,
+    },
+    SfuTrackAdded {
+        call_id: Uuid,
+        publisher_id: Uuid,
+        track_id: String,
+        kind: String,
+    },
+    SfuTrackRemoved {
+        call_id: Uuid,
+        publisher_id: Uuid,
+        track_id: String,
+    },
+
+    // Recording. Every participant must consent before recording starts.
+    // In peer-to-peer calls `RecordingStarted` asks clients to publish to the
+    // server with a non-trickle `SfuOffer`.
+    RecordingConsentRequested {
+        call_id: Uuid,
+        recording_id: Uuid,
+        requested_by: Uuid,
+    },
+    RecordingStarted {
+        call_id: Uuid,
+        recording_id: Uuid,
+        publish_to_recorder: bool,
+    },
+    RecordingDeclined {
+        call_id: Uuid,
+        recording_id: Uuid,
+        declined_by: Uuid,
+    },
+    RecordingStopped {
+        call_id: Uuid,
+        recording_id: Uuid,
+    },
+
+    // Live captions. Like recording, every participant must consent before
+    // any audio is sent to the recognizer, and `CaptionsStarted` asks clients
+    // in peer-to-peer calls to publish to the server.
+    CaptionsConsentRequested {
+        call_id: Uuid,
+        transcript_id: Uuid,
+        requested_by: Uuid,
+    },
+    CaptionsStarted {
+        call_id: Uuid,
+        transcript_id: Uuid,
+        started_by: Uuid,
+        publish_to_recorder: bool,
+    },
+    Caption {
+        call_id: Uuid,
+        transcript_id: Uuid,
+        speaker_id: Uuid,
+        text: String,
+        // Milliseconds since captions started
+        start_ms: u64,
+        end_ms: u64,
+    },
+    CaptionsDeclined {
+        call_id: Uuid,
+        transcript_id: Uuid,
+        declined_by: Uuid,
+    },
+    CaptionsStopped {
+        call_id: Uuid,
+        transcript_id: Uuid,
+    },
+
     // Call quality
     QualityReport {
         call_id: Uuid,
         participant_id: Uuid,
         metrics: CallQualityMetrics,
     },
+    // Sent to a participant whose quality has changed enough to act on
+    QualityAdvice {
+        call_id: Uuid,
+        participant_id: Uuid,
+        mos: f32,
+        rating: QualityRating,
+        suggestions: Vec<QualitySuggestion>,
+    },
     
     // Error handling
     Error {
@@ -109,6 +338,12 @@ pub struct InitiateCallRequest {
     pub session_id: Option<Uuid>,
     pub call_type: CallType,
     pub sdp_offer: String,
+    /// Additional invitees for group calls
+    #[serde(default)]
+    pub invitee_ids: Vec<Uuid>,
+    /// Hold invitees in a waiting room until the host admits them (group calls only)
+    #[serde(default)]
+    pub waiting_room: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -119,8 +354,18 @@ pub struct CallResponse {
     pub session_id: Option<Uuid>,
     pub call_type: CallType,
     pub state: CallState,
+    pub topology: CallTopology,
+    pub host_id: Uuid,
+    pub locked: bool,
     pub created_at: DateTime<Utc>,
     pub turn_credentials: Option<TurnCredentials>,
+    /// The SFU's answer to the caller's offer (SFU calls only)
+    pub sdp_answer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallBackRequest {
+    pub sdp_offer: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -157,6 +402,22 @@ pub struct ScreenShareResponse {
     pub sdp_answer: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SfuOfferRequest {
+    pub sdp_offer: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SfuAnswerRequest {
+    pub sdp_answer: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SfuAnswerResponse {
+    /// None while the participant waits for the host to admit them
+    pub sdp_answer: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CallQualityRequest {
     pub metrics: CallQualityMetrics,
@@ -168,6 +429,24 @@ pub enum CallType {
     Audio,
     Video,
     ScreenShare,
+    GroupAudio,
+    GroupVideo,
+}
+
+impl CallType {
+    pub fn is_group(&self) -> bool {
+        matches!(self, CallType::GroupAudio | CallType::GroupVideo)
+    }
+}
+
+/// How media flows in a call: directly between two peers, or through the
+/// service's SFU. Group call types use the SFU when it is enabled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum CallTopology {
+    #[default]
+    Mesh,
+    Sfu,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -181,6 +460,7 @@ pub enum CallState {
     Failed,
     Cancelled,
     Rejected,
+    Missed,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -191,6 +471,9 @@ pub struct CallParticipant {
     pub left_at: Option<DateTime<Utc>>,
     pub media_state: MediaState,
     pub connection_state: ParticipantConnectionState,
+    /// Set while the participant is `Reconnecting`
+    #[serde(default)]
+    pub reconnecting_since: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -198,14 +481,17 @@ pub struct MediaState {
     pub audio_enabled: bool,
     pub video_enabled: bool,
     pub screen_sharing: bool,
+    // Muted by the host, until the participant unmutes
     pub audio_muted: bool,
+    // Video stopped by the host, until the host allows it again
     pub video_muted: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ParticipantConnectionState {
     Connecting,
     Connected,
+    Reconnecting,
     Disconnected,
     Failed,
 }
@@ -223,6 +509,39 @@ pub struct CallQualityMetrics {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Bands of estimated MOS (1.0 - 4.5)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum QualityRating {
+    Excellent,
+    Good,
+    Fair,
+    Poor,
+    Bad,
+}
+
+impl QualityRating {
+    pub fn from_mos(mos: f32) -> Self {
+        if mos >= 4.3 {
+            QualityRating::Excellent
+        } else if mos >= 4.0 {
+            QualityRating::Good
+        } else if mos >= 3.6 {
+            QualityRating::Fair
+        } else if mos >= 3.1 {
+            QualityRating::Poor
+        } else {
+            QualityRating::Bad
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum QualitySuggestion {
+    LowerResolution,
+    DisableVideo,
+    SwitchToTurnTcp,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TurnCredentials {
     pub username: String,
@@ -231,6 +550,36 @@ pub struct TurnCredentials {
     pub uris: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RotateTurnSecretRequest {
+    /// Stop accepting credentials signed by previous secrets right away
+    #[serde(default)]
+    pub retire_previous: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnSecretRotation {
+    pub secret_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub retired_secrets: usize,
+    /// When the relay stops accepting the previous secrets
+    pub retire_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevokeTurnCredentialsRequest {
+    /// Rotate and retire all secrets so the revoked credentials stop working at the relay immediately
+    #[serde(default)]
+    pub rotate_secrets: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnRevocation {
+    pub user_id: Uuid,
+    pub revoked_credentials: u64,
+    pub rotation: Option<TurnSecretRotation>,
+}
+
 // Database Models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallSession {
@@ -243,6 +592,8 @@ pub struct CallSession {
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
     pub duration_seconds: Option<i32>,
+    pub billable_seconds: Option<i32>,
+    pub callback_call_id: Option<Uuid>,
     pub quality_metrics: Option<serde_json::Value>,
     pub recording_path: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -278,12 +629,64 @@ pub struct ActiveCall {
     pub callee_id: Uuid,
     pub session_id: Option<Uuid>,
     pub call_type: CallType,
+    pub topology: CallTopology,
     pub state: CallState,
     pub participants: HashMap<Uuid, CallParticipant>,
     pub started_at: DateTime<Utc>,
+    /// When someone other than the caller first answered
+    pub answered_at: Option<DateTime<Utc>>,
     pub last_activity: DateTime<Utc>,
     pub recording_active: bool,
     pub screen_sharing_participant: Option<Uuid>,
+    /// Set while billing is paused because a participant is reconnecting
+    pub billing_paused_at: Option<DateTime<Utc>>,
+    pub billing_paused_seconds: i64,
+    /// Set on breakout rooms
+    pub parent_call_id: Option<Uuid>,
+    pub host_id: Uuid,
+    /// No one else can join a locked call
+    pub locked: bool,
+    pub waiting_room_enabled: bool,
+    pub waiting_room: Vec<WaitingParticipant>,
+    // Let in from the waiting room but not joined yet
+    pub admitted: HashSet<Uuid>,
+    // Removed by the host; they can't rejoin
+    pub removed: HashSet<Uuid>,
+    // In the order hands were raised
+    pub raised_hands: Vec<RaisedHand>,
+    // Last reaction per participant, for throttling
+    pub last_reactions: HashMap<Uuid, DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitingParticipant {
+    pub user_id: Uuid,
+    pub username: String,
+    pub requested_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaisedHand {
+    pub user_id: Uuid,
+    pub username: String,
+    pub raised_at: DateTime<Utc>,
+}
+
+impl ActiveCall {
+    /// Time since the call was answered, excluding time spent waiting for
+    /// participants to reconnect. Ringing isn't billed, so calls nobody
+    /// answered have none.
+    pub fn billable_seconds(&self, now: DateTime<Utc>) -> i64 {
+        let Some(answered_at) = self.answered_at else {
+            return 0;
+        };
+
+        let paused_now = self.billing_paused_at
+            .map(|at| (now - at.max(answered_at)).num_seconds().max(0))
+            .unwrap_or(0);
+
+        ((now - answered_at).num_seconds() - self.billing_paused_seconds - paused_now).max(0)
+    }
 }
 
 // Recording Models
@@ -305,6 +708,13 @@ pub struct CallRecording {
 pub struct StartRecordingRequest {
     pub quality: Option<String>,
     pub format: Option<String>,
+    /// Title of the private lecture the recording is published as
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordingConsentRequest {
+    pub granted: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -313,17 +723,145 @@ pub struct RecordingResponse {
     pub status: RecordingStatus,
     pub file_path: Option<String>,
     pub duration_seconds: Option<i32>,
+    /// Participants who have not consented yet
+    #[serde(default)]
+    pub pending_consent: Vec<Uuid>,
+    pub lecture_id: Option<Uuid>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RecordingStatus {
+    AwaitingConsent,
     Starting,
     Recording,
     Stopping,
+    Processing,
     Completed,
+    Declined,
     Failed,
 }
 
+// Engagement Models
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParticipantEngagement {
+    pub user_id: Uuid,
+    pub hands_raised: i64,
+    pub reactions: i64,
+}
+
+/// Raised hands and reactions over one call, or every call of a session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EngagementSummary {
+    pub call_count: i64,
+    pub hands_raised: i64,
+    /// Hands the host lowered, i.e. questions taken
+    pub hands_answered: i64,
+    pub reactions: i64,
+    pub reactions_by_emoji: HashMap<String, i64>,
+    pub participants: Vec<ParticipantEngagement>,
+}
+
+// Transcript Models
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TranscriptStatus {
+    AwaitingConsent,
+    Live,
+    Processing,
+    Completed,
+    Declined,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TranscriptSource {
+    Captions,
+    Recording,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranscriptResponse {
+    pub transcript_id: Uuid,
+    pub status: TranscriptStatus,
+    pub started_at: DateTime<Utc>,
+    /// Participants who have not consented yet
+    #[serde(default)]
+    pub pending_consent: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaptionConsentRequest {
+    pub granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub speaker_id: Option<Uuid>,
+    pub speaker_name: Option<String>,
+    /// Milliseconds since the transcript started
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallTranscript {
+    pub transcript_id: Uuid,
+    pub call_id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub source: TranscriptSource,
+    pub status: TranscriptStatus,
+    pub language: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Session note the transcript was attached to
+    pub note_id: Option<Uuid>,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+// Breakout Room Models
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BreakoutAssignment {
+    Manual,
+    Random,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBreakoutRoomsRequest {
+    pub room_count: usize,
+    pub assignment: BreakoutAssignment,
+    /// Room index per participant for manual assignment; anyone left out
+    /// stays in the main room
+    #[serde(default)]
+    pub assignments: HashMap<Uuid, usize>,
+    /// Rooms close automatically after this long
+    pub duration_minutes: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveBreakoutParticipantRequest {
+    /// Target room, or None to send the participant back to the main room
+    pub room_index: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BreakoutBroadcastRequest {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakoutRoomResponse {
+    pub breakout_call_id: Uuid,
+    pub name: String,
+    pub participant_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BreakoutSessionResponse {
+    pub call_id: Uuid,
+    pub rooms: Vec<BreakoutRoomResponse>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
 // Analytics Models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CallAnalytics {
@@ -347,6 +885,66 @@ pub struct CallStatistics {
     pub peak_concurrent_calls: i32,
 }
 
+// Calls flagged for a possible refund because of poor quality
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CallQualityFlag {
+    pub call_id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub average_mos: f32,
+    pub poor_ratio: f32,
+    pub reason: String,
+    pub status: String,
+    pub flagged_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveQualityFlagRequest {
+    /// refunded or dismissed
+    pub status: String,
+}
+
+// Call Log Models
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CallDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallLogQuery {
+    pub limit: Option<u32>,
+    pub before_call_id: Option<Uuid>,
+    pub direction: Option<CallDirection>,
+    /// Final state of the call: Ended, Missed, Rejected, Cancelled or Failed
+    pub outcome: Option<CallState>,
+    pub min_duration_seconds: Option<i32>,
+    pub max_duration_seconds: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallLogEntry {
+    pub call_id: Uuid,
+    pub direction: CallDirection,
+    /// The callee of outgoing calls and the caller of incoming ones
+    pub other_party_id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub call_type: CallType,
+    pub outcome: CallState,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_seconds: i32,
+    /// The call placed to return this one, if any
+    pub callback_call_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallLogResponse {
+    pub calls: Vec<CallLogEntry>,
+    pub has_more: bool,
+}
+
 // Error Types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CallError {
@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use uuid::Uuid;
+
+use crate::models::{CallQualityMetrics, QualityRating, QualitySuggestion};
+
+// Window MOS below which a sample counts as poor
+const POOR_MOS: f32 = 3.1;
+// Calls are flagged for a possible refund when their average MOS is below
+// POOR_MOS or at least this share of their samples were poor
+const FLAG_POOR_RATIO: f32 = 0.3;
+// Samples needed before a participant gets advice or a call can be flagged
+const MIN_SAMPLES: usize = 3;
+// Unchanged advice is repeated at most this often
+const ADVICE_COOLDOWN_SECONDS: i64 = 30;
+
+/// Turns the quality reports clients send into an estimated MOS per
+/// participant, averaged over a sliding window, and decides what clients
+/// should do about poor quality.
+#[derive(Clone)]
+pub struct QualityEngine {
+    window: chrono::Duration,
+    participants: Arc<DashMap<(Uuid, Uuid), ParticipantWindow>>,
+    calls: Arc<DashMap<Uuid, CallTotals>>,
+}
+
+#[derive(Default)]
+struct ParticipantWindow {
+    samples: VecDeque<Sample>,
+    last_advice: Option<(Vec<QualitySuggestion>, DateTime<Utc>)>,
+}
+
+struct Sample {
+    at: DateTime<Utc>,
+    mos: f32,
+    packet_loss: f32,
+    rtt: u32,
+    bandwidth: Option<u32>,
+    sending_video: bool,
+}
+
+#[derive(Default)]
+struct CallTotals {
+    samples: u32,
+    mos_sum: f32,
+    poor_samples: u32,
+}
+
+/// Quality of one participant's connection after a report.
+#[derive(Debug, Clone)]
+pub struct QualityAssessment {
+    /// Average MOS over the window
+    pub mos: f32,
+    pub rating: QualityRating,
+    pub suggestions: Vec<QualitySuggestion>,
+    /// Whether the suggestions should be pushed to the client now
+    pub should_advise: bool,
+}
+
+/// Quality of a finished call.
+#[derive(Debug, Clone)]
+pub struct CallQualitySummary {
+    pub average_mos: f32,
+    pub poor_ratio: f32,
+    pub samples: u32,
+    /// Why the call should be considered for a refund, if it should
+    pub flag_reason: Option<String>,
+}
+
+impl QualityEngine {
+    pub fn new(window_seconds: i64) -> Self {
+        Self {
+            window: chrono::Duration::seconds(window_seconds),
+            participants: Arc::new(DashMap::new()),
+            calls: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Adds a report to the participant's window. Returns None when the
+    /// report has nothing to estimate MOS from.
+    pub fn record(&self, call_id: Uuid, user_id: Uuid, metrics: &CallQualityMetrics) -> Option<QualityAssessment> {
+        if metrics.packet_loss.is_none() && metrics.jitter.is_none() && metrics.rtt.is_none() {
+            return None;
+        }
+
+        let packet_loss = metrics.packet_loss.unwrap_or(0.0).clamp(0.0, 1.0);
+        let jitter = metrics.jitter.unwrap_or(0.0).max(0.0);
+        let rtt = metrics.rtt.unwrap_or(0);
+        let sample = Sample {
+            at: metrics.timestamp,
+            mos: estimate_mos(packet_loss, jitter, rtt),
+            packet_loss,
+            rtt,
+            bandwidth: metrics.bandwidth,
+            sending_video: metrics.video_bitrate.map_or(false, |bitrate| bitrate > 0),
+        };
+
+        let mut window = self.participants.entry((call_id, user_id)).or_default();
+        let cutoff = sample.at - self.window;
+        window.samples.push_back(sample);
+        while window.samples.front().map_or(false, |oldest| oldest.at < cutoff) {
+            window.samples.pop_front();
+        }
+
+        let count = window.samples.len();
+        let mos = window.samples.iter().map(|sample| sample.mos).sum::<f32>() / count as f32;
+        let packet_loss = window.samples.iter().map(|sample| sample.packet_loss).sum::<f32>() / count as f32;
+        // Summed as u64 since the RTTs are client-reported and may be huge
+        let rtt = (window.samples.iter().map(|sample| sample.rtt as u64).sum::<u64>() / count as u64) as u32;
+        let latest = window.samples.back()?;
+        let suggestions = suggest(mos, packet_loss, rtt, latest.bandwidth, latest.sending_video);
+
+        {
+            let mut totals = self.calls.entry(call_id).or_default();
+            totals.samples += 1;
+            totals.mos_sum += mos;
+            if mos < POOR_MOS {
+                totals.poor_samples += 1;
+            }
+        }
+
+        // Advise once the window has settled, when the advice changes or
+        // the same problem persists past the cooldown
+        let now = Utc::now();
+        let should_advise = count >= MIN_SAMPLES && match &window.last_advice {
+            Some((previous, at)) => {
+                *previous != suggestions
+                    || (!suggestions.is_empty() && (now - *at).num_seconds() >= ADVICE_COOLDOWN_SECONDS)
+            }
+            None => !suggestions.is_empty(),
+        };
+        if should_advise {
+            window.last_advice = Some((suggestions.clone(), now));
+        }
+
+        Some(QualityAssessment {
+            mos,
+            rating: QualityRating::from_mos(mos),
+            suggestions,
+            should_advise,
+        })
+    }
+
+    /// Average window MOS of everyone in the call so far.
+    pub fn call_score(&self, call_id: Uuid) -> Option<f32> {
+        self.calls.get(&call_id)
+            .filter(|totals| totals.samples > 0)
+            .map(|totals| totals.mos_sum / totals.samples as f32)
+    }
+
+    /// Forgets a participant's window, e.g. when they leave.
+    pub fn remove_participant(&self, call_id: Uuid, user_id: Uuid) {
+        self.participants.remove(&(call_id, user_id));
+    }
+
+    /// Drops the call's state and summarizes its quality.
+    pub fn finish_call(&self, call_id: Uuid) -> Option<CallQualitySummary> {
+        self.participants.retain(|(id, _), _| *id != call_id);
+        let (_, totals) = self.calls.remove(&call_id)?;
+
+        if (totals.samples as usize) < MIN_SAMPLES {
+            return None;
+        }
+
+        let average_mos = totals.mos_sum / totals.samples as f32;
+        let poor_ratio = totals.poor_samples as f32 / totals.samples as f32;
+        let flag_reason = if average_mos < POOR_MOS {
+            Some(format!("Average MOS {:.2} is below {:.1}", average_mos, POOR_MOS))
+        } else if poor_ratio >= FLAG_POOR_RATIO {
+            Some(format!("Quality was poor for {:.0}% of the call", poor_ratio * 100.0))
+        } else {
+            None
+        };
+
+        Some(CallQualitySummary {
+            average_mos,
+            poor_ratio,
+            samples: totals.samples,
+            flag_reason,
+        })
+    }
+}
+
+/// Estimates MOS with the simplified ITU-T G.107 E-model. `packet_loss` is
+/// a fraction, `jitter` and `rtt` are in milliseconds.
+pub fn estimate_mos(packet_loss: f32, jitter: f32, rtt: u32) -> f32 {
+    let effective_latency = rtt as f32 / 2.0 + jitter * 2.0 + 10.0;
+
+    let mut r = if effective_latency < 160.0 {
+        93.2 - effective_latency / 40.0
+    } else {
+        93.2 - (effective_latency - 120.0) / 10.0
+    };
+    r -= packet_loss * 100.0 * 2.5;
+    let r = r.clamp(0.0, 100.0);
+
+    (1.0 + 0.035 * r + 0.000007 * r * (r - 60.0) * (100.0 - r)).clamp(1.0, 4.5)
+}
+
+// What a client should change given its recent quality; `packet_loss` and
+// `rtt` are window averages
+fn suggest(
+    mos: f32,
+    packet_loss: f32,
+    rtt: u32,
+    bandwidth: Option<u32>,
+    sending_video: bool,
+) -> Vec<QualitySuggestion> {
+    let mut suggestions = Vec::new();
+
+    // Heavy loss or very high latency over UDP usually means a relay path works better
+    if packet_loss >= 0.15 || rtt >= 500 {
+        suggestions.push(QualitySuggestion::SwitchToTurnTcp);
+    }
+
+    if sending_video {
+        let low_bandwidth = bandwidth.map_or(false, |bandwidth| bandwidth < 300_000);
+        if mos < 2.6 || packet_loss >= 0.10 || low_bandwidth {
+            suggestions.push(QualitySuggestion::DisableVideo);
+        } else if mos < POOR_MOS + 0.5 || packet_loss >= 0.03 || bandwidth.map_or(false, |bandwidth| bandwidth < 800_000) {
+            suggestions.push(QualitySuggestion::LowerResolution);
+        }
+    }
+
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(packet_loss: f32, jitter: f32, rtt: u32, at: DateTime<Utc>) -> CallQualityMetrics {
+        CallQualityMetrics {
+            audio_bitrate: Some(32_000),
+            video_bitrate: Some(1_000_000),
+            packet_loss: Some(packet_loss),
+            jitter: Some(jitter),
+            rtt: Some(rtt),
+            bandwidth: Some(2_000_000),
+            resolution: Some("1280x720".to_string()),
+            frame_rate: Some(30),
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_estimate_mos() {
+        assert!(estimate_mos(0.0, 2.0, 40) > 4.3);
+        assert!(estimate_mos(0.02, 20.0, 150) > 3.6);
+        assert!(estimate_mos(0.10, 50.0, 400) < 3.1);
+        assert_eq!(estimate_mos(1.0, 0.0, 0), 1.0);
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert!(suggest(4.4, 0.0, 40, Some(2_000_000), true).is_empty());
+        assert_eq!(suggest(3.4, 0.04, 100, Some(2_000_000), true), vec![QualitySuggestion::LowerResolution]);
+        assert_eq!(
+            suggest(1.5, 0.2, 600, Some(2_000_000), true),
+            vec![QualitySuggestion::SwitchToTurnTcp, QualitySuggestion::DisableVideo]
+        );
+        assert_eq!(suggest(1.5, 0.2, 100, None, false), vec![QualitySuggestion::SwitchToTurnTcp]);
+    }
+
+    #[test]
+    fn test_window_and_call_flag() {
+        let engine = QualityEngine::new(30);
+        let (call_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let start = Utc::now();
+
+        // Good samples age out of the window once the connection degrades
+        for second in 0..3 {
+            let assessment = engine.record(call_id, user_id, &metrics(0.0, 2.0, 40, start + chrono::Duration::seconds(second))).unwrap();
+            assert!(!assessment.should_advise);
+        }
+        let mut assessment = None;
+        for second in 60..63 {
+            assessment = engine.record(call_id, user_id, &metrics(0.12, 60.0, 300, start + chrono::Duration::seconds(second)));
+        }
+        let assessment = assessment.unwrap();
+        assert_eq!(assessment.rating, QualityRating::Bad);
+        assert!(assessment.should_advise);
+        assert_eq!(assessment.suggestions, vec![QualitySuggestion::DisableVideo]);
+
+        // The same advice isn't repeated straight away
+        let assessment = engine.record(call_id, user_id, &metrics(0.12, 60.0, 300, start + chrono::Duration::seconds(63))).unwrap();
+        assert!(!assessment.should_advise);
+
+        let summary = engine.finish_call(call_id).unwrap();
+        assert_eq!(summary.samples, 7);
+        assert!(summary.flag_reason.is_some());
+        assert!(engine.call_score(call_id).is_none());
+    }
+
+    #[test]
+    fn test_huge_rtts_do_not_overflow() {
+        let engine = QualityEngine::new(30);
+        let (call_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let start = Utc::now();
+
+        engine.record(call_id, user_id, &metrics(0.0, 0.0, u32::MAX, start)).unwrap();
+        let assessment = engine.record(call_id, user_id, &metrics(0.0, 0.0, u32::MAX, start)).unwrap();
+        assert_eq!(assessment.rating, QualityRating::Bad);
+    }
+}
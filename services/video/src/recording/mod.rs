@@ -0,0 +1,611 @@
+pub mod muxer;
+pub mod storage;
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use linkwithmentor_common::{ApiResponse, AppError};
+use crate::{
+    call_manager::CallManager,
+    config::{RecordingConfig, VideoServiceConfig},
+    models::{
+        ActiveCall, CallTopology, RecordingResponse, RecordingStatus, SignalingMessage,
+        StartRecordingRequest,
+    },
+    sfu::{
+        recorder::{RecordedTrackFile, RoomRecorder},
+        SfuService,
+    },
+    transcription::TranscriptionService,
+};
+
+use self::{
+    muxer::OutputFormat,
+    storage::RecordingStorage,
+};
+
+/// Server-side call recording. A recording starts once every participant in
+/// the call has consented; media is captured by the SFU (or, for
+/// peer-to-peer calls, a recorder peer each client publishes to), muxed
+/// after the recording stops, stored through the configured backend and
+/// handed to the video lectures service as a private lecture.
+#[derive(Clone)]
+pub struct RecordingService {
+    db_pool: PgPool,
+    config: RecordingConfig,
+    scratch_path: PathBuf,
+    enabled: bool,
+    call_manager: CallManager,
+    sfu_service: SfuService,
+    storage: Arc<dyn RecordingStorage>,
+    transcription_service: TranscriptionService,
+    http_client: reqwest::Client,
+    // Active recordings by call
+    active: Arc<DashMap<Uuid, ActiveRecording>>,
+}
+
+#[derive(Debug, Clone)]
+struct ActiveRecording {
+    recording_id: Uuid,
+    call_id: Uuid,
+    requested_by: Uuid,
+    title: String,
+    format: OutputFormat,
+    quality: String,
+    status: RecordingStatus,
+    // Participants whose consent is needed before recording starts
+    required: HashSet<Uuid>,
+    consented: HashSet<Uuid>,
+    started_at: Option<DateTime<Utc>>,
+}
+
+impl ActiveRecording {
+    fn pending_consent(&self) -> Vec<Uuid> {
+        self.required.difference(&self.consented).copied().collect()
+    }
+
+    fn response(&self) -> RecordingResponse {
+        RecordingResponse {
+            recording_id: self.recording_id,
+            status: self.status.clone(),
+            file_path: None,
+            duration_seconds: self.started_at.map(|at| (Utc::now() - at).num_seconds() as i32),
+            pending_consent: self.pending_consent(),
+            lecture_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedLecture {
+    lecture_id: Uuid,
+}
+
+impl RecordingService {
+    pub fn new(
+        db_pool: PgPool,
+        config: &RecordingConfig,
+        video_config: &VideoServiceConfig,
+        call_manager: CallManager,
+        sfu_service: SfuService,
+        storage: Arc<dyn RecordingStorage>,
+        transcription_service: TranscriptionService,
+    ) -> Self {
+        Self {
+            db_pool,
+            config: config.clone(),
+            scratch_path: PathBuf::from(&video_config.recording_storage_path),
+            enabled: video_config.recording_enabled && video_config.enable_call_recording,
+            call_manager,
+            sfu_service,
+            storage,
+            transcription_service,
+            http_client: reqwest::Client::new(),
+            active: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Asks everyone else in the call to consent to a recording. The
+    /// requester's consent is implied.
+    pub async fn request_recording(
+        &self,
+        call: &ActiveCall,
+        user_id: Uuid,
+        request: StartRecordingRequest,
+    ) -> Result<RecordingResponse, AppError> {
+        if !self.enabled {
+            return Err(AppError::BadRequest("Call recording is not enabled".to_string()));
+        }
+        if self.active.contains_key(&call.call_id) {
+            return Err(AppError::Conflict("Call already has a recording in progress".to_string()));
+        }
+
+        let format = OutputFormat::parse(request.format.as_deref().unwrap_or(&self.config.default_format))?;
+        let quality = request.quality.unwrap_or_else(|| "medium".to_string());
+        let title = request.title.unwrap_or_else(|| {
+            format!("Call recording {}", Utc::now().format("%Y-%m-%d %H:%M UTC"))
+        });
+
+        let required: HashSet<Uuid> = self.call_manager
+            .get_active_participants(call.call_id)
+            .await
+            .into_iter()
+            .collect();
+
+        let recording_id = Uuid::new_v4();
+        let query = r#"
+            INSERT INTO call_recordings (
+                recording_id, call_id, requested_by, file_path, format, quality, status, title
+            ) VALUES ($1, $2, $3, '', $4, $5, $6, $7)
+        "#;
+
+        sqlx::query(query)
+            .bind(recording_id)
+            .bind(call.call_id)
+            .bind(user_id)
+            .bind(format.extension())
+            .bind(&quality)
+            .bind(&RecordingStatus::AwaitingConsent)
+            .bind(&title)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create recording: {}", e)))?;
+
+        let recording = ActiveRecording {
+            recording_id,
+            call_id: call.call_id,
+            requested_by: user_id,
+            title,
+            format,
+            quality,
+            status: RecordingStatus::AwaitingConsent,
+            required,
+            consented: HashSet::new(),
+            started_at: None,
+        };
+        self.active.insert(call.call_id, recording);
+
+        let message = SignalingMessage::RecordingConsentRequested {
+            call_id: call.call_id,
+            recording_id,
+            requested_by: user_id,
+        };
+        for participant_id in self.call_manager.get_active_participants(call.call_id).await {
+            if participant_id != user_id {
+                self.send_to_user(participant_id, &message).await;
+            }
+        }
+
+        tracing::info!("User {} requested recording {} of call {}", user_id, recording_id, call.call_id);
+
+        self.respond_to_consent(call, user_id, true).await
+    }
+
+    /// Records a participant's answer. A refusal cancels a pending recording;
+    /// once everyone has agreed, recording starts. Participants who join
+    /// later are only recorded after they consent too.
+    pub async fn respond_to_consent(
+        &self,
+        call: &ActiveCall,
+        user_id: Uuid,
+        granted: bool,
+    ) -> Result<RecordingResponse, AppError> {
+        let recording = self.active.get(&call.call_id)
+            .map(|recording| recording.clone())
+            .ok_or_else(|| AppError::NotFound("No recording requested for this call".to_string()))?;
+
+        let query = r#"
+            INSERT INTO call_recording_consents (recording_id, user_id, granted, responded_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (recording_id, user_id) DO UPDATE SET
+                granted = EXCLUDED.granted,
+                responded_at = EXCLUDED.responded_at
+        "#;
+
+        sqlx::query(query)
+            .bind(recording.recording_id)
+            .bind(user_id)
+            .bind(granted)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record consent: {}", e)))?;
+
+        if !granted {
+            if recording.status != RecordingStatus::AwaitingConsent {
+                // Already recording: this participant simply stays unrecorded
+                return Ok(recording.response());
+            }
+            return self.decline(&recording, user_id).await;
+        }
+
+        let recording = {
+            let mut entry = self.active.get_mut(&call.call_id)
+                .ok_or_else(|| AppError::NotFound("No recording requested for this call".to_string()))?;
+            entry.consented.insert(user_id);
+            entry.clone()
+        };
+
+        match recording.status {
+            RecordingStatus::AwaitingConsent if recording.pending_consent().is_empty() => {
+                self.begin(call, recording).await
+            }
+            RecordingStatus::Recording => {
+                self.sfu_service.add_recording_consent(call.call_id, user_id);
+                Ok(recording.response())
+            }
+            _ => Ok(recording.response()),
+        }
+    }
+
+    /// Stops the call's recording and processes it in the background.
+    pub async fn stop_recording(&self, call_id: Uuid) -> Result<RecordingResponse, AppError> {
+        let (_, mut recording) = self.active.remove(&call_id)
+            .ok_or_else(|| AppError::NotFound("Call is not being recorded".to_string()))?;
+
+        if recording.status == RecordingStatus::AwaitingConsent {
+            self.update_status(recording.recording_id, RecordingStatus::Failed).await?;
+            recording.status = RecordingStatus::Failed;
+            self.notify_participants(call_id, &SignalingMessage::RecordingStopped {
+                call_id,
+                recording_id: recording.recording_id,
+            }).await;
+            return Ok(recording.response());
+        }
+
+        let tracks = self.sfu_service.stop_recording(call_id).await;
+        self.call_manager.set_recording_active(call_id, false).await;
+        self.update_status(recording.recording_id, RecordingStatus::Processing).await?;
+
+        self.notify_participants(call_id, &SignalingMessage::RecordingStopped {
+            call_id,
+            recording_id: recording.recording_id,
+        }).await;
+
+        let response = RecordingResponse {
+            status: RecordingStatus::Processing,
+            ..recording.response()
+        };
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            let recording_id = recording.recording_id;
+            if let Err(e) = service.finalize(recording, tracks).await {
+                tracing::error!("Failed to process recording {}: {}", recording_id, e);
+                let _ = service.fail(recording_id, &e.to_string()).await;
+            }
+        });
+
+        Ok(response)
+    }
+
+    /// Stops any recording when its call ends. Must run before the SFU room
+    /// is closed.
+    pub async fn handle_call_ended(&self, call_id: Uuid) {
+        if !self.active.contains_key(&call_id) {
+            return;
+        }
+
+        if let Err(e) = self.stop_recording(call_id).await {
+            tracing::error!("Failed to stop recording of ended call {}: {}", call_id, e);
+        }
+    }
+
+    /// Settles a pending recording when someone leaves before answering:
+    /// their consent is no longer needed, or the request is cancelled if the
+    /// requester is the one who left.
+    pub async fn handle_participant_left(&self, call: &ActiveCall, user_id: Uuid) {
+        let recording = {
+            let mut entry = match self.active.get_mut(&call.call_id) {
+                Some(entry) if entry.status == RecordingStatus::AwaitingConsent => entry,
+                _ => return,
+            };
+            if entry.requested_by != user_id {
+                entry.required.remove(&user_id);
+                entry.consented.remove(&user_id);
+            }
+            entry.clone()
+        };
+
+        let result = if recording.requested_by == user_id {
+            self.stop_recording(call.call_id).await.map(|_| ())
+        } else if recording.pending_consent().is_empty() {
+            self.begin(call, recording).await.map(|_| ())
+        } else {
+            Ok(())
+        };
+
+        if let Err(e) = result {
+            tracing::error!("Failed to settle recording of call {} after {} left: {}", call.call_id, user_id, e);
+        }
+    }
+
+    pub async fn get_recording_status(&self, call_id: Uuid) -> Result<RecordingResponse, AppError> {
+        if let Some(recording) = self.active.get(&call_id) {
+            return Ok(recording.response());
+        }
+
+        let row = sqlx::query_as::<_, RecordingRow>(
+            r#"
+            SELECT recording_id, status, file_path, duration_seconds, lecture_id
+            FROM call_recordings
+            WHERE call_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(call_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch recording: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("Call has no recordings".to_string()))?;
+
+        Ok(RecordingResponse {
+            recording_id: row.recording_id,
+            status: row.status,
+            file_path: (!row.file_path.is_empty()).then_some(row.file_path),
+            duration_seconds: Some(row.duration_seconds),
+            pending_consent: Vec::new(),
+            lecture_id: row.lecture_id,
+        })
+    }
+
+    // Recording lifecycle
+
+    async fn begin(&self, call: &ActiveCall, mut recording: ActiveRecording) -> Result<RecordingResponse, AppError> {
+        let dir = self.scratch_path.join(recording.recording_id.to_string());
+        tokio::fs::create_dir_all(&dir).await
+            .map_err(|e| AppError::Internal(format!("Failed to create recording directory: {}", e)))?;
+
+        // Peer-to-peer media never reaches the server, so clients publish to a recorder peer
+        let publish_to_recorder = call.topology == CallTopology::Mesh;
+        if publish_to_recorder {
+            self.sfu_service.open_recorder_room(call.call_id);
+        }
+
+        let recorder = Arc::new(RoomRecorder::new(recording.recording_id, dir, recording.consented.clone()));
+        self.sfu_service.start_recording(call.call_id, recorder)?;
+        self.call_manager.set_recording_active(call.call_id, true).await;
+
+        let now = Utc::now();
+        sqlx::query("UPDATE call_recordings SET status = $1, started_at = $2 WHERE recording_id = $3")
+            .bind(&RecordingStatus::Recording)
+            .bind(now)
+            .bind(recording.recording_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start recording: {}", e)))?;
+
+        recording.status = RecordingStatus::Recording;
+        recording.started_at = Some(now);
+        if let Some(mut entry) = self.active.get_mut(&call.call_id) {
+            entry.status = RecordingStatus::Recording;
+            entry.started_at = Some(now);
+        }
+
+        self.notify_participants(call.call_id, &SignalingMessage::RecordingStarted {
+            call_id: call.call_id,
+            recording_id: recording.recording_id,
+            publish_to_recorder,
+        }).await;
+
+        tracing::info!("Recording {} of call {} started", recording.recording_id, call.call_id);
+        Ok(recording.response())
+    }
+
+    async fn decline(&self, recording: &ActiveRecording, user_id: Uuid) -> Result<RecordingResponse, AppError> {
+        self.active.remove(&recording.call_id);
+        self.update_status(recording.recording_id, RecordingStatus::Declined).await?;
+
+        self.notify_participants(recording.call_id, &SignalingMessage::RecordingDeclined {
+            call_id: recording.call_id,
+            recording_id: recording.recording_id,
+            declined_by: user_id,
+        }).await;
+
+        tracing::info!("User {} declined recording {}", user_id, recording.recording_id);
+
+        Ok(RecordingResponse {
+            status: RecordingStatus::Declined,
+            ..recording.response()
+        })
+    }
+
+    /// Muxes the recorded tracks, stores the result and creates the lecture.
+    async fn finalize(&self, recording: ActiveRecording, tracks: Vec<RecordedTrackFile>) -> Result<(), AppError> {
+        let dir = self.scratch_path.join(recording.recording_id.to_string());
+        let output = dir.join(format!("recording.{}", recording.format.extension()));
+
+        muxer::mux(&self.config.ffmpeg_path, &tracks, &output, recording.format, &recording.quality).await?;
+
+        let file_size = tokio::fs::metadata(&output).await
+            .map_err(|e| AppError::Internal(format!("Failed to read muxed recording: {}", e)))?
+            .len() as i64;
+
+        let key = format!(
+            "recordings/{}/{}.{}",
+            recording.call_id, recording.recording_id, recording.format.extension()
+        );
+        self.storage.store(&key, &output).await?;
+
+        let ended_at = Utc::now();
+        let duration_seconds = recording.started_at
+            .map(|at| (ended_at - at).num_seconds() as i32)
+            .unwrap_or(0);
+
+        let query = r#"
+            UPDATE call_recordings
+            SET file_path = $1, file_size = $2, duration_seconds = $3, ended_at = $4,
+                storage_provider = $5
+            WHERE recording_id = $6
+        "#;
+
+        let stored = sqlx::query(query)
+            .bind(&key)
+            .bind(file_size)
+            .bind(duration_seconds)
+            .bind(ended_at)
+            .bind(self.storage.provider())
+            .bind(recording.recording_id)
+            .execute(&self.db_pool)
+            .await;
+
+        if let Err(e) = stored {
+            // Don't leave an object nothing refers to
+            let _ = self.storage.delete(&key).await;
+            return Err(AppError::Database(format!("Failed to save recording: {}", e)));
+        }
+
+        sqlx::query("UPDATE call_sessions SET recording_path = $1 WHERE call_id = $2")
+            .bind(&key)
+            .bind(recording.call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update call recording path: {}", e)))?;
+
+        // Calls without captions get their transcript from the recording
+        let transcribed = self.transcription_service
+            .transcribe_recording(
+                recording.call_id,
+                recording.requested_by,
+                recording.started_at.unwrap_or(ended_at),
+                &tracks,
+            )
+            .await;
+        if let Err(e) = transcribed {
+            tracing::warn!("Failed to transcribe recording {}: {}", recording.recording_id, e);
+        }
+
+        if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+            tracing::warn!("Failed to clean up recording directory {}: {}", dir.display(), e);
+        }
+
+        let lecture_id = self.publish_lecture(&recording, &key, duration_seconds, file_size).await?;
+
+        sqlx::query("UPDATE call_recordings SET status = $1, lecture_id = $2 WHERE recording_id = $3")
+            .bind(&RecordingStatus::Completed)
+            .bind(lecture_id)
+            .bind(recording.recording_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to complete recording: {}", e)))?;
+
+        tracing::info!(
+            "Recording {} of call {} stored as {} and published as lecture {}",
+            recording.recording_id, recording.call_id, key, lecture_id
+        );
+
+        Ok(())
+    }
+
+    /// Creates a private lecture owned by the requester and queues the
+    /// stored file for processing.
+    async fn publish_lecture(
+        &self,
+        recording: &ActiveRecording,
+        key: &str,
+        duration_seconds: i32,
+        file_size: i64,
+    ) -> Result<Uuid, AppError> {
+        let payload = serde_json::json!({
+            "recording_id": recording.recording_id,
+            "call_id": recording.call_id,
+            "owner_id": recording.requested_by,
+            "title": recording.title,
+            "storage_key": key,
+            "format": recording.format.extension(),
+            "duration_seconds": duration_seconds,
+            "file_size": file_size,
+        });
+
+        let response = self.http_client
+            .post(format!("{}/internal/lectures/recordings", self.config.video_lectures_service_url))
+            .header("X-Internal-Token", &self.config.internal_service_token)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Video lectures service unavailable: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Video lectures service rejected recording: {}",
+                response.status()
+            )));
+        }
+
+        let body: ApiResponse<CreatedLecture> = response
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Invalid lecture response: {}", e)))?;
+
+        body.data
+            .map(|lecture| lecture.lecture_id)
+            .ok_or_else(|| AppError::ExternalService("Video lectures service returned no lecture".to_string()))
+    }
+
+    // Helpers
+
+    async fn update_status(&self, recording_id: Uuid, status: RecordingStatus) -> Result<(), AppError> {
+        sqlx::query("UPDATE call_recordings SET status = $1 WHERE recording_id = $2")
+            .bind(&status)
+            .bind(recording_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update recording status: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn fail(&self, recording_id: Uuid, error: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE call_recordings SET status = $1, error_message = $2 WHERE recording_id = $3")
+            .bind(&RecordingStatus::Failed)
+            .bind(error)
+            .bind(recording_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to mark recording failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn notify_participants(&self, call_id: Uuid, message: &SignalingMessage) {
+        for participant_id in self.call_manager.get_active_participants(call_id).await {
+            self.send_to_user(participant_id, message).await;
+        }
+    }
+
+    async fn send_to_user(&self, user_id: Uuid, message: &SignalingMessage) {
+        let message_json = match serde_json::to_string(message) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Failed to serialize recording message: {}", e);
+                return;
+            }
+        };
+
+        let ws_message = tokio_tungstenite::tungstenite::Message::Text(message_json);
+        for connection in self.call_manager.get_user_connections(user_id).await {
+            if let Err(e) = connection.sender.send(ws_message.clone()) {
+                tracing::warn!("Failed to send recording message to user {}: {}", user_id, e);
+            }
+        }
+    }
+}
+
+// Database row structs
+
+#[derive(sqlx::FromRow)]
+struct RecordingRow {
+    recording_id: Uuid,
+    status: RecordingStatus,
+    file_path: String,
+    duration_seconds: i32,
+    lecture_id: Option<Uuid>,
+}
@@ -0,0 +1,233 @@
+use std::path::Path;
+
+use linkwithmentor_common::AppError;
+use crate::sfu::recorder::RecordedTrackFile;
+
+// Size of each participant's tile in the composited video
+const TILE_WIDTH: u32 = 640;
+const TILE_HEIGHT: u32 = 360;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    WebM,
+    Mp4,
+}
+
+impl OutputFormat {
+    pub fn parse(format: &str) -> Result<Self, AppError> {
+        match format.to_lowercase().as_str() {
+            "webm" => Ok(OutputFormat::WebM),
+            "mp4" => Ok(OutputFormat::Mp4),
+            other => Err(AppError::BadRequest(format!("Unsupported recording format: {}", other))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::WebM => "webm",
+            OutputFormat::Mp4 => "mp4",
+        }
+    }
+}
+
+fn video_bitrate(quality: &str) -> &'static str {
+    match quality {
+        "low" => "500k",
+        "high" => "2500k",
+        _ => "1000k",
+    }
+}
+
+/// Builds the ffmpeg arguments that combine per-track recordings into one
+/// file: audio tracks are mixed and video tracks are tiled in a grid, each
+/// delayed by when its track started.
+pub fn ffmpeg_args(
+    tracks: &[RecordedTrackFile],
+    output: &Path,
+    format: OutputFormat,
+    quality: &str,
+) -> Vec<String> {
+    let mut args = vec!["-y".to_string(), "-hide_banner".to_string()];
+
+    for track in tracks {
+        args.push("-itsoffset".to_string());
+        args.push(format!("{:.3}", track.offset_ms as f64 / 1000.0));
+        args.push("-i".to_string());
+        args.push(track.path.display().to_string());
+    }
+
+    let video_inputs: Vec<usize> = tracks.iter().enumerate()
+        .filter(|(_, track)| track.is_video)
+        .map(|(index, _)| index)
+        .collect();
+    let audio_inputs: Vec<usize> = tracks.iter().enumerate()
+        .filter(|(_, track)| !track.is_video)
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut filters = Vec::new();
+    let mut maps = Vec::new();
+
+    if !video_inputs.is_empty() {
+        let mut tiles = String::new();
+        for (tile, input) in video_inputs.iter().enumerate() {
+            filters.push(format!(
+                "[{}:v]scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1[v{}]",
+                input, tile, w = TILE_WIDTH, h = TILE_HEIGHT
+            ));
+            tiles.push_str(&format!("[v{}]", tile));
+        }
+
+        if video_inputs.len() == 1 {
+            maps.push("[v0]".to_string());
+        } else {
+            filters.push(format!(
+                "{}xstack=inputs={}:layout={}:fill=black[vout]",
+                tiles,
+                video_inputs.len(),
+                grid_layout(video_inputs.len())
+            ));
+            maps.push("[vout]".to_string());
+        }
+    }
+
+    match audio_inputs.len() {
+        0 => {}
+        1 => maps.push(format!("{}:a", audio_inputs[0])),
+        count => {
+            let inputs: String = audio_inputs.iter().map(|input| format!("[{}:a]", input)).collect();
+            filters.push(format!("{}amix=inputs={}:duration=longest[aout]", inputs, count));
+            maps.push("[aout]".to_string());
+        }
+    }
+
+    if !filters.is_empty() {
+        args.push("-filter_complex".to_string());
+        args.push(filters.join(";"));
+    }
+    for map in maps {
+        args.push("-map".to_string());
+        args.push(map);
+    }
+
+    let codecs: &[&str] = match format {
+        OutputFormat::WebM => &["-c:v", "libvpx", "-deadline", "realtime", "-c:a", "libopus"],
+        OutputFormat::Mp4 => &["-c:v", "libx264", "-preset", "veryfast", "-c:a", "aac", "-movflags", "+faststart"],
+    };
+    args.extend(codecs.iter().map(|arg| arg.to_string()));
+
+    if !video_inputs.is_empty() {
+        args.push("-b:v".to_string());
+        args.push(video_bitrate(quality).to_string());
+    }
+
+    args.push(output.display().to_string());
+    args
+}
+
+/// Runs ffmpeg to produce `output` from the recorded tracks.
+pub async fn mux(
+    ffmpeg_path: &str,
+    tracks: &[RecordedTrackFile],
+    output: &Path,
+    format: OutputFormat,
+    quality: &str,
+) -> Result<(), AppError> {
+    if tracks.is_empty() {
+        return Err(AppError::BadRequest("Nothing was recorded".to_string()));
+    }
+
+    run_ffmpeg(ffmpeg_path, ffmpeg_args(tracks, output, format, quality)).await
+}
+
+/// Runs ffmpeg with `args`, surfacing the end of its log on failure.
+pub async fn run_ffmpeg(ffmpeg_path: &str, args: Vec<String>) -> Result<(), AppError> {
+    let result = tokio::process::Command::new(ffmpeg_path)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to run ffmpeg: {}", e)))?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        return Err(AppError::Internal(format!(
+            "ffmpeg exited with {}: {}",
+            result.status,
+            tail.into_iter().rev().collect::<Vec<_>>().join(" | ")
+        )));
+    }
+
+    Ok(())
+}
+
+// xstack layout placing `count` equal tiles in a near-square grid, row by row
+fn grid_layout(count: usize) -> String {
+    let columns = (count as f64).sqrt().ceil() as usize;
+
+    (0..count)
+        .map(|tile| {
+            let x = (tile % columns) as u32 * TILE_WIDTH;
+            let y = (tile / columns) as u32 * TILE_HEIGHT;
+            format!("{}_{}", x, y)
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn track(name: &str, is_video: bool, offset_ms: u64) -> RecordedTrackFile {
+        RecordedTrackFile {
+            publisher_id: Uuid::new_v4(),
+            track_id: name.to_string(),
+            is_video,
+            path: PathBuf::from(format!("/tmp/{}", name)),
+            offset_ms,
+        }
+    }
+
+    #[test]
+    fn test_grid_layout() {
+        assert_eq!(grid_layout(2), "0_0|640_0");
+        assert_eq!(grid_layout(3), "0_0|640_0|0_360");
+        assert_eq!(grid_layout(4), "0_0|640_0|0_360|640_360");
+    }
+
+    #[test]
+    fn test_ffmpeg_args_mix_audio_and_tile_video() {
+        let tracks = vec![
+            track("a.ivf", true, 0),
+            track("a.ogg", false, 0),
+            track("b.ivf", true, 1500),
+            track("b.ogg", false, 1500),
+        ];
+
+        let args = ffmpeg_args(&tracks, Path::new("/tmp/out.webm"), OutputFormat::WebM, "medium");
+
+        assert_eq!(&args[2..6], &["-itsoffset", "0.000", "-i", "/tmp/a.ivf"]);
+        assert!(args.contains(&"1.500".to_string()));
+
+        let filter = &args[args.iter().position(|arg| arg == "-filter_complex").unwrap() + 1];
+        assert!(filter.contains("[v0][v1]xstack=inputs=2:layout=0_0|640_0:fill=black[vout]"));
+        assert!(filter.contains("[1:a][3:a]amix=inputs=2:duration=longest[aout]"));
+        assert!(args.windows(2).any(|pair| pair == ["-map", "[vout]"]));
+        assert!(args.windows(2).any(|pair| pair == ["-map", "[aout]"]));
+        assert_eq!(args.last().unwrap(), "/tmp/out.webm");
+    }
+
+    #[test]
+    fn test_ffmpeg_args_audio_only() {
+        let tracks = vec![track("a.ogg", false, 0)];
+
+        let args = ffmpeg_args(&tracks, Path::new("/tmp/out.mp4"), OutputFormat::Mp4, "low");
+
+        assert!(!args.contains(&"-filter_complex".to_string()));
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0:a"]));
+        assert!(!args.contains(&"-b:v".to_string()));
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::{primitives::ByteStream, Client};
+
+use linkwithmentor_common::AppError;
+use crate::config::RecordingConfig;
+
+/// Where finished recordings are kept. Keys are relative paths such as
+/// `recordings/{call_id}/{recording_id}.webm`; the video lectures service
+/// reads the same key from its own storage when processing the lecture.
+#[async_trait]
+pub trait RecordingStorage: Send + Sync {
+    /// Copies a local file to `key`.
+    async fn store(&self, key: &str, source: &Path) -> Result<(), AppError>;
+
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+
+    fn provider(&self) -> &'static str;
+}
+
+pub async fn create_storage(config: &RecordingConfig) -> Result<Arc<dyn RecordingStorage>, AppError> {
+    match config.storage_provider.as_str() {
+        "local" => Ok(Arc::new(LocalStorage::new(&config.local_storage_path))),
+        "s3" => Ok(Arc::new(S3Storage::new(config).await)),
+        other => Err(AppError::Internal(format!("Unknown recording storage provider: {}", other))),
+    }
+}
+
+/// Keeps recordings on a local or mounted volume.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: &str) -> Self {
+        Self { root: PathBuf::from(root) }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, AppError> {
+        if key.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+            return Err(AppError::BadRequest(format!("Invalid storage key: {}", key)));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl RecordingStorage for LocalStorage {
+    async fn store(&self, key: &str, source: &Path) -> Result<(), AppError> {
+        let destination = self.path_for(key)?;
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await
+                .map_err(|e| AppError::Internal(format!("Failed to create {}: {}", parent.display(), e)))?;
+        }
+
+        tokio::fs::copy(source, &destination).await
+            .map_err(|e| AppError::Internal(format!("Failed to store recording {}: {}", key, e)))?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(format!("Failed to delete recording {}: {}", key, e))),
+        }
+    }
+
+    fn provider(&self) -> &'static str {
+        "local"
+    }
+}
+
+/// Keeps recordings in the S3 bucket shared with the video lectures service.
+pub struct S3Storage {
+    client: Client,
+    bucket_name: String,
+}
+
+impl S3Storage {
+    pub async fn new(config: &RecordingConfig) -> Self {
+        let aws_config = aws_config::defaults(BehaviorVersion::latest())
+            .region(aws_config::Region::new(config.region.clone()))
+            .load()
+            .await;
+
+        Self {
+            client: Client::new(&aws_config),
+            bucket_name: config.bucket_name.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl RecordingStorage for S3Storage {
+    async fn store(&self, key: &str, source: &Path) -> Result<(), AppError> {
+        let body = ByteStream::from_path(source).await
+            .map_err(|e| AppError::Internal(format!("Failed to read {}: {}", source.display(), e)))?;
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Failed to upload recording {}: {}", key, e)))?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Failed to delete recording {}: {}", key, e)))?;
+
+        Ok(())
+    }
+
+    fn provider(&self) -> &'static str {
+        "s3"
+    }
+}
@@ -26,10 +26,15 @@ pub fn create_routes() -> Router<AppState> {
         .route("/calls/:call_id/reject", post(handlers::reject_call))
         .route("/calls/:call_id/end", post(handlers::end_call))
         .route("/calls/:call_id", get(handlers::get_call_info))
+        .route("/calls/:call_id/call-back", post(handlers::call_back))
         
         // WebRTC signaling endpoints
         .route("/calls/:call_id/ice-candidate", post(handlers::add_ice_candidate))
         .route("/calls/:call_id/media-state", put(handlers::update_media_state))
+
+        // SFU negotiation (group calls)
+        .route("/calls/:call_id/sfu/offer", post(handlers::send_sfu_offer))
+        .route("/calls/:call_id/sfu/answer", post(handlers::send_sfu_answer))
         
         // Screen sharing endpoints
         .route("/calls/:call_id/screen-share/start", post(handlers::start_screen_share))
@@ -39,18 +44,45 @@ pub fn create_routes() -> Router<AppState> {
         .route("/calls/:call_id/quality", post(handlers::submit_quality_metrics))
         .route("/calls/:call_id/analytics", get(handlers::get_call_analytics))
         
+        // Raised hands and reactions
+        .route("/calls/:call_id/hands", get(handlers::get_hand_queue))
+        .route("/calls/:call_id/engagement", get(handlers::get_call_engagement))
+        .route("/sessions/:session_id/engagement", get(handlers::get_session_engagement))
+        
         // Recording endpoints
         .route("/calls/:call_id/recording/start", post(handlers::start_recording))
         .route("/calls/:call_id/recording/stop", post(handlers::stop_recording))
+        .route("/calls/:call_id/recording/consent", post(handlers::respond_recording_consent))
+        .route("/calls/:call_id/recording", get(handlers::get_recording_status))
+        
+        // Live captions and transcripts
+        .route("/calls/:call_id/captions/start", post(handlers::start_captions))
+        .route("/calls/:call_id/captions/stop", post(handlers::stop_captions))
+        .route("/calls/:call_id/captions/consent", post(handlers::respond_captions_consent))
+        .route("/calls/:call_id/transcript", get(handlers::get_call_transcript))
+        
+        // Breakout rooms
+        .route(
+            "/calls/:call_id/breakouts",
+            post(handlers::create_breakout_rooms)
+                .get(handlers::get_breakout_rooms)
+                .delete(handlers::close_breakout_rooms),
+        )
+        .route("/calls/:call_id/breakouts/broadcast", post(handlers::broadcast_to_breakout_rooms))
+        .route("/calls/:call_id/breakouts/participants/:user_id", put(handlers::move_breakout_participant))
         
         // ICE servers and TURN credentials
         .route("/ice-servers", get(handlers::get_ice_servers))
+        .route("/turn/secrets/rotate", post(handlers::rotate_turn_secret))
+        .route("/turn/users/:user_id/revoke", post(handlers::revoke_turn_credentials))
         
         // User call management
         .route("/users/calls", get(handlers::get_user_calls))
         
         // Platform statistics (admin only)
         .route("/statistics", get(handlers::get_call_statistics))
+        .route("/quality/flags", get(handlers::get_quality_flags))
+        .route("/quality/flags/:call_id", put(handlers::resolve_quality_flag))
         
         // Apply authentication middleware to all routes except health check and WebSocket
         .layer(middleware::from_fn_with_state(
@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use webrtc::media::io::ogg_writer::OggWriter;
+use webrtc::media::io::Writer;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+
+use linkwithmentor_common::AppError;
+
+/// A closed slice of one participant's audio, ready to be transcribed.
+#[derive(Debug, Clone)]
+pub struct CaptionChunk {
+    pub speaker_id: Uuid,
+    pub path: PathBuf,
+    /// When the chunk's first packet arrived, relative to when captions started
+    pub start_ms: u64,
+}
+
+/// Writes the audio tracks of consenting participants to short Ogg files and
+/// hands each one over as soon as it is `chunk_duration` long, so captions
+/// lag speech by roughly one chunk. Chunks are dropped while the recognizer's
+/// queue is full rather than letting captions fall further behind.
+pub struct CaptionTap {
+    dir: PathBuf,
+    started_at: Instant,
+    chunk_duration: Duration,
+    sender: mpsc::Sender<CaptionChunk>,
+    consented: RwLock<HashSet<Uuid>>,
+    // Open chunk per audio track, keyed like `SfuRoom::tracks`
+    chunks: Mutex<HashMap<String, ChunkWriter>>,
+    sequence: Mutex<u64>,
+}
+
+struct ChunkWriter {
+    chunk: CaptionChunk,
+    writer: OggWriter<File>,
+    opened_at: Instant,
+}
+
+impl CaptionTap {
+    pub fn new(
+        dir: PathBuf,
+        chunk_duration: Duration,
+        sender: mpsc::Sender<CaptionChunk>,
+        consented: HashSet<Uuid>,
+    ) -> Self {
+        Self {
+            dir,
+            started_at: Instant::now(),
+            chunk_duration,
+            sender,
+            consented: RwLock::new(consented),
+            chunks: Mutex::new(HashMap::new()),
+            sequence: Mutex::new(0),
+        }
+    }
+
+    /// Starts captioning a participant who consented after captions began.
+    pub fn add_consent(&self, user_id: Uuid) {
+        if let Ok(mut consented) = self.consented.write() {
+            consented.insert(user_id);
+        }
+    }
+
+    pub fn is_captioned(&self, publisher_id: Uuid) -> bool {
+        self.consented.read().map_or(false, |consented| consented.contains(&publisher_id))
+    }
+
+    /// Writes one Opus packet, closing the track's chunk once it is long enough.
+    pub fn write(&self, key: &str, publisher_id: Uuid, codec: &RTCRtpCodecCapability, packet: &Packet) {
+        if !codec.mime_type.eq_ignore_ascii_case("audio/opus") || !self.is_captioned(publisher_id) {
+            return;
+        }
+
+        let mut chunks = match self.chunks.lock() {
+            Ok(chunks) => chunks,
+            Err(_) => return,
+        };
+
+        if !chunks.contains_key(key) {
+            match self.open_chunk(publisher_id, codec) {
+                Ok(chunk) => {
+                    chunks.insert(key.to_string(), chunk);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to open caption chunk for {}: {}", key, e);
+                    return;
+                }
+            }
+        }
+
+        let full = match chunks.get_mut(key) {
+            Some(chunk) => {
+                if let Err(e) = chunk.writer.write_rtp(packet) {
+                    tracing::debug!("Failed to write packet of {} to caption chunk: {}", key, e);
+                }
+                chunk.opened_at.elapsed() >= self.chunk_duration
+            }
+            None => false,
+        };
+
+        if full {
+            if let Some(chunk) = chunks.remove(key) {
+                self.close_chunk(key, chunk);
+            }
+        }
+    }
+
+    /// Hands over every open chunk. Dropping the tap afterwards closes the
+    /// channel, which tells the transcriber no more audio is coming.
+    pub fn finish(&self) {
+        let mut chunks = match self.chunks.lock() {
+            Ok(chunks) => chunks,
+            Err(_) => return,
+        };
+
+        for (key, chunk) in chunks.drain() {
+            self.close_chunk(&key, chunk);
+        }
+    }
+
+    fn open_chunk(&self, publisher_id: Uuid, codec: &RTCRtpCodecCapability) -> Result<ChunkWriter, AppError> {
+        let sequence = match self.sequence.lock() {
+            Ok(mut sequence) => {
+                *sequence += 1;
+                *sequence
+            }
+            Err(_) => return Err(AppError::Internal("Caption chunk counter poisoned".to_string())),
+        };
+
+        let path = self.dir.join(format!("{}_{:06}.ogg", publisher_id, sequence));
+        let file = File::create(&path)
+            .map_err(|e| AppError::Internal(format!("Failed to create {}: {}", path.display(), e)))?;
+        let writer = OggWriter::new(file, codec.clock_rate, codec.channels.max(1))
+            .map_err(|e| AppError::Internal(format!("Failed to create Ogg writer: {}", e)))?;
+
+        Ok(ChunkWriter {
+            chunk: CaptionChunk {
+                speaker_id: publisher_id,
+                path,
+                start_ms: self.started_at.elapsed().as_millis() as u64,
+            },
+            writer,
+            opened_at: Instant::now(),
+        })
+    }
+
+    fn close_chunk(&self, key: &str, mut chunk: ChunkWriter) {
+        if let Err(e) = chunk.writer.close() {
+            tracing::warn!("Failed to close caption chunk of {}: {}", key, e);
+            return;
+        }
+
+        // The receiver is gone once captions stop; the chunk is cleaned up with the directory
+        match self.sender.try_send(chunk.chunk) {
+            Ok(()) | Err(mpsc::error::TrySendError::Closed(_)) => {}
+            Err(mpsc::error::TrySendError::Full(chunk)) => {
+                tracing::warn!("Caption recognizer is behind, dropping audio of {} at {} ms", chunk.speaker_id, chunk.start_ms);
+                let _ = std::fs::remove_file(&chunk.path);
+            }
+        }
+    }
+}
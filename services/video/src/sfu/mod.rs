@@ -0,0 +1,271 @@
+pub mod captions;
+pub mod recorder;
+pub mod room;
+pub mod simulcast;
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use uuid::Uuid;
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::MediaEngine;
+use webrtc::api::setting_engine::SettingEngine;
+use webrtc::api::{APIBuilder, API};
+use webrtc::ice_transport::ice_candidate_type::RTCIceCandidateType;
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::interceptor::registry::Registry;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpHeaderExtensionCapability, RTPCodecType};
+
+use linkwithmentor_common::AppError;
+use crate::{call_manager::CallManager, config::SfuConfig};
+
+use self::{
+    captions::CaptionTap,
+    recorder::{RecordedTrackFile, RoomRecorder},
+    room::SfuRoom,
+    simulcast::LayerBitrates,
+};
+
+// RTP header extensions browsers use to label simulcast layers
+const SIMULCAST_HEADER_EXTENSIONS: [&str; 3] = [
+    "urn:ietf:params:rtp-hdrext:sdes:mid",
+    "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id",
+    "urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id",
+];
+
+/// In-process selective forwarding unit for group calls. The service
+/// terminates each participant's PeerConnection and forwards their RTP to
+/// everyone else, choosing a simulcast layer per receiver.
+#[derive(Clone)]
+pub struct SfuService {
+    api: Arc<API>,
+    config: SfuConfig,
+    call_manager: CallManager,
+    rooms: Arc<DashMap<Uuid, Arc<SfuRoom>>>,
+}
+
+impl SfuService {
+    pub fn new(config: &SfuConfig, call_manager: CallManager) -> Result<Self, AppError> {
+        let mut media_engine = MediaEngine::default();
+        media_engine.register_default_codecs()
+            .map_err(|e| AppError::Internal(format!("Failed to register SFU codecs: {}", e)))?;
+
+        for uri in SIMULCAST_HEADER_EXTENSIONS {
+            media_engine
+                .register_header_extension(
+                    RTCRtpHeaderExtensionCapability { uri: uri.to_string() },
+                    RTPCodecType::Video,
+                    None,
+                )
+                .map_err(|e| AppError::Internal(format!("Failed to register header extension {}: {}", uri, e)))?;
+        }
+
+        let registry = register_default_interceptors(Registry::new(), &mut media_engine)
+            .map_err(|e| AppError::Internal(format!("Failed to register SFU interceptors: {}", e)))?;
+
+        let mut setting_engine = SettingEngine::default();
+        if let Some(public_ip) = &config.public_ip {
+            setting_engine.set_nat_1to1_ips(vec![public_ip.clone()], RTCIceCandidateType::Host);
+        }
+
+        let api = APIBuilder::new()
+            .with_media_engine(media_engine)
+            .with_interceptor_registry(registry)
+            .with_setting_engine(setting_engine)
+            .build();
+
+        Ok(Self {
+            api: Arc::new(api),
+            config: config.clone(),
+            call_manager,
+            rooms: Arc::new(DashMap::new()),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn max_participants(&self) -> u32 {
+        self.config.max_participants
+    }
+
+    /// Periodically re-picks simulcast layers so receivers pick up layers
+    /// that publishers start or stop sending.
+    pub async fn start_layer_monitor(&self) {
+        let rooms = self.rooms.clone();
+        let interval_seconds = self.config.layer_check_interval_seconds.max(1);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_seconds));
+
+            loop {
+                interval.tick().await;
+
+                let active: Vec<Arc<SfuRoom>> = rooms.iter().map(|room| room.clone()).collect();
+                for room in active {
+                    room.select_all_layers().await;
+                }
+            }
+        });
+    }
+
+    /// Handles a participant's offer, joining them to the call's room on
+    /// first use. Returns the SDP answer.
+    pub async fn handle_offer(&self, call_id: Uuid, user_id: Uuid, sdp: String) -> Result<String, AppError> {
+        let room = self.rooms.entry(call_id)
+            .or_insert_with(|| Arc::new(self.new_room(call_id, true)))
+            .clone();
+
+        room.handle_offer(user_id, sdp).await
+    }
+
+    pub async fn handle_answer(&self, call_id: Uuid, user_id: Uuid, sdp: String) -> Result<(), AppError> {
+        self.get_room(call_id)?.handle_answer(user_id, sdp).await
+    }
+
+    pub async fn add_ice_candidate(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        candidate: String,
+        sdp_mid: Option<String>,
+        sdp_mline_index: Option<u32>,
+    ) -> Result<(), AppError> {
+        self.get_room(call_id)?
+            .add_ice_candidate(user_id, candidate, sdp_mid, sdp_mline_index)
+            .await
+    }
+
+    /// Receiver-reported available bandwidth, e.g. from quality reports.
+    pub async fn update_bandwidth(&self, call_id: Uuid, user_id: Uuid, bandwidth_bps: u32) {
+        if let Ok(room) = self.get_room(call_id) {
+            room.update_bandwidth(user_id, bandwidth_bps).await;
+        }
+    }
+
+    /// Stops forwarding a participant's audio while the host has them muted.
+    pub async fn hold_audio(&self, call_id: Uuid, user_id: Uuid, held: bool) {
+        if let Ok(room) = self.get_room(call_id) {
+            room.set_held(user_id, RTPCodecType::Audio, held).await;
+        }
+    }
+
+    /// Stops forwarding a participant's video while the host has it stopped.
+    pub async fn hold_video(&self, call_id: Uuid, user_id: Uuid, held: bool) {
+        if let Ok(room) = self.get_room(call_id) {
+            room.set_held(user_id, RTPCodecType::Video, held).await;
+        }
+    }
+
+    pub async fn leave(&self, call_id: Uuid, user_id: Uuid) {
+        if let Ok(room) = self.get_room(call_id) {
+            room.remove_peer(user_id).await;
+        }
+    }
+
+    /// Whether the participant's media connection to the call's room is up
+    pub async fn is_connected(&self, call_id: Uuid, user_id: Uuid) -> bool {
+        match self.get_room(call_id) {
+            Ok(room) => room.is_peer_connected(user_id),
+            Err(_) => false,
+        }
+    }
+
+    pub fn has_room(&self, call_id: Uuid) -> bool {
+        self.rooms.contains_key(&call_id)
+    }
+
+    /// Opens a non-forwarding room that participants of a peer-to-peer call
+    /// publish to so the server can record or caption them.
+    pub fn open_recorder_room(&self, call_id: Uuid) {
+        self.rooms.entry(call_id)
+            .or_insert_with(|| Arc::new(self.new_room(call_id, false)));
+    }
+
+    pub fn start_recording(&self, call_id: Uuid, recorder: Arc<RoomRecorder>) -> Result<(), AppError> {
+        self.get_room(call_id)?.start_recording(recorder)
+    }
+
+    /// Stops recording the call, closing the room if it only existed to
+    /// record. Returns the recorded per-track files.
+    pub async fn stop_recording(&self, call_id: Uuid) -> Vec<RecordedTrackFile> {
+        let room = match self.get_room(call_id) {
+            Ok(room) => room,
+            Err(_) => return Vec::new(),
+        };
+
+        let tracks = room.stop_recording().unwrap_or_default();
+        if !room.is_forwarding() && !room.is_captioning() {
+            self.close_room(call_id).await;
+        }
+
+        tracks
+    }
+
+    pub fn add_recording_consent(&self, call_id: Uuid, user_id: Uuid) {
+        if let Ok(room) = self.get_room(call_id) {
+            room.add_recording_consent(user_id);
+        }
+    }
+
+    pub fn start_captions(&self, call_id: Uuid, tap: Arc<CaptionTap>) -> Result<(), AppError> {
+        self.get_room(call_id)?.start_captions(tap)
+    }
+
+    pub fn add_caption_consent(&self, call_id: Uuid, user_id: Uuid) {
+        if let Ok(room) = self.get_room(call_id) {
+            room.add_caption_consent(user_id);
+        }
+    }
+
+    /// Stops captioning the call, closing the room if it only existed to
+    /// capture audio.
+    pub async fn stop_captions(&self, call_id: Uuid) {
+        let room = match self.get_room(call_id) {
+            Ok(room) => room,
+            Err(_) => return,
+        };
+
+        room.stop_captions();
+        if !room.is_forwarding() && !room.is_recording() {
+            self.close_room(call_id).await;
+        }
+    }
+
+    pub async fn close_room(&self, call_id: Uuid) {
+        if let Some((_, room)) = self.rooms.remove(&call_id) {
+            room.close().await;
+            tracing::info!("Closed SFU room for call {}", call_id);
+        }
+    }
+
+    fn new_room(&self, call_id: Uuid, forwarding: bool) -> SfuRoom {
+        SfuRoom::new(
+            call_id,
+            self.api.clone(),
+            self.rtc_configuration(),
+            LayerBitrates::from_config(&self.config),
+            self.call_manager.clone(),
+            forwarding,
+            self.config.max_participants as usize,
+        )
+    }
+
+    fn get_room(&self, call_id: Uuid) -> Result<Arc<SfuRoom>, AppError> {
+        self.rooms.get(&call_id)
+            .map(|room| room.clone())
+            .ok_or_else(|| AppError::NotFound("No media session for this call".to_string()))
+    }
+
+    fn rtc_configuration(&self) -> RTCConfiguration {
+        RTCConfiguration {
+            ice_servers: vec![RTCIceServer {
+                urls: self.config.stun_servers.clone(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+
+use uuid::Uuid;
+use webrtc::media::io::h264_writer::H264Writer;
+use webrtc::media::io::ivf_reader::IVFFileHeader;
+use webrtc::media::io::ivf_writer::IVFWriter;
+use webrtc::media::io::ogg_writer::OggWriter;
+use webrtc::media::io::Writer;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+
+use linkwithmentor_common::AppError;
+
+use super::simulcast::{SequenceRewriter, SimulcastLayer};
+
+/// A finished per-track recording on local disk.
+#[derive(Debug, Clone)]
+pub struct RecordedTrackFile {
+    pub publisher_id: Uuid,
+    pub track_id: String,
+    pub is_video: bool,
+    pub path: PathBuf,
+    /// When the track's first packet arrived, relative to the recording start
+    pub offset_ms: u64,
+}
+
+/// Writes the RTP of consenting participants' tracks to disk, one file per
+/// track, depacketized into a container ffmpeg can read (IVF for VP8, Annex B
+/// for H264, Ogg for Opus). Only the highest simulcast layer is kept.
+pub struct RoomRecorder {
+    recording_id: Uuid,
+    dir: PathBuf,
+    started_at: Instant,
+    consented: RwLock<HashSet<Uuid>>,
+    // Keyed like `SfuRoom::tracks`
+    tracks: Mutex<HashMap<String, TrackWriter>>,
+}
+
+struct TrackWriter {
+    file: RecordedTrackFile,
+    writer: Box<dyn Writer + Send + Sync>,
+    clock_rate: u32,
+    current: Option<SimulcastLayer>,
+    rewriter: SequenceRewriter,
+    last_written: Option<Instant>,
+}
+
+impl RoomRecorder {
+    pub fn new(recording_id: Uuid, dir: PathBuf, consented: HashSet<Uuid>) -> Self {
+        Self {
+            recording_id,
+            dir,
+            started_at: Instant::now(),
+            consented: RwLock::new(consented),
+            tracks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn recording_id(&self) -> Uuid {
+        self.recording_id
+    }
+
+    /// Starts recording a participant who consented after the recording began.
+    pub fn add_consent(&self, user_id: Uuid) {
+        if let Ok(mut consented) = self.consented.write() {
+            consented.insert(user_id);
+        }
+    }
+
+    pub fn is_recorded(&self, publisher_id: Uuid) -> bool {
+        self.consented.read().map_or(false, |consented| consented.contains(&publisher_id))
+    }
+
+    /// Writes one packet from the layer being recorded for a track. Returns
+    /// true when the recorder just switched to a new source, in which case
+    /// the caller should request a keyframe.
+    pub fn write(
+        &self,
+        key: &str,
+        publisher_id: Uuid,
+        track_id: &str,
+        codec: &RTCRtpCodecCapability,
+        layer: SimulcastLayer,
+        packet: &Packet,
+    ) -> bool {
+        if !self.is_recorded(publisher_id) {
+            return false;
+        }
+
+        let mut tracks = match self.tracks.lock() {
+            Ok(tracks) => tracks,
+            Err(_) => return false,
+        };
+
+        if !tracks.contains_key(key) {
+            match self.open_track(publisher_id, track_id, codec) {
+                Ok(Some(writer)) => {
+                    tracks.insert(key.to_string(), writer);
+                }
+                Ok(None) => return false,
+                Err(e) => {
+                    tracing::error!("Failed to record track {} in recording {}: {}", key, self.recording_id, e);
+                    return false;
+                }
+            }
+        }
+
+        let track = match tracks.get_mut(key) {
+            Some(track) => track,
+            None => return false,
+        };
+
+        let mut new_source = false;
+        if track.current != Some(layer) {
+            let clock_rate = u64::from(track.clock_rate);
+            let elapsed_ticks = track.last_written
+                .map(|at| (at.elapsed().as_millis() as u64 * clock_rate / 1000) as u32)
+                .unwrap_or(0);
+            track.rewriter.switch_source(elapsed_ticks);
+            track.current = Some(layer);
+            new_source = true;
+        }
+
+        let (sequence_number, timestamp) = track.rewriter
+            .rewrite(packet.header.sequence_number, packet.header.timestamp);
+        track.last_written = Some(Instant::now());
+
+        let mut packet = packet.clone();
+        packet.header.sequence_number = sequence_number;
+        packet.header.timestamp = timestamp;
+
+        if let Err(e) = track.writer.write_rtp(&packet) {
+            tracing::debug!("Failed to write packet of {} to recording {}: {}", key, self.recording_id, e);
+        }
+
+        new_source
+    }
+
+    /// Closes every track file and returns what was recorded.
+    pub fn finish(&self) -> Vec<RecordedTrackFile> {
+        let mut tracks = match self.tracks.lock() {
+            Ok(tracks) => tracks,
+            Err(_) => return Vec::new(),
+        };
+
+        tracks.drain()
+            .filter_map(|(key, mut track)| match track.writer.close() {
+                Ok(()) => Some(track.file),
+                Err(e) => {
+                    tracing::warn!("Failed to close recorded track {}: {}", key, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn open_track(
+        &self,
+        publisher_id: Uuid,
+        track_id: &str,
+        codec: &RTCRtpCodecCapability,
+    ) -> Result<Option<TrackWriter>, AppError> {
+        let mime_type = codec.mime_type.to_lowercase();
+        let extension = match mime_type.as_str() {
+            "video/vp8" => "ivf",
+            "video/h264" => "h264",
+            "audio/opus" => "ogg",
+            _ => {
+                tracing::warn!("Cannot record {} track {} of {}", codec.mime_type, track_id, publisher_id);
+                return Ok(None);
+            }
+        };
+
+        let path = self.dir.join(format!("{}_{}.{}", publisher_id, sanitize(track_id), extension));
+        let file = create_file(&path)?;
+
+        let writer: Box<dyn Writer + Send + Sync> = match extension {
+            "ivf" => Box::new(
+                IVFWriter::new(file, &IVFFileHeader {
+                    signature: *b"DKIF",
+                    version: 0,
+                    header_size: 32,
+                    four_cc: *b"VP80",
+                    width: 1280,
+                    height: 720,
+                    timebase_denominator: 30,
+                    timebase_numerator: 1,
+                    num_frames: 0,
+                    unused: 0,
+                })
+                .map_err(|e| AppError::Internal(format!("Failed to create IVF writer: {}", e)))?
+            ),
+            "h264" => Box::new(H264Writer::new(file)),
+            _ => Box::new(
+                OggWriter::new(file, codec.clock_rate, codec.channels.max(1))
+                    .map_err(|e| AppError::Internal(format!("Failed to create Ogg writer: {}", e)))?
+            ),
+        };
+
+        tracing::info!("Recording {} track {} of {} to {}", codec.mime_type, track_id, publisher_id, path.display());
+
+        Ok(Some(TrackWriter {
+            file: RecordedTrackFile {
+                publisher_id,
+                track_id: track_id.to_string(),
+                is_video: mime_type.starts_with("video/"),
+                path,
+                offset_ms: self.started_at.elapsed().as_millis() as u64,
+            },
+            writer,
+            clock_rate: codec.clock_rate,
+            current: None,
+            rewriter: SequenceRewriter::default(),
+            last_written: None,
+        }))
+    }
+}
+
+fn create_file(path: &Path) -> Result<File, AppError> {
+    File::create(path)
+        .map_err(|e| AppError::Internal(format!("Failed to create {}: {}", path.display(), e)))
+}
+
+// Track ids come from the client's SDP; keep them safe to use in file names
+fn sanitize(track_id: &str) -> String {
+    track_id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,929 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Instant;
+
+use dashmap::DashMap;
+use uuid::Uuid;
+use webrtc::api::API;
+use webrtc::ice_transport::ice_candidate::{RTCIceCandidate, RTCIceCandidateInit};
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtcp::payload_feedbacks::full_intra_request::FullIntraRequest;
+use webrtc::rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
+use webrtc::rtcp::payload_feedbacks::receiver_estimated_maximum_bitrate::ReceiverEstimatedMaximumBitrate;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTPCodecType};
+use webrtc::rtp_transceiver::rtp_sender::RTCRtpSender;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+use webrtc::track::track_local::{TrackLocal, TrackLocalWriter};
+use webrtc::track::track_remote::TrackRemote;
+
+use linkwithmentor_common::AppError;
+use crate::{call_manager::CallManager, models::SignalingMessage};
+
+use super::captions::CaptionTap;
+use super::recorder::{RecordedTrackFile, RoomRecorder};
+use super::simulcast::{select_layer, LayerBitrates, SequenceRewriter, SimulcastLayer};
+
+/// One group call on the SFU. Every participant has a single PeerConnection
+/// to the server carrying the tracks they publish and one forwarded track per
+/// track published by everyone else.
+///
+/// Rooms opened only to record or caption a peer-to-peer call don't forward: each
+/// participant publishes to the server as a recorder peer and receives
+/// nothing back.
+pub struct SfuRoom {
+    call_id: Uuid,
+    api: Arc<API>,
+    rtc_config: RTCConfiguration,
+    bitrates: LayerBitrates,
+    call_manager: CallManager,
+    forwarding: bool,
+    max_peers: usize,
+    // Serializes joins so concurrent offers can't both take the last seat
+    join_lock: tokio::sync::Mutex<()>,
+    peers: DashMap<Uuid, Arc<SfuPeer>>,
+    // Published tracks keyed by "{publisher_id}:{track_id}"
+    tracks: DashMap<String, Arc<PublishedTrack>>,
+    // Media the host has stopped, by publisher; kept if they rejoin
+    held: DashMap<Uuid, HeldMedia>,
+    recorder: RwLock<Option<Arc<RoomRecorder>>>,
+    captions: RwLock<Option<Arc<CaptionTap>>>,
+}
+
+struct SfuPeer {
+    user_id: Uuid,
+    pc: Arc<RTCPeerConnection>,
+    // Forwarded tracks keyed like `SfuRoom::tracks`
+    subscriptions: DashMap<String, Arc<Subscription>>,
+    // Latest receive bandwidth estimate in bps, 0 when unknown
+    available_bandwidth: AtomicU32,
+    negotiation: tokio::sync::Mutex<NegotiationState>,
+}
+
+#[derive(Default)]
+struct HeldMedia {
+    audio: bool,
+    video: bool,
+}
+
+#[derive(Default)]
+struct NegotiationState {
+    // A server offer is waiting for the client's answer
+    offer_in_flight: bool,
+    // Tracks changed while an offer was in flight
+    pending: bool,
+}
+
+struct PublishedTrack {
+    key: String,
+    publisher_id: Uuid,
+    track_id: String,
+    kind: RTPCodecType,
+    codec: RTCRtpCodecCapability,
+    // SSRC of each simulcast layer currently being received
+    layers: RwLock<BTreeMap<SimulcastLayer, u32>>,
+}
+
+struct Subscription {
+    track: Arc<PublishedTrack>,
+    local_track: Arc<TrackLocalStaticRTP>,
+    sender: Arc<RTCRtpSender>,
+    state: Mutex<SubscriptionState>,
+}
+
+struct SubscriptionState {
+    target: SimulcastLayer,
+    current: Option<SimulcastLayer>,
+    rewriter: SequenceRewriter,
+    last_forwarded: Option<Instant>,
+}
+
+impl SfuRoom {
+    pub fn new(
+        call_id: Uuid,
+        api: Arc<API>,
+        rtc_config: RTCConfiguration,
+        bitrates: LayerBitrates,
+        call_manager: CallManager,
+        forwarding: bool,
+        max_peers: usize,
+    ) -> Self {
+        Self {
+            call_id,
+            api,
+            rtc_config,
+            bitrates,
+            call_manager,
+            forwarding,
+            max_peers,
+            join_lock: tokio::sync::Mutex::new(()),
+            peers: DashMap::new(),
+            tracks: DashMap::new(),
+            held: DashMap::new(),
+            recorder: RwLock::new(None),
+            captions: RwLock::new(None),
+        }
+    }
+
+    pub fn is_forwarding(&self) -> bool {
+        self.forwarding
+    }
+
+    pub fn is_peer_connected(&self, user_id: Uuid) -> bool {
+        self.peers.get(&user_id)
+            .map_or(false, |peer| peer.pc.connection_state() == RTCPeerConnectionState::Connected)
+    }
+
+    /// Applies a client offer - the initial join or a client-side
+    /// renegotiation such as adding a screen share or restarting ICE - and
+    /// returns the answer. A participant whose connection failed outright
+    /// rejoins with a new one.
+    pub async fn handle_offer(self: &Arc<Self>, user_id: Uuid, sdp: String) -> Result<String, AppError> {
+        let existing = self.peers.get(&user_id).map(|peer| peer.clone());
+        let existing = match existing {
+            Some(peer) if matches!(
+                peer.pc.connection_state(),
+                RTCPeerConnectionState::Failed | RTCPeerConnectionState::Closed
+            ) => {
+                self.remove_peer(user_id).await;
+                None
+            }
+            other => other,
+        };
+        let (peer, is_new) = match existing {
+            Some(peer) => (peer, false),
+            None => self.join(user_id).await?,
+        };
+
+        let answer = {
+            let negotiation = peer.negotiation.lock().await;
+            if negotiation.offer_in_flight {
+                return Err(AppError::Conflict(
+                    "Answer the pending server offer before renegotiating".to_string()
+                ));
+            }
+
+            let offer = RTCSessionDescription::offer(sdp)
+                .map_err(|e| AppError::BadRequest(format!("Invalid SDP offer: {}", e)))?;
+            peer.pc.set_remote_description(offer).await
+                .map_err(|e| AppError::BadRequest(format!("Failed to apply SDP offer: {}", e)))?;
+
+            let answer = peer.pc.create_answer(None).await
+                .map_err(|e| AppError::Internal(format!("Failed to create SDP answer: {}", e)))?;
+
+            // Recorder peers don't trickle ICE, since their candidates would be
+            // mistaken for the peer-to-peer connection's; the answer carries them all
+            let mut gathering = if self.forwarding {
+                None
+            } else {
+                Some(peer.pc.gathering_complete_promise().await)
+            };
+
+            peer.pc.set_local_description(answer.clone()).await
+                .map_err(|e| AppError::Internal(format!("Failed to apply SDP answer: {}", e)))?;
+
+            match gathering.as_mut() {
+                Some(gathering) => {
+                    let _ = gathering.recv().await;
+                    peer.pc.local_description().await
+                        .map(|description| description.sdp)
+                        .unwrap_or(answer.sdp)
+                }
+                None => answer.sdp,
+            }
+        };
+
+        if is_new && self.forwarding {
+            // Forward everything already published; this triggers a server offer
+            let tracks: Vec<Arc<PublishedTrack>> = self.tracks.iter()
+                .filter(|track| track.publisher_id != user_id)
+                .map(|track| track.clone())
+                .collect();
+
+            for track in tracks {
+                self.subscribe(&peer, track).await?;
+            }
+        }
+
+        Ok(answer)
+    }
+
+    /// Applies the client's answer to a server offer.
+    pub async fn handle_answer(self: &Arc<Self>, user_id: Uuid, sdp: String) -> Result<(), AppError> {
+        let peer = self.get_peer(user_id)?;
+
+        let renegotiate = {
+            let mut negotiation = peer.negotiation.lock().await;
+            if !negotiation.offer_in_flight {
+                return Err(AppError::BadRequest("No server offer is pending".to_string()));
+            }
+
+            let answer = RTCSessionDescription::answer(sdp)
+                .map_err(|e| AppError::BadRequest(format!("Invalid SDP answer: {}", e)))?;
+            peer.pc.set_remote_description(answer).await
+                .map_err(|e| AppError::BadRequest(format!("Failed to apply SDP answer: {}", e)))?;
+
+            negotiation.offer_in_flight = false;
+            std::mem::take(&mut negotiation.pending)
+        };
+
+        if renegotiate {
+            self.renegotiate(&peer).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn add_ice_candidate(
+        &self,
+        user_id: Uuid,
+        candidate: String,
+        sdp_mid: Option<String>,
+        sdp_mline_index: Option<u32>,
+    ) -> Result<(), AppError> {
+        let peer = self.get_peer(user_id)?;
+
+        let candidate = RTCIceCandidateInit {
+            candidate,
+            sdp_mid,
+            sdp_mline_index: sdp_mline_index.and_then(|index| u16::try_from(index).ok()),
+            ..Default::default()
+        };
+
+        peer.pc.add_ice_candidate(candidate).await
+            .map_err(|e| AppError::BadRequest(format!("Invalid ICE candidate: {}", e)))
+    }
+
+    /// Records a receiver's available bandwidth and re-picks its layers.
+    pub async fn update_bandwidth(&self, user_id: Uuid, bandwidth_bps: u32) {
+        if let Some(peer) = self.peers.get(&user_id).map(|peer| peer.clone()) {
+            peer.available_bandwidth.store(bandwidth_bps, Ordering::Relaxed);
+            self.select_layers(&peer).await;
+        }
+    }
+
+    /// Re-evaluates layer choices for everyone, e.g. after publishers add or
+    /// drop simulcast layers.
+    /// Stops or resumes a publisher's audio or video reaching anyone else,
+    /// the recording or captions. Their tracks stay negotiated and packets
+    /// are dropped, so whatever their client does it isn't heard or seen.
+    pub async fn set_held(&self, publisher_id: Uuid, kind: RTPCodecType, held: bool) {
+        {
+            let mut media = self.held.entry(publisher_id).or_default();
+            match kind {
+                RTPCodecType::Audio => media.audio = held,
+                RTPCodecType::Video => media.video = held,
+                _ => return,
+            }
+        }
+
+        if held || kind != RTPCodecType::Video {
+            return;
+        }
+
+        // Subscribers can't decode the video again until a keyframe arrives
+        let tracks: Vec<Arc<PublishedTrack>> = self.tracks.iter()
+            .filter(|track| track.publisher_id == publisher_id && track.kind == RTPCodecType::Video)
+            .map(|track| track.clone())
+            .collect();
+        for track in tracks {
+            let layers: Vec<SimulcastLayer> = track.layers.read()
+                .map(|layers| layers.keys().copied().collect())
+                .unwrap_or_default();
+            for layer in layers {
+                self.request_keyframe(&track, layer).await;
+            }
+        }
+    }
+
+    pub async fn select_all_layers(&self) {
+        let peers: Vec<Arc<SfuPeer>> = self.peers.iter().map(|peer| peer.clone()).collect();
+        for peer in peers {
+            self.select_layers(&peer).await;
+        }
+    }
+
+    pub async fn remove_peer(&self, user_id: Uuid) {
+        let peer = match self.peers.remove(&user_id) {
+            Some((_, peer)) => peer,
+            None => return,
+        };
+
+        let published: Vec<String> = self.tracks.iter()
+            .filter(|track| track.publisher_id == user_id)
+            .map(|track| track.key.clone())
+            .collect();
+        for key in published {
+            self.unpublish(&key).await;
+        }
+
+        if let Err(e) = peer.pc.close().await {
+            tracing::warn!("Failed to close SFU peer connection for {}: {}", user_id, e);
+        }
+
+        tracing::info!("Participant {} left SFU room {}", user_id, self.call_id);
+    }
+
+    /// Starts writing consenting participants' tracks to disk.
+    pub fn start_recording(&self, recorder: Arc<RoomRecorder>) -> Result<(), AppError> {
+        let mut current = self.recorder.write()
+            .map_err(|_| AppError::Internal("Recorder lock poisoned".to_string()))?;
+        if current.is_some() {
+            return Err(AppError::Conflict("Call is already being recorded".to_string()));
+        }
+
+        tracing::info!("Recording {} started in SFU room {}", recorder.recording_id(), self.call_id);
+        *current = Some(recorder);
+        Ok(())
+    }
+
+    /// Stops recording and returns the closed per-track files.
+    pub fn stop_recording(&self) -> Option<Vec<RecordedTrackFile>> {
+        let recorder = self.recorder.write().ok()?.take()?;
+        tracing::info!("Recording {} stopped in SFU room {}", recorder.recording_id(), self.call_id);
+        Some(recorder.finish())
+    }
+
+    pub fn add_recording_consent(&self, user_id: Uuid) {
+        if let Some(recorder) = self.recorder.read().ok().and_then(|recorder| recorder.clone()) {
+            recorder.add_consent(user_id);
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.read().map_or(false, |recorder| recorder.is_some())
+    }
+
+    /// Starts chunking participants' audio for live captions.
+    pub fn start_captions(&self, tap: Arc<CaptionTap>) -> Result<(), AppError> {
+        let mut current = self.captions.write()
+            .map_err(|_| AppError::Internal("Caption lock poisoned".to_string()))?;
+        if current.is_some() {
+            return Err(AppError::Conflict("Call already has captions enabled".to_string()));
+        }
+
+        tracing::info!("Captions started in SFU room {}", self.call_id);
+        *current = Some(tap);
+        Ok(())
+    }
+
+    /// Stops captioning, handing over the audio chunks still open.
+    pub fn stop_captions(&self) {
+        if let Some(tap) = self.captions.write().ok().and_then(|mut captions| captions.take()) {
+            tap.finish();
+            tracing::info!("Captions stopped in SFU room {}", self.call_id);
+        }
+    }
+
+    pub fn add_caption_consent(&self, user_id: Uuid) {
+        if let Some(tap) = self.captions.read().ok().and_then(|captions| captions.clone()) {
+            tap.add_consent(user_id);
+        }
+    }
+
+    pub fn is_captioning(&self) -> bool {
+        self.captions.read().map_or(false, |captions| captions.is_some())
+    }
+
+    pub async fn close(&self) {
+        let user_ids: Vec<Uuid> = self.peers.iter().map(|peer| peer.user_id).collect();
+        for user_id in user_ids {
+            self.remove_peer(user_id).await;
+        }
+    }
+
+    // Peer setup
+
+    /// Adds a peer if the room has space. Returns the existing peer instead
+    /// if a concurrent offer from the same user got there first.
+    async fn join(self: &Arc<Self>, user_id: Uuid) -> Result<(Arc<SfuPeer>, bool), AppError> {
+        let _guard = self.join_lock.lock().await;
+
+        if let Some(peer) = self.peers.get(&user_id).map(|peer| peer.clone()) {
+            return Ok((peer, false));
+        }
+
+        if self.peers.len() >= self.max_peers {
+            return Err(AppError::BadRequest("Maximum participants reached".to_string()));
+        }
+
+        Ok((self.create_peer(user_id).await?, true))
+    }
+
+    async fn create_peer(self: &Arc<Self>, user_id: Uuid) -> Result<Arc<SfuPeer>, AppError> {
+        let pc = Arc::new(
+            self.api.new_peer_connection(self.rtc_config.clone()).await
+                .map_err(|e| AppError::Internal(format!("Failed to create SFU peer connection: {}", e)))?
+        );
+
+        let room = Arc::downgrade(self);
+        pc.on_track(Box::new(move |remote, _receiver, _transceiver| {
+            let room = room.clone();
+            Box::pin(async move {
+                if let Some(room) = room.upgrade() {
+                    room.on_remote_track(user_id, remote).await;
+                }
+            })
+        }));
+
+        let call_manager = self.call_manager.clone();
+        let call_id = self.call_id;
+        let trickle = self.forwarding;
+        pc.on_ice_candidate(Box::new(move |candidate: Option<RTCIceCandidate>| {
+            let call_manager = call_manager.clone();
+            Box::pin(async move {
+                if !trickle {
+                    return;
+                }
+
+                let candidate = match candidate.map(|candidate| candidate.to_json()) {
+                    Some(Ok(candidate)) => candidate,
+                    _ => return,
+                };
+
+                let message = SignalingMessage::IceCandidate {
+                    call_id,
+                    candidate: candidate.candidate,
+                    sdp_mid: candidate.sdp_mid,
+                    sdp_mline_index: candidate.sdp_mline_index.map(u32::from),
+                };
+                send_to_user(&call_manager, user_id, &message).await;
+            })
+        }));
+
+        let room = Arc::downgrade(self);
+        pc.on_peer_connection_state_change(Box::new(move |state: RTCPeerConnectionState| {
+            let room = room.clone();
+            Box::pin(async move {
+                if let Some(room) = room.upgrade() {
+                    room.on_connection_state(user_id, state).await;
+                }
+            })
+        }));
+
+        let peer = Arc::new(SfuPeer {
+            user_id,
+            pc,
+            subscriptions: DashMap::new(),
+            available_bandwidth: AtomicU32::new(0),
+            negotiation: tokio::sync::Mutex::new(NegotiationState::default()),
+        });
+
+        self.peers.insert(user_id, peer.clone());
+        tracing::info!("Participant {} joined SFU room {}", user_id, self.call_id);
+
+        Ok(peer)
+    }
+
+    // A participant whose connection drops is kept, with their tracks, for
+    // the call's reconnect grace period so they can restart ICE. Recorder
+    // peers are just dropped; the peer-to-peer call handles reconnection.
+    async fn on_connection_state(&self, user_id: Uuid, state: RTCPeerConnectionState) {
+        match state {
+            RTCPeerConnectionState::Disconnected | RTCPeerConnectionState::Failed if self.forwarding => {
+                tracing::warn!("SFU connection {} for {} in call {}", state, user_id, self.call_id);
+
+                match self.call_manager.mark_reconnecting(self.call_id, user_id).await {
+                    Ok(Some(reconnect_deadline)) => {
+                        self.broadcast(&SignalingMessage::ParticipantReconnecting {
+                            call_id: self.call_id,
+                            participant_id: user_id,
+                            reconnect_deadline,
+                        }, Some(user_id)).await;
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to mark {} reconnecting in call {}: {}", user_id, self.call_id, e),
+                }
+            }
+            RTCPeerConnectionState::Failed => {
+                tracing::warn!("SFU connection failed for {} in call {}", user_id, self.call_id);
+                self.remove_peer(user_id).await;
+            }
+            RTCPeerConnectionState::Connected if self.forwarding => {
+                // Signaling must be back too before the participant counts as reconnected
+                if self.call_manager.get_user_connections(user_id).await.is_empty() {
+                    return;
+                }
+
+                match self.call_manager.mark_reconnected(self.call_id, user_id).await {
+                    Ok(true) => {
+                        self.broadcast(&SignalingMessage::ParticipantReconnected {
+                            call_id: self.call_id,
+                            participant_id: user_id,
+                        }, Some(user_id)).await;
+                    }
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Failed to mark {} reconnected in call {}: {}", user_id, self.call_id, e),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Publishing
+
+    async fn on_remote_track(self: Arc<Self>, publisher_id: Uuid, remote: Arc<TrackRemote>) {
+        let layer = SimulcastLayer::from_rid(remote.rid());
+        let key = format!("{}:{}", publisher_id, remote.id());
+
+        let mut is_new = false;
+        let track = self.tracks.entry(key.clone())
+            .or_insert_with(|| {
+                is_new = true;
+                Arc::new(PublishedTrack {
+                    key,
+                    publisher_id,
+                    track_id: remote.id(),
+                    kind: remote.kind(),
+                    codec: remote.codec().capability,
+                    layers: RwLock::new(BTreeMap::new()),
+                })
+            })
+            .clone();
+
+        if let Ok(mut layers) = track.layers.write() {
+            layers.insert(layer, remote.ssrc());
+        }
+
+        tokio::spawn(forward_layer(Arc::downgrade(&self), track.clone(), remote, layer));
+
+        if !is_new || !self.forwarding {
+            // Another simulcast layer of a known track, or nothing to forward to
+            return;
+        }
+
+        tracing::info!(
+            "Participant {} published {} track {} in call {}",
+            publisher_id, track.kind, track.track_id, self.call_id
+        );
+
+        self.broadcast(&SignalingMessage::SfuTrackAdded {
+            call_id: self.call_id,
+            publisher_id,
+            track_id: track.track_id.clone(),
+            kind: track.kind.to_string(),
+        }, Some(publisher_id)).await;
+
+        let subscribers: Vec<Arc<SfuPeer>> = self.peers.iter()
+            .filter(|peer| peer.user_id != publisher_id)
+            .map(|peer| peer.clone())
+            .collect();
+
+        for peer in subscribers {
+            if let Err(e) = self.subscribe(&peer, track.clone()).await {
+                tracing::error!("Failed to forward track {} to {}: {}", track.key, peer.user_id, e);
+            }
+        }
+    }
+
+    async fn on_layer_ended(&self, track: &PublishedTrack, layer: SimulcastLayer) {
+        let no_layers_left = match track.layers.write() {
+            Ok(mut layers) => {
+                layers.remove(&layer);
+                layers.is_empty()
+            }
+            Err(_) => true,
+        };
+
+        if no_layers_left {
+            self.unpublish(&track.key).await;
+        } else {
+            self.select_all_layers().await;
+        }
+    }
+
+    async fn unpublish(&self, key: &str) {
+        let track = match self.tracks.remove(key) {
+            Some((_, track)) => track,
+            None => return,
+        };
+
+        let peers: Vec<Arc<SfuPeer>> = self.peers.iter().map(|peer| peer.clone()).collect();
+        for peer in peers {
+            if let Some((_, subscription)) = peer.subscriptions.remove(key) {
+                if let Err(e) = peer.pc.remove_track(&subscription.sender).await {
+                    tracing::warn!("Failed to stop forwarding {} to {}: {}", key, peer.user_id, e);
+                }
+                if let Err(e) = self.renegotiate(&peer).await {
+                    tracing::warn!("Failed to renegotiate with {}: {}", peer.user_id, e);
+                }
+            }
+        }
+
+        if !self.forwarding {
+            return;
+        }
+
+        self.broadcast(&SignalingMessage::SfuTrackRemoved {
+            call_id: self.call_id,
+            publisher_id: track.publisher_id,
+            track_id: track.track_id.clone(),
+        }, Some(track.publisher_id)).await;
+    }
+
+    // Subscribing
+
+    async fn subscribe(self: &Arc<Self>, peer: &Arc<SfuPeer>, track: Arc<PublishedTrack>) -> Result<(), AppError> {
+        // The stream id is the publisher, so clients can group tracks by participant
+        let local_track = Arc::new(TrackLocalStaticRTP::new(
+            track.codec.clone(),
+            track.track_id.clone(),
+            track.publisher_id.to_string(),
+        ));
+
+        let sender = peer.pc
+            .add_track(Arc::clone(&local_track) as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to add forwarded track: {}", e)))?;
+
+        let initial_layer = {
+            let layers = track.layers.read().map(|layers| layers.keys().copied().collect::<Vec<_>>()).unwrap_or_default();
+            // Start low and let layer selection step up once bandwidth is known
+            layers.first().copied().unwrap_or(SimulcastLayer::High)
+        };
+
+        let subscription = Arc::new(Subscription {
+            track: track.clone(),
+            local_track,
+            sender: sender.clone(),
+            state: Mutex::new(SubscriptionState {
+                target: initial_layer,
+                current: None,
+                rewriter: SequenceRewriter::default(),
+                last_forwarded: None,
+            }),
+        });
+        peer.subscriptions.insert(track.key.clone(), subscription.clone());
+
+        tokio::spawn(read_subscriber_rtcp(Arc::downgrade(self), peer.user_id, subscription));
+
+        self.select_layers(peer).await;
+        self.request_keyframe(&track, initial_layer).await;
+        self.renegotiate(peer).await
+    }
+
+    /// Splits the receiver's bandwidth across its forwarded video tracks and
+    /// picks the best fitting layer for each. Layer switches ask the
+    /// publisher for a keyframe so the receiver can decode the new layer.
+    async fn select_layers(&self, peer: &SfuPeer) {
+        let video: Vec<Arc<Subscription>> = peer.subscriptions.iter()
+            .filter(|subscription| subscription.track.kind == RTPCodecType::Video)
+            .map(|subscription| subscription.clone())
+            .collect();
+        if video.is_empty() {
+            return;
+        }
+
+        let bandwidth = peer.available_bandwidth.load(Ordering::Relaxed);
+        let budget = (bandwidth > 0).then(|| bandwidth / video.len() as u32);
+
+        for subscription in video {
+            let available: Vec<SimulcastLayer> = match subscription.track.layers.read() {
+                Ok(layers) => layers.keys().copied().collect(),
+                Err(_) => continue,
+            };
+
+            let layer = match select_layer(&available, budget, &self.bitrates) {
+                Some(layer) => layer,
+                None => continue,
+            };
+
+            let changed = match subscription.state.lock() {
+                Ok(mut state) if state.target != layer => {
+                    state.target = layer;
+                    true
+                }
+                _ => false,
+            };
+
+            if changed {
+                tracing::debug!(
+                    "Switching {} to {:?} layer of {} in call {}",
+                    peer.user_id, layer, subscription.track.key, self.call_id
+                );
+                self.request_keyframe(&subscription.track, layer).await;
+            }
+        }
+    }
+
+    /// Hands a packet to the recorder if the call is being recorded. Only the
+    /// highest layer currently published is recorded.
+    async fn record(&self, track: &PublishedTrack, layer: SimulcastLayer, packet: &Packet) {
+        let recorder = match self.recorder.read().ok().and_then(|recorder| recorder.clone()) {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        let record_layer = track.layers.read().ok().and_then(|layers| layers.keys().max().copied());
+        if record_layer != Some(layer) {
+            return;
+        }
+
+        let new_source = recorder.write(&track.key, track.publisher_id, &track.track_id, &track.codec, layer, packet);
+        if new_source {
+            // Recorded files must start on a keyframe to be decodable
+            self.request_keyframe(track, layer).await;
+        }
+    }
+
+    /// Hands an audio packet to the caption tap if captions are on.
+    fn caption(&self, track: &PublishedTrack, packet: &Packet) {
+        if track.kind != RTPCodecType::Audio {
+            return;
+        }
+
+        if let Some(tap) = self.captions.read().ok().and_then(|captions| captions.clone()) {
+            tap.write(&track.key, track.publisher_id, &track.codec, packet);
+        }
+    }
+
+    async fn request_keyframe(&self, track: &PublishedTrack, layer: SimulcastLayer) {
+        if track.kind != RTPCodecType::Video {
+            return;
+        }
+
+        let media_ssrc = match track.layers.read().ok().and_then(|layers| layers.get(&layer).copied()) {
+            Some(ssrc) => ssrc,
+            None => return,
+        };
+
+        let publisher = match self.peers.get(&track.publisher_id).map(|peer| peer.clone()) {
+            Some(publisher) => publisher,
+            None => return,
+        };
+
+        let pli = PictureLossIndication { sender_ssrc: 0, media_ssrc };
+        if let Err(e) = publisher.pc.write_rtcp(&[Box::new(pli)]).await {
+            tracing::debug!("Failed to request keyframe from {}: {}", track.publisher_id, e);
+        }
+    }
+
+    async fn renegotiate(&self, peer: &SfuPeer) -> Result<(), AppError> {
+        let mut negotiation = peer.negotiation.lock().await;
+        if negotiation.offer_in_flight {
+            negotiation.pending = true;
+            return Ok(());
+        }
+
+        let offer = peer.pc.create_offer(None).await
+            .map_err(|e| AppError::Internal(format!("Failed to create SDP offer: {}", e)))?;
+        peer.pc.set_local_description(offer.clone()).await
+            .map_err(|e| AppError::Internal(format!("Failed to apply SDP offer: {}", e)))?;
+        negotiation.offer_in_flight = true;
+        drop(negotiation);
+
+        send_to_user(&self.call_manager, peer.user_id, &SignalingMessage::SfuRenegotiate {
+            call_id: self.call_id,
+            sdp: offer.sdp,
+        }).await;
+
+        Ok(())
+    }
+
+    // Helpers
+
+    fn get_peer(&self, user_id: Uuid) -> Result<Arc<SfuPeer>, AppError> {
+        self.peers.get(&user_id)
+            .map(|peer| peer.clone())
+            .ok_or_else(|| AppError::BadRequest("Not connected to the call's media server".to_string()))
+    }
+
+    fn is_held(&self, track: &PublishedTrack) -> bool {
+        self.held.get(&track.publisher_id).map_or(false, |media| match track.kind {
+            RTPCodecType::Audio => media.audio,
+            RTPCodecType::Video => media.video,
+            _ => false,
+        })
+    }
+
+    fn subscriptions_for(&self, key: &str) -> Vec<Arc<Subscription>> {
+        self.peers.iter()
+            .filter_map(|peer| peer.subscriptions.get(key).map(|subscription| subscription.clone()))
+            .collect()
+    }
+
+    async fn broadcast(&self, message: &SignalingMessage, exclude: Option<Uuid>) {
+        let user_ids: Vec<Uuid> = self.peers.iter()
+            .map(|peer| peer.user_id)
+            .filter(|user_id| Some(*user_id) != exclude)
+            .collect();
+
+        for user_id in user_ids {
+            send_to_user(&self.call_manager, user_id, message).await;
+        }
+    }
+}
+
+impl Subscription {
+    /// Returns the packet to forward, rewritten into this subscriber's
+    /// sequence space, or None if it belongs to a layer not being forwarded.
+    fn prepare(&self, layer: SimulcastLayer, packet: &Packet) -> Option<Packet> {
+        let mut state = self.state.lock().ok()?;
+
+        if state.current != Some(layer) {
+            if state.target != layer {
+                return None;
+            }
+
+            let clock_rate = u64::from(self.track.codec.clock_rate);
+            let elapsed_ticks = state.last_forwarded
+                .map(|at| (at.elapsed().as_millis() as u64 * clock_rate / 1000) as u32)
+                .unwrap_or(0);
+            state.rewriter.switch_source(elapsed_ticks);
+            state.current = Some(layer);
+        }
+
+        let (sequence_number, timestamp) = state.rewriter
+            .rewrite(packet.header.sequence_number, packet.header.timestamp);
+        state.last_forwarded = Some(Instant::now());
+
+        let mut packet = packet.clone();
+        packet.header.sequence_number = sequence_number;
+        packet.header.timestamp = timestamp;
+        Some(packet)
+    }
+
+    fn current_layer(&self) -> Option<SimulcastLayer> {
+        self.state.lock().ok().and_then(|state| state.current)
+    }
+}
+
+/// Reads one simulcast layer of a published track and fans it out to the
+/// subscribers currently forwarding that layer.
+async fn forward_layer(
+    room: Weak<SfuRoom>,
+    track: Arc<PublishedTrack>,
+    remote: Arc<TrackRemote>,
+    layer: SimulcastLayer,
+) {
+    while let Ok((packet, _)) = remote.read_rtp().await {
+        let room = match room.upgrade() {
+            Some(room) => room,
+            None => return,
+        };
+
+        if room.is_held(&track) {
+            continue;
+        }
+
+        for subscription in room.subscriptions_for(&track.key) {
+            if let Some(packet) = subscription.prepare(layer, &packet) {
+                // Errors here mean the subscriber is gone; it is cleaned up elsewhere
+                let _ = subscription.local_track.write_rtp(&packet).await;
+            }
+        }
+
+        room.record(&track, layer, &packet).await;
+        room.caption(&track, &packet);
+    }
+
+    if let Some(room) = room.upgrade() {
+        room.on_layer_ended(&track, layer).await;
+    }
+}
+
+/// Handles RTCP from a subscriber: keyframe requests are relayed to the
+/// publisher and REMB estimates feed layer selection.
+async fn read_subscriber_rtcp(room: Weak<SfuRoom>, subscriber_id: Uuid, subscription: Arc<Subscription>) {
+    while let Ok((packets, _)) = subscription.sender.read_rtcp().await {
+        let room = match room.upgrade() {
+            Some(room) => room,
+            None => return,
+        };
+
+        for packet in packets {
+            let packet = packet.as_any();
+
+            if packet.downcast_ref::<PictureLossIndication>().is_some()
+                || packet.downcast_ref::<FullIntraRequest>().is_some()
+            {
+                if let Some(layer) = subscription.current_layer() {
+                    room.request_keyframe(&subscription.track, layer).await;
+                }
+            } else if let Some(remb) = packet.downcast_ref::<ReceiverEstimatedMaximumBitrate>() {
+                room.update_bandwidth(subscriber_id, remb.bitrate as u32).await;
+            }
+        }
+    }
+}
+
+async fn send_to_user(call_manager: &CallManager, user_id: Uuid, message: &SignalingMessage) {
+    let message_json = match serde_json::to_string(message) {
+        Ok(json) => json,
+        Err(e) => {
+            tracing::error!("Failed to serialize SFU signaling message: {}", e);
+            return;
+        }
+    };
+
+    let ws_message = tokio_tungstenite::tungstenite::Message::Text(message_json);
+    for connection in call_manager.get_user_connections(user_id).await {
+        if let Err(e) = connection.sender.send(ws_message.clone()) {
+            tracing::warn!("Failed to send SFU message to user {}: {}", user_id, e);
+        }
+    }
+}
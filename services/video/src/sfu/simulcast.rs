@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::SfuConfig;
+
+/// Simulcast encodings a publisher may send. Non-simulcast tracks are
+/// treated as a single `High` layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SimulcastLayer {
+    Low,
+    Medium,
+    High,
+}
+
+impl SimulcastLayer {
+    /// Maps the RTP stream id (`a=rid`) used by browsers to a layer. Chrome's
+    /// q/h/f are quarter, half and full resolution, so "h" is the middle layer.
+    pub fn from_rid(rid: &str) -> Self {
+        match rid {
+            "q" | "l" | "low" => SimulcastLayer::Low,
+            "h" | "m" | "mid" | "medium" => SimulcastLayer::Medium,
+            _ => SimulcastLayer::High,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LayerBitrates {
+    pub low_bps: u32,
+    pub medium_bps: u32,
+    pub high_bps: u32,
+}
+
+impl LayerBitrates {
+    pub fn from_config(config: &SfuConfig) -> Self {
+        Self {
+            low_bps: config.low_layer_bitrate_bps,
+            medium_bps: config.medium_layer_bitrate_bps,
+            high_bps: config.high_layer_bitrate_bps,
+        }
+    }
+
+    pub fn for_layer(&self, layer: SimulcastLayer) -> u32 {
+        match layer {
+            SimulcastLayer::Low => self.low_bps,
+            SimulcastLayer::Medium => self.medium_bps,
+            SimulcastLayer::High => self.high_bps,
+        }
+    }
+}
+
+/// Picks the highest layer that fits in a receiver's per-track bandwidth
+/// budget, falling back to the lowest available layer. Without a bandwidth
+/// estimate the highest layer is forwarded.
+pub fn select_layer(
+    available: &[SimulcastLayer],
+    budget_bps: Option<u32>,
+    bitrates: &LayerBitrates,
+) -> Option<SimulcastLayer> {
+    let lowest = available.iter().min().copied()?;
+    let highest = available.iter().max().copied()?;
+
+    let budget_bps = match budget_bps {
+        Some(budget_bps) => budget_bps,
+        None => return Some(highest),
+    };
+
+    Some(
+        available.iter()
+            .copied()
+            .filter(|layer| bitrates.for_layer(*layer) <= budget_bps)
+            .max()
+            .unwrap_or(lowest)
+    )
+}
+
+/// Keeps a subscriber's outgoing RTP sequence numbers and timestamps
+/// continuous when the forwarded simulcast layer changes. Each layer has its
+/// own sequence space, so without rewriting a switch looks like massive loss
+/// to the receiver.
+#[derive(Debug, Default)]
+pub struct SequenceRewriter {
+    seq_offset: u16,
+    ts_offset: u32,
+    last_seq: Option<u16>,
+    last_ts: u32,
+    resync: bool,
+}
+
+impl SequenceRewriter {
+    /// Marks a source switch. The next packet continues the outgoing
+    /// sequence, with its timestamp advanced by `elapsed_ticks` (the time
+    /// since the last forwarded packet in RTP clock units).
+    pub fn switch_source(&mut self, elapsed_ticks: u32) {
+        self.resync = true;
+        self.last_ts = self.last_ts.wrapping_add(elapsed_ticks.max(1)).wrapping_sub(1);
+    }
+
+    pub fn rewrite(&mut self, seq: u16, ts: u32) -> (u16, u32) {
+        if self.resync {
+            if let Some(last_seq) = self.last_seq {
+                self.seq_offset = last_seq.wrapping_add(1).wrapping_sub(seq);
+                self.ts_offset = self.last_ts.wrapping_add(1).wrapping_sub(ts);
+            }
+            self.resync = false;
+        }
+
+        let out_seq = seq.wrapping_add(self.seq_offset);
+        let out_ts = ts.wrapping_add(self.ts_offset);
+
+        // Reordered packets keep their rewritten numbers but don't move the high-water mark
+        let is_newer = self.last_seq.map_or(true, |last| out_seq.wrapping_sub(last) < 0x8000);
+        if is_newer {
+            self.last_seq = Some(out_seq);
+            self.last_ts = out_ts;
+        }
+
+        (out_seq, out_ts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitrates() -> LayerBitrates {
+        LayerBitrates {
+            low_bps: 150_000,
+            medium_bps: 500_000,
+            high_bps: 1_500_000,
+        }
+    }
+
+    #[test]
+    fn test_select_layer_by_budget() {
+        let all = [SimulcastLayer::Low, SimulcastLayer::Medium, SimulcastLayer::High];
+
+        assert_eq!(select_layer(&all, None, &bitrates()), Some(SimulcastLayer::High));
+        assert_eq!(select_layer(&all, Some(2_000_000), &bitrates()), Some(SimulcastLayer::High));
+        assert_eq!(select_layer(&all, Some(600_000), &bitrates()), Some(SimulcastLayer::Medium));
+        assert_eq!(select_layer(&all, Some(50_000), &bitrates()), Some(SimulcastLayer::Low));
+        assert_eq!(
+            select_layer(&[SimulcastLayer::Medium, SimulcastLayer::High], Some(50_000), &bitrates()),
+            Some(SimulcastLayer::Medium)
+        );
+        assert_eq!(select_layer(&[], Some(600_000), &bitrates()), None);
+    }
+
+    #[test]
+    fn test_from_rid() {
+        assert_eq!(SimulcastLayer::from_rid("l"), SimulcastLayer::Low);
+        assert_eq!(SimulcastLayer::from_rid("q"), SimulcastLayer::Low);
+        assert_eq!(SimulcastLayer::from_rid("m"), SimulcastLayer::Medium);
+        assert_eq!(SimulcastLayer::from_rid("h"), SimulcastLayer::Medium);
+        assert_eq!(SimulcastLayer::from_rid("f"), SimulcastLayer::High);
+    }
+
+    #[test]
+    fn test_rewriter_keeps_sequence_continuous_across_switch() {
+        let mut rewriter = SequenceRewriter::default();
+
+        assert_eq!(rewriter.rewrite(100, 9000), (100, 9000));
+        assert_eq!(rewriter.rewrite(101, 12000), (101, 12000));
+
+        rewriter.switch_source(3000);
+        assert_eq!(rewriter.rewrite(40000, 500), (102, 15000));
+        assert_eq!(rewriter.rewrite(40001, 3500), (103, 18000));
+    }
+
+    #[test]
+    fn test_rewriter_wraps_sequence_numbers() {
+        let mut rewriter = SequenceRewriter::default();
+
+        assert_eq!(rewriter.rewrite(65535, 0).0, 65535);
+        rewriter.switch_source(1);
+        assert_eq!(rewriter.rewrite(7, 0).0, 0);
+        assert_eq!(rewriter.rewrite(8, 0).0, 1);
+    }
+}
@@ -6,16 +6,36 @@ use futures::StreamExt;
 
 use linkwithmentor_common::{AppError, RedisService};
 use crate::{
-    call_manager::CallManager,
+    call_manager::{Admission, CallManager},
+    call_notifier::CallNotifier,
+    recording::RecordingService,
+    sfu::SfuService,
+    transcription::TranscriptionService,
     turn_client::TurnClient,
-    models::{SignalingMessage, CallState, CallType, ActiveCall, CallConnection},
+    models::{
+        SignalingMessage, CallState, CallType, CallTopology, CallQualityMetrics, ActiveCall, CallConnection,
+        ParticipantConnectionState,
+    },
 };
 
+/// Result of starting a call. SFU calls also carry the server's answer to
+/// the caller's offer.
+#[derive(Debug)]
+pub struct CallOfferOutcome {
+    pub call_id: Uuid,
+    pub topology: CallTopology,
+    pub sdp_answer: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct SignalingService {
     call_manager: CallManager,
     turn_client: TurnClient,
     redis_service: RedisService,
+    sfu_service: SfuService,
+    recording_service: RecordingService,
+    transcription_service: TranscriptionService,
+    call_notifier: CallNotifier,
     subscriber_client: Arc<RwLock<Option<redis::aio::Connection>>>,
 }
 
@@ -24,11 +44,19 @@ impl SignalingService {
         call_manager: CallManager,
         turn_client: TurnClient,
         redis_service: RedisService,
+        sfu_service: SfuService,
+        recording_service: RecordingService,
+        transcription_service: TranscriptionService,
+        call_notifier: CallNotifier,
     ) -> Self {
         Self {
             call_manager,
             turn_client,
             redis_service,
+            sfu_service,
+            recording_service,
+            transcription_service,
+            call_notifier,
             subscriber_client: Arc::new(RwLock::new(None)),
         }
     }
@@ -187,6 +215,16 @@ impl SignalingService {
 
     // Public methods for handling signaling
 
+    /// Group call types go through the SFU when it is enabled; everything
+    /// else is peer-to-peer between exactly two people.
+    pub fn topology_for(&self, call_type: &CallType) -> CallTopology {
+        if call_type.is_group() && self.sfu_service.is_enabled() {
+            CallTopology::Sfu
+        } else {
+            CallTopology::Mesh
+        }
+    }
+
     pub async fn handle_call_offer(
         &self,
         caller_id: Uuid,
@@ -194,10 +232,34 @@ impl SignalingService {
         session_id: Option<Uuid>,
         call_type: CallType,
         sdp: String,
-    ) -> Result<Uuid, AppError> {
+        invitee_ids: Vec<Uuid>,
+    ) -> Result<CallOfferOutcome, AppError> {
+        if !call_type.is_group() && !invitee_ids.is_empty() {
+            return Err(AppError::BadRequest("Only group calls can have additional invitees".to_string()));
+        }
+
+        let topology = self.topology_for(&call_type);
+
+        match topology {
+            // A peer-to-peer offer is negotiated for one specific peer, so it
+            // can't be shared with a third participant
+            CallTopology::Mesh if !invitee_ids.is_empty() => {
+                return Err(AppError::BadRequest(
+                    "Calls with more than two participants are not available right now".to_string()
+                ));
+            }
+            CallTopology::Sfu if invitee_ids.len() + 2 > self.sfu_service.max_participants() as usize => {
+                return Err(AppError::BadRequest(format!(
+                    "This call type supports at most {} participants",
+                    self.sfu_service.max_participants()
+                )));
+            }
+            _ => {}
+        }
+
         // Create new call
         let call_id = self.call_manager
-            .create_call(caller_id, callee_id, session_id, call_type.clone())
+            .create_call(caller_id, callee_id, session_id, call_type.clone(), topology.clone(), &invitee_ids)
             .await?;
 
         // Add caller as participant
@@ -206,23 +268,148 @@ impl SignalingService {
             .add_participant(call_id, caller_id, caller_username)
             .await?;
 
-        // Send offer to callee
-        let offer_message = SignalingMessage::CallOffer {
-            call_id,
-            caller_id,
-            callee_id,
-            session_id,
-            sdp,
-            call_type,
+        // In SFU calls the caller's offer is answered by the server, and
+        // invitees make their own offers to it when they accept
+        let (sdp, sdp_answer) = match topology {
+            CallTopology::Mesh => (sdp, None),
+            CallTopology::Sfu => {
+                let answer = self.sfu_service.handle_offer(call_id, caller_id, sdp).await?;
+                (String::new(), Some(answer))
+            }
         };
 
-        self.publish_signaling_message("webrtc:signaling", &offer_message).await?;
-        self.send_to_user(callee_id, offer_message).await?;
+        let invited = self.call_manager.get_call_participants(call_id).await;
+        for invitee_id in invited.into_iter().filter(|id| *id != caller_id) {
+            let offer_message = SignalingMessage::CallOffer {
+                call_id,
+                caller_id,
+                callee_id: invitee_id,
+                session_id,
+                sdp: sdp.clone(),
+                call_type: call_type.clone(),
+                topology: topology.clone(),
+            };
+
+            self.publish_signaling_message("webrtc:signaling", &offer_message).await?;
+            self.send_to_user(invitee_id, offer_message).await?;
+        }
 
         // Update call state to ringing
         self.call_manager.update_call_state(call_id, CallState::Ringing).await?;
 
-        Ok(call_id)
+        Ok(CallOfferOutcome {
+            call_id,
+            topology,
+            sdp_answer,
+        })
+    }
+
+    /// Joins (or renegotiates with) the SFU of a group call and returns the
+    /// server's SDP answer, or None if the participant has to wait for the
+    /// host to admit them. In a peer-to-peer call being recorded or
+    /// captioned this connects the participant to the recorder instead.
+    pub async fn handle_sfu_offer(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        username: &str,
+        sdp: String,
+    ) -> Result<Option<String>, AppError> {
+        let call = self.call_manager.get_call(call_id).await
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if call.topology == CallTopology::Mesh {
+            if !call.participants.contains_key(&user_id) {
+                return Err(AppError::Forbidden("Not a participant in this call".to_string()));
+            }
+            if !self.sfu_service.has_room(call_id) {
+                return Err(AppError::BadRequest("Call is not being recorded or captioned".to_string()));
+            }
+            return self.sfu_service.handle_offer(call_id, user_id, sdp).await.map(Some);
+        }
+
+        if !self.call_manager.get_call_participants(call_id).await.contains(&user_id) {
+            return Err(AppError::Forbidden("Not invited to this call".to_string()));
+        }
+
+        let is_joining = call.participants.get(&user_id)
+            .map_or(true, |participant| participant.left_at.is_some());
+        if is_joining && self.call_manager.check_admission(call_id, user_id, username).await? == Admission::Waiting {
+            self.send_to_user(user_id, SignalingMessage::WaitingForHost { call_id }).await?;
+            self.send_waiting_room(call_id).await?;
+            return Ok(None);
+        }
+
+        let answer = self.sfu_service.handle_offer(call_id, user_id, sdp).await?;
+
+        if is_joining {
+            self.call_manager
+                .add_participant(call_id, user_id, username.to_string())
+                .await?;
+
+            let joined_message = SignalingMessage::ParticipantJoined {
+                call_id,
+                participant_id: user_id,
+                username: username.to_string(),
+            };
+            for participant_id in self.call_manager.get_active_participants(call_id).await {
+                if participant_id != user_id {
+                    self.send_to_user(participant_id, joined_message.clone()).await?;
+                }
+            }
+
+            if call.state == CallState::Ringing {
+                self.call_manager.update_call_state(call_id, CallState::Connected).await?;
+            }
+        }
+
+        Ok(Some(answer))
+    }
+
+    /// Applies a client's answer to a server-initiated SFU renegotiation.
+    pub async fn handle_sfu_answer(&self, call_id: Uuid, user_id: Uuid, sdp: String) -> Result<(), AppError> {
+        self.get_sfu_call(call_id).await?;
+        self.sfu_service.handle_answer(call_id, user_id, sdp).await
+    }
+
+    /// Stores quality metrics, advises the participant when their quality
+    /// calls for it and feeds the receiver's bandwidth into SFU simulcast
+    /// layer selection.
+    pub async fn handle_quality_report(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        metrics: CallQualityMetrics,
+    ) -> Result<(), AppError> {
+        let call = self.call_manager.get_call(call_id).await
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        // Only people still in the call can report on it; the reports feed refund flags
+        if !call.participants.get(&user_id).map_or(false, |participant| participant.left_at.is_none()) {
+            return Err(AppError::Forbidden("Not a participant in this call".to_string()));
+        }
+
+        let bandwidth = metrics.bandwidth;
+        let assessment = self.call_manager.record_quality_metrics(call_id, user_id, metrics).await?;
+
+        if let Some(assessment) = assessment.filter(|assessment| assessment.should_advise) {
+            let advice = SignalingMessage::QualityAdvice {
+                call_id,
+                participant_id: user_id,
+                mos: assessment.mos,
+                rating: assessment.rating,
+                suggestions: assessment.suggestions,
+            };
+            self.send_to_user(user_id, advice).await?;
+        }
+
+        if let Some(bandwidth) = bandwidth {
+            if call.topology == CallTopology::Sfu {
+                self.sfu_service.update_bandwidth(call_id, user_id, bandwidth).await;
+            }
+        }
+
+        Ok(())
     }
 
     pub async fn handle_call_answer(
@@ -235,6 +422,10 @@ impl SignalingService {
         let call = self.call_manager.get_call(call_id).await
             .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
 
+        if call.topology == CallTopology::Sfu {
+            return Err(AppError::BadRequest("Join group calls by sending an SFU offer".to_string()));
+        }
+
         if call.callee_id != user_id {
             return Err(AppError::Forbidden("Not authorized to answer this call".to_string()));
         }
@@ -265,6 +456,17 @@ impl SignalingService {
         let call = self.call_manager.get_call(call_id).await
             .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
 
+        // Declining a group call only withdraws this invitee
+        if call.topology == CallTopology::Sfu {
+            if user_id == call.caller_id || !self.call_manager.get_call_participants(call_id).await.contains(&user_id) {
+                return Err(AppError::Forbidden("Not authorized to reject this call".to_string()));
+            }
+
+            self.call_manager.remove_participant(call_id, user_id).await?;
+            self.send_to_user(call.caller_id, SignalingMessage::CallReject { call_id, reason }).await?;
+            return Ok(());
+        }
+
         if call.callee_id != user_id {
             return Err(AppError::Forbidden("Not authorized to reject this call".to_string()));
         }
@@ -297,6 +499,13 @@ impl SignalingService {
             return Err(AppError::Forbidden("Not a participant in this call".to_string()));
         }
 
+        // In SFU calls the server is the only remote peer
+        if call.topology == CallTopology::Sfu {
+            return self.sfu_service
+                .add_ice_candidate(call_id, user_id, candidate, sdp_mid, sdp_mline_index)
+                .await;
+        }
+
         // Send ICE candidate to other participants
         let ice_message = SignalingMessage::IceCandidate {
             call_id,
@@ -330,6 +539,22 @@ impl SignalingService {
             return Err(AppError::Forbidden("Not a participant in this call".to_string()));
         }
 
+        // A caller hanging up before anyone answered cancels the call
+        if user_id == call.caller_id && matches!(call.state, CallState::Initiating | CallState::Ringing) {
+            self.call_manager.update_call_state(call_id, CallState::Cancelled).await?;
+
+            for invitee_id in self.call_manager.get_call_participants(call_id).await {
+                if invitee_id != user_id {
+                    self.send_to_user(invitee_id, SignalingMessage::CallCancel { call_id }).await?;
+                }
+            }
+        }
+
+        // Hanging up a group call only leaves it; the call ends with the last participant
+        if call.topology == CallTopology::Sfu {
+            return self.leave_sfu_call(&call, user_id).await;
+        }
+
         // Send end message to all participants
         let end_message = SignalingMessage::CallEnd { call_id };
         self.publish_signaling_message("webrtc:signaling", &end_message).await?;
@@ -340,13 +565,31 @@ impl SignalingService {
             }
         }
 
-        // End the call
-        self.call_manager.update_call_state(call_id, CallState::Ended).await?;
+        // End the call, finishing any recording and its recorder room first
+        self.close_call(call_id).await
+    }
+
+    /// Ends a call for everyone, finishing any recording and closing its
+    /// media room first.
+    pub async fn close_call(&self, call_id: Uuid) -> Result<(), AppError> {
+        self.recording_service.handle_call_ended(call_id).await;
+        self.transcription_service.handle_call_ended(call_id).await;
+        self.sfu_service.close_room(call_id).await;
         self.call_manager.end_call(call_id).await?;
 
+        if let Err(e) = self.turn_client.release_call_allocations(call_id).await {
+            tracing::warn!("Failed to release TURN allocations for call {}: {}", call_id, e);
+        }
+
         Ok(())
     }
 
+    /// Drops a participant's SFU connection without taking them out of the
+    /// call, e.g. while they are in a breakout room.
+    pub async fn leave_media(&self, call_id: Uuid, user_id: Uuid) {
+        self.sfu_service.leave(call_id, user_id).await;
+    }
+
     pub async fn handle_media_state_change(
         &self,
         call_id: Uuid,
@@ -363,7 +606,11 @@ impl SignalingService {
             video_muted: false,
         };
 
-        self.call_manager.update_media_state(call_id, user_id, media_state).await?;
+        let media_state = self.call_manager.update_media_state(call_id, user_id, media_state).await?;
+
+        // Unmuting lifts a host mute on the media server too
+        self.sfu_service.hold_audio(call_id, user_id, media_state.audio_muted).await;
+        self.sfu_service.hold_video(call_id, user_id, media_state.video_muted).await;
 
         // Broadcast media state change
         let media_message = SignalingMessage::MediaStateChanged {
@@ -379,9 +626,425 @@ impl SignalingService {
         Ok(())
     }
 
+    // Waiting room and host controls
+
+    /// Applies a host control message and tells the affected participants.
+    pub async fn handle_host_action(&self, host_id: Uuid, message: SignalingMessage) -> Result<(), AppError> {
+        match message {
+            SignalingMessage::AdmitParticipant { call_id, participant_id } => {
+                self.call_manager.admit_participant(call_id, host_id, participant_id).await?;
+                self.send_to_user(participant_id, message).await?;
+                self.send_waiting_room(call_id).await?;
+            }
+
+            SignalingMessage::DenyParticipant { call_id, participant_id } => {
+                self.call_manager.deny_participant(call_id, host_id, participant_id).await?;
+                self.send_to_user(participant_id, message).await?;
+                self.send_waiting_room(call_id).await?;
+            }
+
+            SignalingMessage::MuteParticipant { call_id, participant_id } => {
+                self.call_manager.mute_participant(call_id, host_id, participant_id).await?;
+                self.sfu_service.hold_audio(call_id, participant_id, true).await;
+                self.send_to_participants(call_id, message).await?;
+            }
+
+            SignalingMessage::StopParticipantVideo { call_id, participant_id } => {
+                self.call_manager.stop_participant_video(call_id, host_id, participant_id).await?;
+                self.sfu_service.hold_video(call_id, participant_id, true).await;
+                self.send_to_participants(call_id, message).await?;
+            }
+
+            SignalingMessage::AllowParticipantVideo { call_id, participant_id } => {
+                self.call_manager.allow_participant_video(call_id, host_id, participant_id).await?;
+                self.sfu_service.hold_video(call_id, participant_id, false).await;
+                self.send_to_participants(call_id, message).await?;
+            }
+
+            SignalingMessage::StopParticipantScreenShare { call_id, participant_id } => {
+                self.call_manager.stop_participant_screen_share(call_id, host_id, participant_id).await?;
+                self.send_to_participants(call_id, message).await?;
+            }
+
+            SignalingMessage::RemoveParticipant { call_id, participant_id } => {
+                let call = self.call_manager.get_call(call_id).await
+                    .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+                self.call_manager.remove_participant_by_host(call_id, host_id, participant_id).await?;
+                if call.topology == CallTopology::Sfu {
+                    self.sfu_service.leave(call_id, participant_id).await;
+                }
+                self.recording_service.handle_participant_left(&call, participant_id).await;
+                self.transcription_service.handle_participant_left(&call, participant_id).await;
+
+                self.send_to_user(participant_id, message.clone()).await?;
+                self.send_to_participants(call_id, message).await?;
+            }
+
+            SignalingMessage::LockCall { call_id, locked } => {
+                self.call_manager.set_locked(call_id, host_id, locked).await?;
+                self.send_to_participants(call_id, message).await?;
+            }
+
+            SignalingMessage::TransferHost { call_id, participant_id } => {
+                self.call_manager.transfer_host(call_id, host_id, participant_id).await?;
+                self.send_to_participants(call_id, message).await?;
+                // The waiting room and hand queue are the new host's to manage
+                self.send_waiting_room(call_id).await?;
+                self.send_hand_queue(call_id).await?;
+            }
+
+            _ => return Err(AppError::BadRequest("Not a host control message".to_string())),
+        }
+
+        Ok(())
+    }
+
+    // Sends the current waiting room to the host
+    async fn send_waiting_room(&self, call_id: Uuid) -> Result<(), AppError> {
+        let call = match self.call_manager.get_call(call_id).await {
+            Some(call) => call,
+            None => return Ok(()),
+        };
+
+        let message = SignalingMessage::WaitingRoomUpdated {
+            call_id,
+            waiting: call.waiting_room,
+        };
+        self.send_to_user(call.host_id, message).await
+    }
+
+    // Raised hands and reactions
+
+    /// Applies a raise/lower hand or reaction message and tells the call.
+    pub async fn handle_engagement(&self, user_id: Uuid, message: SignalingMessage) -> Result<(), AppError> {
+        match message {
+            SignalingMessage::RaiseHand { call_id } => {
+                if let Some(position) = self.call_manager.raise_hand(call_id, user_id).await? {
+                    self.send_to_participants(call_id, SignalingMessage::HandRaised {
+                        call_id,
+                        participant_id: user_id,
+                        position,
+                    }).await?;
+                    self.send_hand_queue(call_id).await?;
+                }
+            }
+
+            SignalingMessage::LowerHand { call_id, participant_id } => {
+                let participant_id = participant_id.unwrap_or(user_id);
+                if self.call_manager.lower_hand(call_id, user_id, participant_id).await? {
+                    self.send_to_participants(call_id, SignalingMessage::HandLowered {
+                        call_id,
+                        participant_id,
+                        lowered_by: user_id,
+                    }).await?;
+                    self.send_hand_queue(call_id).await?;
+                }
+            }
+
+            SignalingMessage::Reaction { call_id, emoji, .. } => {
+                if self.call_manager.add_reaction(call_id, user_id, &emoji).await? {
+                    self.send_to_participants(call_id, SignalingMessage::Reaction {
+                        call_id,
+                        participant_id: Some(user_id),
+                        emoji,
+                    }).await?;
+                }
+            }
+
+            _ => return Err(AppError::BadRequest("Not an engagement message".to_string())),
+        }
+
+        Ok(())
+    }
+
+    // Sends the current hand queue to the host
+    async fn send_hand_queue(&self, call_id: Uuid) -> Result<(), AppError> {
+        let call = match self.call_manager.get_call(call_id).await {
+            Some(call) => call,
+            None => return Ok(()),
+        };
+
+        let message = SignalingMessage::HandQueueUpdated {
+            call_id,
+            queue: call.raised_hands,
+        };
+        self.send_to_user(call.host_id, message).await
+    }
+
+    async fn send_to_participants(&self, call_id: Uuid, message: SignalingMessage) -> Result<(), AppError> {
+        for participant_id in self.call_manager.get_active_participants(call_id).await {
+            self.send_to_user(participant_id, message.clone()).await?;
+        }
+
+        Ok(())
+    }
+
+    // Reconnection
+
+    /// Called when a user's last signaling connection closes. They stay in
+    /// their calls for the reconnect grace period and everyone else is told
+    /// they are reconnecting.
+    pub async fn handle_connection_lost(&self, user_id: Uuid) -> Result<(), AppError> {
+        if !self.call_manager.get_user_connections(user_id).await.is_empty() {
+            return Ok(());
+        }
+
+        for call in self.call_manager.get_user_active_calls(user_id).await {
+            let call_id = call.call_id;
+            if let Some(reconnect_deadline) = self.call_manager.mark_reconnecting(call_id, user_id).await? {
+                let message = SignalingMessage::ParticipantReconnecting {
+                    call_id,
+                    participant_id: user_id,
+                    reconnect_deadline,
+                };
+                self.send_to_others(&call, user_id, message).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Called when a user opens a signaling connection. Each call they were
+    /// reconnecting to is resumed with its current state; calls through the
+    /// SFU wait until the media connection is back as well.
+    pub async fn handle_connection_restored(&self, user_id: Uuid) -> Result<(), AppError> {
+        for call in self.call_manager.get_user_active_calls(user_id).await {
+            let call_id = call.call_id;
+            let reconnecting = call.participants.get(&user_id)
+                .map_or(false, |participant| participant.connection_state == ParticipantConnectionState::Reconnecting);
+            if !reconnecting {
+                continue;
+            }
+
+            let media_connected = call.topology == CallTopology::Mesh
+                || self.sfu_service.is_connected(call_id, user_id).await;
+            if media_connected && self.call_manager.mark_reconnected(call_id, user_id).await? {
+                let message = SignalingMessage::ParticipantReconnected {
+                    call_id,
+                    participant_id: user_id,
+                };
+                self.send_to_others(&call, user_id, message).await?;
+            }
+
+            let call = self.call_manager.get_call(call_id).await.unwrap_or(call);
+            let resumed_message = SignalingMessage::CallResumed {
+                call_id,
+                state: call.state.clone(),
+                participants: call.participants.values()
+                    .filter(|participant| participant.left_at.is_none())
+                    .cloned()
+                    .collect(),
+            };
+            self.send_to_user(user_id, resumed_message).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Relays an ICE restart offer to the other peer of a peer-to-peer call.
+    pub async fn handle_ice_restart_offer(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        target_id: Option<Uuid>,
+        sdp: String,
+    ) -> Result<(), AppError> {
+        let target_id = self.ice_restart_target(call_id, user_id, target_id).await?;
+
+        let message = SignalingMessage::IceRestartOffer {
+            call_id,
+            participant_id: Some(user_id),
+            sdp,
+        };
+        self.send_to_user(target_id, message).await
+    }
+
+    /// Relays the answer to an ICE restart offer back to the peer that sent it.
+    pub async fn handle_ice_restart_answer(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        target_id: Option<Uuid>,
+        sdp: String,
+    ) -> Result<(), AppError> {
+        let target_id = self.ice_restart_target(call_id, user_id, target_id).await?;
+
+        let message = SignalingMessage::IceRestartAnswer {
+            call_id,
+            participant_id: Some(user_id),
+            sdp,
+        };
+        self.send_to_user(target_id, message).await
+    }
+
+    /// Periodically removes participants who didn't reconnect in time.
+    pub async fn start_reconnect_monitor(&self) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+            loop {
+                interval.tick().await;
+
+                for (call_id, user_id) in service.call_manager.expired_reconnections().await {
+                    tracing::info!("Participant {} did not reconnect to call {} in time", user_id, call_id);
+                    if let Err(e) = service.handle_call_end(call_id, user_id).await {
+                        tracing::warn!("Failed to remove {} from call {}: {}", user_id, call_id, e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Periodically ends calls nobody answered in time as missed.
+    pub async fn start_ring_monitor(&self) {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+            loop {
+                interval.tick().await;
+
+                for call in service.call_manager.expired_rings().await {
+                    if let Err(e) = service.handle_missed_call(&call).await {
+                        tracing::warn!("Failed to end unanswered call {}: {}", call.call_id, e);
+                    }
+                }
+            }
+        });
+    }
+
+    async fn handle_missed_call(&self, call: &ActiveCall) -> Result<(), AppError> {
+        // Someone may have answered since the sweep looked
+        let Some(call) = self.call_manager.mark_missed(call.call_id).await? else {
+            return Ok(());
+        };
+        let call = &call;
+
+        let invited = self.call_manager.get_call_participants(call.call_id).await;
+        let missed_by: Vec<Uuid> = invited.iter()
+            .copied()
+            .filter(|user_id| *user_id != call.caller_id && !call.participants.contains_key(user_id))
+            .collect();
+
+        // Stop the caller's and invitees' ringing
+        let missed_message = SignalingMessage::CallMissed { call_id: call.call_id };
+        for user_id in &invited {
+            self.send_to_user(*user_id, missed_message.clone()).await?;
+        }
+
+        self.close_call(call.call_id).await?;
+
+        for user_id in missed_by {
+            if let Err(e) = self.call_notifier.notify_missed_call(call, user_id).await {
+                tracing::warn!("Failed to notify {} of missed call {}: {}", user_id, call.call_id, e);
+            }
+        }
+
+        tracing::info!("Call {} was not answered in time", call.call_id);
+        Ok(())
+    }
+
     // Helper methods
 
-    async fn send_to_user(&self, user_id: Uuid, message: SignalingMessage) -> Result<(), AppError> {
+    async fn ice_restart_target(
+        &self,
+        call_id: Uuid,
+        user_id: Uuid,
+        target_id: Option<Uuid>,
+    ) -> Result<Uuid, AppError> {
+        let call = self.call_manager.get_call(call_id).await
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if !call.participants.contains_key(&user_id) {
+            return Err(AppError::Forbidden("Not a participant in this call".to_string()));
+        }
+
+        // The SFU is the only remote peer; restarting ICE with it is a client renegotiation
+        if call.topology == CallTopology::Sfu {
+            return Err(AppError::BadRequest("Restart ICE in group calls by sending an SFU offer".to_string()));
+        }
+
+        let others: Vec<Uuid> = self.call_manager.get_active_participants(call_id).await
+            .into_iter()
+            .filter(|participant_id| *participant_id != user_id)
+            .collect();
+
+        match target_id {
+            Some(target_id) if others.contains(&target_id) => Ok(target_id),
+            Some(_) => Err(AppError::BadRequest("Target is not a participant in this call".to_string())),
+            None if others.len() == 1 => Ok(others[0]),
+            None => Err(AppError::BadRequest("Specify which participant to restart ICE with".to_string())),
+        }
+    }
+
+    async fn send_to_others(&self, call: &ActiveCall, user_id: Uuid, message: SignalingMessage) -> Result<(), AppError> {
+        for participant in call.participants.values() {
+            if participant.user_id != user_id && participant.left_at.is_none() {
+                self.send_to_user(participant.user_id, message.clone()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn leave_sfu_call(&self, call: &ActiveCall, user_id: Uuid) -> Result<(), AppError> {
+        let call_id = call.call_id;
+
+        self.sfu_service.leave(call_id, user_id).await;
+        self.call_manager.remove_participant(call_id, user_id).await?;
+        self.recording_service.handle_participant_left(call, user_id).await;
+        self.transcription_service.handle_participant_left(call, user_id).await;
+
+        let remaining = self.call_manager.get_active_participants(call_id).await;
+        if remaining.is_empty() {
+            return self.close_call(call_id).await;
+        }
+
+        if call.host_id == user_id {
+            if let Some(updated) = self.call_manager.get_call(call_id).await {
+                let host_message = SignalingMessage::TransferHost {
+                    call_id,
+                    participant_id: updated.host_id,
+                };
+                for participant_id in &remaining {
+                    self.send_to_user(*participant_id, host_message.clone()).await?;
+                }
+                self.send_waiting_room(call_id).await?;
+                self.send_hand_queue(call_id).await?;
+            }
+        } else if call.raised_hands.iter().any(|hand| hand.user_id == user_id) {
+            self.send_hand_queue(call_id).await?;
+        }
+
+        let username = call.participants.get(&user_id)
+            .map(|participant| participant.username.clone())
+            .unwrap_or_default();
+        let left_message = SignalingMessage::ParticipantLeft {
+            call_id,
+            participant_id: user_id,
+            username,
+        };
+        for participant_id in remaining {
+            self.send_to_user(participant_id, left_message.clone()).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_sfu_call(&self, call_id: Uuid) -> Result<ActiveCall, AppError> {
+        let call = self.call_manager.get_call(call_id).await
+            .ok_or_else(|| AppError::NotFound("Call not found".to_string()))?;
+
+        if call.topology != CallTopology::Sfu {
+            return Err(AppError::BadRequest("Call does not use the SFU".to_string()));
+        }
+
+        Ok(call)
+    }
+
+    pub async fn send_to_user(&self, user_id: Uuid, message: SignalingMessage) -> Result<(), AppError> {
         let connections = self.call_manager.get_user_connections(user_id).await;
         
         let message_json = serde_json::to_string(&message)
@@ -414,8 +1077,12 @@ impl SignalingService {
         Ok(())
     }
 
-    pub async fn get_turn_credentials(&self, user_id: Uuid) -> Result<crate::models::TurnCredentials, AppError> {
-        self.turn_client.generate_credentials(user_id).await
+    pub async fn get_turn_credentials(
+        &self,
+        user_id: Uuid,
+        call_id: Option<Uuid>,
+    ) -> Result<crate::models::TurnCredentials, AppError> {
+        self.turn_client.generate_credentials(user_id, call_id).await
     }
 
     pub async fn get_ice_servers(&self, user_id: Uuid) -> Result<Vec<crate::turn_client::IceServer>, AppError> {
@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+
+use linkwithmentor_common::AppError;
+use crate::recording::muxer::run_ffmpeg;
+
+// What speech recognizers expect: 16 kHz mono 16-bit PCM
+const WAV_ARGS: [&str; 6] = ["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"];
+
+fn input_args(input: &Path) -> Vec<String> {
+    vec![
+        "-y".to_string(),
+        "-hide_banner".to_string(),
+        "-i".to_string(),
+        input.display().to_string(),
+        "-vn".to_string(),
+    ]
+}
+
+/// Converts an audio file to a WAV the recognizer can read.
+pub async fn to_wav(ffmpeg_path: &str, input: &Path, output: &Path) -> Result<(), AppError> {
+    let mut args = input_args(input);
+    args.extend(WAV_ARGS.iter().map(|arg| arg.to_string()));
+    args.push(output.display().to_string());
+
+    run_ffmpeg(ffmpeg_path, args).await
+}
+
+/// Converts a whole recorded track to WAVs of `chunk_seconds` each, named
+/// `{prefix}_00000.wav` onwards, and returns them in order.
+pub async fn split_to_wav(
+    ffmpeg_path: &str,
+    input: &Path,
+    dir: &Path,
+    prefix: &str,
+    chunk_seconds: u64,
+) -> Result<Vec<PathBuf>, AppError> {
+    let mut args = input_args(input);
+    args.extend(WAV_ARGS.iter().map(|arg| arg.to_string()));
+    args.extend([
+        "-f".to_string(),
+        "segment".to_string(),
+        "-segment_time".to_string(),
+        chunk_seconds.to_string(),
+        dir.join(format!("{}_%05d.wav", prefix)).display().to_string(),
+    ]);
+
+    run_ffmpeg(ffmpeg_path, args).await?;
+
+    let mut entries = tokio::fs::read_dir(dir).await
+        .map_err(|e| AppError::Internal(format!("Failed to read {}: {}", dir.display(), e)))?;
+
+    let mut chunks = Vec::new();
+    while let Some(entry) = entries.next_entry().await
+        .map_err(|e| AppError::Internal(format!("Failed to read {}: {}", dir.display(), e)))?
+    {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(&format!("{}_", prefix)) && name.ends_with(".wav") {
+            chunks.push(entry.path());
+        }
+    }
+
+    // Zero-padded indexes sort in time order
+    chunks.sort();
+    Ok(chunks)
+}
@@ -0,0 +1,874 @@
+pub mod audio;
+pub mod recognizer;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use linkwithmentor_common::AppError;
+use crate::{
+    call_manager::CallManager,
+    config::{RecordingConfig, TranscriptionConfig, VideoServiceConfig},
+    models::{
+        ActiveCall, CallTopology, CallTranscript, SignalingMessage, TranscriptResponse, TranscriptSegment,
+        TranscriptSource, TranscriptStatus,
+    },
+    sfu::{
+        captions::{CaptionChunk, CaptionTap},
+        recorder::RecordedTrackFile,
+        SfuService,
+    },
+};
+
+use self::recognizer::{RecognizedSegment, SpeechRecognizer};
+
+/// Live captions and call transcripts. Captions start once every participant
+/// in the call has consented; while they are on, the SFU cuts each consenting
+/// participant's audio into short chunks that are recognized in order and
+/// sent to everyone in the call as they come in. When captions stop (or
+/// a recording of an uncaptioned call finishes) the timestamped transcript
+/// is saved and attached to the meeting's session notes.
+#[derive(Clone)]
+pub struct TranscriptionService {
+    db_pool: PgPool,
+    config: TranscriptionConfig,
+    ffmpeg_path: String,
+    scratch_path: PathBuf,
+    call_manager: CallManager,
+    sfu_service: SfuService,
+    recognizer: Arc<dyn SpeechRecognizer>,
+    // Live captions by call
+    active: Arc<DashMap<Uuid, ActiveCaptions>>,
+}
+
+// Chunks waiting for the recognizer, per call; newer chunks are dropped
+// while it is this far behind
+const CAPTION_QUEUE_CHUNKS: usize = 8;
+
+struct ActiveCaptions {
+    transcript_id: Uuid,
+    requested_by: Uuid,
+    status: TranscriptStatus,
+    // Participants whose consent is needed before captions start
+    required: HashSet<Uuid>,
+    consented: HashSet<Uuid>,
+    started_at: DateTime<Utc>,
+    // Recognizes chunks until the caption tap is dropped; set once captions are live
+    worker: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl ActiveCaptions {
+    fn pending_consent(&self) -> Vec<Uuid> {
+        self.required.difference(&self.consented).copied().collect()
+    }
+
+    fn response(&self) -> TranscriptResponse {
+        TranscriptResponse {
+            transcript_id: self.transcript_id,
+            status: self.status.clone(),
+            started_at: self.started_at,
+            pending_consent: self.pending_consent(),
+        }
+    }
+}
+
+impl TranscriptionService {
+    pub fn new(
+        db_pool: PgPool,
+        config: &TranscriptionConfig,
+        recording_config: &RecordingConfig,
+        video_config: &VideoServiceConfig,
+        call_manager: CallManager,
+        sfu_service: SfuService,
+        recognizer: Arc<dyn SpeechRecognizer>,
+    ) -> Self {
+        Self {
+            db_pool,
+            config: config.clone(),
+            ffmpeg_path: recording_config.ffmpeg_path.clone(),
+            scratch_path: PathBuf::from(&video_config.recording_storage_path).join("transcripts"),
+            call_manager,
+            sfu_service,
+            recognizer,
+            active: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Asks everyone else in the call to consent to live captions. The
+    /// requester's consent is implied.
+    pub async fn start_captions(&self, call: &ActiveCall, user_id: Uuid) -> Result<TranscriptResponse, AppError> {
+        if !self.config.enabled {
+            return Err(AppError::BadRequest("Captions are not enabled".to_string()));
+        }
+        if self.active.contains_key(&call.call_id) {
+            return Err(AppError::Conflict("Call already has captions enabled".to_string()));
+        }
+
+        let required: HashSet<Uuid> = self.call_manager
+            .get_active_participants(call.call_id)
+            .await
+            .into_iter()
+            .collect();
+
+        let transcript_id = Uuid::new_v4();
+        let started_at = Utc::now();
+        self.create_transcript(transcript_id, call.call_id, user_id, TranscriptSource::Captions, TranscriptStatus::AwaitingConsent, started_at)
+            .await?;
+
+        self.active.insert(call.call_id, ActiveCaptions {
+            transcript_id,
+            requested_by: user_id,
+            status: TranscriptStatus::AwaitingConsent,
+            required,
+            consented: HashSet::new(),
+            started_at,
+            worker: None,
+        });
+
+        let message = SignalingMessage::CaptionsConsentRequested {
+            call_id: call.call_id,
+            transcript_id,
+            requested_by: user_id,
+        };
+        for participant_id in self.call_manager.get_active_participants(call.call_id).await {
+            if participant_id != user_id {
+                self.send_to_user(participant_id, &message).await;
+            }
+        }
+
+        tracing::info!("User {} requested captions for call {}", user_id, call.call_id);
+
+        self.respond_to_consent(call, user_id, true).await
+    }
+
+    /// Records a participant's answer. A refusal cancels pending captions;
+    /// once everyone has agreed, captions start. Participants who join later
+    /// are only captioned after they consent too.
+    pub async fn respond_to_consent(
+        &self,
+        call: &ActiveCall,
+        user_id: Uuid,
+        granted: bool,
+    ) -> Result<TranscriptResponse, AppError> {
+        let transcript_id = self.active.get(&call.call_id)
+            .map(|captions| captions.transcript_id)
+            .ok_or_else(|| AppError::NotFound("No captions requested for this call".to_string()))?;
+
+        let query = r#"
+            INSERT INTO call_transcript_consents (transcript_id, user_id, granted, responded_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (transcript_id, user_id) DO UPDATE SET
+                granted = EXCLUDED.granted,
+                responded_at = EXCLUDED.responded_at
+        "#;
+
+        sqlx::query(query)
+            .bind(transcript_id)
+            .bind(user_id)
+            .bind(granted)
+            .bind(Utc::now())
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record consent: {}", e)))?;
+
+        let (status, ready) = {
+            let mut entry = self.active.get_mut(&call.call_id)
+                .ok_or_else(|| AppError::NotFound("No captions requested for this call".to_string()))?;
+            if granted {
+                entry.consented.insert(user_id);
+            }
+            (entry.status.clone(), entry.pending_consent().is_empty())
+        };
+
+        match status {
+            TranscriptStatus::AwaitingConsent if !granted => self.decline(call.call_id, user_id).await,
+            TranscriptStatus::AwaitingConsent if ready => self.begin(call).await,
+            // Already live: a refusal simply leaves this participant uncaptioned
+            TranscriptStatus::Live if granted => {
+                self.sfu_service.add_caption_consent(call.call_id, user_id);
+                self.response(call.call_id)
+            }
+            _ => self.response(call.call_id),
+        }
+    }
+
+    /// Turns captions off and saves the transcript in the background once
+    /// the remaining audio has been recognized.
+    pub async fn stop_captions(&self, call_id: Uuid) -> Result<TranscriptResponse, AppError> {
+        let (_, captions) = self.active.remove(&call_id)
+            .ok_or_else(|| AppError::NotFound("Call does not have captions enabled".to_string()))?;
+
+        let worker = match captions.worker {
+            Some(worker) => worker,
+            None => {
+                // Still waiting for consent, so no audio was captioned
+                self.update_status(captions.transcript_id, TranscriptStatus::Failed).await?;
+                self.notify_participants(call_id, &SignalingMessage::CaptionsStopped {
+                    call_id,
+                    transcript_id: captions.transcript_id,
+                }).await;
+
+                return Ok(TranscriptResponse {
+                    transcript_id: captions.transcript_id,
+                    status: TranscriptStatus::Failed,
+                    started_at: captions.started_at,
+                    pending_consent: Vec::new(),
+                });
+            }
+        };
+
+        // Flushes the open chunks and drops the tap, which ends the worker
+        self.sfu_service.stop_captions(call_id).await;
+        self.update_status(captions.transcript_id, TranscriptStatus::Processing).await?;
+
+        self.notify_participants(call_id, &SignalingMessage::CaptionsStopped {
+            call_id,
+            transcript_id: captions.transcript_id,
+        }).await;
+
+        let response = TranscriptResponse {
+            transcript_id: captions.transcript_id,
+            status: TranscriptStatus::Processing,
+            started_at: captions.started_at,
+            pending_consent: Vec::new(),
+        };
+
+        let service = self.clone();
+        let transcript_id = captions.transcript_id;
+        tokio::spawn(async move {
+            if let Err(e) = worker.await {
+                tracing::error!("Caption worker for transcript {} failed: {}", transcript_id, e);
+            }
+
+            let dir = service.scratch_path.join(transcript_id.to_string());
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                tracing::warn!("Failed to clean up transcript directory {}: {}", dir.display(), e);
+            }
+
+            if let Err(e) = service.finalize(transcript_id).await {
+                tracing::error!("Failed to save transcript {}: {}", transcript_id, e);
+                let _ = service.fail(transcript_id, &e.to_string()).await;
+            }
+        });
+
+        Ok(response)
+    }
+
+    /// Stops captions when their call ends. Must run before the SFU room is
+    /// closed.
+    pub async fn handle_call_ended(&self, call_id: Uuid) {
+        if !self.active.contains_key(&call_id) {
+            return;
+        }
+
+        if let Err(e) = self.stop_captions(call_id).await {
+            tracing::error!("Failed to stop captions of ended call {}: {}", call_id, e);
+        }
+    }
+
+    /// Settles pending captions when someone leaves before answering: their
+    /// consent is no longer needed, or the request is cancelled if the
+    /// requester is the one who left.
+    pub async fn handle_participant_left(&self, call: &ActiveCall, user_id: Uuid) {
+        let (requested_by, ready) = {
+            let mut entry = match self.active.get_mut(&call.call_id) {
+                Some(entry) if entry.status == TranscriptStatus::AwaitingConsent => entry,
+                _ => return,
+            };
+            if entry.requested_by != user_id {
+                entry.required.remove(&user_id);
+                entry.consented.remove(&user_id);
+            }
+            (entry.requested_by, entry.pending_consent().is_empty())
+        };
+
+        let result = if requested_by == user_id {
+            self.stop_captions(call.call_id).await.map(|_| ())
+        } else if ready {
+            self.begin(call).await.map(|_| ())
+        } else {
+            Ok(())
+        };
+
+        if let Err(e) = result {
+            tracing::error!("Failed to settle captions of call {} after {} left: {}", call.call_id, user_id, e);
+        }
+    }
+
+    /// Transcribes a finished recording of a call that had no captions. The
+    /// audio tracks are moved out of the recording's directory so it can be
+    /// cleaned up while they are recognized.
+    pub async fn transcribe_recording(
+        &self,
+        call_id: Uuid,
+        requested_by: Uuid,
+        started_at: DateTime<Utc>,
+        tracks: &[RecordedTrackFile],
+    ) -> Result<(), AppError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let existing = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM call_transcripts WHERE call_id = $1")
+            .bind(call_id)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check for transcripts: {}", e)))?;
+        if existing > 0 {
+            return Ok(());
+        }
+
+        let audio: Vec<&RecordedTrackFile> = tracks.iter().filter(|track| !track.is_video).collect();
+        if audio.is_empty() {
+            return Ok(());
+        }
+
+        let transcript_id = Uuid::new_v4();
+        let dir = self.scratch_path.join(transcript_id.to_string());
+        tokio::fs::create_dir_all(&dir).await
+            .map_err(|e| AppError::Internal(format!("Failed to create transcript directory: {}", e)))?;
+
+        let mut moved = Vec::new();
+        for (index, track) in audio.into_iter().enumerate() {
+            let path = dir.join(format!("track_{}.ogg", index));
+            tokio::fs::rename(&track.path, &path).await
+                .map_err(|e| AppError::Internal(format!("Failed to move {}: {}", track.path.display(), e)))?;
+            moved.push(RecordedTrackFile { path, ..track.clone() });
+        }
+
+        self.create_transcript(transcript_id, call_id, requested_by, TranscriptSource::Recording, TranscriptStatus::Processing, started_at)
+            .await?;
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            let result = match service.recognize_tracks(transcript_id, &dir, &moved).await {
+                Ok(()) => service.finalize(transcript_id).await,
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                tracing::warn!("Failed to clean up transcript directory {}: {}", dir.display(), e);
+            }
+
+            if let Err(e) = result {
+                tracing::error!("Failed to transcribe recording of call {}: {}", call_id, e);
+                let _ = service.fail(transcript_id, &e.to_string()).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Returns the call's latest transcript with its segments.
+    pub async fn get_transcript(&self, call_id: Uuid) -> Result<CallTranscript, AppError> {
+        let row = sqlx::query_as::<_, TranscriptRow>(
+            r#"
+            SELECT transcript_id, call_id, session_id, started_by, source, status, language,
+                   started_at, ended_at, note_id
+            FROM call_transcripts
+            WHERE call_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(call_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch transcript: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("Call has no transcript".to_string()))?;
+
+        let segments = self.get_segments(row.transcript_id).await?;
+
+        Ok(CallTranscript {
+            transcript_id: row.transcript_id,
+            call_id: row.call_id,
+            session_id: row.session_id,
+            source: row.source,
+            status: row.status,
+            language: row.language,
+            started_at: row.started_at,
+            ended_at: row.ended_at,
+            note_id: row.note_id,
+            segments,
+        })
+    }
+
+    // Caption lifecycle
+
+    async fn begin(&self, call: &ActiveCall) -> Result<TranscriptResponse, AppError> {
+        let (transcript_id, requested_by, consented) = {
+            let mut entry = self.active.get_mut(&call.call_id)
+                .ok_or_else(|| AppError::NotFound("No captions requested for this call".to_string()))?;
+            // The last two answers can arrive together; only the first starts captions
+            if entry.status != TranscriptStatus::AwaitingConsent {
+                return Ok(entry.response());
+            }
+            entry.status = TranscriptStatus::Live;
+            (entry.transcript_id, entry.requested_by, entry.consented.clone())
+        };
+
+        let dir = self.scratch_path.join(transcript_id.to_string());
+        if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+            self.active.remove(&call.call_id);
+            let _ = self.fail(transcript_id, &e.to_string()).await;
+            return Err(AppError::Internal(format!("Failed to create transcript directory: {}", e)));
+        }
+
+        // Peer-to-peer media never reaches the server, so clients publish to a recorder peer
+        let publish_to_recorder = call.topology == CallTopology::Mesh;
+        if publish_to_recorder {
+            self.sfu_service.open_recorder_room(call.call_id);
+        }
+
+        let (sender, receiver) = mpsc::channel(CAPTION_QUEUE_CHUNKS);
+        let tap = CaptionTap::new(dir, Duration::from_secs(self.config.chunk_seconds.max(1)), sender, consented);
+        if let Err(e) = self.sfu_service.start_captions(call.call_id, Arc::new(tap)) {
+            self.active.remove(&call.call_id);
+            let _ = self.fail(transcript_id, &e.to_string()).await;
+            return Err(e);
+        }
+
+        let service = self.clone();
+        let call_id = call.call_id;
+        let worker = tokio::spawn(async move {
+            service.caption_chunks(call_id, transcript_id, receiver).await;
+        });
+
+        let started_at = Utc::now();
+        sqlx::query("UPDATE call_transcripts SET status = $1, started_at = $2 WHERE transcript_id = $3")
+            .bind(&TranscriptStatus::Live)
+            .bind(started_at)
+            .bind(transcript_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to start captions: {}", e)))?;
+
+        let response = match self.active.get_mut(&call.call_id) {
+            Some(mut entry) => {
+                entry.started_at = started_at;
+                entry.worker = Some(worker);
+                entry.response()
+            }
+            None => {
+                // Cancelled while starting; dropping the tap ends the worker
+                self.sfu_service.stop_captions(call.call_id).await;
+                let _ = self.update_status(transcript_id, TranscriptStatus::Failed).await;
+                return Err(AppError::NotFound("No captions requested for this call".to_string()));
+            }
+        };
+
+        self.notify_participants(call.call_id, &SignalingMessage::CaptionsStarted {
+            call_id: call.call_id,
+            transcript_id,
+            started_by: requested_by,
+            publish_to_recorder,
+        }).await;
+
+        tracing::info!("Captions {} of call {} started", transcript_id, call.call_id);
+        Ok(response)
+    }
+
+    async fn decline(&self, call_id: Uuid, user_id: Uuid) -> Result<TranscriptResponse, AppError> {
+        let (_, captions) = self.active.remove(&call_id)
+            .ok_or_else(|| AppError::NotFound("No captions requested for this call".to_string()))?;
+        self.update_status(captions.transcript_id, TranscriptStatus::Declined).await?;
+
+        self.notify_participants(call_id, &SignalingMessage::CaptionsDeclined {
+            call_id,
+            transcript_id: captions.transcript_id,
+            declined_by: user_id,
+        }).await;
+
+        tracing::info!("User {} declined captions {}", user_id, captions.transcript_id);
+
+        Ok(TranscriptResponse {
+            status: TranscriptStatus::Declined,
+            ..captions.response()
+        })
+    }
+
+    fn response(&self, call_id: Uuid) -> Result<TranscriptResponse, AppError> {
+        self.active.get(&call_id)
+            .map(|captions| captions.response())
+            .ok_or_else(|| AppError::NotFound("No captions requested for this call".to_string()))
+    }
+
+    // Recognition
+
+    async fn caption_chunks(&self, call_id: Uuid, transcript_id: Uuid, mut receiver: mpsc::Receiver<CaptionChunk>) {
+        while let Some(chunk) = receiver.recv().await {
+            let segments = match self.recognize_chunk(&chunk.path).await {
+                Ok(segments) => segments,
+                Err(e) => {
+                    tracing::warn!("Failed to caption audio of {} in call {}: {}", chunk.speaker_id, call_id, e);
+                    continue;
+                }
+            };
+
+            for segment in segments {
+                let start_ms = chunk.start_ms + segment.start_ms;
+                let end_ms = chunk.start_ms + segment.end_ms;
+
+                if let Err(e) = self.save_segment(transcript_id, Some(chunk.speaker_id), start_ms, end_ms, &segment.text).await {
+                    tracing::warn!("Failed to save caption for transcript {}: {}", transcript_id, e);
+                }
+
+                self.notify_participants(call_id, &SignalingMessage::Caption {
+                    call_id,
+                    transcript_id,
+                    speaker_id: chunk.speaker_id,
+                    text: segment.text,
+                    start_ms,
+                    end_ms,
+                }).await;
+            }
+        }
+    }
+
+    async fn recognize_tracks(&self, transcript_id: Uuid, dir: &Path, tracks: &[RecordedTrackFile]) -> Result<(), AppError> {
+        let chunk_ms = self.config.chunk_seconds.max(1) * 1000;
+
+        for (index, track) in tracks.iter().enumerate() {
+            let chunks = audio::split_to_wav(
+                &self.ffmpeg_path,
+                &track.path,
+                dir,
+                &format!("track_{}", index),
+                self.config.chunk_seconds.max(1),
+            ).await?;
+
+            for (position, chunk) in chunks.iter().enumerate() {
+                let offset_ms = track.offset_ms + position as u64 * chunk_ms;
+                for segment in self.recognizer.transcribe(chunk, &self.config.language).await? {
+                    self.save_segment(
+                        transcript_id,
+                        Some(track.publisher_id),
+                        offset_ms + segment.start_ms,
+                        offset_ms + segment.end_ms,
+                        &segment.text,
+                    ).await?;
+                }
+                let _ = tokio::fs::remove_file(chunk).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn recognize_chunk(&self, path: &Path) -> Result<Vec<RecognizedSegment>, AppError> {
+        let wav = path.with_extension("wav");
+        let result = match audio::to_wav(&self.ffmpeg_path, path, &wav).await {
+            Ok(()) => self.recognizer.transcribe(&wav, &self.config.language).await,
+            Err(e) => Err(e),
+        };
+
+        let _ = tokio::fs::remove_file(path).await;
+        let _ = tokio::fs::remove_file(&wav).await;
+        result
+    }
+
+    /// Marks the transcript completed and attaches it to the meeting's notes.
+    async fn finalize(&self, transcript_id: Uuid) -> Result<(), AppError> {
+        let row = sqlx::query_as::<_, TranscriptRow>(
+            r#"
+            SELECT transcript_id, call_id, session_id, started_by, source, status, language,
+                   started_at, ended_at, note_id
+            FROM call_transcripts
+            WHERE transcript_id = $1
+            "#
+        )
+        .bind(transcript_id)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch transcript: {}", e)))?;
+
+        let segments = self.get_segments(transcript_id).await?;
+        let content = format_transcript(&segments);
+
+        let mut note_id = None;
+        if let (Some(session_id), Some(author_id)) = (row.session_id, row.started_by) {
+            if !segments.is_empty() {
+                note_id = Some(self.attach_to_session_notes(session_id, author_id, row.started_at, &content).await?);
+            }
+        }
+
+        let query = r#"
+            UPDATE call_transcripts
+            SET status = $1, content = $2, note_id = $3, ended_at = $4
+            WHERE transcript_id = $5
+        "#;
+
+        sqlx::query(query)
+            .bind(&TranscriptStatus::Completed)
+            .bind(&content)
+            .bind(note_id)
+            .bind(Utc::now())
+            .bind(transcript_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to complete transcript: {}", e)))?;
+
+        tracing::info!(
+            "Transcript {} of call {} saved with {} segments",
+            transcript_id, row.call_id, segments.len()
+        );
+        Ok(())
+    }
+
+    async fn attach_to_session_notes(
+        &self,
+        session_id: Uuid,
+        author_id: Uuid,
+        started_at: DateTime<Utc>,
+        content: &str,
+    ) -> Result<Uuid, AppError> {
+        let note_id = Uuid::new_v4();
+        let query = r#"
+            INSERT INTO session_notes (note_id, session_id, author_id, title, content, note_type, is_shared, tags)
+            VALUES ($1, $2, $3, $4, $5, 'transcript', true, ARRAY['transcript'])
+        "#;
+
+        sqlx::query(query)
+            .bind(note_id)
+            .bind(session_id)
+            .bind(author_id)
+            .bind(format!("Call transcript {}", started_at.format("%Y-%m-%d %H:%M UTC")))
+            .bind(content)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to attach transcript to session notes: {}", e)))?;
+
+        Ok(note_id)
+    }
+
+    // Helpers
+
+    async fn create_transcript(
+        &self,
+        transcript_id: Uuid,
+        call_id: Uuid,
+        started_by: Uuid,
+        source: TranscriptSource,
+        status: TranscriptStatus,
+        started_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let query = r#"
+            INSERT INTO call_transcripts (
+                transcript_id, call_id, session_id, started_by, source, status, provider, language, started_at
+            )
+            SELECT $1, call_id, session_id, $2, $3, $4, $5, $6, $7
+            FROM call_sessions
+            WHERE call_id = $8
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(transcript_id)
+            .bind(started_by)
+            .bind(&source)
+            .bind(&status)
+            .bind(self.recognizer.provider())
+            .bind(&self.config.language)
+            .bind(started_at)
+            .bind(call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create transcript: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Call not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn save_segment(
+        &self,
+        transcript_id: Uuid,
+        speaker_id: Option<Uuid>,
+        start_ms: u64,
+        end_ms: u64,
+        text: &str,
+    ) -> Result<(), AppError> {
+        let query = r#"
+            INSERT INTO call_transcript_segments (segment_id, transcript_id, speaker_id, start_ms, end_ms, text)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        "#;
+
+        sqlx::query(query)
+            .bind(Uuid::new_v4())
+            .bind(transcript_id)
+            .bind(speaker_id)
+            .bind(start_ms as i64)
+            .bind(end_ms as i64)
+            .bind(text)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save transcript segment: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get_segments(&self, transcript_id: Uuid) -> Result<Vec<TranscriptSegment>, AppError> {
+        let rows = sqlx::query_as::<_, SegmentRow>(
+            r#"
+            SELECT s.speaker_id, u.username AS speaker_name, s.start_ms, s.end_ms, s.text
+            FROM call_transcript_segments s
+            LEFT JOIN users u ON u.user_id = s.speaker_id
+            WHERE s.transcript_id = $1
+            ORDER BY s.start_ms, s.created_at
+            "#
+        )
+        .bind(transcript_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch transcript segments: {}", e)))?;
+
+        Ok(rows.into_iter()
+            .map(|row| TranscriptSegment {
+                speaker_id: row.speaker_id,
+                speaker_name: row.speaker_name,
+                start_ms: row.start_ms,
+                end_ms: row.end_ms,
+                text: row.text,
+            })
+            .collect())
+    }
+
+    async fn update_status(&self, transcript_id: Uuid, status: TranscriptStatus) -> Result<(), AppError> {
+        sqlx::query("UPDATE call_transcripts SET status = $1 WHERE transcript_id = $2")
+            .bind(&status)
+            .bind(transcript_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update transcript status: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn fail(&self, transcript_id: Uuid, error: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE call_transcripts SET status = $1, error_message = $2, ended_at = $3 WHERE transcript_id = $4")
+            .bind(&TranscriptStatus::Failed)
+            .bind(error)
+            .bind(Utc::now())
+            .bind(transcript_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to mark transcript failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn notify_participants(&self, call_id: Uuid, message: &SignalingMessage) {
+        let message_json = match serde_json::to_string(message) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Failed to serialize caption message: {}", e);
+                return;
+            }
+        };
+
+        let ws_message = tokio_tungstenite::tungstenite::Message::Text(message_json);
+        for participant_id in self.call_manager.get_active_participants(call_id).await {
+            for connection in self.call_manager.get_user_connections(participant_id).await {
+                if let Err(e) = connection.sender.send(ws_message.clone()) {
+                    tracing::warn!("Failed to send caption message to user {}: {}", participant_id, e);
+                }
+            }
+        }
+    }
+
+    async fn send_to_user(&self, user_id: Uuid, message: &SignalingMessage) {
+        let message_json = match serde_json::to_string(message) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Failed to serialize caption message: {}", e);
+                return;
+            }
+        };
+
+        let ws_message = tokio_tungstenite::tungstenite::Message::Text(message_json);
+        for connection in self.call_manager.get_user_connections(user_id).await {
+            if let Err(e) = connection.sender.send(ws_message.clone()) {
+                tracing::warn!("Failed to send caption message to user {}: {}", user_id, e);
+            }
+        }
+    }
+}
+
+/// Renders segments as one `[mm:ss] Speaker: text` line each.
+fn format_transcript(segments: &[TranscriptSegment]) -> String {
+    segments.iter()
+        .map(|segment| {
+            let seconds = segment.start_ms.max(0) / 1000;
+            let timestamp = if seconds >= 3600 {
+                format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+            } else {
+                format!("{:02}:{:02}", seconds / 60, seconds % 60)
+            };
+            let speaker = segment.speaker_name.as_deref().unwrap_or("Unknown speaker");
+            format!("[{}] {}: {}", timestamp, speaker, segment.text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Database row structs
+
+#[derive(sqlx::FromRow)]
+struct TranscriptRow {
+    transcript_id: Uuid,
+    call_id: Uuid,
+    session_id: Option<Uuid>,
+    started_by: Option<Uuid>,
+    source: TranscriptSource,
+    status: TranscriptStatus,
+    language: String,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    note_id: Option<Uuid>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SegmentRow {
+    speaker_id: Option<Uuid>,
+    speaker_name: Option<String>,
+    start_ms: i64,
+    end_ms: i64,
+    text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(speaker: &str, start_ms: i64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            speaker_id: None,
+            speaker_name: Some(speaker.to_string()),
+            start_ms,
+            end_ms: start_ms + 1000,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_transcript() {
+        let segments = vec![
+            segment("mentor", 4_200, "Shall we start with your project?"),
+            segment("mentee", 65_000, "Sure."),
+            segment("mentor", 3_725_000, "Let's wrap up."),
+        ];
+
+        assert_eq!(
+            format_transcript(&segments),
+            "[00:04] mentor: Shall we start with your project?\n[01:05] mentee: Sure.\n[1:02:05] mentor: Let's wrap up."
+        );
+    }
+}
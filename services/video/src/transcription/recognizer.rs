@@ -0,0 +1,240 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use linkwithmentor_common::AppError;
+use crate::config::TranscriptionConfig;
+
+/// A stretch of recognized speech. Times are relative to the start of the
+/// audio that was transcribed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecognizedSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Turns speech into text. Audio is always a 16 kHz mono 16-bit WAV file of
+/// a few seconds, as produced by `audio::to_wav`.
+#[async_trait]
+pub trait SpeechRecognizer: Send + Sync {
+    async fn transcribe(&self, audio: &Path, language: &str) -> Result<Vec<RecognizedSegment>, AppError>;
+
+    fn provider(&self) -> &'static str;
+}
+
+pub fn create_recognizer(config: &TranscriptionConfig) -> Result<Arc<dyn SpeechRecognizer>, AppError> {
+    match config.provider.as_str() {
+        "whisper" => Ok(Arc::new(WhisperCppRecognizer::new(config))),
+        "http" => Ok(Arc::new(HttpRecognizer::new(config)?)),
+        other => Err(AppError::Internal(format!("Unknown speech recognition provider: {}", other))),
+    }
+}
+
+/// Runs a bundled whisper.cpp binary and model on the server itself.
+pub struct WhisperCppRecognizer {
+    binary: String,
+    model_path: String,
+    threads: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperOutput {
+    transcription: Vec<WhisperSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    offsets: WhisperOffsets,
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperOffsets {
+    from: u64,
+    to: u64,
+}
+
+impl WhisperCppRecognizer {
+    pub fn new(config: &TranscriptionConfig) -> Self {
+        Self {
+            binary: config.whisper_path.clone(),
+            model_path: config.model_path.clone(),
+            threads: config.threads.max(1),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechRecognizer for WhisperCppRecognizer {
+    async fn transcribe(&self, audio: &Path, language: &str) -> Result<Vec<RecognizedSegment>, AppError> {
+        // whisper.cpp appends ".json" to the output prefix
+        let prefix: PathBuf = audio.with_extension("");
+        let output_path = prefix.with_extension("json");
+
+        let result = tokio::process::Command::new(&self.binary)
+            .arg("-m").arg(&self.model_path)
+            .arg("-f").arg(audio)
+            .arg("-l").arg(language)
+            .arg("-t").arg(self.threads.to_string())
+            .arg("-oj")
+            .arg("-of").arg(&prefix)
+            .arg("-np")
+            .output()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to run whisper.cpp: {}", e)))?;
+
+        if !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Err(AppError::ExternalService(format!(
+                "whisper.cpp exited with {}: {}",
+                result.status,
+                stderr.lines().last().unwrap_or_default()
+            )));
+        }
+
+        let output = tokio::fs::read(&output_path).await
+            .map_err(|e| AppError::Internal(format!("Failed to read whisper.cpp output: {}", e)))?;
+        let _ = tokio::fs::remove_file(&output_path).await;
+
+        parse_whisper_output(&output)
+    }
+
+    fn provider(&self) -> &'static str {
+        "whisper"
+    }
+}
+
+/// Sends audio to an OpenAI-compatible transcription endpoint, such as a
+/// whisper.cpp or faster-whisper server on another host or a hosted API.
+pub struct HttpRecognizer {
+    client: reqwest::Client,
+    endpoint_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerboseTranscription {
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerboseSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+impl HttpRecognizer {
+    pub fn new(config: &TranscriptionConfig) -> Result<Self, AppError> {
+        let endpoint_url = config.endpoint_url.clone()
+            .ok_or_else(|| AppError::Internal("VIDEO_TRANSCRIPTION_ENDPOINT is required for the http provider".to_string()))?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint_url,
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl SpeechRecognizer for HttpRecognizer {
+    async fn transcribe(&self, audio: &Path, language: &str) -> Result<Vec<RecognizedSegment>, AppError> {
+        let bytes = tokio::fs::read(audio).await
+            .map_err(|e| AppError::Internal(format!("Failed to read {}: {}", audio.display(), e)))?;
+
+        let file = reqwest::multipart::Part::bytes(bytes)
+            .file_name("audio.wav")
+            .mime_str("audio/wav")
+            .map_err(|e| AppError::Internal(format!("Failed to build transcription request: {}", e)))?;
+        let form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", self.model.clone())
+            .text("language", language.to_string())
+            .text("response_format", "verbose_json");
+
+        let mut request = self.client.post(&self.endpoint_url).multipart(form);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await
+            .map_err(|e| AppError::ExternalService(format!("Transcription request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Transcription endpoint returned {}",
+                response.status()
+            )));
+        }
+
+        let transcription: VerboseTranscription = response.json().await
+            .map_err(|e| AppError::ExternalService(format!("Invalid transcription response: {}", e)))?;
+
+        Ok(transcription.segments.into_iter()
+            .filter_map(|segment| segment_from(
+                (segment.start * 1000.0) as u64,
+                (segment.end * 1000.0) as u64,
+                &segment.text,
+            ))
+            .collect())
+    }
+
+    fn provider(&self) -> &'static str {
+        "http"
+    }
+}
+
+fn parse_whisper_output(output: &[u8]) -> Result<Vec<RecognizedSegment>, AppError> {
+    let output: WhisperOutput = serde_json::from_slice(output)
+        .map_err(|e| AppError::Internal(format!("Invalid whisper.cpp output: {}", e)))?;
+
+    Ok(output.transcription.into_iter()
+        .filter_map(|segment| segment_from(segment.offsets.from, segment.offsets.to, &segment.text))
+        .collect())
+}
+
+// Whisper marks silence and noise with bracketed tags such as [BLANK_AUDIO]
+fn segment_from(start_ms: u64, end_ms: u64, text: &str) -> Option<RecognizedSegment> {
+    let text = text.trim();
+    let is_marker = (text.starts_with('[') && text.ends_with(']'))
+        || (text.starts_with('(') && text.ends_with(')'));
+    if text.is_empty() || is_marker {
+        return None;
+    }
+
+    Some(RecognizedSegment {
+        start_ms,
+        end_ms: end_ms.max(start_ms),
+        text: text.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_whisper_output_skips_markers() {
+        let output = br#"{
+            "result": {"language": "en"},
+            "transcription": [
+                {"timestamps": {"from": "00:00:00,000", "to": "00:00:01,500"}, "offsets": {"from": 0, "to": 1500}, "text": " Can you hear me?"},
+                {"timestamps": {"from": "00:00:01,500", "to": "00:00:03,000"}, "offsets": {"from": 1500, "to": 3000}, "text": " [BLANK_AUDIO]"},
+                {"timestamps": {"from": "00:00:03,000", "to": "00:00:04,200"}, "offsets": {"from": 3000, "to": 4200}, "text": " Yes, loud and clear."}
+            ]
+        }"#;
+
+        let segments = parse_whisper_output(output).unwrap();
+
+        assert_eq!(segments, vec![
+            RecognizedSegment { start_ms: 0, end_ms: 1500, text: "Can you hear me?".to_string() },
+            RecognizedSegment { start_ms: 3000, end_ms: 4200, text: "Yes, loud and clear.".to_string() },
+        ]);
+    }
+}
@@ -1,43 +1,151 @@
 use chrono::{DateTime, Utc, Duration};
-use sha2::{Sha1, Digest};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
 use base64::{Engine as _, engine::general_purpose};
+use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
 use linkwithmentor_common::AppError;
-use crate::{config::TurnConfig, models::TurnCredentials};
+use crate::{
+    config::TurnConfig,
+    models::{TurnCredentials, TurnRevocation, TurnSecretRotation},
+};
 
+type HmacSha1 = Hmac<Sha1>;
+
+/// Issues TURN REST API credentials (`expiry:user_id` usernames with an
+/// HMAC-SHA1 password) that coturn verifies with `use-auth-secret`.
+///
+/// Secrets live in the `turn_secret` table, which coturn reads through
+/// `psql-userdb` (see coturn/turnserver.conf) and accepts any row of, so
+/// rotating only changes which secret new credentials are signed with; older
+/// secrets are deleted once the credentials they signed expire.
 #[derive(Clone)]
 pub struct TurnClient {
     config: TurnConfig,
+    db_pool: PgPool,
+}
+
+struct TurnSecret {
+    secret_id: Uuid,
+    value: String,
 }
 
 impl TurnClient {
-    pub async fn new(config: &TurnConfig) -> Result<Self, AppError> {
+    pub async fn new(config: &TurnConfig, db_pool: PgPool) -> Result<Self, AppError> {
         // Validate TURN server configuration
         if config.server_url.is_empty() {
             return Err(AppError::Internal("TURN server URL not configured".to_string()));
         }
 
-        Ok(Self {
+        let client = Self {
             config: config.clone(),
-        })
+            db_pool,
+        };
+
+        // The configured secret seeds the table on first start; after that
+        // secrets are managed through rotation
+        if let Some(static_secret) = &config.static_auth_secret {
+            client.seed_secret(static_secret).await?;
+        }
+
+        Ok(client)
     }
 
-    /// Generate TURN credentials for a user
-    pub async fn generate_credentials(&self, user_id: Uuid) -> Result<TurnCredentials, AppError> {
-        let now = Utc::now();
-        let expiry = now + Duration::seconds(self.config.ttl_seconds as i64);
-        let timestamp = expiry.timestamp();
+    /// Generate TURN credentials for a user, reusing their outstanding
+    /// credentials for the call while those are signed with the current
+    /// secret and have at least half their lifetime left
+    pub async fn generate_credentials(
+        &self,
+        user_id: Uuid,
+        call_id: Option<Uuid>,
+    ) -> Result<TurnCredentials, AppError> {
+        let uris = self.generate_turn_uris();
 
-        let (username, password) = if let Some(static_secret) = &self.config.static_auth_secret {
-            // Use static auth secret (recommended for production)
-            self.generate_static_auth_credentials(user_id, timestamp, static_secret)?
-        } else {
-            // Use configured username/password (for development)
-            (self.config.username.clone(), self.config.password.clone())
+        let Some(secret) = self.current_secret().await? else {
+            // No secret seeded: fall back to the static username/password, for TURN servers with static users
+            return Ok(TurnCredentials {
+                username: self.config.username.clone(),
+                password: self.config.password.clone(),
+                ttl: self.config.ttl_seconds,
+                uris,
+            });
         };
 
-        let uris = self.generate_turn_uris();
+        let now = Utc::now();
+        let ttl = Duration::seconds(self.config.ttl_seconds as i64);
+
+        let reusable = sqlx::query(
+            r#"
+            SELECT turn_username, expires_at
+            FROM turn_allocations
+            WHERE user_id = $1 AND call_id IS NOT DISTINCT FROM $2 AND secret_id = $3
+              AND released_at IS NULL AND revoked_at IS NULL AND expires_at > $4
+            ORDER BY expires_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(call_id)
+        .bind(secret.secret_id)
+        .bind(now + ttl / 2)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch TURN allocation: {}", e)))?;
+
+        if let Some(row) = reusable {
+            let username: String = row.get("turn_username");
+            let expires_at: DateTime<Utc> = row.get("expires_at");
+
+            return Ok(TurnCredentials {
+                password: rest_api_password(&secret.value, &username)?,
+                username,
+                ttl: (expires_at - now).num_seconds().max(0) as u32,
+                uris,
+            });
+        }
+
+        let expires_at = now + ttl;
+        let (username, password) = rest_api_credentials(&secret.value, user_id, expires_at)?;
+
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // Concurrent requests from one user would otherwise all see the same
+        // count and overshoot the quota
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("turn_allocations:{}", user_id))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to lock TURN allocations: {}", e)))?;
+
+        // Recorded only while the user is under their quota of outstanding credentials
+        let result = sqlx::query(
+            r#"
+            INSERT INTO turn_allocations (user_id, call_id, turn_username, expires_at, secret_id)
+            SELECT $1, $2, $3, $4, $5
+            WHERE (
+                SELECT COUNT(*) FROM turn_allocations
+                WHERE user_id = $1 AND released_at IS NULL AND revoked_at IS NULL AND expires_at > NOW()
+            ) < $6
+            "#,
+        )
+        .bind(user_id)
+        .bind(call_id)
+        .bind(&username)
+        .bind(expires_at)
+        .bind(secret.secret_id)
+        .bind(self.config.max_allocations_per_user as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to record TURN allocation: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict("TURN allocation quota exceeded".to_string()));
+        }
+
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
 
         Ok(TurnCredentials {
             username,
@@ -47,56 +155,196 @@ impl TurnClient {
         })
     }
 
-    /// Generate credentials using static auth secret (RFC 5389)
-    fn generate_static_auth_credentials(
+    /// Release the allocations handed out for a call once it ends
+    pub async fn release_call_allocations(&self, call_id: Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE turn_allocations SET released_at = NOW() WHERE call_id = $1 AND released_at IS NULL")
+            .bind(call_id)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to release TURN allocations: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Start signing credentials with a new secret. Previous secrets stay
+    /// valid for one credential TTL so outstanding credentials keep working,
+    /// or are retired immediately when `retire_previous` is set.
+    pub async fn rotate_secret(
         &self,
-        user_id: Uuid,
-        timestamp: i64,
-        static_secret: &str,
-    ) -> Result<(String, String), AppError> {
-        // Create username with timestamp
-        let username = format!("{}:{}", timestamp, user_id);
-
-        // Generate password using HMAC-SHA1
-        let mut hasher = Sha1::new();
-        hasher.update(username.as_bytes());
-        let key = static_secret.as_bytes();
-        
-        // Simple HMAC implementation
-        let mut ipad = [0x36u8; 64];
-        let mut opad = [0x5cu8; 64];
-        
-        for (i, &byte) in key.iter().enumerate().take(64) {
-            ipad[i] ^= byte;
-            opad[i] ^= byte;
+        admin_id: Uuid,
+        retire_previous: bool,
+    ) -> Result<TurnSecretRotation, AppError> {
+        let now = Utc::now();
+        let retire_at = if retire_previous {
+            now
+        } else {
+            now + Duration::seconds(self.config.ttl_seconds as i64)
+        };
+
+        let mut tx = self.db_pool.begin().await
+            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let retired: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE turn_secret SET retire_at = $2
+            WHERE realm = $1 AND (retire_at IS NULL OR retire_at > $2)
+            RETURNING secret_id
+            "#,
+        )
+        .bind(&self.config.realm)
+        .bind(retire_at)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to retire TURN secrets: {}", e)))?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO turn_secret (realm, value, created_by)
+            VALUES ($1, $2, $3)
+            RETURNING secret_id, created_at
+            "#,
+        )
+        .bind(&self.config.realm)
+        .bind(generate_secret())
+        .bind(admin_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create TURN secret: {}", e)))?;
+
+        // Credentials signed by a retired secret stop working at the relay
+        if retire_previous {
+            sqlx::query(
+                r#"
+                UPDATE turn_allocations SET released_at = NOW()
+                WHERE secret_id = ANY($1) AND released_at IS NULL
+                "#,
+            )
+            .bind(&retired)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to release TURN allocations: {}", e)))?;
         }
 
-        let mut inner_hasher = Sha1::new();
-        inner_hasher.update(&ipad);
-        inner_hasher.update(username.as_bytes());
-        let inner_hash = inner_hasher.finalize();
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
 
-        let mut outer_hasher = Sha1::new();
-        outer_hasher.update(&opad);
-        outer_hasher.update(&inner_hash);
-        let final_hash = outer_hasher.finalize();
+        self.prune_retired_secrets().await?;
 
-        let password = general_purpose::STANDARD.encode(&final_hash);
+        tracing::info!("TURN secret rotated by {}, {} previous secrets retire at {}", admin_id, retired.len(), retire_at);
 
-        Ok((username, password))
+        Ok(TurnSecretRotation {
+            secret_id: row.get("secret_id"),
+            created_at: row.get("created_at"),
+            retired_secrets: retired.len(),
+            retire_at,
+        })
+    }
+
+    /// Revoke a user's outstanding credentials so they are neither reused nor
+    /// count towards the user's quota. The relay honours them until their
+    /// secret is retired, which `rotate_secrets` does straight away.
+    pub async fn revoke_user_credentials(
+        &self,
+        user_id: Uuid,
+        admin_id: Uuid,
+        rotate_secrets: bool,
+    ) -> Result<TurnRevocation, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE turn_allocations SET revoked_at = NOW(), revoked_by = $2
+            WHERE user_id = $1 AND released_at IS NULL AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(admin_id)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to revoke TURN credentials: {}", e)))?;
+
+        let rotation = if rotate_secrets {
+            Some(self.rotate_secret(admin_id, true).await?)
+        } else {
+            None
+        };
+
+        tracing::info!("Revoked {} TURN credentials of user {}", result.rows_affected(), user_id);
+
+        Ok(TurnRevocation {
+            user_id,
+            revoked_credentials: result.rows_affected(),
+            rotation,
+        })
+    }
+
+    /// Periodically deletes secrets past their retirement so coturn stops
+    /// accepting credentials signed with them.
+    pub async fn start_secret_pruner(&self) {
+        let client = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = client.prune_retired_secrets().await {
+                    tracing::warn!("Failed to prune retired TURN secrets: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn prune_retired_secrets(&self) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM turn_secret WHERE realm = $1 AND retire_at <= NOW()")
+            .bind(&self.config.realm)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to prune TURN secrets: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Newest secret that isn't being retired; new credentials are signed with it
+    async fn current_secret(&self) -> Result<Option<TurnSecret>, AppError> {
+        let row = sqlx::query(
+            r#"
+            SELECT secret_id, value
+            FROM turn_secret
+            WHERE realm = $1 AND retire_at IS NULL
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(&self.config.realm)
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch TURN secret: {}", e)))?;
+
+        Ok(row.map(|row| TurnSecret {
+            secret_id: row.get("secret_id"),
+            value: row.get("value"),
+        }))
+    }
+
+    async fn seed_secret(&self, static_secret: &str) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO turn_secret (realm, value)
+            SELECT $1, $2
+            WHERE NOT EXISTS (SELECT 1 FROM turn_secret WHERE realm = $1)
+            "#,
+        )
+        .bind(&self.config.realm)
+        .bind(static_secret)
+        .execute(&self.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to seed TURN secret: {}", e)))?;
+
+        Ok(())
     }
 
     /// Generate list of TURN server URIs
     fn generate_turn_uris(&self) -> Vec<String> {
-        let base_url = &self.config.server_url;
-        
-        // Support multiple transport protocols
-        vec![
-            format!("{}?transport=udp", base_url),
-            format!("{}?transport=tcp", base_url),
-            // Add TURNS (TLS) if available
-            base_url.replace("turn:", "turns:"),
-        ]
+        turn_uris(&self.config.server_url)
     }
 
     /// Validate TURN server connectivity
@@ -154,7 +402,7 @@ impl TurnClient {
         
         if credentials.ttl <= refresh_threshold {
             tracing::info!("Refreshing TURN credentials for user: {}", user_id);
-            let new_credentials = self.generate_credentials(user_id).await?;
+            let new_credentials = self.generate_credentials(user_id, None).await?;
             Ok(Some(new_credentials))
         } else {
             Ok(None)
@@ -163,29 +411,67 @@ impl TurnClient {
 
     /// Get recommended ICE servers configuration for WebRTC
     pub async fn get_ice_servers(&self, user_id: Uuid) -> Result<Vec<IceServer>, AppError> {
-        let turn_credentials = self.generate_credentials(user_id).await?;
-        
-        let mut ice_servers = vec![
-            // Public STUN servers (for development/fallback)
-            IceServer {
-                urls: vec![
-                    "stun:stun.l.google.com:19302".to_string(),
-                    "stun:stun1.l.google.com:19302".to_string(),
-                ],
-                username: None,
-                credential: None,
-            },
-        ];
-
-        // Add TURN server with credentials
-        ice_servers.push(IceServer {
+        let turn_credentials = self.generate_credentials(user_id, None).await?;
+
+        Ok(ice_servers(turn_credentials))
+    }
+}
+
+fn turn_uris(base_url: &str) -> Vec<String> {
+    // Support multiple transport protocols
+    vec![
+        format!("{}?transport=udp", base_url),
+        format!("{}?transport=tcp", base_url),
+        // Add TURNS (TLS) if available
+        base_url.replace("turn:", "turns:"),
+    ]
+}
+
+fn ice_servers(turn_credentials: TurnCredentials) -> Vec<IceServer> {
+    vec![
+        // Public STUN servers (for development/fallback)
+        IceServer {
+            urls: vec![
+                "stun:stun.l.google.com:19302".to_string(),
+                "stun:stun1.l.google.com:19302".to_string(),
+            ],
+            username: None,
+            credential: None,
+        },
+        // TURN server with credentials
+        IceServer {
             urls: turn_credentials.uris,
             username: Some(turn_credentials.username),
             credential: Some(turn_credentials.password),
-        });
+        },
+    ]
+}
 
-        Ok(ice_servers)
-    }
+/// Credentials in the TURN REST API format: the username is the expiry as a
+/// Unix timestamp followed by the user ID, the password the base64 encoded
+/// HMAC-SHA1 of the username keyed with the shared secret
+pub fn rest_api_credentials(
+    secret: &str,
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<(String, String), AppError> {
+    let username = format!("{}:{}", expires_at.timestamp(), user_id);
+    let password = rest_api_password(secret, &username)?;
+
+    Ok((username, password))
+}
+
+fn rest_api_password(secret: &str, username: &str) -> Result<String, AppError> {
+    let mut mac = HmacSha1::new_from_slice(secret.as_bytes())
+        .map_err(|_| AppError::Internal("Invalid TURN secret".to_string()))?;
+
+    mac.update(username.as_bytes());
+
+    Ok(general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
 }
 
 #[derive(Debug, Clone)]
@@ -208,44 +494,55 @@ pub struct IceServer {
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn test_generate_credentials() {
-        let config = TurnConfig {
-            server_url: "turn:localhost:3478".to_string(),
-            username: "test".to_string(),
-            password: "test".to_string(),
-            realm: "test.com".to_string(),
-            static_auth_secret: Some("secret".to_string()),
-            ttl_seconds: 86400,
-        };
+    #[test]
+    fn test_rest_api_credentials() {
+        let user_id = Uuid::parse_str("6f1b2c3d-0000-4000-8000-000000000001").unwrap();
+        let expires_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
 
-        let turn_client = TurnClient::new(&config).await.unwrap();
-        let user_id = Uuid::new_v4();
-        
-        let credentials = turn_client.generate_credentials(user_id).await.unwrap();
-        
-        assert!(!credentials.username.is_empty());
-        assert!(!credentials.password.is_empty());
-        assert_eq!(credentials.ttl, 86400);
-        assert!(!credentials.uris.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_ice_servers() {
-        let config = TurnConfig {
-            server_url: "turn:localhost:3478".to_string(),
-            username: "test".to_string(),
-            password: "test".to_string(),
-            realm: "test.com".to_string(),
-            static_auth_secret: None,
-            ttl_seconds: 86400,
+        let (username, password) = rest_api_credentials("north", user_id, expires_at).unwrap();
+
+        // Matches what coturn derives for the same secret and username
+        assert_eq!(username, "1700000000:6f1b2c3d-0000-4000-8000-000000000001");
+        assert_eq!(password, "+R2Y+/zjHabkpU4xCxevig6q1AQ=");
+    }
+
+    #[test]
+    fn test_ice_servers() {
+        let (username, password) = rest_api_credentials("north", Uuid::new_v4(), Utc::now() + Duration::hours(1)).unwrap();
+        let credentials = TurnCredentials {
+            username: username.clone(),
+            password: password.clone(),
+            ttl: 3600,
+            uris: turn_uris("turn:localhost:3478"),
         };
 
-        let turn_client = TurnClient::new(&config).await.unwrap();
+        let ice_servers = ice_servers(credentials);
+
+        assert_eq!(ice_servers.len(), 2); // STUN and TURN servers
+        assert!(ice_servers[0].urls.iter().all(|url| url.starts_with("stun:")));
+        assert!(ice_servers[0].username.is_none() && ice_servers[0].credential.is_none());
+        assert_eq!(
+            ice_servers[1].urls,
+            vec![
+                "turn:localhost:3478?transport=udp",
+                "turn:localhost:3478?transport=tcp",
+                "turns:localhost:3478",
+            ]
+        );
+        assert_eq!(ice_servers[1].username.as_deref(), Some(username.as_str()));
+        assert_eq!(ice_servers[1].credential.as_deref(), Some(password.as_str()));
+    }
+
+    #[test]
+    fn test_rotated_secret_changes_password() {
         let user_id = Uuid::new_v4();
-        
-        let ice_servers = turn_client.get_ice_servers(user_id).await.unwrap();
-        
-        assert!(ice_servers.len() >= 2); // At least STUN and TURN servers
+        let expires_at = Utc::now() + Duration::hours(1);
+
+        let (username, old_password) = rest_api_credentials(&generate_secret(), user_id, expires_at).unwrap();
+        let (rotated_username, new_password) = rest_api_credentials(&generate_secret(), user_id, expires_at).unwrap();
+
+        assert_eq!(username, rotated_username);
+        assert_ne!(old_password, new_password);
+        assert_eq!(generate_secret().len(), 64);
     }
-}
\ No newline at end of file
+}
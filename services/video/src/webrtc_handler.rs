@@ -77,6 +77,11 @@ async fn handle_webrtc_connection(
         return;
     }
 
+    // Resume any call the user dropped out of
+    if let Err(e) = state.signaling_service.handle_connection_restored(user_id).await {
+        tracing::warn!("Failed to resume calls for user {}: {}", username, e);
+    }
+
     // Spawn task to handle outgoing messages
     let call_manager_clone = state.call_manager.clone();
     let user_id_clone = user_id;
@@ -178,6 +183,11 @@ async fn handle_webrtc_connection(
         .remove_connection(user_id, &connection_id)
         .await;
 
+    // Hold the user's calls open in case they are only changing networks
+    if let Err(e) = state.signaling_service.handle_connection_lost(user_id).await {
+        tracing::warn!("Failed to mark user {} as reconnecting: {}", username, e);
+    }
+
     tracing::info!("WebRTC connection closed for user: {}", username);
 }
 
@@ -192,11 +202,40 @@ async fn handle_signaling_message(
 
     match message {
         SignalingMessage::CallOffer { callee_id, session_id, sdp, call_type, .. } => {
-            let call_id = state.signaling_service
-                .handle_call_offer(user_id, callee_id, session_id, call_type, sdp)
+            let outcome = state.signaling_service
+                .handle_call_offer(user_id, callee_id, session_id, call_type, sdp, Vec::new())
+                .await?;
+
+            if let Some(sdp) = outcome.sdp_answer {
+                state.signaling_service
+                    .send_to_user(user_id, SignalingMessage::SfuAnswer { call_id: outcome.call_id, sdp })
+                    .await?;
+            }
+
+            tracing::info!("Call offer created: {} from {} to {}", outcome.call_id, user_id, callee_id);
+        }
+
+        SignalingMessage::SfuOffer { call_id, sdp } => {
+            let answer = state.signaling_service
+                .handle_sfu_offer(call_id, user_id, username, sdp)
                 .await?;
 
-            tracing::info!("Call offer created: {} from {} to {}", call_id, user_id, callee_id);
+            // Without an answer the user is in the waiting room and offers again once admitted
+            if let Some(answer) = answer {
+                state.signaling_service
+                    .send_to_user(user_id, SignalingMessage::SfuAnswer { call_id, sdp: answer })
+                    .await?;
+
+                tracing::debug!("SFU offer answered for user {} in call {}", user_id, call_id);
+            }
+        }
+
+        SignalingMessage::SfuAnswer { call_id, sdp } => {
+            state.signaling_service
+                .handle_sfu_answer(call_id, user_id, sdp)
+                .await?;
+
+            tracing::debug!("SFU renegotiation completed for user {} in call {}", user_id, call_id);
         }
 
         SignalingMessage::CallAnswer { call_id, sdp } => {
@@ -231,6 +270,22 @@ async fn handle_signaling_message(
             tracing::debug!("ICE candidate received for call: {}", call_id);
         }
 
+        SignalingMessage::IceRestartOffer { call_id, participant_id, sdp } => {
+            state.signaling_service
+                .handle_ice_restart_offer(call_id, user_id, participant_id, sdp)
+                .await?;
+
+            tracing::debug!("ICE restart offer relayed for user {} in call {}", user_id, call_id);
+        }
+
+        SignalingMessage::IceRestartAnswer { call_id, participant_id, sdp } => {
+            state.signaling_service
+                .handle_ice_restart_answer(call_id, user_id, participant_id, sdp)
+                .await?;
+
+            tracing::debug!("ICE restart answer relayed for user {} in call {}", user_id, call_id);
+        }
+
         SignalingMessage::MediaStateChanged { call_id, audio_enabled, video_enabled, screen_sharing, .. } => {
             state.signaling_service
                 .handle_media_state_change(call_id, user_id, audio_enabled, video_enabled, screen_sharing)
@@ -286,9 +341,35 @@ async fn handle_signaling_message(
             tracing::info!("Screen sharing ended for user {} in call {}", user_id, call_id);
         }
 
+        message @ (SignalingMessage::AdmitParticipant { .. }
+        | SignalingMessage::DenyParticipant { .. }
+        | SignalingMessage::MuteParticipant { .. }
+        | SignalingMessage::StopParticipantVideo { .. }
+        | SignalingMessage::AllowParticipantVideo { .. }
+        | SignalingMessage::StopParticipantScreenShare { .. }
+        | SignalingMessage::RemoveParticipant { .. }
+        | SignalingMessage::LockCall { .. }
+        | SignalingMessage::TransferHost { .. }) => {
+            state.signaling_service
+                .handle_host_action(user_id, message)
+                .await?;
+
+            tracing::debug!("Host control applied by user {}", user_id);
+        }
+
+        message @ (SignalingMessage::RaiseHand { .. }
+        | SignalingMessage::LowerHand { .. }
+        | SignalingMessage::Reaction { .. }) => {
+            state.signaling_service
+                .handle_engagement(user_id, message)
+                .await?;
+
+            tracing::debug!("Engagement signal from user {}", user_id);
+        }
+
         SignalingMessage::QualityReport { call_id, metrics, .. } => {
-            state.call_manager
-                .record_quality_metrics(call_id, user_id, metrics)
+            state.signaling_service
+                .handle_quality_report(call_id, user_id, metrics)
                 .await?;
 
             tracing::debug!("Quality metrics recorded for user {} in call {}", user_id, call_id);
@@ -57,12 +57,17 @@ impl RedisService {
         let mut conn = self.manager.clone();
         let key = format!("user_presence:{}", user_id);
         
+        let now = chrono::Utc::now().timestamp();
+        
         conn.hset_multiple(&key, &[
             ("status", status),
             ("current_role", role),
-            ("last_seen", &chrono::Utc::now().timestamp().to_string())
+            ("last_seen", &now.to_string())
         ]).await.map_err(|e| AppError::Redis(e))?;
         
+        // Last seen outlives the presence hash so it can be shown after users go offline
+        conn.set(RedisKeys::last_seen(user_id), now).await.map_err(|e| AppError::Redis(e))?;
+        
         conn.expire(&key, 300).await.map_err(|e| AppError::Redis(e))
     }
 
@@ -85,6 +90,171 @@ impl RedisService {
         }))
     }
 
+    // Cluster-wide device presence: every connected device heartbeats its own
+    // entry, and a user is online while any device entry is unexpired.
+    /// Records a heartbeat for one device. Returns true if the user was
+    /// offline before this heartbeat.
+    pub async fn presence_heartbeat(&self, user_id: &str, device_id: &str, ttl_seconds: u64) -> Result<bool, AppError> {
+        let mut conn = self.manager.clone();
+        let devices_key = RedisKeys::presence_devices(user_id);
+        let now = chrono::Utc::now().timestamp();
+        let expires_at = now + ttl_seconds as i64;
+
+        let previous_expiry: Option<i64> = conn.zscore(RedisKeys::presence_online(), user_id)
+            .await
+            .map_err(|e| AppError::Redis(e))?;
+        let was_online = previous_expiry.map_or(false, |expiry| expiry > now);
+
+        redis::pipe()
+            .atomic()
+            .zrembyscore(&devices_key, "-inf", now).ignore()
+            .zadd(&devices_key, device_id, expires_at).ignore()
+            .expire(&devices_key, (ttl_seconds * 2) as usize).ignore()
+            .zadd(RedisKeys::presence_online(), user_id, expires_at).ignore()
+            .set(RedisKeys::last_seen(user_id), now).ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .map_err(|e| AppError::Redis(e))?;
+
+        Ok(!was_online)
+    }
+
+    /// Removes a device on clean disconnect. Returns true if the user has no
+    /// live devices left and is now offline.
+    pub async fn remove_presence_device(&self, user_id: &str, device_id: &str) -> Result<bool, AppError> {
+        let mut conn = self.manager.clone();
+        let devices_key = RedisKeys::presence_devices(user_id);
+        let now = chrono::Utc::now().timestamp();
+
+        let (live_devices,): (usize,) = redis::pipe()
+            .atomic()
+            .zrem(&devices_key, device_id).ignore()
+            .zrembyscore(&devices_key, "-inf", now).ignore()
+            .zcard(&devices_key)
+            .set(RedisKeys::last_seen(user_id), now).ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AppError::Redis(e))?;
+
+        if live_devices > 0 {
+            return Ok(false);
+        }
+
+        let removed: usize = conn.zrem(RedisKeys::presence_online(), user_id)
+            .await
+            .map_err(|e| AppError::Redis(e))?;
+
+        Ok(removed > 0)
+    }
+
+    pub async fn get_presence_devices(&self, user_id: &str) -> Result<Vec<String>, AppError> {
+        let mut conn = self.manager.clone();
+        let now = chrono::Utc::now().timestamp();
+
+        conn.zrangebyscore(RedisKeys::presence_devices(user_id), now, "+inf")
+            .await
+            .map_err(|e| AppError::Redis(e))
+    }
+
+    pub async fn is_user_online(&self, user_id: &str) -> Result<bool, AppError> {
+        let mut conn = self.manager.clone();
+        let expires_at: Option<i64> = conn.zscore(RedisKeys::presence_online(), user_id)
+            .await
+            .map_err(|e| AppError::Redis(e))?;
+
+        Ok(expires_at.map_or(false, |expires_at| expires_at > chrono::Utc::now().timestamp()))
+    }
+
+    pub async fn get_online_user_ids(&self) -> Result<Vec<String>, AppError> {
+        let mut conn = self.manager.clone();
+        let now = chrono::Utc::now().timestamp();
+
+        conn.zrangebyscore(RedisKeys::presence_online(), now, "+inf")
+            .await
+            .map_err(|e| AppError::Redis(e))
+    }
+
+    /// Drops users whose devices all stopped heartbeating (e.g. a crashed
+    /// instance) and returns them. Each user is returned by exactly one caller.
+    pub async fn expire_stale_presence(&self) -> Result<Vec<String>, AppError> {
+        let mut conn = self.manager.clone();
+        let now = chrono::Utc::now().timestamp();
+
+        let stale: Vec<String> = conn.zrangebyscore(RedisKeys::presence_online(), "-inf", now)
+            .await
+            .map_err(|e| AppError::Redis(e))?;
+
+        let mut expired = Vec::new();
+        for user_id in stale {
+            let removed: usize = conn.zrem(RedisKeys::presence_online(), &user_id)
+                .await
+                .map_err(|e| AppError::Redis(e))?;
+            if removed > 0 {
+                expired.push(user_id);
+            }
+        }
+
+        Ok(expired)
+    }
+
+    pub async fn get_last_seen(&self, user_id: &str) -> Result<Option<i64>, AppError> {
+        let mut conn = self.manager.clone();
+        conn.get(RedisKeys::last_seen(user_id))
+            .await
+            .map_err(|e| AppError::Redis(e))
+    }
+
+    /// Sets a user-chosen status (busy, in session, away...) shared by all of
+    /// their devices. Without `expires_in_seconds` it stays until cleared.
+    pub async fn set_custom_status(
+        &self,
+        user_id: &str,
+        status: &str,
+        text: Option<&str>,
+        expires_in_seconds: Option<u64>,
+    ) -> Result<(), AppError> {
+        let mut conn = self.manager.clone();
+        let key = RedisKeys::user_status(user_id);
+
+        let _: () = conn.del(&key).await.map_err(|e| AppError::Redis(e))?;
+        conn.hset_multiple(&key, &[
+            ("status", status),
+            ("text", text.unwrap_or("")),
+        ]).await.map_err(|e| AppError::Redis(e))?;
+
+        if let Some(expires_in_seconds) = expires_in_seconds {
+            conn.expire(&key, expires_in_seconds as usize).await.map_err(|e| AppError::Redis(e))?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_custom_status(&self, user_id: &str) -> Result<Option<CustomStatus>, AppError> {
+        let mut conn = self.manager.clone();
+        let key = RedisKeys::user_status(user_id);
+
+        let (status, text, ttl): (Option<String>, Option<String>, i64) = redis::pipe()
+            .hget(&key, "status")
+            .hget(&key, "text")
+            .ttl(&key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AppError::Redis(e))?;
+
+        Ok(status.map(|status| CustomStatus {
+            status,
+            text: text.filter(|text| !text.is_empty()),
+            expires_at: (ttl > 0).then(|| chrono::Utc::now().timestamp() + ttl),
+        }))
+    }
+
+    pub async fn clear_custom_status(&self, user_id: &str) -> Result<(), AppError> {
+        let mut conn = self.manager.clone();
+        conn.del(RedisKeys::user_status(user_id))
+            .await
+            .map_err(|e| AppError::Redis(e))
+    }
+
     // Rate limiting
     pub async fn check_rate_limit(&self, key: &str, limit: u32, window_seconds: u64) -> Result<bool, AppError> {
         let mut conn = self.manager.clone();
@@ -200,6 +370,13 @@ pub struct UserPresence {
     pub last_seen: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomStatus {
+    pub status: String,
+    pub text: Option<String>,
+    pub expires_at: Option<i64>,
+}
+
 // Redis key builders
 pub struct RedisKeys;
 
@@ -216,6 +393,22 @@ impl RedisKeys {
         format!("user_presence:{}", user_id)
     }
 
+    pub fn presence_devices(user_id: &str) -> String {
+        format!("presence_devices:{}", user_id)
+    }
+
+    pub fn presence_online() -> String {
+        "presence_online".to_string()
+    }
+
+    pub fn last_seen(user_id: &str) -> String {
+        format!("last_seen:{}", user_id)
+    }
+
+    pub fn user_status(user_id: &str) -> String {
+        format!("user_status:{}", user_id)
+    }
+
     pub fn rate_limit(user_id: &str, endpoint: &str) -> String {
         format!("rate_limit:{}:{}", user_id, endpoint)
     }
@@ -93,6 +93,10 @@ pub enum MessageType {
     Image,
     File,
     System,
+    Poll,
+    SessionProposal,
+    Checklist,
+    Encrypted, // Content is an opaque E2E ciphertext envelope
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
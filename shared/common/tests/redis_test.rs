@@ -101,6 +101,32 @@ async fn test_redis_connection_and_operations() {
     assert!(!users_after_removal.contains(&user1.to_string()));
     assert!(users_after_removal.contains(&user2.to_string()));
 
+    // Test multi-device presence
+    let presence_user = "test_presence_user";
+    let came_online = redis.presence_heartbeat(presence_user, "device_a", 60).await.expect("Failed to heartbeat device a");
+    assert!(came_online);
+    
+    let second_device = redis.presence_heartbeat(presence_user, "device_b", 60).await.expect("Failed to heartbeat device b");
+    assert!(!second_device, "Second device should not re-announce the user as online");
+    assert!(redis.is_user_online(presence_user).await.expect("Failed to check online state"));
+    
+    let went_offline = redis.remove_presence_device(presence_user, "device_a").await.expect("Failed to remove device a");
+    assert!(!went_offline, "User still has device b connected");
+    
+    let went_offline = redis.remove_presence_device(presence_user, "device_b").await.expect("Failed to remove device b");
+    assert!(went_offline);
+    assert!(!redis.is_user_online(presence_user).await.expect("Failed to check offline state"));
+    assert!(redis.get_last_seen(presence_user).await.expect("Failed to get last seen").is_some());
+    
+    redis.set_custom_status(presence_user, "busy", Some("In a mock interview"), Some(600)).await.expect("Failed to set custom status");
+    let custom_status = redis.get_custom_status(presence_user).await.expect("Failed to get custom status").unwrap();
+    assert_eq!(custom_status.status, "busy");
+    assert_eq!(custom_status.text.as_deref(), Some("In a mock interview"));
+    assert!(custom_status.expires_at.is_some());
+    
+    redis.clear_custom_status(presence_user).await.expect("Failed to clear custom status");
+    assert!(redis.get_custom_status(presence_user).await.expect("Failed to check cleared status").is_none());
+
     // Test whiteboard state
     let whiteboard_state = r#"{"shapes": [{"type": "rectangle", "x": 10, "y": 20}]}"#;
     
@@ -0,0 +1,8 @@
+-- Rollback Message Edit History and Soft-Delete Audit Trail Migration
+
+DROP INDEX IF EXISTS idx_messages_deleted;
+DROP TABLE IF EXISTS message_revisions;
+
+ALTER TABLE messages DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE messages DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE messages DROP COLUMN IF EXISTS edit_count;
@@ -0,0 +1,25 @@
+-- Message Edit History and Soft-Delete Audit Trail Migration
+
+-- Track edit counts and tombstone metadata on messages
+ALTER TABLE messages ADD COLUMN edit_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE messages ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE messages ADD COLUMN deleted_by UUID REFERENCES users(user_id) ON DELETE SET NULL;
+
+-- Every prior version of a message, captured before it is edited or deleted
+CREATE TABLE message_revisions (
+    revision_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message_id UUID NOT NULL REFERENCES messages(message_id) ON DELETE CASCADE,
+    revision_number INTEGER NOT NULL,
+    revision_type VARCHAR(20) NOT NULL, -- edit, delete
+    content TEXT NOT NULL,
+    moderation_status VARCHAR(20) NOT NULL,
+    revised_by UUID NOT NULL REFERENCES users(user_id),
+    revised_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    UNIQUE (message_id, revision_number)
+);
+
+-- Indexes for message revisions
+CREATE INDEX idx_message_revisions_message ON message_revisions(message_id, revision_number);
+CREATE INDEX idx_message_revisions_revised_by ON message_revisions(revised_by, revised_at DESC);
+CREATE INDEX idx_messages_deleted ON messages(deleted_at) WHERE is_deleted = TRUE;
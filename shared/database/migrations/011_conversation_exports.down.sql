@@ -0,0 +1,3 @@
+-- Rollback Conversation Export Migration
+
+DROP TABLE IF EXISTS conversation_exports;
@@ -0,0 +1,29 @@
+-- Conversation Export Migration
+
+-- Background jobs that render a conversation transcript for download
+CREATE TABLE conversation_exports (
+    export_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    requested_by UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    recipient_id UUID REFERENCES users(user_id) ON DELETE CASCADE,
+    session_id UUID REFERENCES mentorship_sessions(session_id) ON DELETE CASCADE,
+    group_id UUID REFERENCES group_chats(group_id) ON DELETE CASCADE,
+    format VARCHAR(20) NOT NULL, -- json, html, plain_text
+    range_start TIMESTAMP WITH TIME ZONE NOT NULL,
+    range_end TIMESTAMP WITH TIME ZONE NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, processing, completed, failed, expired
+    message_count BIGINT,
+    file_path TEXT,
+    download_token UUID UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    completed_at TIMESTAMP WITH TIME ZONE,
+
+    CONSTRAINT chk_conversation_exports_single_scope CHECK (
+        (recipient_id IS NOT NULL)::int + (session_id IS NOT NULL)::int + (group_id IS NOT NULL)::int = 1
+    )
+);
+
+-- Indexes for conversation exports
+CREATE INDEX idx_conversation_exports_requested_by ON conversation_exports(requested_by, created_at DESC);
+CREATE INDEX idx_conversation_exports_expiry ON conversation_exports(expires_at) WHERE status = 'completed';
@@ -0,0 +1,3 @@
+-- Rollback Scheduled and Recurring Chat Messages Migration
+
+DROP TABLE IF EXISTS scheduled_messages;
@@ -0,0 +1,30 @@
+-- Scheduled and Recurring Chat Messages Migration
+
+CREATE TABLE scheduled_messages (
+    scheduled_message_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    sender_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    recipient_id UUID REFERENCES users(user_id) ON DELETE CASCADE,
+    session_id UUID REFERENCES mentorship_sessions(session_id) ON DELETE CASCADE,
+    group_id UUID REFERENCES group_chats(group_id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    message_type VARCHAR(20) DEFAULT 'text',
+    first_send_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    next_send_at TIMESTAMP WITH TIME ZONE,
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC', -- IANA timezone used to expand recurrences
+    recurrence JSONB, -- frequency, interval, days_of_week, end_date, max_occurrences
+    status VARCHAR(20) NOT NULL DEFAULT 'scheduled', -- scheduled, sent, cancelled, failed
+    occurrence_count INTEGER NOT NULL DEFAULT 0,
+    attempt_count INTEGER NOT NULL DEFAULT 0,
+    last_sent_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    CONSTRAINT chk_scheduled_messages_single_target CHECK (
+        (recipient_id IS NOT NULL)::int + (session_id IS NOT NULL)::int + (group_id IS NOT NULL)::int = 1
+    )
+);
+
+-- Indexes for scheduled messages
+CREATE INDEX idx_scheduled_messages_due ON scheduled_messages(next_send_at) WHERE status = 'scheduled';
+CREATE INDEX idx_scheduled_messages_sender ON scheduled_messages(sender_id, next_send_at);
@@ -0,0 +1,3 @@
+-- Rollback Chat Slash Command Bots Migration
+
+DROP TABLE IF EXISTS chat_bots;
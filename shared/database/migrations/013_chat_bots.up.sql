@@ -0,0 +1,17 @@
+-- Chat Slash Command Bots Migration
+
+CREATE TABLE chat_bots (
+    bot_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    command VARCHAR(32) NOT NULL, -- invoked as /command
+    description VARCHAR(200) NOT NULL,
+    usage VARCHAR(200) NOT NULL,
+    webhook_url TEXT NOT NULL,
+    signing_secret VARCHAR(128) NOT NULL, -- HMAC-SHA256 key for X-LinkWithMentor-Signature
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID NOT NULL REFERENCES users(user_id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Indexes for chat bots
+-- Deactivated bots keep their row, so only active commands need to be unique
+CREATE UNIQUE INDEX idx_chat_bots_active ON chat_bots(command) WHERE is_active = TRUE;
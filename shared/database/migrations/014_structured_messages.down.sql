@@ -0,0 +1,8 @@
+-- Rollback Structured Interactive Messages Migration
+
+DROP TABLE IF EXISTS checklist_items;
+DROP TABLE IF EXISTS message_checklists;
+DROP TABLE IF EXISTS session_proposals;
+DROP TABLE IF EXISTS message_poll_votes;
+DROP TABLE IF EXISTS message_poll_options;
+DROP TABLE IF EXISTS message_polls;
@@ -0,0 +1,59 @@
+-- Structured Interactive Messages Migration (polls, session proposals, checklists)
+
+CREATE TABLE message_polls (
+    message_id UUID PRIMARY KEY REFERENCES messages(message_id) ON DELETE CASCADE,
+    question VARCHAR(300) NOT NULL,
+    allow_multiple BOOLEAN NOT NULL DEFAULT FALSE,
+    closes_at TIMESTAMP WITH TIME ZONE,
+    is_closed BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE message_poll_options (
+    option_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message_id UUID NOT NULL REFERENCES message_polls(message_id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    label VARCHAR(100) NOT NULL,
+    UNIQUE(message_id, position)
+);
+
+CREATE TABLE message_poll_votes (
+    message_id UUID NOT NULL REFERENCES message_polls(message_id) ON DELETE CASCADE,
+    option_id UUID NOT NULL REFERENCES message_poll_options(option_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    voted_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (message_id, option_id, user_id)
+);
+
+CREATE TABLE session_proposals (
+    message_id UUID PRIMARY KEY REFERENCES messages(message_id) ON DELETE CASCADE,
+    proposed_by UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    topic VARCHAR(200) NOT NULL,
+    start_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    duration_minutes INTEGER NOT NULL CHECK (duration_minutes BETWEEN 15 AND 480),
+    notes TEXT,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, accepted, declined, withdrawn, expired
+    responded_by UUID REFERENCES users(user_id),
+    responded_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE TABLE message_checklists (
+    message_id UUID PRIMARY KEY REFERENCES messages(message_id) ON DELETE CASCADE,
+    title VARCHAR(200) NOT NULL
+);
+
+CREATE TABLE checklist_items (
+    item_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message_id UUID NOT NULL REFERENCES message_checklists(message_id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    text VARCHAR(500) NOT NULL,
+    assignee_id UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    is_completed BOOLEAN NOT NULL DEFAULT FALSE,
+    completed_by UUID REFERENCES users(user_id),
+    completed_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE(message_id, position)
+);
+
+-- Indexes for structured messages
+CREATE INDEX idx_message_poll_votes_user ON message_poll_votes(message_id, user_id);
+CREATE INDEX idx_session_proposals_pending ON session_proposals(start_time) WHERE status = 'pending';
+CREATE INDEX idx_checklist_items_assignee ON checklist_items(assignee_id) WHERE is_completed = FALSE;
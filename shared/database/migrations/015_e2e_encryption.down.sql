@@ -0,0 +1,7 @@
+-- Rollback End-to-End Encrypted Direct Messages Migration
+
+DROP TABLE IF EXISTS e2e_conversations;
+DROP TABLE IF EXISTS e2e_one_time_prekeys;
+DROP TABLE IF EXISTS e2e_identity_keys;
+
+ALTER TABLE messages DROP COLUMN IF EXISTS is_encrypted;
@@ -0,0 +1,35 @@
+-- End-to-End Encrypted Direct Messages Migration
+
+ALTER TABLE messages ADD COLUMN is_encrypted BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Public halves only; private keys never leave the clients
+CREATE TABLE e2e_identity_keys (
+    user_id UUID PRIMARY KEY REFERENCES users(user_id) ON DELETE CASCADE,
+    identity_key TEXT NOT NULL,
+    signed_prekey_id INTEGER NOT NULL,
+    signed_prekey TEXT NOT NULL,
+    signed_prekey_signature TEXT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE e2e_one_time_prekeys (
+    user_id UUID NOT NULL REFERENCES e2e_identity_keys(user_id) ON DELETE CASCADE,
+    key_id INTEGER NOT NULL,
+    public_key TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (user_id, key_id)
+);
+
+-- One row per opted-in user pair, stored with user_low < user_high
+CREATE TABLE e2e_conversations (
+    user_low UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    user_high UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    enabled_by UUID NOT NULL REFERENCES users(user_id),
+    enabled_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (user_low, user_high),
+    CONSTRAINT chk_e2e_conversations_ordered CHECK (user_low < user_high)
+);
+
+-- Indexes for E2E encryption
+CREATE INDEX idx_e2e_one_time_prekeys_claim ON e2e_one_time_prekeys(user_id, created_at, key_id);
+CREATE INDEX idx_messages_encrypted ON messages(recipient_id) WHERE is_encrypted = TRUE;
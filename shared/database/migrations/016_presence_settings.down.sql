@@ -0,0 +1,3 @@
+-- Rollback Presence Privacy Settings Migration
+
+DROP TABLE IF EXISTS presence_settings;
@@ -0,0 +1,9 @@
+-- Presence Privacy Settings Migration
+
+-- Users without a row use the defaults (visible to everyone, last seen shown)
+CREATE TABLE presence_settings (
+    user_id UUID PRIMARY KEY REFERENCES users(user_id) ON DELETE CASCADE,
+    visibility VARCHAR(20) NOT NULL DEFAULT 'everyone', -- everyone, connections, nobody
+    show_last_seen BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- Rollback Call Topology Migration
+
+ALTER TABLE call_sessions DROP COLUMN IF EXISTS topology;
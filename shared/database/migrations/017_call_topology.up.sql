@@ -0,0 +1,4 @@
+-- Call Topology Migration
+
+-- Whether a call's media flows peer-to-peer (mesh) or through the SFU
+ALTER TABLE call_sessions ADD COLUMN topology VARCHAR(10) NOT NULL DEFAULT 'mesh'; -- mesh, sfu
@@ -0,0 +1,13 @@
+-- Rollback Call Recording Consent Migration
+
+DROP TABLE IF EXISTS call_recording_consents;
+
+DROP INDEX IF EXISTS idx_call_recordings_status;
+
+ALTER TABLE call_recordings
+    DROP COLUMN IF EXISTS error_message,
+    DROP COLUMN IF EXISTS lecture_id,
+    DROP COLUMN IF EXISTS storage_provider,
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS title,
+    DROP COLUMN IF EXISTS requested_by;
@@ -0,0 +1,24 @@
+-- Call Recording Consent Migration
+
+-- Server-side recordings: who asked for them, where they are stored and the
+-- private lecture they were published as
+ALTER TABLE call_recordings
+    ADD COLUMN requested_by UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    ADD COLUMN title VARCHAR(255),
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'completed', -- awaiting_consent, recording, processing, completed, declined, failed
+    ADD COLUMN storage_provider VARCHAR(20), -- local, s3
+    ADD COLUMN lecture_id UUID REFERENCES video_lectures(lecture_id) ON DELETE SET NULL,
+    ADD COLUMN error_message TEXT;
+
+CREATE INDEX idx_call_recordings_status ON call_recordings(status, created_at);
+
+-- Every participant must consent before a call is recorded
+CREATE TABLE call_recording_consents (
+    recording_id UUID NOT NULL REFERENCES call_recordings(recording_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    granted BOOLEAN NOT NULL,
+    responded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (recording_id, user_id)
+);
+
+CREATE INDEX idx_call_recording_consents_user ON call_recording_consents(user_id, responded_at DESC);
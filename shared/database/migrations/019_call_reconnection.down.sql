@@ -0,0 +1,3 @@
+-- Rollback Call Reconnection Migration
+
+ALTER TABLE call_sessions DROP COLUMN IF EXISTS billable_seconds;
@@ -0,0 +1,5 @@
+-- Call Reconnection Migration
+
+-- Call time excluding periods spent waiting for a dropped participant to
+-- reconnect; NULL for calls that ended before this was tracked
+ALTER TABLE call_sessions ADD COLUMN billable_seconds INTEGER;
@@ -0,0 +1,7 @@
+-- Rollback Call Breakout Rooms Migration
+
+DROP TABLE IF EXISTS call_breakout_assignments;
+
+DROP INDEX IF EXISTS idx_call_sessions_parent;
+
+ALTER TABLE call_sessions DROP COLUMN IF EXISTS parent_call_id;
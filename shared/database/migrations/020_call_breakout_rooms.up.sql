@@ -0,0 +1,20 @@
+-- Call Breakout Rooms Migration
+
+-- Breakout rooms are calls of their own, linked to the main call
+ALTER TABLE call_sessions ADD COLUMN parent_call_id UUID REFERENCES call_sessions(call_id) ON DELETE CASCADE;
+
+CREATE INDEX idx_call_sessions_parent ON call_sessions(parent_call_id) WHERE parent_call_id IS NOT NULL;
+
+-- Who was sent to which breakout room and for how long
+CREATE TABLE call_breakout_assignments (
+    assignment_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    main_call_id UUID NOT NULL REFERENCES call_sessions(call_id) ON DELETE CASCADE,
+    breakout_call_id UUID NOT NULL REFERENCES call_sessions(call_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    room_name VARCHAR(100) NOT NULL,
+    assigned_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    left_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_call_breakout_assignments_main ON call_breakout_assignments(main_call_id, assigned_at);
+CREATE INDEX idx_call_breakout_assignments_user ON call_breakout_assignments(user_id, assigned_at DESC);
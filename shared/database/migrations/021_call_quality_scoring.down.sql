@@ -0,0 +1,5 @@
+-- Rollback Call Quality Scoring Migration
+
+DROP TABLE IF EXISTS call_quality_flags;
+
+ALTER TABLE call_quality_metrics DROP COLUMN IF EXISTS mos;
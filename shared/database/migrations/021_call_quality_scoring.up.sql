@@ -0,0 +1,19 @@
+-- Call Quality Scoring Migration
+
+-- Estimated MOS (1.0 - 4.5) over the reporter's sliding window
+ALTER TABLE call_quality_metrics ADD COLUMN mos REAL;
+
+-- Calls with poor quality, for refund review
+CREATE TABLE call_quality_flags (
+    call_id UUID PRIMARY KEY REFERENCES call_sessions(call_id) ON DELETE CASCADE,
+    session_id UUID,
+    average_mos REAL NOT NULL,
+    poor_ratio REAL NOT NULL,
+    reason TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, refunded, dismissed
+    flagged_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    resolved_by UUID REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_call_quality_flags_status ON call_quality_flags(status, flagged_at DESC);
@@ -0,0 +1,10 @@
+-- Rollback TURN REST Credentials Migration
+
+DROP INDEX IF EXISTS idx_turn_allocations_outstanding;
+
+ALTER TABLE turn_allocations
+    DROP COLUMN IF EXISTS revoked_by,
+    DROP COLUMN IF EXISTS revoked_at,
+    DROP COLUMN IF EXISTS secret_id;
+
+DROP TABLE IF EXISTS turn_secret;
@@ -0,0 +1,26 @@
+-- TURN REST Credentials Migration
+
+-- Shared secrets for coturn's use-auth-secret mode, in the layout coturn's
+-- Postgres user database expects. coturn accepts credentials signed by any
+-- row for its realm, so old secrets are kept until retire_at to let
+-- credentials they signed run out.
+CREATE TABLE turn_secret (
+    realm VARCHAR(127) NOT NULL DEFAULT '',
+    value VARCHAR(256) NOT NULL,
+    secret_id UUID NOT NULL UNIQUE DEFAULT uuid_generate_v4(),
+    created_by UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    retire_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (realm, value)
+);
+
+CREATE INDEX idx_turn_secret_realm ON turn_secret(realm, created_at DESC);
+
+-- Which secret signed each credential, and whether an admin revoked it
+ALTER TABLE turn_allocations
+    ADD COLUMN secret_id UUID REFERENCES turn_secret(secret_id) ON DELETE SET NULL,
+    ADD COLUMN revoked_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN revoked_by UUID REFERENCES users(user_id) ON DELETE SET NULL;
+
+CREATE INDEX idx_turn_allocations_outstanding ON turn_allocations(user_id, expires_at)
+    WHERE released_at IS NULL AND revoked_at IS NULL;
@@ -0,0 +1,3 @@
+-- Rollback Missed Calls Migration
+
+ALTER TABLE call_sessions DROP COLUMN IF EXISTS callback_call_id;
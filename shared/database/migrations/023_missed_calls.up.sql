@@ -0,0 +1,8 @@
+-- Missed Calls Migration
+
+-- call_sessions.state also takes 'missed' for calls nobody answered before
+-- the ring timeout; duration_seconds is 0 for calls that were never answered
+
+-- The call placed to return a missed call
+ALTER TABLE call_sessions
+    ADD COLUMN callback_call_id UUID REFERENCES call_sessions(call_id) ON DELETE SET NULL;
@@ -0,0 +1,4 @@
+-- Rollback Call Transcripts Migration
+
+DROP TABLE IF EXISTS call_transcript_segments;
+DROP TABLE IF EXISTS call_transcripts;
@@ -0,0 +1,39 @@
+-- Call Transcripts Migration
+
+-- Transcripts of calls, built from live captions or from a finished recording
+CREATE TABLE call_transcripts (
+    transcript_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    call_id UUID NOT NULL REFERENCES call_sessions(call_id) ON DELETE CASCADE,
+    session_id UUID REFERENCES mentorship_sessions(session_id) ON DELETE SET NULL,
+    started_by UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    source VARCHAR(20) NOT NULL, -- captions, recording
+    status VARCHAR(20) NOT NULL DEFAULT 'live', -- live, processing, completed, failed
+    provider VARCHAR(20) NOT NULL, -- whisper, http
+    language VARCHAR(10) NOT NULL,
+    content TEXT,
+    -- Session note the finished transcript was attached to
+    note_id UUID REFERENCES session_notes(note_id) ON DELETE SET NULL,
+    error_message TEXT,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    ended_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_call_transcripts_call ON call_transcripts(call_id, created_at DESC);
+CREATE INDEX idx_call_transcripts_session ON call_transcripts(session_id) WHERE session_id IS NOT NULL;
+
+-- Recognized speech, timed in milliseconds from the transcript's start
+CREATE TABLE call_transcript_segments (
+    segment_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transcript_id UUID NOT NULL REFERENCES call_transcripts(transcript_id) ON DELETE CASCADE,
+    speaker_id UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    start_ms BIGINT NOT NULL,
+    end_ms BIGINT NOT NULL,
+    text TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_call_transcript_segments_transcript ON call_transcript_segments(transcript_id, start_ms);
+
+-- Finished transcripts are attached to the meeting as session notes with
+-- note_type 'transcript' and the 'transcript' tag
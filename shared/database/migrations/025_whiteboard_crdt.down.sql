@@ -0,0 +1,9 @@
+-- Rollback Whiteboard CRDT Migration
+
+DROP INDEX IF EXISTS idx_whiteboard_operations_stamp;
+
+ALTER TABLE whiteboard_operations
+    DROP COLUMN IF EXISTS lamport,
+    DROP COLUMN IF EXISTS replica_id;
+
+ALTER TABLE whiteboards DROP COLUMN IF EXISTS crdt_state;
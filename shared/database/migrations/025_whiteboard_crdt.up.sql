@@ -0,0 +1,14 @@
+-- Whiteboard CRDT Migration
+
+-- Replicated document the elements column is materialized from
+ALTER TABLE whiteboards ADD COLUMN crdt_state JSONB;
+
+-- Operations are now deltas stamped with the Lamport clock of the replica that made them
+ALTER TABLE whiteboard_operations
+    ADD COLUMN replica_id UUID,
+    ADD COLUMN lamport BIGINT;
+
+-- Lets every instance log the same delta without duplicates
+CREATE UNIQUE INDEX idx_whiteboard_operations_stamp
+    ON whiteboard_operations(whiteboard_id, replica_id, lamport)
+    WHERE replica_id IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Rollback Session Note Attachments Migration
+
+DROP TABLE IF EXISTS session_note_attachments;
@@ -0,0 +1,10 @@
+-- Session Note Attachments Migration
+
+CREATE TABLE session_note_attachments (
+    note_id UUID NOT NULL REFERENCES session_notes(note_id) ON DELETE CASCADE,
+    material_id UUID NOT NULL REFERENCES session_materials(material_id) ON DELETE CASCADE,
+    attached_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (note_id, material_id)
+);
+
+CREATE INDEX idx_session_note_attachments_material ON session_note_attachments(material_id);
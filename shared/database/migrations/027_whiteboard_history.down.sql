@@ -0,0 +1,5 @@
+-- Rollback Whiteboard History Migration
+
+DROP INDEX IF EXISTS idx_whiteboard_operations_version;
+
+DROP TABLE IF EXISTS whiteboard_snapshots;
@@ -0,0 +1,20 @@
+-- Whiteboard History Migration
+
+-- Full copies of a whiteboard at a version: named by users, or left by compaction
+CREATE TABLE whiteboard_snapshots (
+    snapshot_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    whiteboard_id UUID NOT NULL REFERENCES whiteboards(whiteboard_id) ON DELETE CASCADE,
+    name VARCHAR(255),
+    snapshot_type VARCHAR(20) NOT NULL DEFAULT 'named', -- named, compaction
+    version BIGINT NOT NULL,
+    crdt_state JSONB NOT NULL,
+    state_vector JSONB NOT NULL DEFAULT '{}',
+    element_count INTEGER NOT NULL DEFAULT 0,
+    created_by UUID NOT NULL REFERENCES users(user_id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_whiteboard_snapshots_board ON whiteboard_snapshots(whiteboard_id, version DESC);
+
+-- History and replay read operations by version
+CREATE INDEX idx_whiteboard_operations_version ON whiteboard_operations(whiteboard_id, version_after);
@@ -0,0 +1,5 @@
+-- Rollback Calendar Sync Migration
+
+DROP TABLE IF EXISTS calendar_event_links;
+DROP TABLE IF EXISTS external_busy_times;
+DROP TABLE IF EXISTS calendar_connections;
@@ -0,0 +1,45 @@
+-- Calendar Sync Migration
+
+-- External calendars a user has connected (Google, Outlook, iCloud, other CalDAV)
+CREATE TABLE calendar_connections (
+    connection_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    provider VARCHAR(20) NOT NULL, -- google, outlook, apple, caldav
+    calendar_id TEXT NOT NULL, -- provider calendar id, or the collection URL for CalDAV
+    display_name VARCHAR(255),
+    credentials TEXT NOT NULL, -- encrypted tokens or CalDAV password
+    sync_token TEXT,
+    last_synced_at TIMESTAMP WITH TIME ZONE,
+    last_full_sync_at TIMESTAMP WITH TIME ZONE,
+    next_sync_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (user_id, provider, calendar_id)
+);
+
+CREATE INDEX idx_calendar_connections_next_sync ON calendar_connections(next_sync_at);
+
+-- Busy periods mirrored from external calendars, recurring events already expanded
+CREATE TABLE external_busy_times (
+    connection_id UUID NOT NULL REFERENCES calendar_connections(connection_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    external_event_id TEXT NOT NULL,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (connection_id, external_event_id, starts_at)
+);
+
+CREATE INDEX idx_external_busy_times_user ON external_busy_times(user_id, starts_at, ends_at);
+
+-- Events pushed to external calendars for sessions
+CREATE TABLE calendar_event_links (
+    session_id UUID NOT NULL REFERENCES mentorship_sessions(session_id) ON DELETE CASCADE,
+    connection_id UUID NOT NULL REFERENCES calendar_connections(connection_id) ON DELETE CASCADE,
+    external_event_id TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (session_id, connection_id)
+);
+
+CREATE INDEX idx_calendar_event_links_external ON calendar_event_links(connection_id, external_event_id);
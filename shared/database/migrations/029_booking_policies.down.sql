@@ -0,0 +1,3 @@
+-- Rollback Booking Policies Migration
+
+DROP TABLE IF EXISTS booking_policies;
@@ -0,0 +1,17 @@
+-- Booking Policies Migration
+
+-- A mentor's rules for when sessions can be booked with them
+CREATE TABLE booking_policies (
+    user_id UUID PRIMARY KEY REFERENCES users(user_id) ON DELETE CASCADE,
+    timezone VARCHAR(50) NOT NULL DEFAULT 'UTC', -- days and weeks for the caps are counted here
+    min_notice_minutes INTEGER NOT NULL DEFAULT 0 CHECK (min_notice_minutes >= 0),
+    max_horizon_days INTEGER NOT NULL DEFAULT 30 CHECK (max_horizon_days > 0),
+    buffer_before_minutes INTEGER NOT NULL DEFAULT 0 CHECK (buffer_before_minutes >= 0),
+    buffer_after_minutes INTEGER NOT NULL DEFAULT 0 CHECK (buffer_after_minutes >= 0),
+    max_sessions_per_day INTEGER CHECK (max_sessions_per_day >= 0),
+    max_sessions_per_week INTEGER CHECK (max_sessions_per_week >= 0),
+    allowed_durations INTEGER[] NOT NULL DEFAULT '{}', -- empty allows any duration
+    session_type_rules JSONB NOT NULL DEFAULT '{}', -- per-session-type overrides
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
@@ -0,0 +1,19 @@
+-- Rollback Recurrence Rules Migration
+
+DROP TABLE IF EXISTS imported_busy_times;
+DROP TABLE IF EXISTS calendar_imports;
+
+DROP INDEX IF EXISTS idx_sessions_series_occurrence;
+ALTER TABLE mentorship_sessions
+    DROP COLUMN IF EXISTS is_recurrence_exception,
+    DROP COLUMN IF EXISTS recurrence_id;
+
+DROP INDEX IF EXISTS idx_recurring_series_materialized;
+ALTER TABLE recurring_series
+    DROP COLUMN IF EXISTS split_from_series_id,
+    DROP COLUMN IF EXISTS materialized_until,
+    DROP COLUMN IF EXISTS series_start,
+    DROP COLUMN IF EXISTS timezone,
+    DROP COLUMN IF EXISTS exdates,
+    DROP COLUMN IF EXISTS rdates,
+    DROP COLUMN IF EXISTS rrule;
@@ -0,0 +1,45 @@
+-- Recurrence Rules Migration
+
+-- Full RFC 5545 recurrence for session series
+ALTER TABLE recurring_series
+    ADD COLUMN rrule TEXT, -- e.g. FREQ=MONTHLY;BYDAY=2TU
+    ADD COLUMN rdates TIMESTAMP WITH TIME ZONE[] NOT NULL DEFAULT '{}',
+    ADD COLUMN exdates TIMESTAMP WITH TIME ZONE[] NOT NULL DEFAULT '{}',
+    ADD COLUMN timezone VARCHAR(50) NOT NULL DEFAULT 'UTC', -- occurrences keep their wall-clock time here
+    ADD COLUMN series_start TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN materialized_until TIMESTAMP WITH TIME ZONE, -- sessions exist for occurrences before this
+    ADD COLUMN split_from_series_id UUID REFERENCES recurring_series(series_id) ON DELETE SET NULL;
+
+CREATE INDEX idx_recurring_series_materialized ON recurring_series(materialized_until) WHERE rrule IS NOT NULL;
+
+-- Occurrences remember where the rule placed them, so edited ones can be told apart
+ALTER TABLE mentorship_sessions
+    ADD COLUMN IF NOT EXISTS recurring_series_id UUID REFERENCES recurring_series(series_id) ON DELETE SET NULL,
+    ADD COLUMN recurrence_id TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN is_recurrence_exception BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE UNIQUE INDEX idx_sessions_series_occurrence ON mentorship_sessions(recurring_series_id, recurrence_id)
+    WHERE recurrence_id IS NOT NULL;
+
+-- .ics files imported as busy time, e.g. out-of-office blocks from other tools
+CREATE TABLE calendar_imports (
+    import_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    event_count INTEGER NOT NULL DEFAULT 0,
+    busy_period_count INTEGER NOT NULL DEFAULT 0,
+    busy_until TIMESTAMP WITH TIME ZONE NOT NULL, -- recurring events are expanded up to here
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_calendar_imports_user ON calendar_imports(user_id, created_at);
+
+CREATE TABLE imported_busy_times (
+    import_id UUID NOT NULL REFERENCES calendar_imports(import_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_imported_busy_times_user ON imported_busy_times(user_id, starts_at, ends_at);
+CREATE INDEX idx_imported_busy_times_import ON imported_busy_times(import_id);
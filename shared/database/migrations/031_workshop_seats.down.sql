@@ -0,0 +1,4 @@
+-- Rollback Workshop Seats Migration
+
+DROP TABLE IF EXISTS workshop_seats;
+DROP TABLE IF EXISTS workshop_settings;
@@ -0,0 +1,32 @@
+-- Workshop Seats Migration
+
+-- Capacity and pricing of group sessions sold by the seat
+CREATE TABLE workshop_settings (
+    session_id UUID PRIMARY KEY REFERENCES mentorship_sessions(session_id) ON DELETE CASCADE,
+    capacity INTEGER NOT NULL CHECK (capacity > 0),
+    seat_price DECIMAL(10,2) NOT NULL DEFAULT 0.00 CHECK (seat_price >= 0), -- 0 for free workshops
+    currency VARCHAR(3) NOT NULL DEFAULT 'USD',
+    waitlist_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE workshop_seats (
+    seat_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    session_id UUID NOT NULL REFERENCES workshop_settings(session_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL, -- held, confirmed, waitlisted, cancelled, expired
+    amount DECIMAL(10,2) NOT NULL DEFAULT 0.00,
+    hold_expires_at TIMESTAMP WITH TIME ZONE, -- unpaid held seats are released after this
+    payment_id UUID,
+    refund_id UUID,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(), -- also the waitlist order
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- One live booking per attendee; cancelled and expired ones are kept for history
+CREATE UNIQUE INDEX idx_workshop_seats_active_user ON workshop_seats(session_id, user_id)
+    WHERE status IN ('held', 'confirmed', 'waitlisted');
+CREATE INDEX idx_workshop_seats_session ON workshop_seats(session_id, status, created_at);
+CREATE INDEX idx_workshop_seats_hold_expiry ON workshop_seats(hold_expires_at) WHERE status = 'held';
+CREATE INDEX idx_workshop_seats_user ON workshop_seats(user_id, created_at);
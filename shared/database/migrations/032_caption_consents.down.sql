@@ -0,0 +1,3 @@
+-- Rollback Caption Consent Migration
+
+DROP TABLE IF EXISTS call_transcript_consents;
@@ -0,0 +1,13 @@
+-- Caption Consent Migration
+
+-- call_transcripts.status also takes 'awaiting_consent' and 'declined':
+-- like recordings, live captions start only once every participant consents
+CREATE TABLE call_transcript_consents (
+    transcript_id UUID NOT NULL REFERENCES call_transcripts(transcript_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    granted BOOLEAN NOT NULL,
+    responded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (transcript_id, user_id)
+);
+
+CREATE INDEX idx_call_transcript_consents_user ON call_transcript_consents(user_id, responded_at DESC);
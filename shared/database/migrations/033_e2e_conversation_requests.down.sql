@@ -0,0 +1,3 @@
+-- Rollback E2E Conversation Requests Migration
+
+DROP TABLE IF EXISTS e2e_conversation_requests;
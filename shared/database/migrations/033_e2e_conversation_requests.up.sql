@@ -0,0 +1,12 @@
+-- E2E Conversation Requests Migration
+
+-- A direct conversation is only encrypted once both users opt in; this holds
+-- the first opt-in until the other user agrees
+CREATE TABLE e2e_conversation_requests (
+    requester_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    peer_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    requested_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (requester_id, peer_id)
+);
+
+CREATE INDEX idx_e2e_conversation_requests_peer ON e2e_conversation_requests(peer_id);
@@ -0,0 +1,5 @@
+-- Rollback Command Reply Authors Migration
+
+ALTER TABLE messages
+    DROP COLUMN IF EXISTS bot_id,
+    DROP COLUMN IF EXISTS command;
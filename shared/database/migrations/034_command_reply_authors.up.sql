@@ -0,0 +1,8 @@
+-- Command Reply Authors Migration
+
+-- In-channel replies to slash commands are stored in the invoker's
+-- conversation with sender_id set to the invoker, but were written by the
+-- command: command names it and bot_id is the webhook bot, if one replied
+ALTER TABLE messages
+    ADD COLUMN command VARCHAR(32),
+    ADD COLUMN bot_id UUID REFERENCES chat_bots(bot_id) ON DELETE SET NULL;